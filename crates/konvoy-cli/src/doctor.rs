@@ -0,0 +1,838 @@
+//! `konvoy doctor`: environment, toolchain, and dependency health checks.
+//!
+//! Every check appends a [`Check`] to a [`Report`], which is rendered either as
+//! the human `[ok]`/`[--]`/`[!!]` lines or as a JSON document for CI.
+
+use std::path::Path;
+
+/// Outcome of a single doctor check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CheckStatus {
+    /// Healthy.
+    Ok,
+    /// Informational: not a problem yet (e.g. a tool that downloads on first use).
+    Warn,
+    /// A problem that must be fixed before building.
+    Fail,
+}
+
+impl CheckStatus {
+    /// Stable lowercase name used in JSON output.
+    fn as_str(self) -> &'static str {
+        match self {
+            CheckStatus::Ok => "ok",
+            CheckStatus::Warn => "warn",
+            CheckStatus::Fail => "fail",
+        }
+    }
+
+    /// Marker printed in front of a human-readable check line.
+    fn marker(self) -> &'static str {
+        match self {
+            CheckStatus::Ok => "[ok]",
+            CheckStatus::Warn => "[--]",
+            CheckStatus::Fail => "[!!]",
+        }
+    }
+}
+
+/// A single doctor check result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Check {
+    /// Stable machine-readable identifier, e.g. `jre` or `maven_dep.kotlinx-datetime`.
+    pub name: String,
+    pub status: CheckStatus,
+    /// The human-readable line (without the status marker).
+    pub detail: String,
+    /// The remedy `konvoy doctor --fix` can apply, if the problem is fixable.
+    pub fix: Option<Fix>,
+}
+
+/// An automatic remedy for a failed or incomplete check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Fix {
+    /// Install the managed Kotlin/Native toolchain (konanc and its bundled JRE).
+    InstallToolchain { version: String, jre_major: u32 },
+    /// Download the detekt-cli JAR.
+    DownloadDetekt { version: String },
+}
+
+/// All checks from one doctor run, in the order they were performed.
+#[derive(Debug, Default)]
+pub(crate) struct Report {
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    fn push(&mut self, name: impl Into<String>, status: CheckStatus, detail: impl Into<String>) {
+        self.checks.push(Check {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+            fix: None,
+        });
+    }
+
+    fn push_fixable(
+        &mut self,
+        name: impl Into<String>,
+        status: CheckStatus,
+        detail: impl Into<String>,
+        fix: Fix,
+    ) {
+        self.checks.push(Check {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+            fix: Some(fix),
+        });
+    }
+
+    /// The distinct fixes applicable to this report, in check order.
+    pub fn fixes(&self) -> Vec<Fix> {
+        let mut fixes: Vec<Fix> = Vec::new();
+        for fix in self.checks.iter().filter_map(|c| c.fix.as_ref()) {
+            if !fixes.contains(fix) {
+                fixes.push(fix.clone());
+            }
+        }
+        fixes
+    }
+
+    /// Number of failed checks.
+    pub fn issues(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|c| c.status == CheckStatus::Fail)
+            .count()
+    }
+
+    /// Human-readable lines, one per check.
+    pub fn human_lines(&self) -> Vec<String> {
+        self.checks
+            .iter()
+            .map(|c| format!("  {} {}", c.status.marker(), c.detail))
+            .collect()
+    }
+
+    /// The JSON report: `{"issues": N, "checks": [{"name", "status", "detail"}, ...]}`.
+    pub fn to_json(&self) -> serde_json::Value {
+        let checks: Vec<serde_json::Value> = self
+            .checks
+            .iter()
+            .map(|c| {
+                serde_json::json!({
+                    "name": c.name,
+                    "status": c.status.as_str(),
+                    "detail": c.detail,
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "issues": self.issues(),
+            "checks": checks,
+        })
+    }
+}
+
+/// Run every check applicable to `cwd` (project checks when it holds a
+/// `konvoy.toml`, otherwise the installed toolchains).
+pub(crate) fn run_checks(cwd: &Path) -> Report {
+    let mut report = Report::default();
+    check_host_target(&mut report);
+    check_disk_space(&mut report);
+
+    let manifest_path = cwd.join("konvoy.toml");
+    if manifest_path.exists() {
+        match konvoy_config::Manifest::from_path(&manifest_path) {
+            Ok(manifest) => {
+                report.push(
+                    "project",
+                    CheckStatus::Ok,
+                    format!("Project: {}", manifest.package.name),
+                );
+                check_toolchain(&mut report, &manifest, cwd);
+                check_detekt(&mut report, &manifest);
+                check_codegen(&mut report, &manifest);
+                check_maven_deps(&mut report, &manifest, cwd);
+                check_unused_deps(&mut report, &manifest, cwd);
+            }
+            Err(e) => report.push("manifest", CheckStatus::Fail, format!("konvoy.toml: {e}")),
+        }
+    } else {
+        report.push(
+            "manifest",
+            CheckStatus::Warn,
+            "No konvoy.toml in current directory",
+        );
+        check_standalone_toolchains(&mut report);
+    }
+    report
+}
+
+/// How long `konvoy doctor --network` waits for each host before calling it
+/// unreachable.
+const NETWORK_PROBE_TIMEOUT_SECS: u64 = 5;
+
+/// Probe the hosts downloads come from — the Kotlin/Native release host and
+/// the Maven registry, or their configured mirrors — with a HEAD request each.
+/// An unreachable host is a warning: cached builds still work without it.
+pub(crate) fn check_network(report: &mut Report, net: &konvoy_util::net::NetworkClient) {
+    let toolchain = net
+        .toolchain_mirror()
+        .unwrap_or(konvoy_konanc::toolchain::DEFAULT_TOOLCHAIN_BASE_URL);
+    let maven = net
+        .maven_mirror()
+        .unwrap_or(konvoy_util::maven::MAVEN_CENTRAL);
+    for (name, label, url) in [
+        ("network.toolchain", "Kotlin/Native downloads", toolchain),
+        ("network.maven", "Maven registry", maven),
+    ] {
+        match net.probe(url, NETWORK_PROBE_TIMEOUT_SECS) {
+            konvoy_util::net::Reachability::Reachable(code) => report.push(
+                name,
+                CheckStatus::Ok,
+                format!("{label}: {url} reachable (HTTP {code})"),
+            ),
+            konvoy_util::net::Reachability::Unreachable(reason) => report.push(
+                name,
+                CheckStatus::Warn,
+                format!("{label}: {url} unreachable ({reason}) — downloads will fail; check your firewall or proxy, or configure a mirror"),
+            ),
+            konvoy_util::net::Reachability::Offline => report.push(
+                name,
+                CheckStatus::Warn,
+                format!("{label}: {url} not checked (offline)"),
+            ),
+        }
+    }
+}
+
+/// Apply one fix: install the toolchain, or download detekt (verified against
+/// the `konvoy.lock` pin when the locked detekt version matches). The lockfile
+/// itself is not rewritten — `build` and `lint` own the pins.
+///
+/// # Errors
+/// Returns an error if the download or installation fails.
+pub(crate) fn apply_fix(
+    fix: &Fix,
+    cwd: &Path,
+    net: &konvoy_util::net::NetworkClient,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match fix {
+        Fix::InstallToolchain { version, jre_major } => {
+            eprintln!("    Installing Kotlin/Native {version}...");
+            let result = konvoy_konanc::toolchain::install(version, *jre_major, net)?;
+            eprintln!(
+                "    Installed Kotlin/Native {version} at {}",
+                result.konanc_path.display()
+            );
+        }
+        Fix::DownloadDetekt { version } => {
+            let lockfile_path = cwd.join("konvoy.lock");
+            let pinned = if lockfile_path.exists() {
+                konvoy_config::lockfile::Lockfile::from_path(&lockfile_path)?
+                    .toolchain
+                    .filter(|tc| tc.detekt_version.as_deref() == Some(version.as_str()))
+                    .and_then(|tc| tc.detekt_jar_sha256)
+            } else {
+                None
+            };
+            eprintln!("    Downloading detekt {version}...");
+            let resolver = konvoy_engine::ArtifactResolver::new(
+                net,
+                konvoy_engine::LockfileManager::new(false),
+            );
+            let (path, _) =
+                konvoy_engine::detekt::ensure_detekt(version, pinned.as_deref(), resolver)?;
+            eprintln!("    Downloaded detekt {version} to {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+fn check_host_target(report: &mut Report) {
+    match konvoy_targets::host_target() {
+        Ok(target) => report.push(
+            "host_target",
+            CheckStatus::Ok,
+            format!("Host target: {target}"),
+        ),
+        Err(e) => report.push(
+            "host_target",
+            CheckStatus::Fail,
+            format!("Host target: {e}"),
+        ),
+    }
+}
+
+/// Below this much free space under `~/.konvoy`, a toolchain + JRE install
+/// (hundreds of MB, more while extracting) may fail part-way through.
+const LOW_DISK_SPACE_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Warn (never fail) when the filesystem backing `~/.konvoy` is nearly full.
+fn check_disk_space(report: &mut Report) {
+    let space = konvoy_util::fs::konvoy_home()
+        .and_then(|home| konvoy_util::fs::available_space(&home).map(|free| (home, free)));
+    match space {
+        Ok((home, free)) if free < LOW_DISK_SPACE_BYTES => report.push(
+            "disk_space",
+            CheckStatus::Warn,
+            format!(
+                "Disk space: only {} free for {} — toolchain and JRE downloads need several hundred MB and may fail",
+                format_gib(free),
+                home.display()
+            ),
+        ),
+        Ok((home, free)) => report.push(
+            "disk_space",
+            CheckStatus::Ok,
+            format!("Disk space: {} free for {}", format_gib(free), home.display()),
+        ),
+        Err(e) => report.push("disk_space", CheckStatus::Warn, format!("Disk space: {e}")),
+    }
+}
+
+/// Render a byte count as GiB with one decimal.
+fn format_gib(bytes: u64) -> String {
+    const GIB: u64 = 1024 * 1024 * 1024;
+    format!("{}.{} GiB", bytes / GIB, (bytes % GIB) * 10 / GIB)
+}
+
+fn check_toolchain(report: &mut Report, manifest: &konvoy_config::Manifest, cwd: &Path) {
+    let version = &manifest.toolchain.kotlin;
+    let jre_major = manifest
+        .toolchain
+        .jre
+        .unwrap_or(konvoy_konanc::toolchain::DEFAULT_JRE_MAJOR);
+    match konvoy_konanc::toolchain::is_installed(version, jre_major) {
+        Ok(true) => {
+            match konvoy_konanc::toolchain::managed_konanc_path(version) {
+                Ok(path) => report.push(
+                    "konanc",
+                    CheckStatus::Ok,
+                    format!("konanc: {version} ({})", path.display()),
+                ),
+                Err(e) => report.push("konanc", CheckStatus::Fail, format!("konanc: {e}")),
+            }
+            match konvoy_konanc::toolchain::jre_home_path(version, jre_major) {
+                Ok(path) => {
+                    report.push(
+                        "jre",
+                        CheckStatus::Ok,
+                        format!("JRE {jre_major}: {}", path.display()),
+                    );
+                    check_jre_runs(report, &path, version);
+                }
+                Err(e) => report.push_fixable(
+                    "jre",
+                    CheckStatus::Fail,
+                    format!("JRE: {e}"),
+                    Fix::InstallToolchain {
+                        version: version.clone(),
+                        jre_major,
+                    },
+                ),
+            }
+            check_toolchain_fingerprint(report, version, cwd);
+        }
+        Ok(false) => report.push_fixable(
+            "konanc",
+            CheckStatus::Fail,
+            format!("konanc: Kotlin/Native {version} not installed — run `konvoy toolchain install` or `konvoy build`"),
+            Fix::InstallToolchain {
+                version: version.clone(),
+                jre_major,
+            },
+        ),
+        Err(e) => report.push("konanc", CheckStatus::Fail, format!("konanc: {e}")),
+    }
+}
+
+/// Run `java -version` from the JRE at `jre_home`. The JRE existing is not
+/// enough: a corrupted or wrong-architecture install only shows when run.
+fn check_jre_runs(report: &mut Report, jre_home: &Path, version: &str) {
+    let name = "jre_runs";
+    let java = jre_home.join("bin").join("java");
+    let reinstall =
+        format!("remove ~/.konvoy/toolchains/{version}/ and run `konvoy toolchain install`");
+    match std::process::Command::new(&java).arg("-version").output() {
+        Ok(output) if output.status.success() => {
+            // `java -version` prints to stderr.
+            let printed = String::from_utf8_lossy(&output.stderr);
+            let version_line = first_line(&printed).unwrap_or("version not reported");
+            report.push(name, CheckStatus::Ok, format!("JRE runs: {version_line}"));
+        }
+        Ok(output) => {
+            let printed = String::from_utf8_lossy(&output.stderr);
+            let reason = first_line(&printed).unwrap_or("no output");
+            report.push(
+                name,
+                CheckStatus::Fail,
+                format!(
+                    "JRE runs: `{} -version` failed ({}): {reason} — {reinstall}",
+                    java.display(),
+                    output.status
+                ),
+            );
+        }
+        Err(e) => report.push(
+            name,
+            CheckStatus::Fail,
+            format!("JRE runs: cannot run {}: {e} — {reinstall}", java.display()),
+        ),
+    }
+}
+
+/// The first non-blank line of `text`, trimmed.
+fn first_line(text: &str) -> Option<&str> {
+    text.lines().map(str::trim).find(|line| !line.is_empty())
+}
+
+/// Compare the installed toolchain against the konanc fingerprint recorded in
+/// `konvoy.lock`. A missing pin is informational — the next build records it.
+fn check_toolchain_fingerprint(report: &mut Report, version: &str, cwd: &Path) {
+    let name = "konanc_fingerprint";
+    let lockfile = match konvoy_config::lockfile::Lockfile::from_path(&cwd.join("konvoy.lock")) {
+        Ok(lockfile) => lockfile,
+        Err(e) => {
+            report.push(
+                name,
+                CheckStatus::Fail,
+                format!("Toolchain fingerprint: {e}"),
+            );
+            return;
+        }
+    };
+    let Some(expected) = lockfile.konanc_fingerprint(version) else {
+        report.push(
+            name,
+            CheckStatus::Warn,
+            "Toolchain fingerprint: not recorded in konvoy.lock — run `konvoy build` to record it",
+        );
+        return;
+    };
+    match konvoy_konanc::detect::verify_fingerprint(version, expected) {
+        Ok(_) => report.push(
+            name,
+            CheckStatus::Ok,
+            "Toolchain fingerprint: matches konvoy.lock",
+        ),
+        Err(e) => report.push(
+            name,
+            CheckStatus::Fail,
+            format!("Toolchain fingerprint: {e}"),
+        ),
+    }
+}
+
+fn check_detekt(report: &mut Report, manifest: &konvoy_config::Manifest) {
+    let Some(ref detekt_version) = manifest.toolchain.detekt else {
+        return;
+    };
+    match konvoy_engine::detekt::is_installed(detekt_version) {
+        Ok(true) => match konvoy_engine::detekt::detekt_jar_path(detekt_version) {
+            Ok(path) => report.push(
+                "detekt",
+                CheckStatus::Ok,
+                format!("detekt: {detekt_version} ({})", path.display()),
+            ),
+            Err(e) => report.push("detekt", CheckStatus::Fail, format!("detekt: {e}")),
+        },
+        Ok(false) => report.push_fixable(
+            "detekt",
+            CheckStatus::Warn,
+            format!(
+                "detekt: {detekt_version} not downloaded — will download on first `konvoy lint`"
+            ),
+            Fix::DownloadDetekt {
+                version: detekt_version.clone(),
+            },
+        ),
+        Err(e) => report.push("detekt", CheckStatus::Fail, format!("detekt: {e}")),
+    }
+}
+
+/// Report the install status of each configured codegen tool. A not-yet-downloaded
+/// tool is informational (`warn`) — it downloads on first use, like detekt; only a
+/// failure to inspect it counts as an issue.
+fn check_codegen(report: &mut Report, manifest: &konvoy_config::Manifest) {
+    for generator in konvoy_engine::codegen::active_generators(&manifest.codegen) {
+        let tool = generator.managed_tool();
+        let label = generator.display_name();
+        let name = format!("codegen.{}", tool.id());
+        // `artifact_path()` is the same path `is_installed()` checks; compute it
+        // once and `.exists()` it, rather than resolving the path twice.
+        match tool.artifact_path() {
+            Ok(path) if path.exists() => report.push(
+                name,
+                CheckStatus::Ok,
+                format!(
+                    "{label} ({}): {} ({})",
+                    tool.id(),
+                    tool.version(),
+                    path.display()
+                ),
+            ),
+            Ok(_) => report.push(
+                name,
+                CheckStatus::Warn,
+                format!(
+                    "{label} ({}): {} not downloaded — will download on first `konvoy generate` or `konvoy build`",
+                    tool.id(),
+                    tool.version()
+                ),
+            ),
+            Err(e) => report.push(
+                name,
+                CheckStatus::Fail,
+                format!("{label} ({}): {e}", tool.id()),
+            ),
+        }
+    }
+}
+
+fn check_maven_deps(report: &mut Report, manifest: &konvoy_config::Manifest, cwd: &Path) {
+    let maven_deps: Vec<_> = manifest
+        .dependencies
+        .iter()
+        .filter(|(_, spec)| spec.is_maven())
+        .collect();
+
+    if maven_deps.is_empty() {
+        return;
+    }
+
+    for (dep_name, dep_spec) in &maven_deps {
+        if let (Some(ref maven), Some(ref dep_version)) = (&dep_spec.maven, &dep_spec.version) {
+            report.push(
+                format!("maven_dep.{dep_name}"),
+                CheckStatus::Ok,
+                format!("Maven dep: {dep_name} {dep_version} ({maven})"),
+            );
+        }
+    }
+
+    let lockfile_path = cwd.join("konvoy.lock");
+    if !lockfile_path.exists() {
+        report.push(
+            "lockfile",
+            CheckStatus::Fail,
+            "No konvoy.lock found — run 'konvoy update' to resolve Maven dependencies",
+        );
+        return;
+    }
+
+    match konvoy_config::lockfile::Lockfile::from_path(&lockfile_path) {
+        Ok(lockfile) => {
+            for (dep_name, _) in &maven_deps {
+                let name = format!("lockfile_entry.{dep_name}");
+                if lockfile.has_maven_entry(dep_name) {
+                    report.push(name, CheckStatus::Ok, format!("Lockfile entry: {dep_name}"));
+                } else {
+                    report.push(
+                        name,
+                        CheckStatus::Fail,
+                        format!("Lockfile entry: '{dep_name}' not found — run 'konvoy update'"),
+                    );
+                }
+            }
+        }
+        Err(e) => report.push("lockfile", CheckStatus::Fail, format!("Lockfile: {e}")),
+    }
+}
+
+/// Warn about dependencies no source imports from. The analysis is a
+/// heuristic, so it never fails the check run.
+fn check_unused_deps(report: &mut Report, manifest: &konvoy_config::Manifest, cwd: &Path) {
+    match konvoy_engine::unused_dependencies(cwd, manifest) {
+        Ok(unused) => {
+            for dep in unused {
+                report.push(
+                    format!("unused_dep.{}", dep.name),
+                    CheckStatus::Warn,
+                    format!(
+                        "Dependency '{}' looks unused: nothing imports from {} — remove it, or set `allow_unused = true` if it is used via reflection",
+                        dep.name,
+                        dep.packages.join(", ")
+                    ),
+                );
+            }
+        }
+        Err(e) => report.push(
+            "unused_deps",
+            CheckStatus::Warn,
+            format!("Unused dependencies: {e}"),
+        ),
+    }
+}
+
+fn check_standalone_toolchains(report: &mut Report) {
+    match konvoy_konanc::toolchain::list_installed() {
+        Ok(versions) if versions.is_empty() => report.push(
+            "toolchains",
+            CheckStatus::Warn,
+            "No managed toolchains installed",
+        ),
+        Ok(versions) => report.push(
+            "toolchains",
+            CheckStatus::Ok,
+            format!("Managed toolchains: {}", versions.join(", ")),
+        ),
+        Err(e) => report.push(
+            "toolchains",
+            CheckStatus::Fail,
+            format!("Managed toolchains: {e}"),
+        ),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn sample_report() -> Report {
+        let mut report = Report::default();
+        report.push("host_target", CheckStatus::Ok, "Host target: linux_x64");
+        report.push(
+            "detekt",
+            CheckStatus::Warn,
+            "detekt: 1.23.7 not downloaded — will download on first `konvoy lint`",
+        );
+        report.push("jre", CheckStatus::Fail, "JRE: not found");
+        report
+    }
+
+    #[test]
+    fn format_gib_rounds_down_to_one_decimal() {
+        assert_eq!(format_gib(0), "0.0 GiB");
+        assert_eq!(format_gib(LOW_DISK_SPACE_BYTES), "2.0 GiB");
+        assert_eq!(format_gib(1024 * 1024 * 1024 * 3 / 2), "1.5 GiB");
+    }
+
+    #[test]
+    fn run_checks_reports_disk_space_without_failing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let report = run_checks(tmp.path());
+        let json = report.to_json();
+        let check = json["checks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|c| c["name"] == "disk_space")
+            .unwrap();
+        assert_ne!(check["status"], "fail", "{check}");
+    }
+
+    #[test]
+    fn issues_count_only_failures() {
+        assert_eq!(sample_report().issues(), 1);
+        assert_eq!(Report::default().issues(), 0);
+    }
+
+    #[test]
+    fn human_lines_use_status_markers() {
+        assert_eq!(
+            sample_report().human_lines(),
+            vec![
+                "  [ok] Host target: linux_x64",
+                "  [--] detekt: 1.23.7 not downloaded — will download on first `konvoy lint`",
+                "  [!!] JRE: not found",
+            ]
+        );
+    }
+
+    #[test]
+    fn fixes_are_deduplicated_in_check_order() {
+        let mut report = sample_report();
+        let toolchain = Fix::InstallToolchain {
+            version: "2.1.0".to_owned(),
+            jre_major: konvoy_konanc::toolchain::DEFAULT_JRE_MAJOR,
+        };
+        let detekt = Fix::DownloadDetekt {
+            version: "1.23.7".to_owned(),
+        };
+        report.push_fixable(
+            "konanc",
+            CheckStatus::Fail,
+            "konanc: missing",
+            toolchain.clone(),
+        );
+        report.push_fixable("jre", CheckStatus::Fail, "JRE: missing", toolchain.clone());
+        report.push_fixable(
+            "detekt",
+            CheckStatus::Warn,
+            "detekt: missing",
+            detekt.clone(),
+        );
+        assert_eq!(report.fixes(), vec![toolchain, detekt]);
+    }
+
+    #[test]
+    fn unfixable_report_has_no_fixes() {
+        assert!(sample_report().fixes().is_empty());
+    }
+
+    #[test]
+    fn json_report_shape() {
+        let json = sample_report().to_json();
+        assert_eq!(json["issues"], 1);
+        let checks = json["checks"].as_array().unwrap();
+        assert_eq!(checks.len(), 3);
+        assert_eq!(checks[0]["name"], "host_target");
+        assert_eq!(checks[0]["status"], "ok");
+        assert_eq!(checks[1]["status"], "warn");
+        assert_eq!(checks[2]["name"], "jre");
+        assert_eq!(checks[2]["status"], "fail");
+        assert_eq!(checks[2]["detail"], "JRE: not found");
+    }
+
+    #[test]
+    fn check_network_warns_about_unreachable_hosts() {
+        let net = konvoy_util::net::NetworkClient::new(false)
+            .with_toolchain_mirror("http://127.0.0.1:1/kotlin")
+            .with_maven_mirror("http://127.0.0.1:1/maven2");
+        let mut report = Report::default();
+        check_network(&mut report, &net);
+
+        let names: Vec<&str> = report.checks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["network.toolchain", "network.maven"]);
+        for check in &report.checks {
+            assert_eq!(check.status, CheckStatus::Warn, "{}", check.detail);
+            assert!(check.detail.contains("unreachable"), "{}", check.detail);
+            assert!(
+                check.detail.contains("http://127.0.0.1:1/"),
+                "{}",
+                check.detail
+            );
+        }
+        assert_eq!(report.issues(), 0);
+    }
+
+    #[cfg(unix)]
+    fn fake_jre(script: &str) -> tempfile::TempDir {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let java = tmp.path().join("bin").join("java");
+        std::fs::create_dir_all(java.parent().unwrap()).unwrap();
+        std::fs::write(&java, script).unwrap();
+        std::fs::set_permissions(&java, std::fs::Permissions::from_mode(0o755)).unwrap();
+        tmp
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn check_jre_runs_reports_the_java_version() {
+        let jre = fake_jre(
+            "#!/bin/sh\necho 'openjdk version \"21.0.5\" 2024-10-15' >&2\necho 'OpenJDK Runtime Environment' >&2\n",
+        );
+        let mut report = Report::default();
+        check_jre_runs(&mut report, jre.path(), "2.1.0");
+        assert_eq!(report.issues(), 0);
+        let check = report.checks.first().unwrap();
+        assert_eq!(check.name, "jre_runs");
+        assert_eq!(
+            check.detail,
+            "JRE runs: openjdk version \"21.0.5\" 2024-10-15"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn check_jre_runs_fails_when_java_cannot_start() {
+        let jre = fake_jre(
+            "#!/bin/sh\necho 'cannot execute binary file: Exec format error' >&2\nexit 126\n",
+        );
+        let mut report = Report::default();
+        check_jre_runs(&mut report, jre.path(), "2.1.0");
+        assert_eq!(report.issues(), 1);
+        let detail = &report.checks.first().unwrap().detail;
+        assert!(detail.contains("Exec format error"), "{detail}");
+        assert!(detail.contains("~/.konvoy/toolchains/2.1.0/"), "{detail}");
+
+        let mut report = Report::default();
+        check_jre_runs(&mut report, &jre.path().join("missing"), "2.1.0");
+        assert_eq!(report.issues(), 1);
+    }
+
+    #[test]
+    fn run_checks_without_manifest_reports_missing_project() {
+        let tmp = tempfile::tempdir().unwrap();
+        let report = run_checks(tmp.path());
+        let manifest = report.checks.iter().find(|c| c.name == "manifest").unwrap();
+        assert_eq!(manifest.status, CheckStatus::Warn);
+        assert_eq!(manifest.detail, "No konvoy.toml in current directory");
+    }
+
+    #[test]
+    fn run_checks_with_invalid_manifest_fails() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(tmp.path().join("konvoy.toml"), "not toml [").unwrap();
+        let report = run_checks(tmp.path());
+        let manifest = report.checks.iter().find(|c| c.name == "manifest").unwrap();
+        assert_eq!(manifest.status, CheckStatus::Fail);
+        assert!(report.issues() >= 1);
+    }
+
+    #[test]
+    fn run_checks_offers_toolchain_install_when_missing() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tmp.path().join("konvoy.toml"),
+            "[package]\nname = \"app\"\n\n[toolchain]\nkotlin = \"0.0.0-doctor-missing\"\n",
+        )
+        .unwrap();
+        let report = run_checks(tmp.path());
+        let konanc = report.checks.iter().find(|c| c.name == "konanc").unwrap();
+        assert_eq!(konanc.status, CheckStatus::Fail);
+        assert_eq!(
+            report.fixes(),
+            vec![Fix::InstallToolchain {
+                version: "0.0.0-doctor-missing".to_owned(),
+                jre_major: konvoy_konanc::toolchain::DEFAULT_JRE_MAJOR,
+            }]
+        );
+    }
+
+    #[test]
+    fn run_checks_missing_lockfile_for_maven_deps() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tmp.path().join("konvoy.toml"),
+            "[package]\nname = \"app\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n\n[dependencies]\ndt = { maven = \"org.jetbrains.kotlinx:kotlinx-datetime\", version = \"0.6.0\" }\n",
+        )
+        .unwrap();
+        let report = run_checks(tmp.path());
+        let names: Vec<&str> = report.checks.iter().map(|c| c.name.as_str()).collect();
+        assert!(names.contains(&"project"));
+        assert!(names.contains(&"maven_dep.dt"));
+        let lockfile = report.checks.iter().find(|c| c.name == "lockfile").unwrap();
+        assert_eq!(lockfile.status, CheckStatus::Fail);
+    }
+
+    #[test]
+    fn run_checks_warns_about_unused_dependencies() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tmp.path().join("konvoy.toml"),
+            "[package]\nname = \"app\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n\n[dependencies]\ndt = { maven = \"org.jetbrains.kotlinx:kotlinx-datetime\", version = \"0.6.0\" }\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(tmp.path().join("src")).unwrap();
+        std::fs::write(tmp.path().join("src/main.kt"), "fun main() {}\n").unwrap();
+        let report = run_checks(tmp.path());
+        let unused = report
+            .checks
+            .iter()
+            .find(|c| c.name == "unused_dep.dt")
+            .unwrap();
+        assert_eq!(unused.status, CheckStatus::Warn);
+        assert!(
+            unused.detail.contains("kotlinx.datetime"),
+            "{}",
+            unused.detail
+        );
+    }
+}
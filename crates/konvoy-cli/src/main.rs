@@ -1,8 +1,11 @@
 #![forbid(unsafe_code)]
 
+mod doctor;
+
 use std::error::Error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::time::Duration;
 
 use clap::{Parser, Subcommand};
 
@@ -12,24 +15,92 @@ type CliResult = Result<(), Box<dyn Error>>;
 #[command(name = "konvoy", about = "A native-first Kotlin build tool")]
 #[command(version)]
 struct Cli {
+    /// When to color diagnostics: auto (terminal and no NO_COLOR), always, or never; defaults to `color` in ~/.konvoy/config.toml, else auto
+    #[arg(long, global = true, value_enum, value_name = "WHEN")]
+    color: Option<ColorWhen>,
+    /// Suppress progress output (Compiling, Finished, Fresh); diagnostics and errors are still shown
+    #[arg(long, short = 'q', global = true)]
+    quiet: bool,
+    /// Path to the project's konvoy.toml; its directory is the project root instead of the current directory
+    #[arg(long, global = true, value_name = "PATH")]
+    manifest_path: Option<PathBuf>,
+    /// Keep build outputs and the artifact cache in this directory instead of
+    /// the project's .konvoy/ (also read from KONVOY_TARGET_DIR)
+    #[arg(long, global = true, value_name = "DIR")]
+    target_dir: Option<PathBuf>,
     #[command(subcommand)]
     command: Command,
 }
 
+/// The `--vcs` setting of `init` and `new`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum VcsChoice {
+    Git,
+    None,
+}
+
+impl From<VcsChoice> for konvoy_engine::Vcs {
+    fn from(choice: VcsChoice) -> Self {
+        match choice {
+            VcsChoice::Git => Self::Git,
+            VcsChoice::None => Self::None,
+        }
+    }
+}
+
+/// The `--color` setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum ColorWhen {
+    Auto,
+    Always,
+    Never,
+}
+
+impl From<ColorWhen> for konvoy_util::color::ColorChoice {
+    fn from(when: ColorWhen) -> Self {
+        match when {
+            ColorWhen::Auto => Self::Auto,
+            ColorWhen::Always => Self::Always,
+            ColorWhen::Never => Self::Never,
+        }
+    }
+}
+
 #[derive(Debug, Subcommand)]
 enum Command {
     /// Create a new Konvoy project
     Init {
         /// Project name
-        #[arg(long)]
+        #[arg(long, value_parser = project_name)]
         name: Option<String>,
-        /// Create a library project instead of a binary
-        #[arg(long)]
+        /// Create a library project instead of a binary (same as `--template lib`)
+        #[arg(long, conflicts_with = "template")]
+        lib: bool,
+        /// Starter template: bin, lib, or cli
+        #[arg(long, value_name = "NAME")]
+        template: Option<String>,
+        /// Version control to initialize: git (skipped when git is unavailable) or none
+        #[arg(long, value_enum, default_value_t = VcsChoice::Git)]
+        vcs: VcsChoice,
+    },
+    /// Create a new Konvoy project in the current directory
+    New {
+        /// Project name (defaults to the current directory's name)
+        #[arg(long, value_parser = project_name)]
+        name: Option<String>,
+        /// Create a library project instead of a binary (same as `--template lib`)
+        #[arg(long, conflicts_with = "template")]
         lib: bool,
+        /// Starter template: bin, lib, or cli
+        #[arg(long, value_name = "NAME")]
+        template: Option<String>,
+        /// Version control to initialize: git (skipped when git is unavailable) or none
+        #[arg(long, value_enum, default_value_t = VcsChoice::Git)]
+        vcs: VcsChoice,
     },
     /// Compile the project
     Build {
-        /// Target triple (defaults to host)
+        /// Target triple (defaults to host); `all` builds every target this host can build
         #[arg(long)]
         target: Option<String>,
         /// Build in release mode
@@ -41,6 +112,22 @@ enum Command {
         /// Force a rebuild, bypassing the cache
         #[arg(long)]
         force: bool,
+        /// Kill a konanc invocation that runs longer than this many seconds
+        /// (overrides `timeout_secs` under [build] in konvoy.toml)
+        #[arg(long, value_name = "SECS")]
+        timeout: Option<u64>,
+        /// Print each distinct compiler diagnostic once, with a count of repeats
+        #[arg(long)]
+        dedupe_diagnostics: bool,
+        /// Comma-separated features to enable, in addition to the defaults
+        #[arg(long, value_delimiter = ',', value_name = "FEATURES")]
+        features: Vec<String>,
+        /// Do not enable the package's default features
+        #[arg(long)]
+        no_default_features: bool,
+        /// Print how long each build phase took
+        #[arg(long)]
+        timings: bool,
         /// Assert that konvoy.lock is up to date and never modify it (pinned
         /// artifacts may still be downloaded; only lockfile drift is an error)
         #[arg(long)]
@@ -49,6 +136,30 @@ enum Command {
         /// present locally, or the build fails
         #[arg(long)]
         offline: bool,
+        /// Write the exact konanc invocations (binary, arguments, JAVA_HOME)
+        /// to this path as JSON; empty for a cached build unless --force
+        #[arg(long, value_name = "PATH")]
+        emit_command: Option<PathBuf>,
+        /// Also copy the built artifact into this directory (one subdirectory
+        /// per target with `--target all`)
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<PathBuf>,
+        /// Fail the build if konanc reports any warning (same as
+        /// `deny_warnings = true` under [build] in konvoy.toml)
+        #[arg(long)]
+        deny_warnings: bool,
+        /// When a path dependency fails, keep building the ones that do not
+        /// depend on it and report every failure at the end
+        #[arg(long)]
+        keep_going: bool,
+        /// Print what would be compiled, reused from the cache, and
+        /// downloaded, without compiling, downloading, or writing konvoy.lock
+        #[arg(long, conflicts_with_all = ["emit_command", "out_dir", "timings", "print_artifact"])]
+        dry_run: bool,
+        /// After a successful build, print only the artifact's absolute path
+        /// to stdout (one line per target with `--target all`)
+        #[arg(long)]
+        print_artifact: bool,
     },
     /// Build and run the project
     Run {
@@ -64,6 +175,19 @@ enum Command {
         /// Force a rebuild, bypassing the cache
         #[arg(long)]
         force: bool,
+        /// Kill a konanc invocation that runs longer than this many seconds
+        /// (overrides `timeout_secs` under [build] in konvoy.toml)
+        #[arg(long, value_name = "SECS")]
+        timeout: Option<u64>,
+        /// Print each distinct compiler diagnostic once, with a count of repeats
+        #[arg(long)]
+        dedupe_diagnostics: bool,
+        /// Comma-separated features to enable, in addition to the defaults
+        #[arg(long, value_delimiter = ',', value_name = "FEATURES")]
+        features: Vec<String>,
+        /// Do not enable the package's default features
+        #[arg(long)]
+        no_default_features: bool,
         /// Assert that konvoy.lock is up to date and never modify it (pinned
         /// artifacts may still be downloaded; only lockfile drift is an error)
         #[arg(long)]
@@ -72,13 +196,27 @@ enum Command {
         /// present locally, or the build fails
         #[arg(long)]
         offline: bool,
-        /// Arguments to pass to the program
-        #[arg(last = true)]
+        /// Build and run `examples/<NAME>.kt` against the library instead
+        #[arg(long, value_name = "NAME")]
+        example: Option<String>,
+        /// Set an environment variable for the program (repeatable); the
+        /// build itself does not see it
+        #[arg(long, value_name = "KEY=VALUE", value_parser = env_assignment)]
+        env: Vec<(String, String)>,
+        /// Run the program in this directory instead of the current one; the
+        /// build still happens in the project root
+        #[arg(long, value_name = "DIR")]
+        cwd: Option<PathBuf>,
+        /// Arguments to pass to the program. Everything from the first
+        /// positional argument on is forwarded, flags included; put `--`
+        /// first to forward arguments that start with `-`
+        #[arg(trailing_var_arg = true, value_name = "ARGS")]
         args: Vec<String>,
     },
     /// Run tests
     Test {
-        /// Target triple (defaults to host)
+        /// Target triple (defaults to host); a comma-separated list tests
+        /// each target in turn
         #[arg(long)]
         target: Option<String>,
         /// Test in release mode
@@ -90,6 +228,19 @@ enum Command {
         /// Force a rebuild, bypassing the cache
         #[arg(long)]
         force: bool,
+        /// Kill a konanc invocation that runs longer than this many seconds
+        /// (overrides `timeout_secs` under [build] in konvoy.toml)
+        #[arg(long, value_name = "SECS")]
+        timeout: Option<u64>,
+        /// Print each distinct compiler diagnostic once, with a count of repeats
+        #[arg(long)]
+        dedupe_diagnostics: bool,
+        /// Comma-separated features to enable, in addition to the defaults
+        #[arg(long, value_delimiter = ',', value_name = "FEATURES")]
+        features: Vec<String>,
+        /// Do not enable the package's default features
+        #[arg(long)]
+        no_default_features: bool,
         /// Assert that konvoy.lock is up to date and never modify it (pinned
         /// artifacts may still be downloaded; only lockfile drift is an error)
         #[arg(long)]
@@ -101,15 +252,82 @@ enum Command {
         /// Only run tests matching this pattern (forwarded to --ktest_filter)
         #[arg(long)]
         filter: Option<String>,
+        /// Build the test binary and list the tests it contains instead of
+        /// running them (narrowed by --filter when given)
+        #[arg(long)]
+        list: bool,
+        /// Also write the test results as a JUnit XML report to this path
+        #[arg(long, value_name = "PATH", conflicts_with = "list")]
+        junit: Option<PathBuf>,
+        /// Build the test binary and print its path without running it
+        /// (implied when --target is not the host)
+        #[arg(long, conflicts_with_all = ["list", "junit"])]
+        no_run: bool,
+    },
+    /// Build and run the benchmarks in src/bench/ (release mode by default)
+    Bench {
+        /// Target triple (defaults to host)
+        #[arg(long)]
+        target: Option<String>,
+        /// Build the benchmarks without optimizations
+        #[arg(long)]
+        debug: bool,
+        /// Show compiler output
+        #[arg(long, short = 'v')]
+        verbose: bool,
+        /// Force a rebuild, bypassing the cache
+        #[arg(long)]
+        force: bool,
+        /// Kill a konanc invocation that runs longer than this many seconds
+        /// (overrides `timeout_secs` under [build] in konvoy.toml)
+        #[arg(long, value_name = "SECS")]
+        timeout: Option<u64>,
+        /// Print each distinct compiler diagnostic once, with a count of repeats
+        #[arg(long)]
+        dedupe_diagnostics: bool,
+        /// Comma-separated features to enable, in addition to the defaults
+        #[arg(long, value_delimiter = ',', value_name = "FEATURES")]
+        features: Vec<String>,
+        /// Do not enable the package's default features
+        #[arg(long)]
+        no_default_features: bool,
+        /// Assert that konvoy.lock is up to date and never modify it (pinned
+        /// artifacts may still be downloaded; only lockfile drift is an error)
+        #[arg(long)]
+        locked: bool,
+        /// Run without network access: every managed artifact must already be
+        /// present locally, or the build fails
+        #[arg(long)]
+        offline: bool,
+        /// Only run benchmarks matching this pattern (forwarded as --filter)
+        #[arg(long)]
+        filter: Option<String>,
     },
     /// Run detekt linter on Kotlin source files
     Lint {
         /// Show raw detekt output
         #[arg(long, short = 'v')]
         verbose: bool,
-        /// Path to a custom detekt configuration file
+        /// Path to a custom detekt configuration file; repeat to layer several,
+        /// later files overriding earlier ones
+        #[arg(long)]
+        config: Vec<PathBuf>,
+        /// Re-run detekt even if cached results are up to date
+        #[arg(long)]
+        force: bool,
+        /// Apply detekt's auto-corrections to the sources
         #[arg(long)]
-        config: Option<PathBuf>,
+        fix: bool,
+        /// Allow --fix even when the git working tree has uncommitted changes
+        #[arg(long, requires = "fix")]
+        allow_dirty: bool,
+        /// Output format: human-readable text, or a SARIF 2.1.0 log (for code
+        /// scanning)
+        #[arg(long, value_enum, default_value_t = LintFormat::Human)]
+        format: LintFormat,
+        /// Write the SARIF log to this path instead of stdout
+        #[arg(long, value_name = "PATH", requires = "format")]
+        output: Option<PathBuf>,
         /// Assert that konvoy.lock is up to date and never modify it (the pinned
         /// detekt JAR may still be downloaded; only lockfile drift is an error)
         #[arg(long)]
@@ -119,6 +337,24 @@ enum Command {
         #[arg(long)]
         offline: bool,
     },
+    /// Format Kotlin source files with ktlint
+    Fmt {
+        /// Report files that need formatting without rewriting them (non-zero
+        /// exit if any would change)
+        #[arg(long)]
+        check: bool,
+        /// Show raw ktlint output
+        #[arg(long, short = 'v')]
+        verbose: bool,
+        /// Assert that konvoy.lock is up to date and never modify it (the pinned
+        /// ktlint JAR may still be downloaded; only lockfile drift is an error)
+        #[arg(long)]
+        locked: bool,
+        /// Run without network access: ktlint and its JRE must already be
+        /// present locally, or formatting fails
+        #[arg(long)]
+        offline: bool,
+    },
     /// Run code generators (e.g. OpenAPI/Fabrikt) without compiling
     Generate {
         /// Show raw generator output
@@ -138,17 +374,73 @@ enum Command {
     /// Remove build artifacts
     Clean {
         /// Remove the entire .konvoy/ directory, not just build artifacts
-        #[arg(long)]
+        #[arg(long, conflicts_with_all = ["build", "cache"])]
         all: bool,
+        /// Remove the materialized build outputs in .konvoy/build/ (the default)
+        #[arg(long)]
+        build: bool,
+        /// Remove the artifact cache (shared with the main worktree, if any)
+        #[arg(long)]
+        cache: bool,
+        /// List what would be removed and the space it takes, without removing anything
+        #[arg(long)]
+        dry_run: bool,
     },
     /// Check environment and toolchain setup
-    Doctor,
+    Doctor {
+        /// Emit a JSON report on stdout instead of human-readable lines
+        #[arg(long)]
+        json: bool,
+        /// Install missing toolchain components (konanc, JRE, detekt), then re-check
+        #[arg(long)]
+        fix: bool,
+        /// Also check that the Kotlin/Native download host and the Maven registry (or their mirrors) are reachable
+        #[arg(long)]
+        network: bool,
+    },
+    /// Check cached artifacts against the hashes pinned in konvoy.lock, without building
+    Verify {
+        /// Target whose Maven klibs to check (defaults to host)
+        #[arg(long)]
+        target: Option<String>,
+    },
     /// Validate konvoy.toml and report configuration issues
     Check {
         /// Output format: human-readable text, or JSON (for editors/tools)
         #[arg(long, value_enum, default_value_t = CheckFormat::Human)]
         format: CheckFormat,
     },
+    /// Print the effective configuration: konvoy.toml with defaults filled in,
+    /// build profiles, and the dependency versions pinned in konvoy.lock
+    Config {
+        /// Print JSON instead of TOML
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print the package's name, version, kind, description, authors, and
+    /// license from konvoy.toml
+    Metadata {
+        /// Print JSON instead of `key: value` lines
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print the dependency graph: path, git, and Maven dependencies
+    Graph {
+        /// Output format: an indented text tree, or a Graphviz `dot` document
+        #[arg(long, value_enum, default_value_t = GraphFormat::Text)]
+        format: GraphFormat,
+        /// Write the graph to this path instead of stdout
+        #[arg(long, value_name = "PATH")]
+        output: Option<PathBuf>,
+        /// Use the git dependency commits pinned in konvoy.lock without fetching
+        #[arg(long)]
+        offline: bool,
+    },
+    /// Explain an error code, e.g. `konvoy explain lockfile-out-of-date`
+    Explain {
+        /// The error code printed after a failed command
+        code: String,
+    },
     /// Manage Kotlin/Native toolchains
     Toolchain {
         #[command(subcommand)]
@@ -156,6 +448,24 @@ enum Command {
     },
 }
 
+/// Output format for `konvoy lint`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum LintFormat {
+    /// Findings as `file:line: rule: message` lines on stderr.
+    Human,
+    /// A SARIF 2.1.0 log on stdout, or in the `--output` file.
+    Sarif,
+}
+
+/// Output format for `konvoy graph`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum GraphFormat {
+    /// An indented tree rooted at the project.
+    Text,
+    /// A Graphviz `dot` document (render with e.g. `dot -Tsvg`).
+    Dot,
+}
+
 /// Output format for `konvoy check`.
 #[derive(Debug, Clone, Copy, clap::ValueEnum)]
 enum CheckFormat {
@@ -172,62 +482,189 @@ enum ToolchainAction {
     Install {
         /// Kotlin/Native version (e.g. "2.1.0"). If omitted, reads from konvoy.toml.
         version: Option<String>,
+        /// Also pre-fetch the dependencies of every target this host can build
+        #[arg(long)]
+        all_targets: bool,
+        /// Base URL to download the Kotlin/Native tarball from instead of the JetBrains releases
+        #[arg(long, value_name = "URL")]
+        mirror: Option<String>,
     },
     /// List installed Kotlin/Native versions
     List,
+    /// Verify the installed toolchain against the fingerprint in konvoy.lock
+    Verify,
+    /// Remove temp files left behind by interrupted installs
+    Gc,
+    /// Print the konanc and JRE the project's toolchain resolves to
+    Which,
 }
 
-/// Build a command-scoped `ArtifactResolver` from the `--offline`/`--locked`
-/// flags and hand it to `f`.
+/// Build a command-scoped `ArtifactResolver` from the command's network client
+/// and `--locked` flag and hand it to `f`.
 ///
-/// The resolver borrows a `NetworkClient` that lives only for this call, so it is
-/// threaded through a closure rather than returned. Every fetching command builds
-/// its resolver this way, keeping the net/lockfile wiring in one place.
+/// The resolver borrows the `NetworkClient`, which lives only for this call, so it
+/// is threaded through a closure rather than returned. Every fetching command
+/// builds its resolver this way, keeping the net/lockfile wiring in one place.
 fn with_resolver<T>(
-    offline: bool,
+    net: &konvoy_util::net::NetworkClient,
     locked: bool,
     f: impl FnOnce(konvoy_engine::ArtifactResolver<'_>) -> T,
 ) -> T {
-    let net = konvoy_util::net::NetworkClient::new(offline);
     let resolver =
-        konvoy_engine::ArtifactResolver::new(&net, konvoy_engine::LockfileManager::new(locked));
+        konvoy_engine::ArtifactResolver::new(net, konvoy_engine::LockfileManager::new(locked));
     f(resolver)
 }
 
+impl Cli {
+    /// Reject `--quiet` combined with a command's `--verbose`.
+    ///
+    /// `--quiet` is global and may appear before the subcommand, where clap's
+    /// own conflict tracking cannot see it, so the check is done here.
+    fn check_output_flags(&self) -> CliResult {
+        let verbose = match &self.command {
+            Command::Build { verbose, .. }
+            | Command::Run { verbose, .. }
+            | Command::Test { verbose, .. }
+            | Command::Bench { verbose, .. }
+            | Command::Lint { verbose, .. }
+            | Command::Fmt { verbose, .. }
+            | Command::Generate { verbose, .. } => *verbose,
+            _ => false,
+        };
+        if self.quiet && verbose {
+            return Err("--quiet and --verbose are contradictory — pass at most one".into());
+        }
+        if self.manifest_path.is_some()
+            && matches!(self.command, Command::Init { .. } | Command::New { .. })
+        {
+            return Err("--manifest-path names an existing project — `konvoy init` and `konvoy new` create one".into());
+        }
+        Ok(())
+    }
+}
+
 fn main() {
     let cli = Cli::parse();
+    if let Err(msg) = cli.check_output_flags() {
+        eprintln!("error: {msg}");
+        process::exit(1);
+    }
+
+    // Defaults from ~/.konvoy/config.toml sit below flags and environment
+    // variables; a missing file changes nothing.
+    let user = match konvoy_config::UserConfig::load() {
+        Ok(user) => user,
+        Err(e) => {
+            eprintln!("error: {e}");
+            process::exit(1);
+        }
+    };
 
     // The single outbound-HTTP funnel for the whole process: one client per
     // invocation, built here at the program entry from the command's --offline
     // flag (or always-online for inherently-online commands) and threaded into
     // everything that may fetch. `--offline` lives in the client, not in the
     // build/lint options — network access is the client's concern.
+    let net = |offline: bool| user.network_client(user.offline_from_env(offline));
+    let color = user.color_from_env(cli.color.map(Into::into));
+    let quiet = cli.quiet;
+    let manifest_path = cli.manifest_path.as_deref();
+    let target_dir = resolve_target_dir(cli.target_dir.clone(), std::env::var_os(TARGET_DIR_ENV));
     let result = match cli.command {
-        Command::Init { name, lib } => cmd_init(name, lib),
+        Command::Init {
+            name,
+            lib,
+            template,
+            vcs,
+        } => cmd_init(name, lib, template.as_deref(), vcs.into()),
+        Command::New {
+            name,
+            lib,
+            template,
+            vcs,
+        } => cmd_new(name.as_deref(), lib, template.as_deref(), vcs.into()),
         Command::Build {
             target,
             release,
             verbose,
             force,
+            timeout,
+            dedupe_diagnostics,
+            features,
+            no_default_features,
+            timings,
             locked,
             offline,
-        } => with_resolver(offline, locked, |resolver| {
-            cmd_build(target, profile_from_flag(release), verbose, force, resolver)
+            emit_command,
+            out_dir,
+            deny_warnings,
+            keep_going,
+            dry_run,
+            print_artifact,
+        } => with_resolver(&net(offline || dry_run), locked, |resolver| {
+            cmd_build(
+                manifest_path,
+                &konvoy_engine::BuildOptions {
+                    timings,
+                    quiet,
+                    features,
+                    no_default_features,
+                    emit_command,
+                    out_dir,
+                    deny_warnings,
+                    keep_going,
+                    target_dir: target_dir.clone(),
+                    ..build_options(
+                        target,
+                        profile_from_flag(release),
+                        verbose,
+                        force,
+                        timeout,
+                        dedupe_diagnostics,
+                        color,
+                    )
+                },
+                dry_run,
+                print_artifact,
+                resolver,
+            )
         }),
         Command::Run {
             target,
             release,
             verbose,
             force,
+            timeout,
+            dedupe_diagnostics,
+            features,
+            no_default_features,
             locked,
             offline,
+            example,
+            env,
+            cwd,
             args,
-        } => with_resolver(offline, locked, |resolver| {
+        } => with_resolver(&net(offline), locked, |resolver| {
             cmd_run(
-                target,
-                profile_from_flag(release),
-                verbose,
-                force,
+                manifest_path,
+                &konvoy_engine::BuildOptions {
+                    quiet,
+                    features,
+                    no_default_features,
+                    target_dir: target_dir.clone(),
+                    ..build_options(
+                        target,
+                        profile_from_flag(release),
+                        verbose,
+                        force,
+                        timeout,
+                        dedupe_diagnostics,
+                        color,
+                    )
+                },
+                example.as_deref(),
+                &env,
+                cwd.as_deref(),
                 &args,
                 resolver,
             )
@@ -237,51 +674,207 @@ fn main() {
             release,
             verbose,
             force,
+            timeout,
+            dedupe_diagnostics,
+            features,
+            no_default_features,
             locked,
             offline,
             filter,
-        } => with_resolver(offline, locked, |resolver| {
+            list,
+            junit,
+            no_run,
+        } => with_resolver(&net(offline), locked, |resolver| {
             cmd_test(
-                target,
-                profile_from_flag(release),
-                verbose,
-                force,
-                &filter,
+                manifest_path,
+                &konvoy_engine::BuildOptions {
+                    quiet,
+                    features,
+                    no_default_features,
+                    target_dir: target_dir.clone(),
+                    ..build_options(
+                        target,
+                        profile_from_flag(release),
+                        verbose,
+                        force,
+                        timeout,
+                        dedupe_diagnostics,
+                        color,
+                    )
+                },
+                &TestRun {
+                    filter,
+                    list,
+                    junit,
+                    no_run,
+                },
+                resolver,
+            )
+        }),
+        Command::Bench {
+            target,
+            debug,
+            verbose,
+            force,
+            timeout,
+            dedupe_diagnostics,
+            features,
+            no_default_features,
+            locked,
+            offline,
+            filter,
+        } => with_resolver(&net(offline), locked, |resolver| {
+            cmd_bench(
+                manifest_path,
+                &konvoy_engine::BuildOptions {
+                    quiet,
+                    features,
+                    no_default_features,
+                    target_dir: target_dir.clone(),
+                    ..build_options(
+                        target,
+                        profile_from_flag(!debug),
+                        verbose,
+                        force,
+                        timeout,
+                        dedupe_diagnostics,
+                        color,
+                    )
+                },
+                &BenchRun { filter },
                 resolver,
             )
         }),
         Command::Lint {
             verbose,
             config,
+            force,
+            fix,
+            allow_dirty,
+            format,
+            output,
+            locked,
+            offline,
+        } => with_resolver(&net(offline), locked, |resolver| {
+            cmd_lint(
+                manifest_path,
+                &konvoy_engine::LintOptions {
+                    verbose,
+                    quiet,
+                    config,
+                    force,
+                    fix,
+                    allow_dirty,
+                },
+                format,
+                output.as_deref(),
+                resolver,
+            )
+        }),
+        Command::Fmt {
+            check,
+            verbose,
             locked,
             offline,
-        } => with_resolver(offline, locked, |resolver| {
-            cmd_lint(verbose, config, resolver)
+        } => with_resolver(&net(offline), locked, |resolver| {
+            cmd_fmt(
+                manifest_path,
+                &konvoy_engine::FmtOptions {
+                    verbose,
+                    quiet,
+                    check,
+                },
+                resolver,
+            )
         }),
         Command::Generate {
             verbose,
             locked,
             offline,
-        } => with_resolver(offline, locked, |resolver| cmd_generate(verbose, resolver)),
+        } => with_resolver(&net(offline), locked, |resolver| {
+            cmd_generate(manifest_path, verbose, quiet, resolver)
+        }),
         // `konvoy update` is inherently online and never locked: it exists to
         // (re)resolve dependencies and rewrite konvoy.lock.
-        Command::Update => with_resolver(false, false, cmd_update),
-        Command::Clean { all } => cmd_clean(all),
-        Command::Doctor => cmd_doctor(),
-        Command::Check { format } => cmd_check(format),
+        Command::Update => with_resolver(&user.network_client(false), false, |resolver| {
+            cmd_update(manifest_path, resolver)
+        }),
+        Command::Clean {
+            all,
+            build,
+            cache,
+            dry_run,
+        } => cmd_clean(
+            manifest_path,
+            target_dir.as_deref(),
+            CleanScope::from_flags(all, build, cache),
+            dry_run,
+        ),
+        Command::Doctor { json, fix, network } => cmd_doctor(
+            manifest_path,
+            json,
+            fix,
+            network,
+            &user.network_client(false),
+        ),
+        Command::Verify { target } => cmd_verify(manifest_path, &target),
+        Command::Check { format } => cmd_check(manifest_path, format),
+        Command::Config { json } => cmd_config(manifest_path, json),
+        Command::Metadata { json } => cmd_metadata(manifest_path, json),
+        Command::Graph {
+            format,
+            output,
+            offline,
+        } => with_resolver(&net(offline), false, |resolver| {
+            cmd_graph(manifest_path, format, output.as_deref(), resolver)
+        }),
+        Command::Explain { code } => cmd_explain(&code),
         Command::Toolchain { action } => {
-            cmd_toolchain(action, &konvoy_util::net::NetworkClient::new(false))
+            cmd_toolchain(manifest_path, action, &user.network_client(false))
         }
     };
 
     if let Err(msg) = result {
         eprintln!("error: {msg}");
+        if let Some(code) = error_code(msg.as_ref()) {
+            eprintln!("For more information about this error, try `konvoy explain {code}`.");
+        }
         process::exit(1);
     }
 }
 
-/// Find the project root by looking for `konvoy.toml` in the current directory.
-fn project_root() -> Result<PathBuf, Box<dyn Error>> {
+/// The stable code of an engine or compiler error, if `err` is one.
+fn error_code(err: &(dyn Error + 'static)) -> Option<&'static str> {
+    if let Some(e) = err.downcast_ref::<konvoy_engine::EngineError>() {
+        return Some(e.code());
+    }
+    err.downcast_ref::<konvoy_konanc::error::KonancError>()
+        .map(konvoy_konanc::error::KonancError::code)
+}
+
+/// Find the project root: the directory of `--manifest-path` when given,
+/// otherwise the current directory if it holds a `konvoy.toml`.
+///
+/// A `--manifest-path` root is canonicalized, so relative `path`
+/// dependencies resolve against the manifest's directory wherever konvoy runs.
+fn project_root(manifest_path: Option<&Path>) -> Result<PathBuf, Box<dyn Error>> {
+    if let Some(path) = manifest_path {
+        if path.file_name().is_none_or(|name| name != "konvoy.toml") {
+            return Err(format!(
+                "--manifest-path must point to a konvoy.toml file, got `{}`",
+                path.display()
+            )
+            .into());
+        }
+        if !path.is_file() {
+            return Err(format!("manifest not found at `{}`", path.display()).into());
+        }
+        let manifest = path.canonicalize()?;
+        return manifest
+            .parent()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| format!("`{}` has no parent directory", path.display()).into());
+    }
     let cwd = std::env::current_dir()?;
     let manifest = cwd.join("konvoy.toml");
     if !manifest.exists() {
@@ -293,21 +886,59 @@ fn project_root() -> Result<PathBuf, Box<dyn Error>> {
     Ok(cwd)
 }
 
-fn cmd_init(name: Option<String>, lib: bool) -> CliResult {
-    let cwd = std::env::current_dir()?;
+/// Parse `--name` for `konvoy init`/`konvoy new`, rejecting names that are not
+/// valid package names before anything is written.
+fn project_name(name: &str) -> Result<String, String> {
+    konvoy_config::manifest::validate_name(name)
+        .map(|()| name.to_owned())
+        .map_err(|reason| format!("`{name}` is not a valid project name: {reason}"))
+}
 
-    let kind = if lib {
-        konvoy_config::manifest::PackageKind::Lib
-    } else {
-        konvoy_config::manifest::PackageKind::Bin
-    };
+/// A `--env KEY=VALUE` assignment. The value may be empty or contain `=`;
+/// the key may not be empty.
+fn env_assignment(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_owned(), value.to_owned())),
+        _ => Err(format!("`{arg}` is not a KEY=VALUE assignment")),
+    }
+}
+
+/// The template selected by `--template`, or by `--lib` as its shorthand.
+fn project_template(
+    lib: bool,
+    template: Option<&str>,
+) -> Result<konvoy_engine::ProjectTemplate, Box<dyn Error>> {
+    match template {
+        Some(name) => Ok(name.parse()?),
+        None if lib => Ok(konvoy_engine::ProjectTemplate::Lib),
+        None => Ok(konvoy_engine::ProjectTemplate::Bin),
+    }
+}
+
+fn kind_label(template: konvoy_engine::ProjectTemplate) -> &'static str {
+    match template.kind() {
+        konvoy_config::manifest::PackageKind::Lib => "library",
+        konvoy_config::manifest::PackageKind::StaticLib => "static library",
+        konvoy_config::manifest::PackageKind::DynamicLib => "dynamic library",
+        konvoy_config::manifest::PackageKind::Bin => "project",
+    }
+}
 
-    let kind_label = if lib { "library" } else { "project" };
+fn cmd_init(
+    name: Option<String>,
+    lib: bool,
+    template: Option<&str>,
+    vcs: konvoy_engine::Vcs,
+) -> CliResult {
+    let cwd = std::env::current_dir()?;
+    let template = project_template(lib, template)?;
+    let kind_label = kind_label(template);
 
     if let Some(project_name) = name {
         // `konvoy init --name <name>`: create a subdirectory.
         let project_dir = cwd.join(&project_name);
-        konvoy_engine::init_project_with_kind(&project_name, &project_dir, kind)?;
+        konvoy_engine::init_project_from_template(&project_name, &project_dir, template)?;
+        konvoy_engine::init_vcs(&project_dir, vcs)?;
 
         eprintln!(
             "    Created {kind_label} `{project_name}` at {}",
@@ -319,20 +950,54 @@ fn cmd_init(name: Option<String>, lib: bool) -> CliResult {
         eprintln!("    konvoy build");
     } else {
         // `konvoy init` (no --name): initialize in the current directory.
-        let project_name = konvoy_engine::init_project_in_place(&cwd, kind)?;
-
-        eprintln!(
-            "    Created {kind_label} `{project_name}` at {}",
-            cwd.display()
-        );
-        eprintln!();
-        eprintln!("  To get started:");
-        eprintln!("    konvoy build");
+        init_here(&cwd, None, template, vcs)?;
     }
 
     Ok(())
 }
 
+/// `konvoy new`: always initialize the current directory, with `--name` only
+/// overriding the package name.
+fn cmd_new(
+    name: Option<&str>,
+    lib: bool,
+    template: Option<&str>,
+    vcs: konvoy_engine::Vcs,
+) -> CliResult {
+    let cwd = std::env::current_dir()?;
+    init_here(&cwd, name, project_template(lib, template)?, vcs)
+}
+
+fn init_here(
+    cwd: &Path,
+    name: Option<&str>,
+    template: konvoy_engine::ProjectTemplate,
+    vcs: konvoy_engine::Vcs,
+) -> CliResult {
+    let kind_label = kind_label(template);
+    let project_name = konvoy_engine::init_project_in_place(cwd, name, template)?;
+    konvoy_engine::init_vcs(cwd, vcs)?;
+
+    eprintln!(
+        "    Created {kind_label} `{project_name}` at {}",
+        cwd.display()
+    );
+    eprintln!();
+    eprintln!("  To get started:");
+    eprintln!("    konvoy build");
+    Ok(())
+}
+
+/// Environment variable that sets `--target-dir` when the flag is not given.
+const TARGET_DIR_ENV: &str = "KONVOY_TARGET_DIR";
+
+/// The `--target-dir` flag, else a non-empty `KONVOY_TARGET_DIR` (`env`),
+/// made absolute against the current directory.
+fn resolve_target_dir(flag: Option<PathBuf>, env: Option<std::ffi::OsString>) -> Option<PathBuf> {
+    let dir = flag.or_else(|| env.filter(|v| !v.is_empty()).map(PathBuf::from))?;
+    Some(std::path::absolute(&dir).unwrap_or(dir))
+}
+
 /// Map the `--release` CLI flag to a `Profile` at the boundary.
 fn profile_from_flag(release: bool) -> konvoy_config::Profile {
     if release {
@@ -347,139 +1012,639 @@ fn build_options(
     profile: konvoy_config::Profile,
     verbose: bool,
     force: bool,
+    timeout: Option<u64>,
+    dedupe_diagnostics: bool,
+    color: konvoy_util::color::ColorChoice,
 ) -> konvoy_engine::BuildOptions {
     konvoy_engine::BuildOptions {
         target,
         profile,
         verbose,
         force,
+        timeout: timeout.map(Duration::from_secs),
+        dedupe_diagnostics,
+        color,
+        quiet: false,
+        timings: false,
+        features: Vec::new(),
+        no_default_features: false,
+        deny_warnings: false,
+        emit_command: None,
+        out_dir: None,
+        target_dir: None,
+        keep_going: false,
+        progress: std::sync::Arc::new(konvoy_engine::StderrProgress),
     }
 }
 
 fn cmd_build(
-    target: Option<String>,
-    profile: konvoy_config::Profile,
-    verbose: bool,
-    force: bool,
+    manifest_path: Option<&Path>,
+    options: &konvoy_engine::BuildOptions,
+    dry_run: bool,
+    print_artifact: bool,
     resolver: konvoy_engine::ArtifactResolver<'_>,
 ) -> CliResult {
-    let root = project_root()?;
-    let options = build_options(target, profile, verbose, force);
-
-    let result = konvoy_engine::build(&root, &options, resolver)?;
-
-    match result.outcome {
-        konvoy_engine::BuildOutcome::Cached => {
-            eprintln!(
-                "    Finished `{profile}` target in {:.2}s (cached)",
-                result.duration.as_secs_f64()
+    let root = project_root(manifest_path)?;
+    if dry_run {
+        return plan_build(&root, options, resolver);
+    }
+    if options.target.as_deref() == Some(konvoy_engine::ALL_TARGETS) {
+        if options.emit_command.is_some() {
+            return Err(
+                "--emit-command records a single target's build — pass a specific --target".into(),
             );
         }
-        konvoy_engine::BuildOutcome::Fresh => {
-            eprintln!(
-                "    Finished `{profile}` target in {:.2}s",
-                result.duration.as_secs_f64()
-            );
+        return build_all_targets(&root, options, print_artifact, resolver);
+    }
+    let result = konvoy_engine::build(&root, options, resolver)?;
+    if let Some(path) = options.emit_command.as_deref() {
+        if !options.quiet {
+            eprintln!("       Wrote konanc command to {}", path.display());
+        }
+    }
+    if let Some(dir) = options.out_dir.as_deref() {
+        if !options.quiet {
+            eprintln!("      Copied artifact to {}", dir.display());
+        }
+    }
+    if let Some(header) = &result.header_path {
+        if !options.quiet {
+            eprintln!("   Generated C header {}", header.display());
         }
     }
 
-    Ok(())
-}
-
-fn cmd_run(
-    target: Option<String>,
-    profile: konvoy_config::Profile,
-    verbose: bool,
-    force: bool,
-    args: &[String],
-    resolver: konvoy_engine::ArtifactResolver<'_>,
-) -> CliResult {
-    let root = project_root()?;
-
-    // Cannot run a library project.
-    let manifest = konvoy_config::Manifest::from_path(&root.join("konvoy.toml"))?;
-    if manifest.package.kind == konvoy_config::manifest::PackageKind::Lib {
-        return Err(
-            "cannot run a library project — only binary projects (kind = \"bin\") can be run"
-                .into(),
+    if !options.quiet {
+        eprintln!(
+            "    Finished `{}` target in {:.2}s {}",
+            options.profile,
+            result.duration.as_secs_f64(),
+            finish_note(&result)
         );
     }
-
-    let options = build_options(target, profile, verbose, force);
-
-    let result = konvoy_engine::build(&root, &options, resolver)?;
-
-    eprintln!(
-        "    Finished `{profile}` target in {:.2}s",
-        result.duration.as_secs_f64()
-    );
-    eprintln!("     Running `{}`", result.output_path.display());
-
-    let status = std::process::Command::new(&result.output_path)
-        .args(args)
-        .status()
-        .map_err(|e| format!("cannot run {}: {e}", result.output_path.display()))?;
-
-    if !status.success() {
-        let code = status.code().unwrap_or(1);
-        process::exit(code);
+    if options.timings {
+        print_timings(&result.timings);
+    }
+    // The only line `konvoy build` writes to stdout, for `$(konvoy build ...)`.
+    if print_artifact {
+        println!("{}", result.output_path.display());
     }
 
     Ok(())
 }
 
-fn cmd_test(
-    target: Option<String>,
-    profile: konvoy_config::Profile,
-    verbose: bool,
-    force: bool,
-    filter: &Option<String>,
+/// The parenthesized note after a build's `Finished` line: the artifact size,
+/// preceded by `cached` when nothing was compiled and followed by how many
+/// path dependencies were cached, e.g. `(1.80 MiB, 3 of 5 dependencies cached)`.
+fn finish_note(result: &konvoy_engine::BuildResult) -> String {
+    let size = konvoy_util::progress::format_bytes(result.artifact_size);
+    let mut note = match result.outcome {
+        konvoy_engine::BuildOutcome::Cached => format!("(cached, {size}"),
+        konvoy_engine::BuildOutcome::Fresh => format!("({size}"),
+    };
+    let deps = &result.dependency_outcomes;
+    if !deps.is_empty() {
+        let cached = deps
+            .iter()
+            .filter(|(_, outcome)| *outcome == konvoy_engine::BuildOutcome::Cached)
+            .count();
+        let noun = if deps.len() == 1 {
+            "dependency"
+        } else {
+            "dependencies"
+        };
+        note.push_str(&format!(", {cached} of {} {noun} cached", deps.len()));
+    }
+    note.push(')');
+    note
+}
+
+/// `konvoy build --dry-run`: print the plan for each requested target.
+/// Network access is already off, so nothing can be downloaded.
+fn plan_build(
+    root: &Path,
+    options: &konvoy_engine::BuildOptions,
     resolver: konvoy_engine::ArtifactResolver<'_>,
 ) -> CliResult {
-    let root = project_root()?;
-    let options = build_options(target, profile, verbose, force);
-
-    let result = konvoy_engine::build_tests(&root, &options, resolver)?;
+    let targets = if options.target.as_deref() == Some(konvoy_engine::ALL_TARGETS) {
+        let (buildable, _) = partition_targets(konvoy_targets::host_target()?);
+        buildable.iter().map(|t| Some(t.to_string())).collect()
+    } else {
+        vec![options.target.clone()]
+    };
+    for target in targets {
+        let target_options = konvoy_engine::BuildOptions {
+            target,
+            ..options.clone()
+        };
+        print_plan(&konvoy_engine::plan_build(root, &target_options, resolver)?);
+    }
+    Ok(())
+}
 
+fn print_plan(plan: &konvoy_engine::BuildPlan) {
+    for download in &plan.downloads {
+        eprintln!("    Would download {download}");
+    }
+    for step in &plan.steps {
+        match step.status {
+            konvoy_engine::PlanStatus::Cached => eprintln!("    Fresh {} (cached)", step.name),
+            konvoy_engine::PlanStatus::Compile => eprintln!(
+                "    Would compile {} \u{2192} {}",
+                step.name,
+                step.output_path.display()
+            ),
+        }
+    }
+    let compile = plan.compile_count();
     eprintln!(
-        "    Finished `{profile}` test target in {:.2}s",
-        result.compile_duration.as_secs_f64()
+        "    Dry run for {} `{}`: {compile} to compile, {} cached, {} to download; nothing was built",
+        plan.target,
+        plan.profile,
+        plan.steps.len() - compile,
+        plan.downloads.len()
     );
-    eprintln!("     Running `{}`", result.output_path.display());
+}
 
-    let mut cmd = std::process::Command::new(&result.output_path);
-    if let Some(ref pattern) = filter {
-        cmd.arg(format!("--ktest_filter={pattern}"));
+/// `konvoy build --target all`: build every target buildable from this host.
+///
+/// Targets the host cannot build are skipped with a warning. A failing target
+/// does not stop the others; the command fails at the end if any target did.
+/// With `print_artifact`, each built target's artifact path goes to stdout as
+/// soon as it is built.
+fn build_all_targets(
+    root: &Path,
+    options: &konvoy_engine::BuildOptions,
+    print_artifact: bool,
+    resolver: konvoy_engine::ArtifactResolver<'_>,
+) -> CliResult {
+    let host = konvoy_targets::host_target()?;
+    let (buildable, unbuildable) = partition_targets(host);
+    for target in &unbuildable {
+        if target.is_supported() {
+            eprintln!("    warning: skipping {target} — it cannot be built from a {host} host");
+        } else {
+            eprintln!("    warning: skipping {target} — konvoy does not support it yet");
+        }
     }
 
-    let status = cmd
-        .status()
-        .map_err(|e| format!("cannot run {}: {e}", result.output_path.display()))?;
-
-    if !status.success() {
-        let code = status.code().unwrap_or(1);
+    let mut built = Vec::new();
+    let mut failed = Vec::new();
+    for target in buildable {
+        if !options.quiet {
+            eprintln!("    Building {target}...");
+        }
+        let target_options = konvoy_engine::BuildOptions {
+            target: Some(target.to_string()),
+            out_dir: options
+                .out_dir
+                .as_ref()
+                .map(|dir| dir.join(target.to_string())),
+            ..options.clone()
+        };
+        match konvoy_engine::build(root, &target_options, resolver) {
+            Ok(result) => {
+                if !options.quiet {
+                    eprintln!(
+                        "    Finished {target} `{}` in {:.2}s {} \u{2192} {}",
+                        options.profile,
+                        result.duration.as_secs_f64(),
+                        finish_note(&result),
+                        result.output_path.display()
+                    );
+                }
+                if options.timings {
+                    print_timings(&result.timings);
+                }
+                if print_artifact {
+                    println!("{}", result.output_path.display());
+                }
+                built.push(target);
+            }
+            Err(e) => {
+                eprintln!("error: {target} failed: {e}");
+                failed.push(target);
+            }
+        }
+    }
+
+    if !options.quiet {
+        eprintln!(
+            "    Built {} target(s): {}",
+            built.len(),
+            join_targets(&built)
+        );
+    }
+    if !options.quiet && !unbuildable.is_empty() {
+        eprintln!(
+            "    Skipped {} target(s): {}",
+            unbuildable.len(),
+            join_targets(&unbuildable)
+        );
+    }
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} target(s) failed: {}",
+            failed.len(),
+            join_targets(&failed)
+        )
+        .into())
+    }
+}
+
+/// Print `--timings` phase durations as an aligned table on stderr.
+fn print_timings(timings: &[(String, Duration)]) {
+    let width = timings
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    eprintln!("    Timings:");
+    for (name, duration) in timings {
+        eprintln!("      {name:<width$}  {:>7.2}s", duration.as_secs_f64());
+    }
+}
+
+fn cmd_run(
+    manifest_path: Option<&Path>,
+    options: &konvoy_engine::BuildOptions,
+    example: Option<&str>,
+    env: &[(String, String)],
+    cwd: Option<&Path>,
+    args: &[String],
+    resolver: konvoy_engine::ArtifactResolver<'_>,
+) -> CliResult {
+    let root = project_root(manifest_path)?;
+    // Checked before building, so a typo does not cost a compile.
+    if let Some(dir) = cwd {
+        if !dir.is_dir() {
+            return Err(format!("--cwd {} is not a directory", dir.display()).into());
+        }
+    }
+
+    let result = if let Some(name) = example {
+        konvoy_engine::build_example(&root, name, options, resolver)?
+    } else {
+        // Cannot run a library project (its examples can be, via --example).
+        let manifest = konvoy_config::Manifest::from_path(&root.join("konvoy.toml"))?;
+        if manifest.package.kind != konvoy_config::manifest::PackageKind::Bin {
+            return Err(
+                "cannot run a library project — only binary projects (kind = \"bin\") can be run (use --example to run an example)"
+                    .into(),
+            );
+        }
+        konvoy_engine::build(&root, options, resolver)?
+    };
+
+    if !options.quiet {
+        eprintln!(
+            "    Finished `{}` target in {:.2}s",
+            options.profile,
+            result.duration.as_secs_f64()
+        );
+        eprintln!("     Running `{}`", result.output_path.display());
+    }
+
+    let mut command = std::process::Command::new(&result.output_path);
+    command
+        .args(args)
+        .envs(env.iter().map(|(key, value)| (key, value)));
+    if let Some(dir) = cwd {
+        command.current_dir(dir);
+    }
+    let status = command
+        .status()
+        .map_err(|e| format!("cannot run {}: {e}", result.output_path.display()))?;
+
+    if !status.success() {
+        let code = status.code().unwrap_or(1);
+        process::exit(code);
+    }
+
+    Ok(())
+}
+
+/// How `konvoy test` runs the built test binary.
+struct TestRun {
+    /// Only run tests matching this pattern.
+    filter: Option<String>,
+    /// List tests instead of running them.
+    list: bool,
+    /// Where to write a JUnit XML report, if requested.
+    junit: Option<PathBuf>,
+    /// Build the test binary without running it.
+    no_run: bool,
+}
+
+impl TestRun {
+    /// Arguments passed to the Kotlin/Native test binary.
+    ///
+    /// `--list` asks the runner to enumerate tests instead of executing them;
+    /// `--filter` narrows either mode. A JUnit report needs the GTest logger,
+    /// so it is requested explicitly rather than relying on the default.
+    fn runner_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(pattern) = &self.filter {
+            args.push(format!("--ktest_filter={pattern}"));
+        }
+        if self.list {
+            args.push("--ktest_list_tests".to_owned());
+        }
+        if self.junit.is_some() {
+            args.push("--ktest_logger=GTEST".to_owned());
+        }
+        args
+    }
+}
+
+fn cmd_test(
+    manifest_path: Option<&Path>,
+    options: &konvoy_engine::BuildOptions,
+    run: &TestRun,
+    resolver: konvoy_engine::ArtifactResolver<'_>,
+) -> CliResult {
+    let root = project_root(manifest_path)?;
+    if let Some(list) = options.target.as_deref().filter(|t| t.contains(',')) {
+        return test_targets(&root, options, run, list, resolver);
+    }
+    let result = konvoy_engine::build_tests(&root, options, resolver)?;
+
+    if !options.quiet {
+        eprintln!(
+            "    Finished `{}` test target in {:.2}s",
+            options.profile,
+            result.compile_duration.as_secs_f64()
+        );
+    }
+
+    // A test binary for another target cannot run here, so only build it.
+    let host = konvoy_targets::host_target()?;
+    if run.no_run || result.target != host {
+        if !run.no_run {
+            eprintln!(
+                "    note: {} is not the host target ({host}) — built the tests without running them (implies --no-run)",
+                result.target
+            );
+        }
+        if !options.quiet {
+            eprintln!("  Executable `{}`", result.output_path.display());
+        }
+        return Ok(());
+    }
+
+    let status = run_test_binary(&result.output_path, options, run)?;
+    if !status.success() {
+        let code = status.code().unwrap_or(1);
         process::exit(code);
     }
 
     Ok(())
 }
 
+/// Run a built test binary, writing the JUnit report when asked.
+fn run_test_binary(
+    binary: &Path,
+    options: &konvoy_engine::BuildOptions,
+    run: &TestRun,
+) -> Result<std::process::ExitStatus, Box<dyn Error>> {
+    if !options.quiet {
+        let action = if run.list { "Listing" } else { "Running" };
+        eprintln!("     {action} `{}`", binary.display());
+    }
+
+    let mut cmd = std::process::Command::new(binary);
+    cmd.args(run.runner_args());
+    let cannot_run = |e: std::io::Error| format!("cannot run {}: {e}", binary.display());
+
+    let status = match &run.junit {
+        None => cmd.status().map_err(cannot_run)?,
+        Some(junit_path) => {
+            let (status, stdout) = run_teeing_stdout(&mut cmd).map_err(cannot_run)?;
+            let report = konvoy_engine::parse_gtest_output(&stdout);
+            konvoy_engine::write_junit_report(junit_path, &report)?;
+            eprintln!(
+                "       Wrote JUnit report to {} ({} passed, {} failed, {} skipped)",
+                junit_path.display(),
+                report.passed(),
+                report.failed(),
+                report.skipped()
+            );
+            status
+        }
+    };
+    Ok(status)
+}
+
+/// What happened to one target's tests under `konvoy test --target a,b`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TargetTestOutcome {
+    Passed,
+    Failed,
+    BuildFailed,
+    /// The host cannot execute the target's binary, or `--no-run` was given.
+    BuiltNotRun,
+}
+
+impl TargetTestOutcome {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Passed => "passed",
+            Self::Failed => "failed",
+            Self::BuildFailed => "build failed",
+            Self::BuiltNotRun => "built (not run)",
+        }
+    }
+
+    fn is_failure(self) -> bool {
+        matches!(self, Self::Failed | Self::BuildFailed)
+    }
+}
+
+/// `konvoy test --target a,b`: build and run the tests for each listed target
+/// in turn.
+///
+/// A target the host cannot execute is only built. A failing build or test
+/// run does not stop the others; the command fails at the end if any did.
+fn test_targets(
+    root: &Path,
+    options: &konvoy_engine::BuildOptions,
+    run: &TestRun,
+    list: &str,
+    resolver: konvoy_engine::ArtifactResolver<'_>,
+) -> CliResult {
+    if run.junit.is_some() {
+        return Err("--junit writes a single report — pass a single --target".into());
+    }
+    let host = konvoy_targets::host_target()?;
+    let mut outcomes = Vec::new();
+    for name in list.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        if !options.quiet {
+            eprintln!("    Testing {name}...");
+        }
+        let target_options = konvoy_engine::BuildOptions {
+            target: Some(name.to_owned()),
+            ..options.clone()
+        };
+        let outcome = match konvoy_engine::build_tests(root, &target_options, resolver) {
+            Err(e) => {
+                eprintln!("error: {name} failed to build: {e}");
+                TargetTestOutcome::BuildFailed
+            }
+            Ok(result) if run.no_run || result.target != host => {
+                if !options.quiet {
+                    eprintln!("  Executable `{}`", result.output_path.display());
+                }
+                TargetTestOutcome::BuiltNotRun
+            }
+            Ok(result) => {
+                if run_test_binary(&result.output_path, options, run)?.success() {
+                    TargetTestOutcome::Passed
+                } else {
+                    TargetTestOutcome::Failed
+                }
+            }
+        };
+        outcomes.push((name, outcome));
+    }
+
+    if !options.quiet {
+        for (name, outcome) in &outcomes {
+            eprintln!("    {name}: {}", outcome.label());
+        }
+    }
+    let failed: Vec<&str> = outcomes
+        .iter()
+        .filter(|(_, outcome)| outcome.is_failure())
+        .map(|(name, _)| *name)
+        .collect();
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "tests failed on {} target(s): {}",
+            failed.len(),
+            failed.join(", ")
+        )
+        .into())
+    }
+}
+
+/// How `konvoy bench` runs the built benchmark binary.
+struct BenchRun {
+    /// Only run benchmarks matching this pattern.
+    filter: Option<String>,
+}
+
+impl BenchRun {
+    /// Arguments passed to the benchmark binary's `main`.
+    fn runner_args(&self) -> Vec<String> {
+        match &self.filter {
+            Some(pattern) => vec!["--filter".to_owned(), pattern.clone()],
+            None => Vec::new(),
+        }
+    }
+}
+
+fn cmd_bench(
+    manifest_path: Option<&Path>,
+    options: &konvoy_engine::BuildOptions,
+    run: &BenchRun,
+    resolver: konvoy_engine::ArtifactResolver<'_>,
+) -> CliResult {
+    let root = project_root(manifest_path)?;
+    let result = konvoy_engine::build_benches(&root, options, resolver)?;
+
+    if !options.quiet {
+        eprintln!(
+            "    Finished `{}` bench target in {:.2}s",
+            options.profile,
+            result.compile_duration.as_secs_f64()
+        );
+        eprintln!("     Running `{}`", result.output_path.display());
+    }
+
+    let status = std::process::Command::new(&result.output_path)
+        .args(run.runner_args())
+        .status()
+        .map_err(|e| format!("cannot run {}: {e}", result.output_path.display()))?;
+
+    if !status.success() {
+        let code = status.code().unwrap_or(1);
+        process::exit(code);
+    }
+
+    Ok(())
+}
+
+/// Run `cmd`, echoing its stdout line by line while also capturing it.
+///
+/// Stderr and stdin are inherited, so the console output is unchanged.
+fn run_teeing_stdout(
+    cmd: &mut std::process::Command,
+) -> std::io::Result<(std::process::ExitStatus, String)> {
+    use std::io::{BufRead, Write};
+
+    let mut child = cmd.stdout(std::process::Stdio::piped()).spawn()?;
+    let mut captured = String::new();
+    if let Some(stdout) = child.stdout.take() {
+        let mut console = std::io::stdout();
+        for line in std::io::BufReader::new(stdout).lines() {
+            let line = line?;
+            let _ = writeln!(console, "{line}");
+            captured.push_str(&line);
+            captured.push('\n');
+        }
+    }
+    let status = child.wait()?;
+    Ok((status, captured))
+}
+
 fn cmd_lint(
-    verbose: bool,
-    config: Option<PathBuf>,
+    manifest_path: Option<&Path>,
+    options: &konvoy_engine::LintOptions,
+    format: LintFormat,
+    output: Option<&std::path::Path>,
     resolver: konvoy_engine::ArtifactResolver<'_>,
 ) -> CliResult {
-    let root = project_root()?;
-    let options = konvoy_engine::LintOptions { verbose, config };
+    if output.is_some() && format == LintFormat::Human {
+        return Err("--output only applies to `--format sarif`".into());
+    }
+    let root = project_root(manifest_path)?;
 
-    let result = konvoy_engine::lint(&root, &options, resolver)?;
+    let result = konvoy_engine::lint(&root, options, resolver)?;
+
+    if format == LintFormat::Sarif {
+        match output {
+            Some(path) => {
+                konvoy_engine::write_sarif_report(path, &result, &root)?;
+                if !options.quiet {
+                    eprintln!("    Wrote SARIF report to {}", path.display());
+                }
+            }
+            None => println!("{}", konvoy_engine::render_sarif(&result, &root)?),
+        }
+    }
+
+    if options.fix && !options.quiet {
+        for path in &result.fixed_files {
+            let shown = path.strip_prefix(&root).unwrap_or(path);
+            eprintln!("    Fixed {}", shown.display());
+        }
+        if result.fixed_files.is_empty() {
+            eprintln!("    No files needed fixing");
+        }
+    }
 
     if result.success {
-        eprintln!("    No lint issues found");
+        if !options.quiet {
+            eprintln!("    No lint issues found");
+        }
         return Ok(());
     }
 
-    if !verbose {
+    // The SARIF log already carries every finding; only the summary follows.
+    if !options.verbose && format == LintFormat::Human {
         for diag in &result.diagnostics {
             match (&diag.file, diag.line) {
                 (Some(f), Some(l)) => eprintln!("  {f}:{l}: {}: {}", diag.rule, diag.message),
@@ -492,10 +1657,58 @@ fn cmd_lint(
     Err(format!("lint found {} issue(s)", result.finding_count).into())
 }
 
-fn cmd_generate(verbose: bool, resolver: konvoy_engine::ArtifactResolver<'_>) -> CliResult {
-    let root = project_root()?;
+fn cmd_fmt(
+    manifest_path: Option<&Path>,
+    options: &konvoy_engine::FmtOptions,
+    resolver: konvoy_engine::ArtifactResolver<'_>,
+) -> CliResult {
+    let root = project_root(manifest_path)?;
+
+    let result = konvoy_engine::fmt(&root, options, resolver)?;
+
+    let verb = if options.check {
+        "Would reformat"
+    } else {
+        "Formatted"
+    };
+    if !options.quiet || options.check {
+        for path in &result.changed_files {
+            let shown = path.strip_prefix(&root).unwrap_or(path);
+            eprintln!("    {verb} {}", shown.display());
+        }
+    }
+    if !result.fully_formatted && !options.verbose {
+        eprintln!("    warning: ktlint found issues it cannot fix automatically:");
+        for line in result.raw_output.lines().filter(|l| !l.trim().is_empty()) {
+            eprintln!("  {line}");
+        }
+    }
+
+    if options.check && !result.changed_files.is_empty() {
+        return Err(format!(
+            "{} file(s) need formatting — run `konvoy fmt` to fix",
+            result.changed_files.len()
+        )
+        .into());
+    }
+    if result.changed_files.is_empty() && !options.quiet {
+        eprintln!("    All files already formatted");
+    }
+    Ok(())
+}
+
+fn cmd_generate(
+    manifest_path: Option<&Path>,
+    verbose: bool,
+    quiet: bool,
+    resolver: konvoy_engine::ArtifactResolver<'_>,
+) -> CliResult {
+    let root = project_root(manifest_path)?;
 
     let result = konvoy_engine::generate(&root, verbose, resolver)?;
+    if quiet {
+        return Ok(());
+    }
 
     for output in &result.outputs {
         eprintln!(
@@ -508,8 +1721,11 @@ fn cmd_generate(verbose: bool, resolver: konvoy_engine::ArtifactResolver<'_>) ->
     Ok(())
 }
 
-fn cmd_update(resolver: konvoy_engine::ArtifactResolver<'_>) -> CliResult {
-    let root = project_root()?;
+fn cmd_update(
+    manifest_path: Option<&Path>,
+    resolver: konvoy_engine::ArtifactResolver<'_>,
+) -> CliResult {
+    let root = project_root(manifest_path)?;
     // `konvoy update` is inherently online — resolving fetches POMs/klibs.
     let result = konvoy_engine::update(&root, resolver)?;
     eprintln!(
@@ -519,221 +1735,200 @@ fn cmd_update(resolver: konvoy_engine::ArtifactResolver<'_>) -> CliResult {
     Ok(())
 }
 
-fn cmd_clean(all: bool) -> CliResult {
-    let root = project_root()?;
-    clean_project(&root, all)
-}
-
-fn clean_project(root: &std::path::Path, all: bool) -> CliResult {
-    let konvoy_dir = root.join(".konvoy");
-
-    if all {
-        konvoy_util::fs::remove_dir_all_if_exists(&konvoy_dir)?;
-        eprintln!("    Removed .konvoy/");
-    } else {
-        let build_dir = konvoy_dir.join("build");
-        konvoy_util::fs::remove_dir_all_if_exists(&build_dir)?;
-        eprintln!("    Removed build artifacts");
-    }
-
-    Ok(())
+/// What `konvoy clean` removes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CleanScope {
+    /// The whole `.konvoy/` directory.
+    All,
+    /// Any combination of `.konvoy/build/` and the artifact cache.
+    Parts { build: bool, cache: bool },
 }
 
-fn check_host_target() -> u32 {
-    match konvoy_targets::host_target() {
-        Ok(target) => {
-            eprintln!("  [ok] Host target: {target}");
-            0
-        }
-        Err(e) => {
-            eprintln!("  [!!] Host target: {e}");
-            1
+impl CleanScope {
+    /// Map the `--all`, `--build`, and `--cache` flags; no flag means `--build`.
+    fn from_flags(all: bool, build: bool, cache: bool) -> Self {
+        if all {
+            Self::All
+        } else if !build && !cache {
+            Self::Parts {
+                build: true,
+                cache: false,
+            }
+        } else {
+            Self::Parts { build, cache }
         }
     }
 }
 
-fn check_toolchain(manifest: &konvoy_config::Manifest) -> u32 {
-    let mut issues = 0u32;
-    let version = &manifest.toolchain.kotlin;
-    match konvoy_konanc::toolchain::is_installed(version) {
-        Ok(true) => {
-            match konvoy_konanc::toolchain::managed_konanc_path(version) {
-                Ok(path) => eprintln!("  [ok] konanc: {version} ({})", path.display()),
-                Err(e) => {
-                    eprintln!("  [!!] konanc: {e}");
-                    issues = issues.saturating_add(1);
-                }
-            }
-            match konvoy_konanc::toolchain::jre_home_path(version) {
-                Ok(path) => eprintln!("  [ok] JRE: {}", path.display()),
-                Err(e) => {
-                    eprintln!("  [!!] JRE: {e}");
-                    issues = issues.saturating_add(1);
-                }
-            }
-        }
-        Ok(false) => {
-            eprintln!("  [!!] konanc: Kotlin/Native {version} not installed — run `konvoy toolchain install` or `konvoy build`");
-            issues = issues.saturating_add(1);
-        }
-        Err(e) => {
-            eprintln!("  [!!] konanc: {e}");
-            issues = issues.saturating_add(1);
-        }
+fn cmd_clean(
+    manifest_path: Option<&Path>,
+    target_dir: Option<&Path>,
+    scope: CleanScope,
+    dry_run: bool,
+) -> CliResult {
+    let root = project_root(manifest_path)?;
+    if dry_run {
+        preview_clean(&clean_parts(&root, target_dir, scope))
+    } else {
+        clean_project(&root, target_dir, scope)
     }
-    issues
 }
 
-fn check_detekt(manifest: &konvoy_config::Manifest) -> u32 {
-    let Some(ref detekt_version) = manifest.toolchain.detekt else {
-        return 0;
-    };
-    match konvoy_engine::detekt::is_installed(detekt_version) {
-        Ok(true) => match konvoy_engine::detekt::detekt_jar_path(detekt_version) {
-            Ok(path) => {
-                eprintln!("  [ok] detekt: {detekt_version} ({})", path.display());
-                0
-            }
-            Err(e) => {
-                eprintln!("  [!!] detekt: {e}");
-                1
+/// The directories `konvoy clean` removes for `scope`, each with how its
+/// removal is reported. With a `--target-dir`, build outputs and the cache
+/// live there instead, and only its `build/` and `cache/` are removed: the
+/// directory itself may hold other things.
+fn clean_parts(
+    root: &std::path::Path,
+    target_dir: Option<&Path>,
+    scope: CleanScope,
+) -> Vec<(PathBuf, String)> {
+    let konvoy_dir = root.join(".konvoy");
+    let artifact_dir = target_dir.unwrap_or(&konvoy_dir);
+
+    let mut parts = Vec::new();
+    match scope {
+        CleanScope::All => {
+            parts.push((konvoy_dir.clone(), ".konvoy/".to_owned()));
+            if let Some(dir) = target_dir {
+                parts.push((
+                    dir.join("build"),
+                    format!("build artifacts in {}", dir.display()),
+                ));
+                parts.push((
+                    dir.join("cache"),
+                    format!("artifact cache in {}", dir.display()),
+                ));
             }
-        },
-        Ok(false) => {
-            eprintln!("  [--] detekt: {detekt_version} not downloaded — will download on first `konvoy lint`");
-            0
         }
-        Err(e) => {
-            eprintln!("  [!!] detekt: {e}");
-            1
-        }
-    }
-}
-
-/// Report the install status of each configured codegen tool. A not-yet-downloaded
-/// tool is informational (`[--]`, 0 issues) — it downloads on first use, like
-/// detekt; only a failure to inspect it counts as an issue.
-fn check_codegen(manifest: &konvoy_config::Manifest) -> u32 {
-    let mut issues = 0u32;
-    for generator in konvoy_engine::codegen::active_generators(&manifest.codegen) {
-        let tool = generator.managed_tool();
-        let label = generator.display_name();
-        // `artifact_path()` is the same path `is_installed()` checks; compute it
-        // once and `.exists()` it, rather than resolving the path twice.
-        match tool.artifact_path() {
-            Ok(path) if path.exists() => eprintln!(
-                "  [ok] {label} ({}): {} ({})",
-                tool.id(),
-                tool.version(),
-                path.display()
-            ),
-            Ok(_) => eprintln!(
-                "  [--] {label} ({}): {} not downloaded — will download on first `konvoy generate` or `konvoy build`",
-                tool.id(),
-                tool.version()
-            ),
-            Err(e) => {
-                eprintln!("  [!!] {label} ({}): {e}", tool.id());
-                issues = issues.saturating_add(1);
+        CleanScope::Parts { build, cache } => {
+            if build {
+                parts.push((artifact_dir.join("build"), "build artifacts".to_owned()));
+            }
+            if cache {
+                let store = match target_dir {
+                    Some(dir) => konvoy_engine::ArtifactStore::in_dir(dir),
+                    None => konvoy_engine::ArtifactStore::new(root),
+                };
+                let label = format!("artifact cache {}", store.root_dir().display());
+                parts.push((store.root_dir().to_path_buf(), label));
             }
         }
     }
-    issues
+    parts
 }
 
-fn check_maven_deps(manifest: &konvoy_config::Manifest, cwd: &std::path::Path) -> u32 {
-    let maven_deps: Vec<_> = manifest
-        .dependencies
-        .iter()
-        .filter(|(_, spec)| spec.is_maven())
-        .collect();
-
-    if maven_deps.is_empty() {
-        return 0;
+/// Remove the scoped parts of `.konvoy/` (see [`clean_parts`]).
+fn clean_project(
+    root: &std::path::Path,
+    target_dir: Option<&Path>,
+    scope: CleanScope,
+) -> CliResult {
+    for (path, label) in clean_parts(root, target_dir, scope) {
+        konvoy_util::fs::remove_dir_all_if_exists(&path)?;
+        eprintln!("    Removed {label}");
     }
+    Ok(())
+}
 
-    let mut issues = 0u32;
-
-    for (dep_name, dep_spec) in &maven_deps {
-        if let (Some(ref maven), Some(ref dep_version)) = (&dep_spec.maven, &dep_spec.version) {
-            eprintln!("  [ok] Maven dep: {} {} ({})", dep_name, dep_version, maven);
+/// Print the parts `konvoy clean` would remove that exist, with their sizes
+/// and the total, without touching them.
+fn preview_clean(parts: &[(PathBuf, String)]) -> CliResult {
+    let mut total = 0;
+    let mut found = false;
+    for (path, _) in parts {
+        if !path.exists() {
+            continue;
         }
+        let (bytes, files) = disk_usage(path)?;
+        total += bytes;
+        found = true;
+        let noun = if files == 1 { "file" } else { "files" };
+        eprintln!(
+            "    Would remove {} ({}, {files} {noun})",
+            path.display(),
+            konvoy_util::progress::format_bytes(bytes)
+        );
     }
-
-    let lockfile_path = cwd.join("konvoy.lock");
-    if !lockfile_path.exists() {
+    if !found {
+        eprintln!("    Nothing to remove");
+    } else {
         eprintln!(
-            "  [!!] No konvoy.lock found — run 'konvoy update' to resolve Maven dependencies"
+            "    Would free {} (dry run: nothing was removed)",
+            konvoy_util::progress::format_bytes(total)
         );
-        return 1;
     }
+    Ok(())
+}
 
-    match konvoy_config::lockfile::Lockfile::from_path(&lockfile_path) {
-        Ok(lockfile) => {
-            for (dep_name, _) in &maven_deps {
-                if lockfile.has_maven_entry(dep_name) {
-                    eprintln!("  [ok] Lockfile entry: {}", dep_name);
-                } else {
-                    eprintln!(
-                        "  [!!] Lockfile entry: '{}' not found — run 'konvoy update'",
-                        dep_name
-                    );
-                    issues = issues.saturating_add(1);
-                }
-            }
-        }
-        Err(e) => {
-            eprintln!("  [!!] Lockfile: {}", e);
-            issues = issues.saturating_add(1);
-        }
+/// Total size in bytes and number of the files under `path`, which may also
+/// be a single file.
+fn disk_usage(path: &Path) -> Result<(u64, usize), Box<dyn Error>> {
+    let files = if path.is_dir() {
+        konvoy_util::fs::collect_all_files(path)?
+    } else {
+        vec![path.to_path_buf()]
+    };
+    let mut bytes = 0;
+    for file in &files {
+        bytes += std::fs::metadata(file)?.len();
     }
-
-    issues
+    Ok((bytes, files.len()))
 }
 
-fn check_standalone_toolchains() -> u32 {
-    match konvoy_konanc::toolchain::list_installed() {
-        Ok(versions) if versions.is_empty() => {
-            eprintln!("  [--] No managed toolchains installed");
-            0
-        }
-        Ok(versions) => {
-            eprintln!("  [ok] Managed toolchains: {}", versions.join(", "));
-            0
-        }
-        Err(e) => {
-            eprintln!("  [!!] Managed toolchains: {e}");
-            1
+fn cmd_doctor(
+    manifest_path: Option<&Path>,
+    json: bool,
+    fix: bool,
+    network: bool,
+    net: &konvoy_util::net::NetworkClient,
+) -> CliResult {
+    let checks = |cwd: &Path| {
+        let mut report = doctor::run_checks(cwd);
+        if network {
+            doctor::check_network(&mut report, net);
         }
+        report
+    };
+    // Doctor also checks the environment outside a project, so only
+    // `--manifest-path` moves it away from the current directory.
+    let cwd = match manifest_path {
+        Some(path) => project_root(Some(path))?,
+        None => std::env::current_dir()?,
+    };
+    if !json {
+        eprintln!("Checking environment...");
+        eprintln!();
     }
-}
 
-fn cmd_doctor() -> CliResult {
-    eprintln!("Checking environment...");
-    eprintln!();
-
-    let mut issues = check_host_target();
+    let mut report = checks(&cwd);
 
-    let cwd = std::env::current_dir()?;
-    if cwd.join("konvoy.toml").exists() {
-        match konvoy_config::Manifest::from_path(&cwd.join("konvoy.toml")) {
-            Ok(manifest) => {
-                eprintln!("  [ok] Project: {}", manifest.package.name);
-                issues = issues.saturating_add(check_toolchain(&manifest));
-                issues = issues.saturating_add(check_detekt(&manifest));
-                issues = issues.saturating_add(check_codegen(&manifest));
-                issues = issues.saturating_add(check_maven_deps(&manifest, &cwd));
-            }
-            Err(e) => {
-                eprintln!("  [!!] konvoy.toml: {e}");
-                issues = issues.saturating_add(1);
+    // Apply every available fix, then re-check so the output is the final state.
+    // A failed fix is reported but does not stop the others; the re-check shows
+    // whatever is still broken.
+    let fixes = if fix { report.fixes() } else { Vec::new() };
+    if !fixes.is_empty() {
+        for f in &fixes {
+            if let Err(e) = doctor::apply_fix(f, &cwd, net) {
+                eprintln!("    fix failed: {e}");
             }
         }
-    } else {
-        eprintln!("  [--] No konvoy.toml in current directory");
-        issues = issues.saturating_add(check_standalone_toolchains());
+        eprintln!();
+        report = checks(&cwd);
     }
 
+    // Machine contract for CI: the JSON report on stdout, always exit 0 — the
+    // per-check statuses are the payload, so pipelines decide what to fail on.
+    if json {
+        println!("{:#}", report.to_json());
+        return Ok(());
+    }
+
+    for line in report.human_lines() {
+        eprintln!("{line}");
+    }
+
+    let issues = report.issues();
     eprintln!();
     if issues > 0 {
         eprintln!("{issues} issue(s) found — fix them before building");
@@ -744,8 +1939,159 @@ fn cmd_doctor() -> CliResult {
     }
 }
 
-fn cmd_check(format: CheckFormat) -> CliResult {
-    let root = project_root()?;
+fn cmd_explain(code: &str) -> CliResult {
+    let Some(explanation) = konvoy_engine::explain(code) else {
+        let needle = code.trim().to_ascii_lowercase();
+        let similar: Vec<&str> = konvoy_engine::explain::EXPLANATIONS
+            .iter()
+            .map(|e| e.code)
+            .filter(|c| !needle.is_empty() && c.contains(needle.as_str()))
+            .collect();
+        return Err(if similar.is_empty() {
+            format!("unknown error code `{code}`").into()
+        } else {
+            format!(
+                "unknown error code `{code}` — did you mean {}?",
+                similar.join(", ")
+            )
+            .into()
+        });
+    };
+    println!("{}\n", explanation.code);
+    println!("{}\n", explanation.description);
+    println!("Fix: {}", explanation.fix);
+    Ok(())
+}
+
+fn cmd_config(manifest_path: Option<&Path>, json: bool) -> CliResult {
+    let root = project_root(manifest_path)?;
+    let config = konvoy_engine::effective_config(&root)?;
+    let rendered = if json {
+        config.to_json()?
+    } else {
+        config.to_toml()?
+    };
+    println!("{}", rendered.trim_end());
+    Ok(())
+}
+
+fn cmd_metadata(manifest_path: Option<&Path>, json: bool) -> CliResult {
+    let root = project_root(manifest_path)?;
+    let manifest = konvoy_config::Manifest::from_path(&root.join("konvoy.toml"))?;
+    let metadata = package_metadata(&manifest.package);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&metadata)?);
+    } else {
+        print!("{}", render_metadata(&metadata));
+    }
+    Ok(())
+}
+
+/// The `[package]` fields `konvoy metadata` reports, as JSON. Unset optional
+/// fields are `null` and missing authors an empty array, so the keys are
+/// always present.
+fn package_metadata(package: &konvoy_config::manifest::Package) -> serde_json::Value {
+    serde_json::json!({
+        "name": package.name,
+        "version": package.version,
+        "kind": package.kind,
+        "description": package.description,
+        "authors": package.authors,
+        "license": package.license,
+    })
+}
+
+/// `key: value` lines for the fields of [`package_metadata`] that are set.
+fn render_metadata(metadata: &serde_json::Value) -> String {
+    let mut out = String::new();
+    for key in [
+        "name",
+        "version",
+        "kind",
+        "description",
+        "authors",
+        "license",
+    ] {
+        let value = match metadata.get(key) {
+            Some(serde_json::Value::String(value)) => value.clone(),
+            Some(serde_json::Value::Array(items)) if !items.is_empty() => items
+                .iter()
+                .filter_map(serde_json::Value::as_str)
+                .collect::<Vec<_>>()
+                .join(", "),
+            _ => continue,
+        };
+        let label = format!("{key}:");
+        out.push_str(&format!("{label:<12} {value}\n"));
+    }
+    out
+}
+
+fn cmd_graph(
+    manifest_path: Option<&Path>,
+    format: GraphFormat,
+    output: Option<&std::path::Path>,
+    resolver: konvoy_engine::ArtifactResolver<'_>,
+) -> CliResult {
+    let root = project_root(manifest_path)?;
+    let graph = konvoy_engine::dependency_graph(&root, resolver)?;
+    let rendered = match format {
+        GraphFormat::Text => graph.to_tree(),
+        GraphFormat::Dot => graph.to_dot(),
+    };
+    match output {
+        Some(path) => {
+            std::fs::write(path, rendered)
+                .map_err(|e| format!("cannot write {}: {e}", path.display()))?;
+            eprintln!("    Wrote dependency graph to {}", path.display());
+        }
+        None => print!("{rendered}"),
+    }
+    Ok(())
+}
+
+fn cmd_verify(manifest_path: Option<&Path>, target: &Option<String>) -> CliResult {
+    let root = project_root(manifest_path)?;
+    let report = konvoy_engine::verify(&root, target)?;
+
+    for check in &report.checks {
+        match &check.status {
+            konvoy_engine::ArtifactStatus::Verified => eprintln!("  [ok] {}", check.label),
+            konvoy_engine::ArtifactStatus::Missing => eprintln!(
+                "  [!!] {}: missing from {}",
+                check.label,
+                check.path.display()
+            ),
+            konvoy_engine::ArtifactStatus::Mismatch { expected, actual } => eprintln!(
+                "  [!!] {}: SHA-256 mismatch at {} (locked: {}, actual: {})",
+                check.label,
+                check.path.display(),
+                expected,
+                actual
+            ),
+            konvoy_engine::ArtifactStatus::LockfileOutdated { reason } => {
+                eprintln!("  [!!] {}: {reason}", check.label);
+            }
+        }
+    }
+
+    let problems = report.problem_count();
+    if problems == 0 {
+        eprintln!(
+            "    Verified {} artifact(s) against konvoy.lock",
+            report.checks.len()
+        );
+        Ok(())
+    } else {
+        Err(format!(
+            "{problems} artifact(s) failed verification — run `konvoy build` to re-download missing artifacts, or delete corrupted ones first"
+        )
+        .into())
+    }
+}
+
+fn cmd_check(manifest_path: Option<&Path>, format: CheckFormat) -> CliResult {
+    let root = project_root(manifest_path)?;
     let path = root.join("konvoy.toml");
     let content = std::fs::read_to_string(&path)
         .map_err(|e| format!("cannot read {}: {e}", path.display()))?;
@@ -777,34 +2123,83 @@ fn cmd_check(format: CheckFormat) -> CliResult {
     }
 }
 
-fn cmd_toolchain(action: ToolchainAction, net: &konvoy_util::net::NetworkClient) -> CliResult {
+fn cmd_toolchain(
+    manifest_path: Option<&Path>,
+    action: ToolchainAction,
+    net: &konvoy_util::net::NetworkClient,
+) -> CliResult {
     match action {
-        ToolchainAction::Install { version } => {
-            let version = if let Some(v) = version {
-                v
+        ToolchainAction::Install {
+            version,
+            all_targets,
+            mirror,
+        } => {
+            // `--mirror` beats KONVOY_TOOLCHAIN_MIRROR and the user config,
+            // which are already applied to `net`.
+            let mirrored;
+            let net = match mirror {
+                Some(mirror) => {
+                    if !mirror.starts_with("https://") && !mirror.starts_with("http://") {
+                        return Err(format!(
+                            "--mirror must be an http:// or https:// URL, got `{mirror}`"
+                        )
+                        .into());
+                    }
+                    mirrored = net.clone().with_toolchain_mirror(mirror);
+                    &mirrored
+                }
+                None => net,
+            };
+            let (version, jre_major) = if let Some(v) = version {
+                (v, konvoy_konanc::toolchain::DEFAULT_JRE_MAJOR)
             } else {
-                // Read version from konvoy.toml in current directory.
-                let cwd = std::env::current_dir()?;
-                let manifest_path = cwd.join("konvoy.toml");
-                let manifest = konvoy_config::Manifest::from_path(&manifest_path)?;
-                manifest.toolchain.kotlin
+                // Read version (and JRE major) from the project's konvoy.toml.
+                let root = match manifest_path {
+                    Some(path) => project_root(Some(path))?,
+                    None => std::env::current_dir()?,
+                };
+                let manifest = konvoy_config::Manifest::from_path(&root.join("konvoy.toml"))?;
+                let jre_major = manifest
+                    .toolchain
+                    .jre
+                    .unwrap_or(konvoy_konanc::toolchain::DEFAULT_JRE_MAJOR);
+                (manifest.toolchain.kotlin, jre_major)
             };
 
-            match konvoy_konanc::toolchain::is_installed(&version) {
-                Ok(true) => {
-                    eprintln!("    Kotlin/Native {version} is already installed");
-                    return Ok(());
-                }
-                Ok(false) => {}
-                Err(e) => return Err(e.into()),
+            if konvoy_konanc::toolchain::is_installed(&version, jre_major)? {
+                eprintln!("    Kotlin/Native {version} is already installed");
+            } else {
+                eprintln!("    Installing Kotlin/Native {version}...");
+                let result = konvoy_konanc::toolchain::install(&version, jre_major, net)?;
+                eprintln!(
+                    "    Installed Kotlin/Native {version} at {}",
+                    result.konanc_path.display()
+                );
             }
 
-            eprintln!("    Installing Kotlin/Native {version}...");
-            let result = konvoy_konanc::toolchain::install(&version, net)?;
-            eprintln!(
-                "    Installed Kotlin/Native {version} at {}",
-                result.konanc_path.display()
-            );
+            if all_targets {
+                prefetch_all_targets(&version, jre_major, net)?;
+            }
+            Ok(())
+        }
+        ToolchainAction::Verify => {
+            let root = project_root(manifest_path)?;
+            let manifest = konvoy_config::Manifest::from_path(&root.join("konvoy.toml"))?;
+            let version = &manifest.toolchain.kotlin;
+            let lockfile = konvoy_config::lockfile::Lockfile::from_path(&root.join("konvoy.lock"))?;
+            let Some(expected) = lockfile.konanc_fingerprint(version) else {
+                return Err(format!(
+                    "konvoy.lock records no konanc fingerprint for Kotlin/Native {version} — run `konvoy build` to record one"
+                )
+                .into());
+            };
+            if !konvoy_konanc::detect::verify_fingerprint(version, expected)? {
+                return Err(format!(
+                    "Kotlin/Native {version} is not installed — run `konvoy toolchain install`"
+                )
+                .into());
+            }
+            eprintln!("    Verified Kotlin/Native {version} against konvoy.lock");
             Ok(())
         }
         ToolchainAction::List => {
@@ -821,9 +2216,116 @@ fn cmd_toolchain(action: ToolchainAction, net: &konvoy_util::net::NetworkClient)
             }
             Ok(())
         }
+        ToolchainAction::Which => {
+            let root = project_root(manifest_path)?;
+            let manifest = konvoy_config::Manifest::from_path(&root.join("konvoy.toml"))?;
+            let version = &manifest.toolchain.kotlin;
+            let jre_major = manifest
+                .toolchain
+                .jre
+                .unwrap_or(konvoy_konanc::toolchain::DEFAULT_JRE_MAJOR);
+            let Some(resolved) = konvoy_konanc::detect::find_installed_konanc(version, jre_major)?
+            else {
+                return Err(format!(
+                    "Kotlin/Native {version} is not installed — run `konvoy toolchain install`"
+                )
+                .into());
+            };
+            eprintln!("    konanc:   {}", resolved.info.path.display());
+            eprintln!("    version:  {}", resolved.info.version);
+            eprintln!("    source:   {}", resolved.source);
+            match &resolved.jre_home {
+                Some(jre_home) => eprintln!("    jre_home: {}", jre_home.display()),
+                None => eprintln!("    jre_home: (not found)"),
+            }
+            Ok(())
+        }
+        ToolchainAction::Gc => {
+            let removed = konvoy_konanc::toolchain::gc_temp_entries()?;
+            if removed.is_empty() {
+                eprintln!("No stale install leftovers found");
+            } else {
+                for path in &removed {
+                    eprintln!("    Removed {}", path.display());
+                }
+            }
+            Ok(())
+        }
     }
 }
 
+/// Split the known targets into those konanc on `host` can build and those it
+/// cannot (Apple targets from a non-Apple host, and targets Konvoy does not
+/// support yet).
+fn partition_targets(
+    host: konvoy_targets::Target,
+) -> (Vec<konvoy_targets::Target>, Vec<konvoy_targets::Target>) {
+    konvoy_targets::KNOWN_TARGETS
+        .iter()
+        .copied()
+        .partition(|target| target.buildable_from(host))
+}
+
+/// Pre-fetch konanc's dependencies for every target buildable from this host.
+///
+/// A target that cannot be built here, or whose fetch fails, is skipped with a
+/// warning instead of aborting the rest; a summary is printed at the end.
+fn prefetch_all_targets(
+    version: &str,
+    jre_major: u32,
+    net: &konvoy_util::net::NetworkClient,
+) -> CliResult {
+    let host = konvoy_targets::host_target()?;
+    let konanc = konvoy_konanc::detect::resolve_konanc(version, jre_major, net)?;
+    let (buildable, unbuildable) = partition_targets(host);
+
+    let mut skipped = Vec::new();
+    for target in unbuildable {
+        if target.is_supported() {
+            eprintln!("    warning: skipping {target} — it cannot be built from a {host} host");
+        } else {
+            eprintln!("    warning: skipping {target} — konvoy does not support it yet");
+        }
+        skipped.push(target);
+    }
+    let mut installed = Vec::new();
+    for target in buildable {
+        eprintln!("    Fetching {target} dependencies...");
+        match konvoy_konanc::toolchain::prefetch_target(&konanc, target.to_konanc_arg()) {
+            Ok(()) => installed.push(target),
+            Err(e) => {
+                eprintln!("    warning: skipping {target}: {e}");
+                skipped.push(target);
+            }
+        }
+    }
+
+    eprintln!(
+        "    Installed {} target(s): {}",
+        installed.len(),
+        join_targets(&installed)
+    );
+    if !skipped.is_empty() {
+        eprintln!(
+            "    Skipped {} target(s): {}",
+            skipped.len(),
+            join_targets(&skipped)
+        );
+    }
+    Ok(())
+}
+
+fn join_targets(targets: &[konvoy_targets::Target]) -> String {
+    if targets.is_empty() {
+        return "none".to_owned();
+    }
+    targets
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -837,9 +2339,10 @@ mod tests {
     fn parse_init_defaults() {
         let cli = Cli::try_parse_from(["konvoy", "init"]).unwrap();
         match cli.command {
-            Command::Init { name, lib } => {
+            Command::Init { name, lib, vcs, .. } => {
                 assert!(name.is_none());
                 assert!(!lib);
+                assert_eq!(vcs, VcsChoice::Git);
             }
             other => panic!("expected Init, got {other:?}"),
         }
@@ -849,7 +2352,7 @@ mod tests {
     fn parse_init_with_name() {
         let cli = Cli::try_parse_from(["konvoy", "init", "--name", "my-app"]).unwrap();
         match cli.command {
-            Command::Init { name, lib } => {
+            Command::Init { name, lib, .. } => {
                 assert_eq!(name.as_deref(), Some("my-app"));
                 assert!(!lib);
             }
@@ -858,46 +2361,286 @@ mod tests {
     }
 
     #[test]
-    fn parse_init_lib() {
-        let cli = Cli::try_parse_from(["konvoy", "init", "--lib"]).unwrap();
+    fn parse_init_and_new_reject_invalid_names() {
+        for cmd in ["init", "new"] {
+            for name in ["my project", "../evil", "123abc"] {
+                let err = Cli::try_parse_from(["konvoy", cmd, "--name", name]).unwrap_err();
+                assert!(
+                    err.to_string().contains("is not a valid project name"),
+                    "{cmd} {name}: {err}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn parse_new_defaults() {
+        let cli = Cli::try_parse_from(["konvoy", "new"]).unwrap();
+        match cli.command {
+            Command::New { name, lib, .. } => {
+                assert!(name.is_none());
+                assert!(!lib);
+            }
+            other => panic!("expected New, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_new_name_and_lib() {
+        let cli = Cli::try_parse_from(["konvoy", "new", "--name", "tool", "--lib"]).unwrap();
+        match cli.command {
+            Command::New { name, lib, .. } => {
+                assert_eq!(name.as_deref(), Some("tool"));
+                assert!(lib);
+            }
+            other => panic!("expected New, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_init_lib() {
+        let cli = Cli::try_parse_from(["konvoy", "init", "--lib"]).unwrap();
+        match cli.command {
+            Command::Init { lib, .. } => assert!(lib),
+            other => panic!("expected Init, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_init_name_and_lib() {
+        let args = ["konvoy", "init", "--name", "mylib", "--lib"];
+        let cli = Cli::try_parse_from(args).unwrap();
+        match cli.command {
+            Command::Init { name, lib, .. } => {
+                assert_eq!(name.as_deref(), Some("mylib"));
+                assert!(lib);
+            }
+            other => panic!("expected Init, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_init_template() {
+        let cli = Cli::try_parse_from(["konvoy", "init", "--template", "cli"]).unwrap();
+        match cli.command {
+            Command::Init { template, lib, .. } => {
+                assert_eq!(template.as_deref(), Some("cli"));
+                assert!(!lib);
+            }
+            other => panic!("expected Init, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_init_vcs_none() {
+        let cli = Cli::try_parse_from(["konvoy", "init", "--vcs", "none"]).unwrap();
+        match cli.command {
+            Command::Init { vcs, .. } => assert_eq!(vcs, VcsChoice::None),
+            other => panic!("expected Init, got {other:?}"),
+        }
+        assert!(Cli::try_parse_from(["konvoy", "init", "--vcs", "hg"]).is_err());
+    }
+
+    #[test]
+    fn project_template_lib_is_shorthand() {
+        assert_eq!(
+            project_template(true, None).unwrap(),
+            konvoy_engine::ProjectTemplate::Lib
+        );
+        assert_eq!(
+            project_template(false, None).unwrap(),
+            konvoy_engine::ProjectTemplate::Bin
+        );
+        assert_eq!(
+            project_template(false, Some("cli")).unwrap(),
+            konvoy_engine::ProjectTemplate::Cli
+        );
+        assert!(project_template(false, Some("web")).is_err());
+    }
+
+    #[test]
+    fn parse_color_defaults_to_unset() {
+        // Unset falls back to NO_COLOR, then the user config, then auto.
+        let cli = Cli::try_parse_from(["konvoy", "build"]).unwrap();
+        assert_eq!(cli.color, None);
+    }
+
+    #[test]
+    fn parse_color_is_global() {
+        let cli = Cli::try_parse_from(["konvoy", "build", "--color", "never"]).unwrap();
+        assert_eq!(cli.color, Some(ColorWhen::Never));
+        let cli = Cli::try_parse_from(["konvoy", "--color", "always", "test"]).unwrap();
+        assert_eq!(cli.color, Some(ColorWhen::Always));
+    }
+
+    #[test]
+    fn error_invalid_color() {
+        let err = Cli::try_parse_from(["konvoy", "build", "--color", "sometimes"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidValue);
+    }
+
+    #[test]
+    fn parse_quiet_is_global() {
+        assert!(!Cli::try_parse_from(["konvoy", "build"]).unwrap().quiet);
+        assert!(
+            Cli::try_parse_from(["konvoy", "build", "--quiet"])
+                .unwrap()
+                .quiet
+        );
+        assert!(Cli::try_parse_from(["konvoy", "-q", "test"]).unwrap().quiet);
+        assert!(Cli::try_parse_from(["konvoy", "lint", "-q"]).unwrap().quiet);
+    }
+
+    #[test]
+    fn error_quiet_with_verbose() {
+        for cmd in ["build", "run", "test", "lint", "generate"] {
+            let cli = Cli::try_parse_from(["konvoy", cmd, "--quiet", "--verbose"]).unwrap();
+            assert!(cli.check_output_flags().is_err(), "{cmd}");
+        }
+        let cli = Cli::try_parse_from(["konvoy", "-q", "build", "-v"]).unwrap();
+        let err = cli.check_output_flags().unwrap_err();
+        assert!(err.to_string().contains("contradictory"), "{err}");
+        let cli = Cli::try_parse_from(["konvoy", "-q", "build"]).unwrap();
+        assert!(cli.check_output_flags().is_ok());
+    }
+
+    #[test]
+    fn parse_manifest_path_is_global() {
+        assert!(Cli::try_parse_from(["konvoy", "build"])
+            .unwrap()
+            .manifest_path
+            .is_none());
+        let cli =
+            Cli::try_parse_from(["konvoy", "--manifest-path", "app/konvoy.toml", "graph"]).unwrap();
+        assert_eq!(cli.manifest_path, Some(PathBuf::from("app/konvoy.toml")));
+        assert!(cli.check_output_flags().is_ok());
+        let cli = Cli::try_parse_from(["konvoy", "new", "--manifest-path", "konvoy.toml"]).unwrap();
+        let err = cli.check_output_flags().unwrap_err();
+        assert!(err.to_string().contains("--manifest-path"), "{err}");
+    }
+
+    #[test]
+    fn project_root_is_the_manifest_directory() {
+        let tmp = tempfile::tempdir().unwrap();
+        let manifest = tmp.path().join("konvoy.toml");
+        std::fs::write(&manifest, "").unwrap();
+        assert_eq!(
+            project_root(Some(&manifest)).unwrap(),
+            tmp.path().canonicalize().unwrap()
+        );
+        let err = project_root(Some(tmp.path())).unwrap_err();
+        assert!(
+            err.to_string().contains("must point to a konvoy.toml"),
+            "{err}"
+        );
+        let err = project_root(Some(&tmp.path().join("app/konvoy.toml"))).unwrap_err();
+        assert!(err.to_string().contains("manifest not found"), "{err}");
+    }
+
+    #[test]
+    fn parse_build_defaults() {
+        let cli = Cli::try_parse_from(["konvoy", "build"]).unwrap();
+        match cli.command {
+            Command::Build {
+                target,
+                release,
+                verbose,
+                force,
+                timeout,
+                dedupe_diagnostics,
+                features,
+                no_default_features,
+                timings,
+                locked,
+                offline,
+                emit_command,
+                out_dir,
+                deny_warnings,
+                keep_going,
+                dry_run,
+                print_artifact,
+            } => {
+                assert!(target.is_none());
+                assert!(!release);
+                assert!(!verbose);
+                assert!(!force);
+                assert!(timeout.is_none());
+                assert!(!dedupe_diagnostics);
+                assert!(features.is_empty());
+                assert!(!no_default_features);
+                assert!(!timings);
+                assert!(!locked);
+                assert!(!offline);
+                assert!(emit_command.is_none());
+                assert!(out_dir.is_none());
+                assert!(!deny_warnings);
+                assert!(!keep_going);
+                assert!(!dry_run);
+                assert!(!print_artifact);
+            }
+            other => panic!("expected Build, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_build_dry_run() {
+        let cli = Cli::try_parse_from(["konvoy", "build", "--dry-run", "--release"]).unwrap();
+        match cli.command {
+            Command::Build {
+                dry_run, release, ..
+            } => assert!(dry_run && release),
+            other => panic!("expected Build, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn error_build_dry_run_conflicts_with_outputs() {
+        for flag in [
+            ["--emit-command", "cmd.json"].as_slice(),
+            ["--out-dir", "dist"].as_slice(),
+            ["--timings"].as_slice(),
+            ["--print-artifact"].as_slice(),
+        ] {
+            let args = ["konvoy", "build", "--dry-run"].iter().chain(flag);
+            let err = Cli::try_parse_from(args).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::ArgumentConflict, "{flag:?}");
+        }
+    }
+
+    #[test]
+    fn parse_build_print_artifact() {
+        let cli = Cli::try_parse_from(["konvoy", "build", "--print-artifact"]).unwrap();
         match cli.command {
-            Command::Init { lib, .. } => assert!(lib),
-            other => panic!("expected Init, got {other:?}"),
+            Command::Build { print_artifact, .. } => assert!(print_artifact),
+            other => panic!("expected Build, got {other:?}"),
         }
     }
 
     #[test]
-    fn parse_init_name_and_lib() {
-        let args = ["konvoy", "init", "--name", "mylib", "--lib"];
-        let cli = Cli::try_parse_from(args).unwrap();
+    fn parse_build_keep_going() {
+        let cli = Cli::try_parse_from(["konvoy", "build", "--keep-going"]).unwrap();
         match cli.command {
-            Command::Init { name, lib } => {
-                assert_eq!(name.as_deref(), Some("mylib"));
-                assert!(lib);
-            }
-            other => panic!("expected Init, got {other:?}"),
+            Command::Build { keep_going, .. } => assert!(keep_going),
+            other => panic!("expected Build, got {other:?}"),
         }
     }
 
     #[test]
-    fn parse_build_defaults() {
-        let cli = Cli::try_parse_from(["konvoy", "build"]).unwrap();
+    fn parse_build_dedupe_diagnostics() {
+        let cli = Cli::try_parse_from(["konvoy", "build", "--dedupe-diagnostics"]).unwrap();
         match cli.command {
             Command::Build {
-                target,
-                release,
-                verbose,
-                force,
-                locked,
-                offline,
-            } => {
-                assert!(target.is_none());
-                assert!(!release);
-                assert!(!verbose);
-                assert!(!force);
-                assert!(!locked);
-                assert!(!offline);
-            }
+                dedupe_diagnostics, ..
+            } => assert!(dedupe_diagnostics),
+            other => panic!("expected Build, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_build_timings() {
+        let cli = Cli::try_parse_from(["konvoy", "build", "--timings"]).unwrap();
+        match cli.command {
+            Command::Build { timings, .. } => assert!(timings),
             other => panic!("expected Build, got {other:?}"),
         }
     }
@@ -950,6 +2693,8 @@ mod tests {
             "--release",
             "--verbose",
             "--force",
+            "--timeout",
+            "120",
             "--locked",
             "--offline",
         ])
@@ -960,20 +2705,157 @@ mod tests {
                 release,
                 verbose,
                 force,
+                timeout,
+                dedupe_diagnostics,
+                features,
+                no_default_features,
+                timings,
                 locked,
                 offline,
+                emit_command,
+                out_dir,
+                deny_warnings,
+                keep_going,
+                dry_run,
+                print_artifact,
             } => {
                 assert_eq!(target.as_deref(), Some("linux_x64"));
                 assert!(release);
                 assert!(verbose);
                 assert!(force);
+                assert_eq!(timeout, Some(120));
+                assert!(!dedupe_diagnostics);
+                assert!(features.is_empty());
+                assert!(!no_default_features);
+                assert!(!timings);
                 assert!(locked);
                 assert!(offline);
+                assert!(emit_command.is_none());
+                assert!(out_dir.is_none());
+                assert!(!deny_warnings);
+                assert!(!keep_going);
+                assert!(!dry_run);
+                assert!(!print_artifact);
+            }
+            other => panic!("expected Build, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_build_emit_command() {
+        let cli =
+            Cli::try_parse_from(["konvoy", "build", "--emit-command", "konanc.json"]).unwrap();
+        match cli.command {
+            Command::Build { emit_command, .. } => {
+                assert_eq!(emit_command, Some(PathBuf::from("konanc.json")));
+            }
+            other => panic!("expected Build, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_build_out_dir() {
+        let cli = Cli::try_parse_from(["konvoy", "build", "--out-dir", "dist"]).unwrap();
+        match cli.command {
+            Command::Build { out_dir, .. } => assert_eq!(out_dir, Some(PathBuf::from("dist"))),
+            other => panic!("expected Build, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_build_deny_warnings() {
+        let cli = Cli::try_parse_from(["konvoy", "build", "--deny-warnings"]).unwrap();
+        match cli.command {
+            Command::Build { deny_warnings, .. } => assert!(deny_warnings),
+            other => panic!("expected Build, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_build_features() {
+        let cli = Cli::try_parse_from([
+            "konvoy",
+            "build",
+            "--features",
+            "json,metrics",
+            "--no-default-features",
+        ])
+        .unwrap();
+        match cli.command {
+            Command::Build {
+                features,
+                no_default_features,
+                ..
+            } => {
+                assert_eq!(features, vec!["json", "metrics"]);
+                assert!(no_default_features);
             }
             other => panic!("expected Build, got {other:?}"),
         }
     }
 
+    #[test]
+    fn parse_run_and_test_features() {
+        let cli = Cli::try_parse_from(["konvoy", "run", "--features", "json"]).unwrap();
+        match cli.command {
+            Command::Run { features, .. } => assert_eq!(features, vec!["json"]),
+            other => panic!("expected Run, got {other:?}"),
+        }
+        let cli = Cli::try_parse_from(["konvoy", "test", "--no-default-features"]).unwrap();
+        match cli.command {
+            Command::Test {
+                no_default_features,
+                ..
+            } => assert!(no_default_features),
+            other => panic!("expected Test, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_run_env() {
+        let cli = Cli::try_parse_from([
+            "konvoy",
+            "run",
+            "--env",
+            "RUST_LOG=debug",
+            "--env",
+            "URL=http://x/?a=b",
+            "--env",
+            "EMPTY=",
+        ])
+        .unwrap();
+        match cli.command {
+            Command::Run { env, args, .. } => {
+                assert_eq!(
+                    env,
+                    [
+                        ("RUST_LOG".to_owned(), "debug".to_owned()),
+                        ("URL".to_owned(), "http://x/?a=b".to_owned()),
+                        ("EMPTY".to_owned(), String::new()),
+                    ]
+                );
+                assert!(args.is_empty());
+            }
+            other => panic!("expected Run, got {other:?}"),
+        }
+        for bad in ["NOVALUE", "=value", ""] {
+            let err = Cli::try_parse_from(["konvoy", "run", "--env", bad]).unwrap_err();
+            assert!(err.to_string().contains("KEY=VALUE"), "{bad:?} gave: {err}");
+        }
+    }
+
+    #[test]
+    fn parse_run_cwd() {
+        let cli = Cli::try_parse_from(["konvoy", "run", "--cwd", "fixtures", "--", "a"]).unwrap();
+        match cli.command {
+            Command::Run { cwd, args, .. } => {
+                assert_eq!(cwd.as_deref(), Some(Path::new("fixtures")));
+                assert_eq!(args, vec!["a"]);
+            }
+            other => panic!("expected Run, got {other:?}"),
+        }
+    }
+
     #[test]
     fn parse_build_offline() {
         let cli = Cli::try_parse_from(["konvoy", "build", "--offline"]).unwrap();
@@ -997,16 +2879,30 @@ mod tests {
                 release,
                 verbose,
                 force,
+                timeout,
+                dedupe_diagnostics,
+                features,
+                no_default_features,
                 locked,
                 offline,
+                example,
+                env,
+                cwd,
                 args,
             } => {
                 assert!(target.is_none());
                 assert!(!release);
                 assert!(!verbose);
                 assert!(!force);
+                assert!(timeout.is_none());
+                assert!(!dedupe_diagnostics);
+                assert!(features.is_empty());
+                assert!(!no_default_features);
                 assert!(!locked);
                 assert!(!offline);
+                assert!(example.is_none());
+                assert!(env.is_empty());
+                assert!(cwd.is_none());
                 assert!(args.is_empty());
             }
             other => panic!("expected Run, got {other:?}"),
@@ -1041,6 +2937,8 @@ mod tests {
             "--release",
             "--verbose",
             "--force",
+            "--timeout",
+            "120",
             "--locked",
             "--offline",
             "--",
@@ -1053,22 +2951,49 @@ mod tests {
                 release,
                 verbose,
                 force,
+                timeout,
+                dedupe_diagnostics,
+                features,
+                no_default_features,
                 locked,
                 offline,
+                example,
+                env,
+                cwd,
                 args,
             } => {
                 assert_eq!(target.as_deref(), Some("linux_x64"));
                 assert!(release);
                 assert!(verbose);
                 assert!(force);
+                assert_eq!(timeout, Some(120));
+                assert!(!dedupe_diagnostics);
+                assert!(features.is_empty());
+                assert!(!no_default_features);
                 assert!(locked);
                 assert!(offline);
+                assert!(example.is_none());
+                assert!(env.is_empty());
+                assert!(cwd.is_none());
                 assert_eq!(args, vec!["arg1"]);
             }
             other => panic!("expected Run, got {other:?}"),
         }
     }
 
+    #[test]
+    fn parse_run_example() {
+        let args = ["konvoy", "run", "--example", "hello", "--", "world"];
+        let cli = Cli::try_parse_from(args).unwrap();
+        match cli.command {
+            Command::Run { example, args, .. } => {
+                assert_eq!(example.as_deref(), Some("hello"));
+                assert_eq!(args, vec!["world"]);
+            }
+            other => panic!("expected Run, got {other:?}"),
+        }
+    }
+
     #[test]
     fn parse_run_with_passthrough_args() {
         let args = ["konvoy", "run", "--", "arg1", "arg2", "--flag"];
@@ -1119,17 +3044,31 @@ mod tests {
                 release,
                 verbose,
                 force,
+                timeout,
+                dedupe_diagnostics,
+                features,
+                no_default_features,
                 locked,
                 offline,
                 filter,
+                list,
+                junit,
+                no_run,
             } => {
                 assert!(target.is_none());
                 assert!(!release);
                 assert!(!verbose);
                 assert!(!force);
+                assert!(timeout.is_none());
+                assert!(!dedupe_diagnostics);
+                assert!(features.is_empty());
+                assert!(!no_default_features);
                 assert!(!locked);
                 assert!(!offline);
                 assert!(filter.is_none());
+                assert!(!list);
+                assert!(junit.is_none());
+                assert!(!no_run);
             }
             other => panic!("expected Test, got {other:?}"),
         }
@@ -1154,10 +3093,13 @@ mod tests {
             "--target",
             "linux_x64",
             "--force",
+            "--timeout",
+            "120",
             "--locked",
             "--offline",
             "--filter",
             "MathTest.*",
+            "--list",
         ])
         .unwrap();
         match cli.command {
@@ -1166,17 +3108,31 @@ mod tests {
                 release,
                 verbose,
                 force,
+                timeout,
+                dedupe_diagnostics,
+                features,
+                no_default_features,
                 locked,
                 offline,
                 filter,
+                list,
+                junit,
+                no_run,
             } => {
                 assert_eq!(target.as_deref(), Some("linux_x64"));
                 assert!(release);
                 assert!(verbose);
                 assert!(force);
+                assert_eq!(timeout, Some(120));
+                assert!(!dedupe_diagnostics);
+                assert!(features.is_empty());
+                assert!(!no_default_features);
                 assert!(locked);
                 assert!(offline);
                 assert_eq!(filter.as_deref(), Some("MathTest.*"));
+                assert!(list);
+                assert!(junit.is_none());
+                assert!(!no_run);
             }
             other => panic!("expected Test, got {other:?}"),
         }
@@ -1186,7 +3142,22 @@ mod tests {
     fn parse_clean_defaults() {
         let cli = Cli::try_parse_from(["konvoy", "clean"]).unwrap();
         match cli.command {
-            Command::Clean { all } => assert!(!all),
+            Command::Clean {
+                all,
+                build,
+                cache,
+                dry_run,
+            } => {
+                assert!(!all);
+                assert!(!dry_run);
+                assert_eq!(
+                    CleanScope::from_flags(all, build, cache),
+                    CleanScope::Parts {
+                        build: true,
+                        cache: false
+                    }
+                );
+            }
             other => panic!("expected Clean, got {other:?}"),
         }
     }
@@ -1195,48 +3166,377 @@ mod tests {
     fn parse_clean_all() {
         let cli = Cli::try_parse_from(["konvoy", "clean", "--all"]).unwrap();
         match cli.command {
-            Command::Clean { all } => assert!(all),
+            Command::Clean { all, .. } => assert!(all),
+            other => panic!("expected Clean, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_clean_build_and_cache_combine() {
+        let cli = Cli::try_parse_from(["konvoy", "clean", "--cache", "--build"]).unwrap();
+        match cli.command {
+            Command::Clean {
+                all, build, cache, ..
+            } => assert_eq!(
+                CleanScope::from_flags(all, build, cache),
+                CleanScope::Parts {
+                    build: true,
+                    cache: true
+                }
+            ),
+            other => panic!("expected Clean, got {other:?}"),
+        }
+        assert!(Cli::try_parse_from(["konvoy", "clean", "--all", "--cache"]).is_err());
+    }
+
+    #[test]
+    fn parse_clean_dry_run_combines_with_scope() {
+        let cli = Cli::try_parse_from(["konvoy", "clean", "--cache", "--dry-run"]).unwrap();
+        match cli.command {
+            Command::Clean { cache, dry_run, .. } => assert!(cache && dry_run),
             other => panic!("expected Clean, got {other:?}"),
         }
     }
 
     #[test]
-    fn parse_doctor() {
-        let cli = Cli::try_parse_from(["konvoy", "doctor"]).unwrap();
-        assert!(matches!(cli.command, Command::Doctor));
+    fn parse_doctor() {
+        let cli = Cli::try_parse_from(["konvoy", "doctor"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Doctor {
+                json: false,
+                fix: false,
+                network: false
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_explain() {
+        let cli = Cli::try_parse_from(["konvoy", "explain", "lockfile-out-of-date"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Explain { code } if code == "lockfile-out-of-date"
+        ));
+        assert!(Cli::try_parse_from(["konvoy", "explain"]).is_err());
+    }
+
+    #[test]
+    fn error_code_finds_engine_and_compiler_codes() {
+        let err: Box<dyn Error> = Box::new(konvoy_engine::EngineError::LockfileUpdateRequired);
+        assert_eq!(error_code(err.as_ref()), Some("lockfile-out-of-date"));
+        let err: Box<dyn Error> = Box::new(konvoy_konanc::error::KonancError::NotFound);
+        assert_eq!(error_code(err.as_ref()), Some("konanc-not-found"));
+        let err: Box<dyn Error> = "plain message".into();
+        assert_eq!(error_code(err.as_ref()), None);
+    }
+
+    #[test]
+    fn cmd_explain_rejects_unknown_codes() {
+        assert!(cmd_explain("lockfile-out-of-date").is_ok());
+        let err = cmd_explain("lockfile").unwrap_err().to_string();
+        assert!(err.contains("did you mean"), "{err}");
+        assert!(err.contains("lockfile-invalid"), "{err}");
+        let err = cmd_explain("bogus").unwrap_err().to_string();
+        assert_eq!(err, "unknown error code `bogus`");
+    }
+
+    #[test]
+    fn parse_config() {
+        let cli = Cli::try_parse_from(["konvoy", "config"]).unwrap();
+        assert!(matches!(cli.command, Command::Config { json: false }));
+        let cli = Cli::try_parse_from(["konvoy", "config", "--json"]).unwrap();
+        assert!(matches!(cli.command, Command::Config { json: true }));
+    }
+
+    #[test]
+    fn parse_metadata() {
+        let cli = Cli::try_parse_from(["konvoy", "metadata"]).unwrap();
+        assert!(matches!(cli.command, Command::Metadata { json: false }));
+        let cli = Cli::try_parse_from(["konvoy", "metadata", "--json"]).unwrap();
+        assert!(matches!(cli.command, Command::Metadata { json: true }));
+    }
+
+    #[test]
+    fn metadata_lists_the_set_package_fields() {
+        let manifest = konvoy_config::Manifest::from_str(
+            "[package]\nname = \"shared\"\nkind = \"lib\"\nversion = \"1.2.0\"\nauthors = [\"Jane Doe\", \"Build Team\"]\nlicense = \"MIT\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n",
+            "konvoy.toml",
+        )
+        .unwrap();
+        let metadata = package_metadata(&manifest.package);
+        assert_eq!(metadata["kind"], "lib");
+        assert!(metadata["description"].is_null());
+        assert_eq!(
+            render_metadata(&metadata),
+            "name:        shared\nversion:     1.2.0\nkind:        lib\nauthors:     Jane Doe, Build Team\nlicense:     MIT\n"
+        );
+    }
+
+    #[test]
+    fn parse_graph_defaults_to_text() {
+        let cli = Cli::try_parse_from(["konvoy", "graph"]).unwrap();
+        match cli.command {
+            Command::Graph {
+                format,
+                output,
+                offline,
+            } => {
+                assert_eq!(format, GraphFormat::Text);
+                assert!(output.is_none());
+                assert!(!offline);
+            }
+            other => panic!("expected Graph, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_graph_dot_to_file() {
+        let cli = Cli::try_parse_from([
+            "konvoy",
+            "graph",
+            "--format",
+            "dot",
+            "--output",
+            "deps.dot",
+            "--offline",
+        ])
+        .unwrap();
+        match cli.command {
+            Command::Graph {
+                format,
+                output,
+                offline,
+            } => {
+                assert_eq!(format, GraphFormat::Dot);
+                assert_eq!(output, Some(PathBuf::from("deps.dot")));
+                assert!(offline);
+            }
+            other => panic!("expected Graph, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_doctor_json() {
+        let cli = Cli::try_parse_from(["konvoy", "doctor", "--json"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Doctor {
+                json: true,
+                fix: false,
+                network: false
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_doctor_network() {
+        let cli = Cli::try_parse_from(["konvoy", "doctor", "--network"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Doctor {
+                json: false,
+                fix: false,
+                network: true
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_doctor_fix() {
+        let cli = Cli::try_parse_from(["konvoy", "doctor", "--fix"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Doctor {
+                json: false,
+                fix: true,
+                network: false
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_update() {
+        let cli = Cli::try_parse_from(["konvoy", "update"]).unwrap();
+        assert!(matches!(cli.command, Command::Update));
+    }
+
+    #[test]
+    fn parse_toolchain_install_with_version() {
+        let args = ["konvoy", "toolchain", "install", "2.1.0"];
+        let cli = Cli::try_parse_from(args).unwrap();
+        match cli.command {
+            Command::Toolchain {
+                action: ToolchainAction::Install { version, .. },
+            } => {
+                assert_eq!(version.as_deref(), Some("2.1.0"));
+            }
+            other => panic!("expected Toolchain Install, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_toolchain_install_no_version() {
+        let cli = Cli::try_parse_from(["konvoy", "toolchain", "install"]).unwrap();
+        match cli.command {
+            Command::Toolchain {
+                action:
+                    ToolchainAction::Install {
+                        version,
+                        all_targets,
+                        mirror,
+                    },
+            } => {
+                assert!(version.is_none());
+                assert!(!all_targets);
+                assert!(mirror.is_none());
+            }
+            other => panic!("expected Toolchain Install, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_toolchain_install_all_targets() {
+        let cli = Cli::try_parse_from(["konvoy", "toolchain", "install", "--all-targets"]).unwrap();
+        match cli.command {
+            Command::Toolchain {
+                action:
+                    ToolchainAction::Install {
+                        version,
+                        all_targets,
+                        ..
+                    },
+            } => {
+                assert!(version.is_none());
+                assert!(all_targets);
+            }
+            other => panic!("expected Toolchain Install, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_toolchain_install_mirror() {
+        let cli = Cli::try_parse_from([
+            "konvoy",
+            "toolchain",
+            "install",
+            "2.1.0",
+            "--mirror",
+            "https://mirror.example.com/kotlin",
+        ])
+        .unwrap();
+        match cli.command {
+            Command::Toolchain {
+                action: ToolchainAction::Install { mirror, .. },
+            } => {
+                assert_eq!(mirror.as_deref(), Some("https://mirror.example.com/kotlin"));
+            }
+            other => panic!("expected Toolchain Install, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn finish_note_reports_size_in_binary_units() {
+        let mut result = konvoy_engine::BuildResult {
+            outcome: konvoy_engine::BuildOutcome::Fresh,
+            output_path: PathBuf::from("app"),
+            artifact_size: 1_887_437,
+            header_path: None,
+            duration: Duration::from_secs(1),
+            timings: Vec::new(),
+            dependency_outcomes: Vec::new(),
+        };
+        assert_eq!(finish_note(&result), "(1.80 MiB)");
+        result.outcome = konvoy_engine::BuildOutcome::Cached;
+        result.artifact_size = 2048;
+        assert_eq!(finish_note(&result), "(cached, 2.00 KiB)");
+    }
+
+    #[test]
+    fn finish_note_counts_cached_dependencies() {
+        use konvoy_engine::BuildOutcome::{Cached, Fresh};
+        let mut result = konvoy_engine::BuildResult {
+            outcome: Fresh,
+            output_path: PathBuf::from("app"),
+            artifact_size: 2048,
+            header_path: None,
+            duration: Duration::from_secs(1),
+            timings: Vec::new(),
+            dependency_outcomes: vec![("core".to_owned(), Cached)],
+        };
+        assert_eq!(finish_note(&result), "(2.00 KiB, 1 of 1 dependency cached)");
+        result
+            .dependency_outcomes
+            .extend([("json".to_owned(), Fresh), ("util".to_owned(), Cached)]);
+        assert_eq!(
+            finish_note(&result),
+            "(2.00 KiB, 2 of 3 dependencies cached)"
+        );
+    }
+
+    #[test]
+    fn partition_targets_skips_apple_from_linux() {
+        use konvoy_targets::Target;
+        let (buildable, skipped) = partition_targets(Target::LinuxX64);
+        assert_eq!(buildable, vec![Target::LinuxX64, Target::LinuxArm64]);
+        assert_eq!(
+            skipped,
+            vec![Target::MacOsX64, Target::MacOsArm64, Target::WindowsX64]
+        );
+    }
+
+    #[test]
+    fn build_all_targets_fails_when_a_target_fails() {
+        // Outside a project every target's build fails; each is still
+        // attempted and the command reports the failures instead of stopping.
+        let tmp = tempfile::tempdir().unwrap();
+        let options = konvoy_engine::BuildOptions {
+            target: Some(konvoy_engine::ALL_TARGETS.to_owned()),
+            ..konvoy_engine::BuildOptions::default()
+        };
+        let err = with_resolver(
+            &konvoy_util::net::NetworkClient::new(true),
+            false,
+            |resolver| build_all_targets(tmp.path(), &options, false, resolver),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("target(s) failed"), "got: {err}");
+    }
+
+    #[test]
+    fn partition_targets_builds_every_supported_target_from_macos() {
+        let (buildable, skipped) = partition_targets(konvoy_targets::Target::MacOsArm64);
+        assert_eq!(buildable, konvoy_targets::SUPPORTED_TARGETS.to_vec());
+        assert_eq!(skipped, vec![konvoy_targets::Target::WindowsX64]);
     }
 
     #[test]
-    fn parse_update() {
-        let cli = Cli::try_parse_from(["konvoy", "update"]).unwrap();
-        assert!(matches!(cli.command, Command::Update));
+    fn parse_verify() {
+        let cli = Cli::try_parse_from(["konvoy", "verify"]).unwrap();
+        match cli.command {
+            Command::Verify { target } => assert!(target.is_none()),
+            other => panic!("expected Verify, got {other:?}"),
+        }
     }
 
     #[test]
-    fn parse_toolchain_install_with_version() {
-        let args = ["konvoy", "toolchain", "install", "2.1.0"];
-        let cli = Cli::try_parse_from(args).unwrap();
+    fn parse_verify_with_target() {
+        let cli = Cli::try_parse_from(["konvoy", "verify", "--target", "linux_arm64"]).unwrap();
         match cli.command {
-            Command::Toolchain {
-                action: ToolchainAction::Install { version },
-            } => {
-                assert_eq!(version.as_deref(), Some("2.1.0"));
-            }
-            other => panic!("expected Toolchain Install, got {other:?}"),
+            Command::Verify { target } => assert_eq!(target.as_deref(), Some("linux_arm64")),
+            other => panic!("expected Verify, got {other:?}"),
         }
     }
 
     #[test]
-    fn parse_toolchain_install_no_version() {
-        let cli = Cli::try_parse_from(["konvoy", "toolchain", "install"]).unwrap();
-        match cli.command {
+    fn parse_toolchain_verify() {
+        let cli = Cli::try_parse_from(["konvoy", "toolchain", "verify"]).unwrap();
+        assert!(matches!(
+            cli.command,
             Command::Toolchain {
-                action: ToolchainAction::Install { version },
-            } => {
-                assert!(version.is_none());
+                action: ToolchainAction::Verify
             }
-            other => panic!("expected Toolchain Install, got {other:?}"),
-        }
+        ));
     }
 
     #[test]
@@ -1250,6 +3550,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_toolchain_which() {
+        let cli = Cli::try_parse_from(["konvoy", "toolchain", "which"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Toolchain {
+                action: ToolchainAction::Which
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_toolchain_gc() {
+        let cli = Cli::try_parse_from(["konvoy", "toolchain", "gc"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Toolchain {
+                action: ToolchainAction::Gc
+            }
+        ));
+    }
+
     // ── Flag order independence ────────────────────────────────────
 
     #[test]
@@ -1298,7 +3620,7 @@ mod tests {
         let args = ["konvoy", "init", "--lib", "--name", "foo"];
         let cli = Cli::try_parse_from(args).unwrap();
         match cli.command {
-            Command::Init { name, lib } => {
+            Command::Init { name, lib, .. } => {
                 assert_eq!(name.as_deref(), Some("foo"));
                 assert!(lib);
             }
@@ -1340,6 +3662,49 @@ mod tests {
         );
     }
 
+    #[test]
+    fn error_timeout_not_a_number() {
+        let err = Cli::try_parse_from(["konvoy", "build", "--timeout", "soon"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValueValidation);
+    }
+
+    #[test]
+    fn parse_test_no_run() {
+        let cli = Cli::try_parse_from(["konvoy", "test", "--no-run"]).unwrap();
+        match cli.command {
+            Command::Test { no_run, .. } => assert!(no_run),
+            other => panic!("expected Test, got {other:?}"),
+        }
+        assert!(Cli::try_parse_from(["konvoy", "test", "--no-run", "--list"]).is_err());
+        assert!(Cli::try_parse_from(["konvoy", "test", "--no-run", "--junit", "j.xml"]).is_err());
+    }
+
+    #[test]
+    fn parse_test_junit() {
+        let cli = Cli::try_parse_from(["konvoy", "test", "--junit", "out/junit.xml"]).unwrap();
+        match cli.command {
+            Command::Test { junit, list, .. } => {
+                assert_eq!(junit, Some(PathBuf::from("out/junit.xml")));
+                assert!(!list);
+            }
+            other => panic!("expected Test, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn error_junit_conflicts_with_list() {
+        let err =
+            Cli::try_parse_from(["konvoy", "test", "--list", "--junit", "junit.xml"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn error_init_lib_conflicts_with_template() {
+        let err =
+            Cli::try_parse_from(["konvoy", "init", "--lib", "--template", "cli"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+    }
+
     #[test]
     fn error_unknown_flag_on_init() {
         let err = Cli::try_parse_from(["konvoy", "init", "--force"]).unwrap_err();
@@ -1364,7 +3729,7 @@ mod tests {
 
     #[test]
     fn error_doctor_takes_no_args() {
-        let err = Cli::try_parse_from(["konvoy", "doctor", "--fix"]).unwrap_err();
+        let err = Cli::try_parse_from(["konvoy", "doctor", "--repair"]).unwrap_err();
         assert_eq!(err.kind(), ErrorKind::UnknownArgument);
     }
 
@@ -1522,6 +3887,57 @@ mod tests {
         }
     }
 
+    #[test]
+    fn run_forwards_trailing_args_without_separator() {
+        let args = ["konvoy", "run", "--release", "foo", "bar"];
+        let cli = Cli::try_parse_from(args).unwrap();
+        match cli.command {
+            Command::Run { release, args, .. } => {
+                assert!(release);
+                assert_eq!(args, vec!["foo", "bar"]);
+            }
+            other => panic!("expected Run, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn run_forwards_flags_after_first_trailing_arg() {
+        let args = ["konvoy", "run", "foo", "--release", "-v"];
+        let cli = Cli::try_parse_from(args).unwrap();
+        match cli.command {
+            Command::Run {
+                release,
+                verbose,
+                args,
+                ..
+            } => {
+                assert!(!release);
+                assert!(!verbose);
+                assert_eq!(args, vec!["foo", "--release", "-v"]);
+            }
+            other => panic!("expected Run, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn run_separator_still_forwards_leading_flags() {
+        let args = ["konvoy", "run", "--release", "--", "--version"];
+        let cli = Cli::try_parse_from(args).unwrap();
+        match cli.command {
+            Command::Run { release, args, .. } => {
+                assert!(release);
+                assert_eq!(args, vec!["--version"]);
+            }
+            other => panic!("expected Run, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn run_unknown_flag_before_args_is_an_error() {
+        let err = Cli::try_parse_from(["konvoy", "run", "--relase", "foo"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnknownArgument);
+    }
+
     // ── Lint parsing ─────────────────────────────────────────────────
 
     #[test]
@@ -1531,11 +3947,21 @@ mod tests {
             Command::Lint {
                 verbose,
                 config,
+                force,
+                fix,
+                allow_dirty,
+                format,
+                output,
                 locked,
                 offline,
             } => {
                 assert!(!verbose);
-                assert!(config.is_none());
+                assert!(config.is_empty());
+                assert!(!force);
+                assert!(!fix);
+                assert!(!allow_dirty);
+                assert_eq!(format, LintFormat::Human);
+                assert!(output.is_none());
                 assert!(!locked);
                 assert!(!offline);
             }
@@ -1566,12 +3992,70 @@ mod tests {
         let cli = Cli::try_parse_from(["konvoy", "lint", "--config", "my-detekt.yml"]).unwrap();
         match cli.command {
             Command::Lint { config, .. } => {
-                assert_eq!(config, Some(PathBuf::from("my-detekt.yml")));
+                assert_eq!(config, vec![PathBuf::from("my-detekt.yml")]);
+            }
+            other => panic!("expected Lint, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_lint_with_multiple_configs_keeps_order() {
+        let cli = Cli::try_parse_from([
+            "konvoy",
+            "lint",
+            "--config",
+            "base.yml",
+            "--config",
+            "project.yml",
+        ])
+        .unwrap();
+        match cli.command {
+            Command::Lint { config, .. } => {
+                assert_eq!(
+                    config,
+                    vec![PathBuf::from("base.yml"), PathBuf::from("project.yml")]
+                );
+            }
+            other => panic!("expected Lint, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_lint_fix_allow_dirty() {
+        let cli = Cli::try_parse_from(["konvoy", "lint", "--fix", "--allow-dirty"]).unwrap();
+        match cli.command {
+            Command::Lint {
+                fix, allow_dirty, ..
+            } => {
+                assert!(fix);
+                assert!(allow_dirty);
+            }
+            other => panic!("expected Lint, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn error_lint_allow_dirty_without_fix() {
+        assert!(Cli::try_parse_from(["konvoy", "lint", "--allow-dirty"]).is_err());
+    }
+
+    #[test]
+    fn parse_lint_sarif_to_stdout() {
+        let cli = Cli::try_parse_from(["konvoy", "lint", "--format", "sarif"]).unwrap();
+        match cli.command {
+            Command::Lint { format, output, .. } => {
+                assert_eq!(format, LintFormat::Sarif);
+                assert!(output.is_none());
             }
             other => panic!("expected Lint, got {other:?}"),
         }
     }
 
+    #[test]
+    fn error_lint_output_without_format() {
+        assert!(Cli::try_parse_from(["konvoy", "lint", "--output", "lint.sarif"]).is_err());
+    }
+
     #[test]
     fn parse_lint_all_flags() {
         let cli = Cli::try_parse_from([
@@ -1580,6 +4064,13 @@ mod tests {
             "--verbose",
             "--config",
             "custom.yml",
+            "--force",
+            "--fix",
+            "--allow-dirty",
+            "--format",
+            "sarif",
+            "--output",
+            "lint.sarif",
             "--locked",
             "--offline",
         ])
@@ -1588,11 +4079,21 @@ mod tests {
             Command::Lint {
                 verbose,
                 config,
+                force,
+                fix,
+                allow_dirty,
+                format,
+                output,
                 locked,
                 offline,
             } => {
                 assert!(verbose);
-                assert_eq!(config, Some(PathBuf::from("custom.yml")));
+                assert_eq!(config, vec![PathBuf::from("custom.yml")]);
+                assert!(force);
+                assert!(fix);
+                assert!(allow_dirty);
+                assert_eq!(format, LintFormat::Sarif);
+                assert_eq!(output, Some(PathBuf::from("lint.sarif")));
                 assert!(locked);
                 assert!(offline);
             }
@@ -1664,6 +4165,37 @@ mod tests {
         assert_eq!(err.kind(), ErrorKind::UnknownArgument);
     }
 
+    #[test]
+    fn parse_fmt_defaults() {
+        let cli = Cli::try_parse_from(["konvoy", "fmt"]).unwrap();
+        match cli.command {
+            Command::Fmt {
+                check,
+                verbose,
+                locked,
+                offline,
+            } => {
+                assert!(!check);
+                assert!(!verbose);
+                assert!(!locked);
+                assert!(!offline);
+            }
+            other => panic!("expected Fmt, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_fmt_check() {
+        let cli = Cli::try_parse_from(["konvoy", "fmt", "--check", "--offline"]).unwrap();
+        match cli.command {
+            Command::Fmt { check, offline, .. } => {
+                assert!(check);
+                assert!(offline);
+            }
+            other => panic!("expected Fmt, got {other:?}"),
+        }
+    }
+
     #[test]
     fn help_flag_on_lint() {
         let err = Cli::try_parse_from(["konvoy", "lint", "--help"]).unwrap_err();
@@ -1692,7 +4224,7 @@ mod tests {
         let tmp = make_clean_fixture();
         let root = tmp.path();
 
-        clean_project(root, false).unwrap();
+        clean_project(root, None, CleanScope::from_flags(false, false, false)).unwrap();
 
         assert!(
             !root.join(".konvoy").join("build").exists(),
@@ -1713,7 +4245,7 @@ mod tests {
         let tmp = make_clean_fixture();
         let root = tmp.path();
 
-        clean_project(root, true).unwrap();
+        clean_project(root, None, CleanScope::All).unwrap();
 
         assert!(
             !root.join(".konvoy").exists(),
@@ -1728,7 +4260,7 @@ mod tests {
 
         std::fs::remove_dir_all(root.join(".konvoy").join("build")).unwrap();
 
-        clean_project(root, false).unwrap();
+        clean_project(root, None, CleanScope::from_flags(false, false, false)).unwrap();
 
         assert!(
             root.join(".konvoy").join("cache").exists(),
@@ -1736,6 +4268,124 @@ mod tests {
         );
     }
 
+    #[test]
+    fn clean_cache_keeps_build_dir() {
+        let tmp = make_clean_fixture();
+        let root = tmp.path();
+
+        clean_project(root, None, CleanScope::from_flags(false, false, true)).unwrap();
+
+        assert!(
+            !root.join(".konvoy").join("cache").exists(),
+            "cache dir should be removed"
+        );
+        assert!(
+            root.join(".konvoy").join("build").exists(),
+            "build dir should be preserved"
+        );
+    }
+
+    #[test]
+    fn clean_build_and_cache_removes_both() {
+        let tmp = make_clean_fixture();
+        let root = tmp.path();
+        std::fs::write(root.join(".konvoy").join("timings.json"), b"[]").unwrap();
+
+        clean_project(root, None, CleanScope::from_flags(false, true, true)).unwrap();
+
+        assert!(!root.join(".konvoy").join("build").exists());
+        assert!(!root.join(".konvoy").join("cache").exists());
+        assert!(
+            root.join(".konvoy").join("timings.json").exists(),
+            "the rest of .konvoy should be preserved"
+        );
+    }
+
+    #[test]
+    fn clean_respects_target_dir() {
+        let tmp = make_clean_fixture();
+        let root = tmp.path();
+        let target_dir = tempfile::tempdir().unwrap();
+        let shared = target_dir.path();
+        for sub in ["build", "cache", "other"] {
+            std::fs::create_dir_all(shared.join(sub)).unwrap();
+        }
+
+        clean_project(
+            root,
+            Some(shared),
+            CleanScope::from_flags(false, true, false),
+        )
+        .unwrap();
+        assert!(!shared.join("build").exists());
+        assert!(shared.join("cache").exists());
+        assert!(
+            root.join(".konvoy").join("build").exists(),
+            "the project's own .konvoy is not the build dir under --target-dir"
+        );
+
+        clean_project(root, Some(shared), CleanScope::All).unwrap();
+        assert!(!shared.join("cache").exists());
+        assert!(!root.join(".konvoy").exists());
+        assert!(
+            shared.join("other").exists(),
+            "only Konvoy's directories are removed from the target dir"
+        );
+    }
+
+    #[test]
+    fn resolve_target_dir_prefers_the_flag_over_the_environment() {
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(
+            resolve_target_dir(Some(PathBuf::from("flag")), Some("env".into())),
+            Some(cwd.join("flag"))
+        );
+        assert_eq!(
+            resolve_target_dir(None, Some("/shared/konvoy".into())),
+            Some(PathBuf::from("/shared/konvoy"))
+        );
+        assert_eq!(resolve_target_dir(None, Some("".into())), None);
+        assert_eq!(resolve_target_dir(None, None), None);
+    }
+
+    #[test]
+    fn clean_parts_follow_the_scope() {
+        let tmp = make_clean_fixture();
+        let root = tmp.path();
+        let konvoy = root.join(".konvoy");
+        let paths = |scope| -> Vec<PathBuf> {
+            clean_parts(root, None, scope)
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect()
+        };
+
+        assert_eq!(paths(CleanScope::All), [konvoy.clone()]);
+        assert_eq!(
+            paths(CleanScope::from_flags(false, false, false)),
+            [konvoy.join("build")]
+        );
+        assert_eq!(
+            paths(CleanScope::from_flags(false, true, true)),
+            [konvoy.join("build"), konvoy.join("cache")]
+        );
+    }
+
+    #[test]
+    fn dry_run_measures_without_removing() {
+        let tmp = make_clean_fixture();
+        let root = tmp.path();
+        std::fs::write(root.join(".konvoy/cache/other.json"), b"[1]").unwrap();
+
+        assert_eq!(disk_usage(&root.join(".konvoy/cache")).unwrap(), (5, 2));
+        assert_eq!(disk_usage(&root.join(".konvoy")).unwrap(), (11, 3));
+
+        let parts = clean_parts(root, None, CleanScope::All);
+        preview_clean(&parts).unwrap();
+        assert!(root.join(".konvoy/build/artifact.exe").exists());
+        assert!(root.join(".konvoy/cache/key.json").exists());
+    }
+
     #[test]
     fn clean_all_no_konvoy_dir_is_ok() {
         let tmp = make_clean_fixture();
@@ -1743,7 +4393,7 @@ mod tests {
 
         std::fs::remove_dir_all(root.join(".konvoy")).unwrap();
 
-        clean_project(root, true).unwrap();
+        clean_project(root, None, CleanScope::All).unwrap();
     }
 
     // ── Flag → Profile mapping ─────────────────────────────────────
@@ -1758,6 +4408,102 @@ mod tests {
         assert_eq!(profile_from_flag(true), konvoy_config::Profile::Release);
     }
 
+    #[test]
+    fn parse_bench_defaults_to_release() {
+        let cli = Cli::try_parse_from(["konvoy", "bench"]).unwrap();
+        match cli.command {
+            Command::Bench { debug, filter, .. } => {
+                assert!(!debug);
+                assert!(filter.is_none());
+                assert_eq!(profile_from_flag(!debug), konvoy_config::Profile::Release);
+            }
+            other => panic!("expected Bench, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_bench_debug_and_filter() {
+        let cli =
+            Cli::try_parse_from(["konvoy", "bench", "--debug", "--filter", "parse*"]).unwrap();
+        match cli.command {
+            Command::Bench { debug, filter, .. } => {
+                assert!(debug);
+                assert_eq!(filter.as_deref(), Some("parse*"));
+            }
+            other => panic!("expected Bench, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn bench_runner_args_forward_filter() {
+        assert!(BenchRun { filter: None }.runner_args().is_empty());
+        assert_eq!(
+            BenchRun {
+                filter: Some("parse*".to_owned())
+            }
+            .runner_args(),
+            vec!["--filter", "parse*"]
+        );
+    }
+
+    // ── TestRun::runner_args ──────────────────────────────────────
+
+    fn test_run(filter: Option<&str>, list: bool, junit: Option<&str>) -> TestRun {
+        TestRun {
+            filter: filter.map(str::to_owned),
+            list,
+            junit: junit.map(PathBuf::from),
+            no_run: false,
+        }
+    }
+
+    #[test]
+    fn runner_args_empty_by_default() {
+        assert!(test_run(None, false, None).runner_args().is_empty());
+    }
+
+    #[test]
+    fn runner_args_filter_only() {
+        let args = test_run(Some("MathTest.*"), false, None).runner_args();
+        assert_eq!(args, vec!["--ktest_filter=MathTest.*"]);
+    }
+
+    #[test]
+    fn runner_args_list_narrowed_by_filter() {
+        let args = test_run(Some("MathTest.*"), true, None).runner_args();
+        assert_eq!(
+            args,
+            vec!["--ktest_filter=MathTest.*", "--ktest_list_tests"]
+        );
+    }
+
+    #[test]
+    fn runner_args_list_only() {
+        let args = test_run(None, true, None).runner_args();
+        assert_eq!(args, vec!["--ktest_list_tests"]);
+    }
+
+    #[test]
+    fn runner_args_junit_requests_gtest_logger() {
+        let args = test_run(Some("MathTest.*"), false, Some("junit.xml")).runner_args();
+        assert_eq!(
+            args,
+            vec!["--ktest_filter=MathTest.*", "--ktest_logger=GTEST"]
+        );
+    }
+
+    // ── run_teeing_stdout ─────────────────────────────────────────
+
+    #[cfg(unix)]
+    #[test]
+    fn run_teeing_stdout_captures_output_and_status() {
+        let mut cmd = std::process::Command::new("sh");
+        cmd.args(["-c", "echo one; echo two; exit 3"]);
+        let (status, stdout) = run_teeing_stdout(&mut cmd).unwrap();
+        assert_eq!(status.code(), Some(3));
+        assert_eq!(stdout, "one\ntwo\n");
+    }
+
     // ── build_options constructor ──────────────────────────────────
 
     #[test]
@@ -1767,19 +4513,34 @@ mod tests {
             konvoy_config::Profile::Release,
             true,
             true,
+            Some(90),
+            true,
+            konvoy_util::color::ColorChoice::Always,
         );
         assert_eq!(opts.target.as_deref(), Some("linux_x64"));
         assert_eq!(opts.profile, konvoy_config::Profile::Release);
         assert!(opts.verbose);
         assert!(opts.force);
+        assert_eq!(opts.timeout, Some(Duration::from_secs(90)));
+        assert!(opts.dedupe_diagnostics);
+        assert_eq!(opts.color, konvoy_util::color::ColorChoice::Always);
     }
 
     #[test]
     fn build_options_defaults_are_false() {
-        let opts = build_options(None, konvoy_config::Profile::Debug, false, false);
+        let opts = build_options(
+            None,
+            konvoy_config::Profile::Debug,
+            false,
+            false,
+            None,
+            false,
+            konvoy_util::color::ColorChoice::Auto,
+        );
         assert!(opts.target.is_none());
         assert_eq!(opts.profile, konvoy_config::Profile::Debug);
         assert!(!opts.verbose);
         assert!(!opts.force);
+        assert!(opts.timeout.is_none());
     }
 }
@@ -57,6 +57,74 @@ fn test_outside_project_reports_missing_manifest() {
     assert!(stderr.contains("no konvoy.toml"), "stderr was: {stderr}");
 }
 
+#[test]
+fn verify_outside_project_reports_missing_manifest() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (ok, _stdout, stderr) = run_in(tmp.path(), &["verify"]);
+    assert!(!ok);
+    assert!(stderr.contains("no konvoy.toml"), "stderr was: {stderr}");
+}
+
+#[test]
+fn verify_reports_unpinned_toolchain() {
+    let tmp = tempfile::tempdir().unwrap();
+    write_manifest(
+        tmp.path(),
+        "[package]\nname = \"app\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n",
+    );
+    let (ok, _stdout, stderr) = run_in(tmp.path(), &["verify"]);
+    assert!(!ok, "verify should fail without a lockfile");
+    assert!(
+        stderr.contains("[!!] Kotlin/Native 2.1.0") && stderr.contains("failed verification"),
+        "stderr was: {stderr}"
+    );
+}
+
+#[test]
+fn toolchain_which_reports_missing_toolchain() {
+    let tmp = tempfile::tempdir().unwrap();
+    write_manifest(
+        tmp.path(),
+        "[package]\nname = \"app\"\n\n[toolchain]\nkotlin = \"0.0.0-which-missing\"\n",
+    );
+    let (ok, _stdout, stderr) = run_in(tmp.path(), &["toolchain", "which"]);
+    assert!(!ok, "which should fail when nothing is installed");
+    assert!(
+        stderr.contains("0.0.0-which-missing is not installed")
+            && stderr.contains("konvoy toolchain install"),
+        "stderr was: {stderr}"
+    );
+}
+
+// ── `konvoy new` (in-place scaffolding) ───────────────────────────────
+
+#[test]
+fn new_initializes_current_directory() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path().join("my-tool");
+    std::fs::create_dir(&dir).unwrap();
+    let (ok, _stdout, stderr) = run_in(&dir, &["new"]);
+    assert!(ok, "stderr was: {stderr}");
+    let manifest = std::fs::read_to_string(dir.join("konvoy.toml")).unwrap();
+    assert!(manifest.contains("name = \"my-tool\""), "{manifest}");
+    assert!(dir.join("src/main.kt").exists());
+
+    let (ok, _stdout, stderr) = run_in(&dir, &["new"]);
+    assert!(!ok, "a second `konvoy new` must not overwrite the project");
+    assert!(stderr.contains("already exists"), "stderr was: {stderr}");
+}
+
+#[test]
+fn new_refuses_non_empty_src() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::create_dir(tmp.path().join("src")).unwrap();
+    std::fs::write(tmp.path().join("src/app.kt"), "fun main() {}\n").unwrap();
+    let (ok, _stdout, stderr) = run_in(tmp.path(), &["new", "--name", "app"]);
+    assert!(!ok);
+    assert!(stderr.contains("not empty"), "stderr was: {stderr}");
+    assert!(!tmp.path().join("konvoy.toml").exists());
+}
+
 // ── Profile flag dispatch (exercises profile_from_flag + dispatch arms) ─
 
 #[test]
@@ -83,6 +151,52 @@ fn test_dispatches_with_filter() {
     assert!(!ok);
 }
 
+// ── `test` with a comma-separated --target list ────────────────────────
+
+fn write_test_project(dir: &Path) {
+    write_manifest(
+        dir,
+        "[package]\nname = \"app\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n",
+    );
+    std::fs::create_dir_all(dir.join("src/test")).unwrap();
+    std::fs::write(dir.join("src/main.kt"), "fun main() {}\n").unwrap();
+}
+
+#[test]
+fn test_multiple_targets_reports_each_and_fails_at_the_end() {
+    let tmp = tempfile::tempdir().unwrap();
+    write_test_project(tmp.path());
+    let (ok, _stdout, stderr) = run_in(tmp.path(), &["test", "--target", "bogus,nope"]);
+    assert!(!ok);
+    assert!(stderr.contains("Testing nope"), "stderr was: {stderr}");
+    assert!(
+        stderr.contains("bogus: build failed"),
+        "stderr was: {stderr}"
+    );
+    assert!(
+        stderr.contains("tests failed on 2 target(s): bogus, nope"),
+        "stderr was: {stderr}"
+    );
+}
+
+#[test]
+fn test_multiple_targets_rejects_junit() {
+    let tmp = tempfile::tempdir().unwrap();
+    write_test_project(tmp.path());
+    let (ok, _stdout, stderr) = run_in(
+        tmp.path(),
+        &[
+            "test",
+            "--target",
+            "linux_x64,macos_arm64",
+            "--junit",
+            "r.xml",
+        ],
+    );
+    assert!(!ok);
+    assert!(stderr.contains("single --target"), "stderr was: {stderr}");
+}
+
 // ── `run` against a library project: the "cannot run a library" branch ─
 
 #[test]
@@ -111,6 +225,246 @@ kotlin = "2.1.0"
     );
 }
 
+#[test]
+fn run_rejects_missing_cwd_before_building() {
+    let tmp = tempfile::tempdir().unwrap();
+    write_manifest(
+        tmp.path(),
+        r#"
+[package]
+name = "my-app"
+
+[toolchain]
+kotlin = "2.1.0"
+"#,
+    );
+
+    let (ok, _stdout, stderr) = run_in(tmp.path(), &["run", "--cwd", "missing"]);
+    assert!(!ok, "run with a missing --cwd must fail");
+    assert!(
+        stderr.contains("--cwd missing is not a directory"),
+        "expected --cwd error, got stderr: {stderr}"
+    );
+    assert!(!stderr.contains("Compiling"), "stderr was: {stderr}");
+}
+
+#[test]
+fn run_on_c_library_project_fails_with_actionable_error() {
+    let tmp = tempfile::tempdir().unwrap();
+    write_manifest(
+        tmp.path(),
+        r#"
+[package]
+name = "ffi"
+kind = "cdylib"
+
+[toolchain]
+kotlin = "2.1.0"
+"#,
+    );
+    std::fs::create_dir_all(tmp.path().join("src")).unwrap();
+    std::fs::write(tmp.path().join("src").join("lib.kt"), "fun lib() {}").unwrap();
+
+    let (ok, _stdout, stderr) = run_in(tmp.path(), &["run"]);
+    assert!(!ok, "run on a cdylib project must fail");
+    assert!(
+        stderr.contains("cannot run a library project"),
+        "expected library-project error, got stderr: {stderr}"
+    );
+}
+
+#[test]
+fn run_unknown_example_lists_available_examples() {
+    let tmp = tempfile::tempdir().unwrap();
+    write_manifest(
+        tmp.path(),
+        r#"
+[package]
+name = "my-lib"
+kind = "lib"
+
+[toolchain]
+kotlin = "2.1.0"
+"#,
+    );
+    std::fs::create_dir_all(tmp.path().join("src")).unwrap();
+    std::fs::write(tmp.path().join("src").join("lib.kt"), "fun lib() {}").unwrap();
+    std::fs::create_dir_all(tmp.path().join("examples")).unwrap();
+    std::fs::write(
+        tmp.path().join("examples").join("hello.kt"),
+        "fun main() {}",
+    )
+    .unwrap();
+
+    let (ok, _stdout, stderr) = run_in(tmp.path(), &["run", "--example", "nope", "--offline"]);
+    assert!(!ok, "unknown example must fail");
+    assert!(
+        stderr.contains("no example named `nope`") && stderr.contains("hello"),
+        "expected unknown-example error listing `hello`, got stderr: {stderr}"
+    );
+}
+
+// ── `--manifest-path`: a project root other than the current directory ─
+
+#[test]
+fn manifest_path_resolves_path_dependencies_from_the_manifest_directory() {
+    let tmp = tempfile::tempdir().unwrap();
+    for (name, kind, deps) in [
+        ("core", "lib", ""),
+        (
+            "app",
+            "bin",
+            "\n[dependencies]\ncore = { path = \"../core\" }\n",
+        ),
+    ] {
+        let dir = tmp.path().join(name);
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        write_manifest(
+            &dir,
+            &format!(
+                "[package]\nname = \"{name}\"\nkind = \"{kind}\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n{deps}"
+            ),
+        );
+    }
+
+    let (ok, stdout, stderr) = run_in(
+        tmp.path(),
+        &["graph", "--offline", "--manifest-path", "app/konvoy.toml"],
+    );
+    assert!(ok, "graph should succeed, stderr: {stderr}");
+    assert!(
+        stdout.contains("app") && stdout.contains("core"),
+        "expected app and its path dependency core, got stdout: {stdout}"
+    );
+}
+
+#[test]
+fn manifest_path_must_exist() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (ok, _stdout, stderr) = run_in(
+        tmp.path(),
+        &["build", "--manifest-path", "missing/konvoy.toml"],
+    );
+    assert!(!ok);
+    assert!(
+        stderr.contains("manifest not found at `missing/konvoy.toml`"),
+        "stderr was: {stderr}"
+    );
+}
+
+#[test]
+fn manifest_path_is_rejected_by_init() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (ok, _stdout, stderr) = run_in(
+        tmp.path(),
+        &["init", "--name", "demo", "--manifest-path", "konvoy.toml"],
+    );
+    assert!(!ok);
+    assert!(stderr.contains("--manifest-path"), "stderr was: {stderr}");
+    assert!(!tmp.path().join("demo").exists());
+}
+
+// ── `KONVOY_KONANC` override ──────────────────────────────────────────
+
+/// A `konanc` stand-in reporting a dev version that writes `<out>.kexe`, the
+/// way konanc names a Linux program.
+#[cfg(unix)]
+fn write_fake_konanc(dir: &Path) -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let script = dir.join("konanc");
+    std::fs::write(
+        &script,
+        "#!/bin/sh\n\
+         if [ \"$1\" = -version ]; then echo 'info: kotlinc-native 2.3.255-SNAPSHOT'; exit 0; fi\n\
+         while [ $# -gt 0 ]; do [ \"$1\" = -o ] && out=\"$2\"; shift; done\n\
+         echo fake > \"$out.kexe\"\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    script
+}
+
+#[cfg(unix)]
+#[test]
+fn override_build_leaves_the_toolchain_pin_untouched_under_locked() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = tmp.path().join("app");
+    std::fs::create_dir_all(project.join("src")).unwrap();
+    std::fs::write(project.join("src/main.kt"), "fun main() {}\n").unwrap();
+    write_manifest(
+        &project,
+        "[package]\nname = \"app\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n",
+    );
+    let lockfile = "version = 2\n\n\
+                    [toolchain]\n\
+                    konanc_version = \"2.1.0\"\n\
+                    konanc_tarball_sha256 = \"aaaa\"\n\
+                    konanc_tarball_url = \"https://mirror.example.com/kotlin-native.tar.gz\"\n\
+                    jre_tarball_sha256 = \"bbbb\"\n\
+                    konanc_fingerprint = \"cccc\"\n";
+    std::fs::write(project.join("konvoy.lock"), lockfile).unwrap();
+    let konanc = write_fake_konanc(tmp.path());
+
+    let output = Command::new(konvoy_bin())
+        .args(["build", "--locked"])
+        .current_dir(&project)
+        .env("HOME", tmp.path())
+        .env("KONVOY_KONANC", &konanc)
+        .output()
+        .expect("failed to spawn konvoy");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(output.status.success(), "stderr was: {stderr}");
+    assert_eq!(
+        std::fs::read_to_string(project.join("konvoy.lock")).unwrap(),
+        lockfile
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn build_without_the_feature_keeps_the_optional_git_pin() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = tmp.path().join("app");
+    std::fs::create_dir_all(project.join("src")).unwrap();
+    std::fs::write(project.join("src/main.kt"), "fun main() {}\n").unwrap();
+    write_manifest(
+        &project,
+        "[package]\nname = \"app\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n\n\
+         [dependencies]\n\
+         extra = { git = \"https://example.com/extra.git\", rev = \"main\", optional = true }\n",
+    );
+    let lockfile = "version = 2\n\n\
+                    [toolchain]\n\
+                    konanc_version = \"2.1.0\"\n\n\
+                    [[dependencies]]\n\
+                    name = \"extra\"\n\
+                    source_type = \"git\"\n\
+                    url = \"https://example.com/extra.git\"\n\
+                    rev = \"main\"\n\
+                    commit = \"0123456789abcdef0123456789abcdef01234567\"\n\
+                    source_hash = \"abcdef1234\"\n";
+    std::fs::write(project.join("konvoy.lock"), lockfile).unwrap();
+    let konanc = write_fake_konanc(tmp.path());
+
+    let output = Command::new(konvoy_bin())
+        .arg("build")
+        .current_dir(&project)
+        .env("HOME", tmp.path())
+        .env("KONVOY_KONANC", &konanc)
+        .output()
+        .expect("failed to spawn konvoy");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(output.status.success(), "stderr was: {stderr}");
+    assert!(!stderr.contains("extra"), "stderr was: {stderr}");
+    assert_eq!(
+        std::fs::read_to_string(project.join("konvoy.lock")).unwrap(),
+        lockfile
+    );
+}
+
 // ── Help / version sanity (exercises clap dispatch fall-through) ──────
 
 #[test]
@@ -1,10 +1,14 @@
 #![forbid(unsafe_code)]
-//! Parse and validate `konvoy.toml` and `konvoy.lock`.
+//! Parse and validate `konvoy.toml`, `konvoy.lock`, and the user-wide
+//! `~/.konvoy/config.toml`.
 
 pub mod lockfile;
 pub mod manifest;
 pub mod profile;
+pub mod user_config;
+pub mod version_req;
 
 pub use lockfile::Lockfile;
 pub use manifest::Manifest;
 pub use profile::Profile;
+pub use user_config::UserConfig;
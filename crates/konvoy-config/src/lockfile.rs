@@ -1,10 +1,37 @@
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
+use crate::version_req::VersionReq;
+
+pub use konvoy_util::hash::HashAlgorithm;
+
+/// The newest `konvoy.lock` schema version this build of Konvoy reads and
+/// writes.
+///
+/// Bump it whenever the on-disk layout changes, and teach
+/// [`Lockfile::from_path`]'s migration step to upgrade the previous version.
+/// A lockfile is written with the lowest version that can hold it (see
+/// [`Lockfile::required_version`]), so one that uses nothing new stays
+/// readable by older Konvoy.
+///
+/// Version 2 added Maven `hash_algorithm`, which a reader that drops the key
+/// would check SHA-512 pins without, and the toolchain's `konanc_tarball_url`,
+/// `ktlint_version`, and `ktlint_jar_sha256`, which an older reader rejects
+/// as unknown fields.
+pub const LOCKFILE_VERSION: u32 = 2;
+
+/// The version a lockfile that uses no key added since is written with.
+const BASE_LOCKFILE_VERSION: u32 = 1;
+
 /// The `konvoy.lock` lockfile.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Lockfile {
+    /// Schema version the lockfile is written with: the lowest that can hold
+    /// its contents. Lockfiles from before versioning have no `version` key
+    /// and are read as the current version.
+    #[serde(default = "current_version")]
+    pub version: u32,
     #[serde(default)]
     pub toolchain: Option<ToolchainLock>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -15,6 +42,22 @@ pub struct Lockfile {
     pub codegen_tools: Vec<CodegenToolLock>,
 }
 
+impl Default for Lockfile {
+    fn default() -> Self {
+        Self {
+            version: BASE_LOCKFILE_VERSION,
+            toolchain: None,
+            dependencies: Vec::new(),
+            plugins: Vec::new(),
+            codegen_tools: Vec::new(),
+        }
+    }
+}
+
+fn current_version() -> u32 {
+    LOCKFILE_VERSION
+}
+
 /// A locked code-generation tool entry (e.g. the Fabrikt JAR) in the lockfile.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -50,12 +93,30 @@ pub struct ToolchainLock {
     pub konanc_version: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub konanc_tarball_sha256: Option<String>,
+    /// URL the konanc tarball pinned by `konanc_tarball_sha256` was
+    /// downloaded from, which differs from the default under a toolchain
+    /// mirror. Informational: it is not part of the build cache key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub konanc_tarball_url: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub jre_tarball_sha256: Option<String>,
+    /// JDK major of the bundled JRE that `jre_tarball_sha256` pins, from
+    /// `[toolchain] jre`. Absent for the default JDK (21).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jre_major: Option<u32>,
+    /// SHA-256 of the installed `konanc` launcher, recorded so `konvoy doctor` and
+    /// `konvoy toolchain verify` can detect a modified or partial install (the
+    /// tarball itself is discarded after extraction and cannot be re-hashed).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub konanc_fingerprint: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub detekt_version: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub detekt_jar_sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ktlint_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ktlint_jar_sha256: Option<String>,
 }
 
 /// A locked dependency entry.
@@ -90,15 +151,52 @@ pub enum DepSource {
         /// Most dependencies do not have a classifier.
         #[serde(default, skip_serializing_if = "Option::is_none")]
         classifier: Option<String>,
+        /// Algorithm of the `targets` hashes. Omitted for SHA-256, so entries
+        /// of either algorithm can sit side by side in one lockfile.
+        #[serde(default, skip_serializing_if = "HashAlgorithm::is_sha256")]
+        hash_algorithm: HashAlgorithm,
     },
+    Git {
+        /// Repository URL, as declared in `konvoy.toml`.
+        url: String,
+        /// The requested commit, tag, or branch, as declared in `konvoy.toml`.
+        rev: String,
+        /// The concrete commit `rev` resolved to; builds check out exactly this.
+        commit: String,
+    },
+}
+
+impl DepSource {
+    /// The `source_type` this source is written with: `path`, `maven`, or `git`.
+    #[must_use]
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DepSource::Path { .. } => "path",
+            DepSource::Maven { .. } => "maven",
+            DepSource::Git { .. } => "git",
+        }
+    }
+}
+
+/// Sort dependency entries into lockfile order: by name, then by source kind.
+pub fn sort_dependency_locks(dependencies: &mut [DependencyLock]) {
+    dependencies.sort_by(|a, b| {
+        (a.name.as_str(), a.source.kind()).cmp(&(b.name.as_str(), b.source.kind()))
+    });
 }
 
 impl Lockfile {
     /// Read and parse a `konvoy.lock` from the given path.
     /// Returns a default lockfile if the file does not exist.
     ///
+    /// Lockfiles written with an older schema version are migrated in memory
+    /// to [`LOCKFILE_VERSION`]. The result's `version` is then the one it
+    /// would be written with, its [`required_version`](Self::required_version).
+    ///
     /// # Errors
-    /// Returns an error if the file exists but cannot be read or contains invalid TOML.
+    /// Returns an error if the file exists but cannot be read, contains invalid
+    /// TOML, or was written by a newer Konvoy with a schema this build does not
+    /// understand.
     pub fn from_path(path: &Path) -> Result<Self, LockfileError> {
         if !path.exists() {
             return Ok(Self::default());
@@ -107,22 +205,65 @@ impl Lockfile {
             path: path.display().to_string(),
             source: e,
         })?;
-        let lockfile: Lockfile = toml::from_str(&content).map_err(|e| LockfileError::Parse {
+        Self::parse(&content, path, LOCKFILE_VERSION)
+    }
+
+    /// Parse lockfile `content` read from `path` as a Konvoy that supports
+    /// schema versions up to `supported` would.
+    fn parse(content: &str, path: &Path, supported: u32) -> Result<Self, LockfileError> {
+        let parse_error = |e| LockfileError::Parse {
             path: path.display().to_string(),
             source: e,
-        })?;
+        };
+        let mut table: toml::Table = toml::from_str(content).map_err(parse_error)?;
+        let found = match table.get("version") {
+            // Lockfiles from before versioning carry no `version` key.
+            None => 0,
+            Some(value) => value
+                .as_integer()
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or_else(|| LockfileError::InvalidVersion {
+                    path: path.display().to_string(),
+                    value: value.to_string(),
+                })?,
+        };
+        if found > supported {
+            return Err(LockfileError::NewerVersion {
+                path: path.display().to_string(),
+                found,
+                supported,
+            });
+        }
+        migrate(&mut table, found);
+        let mut lockfile: Lockfile = table.try_into().map_err(parse_error)?;
+        lockfile.version = lockfile.required_version();
         Ok(lockfile)
     }
 
+    /// The recorded `konanc` fingerprint, if the lockfile pins `version`.
+    #[must_use]
+    pub fn konanc_fingerprint(&self, version: &str) -> Option<&str> {
+        self.toolchain
+            .as_ref()
+            .filter(|tc| tc.konanc_version == version)
+            .and_then(|tc| tc.konanc_fingerprint.as_deref())
+    }
+
     /// Create a lockfile with a pinned toolchain version.
     pub fn with_toolchain(version: &str) -> Self {
         Self {
+            version: BASE_LOCKFILE_VERSION,
             toolchain: Some(ToolchainLock {
                 konanc_version: version.to_owned(),
                 konanc_tarball_sha256: None,
+                konanc_tarball_url: None,
                 jre_tarball_sha256: None,
+                jre_major: None,
+                konanc_fingerprint: None,
                 detekt_version: None,
                 detekt_jar_sha256: None,
+                ktlint_version: None,
+                ktlint_jar_sha256: None,
             }),
             dependencies: Vec::new(),
             plugins: Vec::new(),
@@ -137,12 +278,18 @@ impl Lockfile {
         jre_sha256: Option<&str>,
     ) -> Self {
         Self {
+            version: BASE_LOCKFILE_VERSION,
             toolchain: Some(ToolchainLock {
                 konanc_version: version.to_owned(),
                 konanc_tarball_sha256: konanc_sha256.map(str::to_owned),
+                konanc_tarball_url: None,
                 jre_tarball_sha256: jre_sha256.map(str::to_owned),
+                jre_major: None,
+                konanc_fingerprint: None,
                 detekt_version: None,
                 detekt_jar_sha256: None,
+                ktlint_version: None,
+                ktlint_jar_sha256: None,
             }),
             dependencies: Vec::new(),
             plugins: Vec::new(),
@@ -150,7 +297,46 @@ impl Lockfile {
         }
     }
 
-    /// Write the lockfile to disk as human-readable TOML.
+    /// The lowest schema version that can hold this lockfile: 2 if a Maven
+    /// entry is pinned with another algorithm than SHA-256, or the toolchain
+    /// records its tarball URL or a ktlint pin, else 1.
+    #[must_use]
+    pub fn required_version(&self) -> u32 {
+        let non_sha256 = self.dependencies.iter().any(|d| {
+            matches!(&d.source, DepSource::Maven { hash_algorithm, .. } if !hash_algorithm.is_sha256())
+        });
+        let version_2_toolchain = self.toolchain.as_ref().is_some_and(|tc| {
+            tc.konanc_tarball_url.is_some()
+                || tc.ktlint_version.is_some()
+                || tc.ktlint_jar_sha256.is_some()
+        });
+        if non_sha256 || version_2_toolchain {
+            2
+        } else {
+            BASE_LOCKFILE_VERSION
+        }
+    }
+
+    /// Put every list in its canonical order, so the same pins always
+    /// serialize to the same bytes: dependencies by name then source kind
+    /// (each Maven entry's `required_by` sorted too), plugins by name then
+    /// coordinate, and codegen tools by name. The `version` becomes the
+    /// [`required_version`](Self::required_version).
+    pub fn canonicalize(&mut self) {
+        self.version = self.required_version();
+        sort_dependency_locks(&mut self.dependencies);
+        for dep in &mut self.dependencies {
+            if let DepSource::Maven { required_by, .. } = &mut dep.source {
+                required_by.sort();
+            }
+        }
+        self.plugins
+            .sort_by(|a, b| (&a.name, &a.maven).cmp(&(&b.name, &b.maven)));
+        self.codegen_tools.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+
+    /// Write the lockfile to disk as human-readable TOML, in the order
+    /// [`Self::canonicalize`] gives.
     ///
     /// Uses an atomic write-to-temp-then-rename pattern so that readers never
     /// observe a partially-written lockfile.
@@ -158,8 +344,10 @@ impl Lockfile {
     /// # Errors
     /// Returns an error if serialization fails or the file cannot be written.
     pub fn write_to(&self, path: &Path) -> Result<(), LockfileError> {
-        let content =
-            toml::to_string_pretty(self).map_err(|e| LockfileError::Serialize { source: e })?;
+        let mut canonical = self.clone();
+        canonical.canonicalize();
+        let content = toml::to_string_pretty(&canonical)
+            .map_err(|e| LockfileError::Serialize { source: e })?;
         let tmp_path = path.with_extension("lock.tmp");
         std::fs::write(&tmp_path, &content).map_err(|e| LockfileError::Write {
             path: tmp_path.display().to_string(),
@@ -179,6 +367,21 @@ impl Lockfile {
             .any(|d| d.name == dep_name && matches!(&d.source, DepSource::Maven { .. }))
     }
 
+    /// The commit pinned for git dependency `dep_name`, if the lockfile has an
+    /// entry for the same `url` and `rev`. Changing either in `konvoy.toml`
+    /// invalidates the pin.
+    #[must_use]
+    pub fn git_commit(&self, dep_name: &str, url: &str, rev: &str) -> Option<&str> {
+        self.dependencies.iter().find_map(|d| match &d.source {
+            DepSource::Git {
+                url: u,
+                rev: r,
+                commit,
+            } if d.name == dep_name && u == url && r == rev => Some(commit.as_str()),
+            _ => None,
+        })
+    }
+
     /// Whether the lockfile pins a Maven dependency at the given
     /// `groupId:artifactId` coordinate and version.
     ///
@@ -193,6 +396,42 @@ impl Lockfile {
                 if m == maven && v == version)
         })
     }
+
+    /// Whether the lockfile pins the Maven coordinate at a version satisfying
+    /// `req` — the [`Self::has_maven_coord`] check for a `[dependencies]`
+    /// version range, which the lock records as the concrete version it
+    /// resolved to.
+    #[must_use]
+    pub fn has_maven_coord_matching(&self, maven: &str, req: &VersionReq) -> bool {
+        self.maven_versions(maven).any(|v| req.matches(v))
+    }
+
+    /// The versions pinned for a Maven `groupId:artifactId` coordinate.
+    pub fn maven_versions<'a>(&'a self, maven: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.dependencies
+            .iter()
+            .filter_map(move |d| match &d.source {
+                DepSource::Maven {
+                    maven: m, version, ..
+                } if m == maven => Some(version.as_str()),
+                _ => None,
+            })
+    }
+}
+
+/// Upgrade a raw lockfile table from schema version `from` to
+/// [`LOCKFILE_VERSION`], one version step at a time.
+fn migrate(table: &mut toml::Table, from: u32) {
+    if from < 1 {
+        // 0 → 1: unversioned lockfiles already use the version 1 layout; only
+        // the `version` key is new.
+        table.insert("version".to_owned(), toml::Value::Integer(1));
+    }
+    if from < 2 {
+        // 1 → 2: version 2 only adds optional keys, which a version 1
+        // lockfile does not have.
+        table.insert("version".to_owned(), toml::Value::Integer(2));
+    }
 }
 
 /// Errors produced when reading, parsing, or writing a `konvoy.lock` lockfile.
@@ -208,6 +447,16 @@ pub enum LockfileError {
         path: String,
         source: toml::de::Error,
     },
+    #[error("{path} was written by a newer version of konvoy (lockfile version {found}, this konvoy supports up to {supported}) — upgrade konvoy to read it")]
+    NewerVersion {
+        path: String,
+        found: u32,
+        supported: u32,
+    },
+    #[error(
+        "invalid konvoy.lock at {path}: `version` must be a non-negative integer, got {value}"
+    )]
+    InvalidVersion { path: String, value: String },
     #[error("cannot serialize lockfile: {source}")]
     Serialize { source: toml::ser::Error },
     #[error("cannot write {path}: {source}")]
@@ -244,6 +493,175 @@ konanc_version = "1.9.22"
         assert_eq!(toolchain.konanc_version, "1.9.22");
     }
 
+    #[test]
+    fn unversioned_lockfile_migrates_to_current_version() {
+        let dir = make_test_dir();
+        let path = dir.path().join("konvoy.lock");
+        fs::write(&path, "[toolchain]\nkonanc_version = \"2.1.0\"\n").unwrap();
+
+        // Nothing in it needs version 2, so it is written readable by older
+        // Konvoy.
+        let lockfile = Lockfile::from_path(&path).unwrap();
+        assert_eq!(lockfile.version, 1);
+
+        lockfile.write_to(&path).unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        assert!(written.starts_with("version = 1\n"), "got:\n{written}");
+        assert_eq!(Lockfile::from_path(&path).unwrap(), lockfile);
+    }
+
+    fn sha512_maven_lock() -> DependencyLock {
+        DependencyLock {
+            name: "datetime".to_owned(),
+            source: DepSource::Maven {
+                version: "0.6.0".to_owned(),
+                maven: "org.jetbrains.kotlinx:kotlinx-datetime".to_owned(),
+                targets: std::collections::BTreeMap::from([(
+                    "linux_x64".to_owned(),
+                    "ab".repeat(64),
+                )]),
+                required_by: Vec::new(),
+                classifier: None,
+                hash_algorithm: HashAlgorithm::Sha512,
+            },
+            source_hash: "cd".repeat(32),
+        }
+    }
+
+    #[test]
+    fn sha512_pins_need_version_2_which_a_version_1_reader_rejects() {
+        let dir = make_test_dir();
+        let path = dir.path().join("konvoy.lock");
+        let mut lockfile = Lockfile::with_toolchain("2.1.0");
+        lockfile.dependencies.push(sha512_maven_lock());
+        assert_eq!(lockfile.required_version(), 2);
+        lockfile.write_to(&path).unwrap();
+
+        let written = fs::read_to_string(&path).unwrap();
+        assert!(written.starts_with("version = 2\n"), "got:\n{written}");
+        assert_eq!(
+            Lockfile::from_path(&path).unwrap().dependencies,
+            lockfile.dependencies
+        );
+
+        let err = Lockfile::parse(&written, &path, 1).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                LockfileError::NewerVersion {
+                    found: 2,
+                    supported: 1,
+                    ..
+                }
+            ),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn tarball_url_needs_version_2_which_a_version_1_reader_rejects() {
+        let dir = make_test_dir();
+        let path = dir.path().join("konvoy.lock");
+        let mut lockfile = Lockfile::with_managed_toolchain("2.1.0", Some("abc"), None);
+        if let Some(tc) = lockfile.toolchain.as_mut() {
+            tc.konanc_tarball_url = Some("https://mirror.example.com/k.tar.gz".to_owned());
+        }
+        lockfile.write_to(&path).unwrap();
+
+        let written = fs::read_to_string(&path).unwrap();
+        assert!(written.starts_with("version = 2\n"), "got:\n{written}");
+        let err = Lockfile::parse(&written, &path, 1).unwrap_err();
+        assert!(
+            matches!(err, LockfileError::NewerVersion { found: 2, .. }),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn ktlint_pin_needs_version_2_which_a_version_1_reader_rejects() {
+        let dir = make_test_dir();
+        let path = dir.path().join("konvoy.lock");
+        let mut lockfile = Lockfile::with_toolchain("2.1.0");
+        if let Some(tc) = lockfile.toolchain.as_mut() {
+            tc.ktlint_version = Some("1.3.1".to_owned());
+            tc.ktlint_jar_sha256 = Some("ef".repeat(32));
+        }
+        lockfile.write_to(&path).unwrap();
+
+        let written = fs::read_to_string(&path).unwrap();
+        assert!(written.starts_with("version = 2\n"), "got:\n{written}");
+        let err = Lockfile::parse(&written, &path, 1).unwrap_err();
+        assert!(
+            matches!(err, LockfileError::NewerVersion { found: 2, .. }),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn version_1_lockfile_migrates_to_version_2() {
+        let dir = make_test_dir();
+        let path = dir.path().join("konvoy.lock");
+        fs::write(
+            &path,
+            "version = 1\n\n[toolchain]\nkonanc_version = \"2.1.0\"\n",
+        )
+        .unwrap();
+        let mut table: toml::Table = toml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        migrate(&mut table, 1);
+        assert_eq!(table.get("version"), Some(&toml::Value::Integer(2)));
+        assert_eq!(Lockfile::from_path(&path).unwrap().version, 1);
+    }
+
+    #[test]
+    fn newer_lockfile_version_rejected() {
+        let dir = make_test_dir();
+        let path = dir.path().join("konvoy.lock");
+        fs::write(
+            &path,
+            "version = 99\nfuture_section = true\n\n[toolchain]\nkonanc_version = \"2.1.0\"\n",
+        )
+        .unwrap();
+
+        let err = Lockfile::from_path(&path).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                LockfileError::NewerVersion {
+                    found: 99,
+                    supported: LOCKFILE_VERSION,
+                    ..
+                }
+            ),
+            "got: {err}"
+        );
+        assert!(
+            err.to_string().contains("written by a newer version"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn non_integer_lockfile_version_rejected() {
+        let dir = make_test_dir();
+        let path = dir.path().join("konvoy.lock");
+        fs::write(&path, "version = \"one\"\n").unwrap();
+
+        let err = Lockfile::from_path(&path).unwrap_err();
+        assert!(
+            matches!(err, LockfileError::InvalidVersion { .. }),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn default_lockfile_has_base_version() {
+        assert_eq!(Lockfile::default().version, BASE_LOCKFILE_VERSION);
+        assert_eq!(
+            Lockfile::with_toolchain("2.1.0").version,
+            BASE_LOCKFILE_VERSION
+        );
+    }
+
     #[test]
     fn default_when_absent() {
         let dir = make_test_dir();
@@ -263,6 +681,68 @@ konanc_version = "1.9.22"
         assert!(content.contains("2.0.0"), "content was: {content}");
     }
 
+    #[test]
+    fn write_to_is_independent_of_entry_order() {
+        let dir = make_test_dir();
+        let maven = |name: &str, required_by: &[&str]| DependencyLock {
+            name: name.to_owned(),
+            source: DepSource::Maven {
+                version: "1.0.0".to_owned(),
+                maven: format!("org.example:{name}"),
+                targets: std::collections::BTreeMap::new(),
+                required_by: required_by.iter().map(|r| (*r).to_owned()).collect(),
+                classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
+            },
+            source_hash: String::new(),
+        };
+        let path_dep = DependencyLock {
+            name: "json".to_owned(),
+            source: DepSource::Path {
+                path: "../json".to_owned(),
+            },
+            source_hash: "h".to_owned(),
+        };
+        let plugin = |name: &str| PluginLock {
+            name: name.to_owned(),
+            maven: format!("org.example:{name}"),
+            version: "1.0.0".to_owned(),
+            sha256: "s".to_owned(),
+            url: "u".to_owned(),
+        };
+
+        let mut forward = Lockfile::with_toolchain("2.1.0");
+        forward.dependencies = vec![
+            maven("coroutines", &["app", "http"]),
+            path_dep.clone(),
+            maven("json", &[]),
+        ];
+        forward.plugins = vec![plugin("allopen"), plugin("serialization")];
+        let mut backward = Lockfile::with_toolchain("2.1.0");
+        backward.dependencies = vec![
+            maven("json", &[]),
+            path_dep,
+            maven("coroutines", &["http", "app"]),
+        ];
+        backward.plugins = vec![plugin("serialization"), plugin("allopen")];
+
+        let (a, b) = (dir.path().join("a.lock"), dir.path().join("b.lock"));
+        forward.write_to(&a).unwrap();
+        backward.write_to(&b).unwrap();
+        let written = fs::read_to_string(&a).unwrap();
+        assert_eq!(written, fs::read_to_string(&b).unwrap());
+        let reparsed = Lockfile::from_path(&a).unwrap();
+        let order: Vec<_> = reparsed
+            .dependencies
+            .iter()
+            .map(|d| (d.name.as_str(), d.source.kind()))
+            .collect();
+        assert_eq!(
+            order,
+            [("coroutines", "maven"), ("json", "maven"), ("json", "path")]
+        );
+    }
+
     #[test]
     fn round_trip() {
         let dir = make_test_dir();
@@ -329,6 +809,38 @@ konanc_version = "2.1.0"
             .unwrap_or_else(|| panic!("missing toolchain"));
         assert!(toolchain.konanc_tarball_sha256.is_none());
         assert!(toolchain.jre_tarball_sha256.is_none());
+        assert!(toolchain.jre_major.is_none());
+        assert!(toolchain.konanc_fingerprint.is_none());
+    }
+
+    #[test]
+    fn jre_major_round_trip() {
+        let dir = make_test_dir();
+        let path = dir.path().join("konvoy.lock");
+        let mut original = Lockfile::with_managed_toolchain("2.1.0", None, Some("cafebabe"));
+        if let Some(tc) = original.toolchain.as_mut() {
+            tc.jre_major = Some(17);
+        }
+        original.write_to(&path).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("jre_major = 17"), "{content}");
+        assert_eq!(Lockfile::from_path(&path).unwrap(), original);
+    }
+
+    #[test]
+    fn konanc_fingerprint_round_trip() {
+        let dir = make_test_dir();
+        let path = dir.path().join("konvoy.lock");
+        let mut original = Lockfile::with_toolchain("2.1.0");
+        if let Some(tc) = original.toolchain.as_mut() {
+            tc.konanc_fingerprint = Some("f1f2".to_owned());
+        }
+        original.write_to(&path).unwrap();
+        let reparsed = Lockfile::from_path(&path).unwrap();
+        assert_eq!(original, reparsed);
+        assert_eq!(reparsed.konanc_fingerprint("2.1.0"), Some("f1f2"));
+        // A fingerprint recorded for another version does not apply.
+        assert_eq!(reparsed.konanc_fingerprint("2.2.0"), None);
     }
 
     #[test]
@@ -379,6 +891,40 @@ tarball_sha256 = "oldvalue"
         assert_eq!(dep.source_hash, "abcdef1234");
     }
 
+    #[test]
+    fn round_trip_git_dependency() {
+        let dir = make_test_dir();
+        let path = dir.path().join("konvoy.lock");
+        let mut lockfile = Lockfile::with_toolchain("2.1.0");
+        lockfile.dependencies.push(DependencyLock {
+            name: "utils".to_owned(),
+            source: DepSource::Git {
+                url: "https://example.com/utils.git".to_owned(),
+                rev: "main".to_owned(),
+                commit: "0123456789abcdef0123456789abcdef01234567".to_owned(),
+            },
+            source_hash: "abcdef1234".to_owned(),
+        });
+        lockfile.write_to(&path).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("source_type = \"git\""), "{content}");
+        let reparsed = Lockfile::from_path(&path).unwrap();
+        assert_eq!(lockfile, reparsed);
+        assert_eq!(
+            reparsed.git_commit("utils", "https://example.com/utils.git", "main"),
+            Some("0123456789abcdef0123456789abcdef01234567")
+        );
+        // A different rev or URL in konvoy.toml invalidates the pin.
+        assert_eq!(
+            reparsed.git_commit("utils", "https://example.com/utils.git", "v2"),
+            None
+        );
+        assert_eq!(
+            reparsed.git_commit("utils", "https://example.com/fork.git", "main"),
+            None
+        );
+    }
+
     #[test]
     fn backward_compat_no_deps() {
         let dir = make_test_dir();
@@ -504,6 +1050,7 @@ konanc_version = "2.1.0"
                 targets,
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "maven-hash-1234".to_owned(),
         });
@@ -523,6 +1070,7 @@ konanc_version = "2.1.0"
                 targets,
                 required_by,
                 classifier,
+                hash_algorithm,
             } => {
                 assert_eq!(version, "1.8.0");
                 assert_eq!(maven, "org.jetbrains.kotlinx:kotlinx-coroutines-core");
@@ -531,6 +1079,7 @@ konanc_version = "2.1.0"
                 assert_eq!(targets.get("macos_arm64").unwrap(), "11223344");
                 assert!(required_by.is_empty());
                 assert!(classifier.is_none());
+                assert!(hash_algorithm.is_sha256());
             }
             other => panic!("expected Maven source, got: {other:?}"),
         }
@@ -549,6 +1098,7 @@ konanc_version = "2.1.0"
                 targets,
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "hash-5678".to_owned(),
         });
@@ -576,6 +1126,43 @@ konanc_version = "2.1.0"
         );
     }
 
+    #[test]
+    fn mixed_hash_algorithms_round_trip() {
+        let tmp = make_test_dir();
+        let path = tmp.path().join("konvoy.lock");
+        let mut lockfile = Lockfile::with_toolchain("2.1.0");
+        for (name, hash_algorithm) in [
+            ("kotlinx-datetime", HashAlgorithm::Sha256),
+            ("kotlinx-io", HashAlgorithm::Sha512),
+        ] {
+            lockfile.dependencies.push(DependencyLock {
+                name: name.to_owned(),
+                source: DepSource::Maven {
+                    version: "0.6.0".to_owned(),
+                    maven: format!("org.jetbrains.kotlinx:{name}"),
+                    targets: std::collections::BTreeMap::new(),
+                    required_by: Vec::new(),
+                    classifier: None,
+                    hash_algorithm,
+                },
+                source_hash: String::new(),
+            });
+        }
+        let content = toml::to_string_pretty(&lockfile).unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(
+            content.matches("hash_algorithm = \"sha512\"").count(),
+            1,
+            "content was: {content}"
+        );
+        assert!(!content.contains("sha256\""), "content was: {content}");
+        lockfile.write_to(&path).unwrap_or_else(|e| panic!("{e}"));
+        let reparsed = Lockfile::from_path(&path).unwrap_or_else(|e| panic!("{e}"));
+        // The SHA-512 entry needs schema version 2.
+        lockfile.canonicalize();
+        assert_eq!(lockfile.version, 2);
+        assert_eq!(lockfile, reparsed);
+    }
+
     #[test]
     fn backward_compat_path_deps_still_work() {
         let dir = make_test_dir();
@@ -626,6 +1213,7 @@ source_hash = "abcdef1234"
                 targets,
                 required_by: vec!["kotlinx-coroutines".to_owned()],
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "transitive-hash".to_owned(),
         });
@@ -664,6 +1252,7 @@ source_hash = "abcdef1234"
                 targets,
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "direct-hash".to_owned(),
         });
@@ -687,6 +1276,7 @@ source_hash = "abcdef1234"
                 targets,
                 required_by: vec!["kotlinx-coroutines".to_owned()],
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "transitive-hash".to_owned(),
         });
@@ -716,6 +1306,7 @@ source_hash = "abcdef1234"
                 targets,
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "some-hash".to_owned(),
         });
@@ -752,6 +1343,7 @@ source_hash = "abcdef1234"
                 targets,
                 required_by: vec!["atomicfu".to_owned()],
                 classifier: Some("cinterop-interop".to_owned()),
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "classifier-hash".to_owned(),
         });
@@ -783,6 +1375,7 @@ source_hash = "abcdef1234"
                 targets,
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "some-hash".to_owned(),
         });
@@ -806,6 +1399,7 @@ source_hash = "abcdef1234"
                 targets,
                 required_by: vec!["atomicfu".to_owned()],
                 classifier: Some("cinterop-interop".to_owned()),
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "hash".to_owned(),
         });
@@ -869,6 +1463,7 @@ linux_x64 = "hash123"
                 targets: targets1,
                 required_by: vec!["kotlinx-coroutines".to_owned()],
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "main-source-hash".to_owned(),
         });
@@ -884,6 +1479,7 @@ linux_x64 = "hash123"
                 targets: targets2,
                 required_by: vec!["atomicfu".to_owned()],
                 classifier: Some("cinterop-interop".to_owned()),
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "cinterop-source-hash".to_owned(),
         });
@@ -1039,10 +1635,12 @@ url = "https://example.com/plugin.jar"
         });
         lockfile.write_to(&path).unwrap();
         let reparsed = Lockfile::from_path(&path).unwrap();
+        lockfile.canonicalize();
         assert_eq!(lockfile, reparsed);
         assert_eq!(reparsed.plugins.len(), 2);
-        assert_eq!(reparsed.plugins[0].name, "kotlin-serialization");
-        assert_eq!(reparsed.plugins[1].name, "kotlin-allopen");
+        // Written sorted by name, not in insertion order.
+        assert_eq!(reparsed.plugins[0].name, "kotlin-allopen");
+        assert_eq!(reparsed.plugins[1].name, "kotlin-serialization");
     }
 
     #[test]
@@ -1082,6 +1680,7 @@ url = "https://example.com/plugin.jar"
                 targets,
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "dep-source-hash".to_owned(),
         });
@@ -1210,6 +1809,7 @@ url = "https://example.com/plugin.jar"
                 targets: std::collections::BTreeMap::new(),
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "abc".to_owned(),
         });
@@ -1230,6 +1830,7 @@ url = "https://example.com/plugin.jar"
                 targets: std::collections::BTreeMap::new(),
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "abc".to_owned(),
         });
@@ -1273,6 +1874,7 @@ url = "https://example.com/plugin.jar"
                 targets: std::collections::BTreeMap::new(),
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "h".to_owned(),
         };
@@ -1285,6 +1887,34 @@ url = "https://example.com/plugin.jar"
         assert!(!lockfile.has_maven_coord("g:lib", "3.0.0"));
     }
 
+    #[test]
+    fn has_maven_coord_matching_checks_the_range() {
+        let lockfile = Lockfile {
+            dependencies: vec![DependencyLock {
+                name: "lib".to_owned(),
+                source: DepSource::Maven {
+                    version: "1.9.2".to_owned(),
+                    maven: "g:lib".to_owned(),
+                    targets: std::collections::BTreeMap::new(),
+                    required_by: Vec::new(),
+                    classifier: None,
+                    hash_algorithm: HashAlgorithm::Sha256,
+                },
+                source_hash: "h".to_owned(),
+            }],
+            ..Lockfile::default()
+        };
+        let req = |r: &str| VersionReq::parse(r).unwrap();
+        assert!(lockfile.has_maven_coord_matching("g:lib", &req("^1.8")));
+        assert!(lockfile.has_maven_coord_matching("g:lib", &req("1.9.2")));
+        assert!(!lockfile.has_maven_coord_matching("g:lib", &req("^2.0")));
+        assert!(!lockfile.has_maven_coord_matching("g:other", &req("^1.8")));
+        assert_eq!(
+            lockfile.maven_versions("g:lib").collect::<Vec<_>>(),
+            vec!["1.9.2"]
+        );
+    }
+
     #[test]
     fn has_maven_coord_matches_a_classifier_bearing_entry() {
         // has_maven_coord keys on (coordinate, version) only — a cinterop entry
@@ -1299,6 +1929,7 @@ url = "https://example.com/plugin.jar"
                     targets: std::collections::BTreeMap::new(),
                     required_by: vec!["atomicfu".to_owned()],
                     classifier: Some("cinterop-interop".to_owned()),
+                    hash_algorithm: HashAlgorithm::Sha256,
                 },
                 source_hash: "h".to_owned(),
             }],
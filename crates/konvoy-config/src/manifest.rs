@@ -1,8 +1,12 @@
 use std::collections::BTreeMap;
 use std::path::Path;
 
+use konvoy_targets::Target;
 use serde::{Deserialize, Serialize};
 
+use crate::lockfile::HashAlgorithm;
+use crate::version_req::{compare_maven_versions, VersionReq};
+
 /// The `konvoy.toml` project manifest.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -11,10 +15,16 @@ pub struct Manifest {
     pub toolchain: Toolchain,
     #[serde(default, skip_serializing_if = "Codegen::is_empty")]
     pub codegen: Codegen,
+    #[serde(default, skip_serializing_if = "BuildConfig::is_empty")]
+    pub build: BuildConfig,
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub dependencies: BTreeMap<String, DependencySpec>,
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub plugins: BTreeMap<String, DependencySpec>,
+    /// Optional features: each name maps to the other features it enables. The
+    /// reserved `default` entry lists the features enabled unless a build opts out.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub features: BTreeMap<String, Vec<String>>,
 }
 
 /// Toolchain specification declaring the Kotlin/Native version and optional tools.
@@ -26,6 +36,52 @@ pub struct Toolchain {
     /// Detekt version, e.g. "1.23.7". When set, enables `konvoy lint`.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub detekt: Option<String>,
+    /// ktlint version, e.g. "1.3.1". When set, enables `konvoy fmt`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ktlint: Option<String>,
+    /// JDK major version of the bundled JRE, e.g. `17`. Defaults to 21.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jre: Option<u32>,
+    /// Kotlin language level to compile against (`-language-version`), e.g. "1.9".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language_version: Option<String>,
+    /// Kotlin standard library API level to allow (`-api-version`), e.g. "1.9".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_version: Option<String>,
+}
+
+/// Build behavior settings from the `[build]` section of `konvoy.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BuildConfig {
+    /// Kill a `konanc` invocation that runs longer than this many seconds.
+    /// Unset means no limit. The `--timeout` CLI flag takes precedence.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Fail the build when the compiler reports any warning. The
+    /// `--deny-warnings` CLI flag turns this on for a single build.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deny_warnings: bool,
+    /// Algorithm `konvoy update` pins Maven klibs with (`sha256` or
+    /// `sha512`). Only the root project's setting applies. Entries already
+    /// in `konvoy.lock` are verified with the algorithm recorded there.
+    #[serde(default, skip_serializing_if = "HashAlgorithm::is_sha256")]
+    pub maven_hash: HashAlgorithm,
+    /// Store new artifact cache entries zstd-compressed. Only the root
+    /// project's setting applies; `KONVOY_COMPRESS_CACHE` overrides it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub compress_cache: bool,
+}
+
+impl BuildConfig {
+    /// Return `true` when no build settings are configured.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.timeout_secs.is_none()
+            && !self.deny_warnings
+            && self.maven_hash.is_sha256()
+            && !self.compress_cache
+    }
 }
 
 /// Whether this package produces an executable or a library.
@@ -37,6 +93,12 @@ pub enum PackageKind {
     Bin,
     /// A Kotlin/Native library (`.klib`).
     Lib,
+    /// A static C library (`.a`) with a C header, for linking into C or other
+    /// native code.
+    StaticLib,
+    /// A dynamic C library (`.so`, `.dylib`) with a C header, for FFI.
+    #[serde(rename = "cdylib")]
+    DynamicLib,
 }
 
 /// Package metadata from the `[package]` section of `konvoy.toml`.
@@ -48,8 +110,91 @@ pub struct Package {
     pub kind: PackageKind,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
+    /// One-line summary of what the package does. Informational only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The package's authors, e.g. `Jane Doe <jane@example.com>`.
+    /// Informational only.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+    /// The package's license, as an SPDX expression such as `Apache-2.0`.
+    /// Informational only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
     #[serde(default = "default_entrypoint")]
     pub entrypoint: String,
+    /// Project-relative directories holding the package's Kotlin sources.
+    /// Empty means the default layout, [`DEFAULT_SOURCE_DIR`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub source_dirs: Vec<String>,
+    /// Project-relative directory holding the test sources, compiled only into
+    /// the `konvoy test` binary. Defaults to [`DEFAULT_TEST_DIR`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_dir: Option<String>,
+    /// Oldest Konvoy release able to build the project, as `>=X.Y` or
+    /// `>=X.Y.Z`. Checked against the running binary before anything else in
+    /// the manifest is parsed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub konvoy_version: Option<String>,
+    /// Expand `${VAR}` references in string values against the process
+    /// environment. Off by default; see [`expand_env_vars`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub expand_env: bool,
+    /// File name of the built executable for a `bin` package. Defaults to `name`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bin_name: Option<String>,
+    /// File stem of the built library for a `lib`, `staticlib`, or `cdylib`
+    /// package. Defaults to `name`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lib_name: Option<String>,
+}
+
+/// Source directory used when `[package] source_dirs` is not set.
+pub const DEFAULT_SOURCE_DIR: &str = "src";
+
+/// Test directory used when `[package] test_dir` is not set.
+pub const DEFAULT_TEST_DIR: &str = "src/test";
+
+impl Package {
+    /// The configured source directories, or [`DEFAULT_SOURCE_DIR`] when none
+    /// are set. Paths are relative to the project root.
+    pub fn effective_source_dirs(&self) -> Vec<&str> {
+        if self.source_dirs.is_empty() {
+            vec![DEFAULT_SOURCE_DIR]
+        } else {
+            self.source_dirs.iter().map(String::as_str).collect()
+        }
+    }
+
+    /// The configured test directory, or [`DEFAULT_TEST_DIR`]. Relative to
+    /// the project root.
+    pub fn effective_test_dir(&self) -> &str {
+        self.test_dir.as_deref().unwrap_or(DEFAULT_TEST_DIR)
+    }
+
+    /// Stem of the build artifact's file name: `bin_name` for a program,
+    /// `lib_name` for a library, else the package `name`.
+    pub fn artifact_stem(&self) -> &str {
+        let renamed = match self.kind {
+            PackageKind::Bin => &self.bin_name,
+            PackageKind::Lib | PackageKind::StaticLib | PackageKind::DynamicLib => &self.lib_name,
+        };
+        renamed.as_deref().unwrap_or(&self.name)
+    }
+
+    /// File name of the build artifact for `target`: the
+    /// [stem](Self::artifact_stem) for a program, `<stem>.klib` for a library,
+    /// and the platform's static or dynamic library name (`lib<stem>.a`,
+    /// `lib<stem>.so`, ...) for a C library.
+    pub fn output_name(&self, target: Target) -> String {
+        let stem = self.artifact_stem();
+        match self.kind {
+            PackageKind::Bin => stem.to_owned(),
+            PackageKind::Lib => format!("{stem}.klib"),
+            PackageKind::StaticLib => target.static_lib_name(stem),
+            PackageKind::DynamicLib => target.dynamic_lib_name(stem),
+        }
+    }
 }
 
 /// Specification for a single dependency.
@@ -59,12 +204,42 @@ pub struct DependencySpec {
     /// Path to the dependency project, relative to this manifest.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub path: Option<String>,
-    /// Maven dependency version requirement (e.g. "1.8.0").
+    /// Maven dependency version requirement: an exact version (e.g. "1.8.0") or a
+    /// range (e.g. "^1.8") that `konvoy update` resolves and pins in `konvoy.lock`.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
     /// Maven coordinate in `groupId:artifactId` format (e.g. "org.jetbrains.kotlinx:kotlinx-coroutines-core").
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub maven: Option<String>,
+    /// Git repository URL of the dependency project.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<String>,
+    /// Commit, tag, or branch to check out from `git`. A moving ref is pinned to
+    /// the concrete commit in `konvoy.lock`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    /// Left out of the build unless enabled like a feature of the same name
+    /// (`--features <name>`, or listed by another feature). Path and git
+    /// dependencies only.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+    /// Keep `konvoy doctor` from reporting this dependency as unused, e.g.
+    /// when it is only reached through reflection. Dependencies only.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_unused: bool,
+    /// Build this path or git dependency even though its `[toolchain] kotlin`
+    /// differs from the root project's, warning instead of failing. The
+    /// dependency is compiled with the root's Kotlin. Dependencies only.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_toolchain_mismatch: bool,
+    /// Compiler plugin id that `options` are passed under (e.g.
+    /// "org.jetbrains.kotlinx.serialization"). Plugins only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Compiler plugin options, passed to konanc as
+    /// `-P plugin:<id>:<key>=<value>`. Plugins only; requires `id`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub options: BTreeMap<String, String>,
 }
 
 /// Code generation tools configured for this project (the `[codegen]` section of
@@ -118,6 +293,14 @@ impl DependencySpec {
         self.as_maven_coord().is_some()
     }
 
+    /// Return `(url, rev)` if this spec is a git dependency.
+    pub fn as_git(&self) -> Option<(&str, &str)> {
+        match (&self.git, &self.rev) {
+            (Some(url), Some(rev)) => Some((url.as_str(), rev.as_str())),
+            _ => None,
+        }
+    }
+
     /// Return `(maven_coord, version)` if this spec is a complete Maven dependency.
     pub fn as_maven_coord(&self) -> Option<(&str, &str)> {
         match (&self.maven, &self.version) {
@@ -125,6 +308,14 @@ impl DependencySpec {
             _ => None,
         }
     }
+
+    /// Parse `version` as a requirement. `None` when no version is set or it is
+    /// malformed (rejected at manifest load, so only unvalidated specs see that).
+    pub fn version_req(&self) -> Option<VersionReq> {
+        self.version
+            .as_deref()
+            .and_then(|v| VersionReq::parse(v).ok())
+    }
 }
 
 fn default_entrypoint() -> String {
@@ -165,6 +356,27 @@ pub fn is_valid_name(name: &str) -> bool {
     validate_name(name).is_ok()
 }
 
+/// Turn an arbitrary string, such as a directory name, into a valid package
+/// name: each disallowed character becomes a hyphen, and an underscore is
+/// prepended when the result would not start with a letter or underscore
+/// (`my project` gives `my-project`, `123abc` gives `_123abc`).
+pub fn sanitize_name(raw: &str) -> String {
+    let mut name: String = raw
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        name.insert(0, '_');
+    }
+    name
+}
+
 /// Check whether an entrypoint path ends with `.kt`.
 fn is_valid_entrypoint(entrypoint: &str) -> bool {
     entrypoint.ends_with(".kt")
@@ -197,6 +409,45 @@ fn validate_plugins(
                 "plugins must use `maven` coordinates, not `path`".to_owned()
             ));
         }
+        if spec.git.is_some() || spec.rev.is_some() {
+            return Err(err(
+                "plugins must use `maven` coordinates, not `git`".to_owned()
+            ));
+        }
+        if spec.optional {
+            return Err(err("plugins cannot be `optional`".to_owned()));
+        }
+        if spec.allow_unused {
+            return Err(err(
+                "`allow_unused` applies to dependencies, not plugins".to_owned()
+            ));
+        }
+        if spec.allow_toolchain_mismatch {
+            return Err(err(
+                "`allow_toolchain_mismatch` applies to dependencies, not plugins".to_owned(),
+            ));
+        }
+        if let Some(id) = &spec.id {
+            if id.trim().is_empty() || id.contains(':') || id.contains(char::is_whitespace) {
+                return Err(err(format!(
+                    "plugin `id` `{id}` must be non-empty, without `:` or whitespace"
+                )));
+            }
+        }
+        if !spec.options.is_empty() && spec.id.is_none() {
+            return Err(err(
+                "plugin `options` need an `id` to pass them under".to_owned()
+            ));
+        }
+        if let Some(key) = spec
+            .options
+            .keys()
+            .find(|key| key.is_empty() || key.contains('='))
+        {
+            return Err(err(format!(
+                "plugin option key `{key}` must be non-empty and must not contain `=`"
+            )));
+        }
         if spec.maven.is_none() {
             return Err(err(
                 "plugin must have `maven` set to a `groupId:artifactId` coordinate".to_owned(),
@@ -241,6 +492,44 @@ fn check_project_relative(value: &str, label: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// The `[features]` key listing the features enabled by default.
+pub const DEFAULT_FEATURES_KEY: &str = "default";
+
+/// Validate `[features]`: names use package-name characters, and every feature
+/// named in a list (including `default`) is itself declared or is an optional
+/// dependency.
+fn validate_features(
+    features: &BTreeMap<String, Vec<String>>,
+    dependencies: &BTreeMap<String, DependencySpec>,
+    path: &str,
+) -> Result<(), ManifestError> {
+    for (name, enables) in features {
+        let err = |reason: String| ManifestError::InvalidFeature {
+            path: path.to_owned(),
+            name: name.clone(),
+            reason,
+        };
+        validate_name(name).map_err(|reason| err(format!("invalid feature name: {reason}")))?;
+        for enabled in enables {
+            if enabled == DEFAULT_FEATURES_KEY {
+                return Err(err(
+                    "`default` cannot be enabled by another feature".to_owned()
+                ));
+            }
+            if enabled == name {
+                return Err(err("a feature cannot enable itself".to_owned()));
+            }
+            let optional_dep = dependencies.get(enabled).is_some_and(|spec| spec.optional);
+            if !features.contains_key(enabled) && !optional_dep {
+                return Err(err(format!(
+                    "enables `{enabled}`, which is neither declared in [features] nor an optional dependency"
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
 fn validate_codegen(codegen: &Codegen, path: &str) -> Result<(), ManifestError> {
     let Some(openapi) = &codegen.openapi else {
         return Ok(());
@@ -322,6 +611,9 @@ fn validate_codegen(codegen: &Codegen, path: &str) -> Result<(), ManifestError>
     Ok(())
 }
 
+/// Oldest JDK major `[toolchain] jre` accepts; Adoptium publishes no older JREs.
+const MIN_JRE_MAJOR: u32 = 8;
+
 /// Minimum Fabrikt major version Konvoy supports (the version that introduced
 /// the `--serialization-library` flag Konvoy always passes).
 const MIN_FABRIKT_MAJOR: u64 = 18;
@@ -353,6 +645,25 @@ fn validate_dependencies(
                 name: name.clone(),
             });
         }
+        validate_git_dependency(name, spec, path)?;
+        if spec.id.is_some() || !spec.options.is_empty() {
+            return Err(ManifestError::DependencyPluginOptions {
+                path: path.to_owned(),
+                name: name.clone(),
+            });
+        }
+        if spec.optional && spec.maven.is_some() {
+            return Err(ManifestError::DependencyOptionalMaven {
+                path: path.to_owned(),
+                name: name.clone(),
+            });
+        }
+        if spec.allow_toolchain_mismatch && spec.maven.is_some() {
+            return Err(ManifestError::DependencyToolchainOptInMaven {
+                path: path.to_owned(),
+                name: name.clone(),
+            });
+        }
         // maven + path is an error — pick one source type.
         if spec.maven.is_some() && spec.path.is_some() {
             return Err(ManifestError::DependencyMavenWithPath {
@@ -360,6 +671,13 @@ fn validate_dependencies(
                 name: name.clone(),
             });
         }
+        // path + version is an error — versions only apply to Maven artifacts.
+        if spec.path.is_some() && spec.version.is_some() {
+            return Err(ManifestError::DependencyPathWithVersion {
+                path: path.to_owned(),
+                name: name.clone(),
+            });
+        }
         // version without maven is an error — needs a coordinate.
         if spec.version.is_some() && spec.maven.is_none() {
             return Err(ManifestError::DependencyVersionWithoutMaven {
@@ -381,6 +699,17 @@ fn validate_dependencies(
                 name: name.clone(),
             });
         }
+        // The version must be an exact version or a well-formed range.
+        if let Some(ref version) = spec.version {
+            if let Err(reason) = VersionReq::parse(version) {
+                return Err(ManifestError::DependencyInvalidVersion {
+                    path: path.to_owned(),
+                    name: name.clone(),
+                    version: version.clone(),
+                    reason,
+                });
+            }
+        }
         // Validate maven coordinate format: exactly one colon, non-empty parts.
         if let Some(ref maven) = spec.maven {
             if !is_valid_maven_coordinate(maven) {
@@ -391,8 +720,12 @@ fn validate_dependencies(
                 });
             }
         }
-        // No source at all — need path or maven+version.
-        if spec.path.is_none() && spec.maven.is_none() && spec.version.is_none() {
+        // No source at all — need path, git+rev, or maven+version.
+        if spec.path.is_none()
+            && spec.git.is_none()
+            && spec.maven.is_none()
+            && spec.version.is_none()
+        {
             return Err(ManifestError::DependencyNoSource {
                 path: path.to_owned(),
                 name: name.clone(),
@@ -402,52 +735,406 @@ fn validate_dependencies(
     Ok(())
 }
 
-/// Validate a parsed manifest and return validation errors.
-fn validate(manifest: &Manifest, path: &str) -> Result<(), ManifestError> {
-    if manifest.package.name.is_empty() {
-        return Err(ManifestError::EmptyName {
-            path: path.to_owned(),
-        });
+/// Validate the `git`/`rev` fields of a dependency: `git` is its own source type
+/// and needs a `rev`. Neither may start with `-`, since both are passed to `git`
+/// as arguments.
+fn validate_git_dependency(
+    name: &str,
+    spec: &DependencySpec,
+    path: &str,
+) -> Result<(), ManifestError> {
+    let err = |reason: &str| ManifestError::DependencyInvalidGit {
+        path: path.to_owned(),
+        name: name.to_owned(),
+        reason: reason.to_owned(),
+    };
+    let Some(ref url) = spec.git else {
+        return match spec.rev {
+            Some(_) => Err(err("`rev` requires `git`")),
+            None => Ok(()),
+        };
+    };
+    if spec.path.is_some() || spec.maven.is_some() || spec.version.is_some() {
+        return Err(err(
+            "`git` cannot be combined with `path`, `maven`, or `version` — use exactly one source",
+        ));
     }
-    if !is_valid_name(&manifest.package.name) {
-        return Err(ManifestError::InvalidName {
-            path: path.to_owned(),
-            name: manifest.package.name.clone(),
-        });
+    if url.trim().is_empty() || url.starts_with('-') {
+        return Err(err("`git` must be a repository URL"));
     }
-    // Only validate entrypoint for binary projects.
-    if manifest.package.kind == PackageKind::Bin
-        && !is_valid_entrypoint(&manifest.package.entrypoint)
-    {
-        return Err(ManifestError::InvalidEntrypoint {
-            path: path.to_owned(),
-            entrypoint: manifest.package.entrypoint.clone(),
-        });
+    match spec.rev.as_deref().map(str::trim) {
+        None | Some("") => Err(err(
+            "`git` requires `rev` — set it to a commit, tag, or branch",
+        )),
+        Some(rev) if rev.starts_with('-') => Err(err("`rev` must not start with `-`")),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Replace every `${VAR}` in `content` with `lookup(VAR)`.
+///
+/// A manifest applies this to each string value after parsing, so a variable's
+/// value is taken verbatim, quotes and all, and keys, numbers, booleans, and
+/// comments are never expanded. `$$` writes a literal `$`; any other `$` is
+/// kept as is.
+///
+/// # Errors
+/// Returns an error naming the variable if a referenced variable is unset, or
+/// if a `${` is unterminated or holds an invalid variable name.
+pub fn expand_env_vars(
+    content: &str,
+    path: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, ManifestError> {
+    let invalid = |message: String| ManifestError::InvalidEnvReference {
+        path: path.to_owned(),
+        message,
+    };
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(pos) = rest.find('$') {
+        let (before, after) = rest.split_at(pos);
+        out.push_str(before);
+        let after = after.get(1..).unwrap_or_default();
+        if let Some(tail) = after.strip_prefix('$') {
+            out.push('$');
+            rest = tail;
+        } else if let Some(tail) = after.strip_prefix('{') {
+            let Some((name, tail)) = tail.split_once('}') else {
+                return Err(invalid(
+                    "unterminated `${` — close it with `}` or write `$$` for a literal `$`"
+                        .to_owned(),
+                ));
+            };
+            if !is_valid_env_name(name) {
+                return Err(invalid(format!(
+                    "`${{{name}}}` is not a valid variable reference — names use letters, digits, and underscores"
+                )));
+            }
+            let value = lookup(name).ok_or_else(|| ManifestError::UndefinedEnvVar {
+                path: path.to_owned(),
+                name: name.to_owned(),
+            })?;
+            out.push_str(&value);
+            rest = tail;
+        } else {
+            out.push('$');
+            rest = after;
+        }
     }
-    if manifest.toolchain.kotlin.is_empty() {
-        return Err(ManifestError::InvalidToolchain {
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Run [`expand_env_vars`] over every string value in `value`, recursing into
+/// arrays and tables. Keys are left alone.
+fn expand_env_in_strings(
+    value: &mut toml::Value,
+    path: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<(), ManifestError> {
+    match value {
+        toml::Value::String(text) => *text = expand_env_vars(text, path, lookup)?,
+        toml::Value::Array(items) => {
+            for item in items {
+                expand_env_in_strings(item, path, lookup)?;
+            }
+        }
+        toml::Value::Table(table) => {
+            for (_, item) in table.iter_mut() {
+                expand_env_in_strings(item, path, lookup)?;
+            }
+        }
+        toml::Value::Integer(_)
+        | toml::Value::Float(_)
+        | toml::Value::Boolean(_)
+        | toml::Value::Datetime(_) => {}
+    }
+    Ok(())
+}
+
+/// Return `true` if `name` is a valid environment variable name:
+/// `[A-Za-z_][A-Za-z0-9_]*`.
+fn is_valid_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Return `true` if `version` looks like a release version: two or more
+/// dot-separated numbers, optionally followed by a `-` qualifier such as
+/// `-RC` or `-Beta1`.
+/// Whether `version` is a Kotlin language or API level: `X.Y`, digits only.
+fn is_language_level(version: &str) -> bool {
+    version.split_once('.').is_some_and(|(major, minor)| {
+        [major, minor]
+            .iter()
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+    })
+}
+
+fn looks_like_version(version: &str) -> bool {
+    let (core, qualifier) = match version.split_once('-') {
+        Some((core, qualifier)) => (core, Some(qualifier)),
+        None => (version, None),
+    };
+    let numeric_parts = core.split('.').collect::<Vec<_>>();
+    numeric_parts.len() >= 2
+        && numeric_parts
+            .iter()
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+        && qualifier.is_none_or(|q| {
+            !q.is_empty()
+                && q.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+        })
+}
+
+/// Check `[package] konvoy_version` against `current`, the running Konvoy's
+/// version. Reads the raw TOML so the check runs before the typed parse; a
+/// syntax error or a non-string value is left for that parse to report.
+fn check_konvoy_version(content: &str, path: &str, current: &str) -> Result<(), ManifestError> {
+    let Ok(table) = toml::from_str::<toml::Table>(content) else {
+        return Ok(());
+    };
+    let Some(required) = table
+        .get("package")
+        .and_then(|package| package.get("konvoy_version"))
+        .and_then(toml::Value::as_str)
+    else {
+        return Ok(());
+    };
+    let minimum =
+        parse_minimum_version(required).map_err(|message| ManifestError::InvalidKonvoyVersion {
             path: path.to_owned(),
-            message: "kotlin version must not be empty".to_owned(),
+            message,
+        })?;
+    // Compare the release core only, so a `1.4.0-dev` build satisfies `>=1.4`.
+    let current_core = current.split_once('-').map_or(current, |(core, _)| core);
+    if compare_maven_versions(current_core, minimum) == std::cmp::Ordering::Less {
+        return Err(ManifestError::KonvoyVersionUnsatisfied {
+            path: path.to_owned(),
+            required: format!(">={minimum}"),
+            current: current.to_owned(),
         });
     }
-    if manifest
-        .toolchain
-        .detekt
-        .as_ref()
-        .is_some_and(String::is_empty)
+    Ok(())
+}
+
+/// Parse a `>=X.Y` / `>=X.Y.Z` requirement into its version.
+fn parse_minimum_version(required: &str) -> Result<&str, String> {
+    let version = required
+        .trim()
+        .strip_prefix(">=")
+        .map(str::trim)
+        .ok_or_else(|| format!("`{required}` must be written as `>=X.Y`, e.g. `>=1.2`"))?;
+    let segments: Vec<&str> = version.split('.').collect();
+    let numeric = segments
+        .iter()
+        .all(|seg| !seg.is_empty() && seg.chars().all(|c| c.is_ascii_digit()));
+    if !numeric || !(2..=3).contains(&segments.len()) {
+        return Err(format!(
+            "`{version}` is not a version — use `>=X.Y` or `>=X.Y.Z`, e.g. `>=1.2`"
+        ));
+    }
+    Ok(version)
+}
+
+/// Validate `[package] test_dir`: a non-empty path inside the project that is
+/// neither a source directory nor a parent of one, since everything under it is
+/// left out of the main build.
+fn validate_test_dir(package: &Package, path: &str) -> Result<(), ManifestError> {
+    let Some(dir) = package.test_dir.as_deref() else {
+        return Ok(());
+    };
+    let err = |message: String| ManifestError::InvalidTestDir {
+        path: path.to_owned(),
+        message,
+    };
+    if dir.is_empty() {
+        return Err(err("must not be empty".to_owned()));
+    }
+    check_project_relative(dir, "test_dir").map_err(err)?;
+    if let Some(source_dir) = package
+        .effective_source_dirs()
+        .into_iter()
+        .find(|source_dir| Path::new(source_dir).starts_with(dir))
     {
-        return Err(ManifestError::InvalidToolchain {
+        return Err(err(format!(
+            "`{dir}` contains the source directory `{source_dir}` — its sources would be left out of the build"
+        )));
+    }
+    Ok(())
+}
+
+/// Validate `[package] source_dirs`: every entry must be a non-empty,
+/// project-relative path, listed once.
+fn validate_source_dirs(source_dirs: &[String], path: &str) -> Result<(), ManifestError> {
+    let err = |message: String| ManifestError::InvalidSourceDirs {
+        path: path.to_owned(),
+        message,
+    };
+    for (i, dir) in source_dirs.iter().enumerate() {
+        if dir.is_empty() {
+            return Err(err("entries must not be empty".to_owned()));
+        }
+        check_project_relative(dir, "source_dirs entry").map_err(err)?;
+        if source_dirs.iter().take(i).any(|earlier| earlier == dir) {
+            return Err(err(format!("`{dir}` is listed more than once")));
+        }
+    }
+    Ok(())
+}
+
+/// Validate `[package] bin_name` / `lib_name`: `bin_name` only applies to
+/// `bin` packages and `lib_name` to the library kinds, and each is a file name
+/// built from package-name characters.
+fn validate_output_names(package: &Package, path: &str) -> Result<(), ManifestError> {
+    let is_bin = package.kind == PackageKind::Bin;
+    let fields = [
+        (
+            "bin_name",
+            &package.bin_name,
+            is_bin,
+            "`kind = \"bin\"`",
+            "lib_name",
+        ),
+        (
+            "lib_name",
+            &package.lib_name,
+            !is_bin,
+            "`kind = \"lib\"`, `\"staticlib\"`, or `\"cdylib\"`",
+            "bin_name",
+        ),
+    ];
+    for (field, value, applies, kinds, other) in fields {
+        let Some(value) = value else {
+            continue;
+        };
+        let err = |reason: String| ManifestError::InvalidOutputName {
             path: path.to_owned(),
-            message: "detekt version must not be empty".to_owned(),
-        });
+            field: field.to_owned(),
+            reason,
+        };
+        if !applies {
+            return Err(err(format!(
+                "only applies to {kinds} packages — use `{other}` instead"
+            )));
+        }
+        if value.is_empty() {
+            return Err(err("must not be empty".to_owned()));
+        }
+        if !is_valid_name(value) {
+            return Err(err(format!(
+                "`{value}` contains invalid characters (only alphanumeric, hyphen, underscore allowed)"
+            )));
+        }
     }
-    validate_plugins(&manifest.plugins, path)?;
-    validate_codegen(&manifest.codegen, path)?;
-    validate_dependencies(&manifest.dependencies, &manifest.package.name, path)?;
     Ok(())
 }
 
 impl Manifest {
+    /// Check the manifest for semantic errors that TOML parsing cannot catch:
+    /// an invalid package name or entrypoint, a malformed toolchain version,
+    /// dependencies with conflicting or missing sources, and incomplete plugin
+    /// or codegen entries.
+    ///
+    /// [`from_str`](Self::from_str) and [`from_path`](Self::from_path) call this
+    /// after parsing; call it directly after editing a manifest in memory.
+    /// `path` is only used in error messages.
+    ///
+    /// # Errors
+    /// Returns the first validation error, naming the offending field.
+    pub fn validate(&self, path: &str) -> Result<(), ManifestError> {
+        if self.package.name.is_empty() {
+            return Err(ManifestError::EmptyName {
+                path: path.to_owned(),
+            });
+        }
+        if !is_valid_name(&self.package.name) {
+            return Err(ManifestError::InvalidName {
+                path: path.to_owned(),
+                name: self.package.name.clone(),
+            });
+        }
+        // Only validate entrypoint for binary projects.
+        if self.package.kind == PackageKind::Bin && !is_valid_entrypoint(&self.package.entrypoint) {
+            return Err(ManifestError::InvalidEntrypoint {
+                path: path.to_owned(),
+                entrypoint: self.package.entrypoint.clone(),
+            });
+        }
+        validate_source_dirs(&self.package.source_dirs, path)?;
+        validate_test_dir(&self.package, path)?;
+        validate_output_names(&self.package, path)?;
+        if self.toolchain.kotlin.is_empty() {
+            return Err(ManifestError::InvalidToolchain {
+                path: path.to_owned(),
+                message: "kotlin version must not be empty".to_owned(),
+            });
+        }
+        if !looks_like_version(&self.toolchain.kotlin) {
+            return Err(ManifestError::InvalidToolchain {
+                path: path.to_owned(),
+                message: format!(
+                    "kotlin version `{}` is not a version — expected e.g. `2.1.0` or `2.2.0-RC`",
+                    self.toolchain.kotlin
+                ),
+            });
+        }
+        if self.toolchain.detekt.as_ref().is_some_and(String::is_empty) {
+            return Err(ManifestError::InvalidToolchain {
+                path: path.to_owned(),
+                message: "detekt version must not be empty".to_owned(),
+            });
+        }
+        if self.toolchain.ktlint.as_ref().is_some_and(String::is_empty) {
+            return Err(ManifestError::InvalidToolchain {
+                path: path.to_owned(),
+                message: "ktlint version must not be empty".to_owned(),
+            });
+        }
+        if self
+            .toolchain
+            .jre
+            .is_some_and(|major| major < MIN_JRE_MAJOR)
+        {
+            return Err(ManifestError::InvalidToolchain {
+                path: path.to_owned(),
+                message: format!(
+                    "jre must be a JDK major version of at least {MIN_JRE_MAJOR}, e.g. `jre = 17`"
+                ),
+            });
+        }
+        for (field, value) in [
+            ("language_version", &self.toolchain.language_version),
+            ("api_version", &self.toolchain.api_version),
+        ] {
+            if let Some(value) = value.as_ref().filter(|v| !is_language_level(v)) {
+                return Err(ManifestError::InvalidToolchain {
+                    path: path.to_owned(),
+                    message: format!(
+                        "{field} `{value}` is not a Kotlin language level — expected `X.Y`, e.g. `{field} = \"1.9\"`"
+                    ),
+                });
+            }
+        }
+        if self.build.timeout_secs == Some(0) {
+            return Err(ManifestError::InvalidBuildConfig {
+                path: path.to_owned(),
+                message: "timeout_secs must be greater than 0 — remove it to disable the timeout"
+                    .to_owned(),
+            });
+        }
+        validate_plugins(&self.plugins, path)?;
+        validate_features(&self.features, &self.dependencies, path)?;
+        validate_codegen(&self.codegen, path)?;
+        validate_dependencies(&self.dependencies, &self.package.name, path)?;
+        Ok(())
+    }
+
     /// Read and parse a `konvoy.toml` from the given path.
     ///
     /// # Errors
@@ -468,10 +1155,52 @@ impl Manifest {
     /// Returns an error if the string contains invalid TOML, has unknown keys,
     /// or fails validation.
     pub fn from_str(content: &str, path: &str) -> Result<Self, ManifestError> {
-        let mut manifest: Manifest = toml::from_str(content).map_err(|e| ManifestError::Parse {
-            path: path.to_owned(),
-            source: e,
-        })?;
+        Self::from_str_with_env(content, path, |name| std::env::var(name).ok())
+    }
+
+    /// Parse a manifest, resolving `${VAR}` references through `lookup` when
+    /// `[package] expand_env = true`.
+    fn from_str_with_env(
+        content: &str,
+        path: &str,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ManifestError> {
+        let parse = |text: &str| {
+            toml::from_str::<Manifest>(text).map_err(|e| ManifestError::Parse {
+                path: path.to_owned(),
+                source: e,
+            })
+        };
+        // Checked on the raw table first: a manifest written for a newer
+        // Konvoy may use keys this one rejects as unknown, and "upgrade
+        // Konvoy" is the useful error there, not "unknown field".
+        check_konvoy_version(content, path, env!("CARGO_PKG_VERSION"))?;
+        let mut manifest = parse(content)?;
+        // The flag lives in the file itself, so the first parse only reads it.
+        // Expansion then runs on the parsed string values, never on the TOML
+        // text, so a value cannot add keys or tables to the manifest.
+        if manifest.package.expand_env {
+            let mut document: toml::Value =
+                toml::from_str(content).map_err(|e| ManifestError::Parse {
+                    path: path.to_owned(),
+                    source: e,
+                })?;
+            expand_env_in_strings(&mut document, path, &lookup)?;
+            manifest = document.try_into().map_err(|e| ManifestError::Parse {
+                path: path.to_owned(),
+                source: e,
+            })?;
+        }
+        // Normalize source dirs so `"src/main/kotlin/ "` and `"src/main/kotlin"`
+        // name the same directory in the cache key and duplicate check.
+        for dir in manifest
+            .package
+            .source_dirs
+            .iter_mut()
+            .chain(manifest.package.test_dir.as_mut())
+        {
+            *dir = dir.trim().trim_end_matches('/').to_owned();
+        }
         // Normalize codegen fields (trim whitespace) once, here, so the stored
         // values are what the rest of the pipeline uses — e.g. the Maven
         // coordinate built from `version` — and so `validate_codegen` can read
@@ -486,7 +1215,7 @@ impl Manifest {
                 *dir = dir.trim().to_owned();
             }
         }
-        validate(&manifest, path)?;
+        manifest.validate(path)?;
         Ok(manifest)
     }
 
@@ -514,18 +1243,50 @@ pub enum ManifestError {
     },
     #[error("cannot serialize manifest: {source}")]
     Serialize { source: toml::ser::Error },
+    #[error("environment variable `{name}` referenced in {path} is not set — set it, or write `$${{{name}}}` for a literal `${{{name}}}`")]
+    UndefinedEnvVar { path: String, name: String },
+    #[error("invalid environment variable reference in {path}: {message}")]
+    InvalidEnvReference { path: String, message: String },
     #[error("package name must not be empty in {path}")]
     EmptyName { path: String },
     #[error("package name `{name}` contains invalid characters in {path} (only alphanumeric, hyphen, underscore allowed)")]
     InvalidName { path: String, name: String },
     #[error("entrypoint `{entrypoint}` must end with .kt in {path}")]
     InvalidEntrypoint { path: String, entrypoint: String },
+    #[error("invalid `source_dirs` in [package] of {path}: {message}")]
+    InvalidSourceDirs { path: String, message: String },
+    #[error("invalid `test_dir` in [package] of {path}: {message}")]
+    InvalidTestDir { path: String, message: String },
+    #[error("invalid `konvoy_version` in [package] of {path}: {message}")]
+    InvalidKonvoyVersion { path: String, message: String },
+    #[error("this project requires Konvoy {required}, you have {current} — upgrade Konvoy to build {path}")]
+    KonvoyVersionUnsatisfied {
+        path: String,
+        required: String,
+        current: String,
+    },
+    #[error("invalid `{field}` in [package] of {path}: {reason}")]
+    InvalidOutputName {
+        path: String,
+        field: String,
+        reason: String,
+    },
     #[error("invalid [toolchain] in {path}: {message}")]
     InvalidToolchain { path: String, message: String },
-    #[error("dependency `{name}` has no source (set `path` or `maven` + `version`) in {path}")]
+    #[error("invalid [build] in {path}: {message}")]
+    InvalidBuildConfig { path: String, message: String },
+    #[error("dependency `{name}` has no source (set `path`, `git` + `rev`, or `maven` + `version`) in {path}")]
     DependencyNoSource { path: String, name: String },
     #[error("dependency `{name}` has both `maven` and `path` set in {path} — use exactly one")]
     DependencyMavenWithPath { path: String, name: String },
+    #[error("dependency `{name}` has both `path` and `version` set in {path} — `version` only applies to `maven` dependencies; remove it or replace `path` with `maven`")]
+    DependencyPathWithVersion { path: String, name: String },
+    #[error("dependency `{name}` is a `maven` dependency marked `optional` in {path} — only path and git dependencies can be optional")]
+    DependencyOptionalMaven { path: String, name: String },
+    #[error("dependency `{name}` is a `maven` dependency with `allow_toolchain_mismatch` in {path} — only path and git dependencies are checked against the project's Kotlin version")]
+    DependencyToolchainOptInMaven { path: String, name: String },
+    #[error("dependency `{name}` sets `id` or `options` in {path} — those configure compiler plugins; move it under [plugins]")]
+    DependencyPluginOptions { path: String, name: String },
     #[error(
         "dependency `{name}` has `maven` without `version` in {path} — add `version = \"X.Y.Z\"`"
     )]
@@ -534,12 +1295,25 @@ pub enum ManifestError {
     DependencyVersionWithoutMaven { path: String, name: String },
     #[error("dependency `{name}` version must not be empty or whitespace in {path}")]
     DependencyEmptyVersion { path: String, name: String },
+    #[error("dependency `{name}` has invalid version `{version}` in {path} — {reason}")]
+    DependencyInvalidVersion {
+        path: String,
+        name: String,
+        version: String,
+        reason: String,
+    },
     #[error("dependency `{name}` has invalid maven coordinate `{maven}` in {path} — expected format `groupId:artifactId` (exactly one colon)")]
     DependencyInvalidMaven {
         path: String,
         name: String,
         maven: String,
     },
+    #[error("invalid git dependency `{name}` in {path}: {reason}")]
+    DependencyInvalidGit {
+        path: String,
+        name: String,
+        reason: String,
+    },
     #[error("dependency name `{name}` contains invalid characters in {path}")]
     DependencyInvalidName { path: String, name: String },
     #[error("dependency `{name}` references itself in {path}")]
@@ -550,6 +1324,12 @@ pub enum ManifestError {
         name: String,
         reason: String,
     },
+    #[error("invalid feature `{name}` in {path}: {reason}")]
+    InvalidFeature {
+        path: String,
+        name: String,
+        reason: String,
+    },
     #[error("invalid codegen `{name}` in {path}: {reason}")]
     InvalidCodegenConfig {
         path: String,
@@ -640,20 +1420,38 @@ impl ManifestError {
                 key("package.name".to_owned())
             }
             ManifestError::InvalidEntrypoint { .. } => key("package.entrypoint".to_owned()),
+            ManifestError::InvalidSourceDirs { .. } => key("package.source_dirs".to_owned()),
+            ManifestError::InvalidTestDir { .. } => key("package.test_dir".to_owned()),
+            ManifestError::InvalidKonvoyVersion { .. }
+            | ManifestError::KonvoyVersionUnsatisfied { .. } => {
+                key("package.konvoy_version".to_owned())
+            }
+            ManifestError::InvalidOutputName { field, .. } => key(format!("package.{field}")),
             ManifestError::InvalidToolchain { .. } => key("toolchain".to_owned()),
+            ManifestError::InvalidBuildConfig { .. } => key("build".to_owned()),
             ManifestError::DependencyNoSource { name, .. }
             | ManifestError::DependencyMavenWithPath { name, .. }
+            | ManifestError::DependencyPathWithVersion { name, .. }
+            | ManifestError::DependencyOptionalMaven { name, .. }
+            | ManifestError::DependencyToolchainOptInMaven { name, .. }
+            | ManifestError::DependencyPluginOptions { name, .. }
             | ManifestError::DependencyMavenWithoutVersion { name, .. }
             | ManifestError::DependencyVersionWithoutMaven { name, .. }
             | ManifestError::DependencyEmptyVersion { name, .. }
+            | ManifestError::DependencyInvalidVersion { name, .. }
             | ManifestError::DependencyInvalidMaven { name, .. }
+            | ManifestError::DependencyInvalidGit { name, .. }
             | ManifestError::DependencyInvalidName { name, .. }
             | ManifestError::DependencySelfReference { name, .. } => {
                 key(format!("dependencies.{name}"))
             }
             ManifestError::InvalidPluginConfig { name, .. } => key(format!("plugins.{name}")),
             ManifestError::InvalidCodegenConfig { name, .. } => key(format!("codegen.{name}")),
-            ManifestError::Read { .. } | ManifestError::Serialize { .. } => (None, None, None),
+            ManifestError::InvalidFeature { name, .. } => key(format!("features.{name}")),
+            ManifestError::Read { .. }
+            | ManifestError::Serialize { .. }
+            | ManifestError::UndefinedEnvVar { .. }
+            | ManifestError::InvalidEnvReference { .. } => (None, None, None),
         }
     }
 }
@@ -740,6 +1538,155 @@ kotlin = ""
         assert!(err.contains("empty"), "error was: {err}");
     }
 
+    #[test]
+    fn reject_malformed_kotlin_version() {
+        for version in ["latest", "2", "2.1.", "v2.1.0", "2.1.0-", "2.1.0 RC"] {
+            let toml = format!(
+                r#"
+[package]
+name = "bad-ver"
+
+[toolchain]
+kotlin = "{version}"
+"#
+            );
+            let err = Manifest::from_str(&toml, "konvoy.toml")
+                .unwrap_err()
+                .to_string();
+            assert!(
+                err.contains("kotlin version") && err.contains(version),
+                "{version}: error was: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn accept_kotlin_version_with_qualifier() {
+        for version in ["2.1.0", "2.2.0-RC", "2.2.20-Beta1", "2.1"] {
+            let toml = format!(
+                r#"
+[package]
+name = "qualified"
+
+[toolchain]
+kotlin = "{version}"
+"#
+            );
+            assert!(
+                Manifest::from_str(&toml, "konvoy.toml").is_ok(),
+                "{version} should be accepted"
+            );
+        }
+    }
+
+    #[test]
+    fn validate_catches_in_memory_edits() {
+        let mut manifest = Manifest::from_str(
+            &format!("[package]\nname = \"app\"\n{TOOLCHAIN}"),
+            "konvoy.toml",
+        )
+        .unwrap();
+        assert!(manifest.validate("konvoy.toml").is_ok());
+
+        manifest.package.name = "1app".to_owned();
+        let err = manifest.validate("konvoy.toml").unwrap_err().to_string();
+        assert!(err.contains("package name `1app`"), "error was: {err}");
+    }
+
+    fn test_env(name: &str) -> Option<String> {
+        match name {
+            "KOTLIN_VERSION" => Some("2.2.0".to_owned()),
+            "APP" => Some("ci-app".to_owned()),
+            "INJECTED" => {
+                Some("x\"\n\n[dependencies]\nevil = { path = \"../evil\" }\n# \"".to_owned())
+            }
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn expand_env_substitutes_when_enabled() {
+        let toml = r#"
+[package]
+name = "${APP}"
+expand_env = true
+
+[toolchain]
+kotlin = "${KOTLIN_VERSION}"
+"#;
+        let manifest = Manifest::from_str_with_env(toml, "konvoy.toml", test_env).unwrap();
+        assert_eq!(manifest.package.name, "ci-app");
+        assert_eq!(manifest.toolchain.kotlin, "2.2.0");
+    }
+
+    #[test]
+    fn expand_env_keeps_a_value_inside_its_string() {
+        // The value carries quotes and a table header; it must land in the
+        // description verbatim instead of adding a dependency.
+        let toml = r#"
+[package]
+name = "app"
+description = "${INJECTED}" # ${UNSET_IN_A_COMMENT}
+expand_env = true
+
+[toolchain]
+kotlin = "${KOTLIN_VERSION}"
+"#;
+        let manifest = Manifest::from_str_with_env(toml, "konvoy.toml", test_env).unwrap();
+        assert_eq!(
+            manifest.package.description.as_deref(),
+            test_env("INJECTED").as_deref()
+        );
+        assert!(manifest.dependencies.is_empty());
+        assert_eq!(manifest.toolchain.kotlin, "2.2.0");
+    }
+
+    #[test]
+    fn expand_env_is_opt_in() {
+        let toml = r#"
+[package]
+name = "app"
+
+[toolchain]
+kotlin = "${KOTLIN_VERSION}"
+"#;
+        let err = Manifest::from_str_with_env(toml, "konvoy.toml", test_env)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("${KOTLIN_VERSION}"), "error was: {err}");
+    }
+
+    #[test]
+    fn expand_env_rejects_unset_variable() {
+        let toml = r#"
+[package]
+name = "app"
+expand_env = true
+
+[toolchain]
+kotlin = "${MISSING_KOTLIN}"
+"#;
+        let err = Manifest::from_str_with_env(toml, "konvoy.toml", test_env).unwrap_err();
+        assert!(
+            matches!(&err, ManifestError::UndefinedEnvVar { name, .. } if name == "MISSING_KOTLIN"),
+            "error was: {err}"
+        );
+    }
+
+    #[test]
+    fn expand_env_vars_escapes_and_literals() {
+        let out = expand_env_vars("a $${APP} b $5 c ${APP}$", "konvoy.toml", test_env).unwrap();
+        assert_eq!(out, "a ${APP} b $5 c ci-app$");
+    }
+
+    #[test]
+    fn expand_env_vars_rejects_malformed_references() {
+        let unterminated = expand_env_vars("x = \"${APP\"", "konvoy.toml", test_env).unwrap_err();
+        assert!(unterminated.to_string().contains("unterminated"));
+        let bad_name = expand_env_vars("x = \"${1X}\"", "konvoy.toml", test_env).unwrap_err();
+        assert!(bad_name.to_string().contains("${1X}"), "{bad_name}");
+    }
+
     #[test]
     fn reject_missing_package() {
         let toml = r#"
@@ -815,7 +1762,35 @@ name = "round-trip"
 entrypoint = "src/app.kt"
 {TOOLCHAIN}"#
         );
-        let original = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        let original = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        let serialized = original.to_toml().unwrap();
+        let reparsed = Manifest::from_str(&serialized, "konvoy.toml").unwrap();
+        assert_eq!(original, reparsed);
+    }
+
+    #[test]
+    fn round_trip_keeps_package_metadata() {
+        let toml = format!(
+            r#"
+[package]
+name = "shared"
+kind = "lib"
+version = "1.2.0"
+description = "Helpers shared across services"
+authors = ["Jane Doe <jane@example.com>", "Build Team"]
+license = "Apache-2.0"
+{TOOLCHAIN}"#
+        );
+        let original = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert_eq!(
+            original.package.description.as_deref(),
+            Some("Helpers shared across services")
+        );
+        assert_eq!(
+            original.package.authors,
+            ["Jane Doe <jane@example.com>", "Build Team"]
+        );
+        assert_eq!(original.package.license.as_deref(), Some("Apache-2.0"));
         let serialized = original.to_toml().unwrap();
         let reparsed = Manifest::from_str(&serialized, "konvoy.toml").unwrap();
         assert_eq!(original, reparsed);
@@ -836,6 +1811,18 @@ entrypoint = "src/app.kt"
         assert!(!is_valid_name("-leading-hyphen"));
     }
 
+    #[test]
+    fn sanitize_name_produces_valid_names() {
+        assert_eq!(sanitize_name("my-app"), "my-app");
+        assert_eq!(sanitize_name("my project"), "my-project");
+        assert_eq!(sanitize_name("../evil"), "_---evil");
+        assert_eq!(sanitize_name("123abc"), "_123abc");
+        assert_eq!(sanitize_name(""), "_");
+        for raw in ["my project", "../evil", "123abc", "-x", "caf\u{e9}", ""] {
+            assert!(is_valid_name(&sanitize_name(raw)), "{raw:?}");
+        }
+    }
+
     #[test]
     fn parse_lib_manifest() {
         let toml = format!(
@@ -914,6 +1901,26 @@ bad-dep = {{}}
         assert!(err.contains("no source"), "error was: {err}");
     }
 
+    #[test]
+    fn reject_dependency_with_path_and_version() {
+        let toml = format!(
+            r#"
+[package]
+name = "my-app"
+{TOOLCHAIN}
+[dependencies]
+my-lib = {{ path = "../my-lib", version = "1.0.0" }}
+"#
+        );
+        let err = Manifest::from_str(&toml, "konvoy.toml")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("dependency `my-lib` has both `path` and `version`"),
+            "error was: {err}"
+        );
+    }
+
     #[test]
     fn reject_self_referencing_dependency() {
         let toml = format!(
@@ -1085,6 +2092,103 @@ name = "my-app"
         );
     }
 
+    #[test]
+    fn parse_toolchain_jre() {
+        let toml = r#"
+[package]
+name = "my-app"
+
+[toolchain]
+kotlin = "2.1.0"
+jre = 17
+"#;
+        let manifest = Manifest::from_str(toml, "konvoy.toml").unwrap();
+        assert_eq!(manifest.toolchain.jre, Some(17));
+        assert!(manifest.to_toml().unwrap().contains("jre = 17"));
+    }
+
+    #[test]
+    fn toolchain_jre_omitted_by_default() {
+        let toml = format!(
+            r#"
+[package]
+name = "my-app"
+{TOOLCHAIN}"#
+        );
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert!(manifest.toolchain.jre.is_none());
+        assert!(!manifest.to_toml().unwrap().contains("jre"));
+    }
+
+    #[test]
+    fn reject_too_old_jre() {
+        let toml = r#"
+[package]
+name = "my-app"
+
+[toolchain]
+kotlin = "2.1.0"
+jre = 7
+"#;
+        let err = Manifest::from_str(toml, "konvoy.toml")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("at least 8"), "error was: {err}");
+    }
+
+    #[test]
+    fn parse_toolchain_language_and_api_version() {
+        let toml = r#"
+[package]
+name = "my-app"
+
+[toolchain]
+kotlin = "2.1.0"
+language_version = "1.9"
+api_version = "1.8"
+"#;
+        let manifest = Manifest::from_str(toml, "konvoy.toml").unwrap();
+        assert_eq!(manifest.toolchain.language_version.as_deref(), Some("1.9"));
+        assert_eq!(manifest.toolchain.api_version.as_deref(), Some("1.8"));
+        // The serialized manifest feeds the build cache key.
+        let serialized = manifest.to_toml().unwrap();
+        assert!(
+            serialized.contains("language_version = \"1.9\""),
+            "{serialized}"
+        );
+        assert!(serialized.contains("api_version = \"1.8\""), "{serialized}");
+    }
+
+    #[test]
+    fn reject_malformed_language_version() {
+        for bad in ["1", "1.9.0", "1.x", ".9", "latest"] {
+            let toml = format!(
+                "[package]\nname = \"my-app\"\n\n[toolchain]\nkotlin = \"2.1.0\"\nlanguage_version = \"{bad}\"\n"
+            );
+            let err = Manifest::from_str(&toml, "konvoy.toml")
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains("language_version"), "error was: {err}");
+            assert!(err.contains("X.Y"), "error was: {err}");
+        }
+    }
+
+    #[test]
+    fn reject_malformed_api_version() {
+        let toml = r#"
+[package]
+name = "my-app"
+
+[toolchain]
+kotlin = "2.1.0"
+api_version = "2"
+"#;
+        let err = Manifest::from_str(toml, "konvoy.toml")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("api_version `2`"), "error was: {err}");
+    }
+
     #[test]
     fn reject_empty_detekt_version() {
         let toml = r#"
@@ -1104,6 +2208,136 @@ detekt = ""
         );
     }
 
+    #[test]
+    fn parse_manifest_with_features() {
+        let toml = r#"
+[package]
+name = "my-app"
+
+[toolchain]
+kotlin = "2.1.0"
+
+[features]
+default = ["json"]
+json = []
+metrics = ["json"]
+"#;
+        let manifest = Manifest::from_str(toml, "konvoy.toml").unwrap();
+        assert_eq!(manifest.features.len(), 3);
+        assert_eq!(manifest.features["default"], vec!["json".to_owned()]);
+        assert_eq!(manifest.features["metrics"], vec!["json".to_owned()]);
+        let reparsed = Manifest::from_str(&manifest.to_toml().unwrap(), "konvoy.toml").unwrap();
+        assert_eq!(reparsed.features, manifest.features);
+    }
+
+    #[test]
+    fn reject_feature_enabling_undeclared_feature() {
+        let toml = r#"
+[package]
+name = "my-app"
+
+[toolchain]
+kotlin = "2.1.0"
+
+[features]
+default = ["jsn"]
+json = []
+"#;
+        let err = Manifest::from_str(toml, "konvoy.toml").unwrap_err();
+        assert!(
+            matches!(&err, ManifestError::InvalidFeature { name, .. } if name == "default"),
+            "got: {err}"
+        );
+        assert!(err.to_string().contains("`jsn`"), "got: {err}");
+    }
+
+    #[test]
+    fn reject_feature_enabling_default_or_itself() {
+        let base = "[package]\nname = \"my-app\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n\n[features]\ndefault = []\n";
+        let err = Manifest::from_str(&format!("{base}a = [\"default\"]\n"), "konvoy.toml")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("`default` cannot be enabled"), "got: {err}");
+        let err = Manifest::from_str(&format!("{base}a = [\"a\"]\n"), "konvoy.toml")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("cannot enable itself"), "got: {err}");
+    }
+
+    #[test]
+    fn feature_may_enable_optional_dependency() {
+        let base = "[package]\nname = \"my-app\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n\n[features]\nmetrics = [\"prom\"]\n\n[dependencies]\n";
+        let toml = format!("{base}prom = {{ path = \"../prom\", optional = true }}\n");
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert!(manifest.dependencies["prom"].optional);
+        let reparsed = Manifest::from_str(&manifest.to_toml().unwrap(), "konvoy.toml").unwrap();
+        assert_eq!(manifest, reparsed);
+
+        let toml = format!("{base}prom = {{ path = \"../prom\" }}\n");
+        let err = Manifest::from_str(&toml, "konvoy.toml").unwrap_err();
+        assert!(
+            matches!(&err, ManifestError::InvalidFeature { name, .. } if name == "metrics"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn reject_optional_maven_dependency() {
+        let toml = "[package]\nname = \"my-app\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n\n[dependencies]\njson = { maven = \"org.example:json\", version = \"1.0.0\", optional = true }\n";
+        let err = Manifest::from_str(toml, "konvoy.toml").unwrap_err();
+        assert!(
+            matches!(&err, ManifestError::DependencyOptionalMaven { name, .. } if name == "json"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn reject_invalid_feature_name() {
+        let toml = "[package]\nname = \"my-app\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n\n[features]\n\"bad.name\" = []\n";
+        let err = Manifest::from_str(toml, "konvoy.toml")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("invalid feature name"), "got: {err}");
+    }
+
+    #[test]
+    fn parse_manifest_with_ktlint() {
+        let toml = r#"
+[package]
+name = "my-app"
+
+[toolchain]
+kotlin = "2.1.0"
+ktlint = "1.3.1"
+"#;
+        let manifest = Manifest::from_str(toml, "konvoy.toml").unwrap();
+        assert_eq!(manifest.toolchain.ktlint.as_deref(), Some("1.3.1"));
+        let serialized = manifest.to_toml().unwrap();
+        assert!(
+            serialized.contains("ktlint = \"1.3.1\""),
+            "got: {serialized}"
+        );
+    }
+
+    #[test]
+    fn reject_empty_ktlint_version() {
+        let toml = r#"
+[package]
+name = "my-app"
+
+[toolchain]
+kotlin = "2.1.0"
+ktlint = ""
+"#;
+        let err = Manifest::from_str(toml, "konvoy.toml")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("ktlint version must not be empty"),
+            "error was: {err}"
+        );
+    }
+
     #[test]
     fn parse_manifest_with_openapi_codegen() {
         let toml = format!(
@@ -1745,53 +2979,207 @@ version = "1.0"
     }
 
     #[test]
-    fn reject_plugin_maven_trailing_colon() {
+    fn reject_plugin_maven_trailing_colon() {
+        let toml = format!(
+            r#"
+[package]
+name = "my-app"
+{TOOLCHAIN}
+[plugins.bad-plugin]
+maven = "group:"
+version = "1.0"
+"#
+        );
+        let result = Manifest::from_str(&toml, "konvoy.toml");
+        assert!(result.is_err());
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("invalid maven coordinate"), "error was: {err}");
+    }
+
+    #[test]
+    fn empty_plugins_table_parses() {
+        let toml = format!(
+            r#"
+[package]
+name = "my-app"
+{TOOLCHAIN}
+[plugins]
+"#
+        );
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert!(manifest.plugins.is_empty());
+    }
+
+    #[test]
+    fn multiple_plugins_parse() {
+        let toml = format!(
+            r#"
+[package]
+name = "my-app"
+{TOOLCHAIN}
+[plugins]
+kotlin-serialization = {{ maven = "org.jetbrains.kotlin:kotlin-serialization-compiler-plugin", version = "{{kotlin}}" }}
+kotlin-allopen = {{ maven = "org.jetbrains.kotlin:kotlin-allopen-compiler-plugin", version = "{{kotlin}}" }}
+"#
+        );
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert_eq!(manifest.plugins.len(), 2);
+        assert!(manifest.plugins.contains_key("kotlin-serialization"));
+        assert!(manifest.plugins.contains_key("kotlin-allopen"));
+    }
+
+    #[test]
+    fn plugin_options_parse() {
+        let toml = format!(
+            r#"
+[package]
+name = "my-app"
+{TOOLCHAIN}
+[plugins.kotlin-allopen]
+maven = "org.jetbrains.kotlin:kotlin-allopen-compiler-plugin"
+version = "{{kotlin}}"
+id = "org.jetbrains.kotlin.allopen"
+options = {{ annotation = "com.example.Open", preset = "spring" }}
+"#
+        );
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        let plugin = manifest.plugins.get("kotlin-allopen").unwrap();
+        assert_eq!(plugin.id.as_deref(), Some("org.jetbrains.kotlin.allopen"));
+        assert_eq!(
+            plugin.options.get("annotation").map(String::as_str),
+            Some("com.example.Open")
+        );
+        assert_eq!(plugin.options.len(), 2);
+        let serialized = manifest.to_toml().unwrap();
+        assert!(serialized.contains("preset"), "{serialized}");
+    }
+
+    #[test]
+    fn reject_plugin_options_without_id() {
+        let toml = format!(
+            r#"
+[package]
+name = "my-app"
+{TOOLCHAIN}
+[plugins.kotlin-allopen]
+maven = "org.jetbrains.kotlin:kotlin-allopen-compiler-plugin"
+version = "{{kotlin}}"
+options = {{ preset = "spring" }}
+"#
+        );
+        let err = Manifest::from_str(&toml, "konvoy.toml")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("need an `id`"), "error was: {err}");
+    }
+
+    #[test]
+    fn allow_unused_parses_for_dependencies_only() {
+        let base = "[package]\nname = \"my-app\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n\n";
+        let toml = format!(
+            "{base}[dependencies]\njson = {{ maven = \"org.example:json\", version = \"1.0.0\", allow_unused = true }}\n"
+        );
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert!(manifest.dependencies["json"].allow_unused);
+        let reparsed = Manifest::from_str(&manifest.to_toml().unwrap(), "konvoy.toml").unwrap();
+        assert_eq!(manifest, reparsed);
+
+        let toml = format!(
+            "{base}[plugins.allopen]\nmaven = \"org.jetbrains.kotlin:kotlin-allopen-compiler-plugin\"\nversion = \"2.1.0\"\nallow_unused = true\n"
+        );
+        let err = Manifest::from_str(&toml, "konvoy.toml")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("`allow_unused`"), "error was: {err}");
+    }
+
+    #[test]
+    fn allow_toolchain_mismatch_parses_for_path_dependencies_only() {
+        let base = "[package]\nname = \"my-app\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n\n";
+        let toml = format!(
+            "{base}[dependencies]\nlegacy = {{ path = \"../legacy\", allow_toolchain_mismatch = true }}\n"
+        );
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert!(manifest.dependencies["legacy"].allow_toolchain_mismatch);
+        let reparsed = Manifest::from_str(&manifest.to_toml().unwrap(), "konvoy.toml").unwrap();
+        assert_eq!(manifest, reparsed);
+
+        let toml = format!(
+            "{base}[dependencies]\njson = {{ maven = \"org.example:json\", version = \"1.0.0\", allow_toolchain_mismatch = true }}\n"
+        );
+        let err = Manifest::from_str(&toml, "konvoy.toml").unwrap_err();
+        assert!(
+            matches!(&err, ManifestError::DependencyToolchainOptInMaven { name, .. } if name == "json"),
+            "got: {err}"
+        );
+
+        let toml = format!(
+            "{base}[plugins.allopen]\nmaven = \"org.jetbrains.kotlin:kotlin-allopen-compiler-plugin\"\nversion = \"2.1.0\"\nallow_toolchain_mismatch = true\n"
+        );
+        let err = Manifest::from_str(&toml, "konvoy.toml")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("`allow_toolchain_mismatch`"),
+            "error was: {err}"
+        );
+    }
+
+    #[test]
+    fn reject_plugin_id_with_colon() {
         let toml = format!(
             r#"
 [package]
 name = "my-app"
 {TOOLCHAIN}
-[plugins.bad-plugin]
-maven = "group:"
-version = "1.0"
+[plugins.kotlin-allopen]
+maven = "org.jetbrains.kotlin:kotlin-allopen-compiler-plugin"
+version = "{{kotlin}}"
+id = "plugin:allopen"
 "#
         );
-        let result = Manifest::from_str(&toml, "konvoy.toml");
-        assert!(result.is_err());
-        let err = result.unwrap_err().to_string();
-        assert!(err.contains("invalid maven coordinate"), "error was: {err}");
+        let err = Manifest::from_str(&toml, "konvoy.toml")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("plugin `id`"), "error was: {err}");
     }
 
     #[test]
-    fn empty_plugins_table_parses() {
+    fn reject_plugin_option_key_with_equals() {
         let toml = format!(
             r#"
 [package]
 name = "my-app"
 {TOOLCHAIN}
-[plugins]
+[plugins.kotlin-allopen]
+maven = "org.jetbrains.kotlin:kotlin-allopen-compiler-plugin"
+version = "{{kotlin}}"
+id = "org.jetbrains.kotlin.allopen"
+options = {{ "a=b" = "c" }}
 "#
         );
-        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
-        assert!(manifest.plugins.is_empty());
+        let err = Manifest::from_str(&toml, "konvoy.toml")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("option key `a=b`"), "error was: {err}");
     }
 
     #[test]
-    fn multiple_plugins_parse() {
+    fn reject_plugin_options_on_dependency() {
         let toml = format!(
             r#"
 [package]
 name = "my-app"
 {TOOLCHAIN}
-[plugins]
-kotlin-serialization = {{ maven = "org.jetbrains.kotlin:kotlin-serialization-compiler-plugin", version = "{{kotlin}}" }}
-kotlin-allopen = {{ maven = "org.jetbrains.kotlin:kotlin-allopen-compiler-plugin", version = "{{kotlin}}" }}
+[dependencies]
+json = {{ maven = "org.example:json", version = "1.0.0", id = "org.example.json" }}
 "#
         );
-        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
-        assert_eq!(manifest.plugins.len(), 2);
-        assert!(manifest.plugins.contains_key("kotlin-serialization"));
-        assert!(manifest.plugins.contains_key("kotlin-allopen"));
+        let err = Manifest::from_str(&toml, "konvoy.toml").unwrap_err();
+        assert!(
+            matches!(&err, ManifestError::DependencyPluginOptions { name, .. } if name == "json"),
+            "got: {err}"
+        );
     }
 
     #[test]
@@ -1966,6 +3354,83 @@ kotlinx-coroutines = {{ maven = "org.jetbrains.kotlinx:kotlinx-coroutines-core",
         assert!(dep.path.is_none());
     }
 
+    #[test]
+    fn parse_git_dependency() {
+        let toml = format!(
+            r#"
+[package]
+name = "my-app"
+{TOOLCHAIN}
+[dependencies]
+utils = {{ git = "https://example.com/utils.git", rev = "main" }}
+"#
+        );
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap_or_else(|e| panic!("{e}"));
+        let dep = manifest
+            .dependencies
+            .get("utils")
+            .unwrap_or_else(|| panic!("missing dep"));
+        assert_eq!(
+            dep.as_git(),
+            Some(("https://example.com/utils.git", "main"))
+        );
+        assert!(!dep.is_maven());
+        assert!(dep.path.is_none());
+    }
+
+    fn git_dependency_error(spec: &str) -> String {
+        let toml =
+            format!("[package]\nname = \"my-app\"\n{TOOLCHAIN}\n[dependencies]\nutils = {spec}\n");
+        Manifest::from_str(&toml, "konvoy.toml")
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn reject_git_dependency_without_rev() {
+        let err = git_dependency_error(r#"{ git = "https://example.com/u.git" }"#);
+        assert!(err.contains("`git` requires `rev`"), "error was: {err}");
+        let err = git_dependency_error(r#"{ git = "https://example.com/u.git", rev = " " }"#);
+        assert!(err.contains("`git` requires `rev`"), "error was: {err}");
+    }
+
+    #[test]
+    fn reject_rev_without_git() {
+        let err = git_dependency_error(r#"{ path = "../u", rev = "main" }"#);
+        assert!(err.contains("`rev` requires `git`"), "error was: {err}");
+    }
+
+    #[test]
+    fn reject_git_combined_with_other_sources() {
+        let err = git_dependency_error(
+            r#"{ git = "https://example.com/u.git", rev = "v1", path = "../u" }"#,
+        );
+        assert!(err.contains("exactly one source"), "error was: {err}");
+        let err = git_dependency_error(
+            r#"{ git = "https://example.com/u.git", rev = "v1", maven = "a:b", version = "1.0" }"#,
+        );
+        assert!(err.contains("exactly one source"), "error was: {err}");
+    }
+
+    #[test]
+    fn reject_git_arguments_that_look_like_options() {
+        let err = git_dependency_error(r#"{ git = "--upload-pack=x", rev = "main" }"#);
+        assert!(err.contains("repository URL"), "error was: {err}");
+        let err = git_dependency_error(r#"{ git = "https://example.com/u.git", rev = "-x" }"#);
+        assert!(err.contains("must not start with `-`"), "error was: {err}");
+    }
+
+    #[test]
+    fn reject_git_plugin() {
+        let toml = format!(
+            "[package]\nname = \"my-app\"\n{TOOLCHAIN}\n[plugins]\nser = {{ git = \"https://example.com/p.git\", rev = \"main\" }}\n"
+        );
+        let err = Manifest::from_str(&toml, "konvoy.toml")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("not `git`"), "error was: {err}");
+    }
+
     #[test]
     fn reject_dependency_maven_with_path() {
         let toml = format!(
@@ -2006,6 +3471,45 @@ bad-dep = {{ maven = "com.example:lib", version = "" }}
         );
     }
 
+    #[test]
+    fn parse_dependency_version_range() {
+        let toml = format!(
+            r#"
+[package]
+name = "my-app"
+{TOOLCHAIN}
+[dependencies]
+kotlinx-coroutines = {{ maven = "org.jetbrains.kotlinx:kotlinx-coroutines-core", version = "^1.8" }}
+"#
+        );
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        let spec = manifest.dependencies.get("kotlinx-coroutines").unwrap();
+        let req = spec.version_req().unwrap();
+        assert_eq!(req.as_exact(), None);
+        assert!(req.matches("1.9.0"));
+        assert!(!req.matches("2.0.0"));
+    }
+
+    #[test]
+    fn reject_dependency_malformed_version_range() {
+        let toml = format!(
+            r#"
+[package]
+name = "my-app"
+{TOOLCHAIN}
+[dependencies]
+bad-dep = {{ maven = "com.example:lib", version = "^1.x" }}
+"#
+        );
+        let err = Manifest::from_str(&toml, "konvoy.toml")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("invalid version `^1.x`") && err.contains("bad-dep"),
+            "error should name the dep and version: {err}"
+        );
+    }
+
     #[test]
     fn reject_dependency_whitespace_only_version() {
         let toml = format!(
@@ -2445,6 +3949,13 @@ version = "1.0"
             path: None,
             maven: Some("org.example:lib".to_owned()),
             version: Some("1.0.0".to_owned()),
+            git: None,
+            rev: None,
+            optional: false,
+            allow_unused: false,
+            allow_toolchain_mismatch: false,
+            id: None,
+            options: BTreeMap::new(),
         };
         assert!(spec.is_maven());
     }
@@ -2455,6 +3966,13 @@ version = "1.0"
             path: None,
             maven: Some("org.example:lib".to_owned()),
             version: None,
+            git: None,
+            rev: None,
+            optional: false,
+            allow_unused: false,
+            allow_toolchain_mismatch: false,
+            id: None,
+            options: BTreeMap::new(),
         };
         assert!(!spec.is_maven());
     }
@@ -2465,6 +3983,13 @@ version = "1.0"
             path: None,
             maven: None,
             version: Some("1.0.0".to_owned()),
+            git: None,
+            rev: None,
+            optional: false,
+            allow_unused: false,
+            allow_toolchain_mismatch: false,
+            id: None,
+            options: BTreeMap::new(),
         };
         assert!(!spec.is_maven());
     }
@@ -2475,6 +4000,13 @@ version = "1.0"
             path: Some("../lib".to_owned()),
             maven: None,
             version: None,
+            git: None,
+            rev: None,
+            optional: false,
+            allow_unused: false,
+            allow_toolchain_mismatch: false,
+            id: None,
+            options: BTreeMap::new(),
         };
         assert!(!spec.is_maven());
     }
@@ -2485,6 +4017,13 @@ version = "1.0"
             path: None,
             maven: Some("org.example:lib".to_owned()),
             version: Some("1.0.0".to_owned()),
+            git: None,
+            rev: None,
+            optional: false,
+            allow_unused: false,
+            allow_toolchain_mismatch: false,
+            id: None,
+            options: BTreeMap::new(),
         };
         assert_eq!(spec.as_maven_coord(), Some(("org.example:lib", "1.0.0")));
     }
@@ -2495,22 +4034,363 @@ version = "1.0"
             path: None,
             maven: Some("org.example:lib".to_owned()),
             version: None,
+            git: None,
+            rev: None,
+            optional: false,
+            allow_unused: false,
+            allow_toolchain_mismatch: false,
+            id: None,
+            options: BTreeMap::new(),
         };
         let version_only = DependencySpec {
             path: None,
             maven: None,
             version: Some("1.0.0".to_owned()),
+            git: None,
+            rev: None,
+            optional: false,
+            allow_unused: false,
+            allow_toolchain_mismatch: false,
+            id: None,
+            options: BTreeMap::new(),
         };
         let neither = DependencySpec {
             path: None,
             maven: None,
             version: None,
+            git: None,
+            rev: None,
+            optional: false,
+            allow_unused: false,
+            allow_toolchain_mismatch: false,
+            id: None,
+            options: BTreeMap::new(),
         };
         assert_eq!(maven_only.as_maven_coord(), None);
         assert_eq!(version_only.as_maven_coord(), None);
         assert_eq!(neither.as_maven_coord(), None);
     }
 
+    // ---- [package] source_dirs ----------------------------------------------
+
+    #[test]
+    fn source_dirs_default_to_src() {
+        let toml = format!("[package]\nname = \"ok\"\n{TOOLCHAIN}");
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert!(manifest.package.source_dirs.is_empty());
+        assert_eq!(manifest.package.effective_source_dirs(), vec!["src"]);
+        let serialized = manifest.to_toml().unwrap();
+        assert!(!serialized.contains("source_dirs"), "{serialized}");
+    }
+
+    #[test]
+    fn parse_source_dirs() {
+        let toml = format!(
+            "[package]\nname = \"ok\"\nsource_dirs = [\"src/main/kotlin\", \" gen/ \"]\n{TOOLCHAIN}"
+        );
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert_eq!(
+            manifest.package.effective_source_dirs(),
+            vec!["src/main/kotlin", "gen"]
+        );
+        let reparsed = Manifest::from_str(&manifest.to_toml().unwrap(), "konvoy.toml").unwrap();
+        assert_eq!(manifest, reparsed);
+    }
+
+    #[test]
+    fn reject_source_dirs_escaping_project() {
+        for dir in ["../shared", "/abs/src", ""] {
+            let toml = format!("[package]\nname = \"ok\"\nsource_dirs = [\"{dir}\"]\n{TOOLCHAIN}");
+            let err = Manifest::from_str(&toml, "konvoy.toml").unwrap_err();
+            assert!(
+                matches!(err, ManifestError::InvalidSourceDirs { .. }),
+                "{dir}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_dir_defaults_to_src_test() {
+        let toml = format!("[package]\nname = \"ok\"\n{TOOLCHAIN}");
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert_eq!(manifest.package.effective_test_dir(), "src/test");
+        let serialized = manifest.to_toml().unwrap();
+        assert!(!serialized.contains("test_dir"), "{serialized}");
+    }
+
+    #[test]
+    fn parse_test_dir() {
+        let toml = format!("[package]\nname = \"ok\"\ntest_dir = \"tests/\"\n{TOOLCHAIN}");
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert_eq!(manifest.package.effective_test_dir(), "tests");
+        let reparsed = Manifest::from_str(&manifest.to_toml().unwrap(), "konvoy.toml").unwrap();
+        assert_eq!(manifest, reparsed);
+    }
+
+    #[test]
+    fn reject_invalid_test_dir() {
+        for (extra, needle) in [
+            ("test_dir = \"../tests\"", "`..`"),
+            ("test_dir = \"\"", "must not be empty"),
+            ("test_dir = \"src\"", "contains the source directory `src`"),
+            (
+                "source_dirs = [\"src/main/kotlin\"]\ntest_dir = \"src/main\"",
+                "src/main/kotlin",
+            ),
+        ] {
+            let toml = format!("[package]\nname = \"ok\"\n{extra}\n{TOOLCHAIN}");
+            let err = Manifest::from_str(&toml, "konvoy.toml").unwrap_err();
+            assert!(
+                matches!(err, ManifestError::InvalidTestDir { .. }),
+                "{extra}: {err}"
+            );
+            assert!(err.to_string().contains(needle), "{extra}: {err}");
+        }
+        let toml = format!("[package]\nname = \"ok\"\ntest_dir = \"src\"\n{TOOLCHAIN}");
+        let diags = Manifest::check_str(&toml, "konvoy.toml");
+        assert_eq!(
+            diags.first().and_then(|d| d.key_path.as_deref()),
+            Some("package.test_dir")
+        );
+    }
+
+    #[test]
+    fn konvoy_version_satisfied_by_running_binary() {
+        let toml = format!("[package]\nname = \"ok\"\nkonvoy_version = \">=0.5\"\n{TOOLCHAIN}");
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert_eq!(manifest.package.konvoy_version.as_deref(), Some(">=0.5"));
+        let reparsed = Manifest::from_str(&manifest.to_toml().unwrap(), "konvoy.toml").unwrap();
+        assert_eq!(manifest, reparsed);
+    }
+
+    #[test]
+    fn konvoy_version_compares_against_current() {
+        let toml = |req: &str| format!("[package]\nname = \"ok\"\nkonvoy_version = \"{req}\"\n");
+        assert!(check_konvoy_version(&toml(">=1.2"), "konvoy.toml", "1.2.0").is_ok());
+        assert!(check_konvoy_version(&toml(">= 1.10"), "konvoy.toml", "1.10.1").is_ok());
+        assert!(check_konvoy_version(&toml(">=1.4"), "konvoy.toml", "1.4.0-dev").is_ok());
+        let err = check_konvoy_version(&toml(">=1.10"), "konvoy.toml", "1.9.3").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "this project requires Konvoy >=1.10, you have 1.9.3 — upgrade Konvoy to build konvoy.toml"
+        );
+    }
+
+    #[test]
+    fn konvoy_version_checked_before_unknown_keys() {
+        let toml =
+            "[package]\nname = \"ok\"\nkonvoy_version = \">=999.0\"\nfrom_the_future = true\n";
+        let err = Manifest::from_str(toml, "konvoy.toml").unwrap_err();
+        assert!(
+            matches!(err, ManifestError::KonvoyVersionUnsatisfied { .. }),
+            "{err}"
+        );
+        let diags = Manifest::check_str(toml, "konvoy.toml");
+        assert_eq!(
+            diags.first().and_then(|d| d.key_path.as_deref()),
+            Some("package.konvoy_version")
+        );
+    }
+
+    #[test]
+    fn reject_malformed_konvoy_version() {
+        for req in ["0.5", "^0.5", ">=", ">=1", ">=1.x", ">=1.2.3.4"] {
+            let toml = format!("[package]\nname = \"ok\"\nkonvoy_version = \"{req}\"\n{TOOLCHAIN}");
+            let err = Manifest::from_str(&toml, "konvoy.toml").unwrap_err();
+            assert!(
+                matches!(err, ManifestError::InvalidKonvoyVersion { .. }),
+                "{req}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn reject_duplicate_source_dirs() {
+        let toml =
+            format!("[package]\nname = \"ok\"\nsource_dirs = [\"src\", \"src/\"]\n{TOOLCHAIN}");
+        let err = Manifest::from_str(&toml, "konvoy.toml").unwrap_err();
+        assert!(err.to_string().contains("listed more than once"), "{err}");
+        let diags = Manifest::check_str(&toml, "konvoy.toml");
+        assert_eq!(
+            diags.first().and_then(|d| d.key_path.as_deref()),
+            Some("package.source_dirs")
+        );
+    }
+
+    // ---- [package] bin_name / lib_name --------------------------------------
+
+    #[test]
+    fn output_name_defaults_to_package_name() {
+        let toml = format!("[package]\nname = \"my-cool-lib\"\n{TOOLCHAIN}");
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert_eq!(
+            manifest.package.output_name(Target::LinuxX64),
+            "my-cool-lib"
+        );
+        let serialized = manifest.to_toml().unwrap();
+        assert!(!serialized.contains("bin_name"), "{serialized}");
+    }
+
+    #[test]
+    fn bin_name_and_lib_name_rename_the_artifact() {
+        let toml = format!("[package]\nname = \"my-cool-lib\"\nbin_name = \"mytool\"\n{TOOLCHAIN}");
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert_eq!(manifest.package.output_name(Target::LinuxX64), "mytool");
+
+        let toml = format!(
+            "[package]\nname = \"my-cool-lib\"\nkind = \"lib\"\nlib_name = \"cool\"\n{TOOLCHAIN}"
+        );
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert_eq!(manifest.package.output_name(Target::LinuxX64), "cool.klib");
+        let reparsed = Manifest::from_str(&manifest.to_toml().unwrap(), "konvoy.toml").unwrap();
+        assert_eq!(manifest, reparsed);
+    }
+
+    #[test]
+    fn c_library_kinds_name_their_artifact_per_target() {
+        let toml = format!(
+            "[package]\nname = \"ffi\"\nkind = \"staticlib\"\nlib_name = \"bridge\"\n{TOOLCHAIN}"
+        );
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert_eq!(manifest.package.kind, PackageKind::StaticLib);
+        assert_eq!(
+            manifest.package.output_name(Target::MacOsArm64),
+            "libbridge.a"
+        );
+
+        let toml = format!("[package]\nname = \"ffi\"\nkind = \"cdylib\"\n{TOOLCHAIN}");
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert_eq!(manifest.package.kind, PackageKind::DynamicLib);
+        assert_eq!(manifest.package.output_name(Target::LinuxX64), "libffi.so");
+        assert_eq!(
+            manifest.package.output_name(Target::MacOsX64),
+            "libffi.dylib"
+        );
+        assert!(manifest.to_toml().unwrap().contains("kind = \"cdylib\""));
+
+        let toml =
+            format!("[package]\nname = \"ffi\"\nkind = \"cdylib\"\nbin_name = \"x\"\n{TOOLCHAIN}");
+        let err = Manifest::from_str(&toml, "konvoy.toml").unwrap_err();
+        assert!(err.to_string().contains("use `lib_name` instead"), "{err}");
+    }
+
+    #[test]
+    fn reject_output_name_for_the_other_kind() {
+        let toml = format!("[package]\nname = \"ok\"\nlib_name = \"cool\"\n{TOOLCHAIN}");
+        let err = Manifest::from_str(&toml, "konvoy.toml").unwrap_err();
+        assert!(err.to_string().contains("use `bin_name` instead"), "{err}");
+        let diags = Manifest::check_str(&toml, "konvoy.toml");
+        assert_eq!(
+            diags.first().and_then(|d| d.key_path.as_deref()),
+            Some("package.lib_name")
+        );
+    }
+
+    #[test]
+    fn reject_invalid_bin_name() {
+        for name in ["", "my/tool", "my tool"] {
+            let toml = format!("[package]\nname = \"ok\"\nbin_name = \"{name}\"\n{TOOLCHAIN}");
+            let err = Manifest::from_str(&toml, "konvoy.toml").unwrap_err();
+            assert!(
+                matches!(err, ManifestError::InvalidOutputName { .. }),
+                "{name}: {err}"
+            );
+        }
+    }
+
+    // ---- [build] ------------------------------------------------------------
+
+    #[test]
+    fn parse_build_timeout_secs() {
+        let toml = format!("[package]\nname = \"ok\"\n{TOOLCHAIN}\n[build]\ntimeout_secs = 600\n");
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert_eq!(manifest.build.timeout_secs, Some(600));
+    }
+
+    #[test]
+    fn parse_build_deny_warnings() {
+        let toml =
+            format!("[package]\nname = \"ok\"\n{TOOLCHAIN}\n[build]\ndeny_warnings = true\n");
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert!(manifest.build.deny_warnings);
+        assert!(!manifest.build.is_empty());
+        let serialized = manifest.to_toml().unwrap();
+        assert!(
+            serialized.contains("deny_warnings = true"),
+            "serialized was: {serialized}"
+        );
+    }
+
+    #[test]
+    fn parse_build_maven_hash() {
+        let toml =
+            format!("[package]\nname = \"ok\"\n{TOOLCHAIN}\n[build]\nmaven_hash = \"sha512\"\n");
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert_eq!(manifest.build.maven_hash, HashAlgorithm::Sha512);
+        assert!(!manifest.build.is_empty());
+        let serialized = manifest.to_toml().unwrap();
+        assert!(
+            serialized.contains("maven_hash = \"sha512\""),
+            "serialized was: {serialized}"
+        );
+
+        let toml =
+            format!("[package]\nname = \"ok\"\n{TOOLCHAIN}\n[build]\nmaven_hash = \"md5\"\n");
+        assert!(Manifest::from_str(&toml, "konvoy.toml").is_err());
+    }
+
+    #[test]
+    fn parse_build_compress_cache() {
+        let toml =
+            format!("[package]\nname = \"ok\"\n{TOOLCHAIN}\n[build]\ncompress_cache = true\n");
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert!(manifest.build.compress_cache);
+        assert!(!manifest.build.is_empty());
+        let serialized = manifest.to_toml().unwrap();
+        assert!(
+            serialized.contains("compress_cache = true"),
+            "serialized was: {serialized}"
+        );
+    }
+
+    #[test]
+    fn build_section_defaults_to_empty_and_is_omitted() {
+        let toml = format!("[package]\nname = \"ok\"\n{TOOLCHAIN}");
+        let manifest = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        assert!(manifest.build.is_empty());
+        let serialized = manifest.to_toml().unwrap();
+        assert!(
+            !serialized.contains("[build]"),
+            "serialized was: {serialized}"
+        );
+    }
+
+    #[test]
+    fn round_trip_with_build_timeout() {
+        let toml = format!("[package]\nname = \"ok\"\n{TOOLCHAIN}\n[build]\ntimeout_secs = 30\n");
+        let original = Manifest::from_str(&toml, "konvoy.toml").unwrap();
+        let reparsed = Manifest::from_str(&original.to_toml().unwrap(), "konvoy.toml").unwrap();
+        assert_eq!(original, reparsed);
+    }
+
+    #[test]
+    fn reject_zero_build_timeout() {
+        let toml = format!("[package]\nname = \"ok\"\n{TOOLCHAIN}\n[build]\ntimeout_secs = 0\n");
+        let err = Manifest::from_str(&toml, "konvoy.toml").unwrap_err();
+        assert!(matches!(err, ManifestError::InvalidBuildConfig { .. }));
+        let diags = Manifest::check_str(&toml, "konvoy.toml");
+        assert_eq!(
+            diags.first().and_then(|d| d.key_path.as_deref()),
+            Some("build")
+        );
+    }
+
+    #[test]
+    fn reject_unknown_build_key() {
+        let toml = format!("[package]\nname = \"ok\"\n{TOOLCHAIN}\n[build]\nthreads = 4\n");
+        assert!(Manifest::from_str(&toml, "konvoy.toml").is_err());
+    }
+
     // ---- check_str (structured diagnostics for `konvoy check`) --------------
 
     #[test]
@@ -0,0 +1,351 @@
+//! The user-wide `~/.konvoy/config.toml`: defaults shared by every project.
+//!
+//! A setting is taken from the first of these that provides it:
+//!
+//! 1. a command-line flag (`--offline`, `--color`, `--mirror`),
+//! 2. an environment variable (`KONVOY_OFFLINE`, `NO_COLOR`,
+//!    `KONVOY_TOOLCHAIN_MIRROR`, `KONVOY_DOWNLOAD_IDLE_TIMEOUT`),
+//! 3. the project's `konvoy.toml`,
+//! 4. this file,
+//! 5. the built-in default.
+//!
+//! Not every setting exists at every level; no setting here has a
+//! `konvoy.toml` counterpart yet. A missing file is the same as an empty one.
+
+use std::path::{Path, PathBuf};
+
+use konvoy_util::color::ColorChoice;
+use konvoy_util::net::NetworkClient;
+use serde::Deserialize;
+
+/// File name of the user config inside the Konvoy home directory.
+pub const USER_CONFIG_FILE: &str = "config.toml";
+
+/// Environment variable that overrides the user config's `offline`: `1` or
+/// `true` forbids network access, `0` or `false` allows it.
+pub const OFFLINE_ENV: &str = "KONVOY_OFFLINE";
+
+/// Environment variable that overrides the user config's `toolchain_mirror`.
+pub const TOOLCHAIN_MIRROR_ENV: &str = "KONVOY_TOOLCHAIN_MIRROR";
+
+/// Environment variable that sets how many seconds a download may receive
+/// nothing before it counts as stalled.
+pub const DOWNLOAD_IDLE_TIMEOUT_ENV: &str = "KONVOY_DOWNLOAD_IDLE_TIMEOUT";
+
+/// Environment variable that disables color when `--color` is not given
+/// (<https://no-color.org>).
+const NO_COLOR_ENV: &str = "NO_COLOR";
+
+/// Contents of `~/.konvoy/config.toml`. Every key is optional.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UserConfig {
+    /// Maven repository that replaces Maven Central for every download, e.g.
+    /// a corporate mirror.
+    pub maven_mirror: Option<String>,
+    /// Base URL that replaces the JetBrains releases for Kotlin/Native
+    /// tarballs, keeping the `v{version}/kotlin-native-prebuilt-...` path.
+    pub toolchain_mirror: Option<String>,
+    /// How many times a dropped download is resumed before it fails.
+    pub download_retries: Option<u32>,
+    /// Forbid network access, as if every command were given `--offline`.
+    pub offline: Option<bool>,
+    /// Default for `--color`.
+    pub color: Option<ColorChoice>,
+}
+
+impl UserConfig {
+    /// Path of the user config, `~/.konvoy/config.toml`.
+    ///
+    /// # Errors
+    /// Returns an error if the home directory cannot be determined.
+    pub fn default_path() -> Result<PathBuf, UserConfigError> {
+        Ok(konvoy_util::fs::konvoy_home()?.join(USER_CONFIG_FILE))
+    }
+
+    /// Load `~/.konvoy/config.toml`. Without a home directory there is no
+    /// user config, so the defaults apply.
+    ///
+    /// # Errors
+    /// Returns an error if the file exists but cannot be read or is invalid.
+    pub fn load() -> Result<Self, UserConfigError> {
+        match Self::default_path() {
+            Ok(path) => Self::from_path(&path),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Load a user config from `path`, or the defaults if it does not exist.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read, is not valid TOML, has an
+    /// unknown key, or sets a mirror to something other than an HTTP(S) URL.
+    pub fn from_path(path: &Path) -> Result<Self, UserConfigError> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(source) => {
+                return Err(UserConfigError::Read {
+                    path: path.display().to_string(),
+                    source,
+                })
+            }
+        };
+        let config: Self = toml::from_str(&content).map_err(|source| UserConfigError::Parse {
+            path: path.display().to_string(),
+            source,
+        })?;
+        let mirrors = [
+            ("maven_mirror", &config.maven_mirror),
+            ("toolchain_mirror", &config.toolchain_mirror),
+        ];
+        for (key, mirror) in mirrors {
+            if let Some(url) = mirror.as_ref().filter(|url| !is_http_url(url)) {
+                return Err(UserConfigError::InvalidMirror {
+                    path: path.display().to_string(),
+                    key,
+                    url: url.clone(),
+                });
+            }
+        }
+        Ok(config)
+    }
+
+    /// Whether the command runs offline: `--offline`, then `KONVOY_OFFLINE`
+    /// (`env`), then `offline` from this file.
+    pub fn offline(&self, flag: bool, env: Option<&str>) -> bool {
+        let from_env = match env.map(str::trim) {
+            Some("1" | "true") => Some(true),
+            Some("0" | "false") => Some(false),
+            _ => None,
+        };
+        flag || from_env.or(self.offline).unwrap_or(false)
+    }
+
+    /// [`offline`](Self::offline), reading `KONVOY_OFFLINE` from the
+    /// environment.
+    pub fn offline_from_env(&self, flag: bool) -> bool {
+        self.offline(flag, std::env::var(OFFLINE_ENV).ok().as_deref())
+    }
+
+    /// The color choice: `--color` (`flag`), then a non-empty `NO_COLOR`,
+    /// then `color` from this file, then `auto`.
+    pub fn color(&self, flag: Option<ColorChoice>, no_color: Option<&str>) -> ColorChoice {
+        if let Some(choice) = flag {
+            return choice;
+        }
+        if no_color.is_some_and(|v| !v.is_empty()) {
+            return ColorChoice::Never;
+        }
+        self.color.unwrap_or_default()
+    }
+
+    /// [`color`](Self::color), reading `NO_COLOR` from the environment.
+    pub fn color_from_env(&self, flag: Option<ColorChoice>) -> ColorChoice {
+        self.color(flag, std::env::var(NO_COLOR_ENV).ok().as_deref())
+    }
+
+    /// The Kotlin/Native toolchain mirror: `KONVOY_TOOLCHAIN_MIRROR` (`env`,
+    /// when non-empty), then `toolchain_mirror` from this file. `--mirror`
+    /// is applied on top by `konvoy toolchain install`.
+    pub fn toolchain_mirror(&self, env: Option<&str>) -> Option<String> {
+        env.filter(|v| !v.is_empty())
+            .map(str::to_owned)
+            .or_else(|| self.toolchain_mirror.clone())
+    }
+
+    /// The download idle timeout in seconds from `KONVOY_DOWNLOAD_IDLE_TIMEOUT`
+    /// (`env`), if it is a positive integer; otherwise the built-in default
+    /// applies.
+    pub fn download_idle_timeout(env: Option<&str>) -> Option<u64> {
+        env.and_then(|v| v.trim().parse().ok())
+            .filter(|secs| *secs > 0)
+    }
+
+    /// The network client for a command whose offline setting resolved to
+    /// `offline`, with this file's mirrors and retry count and the
+    /// environment's download idle timeout applied.
+    pub fn network_client(&self, offline: bool) -> NetworkClient {
+        let mut net = NetworkClient::new(offline);
+        if let Some(mirror) = &self.maven_mirror {
+            net = net.with_maven_mirror(mirror.as_str());
+        }
+        let env = std::env::var(TOOLCHAIN_MIRROR_ENV).ok();
+        if let Some(mirror) = self.toolchain_mirror(env.as_deref()) {
+            net = net.with_toolchain_mirror(mirror);
+        }
+        if let Some(retries) = self.download_retries {
+            net = net.with_download_retries(retries);
+        }
+        let idle_env = std::env::var(DOWNLOAD_IDLE_TIMEOUT_ENV).ok();
+        if let Some(secs) = Self::download_idle_timeout(idle_env.as_deref()) {
+            net = net.with_download_idle_timeout(secs);
+        }
+        net
+    }
+}
+
+/// Errors produced when reading `~/.konvoy/config.toml`.
+#[derive(Debug, thiserror::Error)]
+pub enum UserConfigError {
+    #[error("{0}")]
+    Util(#[from] konvoy_util::error::UtilError),
+    #[error("cannot read {path}: {source}")]
+    Read {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("invalid user config at {path}: {source}")]
+    Parse {
+        path: String,
+        source: toml::de::Error,
+    },
+    #[error(
+        "invalid user config at {path}: `{key}` must be an http:// or https:// URL, got `{url}`"
+    )]
+    InvalidMirror {
+        path: String,
+        key: &'static str,
+        url: String,
+    },
+}
+
+fn is_http_url(url: &str) -> bool {
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn load(content: &str) -> Result<UserConfig, UserConfigError> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(USER_CONFIG_FILE);
+        fs::write(&path, content).unwrap();
+        UserConfig::from_path(&path)
+    }
+
+    #[test]
+    fn missing_file_is_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = UserConfig::from_path(&dir.path().join(USER_CONFIG_FILE)).unwrap();
+        assert_eq!(config, UserConfig::default());
+    }
+
+    #[test]
+    fn parses_every_setting() {
+        let config = load(
+            "maven_mirror = \"https://maven.example.com/maven2\"\ntoolchain_mirror = \"https://kotlin.example.com\"\ndownload_retries = 5\noffline = true\ncolor = \"never\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.maven_mirror.as_deref(),
+            Some("https://maven.example.com/maven2")
+        );
+        assert_eq!(
+            config.toolchain_mirror.as_deref(),
+            Some("https://kotlin.example.com")
+        );
+        assert_eq!(config.download_retries, Some(5));
+        assert_eq!(config.offline, Some(true));
+        assert_eq!(config.color, Some(ColorChoice::Never));
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        let err = load("registy = \"https://x\"\n").unwrap_err().to_string();
+        assert!(err.contains("registy"), "error was: {err}");
+    }
+
+    #[test]
+    fn rejects_non_http_mirror() {
+        let err = load("maven_mirror = \"file:///srv/m2\"\n")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("maven_mirror"), "error was: {err}");
+        let err = load("toolchain_mirror = \"mirror.example.com\"\n")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("toolchain_mirror"), "error was: {err}");
+    }
+
+    #[test]
+    fn toolchain_mirror_precedence() {
+        let config = UserConfig {
+            toolchain_mirror: Some("https://config.example.com".to_owned()),
+            ..UserConfig::default()
+        };
+        assert_eq!(
+            config.toolchain_mirror(None).as_deref(),
+            Some("https://config.example.com")
+        );
+        assert_eq!(
+            config.toolchain_mirror(Some("")).as_deref(),
+            Some("https://config.example.com")
+        );
+        assert_eq!(
+            config
+                .toolchain_mirror(Some("https://env.example.com"))
+                .as_deref(),
+            Some("https://env.example.com")
+        );
+        assert_eq!(UserConfig::default().toolchain_mirror(None), None);
+    }
+
+    #[test]
+    fn offline_precedence() {
+        let online = UserConfig::default();
+        let offline = UserConfig {
+            offline: Some(true),
+            ..UserConfig::default()
+        };
+        assert!(!online.offline(false, None));
+        assert!(offline.offline(false, None));
+        assert!(!offline.offline(false, Some("0")));
+        assert!(online.offline(false, Some("true")));
+        assert!(offline.offline(false, Some("maybe")));
+        assert!(online.offline(true, Some("0")));
+    }
+
+    #[test]
+    fn color_precedence() {
+        let always = UserConfig {
+            color: Some(ColorChoice::Always),
+            ..UserConfig::default()
+        };
+        assert_eq!(UserConfig::default().color(None, None), ColorChoice::Auto);
+        assert_eq!(always.color(None, None), ColorChoice::Always);
+        assert_eq!(always.color(None, Some("")), ColorChoice::Always);
+        assert_eq!(always.color(None, Some("1")), ColorChoice::Never);
+        assert_eq!(
+            always.color(Some(ColorChoice::Always), Some("1")),
+            ColorChoice::Always
+        );
+    }
+
+    #[test]
+    fn download_idle_timeout_accepts_positive_seconds() {
+        assert_eq!(UserConfig::download_idle_timeout(Some("120")), Some(120));
+        assert_eq!(UserConfig::download_idle_timeout(Some(" 5 ")), Some(5));
+        assert_eq!(UserConfig::download_idle_timeout(Some("0")), None);
+        assert_eq!(UserConfig::download_idle_timeout(Some("soon")), None);
+        assert_eq!(UserConfig::download_idle_timeout(None), None);
+    }
+
+    #[test]
+    fn network_client_applies_retries() {
+        let config = UserConfig {
+            download_retries: Some(7),
+            ..UserConfig::default()
+        };
+        assert_eq!(config.network_client(false).download_retries(), 7);
+        assert_eq!(
+            UserConfig::default()
+                .network_client(true)
+                .download_retries(),
+            konvoy_util::net::DEFAULT_DOWNLOAD_RETRIES
+        );
+    }
+}
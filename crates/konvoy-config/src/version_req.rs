@@ -0,0 +1,416 @@
+//! Version requirements for Maven dependencies in `konvoy.toml`.
+//!
+//! A `[dependencies]` entry's `version` is either an exact Maven version
+//! (`"1.8.0"`, pinned as-is) or a range that `konvoy update` resolves to a
+//! concrete release by consulting `maven-metadata.xml`:
+//!
+//! - `^1.8` — compatible with 1.8: `>=1.8.0, <2.0.0` (`^0.6` is `>=0.6.0, <0.7.0`)
+//! - `~1.8.1` — patch updates only: `>=1.8.1, <1.9.0`
+//! - `>=1.8, <1.10` — explicit comparators (`>`, `>=`, `<`, `<=`, `=`), comma-separated
+//!
+//! Ranges never match pre-release versions (`1.9.0-RC`); pin one exactly to use it.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A parsed `version` requirement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionReq {
+    /// All comparators must hold for a version to match.
+    comparators: Vec<Comparator>,
+    /// Whether the requirement was written as a bare version (`"1.8.0"`).
+    exact: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comparator {
+    op: Op,
+    version: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+}
+
+impl VersionReq {
+    /// Parse a `version` value from `konvoy.toml`.
+    ///
+    /// # Errors
+    /// Returns a human-readable reason when the requirement is malformed.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err("version must not be empty".to_owned());
+        }
+        if !input.starts_with(['^', '~', '>', '<', '=']) {
+            let is_version_char = |c: char| c.is_ascii_alphanumeric() || "._-+".contains(c);
+            if !input.chars().all(is_version_char) {
+                return Err(format!(
+                    "`{input}` is neither a version nor a range — use e.g. `1.8.0`, `^1.8`, or `>=1.8, <2.0`"
+                ));
+            }
+            return Ok(Self {
+                comparators: vec![Comparator {
+                    op: Op::Eq,
+                    version: input.to_owned(),
+                }],
+                exact: true,
+            });
+        }
+
+        let mut comparators = Vec::new();
+        for part in input.split(',') {
+            comparators.extend(parse_comparator(part.trim())?);
+        }
+        Ok(Self {
+            comparators,
+            exact: false,
+        })
+    }
+
+    /// The pinned version when the requirement is a bare exact version.
+    #[must_use]
+    pub fn as_exact(&self) -> Option<&str> {
+        match self.comparators.as_slice() {
+            [only] if self.exact => Some(only.version.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Whether `version` satisfies every comparator. Ranges reject pre-release
+    /// versions; an exact requirement matches its own version only.
+    #[must_use]
+    pub fn matches(&self, version: &str) -> bool {
+        if self.exact {
+            return self.as_exact() == Some(version);
+        }
+        if version.contains('-') {
+            return false;
+        }
+        self.comparators.iter().all(|c| {
+            let ord = compare_maven_versions(version, &c.version);
+            match c.op {
+                Op::Eq => ord == Ordering::Equal,
+                Op::Greater => ord == Ordering::Greater,
+                Op::GreaterEq => ord != Ordering::Less,
+                Op::Less => ord == Ordering::Less,
+                Op::LessEq => ord != Ordering::Greater,
+            }
+        })
+    }
+
+    /// The highest of `candidates` that satisfies the requirement.
+    #[must_use]
+    pub fn best_match<'a>(&self, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+        candidates
+            .into_iter()
+            .filter(|v| self.matches(v))
+            .max_by(|a, b| compare_maven_versions(a, b))
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(version) = self.as_exact() {
+            return f.write_str(version);
+        }
+        let parts: Vec<String> = self
+            .comparators
+            .iter()
+            .map(|c| {
+                let op = match c.op {
+                    Op::Eq => "=",
+                    Op::Greater => ">",
+                    Op::GreaterEq => ">=",
+                    Op::Less => "<",
+                    Op::LessEq => "<=",
+                };
+                format!("{op}{}", c.version)
+            })
+            .collect();
+        f.write_str(&parts.join(", "))
+    }
+}
+
+/// Parse one comma-separated clause; `^` and `~` expand to a lower and an
+/// upper bound.
+fn parse_comparator(part: &str) -> Result<Vec<Comparator>, String> {
+    let (op, rest) = if let Some(rest) = part.strip_prefix('^') {
+        return caret_bounds(rest.trim());
+    } else if let Some(rest) = part.strip_prefix('~') {
+        return tilde_bounds(rest.trim());
+    } else if let Some(rest) = part.strip_prefix(">=") {
+        (Op::GreaterEq, rest)
+    } else if let Some(rest) = part.strip_prefix("<=") {
+        (Op::LessEq, rest)
+    } else if let Some(rest) = part.strip_prefix('>') {
+        (Op::Greater, rest)
+    } else if let Some(rest) = part.strip_prefix('<') {
+        (Op::Less, rest)
+    } else if let Some(rest) = part.strip_prefix('=') {
+        (Op::Eq, rest)
+    } else {
+        return Err(format!(
+            "`{part}` must start with one of `^`, `~`, `>=`, `>`, `<=`, `<`, `=`"
+        ));
+    };
+    let version = rest.trim();
+    if version.is_empty() || version.contains([' ', '*']) {
+        return Err(format!("`{part}` has no valid version after the operator"));
+    }
+    Ok(vec![Comparator {
+        op,
+        version: version.to_owned(),
+    }])
+}
+
+/// Split a dotted-numeric version (`1.8`, `1.8.0`) into its numeric segments.
+fn numeric_segments(version: &str) -> Result<Vec<u64>, String> {
+    if version.is_empty() {
+        return Err("a range needs a version after `^` or `~`".to_owned());
+    }
+    version
+        .split('.')
+        .map(|s| {
+            s.parse::<u64>().map_err(|_| {
+                format!("`{version}` must be a dotted numeric version (e.g. `1.8` or `1.8.0`) in a `^`/`~` range")
+            })
+        })
+        .collect()
+}
+
+fn join_segments(segments: &[u64]) -> String {
+    segments
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn bounds(lower: &str, upper: &[u64]) -> Vec<Comparator> {
+    vec![
+        Comparator {
+            op: Op::GreaterEq,
+            version: lower.to_owned(),
+        },
+        Comparator {
+            op: Op::Less,
+            version: join_segments(upper),
+        },
+    ]
+}
+
+/// `^X.Y.Z`: bump the leftmost non-zero segment (`^1.8` → `<2`, `^0.6` → `<0.7`).
+fn caret_bounds(version: &str) -> Result<Vec<Comparator>, String> {
+    let segments = numeric_segments(version)?;
+    let pivot = segments
+        .iter()
+        .position(|&s| s != 0)
+        .unwrap_or(segments.len().saturating_sub(1));
+    Ok(bounds(version, &bump(&segments, pivot)))
+}
+
+/// `~X.Y.Z`: allow patch updates (`~1.8.1` → `<1.9`); `~1` allows minor ones.
+fn tilde_bounds(version: &str) -> Result<Vec<Comparator>, String> {
+    let segments = numeric_segments(version)?;
+    let pivot = if segments.len() > 1 { 1 } else { 0 };
+    Ok(bounds(version, &bump(&segments, pivot)))
+}
+
+/// Keep `segments[..=pivot]`, incrementing the last kept one.
+fn bump(segments: &[u64], pivot: usize) -> Vec<u64> {
+    let mut upper: Vec<u64> = segments.iter().take(pivot + 1).copied().collect();
+    if let Some(last) = upper.last_mut() {
+        *last = last.saturating_add(1);
+    }
+    upper
+}
+
+/// Order two Maven version strings. Compares the dotted-numeric core (`1.10.0` > `1.9.0`, and a
+/// missing trailing segment counts as `0` so `1.0` == `1.0.0`); a version with a
+/// pre-release suffix sorts BELOW the same core (`1.0.0-beta` < `1.0.0`), and two
+/// suffixes compare lexicographically. Not a full semver implementation — it
+/// only has to tolerate arbitrary Maven version strings, pick the higher one for
+/// a conflict hint, and order the candidates a [`VersionReq`] is resolved from.
+#[must_use]
+pub fn compare_maven_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    // Split a version into its numeric core (segments before the first `-`) and
+    // an optional pre-release suffix.
+    fn split_core(v: &str) -> (&str, Option<&str>) {
+        match v.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (v, None),
+        }
+    }
+
+    let (a_core, a_pre) = split_core(a);
+    let (b_core, b_pre) = split_core(b);
+
+    let mut a_parts = a_core.split('.');
+    let mut b_parts = b_core.split('.');
+    loop {
+        match (a_parts.next(), b_parts.next()) {
+            (None, None) => break,
+            // Pad a missing segment with 0 so `1.0` and `1.0.0` compare equal,
+            // and `1.0.1` > `1.0`.
+            (a_seg, b_seg) => {
+                let x = a_seg.unwrap_or("0");
+                let y = b_seg.unwrap_or("0");
+                let ord = match (x.parse::<u64>(), y.parse::<u64>()) {
+                    (Ok(nx), Ok(ny)) => nx.cmp(&ny),
+                    _ => x.cmp(y),
+                };
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+        }
+    }
+
+    // Equal numeric core: a pre-release suffix is LESS than no suffix; two
+    // suffixes compare lexicographically.
+    match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(x), Some(y)) => x.cmp(y),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bare_version_is_exact() {
+        let req = VersionReq::parse("1.8.0").unwrap();
+        assert_eq!(req.as_exact(), Some("1.8.0"));
+        assert!(req.matches("1.8.0"));
+        assert!(!req.matches("1.8.1"));
+        // An exact pin may name a pre-release.
+        assert!(VersionReq::parse("1.9.0-RC").unwrap().matches("1.9.0-RC"));
+    }
+
+    #[test]
+    fn caret_allows_compatible_updates() {
+        let req = VersionReq::parse("^1.8").unwrap();
+        assert_eq!(req.as_exact(), None);
+        assert!(req.matches("1.8.0"));
+        assert!(req.matches("1.10.2"));
+        assert!(!req.matches("1.7.3"));
+        assert!(!req.matches("2.0.0"));
+        assert_eq!(req.to_string(), ">=1.8, <2");
+
+        let zero = VersionReq::parse("^0.6").unwrap();
+        assert!(zero.matches("0.6.1"));
+        assert!(!zero.matches("0.7.0"));
+    }
+
+    #[test]
+    fn tilde_allows_patch_updates() {
+        let req = VersionReq::parse("~1.8.1").unwrap();
+        assert!(req.matches("1.8.1"));
+        assert!(req.matches("1.8.9"));
+        assert!(!req.matches("1.8.0"));
+        assert!(!req.matches("1.9.0"));
+    }
+
+    #[test]
+    fn comparators_combine() {
+        let req = VersionReq::parse(">=1.8, <1.10").unwrap();
+        assert!(req.matches("1.9.3"));
+        assert!(!req.matches("1.10.0"));
+        assert!(!req.matches("1.7.0"));
+    }
+
+    #[test]
+    fn ranges_skip_prereleases() {
+        let req = VersionReq::parse("^1.8").unwrap();
+        assert!(!req.matches("1.9.0-RC"));
+        assert_eq!(
+            req.best_match(["1.8.0", "1.9.0-RC", "1.8.1", "2.0.0", "1.10.0"]),
+            Some("1.10.0")
+        );
+        assert_eq!(req.best_match(["2.0.0", "1.7.0"]), None);
+    }
+
+    #[test]
+    fn malformed_requirements_are_rejected() {
+        for bad in ["", "^", "^1.x", "1.8, 1.9", "1.*", ">= ", "!1.0"] {
+            assert!(VersionReq::parse(bad).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn compare_maven_versions_is_numeric_not_lexicographic() {
+        use std::cmp::Ordering;
+        // The bug this guards: a string compare makes "1.10.0" < "1.9.0".
+        assert_eq!(compare_maven_versions("1.10.0", "1.9.0"), Ordering::Greater);
+        assert_eq!(compare_maven_versions("1.9.0", "1.10.0"), Ordering::Less);
+        assert_eq!(compare_maven_versions("2.0.0", "2.0.0"), Ordering::Equal);
+        assert_eq!(compare_maven_versions("1.0.1", "1.0"), Ordering::Greater);
+        assert_eq!(compare_maven_versions("0.6.1", "0.6.0"), Ordering::Greater);
+    }
+
+    #[test]
+    fn compare_maven_versions_handles_segments_and_prerelease() {
+        use std::cmp::Ordering;
+        // Missing trailing segments count as 0.
+        assert_eq!(compare_maven_versions("1.0", "1.0.0"), Ordering::Equal);
+        assert_eq!(compare_maven_versions("1.0.0", "1.0"), Ordering::Equal);
+        // A pre-release suffix sorts BELOW the same numeric core (the bug the
+        // old lexicographic fallback got backwards: "1.0.0-beta" > "1.0.0").
+        assert_eq!(
+            compare_maven_versions("1.0.0-beta", "1.0.0"),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_maven_versions("1.0.0", "1.0.0-RC1"),
+            Ordering::Greater
+        );
+        // Two suffixes compare lexicographically; numeric core still dominates.
+        assert_eq!(
+            compare_maven_versions("1.0.0-rc1", "1.0.0-rc2"),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_maven_versions("2.0.0-beta", "1.0.0"),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn compare_maven_versions_reflexive_and_antisymmetric() {
+        use std::cmp::Ordering;
+        for v in ["1.0.0", "1.2", "0.6.1", "1.0.0-beta", "2.0"] {
+            assert_eq!(compare_maven_versions(v, v), Ordering::Equal);
+        }
+        // a < b  <=>  b > a, across mixed numeric/suffix cases.
+        for (a, b) in [
+            ("1.9.0", "1.10.0"),
+            ("1.0.0-beta", "1.0.0"),
+            ("0.6.0", "0.6.1"),
+        ] {
+            assert_eq!(compare_maven_versions(a, b), Ordering::Less);
+            assert_eq!(compare_maven_versions(b, a), Ordering::Greater);
+        }
+    }
+
+    #[test]
+    fn compare_maven_versions_non_numeric_segment_falls_back_lexically() {
+        use std::cmp::Ordering;
+        // A non-numeric core segment can't be parsed → byte comparison for that
+        // segment (best effort; the function is hint-only).
+        assert_eq!(compare_maven_versions("1.x.0", "1.y.0"), Ordering::Less);
+        // Trailing zeros don't change ordering.
+        assert_eq!(compare_maven_versions("1", "1.0.0.0"), Ordering::Equal);
+    }
+}
@@ -41,7 +41,9 @@ impl Drop for TempDirGuard {
 ///
 /// `target` and `profile` are serialized as their canonical wire strings
 /// (e.g. `"linux_x64"`, `"debug"`) to preserve on-disk format compatibility
-/// with existing `metadata.toml` files.
+/// with existing `metadata.toml` files. Fields are written in declaration
+/// order, so with `SOURCE_DATE_EPOCH` pinning `built_at` the file is
+/// byte-identical across machines.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BuildMetadata {
     /// Kotlin/Native target.
@@ -54,10 +56,48 @@ pub struct BuildMetadata {
     pub built_at: String,
 }
 
+/// Set to `1` to store new cache entries zstd-compressed, or `0` to store
+/// them uncompressed, whatever `[build] compress_cache` says.
+pub const COMPRESS_CACHE_ENV: &str = "KONVOY_COMPRESS_CACHE";
+
+/// Whether to store new cache entries compressed: as `KONVOY_COMPRESS_CACHE`
+/// says when it is set, else `configured` (the root manifest's
+/// `[build] compress_cache`).
+pub(crate) fn compress_cache(configured: bool) -> bool {
+    compress_cache_with(
+        std::env::var(COMPRESS_CACHE_ENV).ok().as_deref(),
+        configured,
+    )
+}
+
+/// [`compress_cache`] with the environment value passed in: `0` turns
+/// compression off, any other non-empty value turns it on.
+fn compress_cache_with(env: Option<&str>, configured: bool) -> bool {
+    match env.map(str::trim) {
+        Some("0") => false,
+        Some(value) if !value.is_empty() => true,
+        _ => configured,
+    }
+}
+
+/// `metadata.toml` as written to a cache entry: the build metadata plus how
+/// the entry's files are stored. Entries written before compression existed
+/// have no `compressed` key and read back as uncompressed.
+#[derive(Debug, Serialize, Deserialize)]
+struct EntryMetadata {
+    #[serde(flatten)]
+    build: BuildMetadata,
+    /// The entry's files are zstd frames, decompressed on materialize.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    compressed: bool,
+}
+
 /// Content-addressed store for compiled artifacts under `.konvoy/cache/`.
 #[derive(Debug)]
 pub struct ArtifactStore {
     cache_root: PathBuf,
+    /// Whether [`store_all`](Self::store_all) compresses new entries.
+    compress: bool,
 }
 
 impl ArtifactStore {
@@ -69,19 +109,110 @@ impl ArtifactStore {
     pub fn new(project_root: &Path) -> Self {
         Self {
             cache_root: resolve_cache_root(project_root),
+            compress: false,
         }
     }
 
+    /// Create a store at `<base_dir>/cache/`, for builds given an explicit
+    /// `--target-dir`. The worktree sharing of [`new`](Self::new) does not
+    /// apply: the caller chose the location.
+    pub fn in_dir(base_dir: &Path) -> Self {
+        Self {
+            cache_root: base_dir.join("cache"),
+            compress: false,
+        }
+    }
+
+    /// Store new entries zstd-compressed when `compress` is set. Entries are
+    /// read the way their metadata says they were written, so a cache may
+    /// hold both forms; the cache key is the same either way.
+    #[must_use]
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// The store's directory: `.konvoy/cache/` of this project, or of the main
+    /// worktree when the cache is shared, or `cache/` under `--target-dir`.
+    pub fn root_dir(&self) -> &Path {
+        &self.cache_root
+    }
+
     /// Return the cache directory path for a given key.
     pub fn cache_path(&self, key: &CacheKey) -> PathBuf {
         self.cache_root.join(key.as_hex())
     }
 
+    /// Path of the file/directory hash cache (`hashes.json`) kept alongside
+    /// the cached artifacts.
+    pub fn hash_cache_path(&self) -> PathBuf {
+        self.cache_root.join("hashes.json")
+    }
+
+    /// Path of the cached `konvoy lint` result for a lint cache key.
+    pub fn lint_result_path(&self, key: &str) -> PathBuf {
+        self.cache_root.join("lint").join(format!("{key}.json"))
+    }
+
     /// Check whether a cache entry exists for the given key.
     pub fn has(&self, key: &CacheKey) -> bool {
         self.cache_path(key).is_dir()
     }
 
+    /// The first of `artifact_names` missing from the entry for `key`, if any.
+    /// An empty file counts as missing: no artifact konanc writes is empty.
+    pub fn missing_artifact<'n>(
+        &self,
+        key: &CacheKey,
+        artifact_names: &[&'n str],
+    ) -> Option<&'n str> {
+        let entry_dir = self.cache_path(key);
+        artifact_names.iter().copied().find(|name| {
+            std::fs::metadata(entry_dir.join(name)).map_or(true, |meta| meta.len() == 0)
+        })
+    }
+
+    /// SHA-256 of `artifact_name` in the entry for `key` as it materializes:
+    /// a compressed artifact is hashed decompressed, so the hash feeds a
+    /// dependent's cache key the same whichever form the entry has.
+    ///
+    /// # Errors
+    /// Returns an error if the artifact or the entry's metadata cannot be read.
+    pub fn artifact_sha256(
+        &self,
+        key: &CacheKey,
+        artifact_name: &str,
+    ) -> Result<String, EngineError> {
+        let entry_dir = self.cache_path(key);
+        let cached_artifact = entry_dir.join(artifact_name);
+        if !entry_is_compressed(&entry_dir)? {
+            return Ok(konvoy_util::hash::sha256_file(&cached_artifact)?);
+        }
+        let input = std::fs::File::open(&cached_artifact).map_err(|source| {
+            konvoy_util::error::UtilError::Io {
+                path: cached_artifact.display().to_string(),
+                source,
+            }
+        })?;
+        let decoder = zstd::stream::read::Decoder::new(input).map_err(|source| {
+            konvoy_util::error::UtilError::Io {
+                path: cached_artifact.display().to_string(),
+                source,
+            }
+        })?;
+        Ok(konvoy_util::hash::sha256_reader(decoder, &cached_artifact)?)
+    }
+
+    /// Delete the entry for `key`, so a fresh [`store`](Self::store) can
+    /// replace it. Does nothing if there is no entry.
+    ///
+    /// # Errors
+    /// Returns an error if the entry exists but cannot be removed.
+    pub fn remove(&self, key: &CacheKey) -> Result<(), EngineError> {
+        konvoy_util::fs::remove_dir_all_if_exists(&self.cache_path(key))?;
+        Ok(())
+    }
+
     /// Store an artifact and its metadata in the cache atomically.
     ///
     /// Writes to a temporary directory first, then atomically renames it to the
@@ -100,6 +231,21 @@ impl ArtifactStore {
         key: &CacheKey,
         artifact: &Path,
         metadata: &BuildMetadata,
+    ) -> Result<(), EngineError> {
+        self.store_all(key, &[artifact], metadata)
+    }
+
+    /// Like [`store`](Self::store), but the entry holds several files, e.g. a
+    /// C library and its header; each is materialized by its file name.
+    ///
+    /// # Errors
+    /// Returns an error if any artifact cannot be copied or the metadata cannot
+    /// be written.
+    pub fn store_all(
+        &self,
+        key: &CacheKey,
+        artifacts: &[&Path],
+        metadata: &BuildMetadata,
     ) -> Result<(), EngineError> {
         let entry_dir = self.cache_path(key);
 
@@ -116,27 +262,38 @@ impl ArtifactStore {
         let tmp_dir = make_temp_dir(&self.cache_root)?;
         let mut guard = TempDirGuard::new(tmp_dir.clone());
 
-        // Copy the artifact into the temp directory.
-        let Some(file_name) = artifact.file_name() else {
-            return Err(konvoy_util::error::UtilError::Io {
-                path: artifact.display().to_string(),
-                source: std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    "artifact path has no file name",
-                ),
+        // Copy the artifacts into the temp directory.
+        for artifact in artifacts {
+            let Some(file_name) = artifact.file_name() else {
+                return Err(konvoy_util::error::UtilError::Io {
+                    path: artifact.display().to_string(),
+                    source: std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "artifact path has no file name",
+                    ),
+                }
+                .into());
+            };
+            let staged_artifact = tmp_dir.join(file_name);
+            if self.compress {
+                compress_file(artifact, &staged_artifact)?;
+            } else {
+                konvoy_util::fs::copy_file(artifact, &staged_artifact)?;
             }
-            .into());
-        };
-        let staged_artifact = tmp_dir.join(file_name);
-        konvoy_util::fs::copy_file(artifact, &staged_artifact)?;
+        }
 
         // Write metadata alongside the artifact.
         let metadata_path = tmp_dir.join("metadata.toml");
-        let metadata_toml =
-            toml::to_string_pretty(metadata).map_err(|source| EngineError::TomlSerialize {
+        let entry_metadata = EntryMetadata {
+            build: metadata.clone(),
+            compressed: self.compress,
+        };
+        let metadata_toml = toml::to_string_pretty(&entry_metadata).map_err(|source| {
+            EngineError::TomlSerialize {
                 what: "metadata.toml",
                 source,
-            })?;
+            }
+        })?;
         konvoy_util::fs::write_file(&metadata_path, metadata_toml)?;
 
         // Atomically move the temp directory to the final cache entry path.
@@ -167,7 +324,8 @@ impl ArtifactStore {
     /// Materialize a cached artifact to the given destination path.
     ///
     /// Prefers hard linking for disk efficiency, falls back to copy if linking
-    /// fails (e.g. cross-filesystem).
+    /// fails (e.g. cross-filesystem). A compressed entry is decompressed into
+    /// a new file instead.
     ///
     /// # Errors
     /// Returns an error if the cache entry does not exist or the artifact
@@ -192,11 +350,73 @@ impl ArtifactStore {
             .into());
         }
 
-        konvoy_util::fs::materialize(&cached_artifact, dest)?;
+        if entry_is_compressed(&entry_dir)? {
+            decompress_file(&cached_artifact, dest)?;
+        } else {
+            konvoy_util::fs::materialize(&cached_artifact, dest)?;
+        }
         Ok(())
     }
 }
 
+/// Whether the entry in `entry_dir` was stored compressed, per its
+/// `metadata.toml`. An entry without metadata is uncompressed.
+fn entry_is_compressed(entry_dir: &Path) -> Result<bool, EngineError> {
+    let path = entry_dir.join("metadata.toml");
+    if !path.exists() {
+        return Ok(false);
+    }
+    let content = konvoy_util::fs::read_file(&path)?;
+    let metadata: EntryMetadata =
+        toml::from_str(&String::from_utf8_lossy(&content)).map_err(|e| {
+            konvoy_util::error::UtilError::Io {
+                path: path.display().to_string(),
+                source: std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()),
+            }
+        })?;
+    Ok(metadata.compressed)
+}
+
+/// Write `src` to `dest` as a zstd frame at the default level, keeping its
+/// permissions so a compressed executable is executable when restored.
+fn compress_file(src: &Path, dest: &Path) -> Result<(), EngineError> {
+    let io_err = |path: &Path| {
+        let path = path.display().to_string();
+        move |source| konvoy_util::error::UtilError::Io { path, source }
+    };
+    let input = std::fs::File::open(src).map_err(io_err(src))?;
+    let output = std::fs::File::create(dest).map_err(io_err(dest))?;
+    zstd::stream::copy_encode(input, &output, 0).map_err(io_err(dest))?;
+    let permissions = std::fs::metadata(src).map_err(io_err(src))?.permissions();
+    std::fs::set_permissions(dest, permissions).map_err(io_err(dest))?;
+    Ok(())
+}
+
+/// Decompress the zstd frame `src` into `dest`, with `src`'s permissions.
+///
+/// The output is written to a temporary file next to `dest` and renamed into
+/// place, so a concurrent materialize never sees a partial file.
+fn decompress_file(src: &Path, dest: &Path) -> Result<(), EngineError> {
+    let io_err = |path: &Path| {
+        let path = path.display().to_string();
+        move |source| konvoy_util::error::UtilError::Io { path, source }
+    };
+    let parent = dest.parent().unwrap_or_else(|| Path::new("."));
+    konvoy_util::fs::ensure_dir(parent)?;
+    let input = std::fs::File::open(src).map_err(io_err(src))?;
+    let staged = tempfile::NamedTempFile::new_in(parent).map_err(io_err(parent))?;
+    zstd::stream::copy_decode(input, staged.as_file()).map_err(io_err(src))?;
+    let permissions = std::fs::metadata(src).map_err(io_err(src))?.permissions();
+    std::fs::set_permissions(staged.path(), permissions).map_err(io_err(dest))?;
+    staged
+        .persist(dest)
+        .map_err(|e| konvoy_util::error::UtilError::Io {
+            path: dest.display().to_string(),
+            source: e.error,
+        })?;
+    Ok(())
+}
+
 /// Check whether any component of `path` is a symlink.
 ///
 /// Walks from the root toward the leaf, checking each prefix with
@@ -362,12 +582,15 @@ mod tests {
             konanc_fingerprint: "abc123".to_owned(),
             target: Target::LinuxX64,
             profile: Profile::Debug,
-            source_dir: tmp.path().to_path_buf(),
+            source_dirs: vec![tmp.path().to_path_buf()],
             source_glob: "**/*.kt".to_owned(),
+            ignore: konvoy_util::ignore::IgnoreRules::default(),
             os: "linux".to_owned(),
             arch: "x86_64".to_owned(),
             dependency_hashes: Vec::new(),
             codegen_hashes: Vec::new(),
+            features: Vec::new(),
+            deny_warnings: false,
         };
         CacheKey::compute(&inputs).unwrap()
     }
@@ -439,6 +662,20 @@ built_at = "1s-since-epoch"
         assert_eq!(back.profile, Profile::Debug);
     }
 
+    #[test]
+    fn metadata_serializes_in_stable_field_order() {
+        let meta = BuildMetadata {
+            target: Target::LinuxX64,
+            profile: Profile::Debug,
+            konanc_version: "2.1.0".to_owned(),
+            built_at: "1700000000s-since-epoch".to_owned(),
+        };
+        assert_eq!(
+            toml::to_string_pretty(&meta).unwrap(),
+            "target = \"linux_x64\"\nprofile = \"debug\"\nkonanc_version = \"2.1.0\"\nbuilt_at = \"1700000000s-since-epoch\"\n"
+        );
+    }
+
     #[test]
     fn store_is_immutable() {
         let tmp = tempfile::tempdir().unwrap();
@@ -504,6 +741,160 @@ built_at = "1s-since-epoch"
         assert_eq!(fs::read(&dest).unwrap(), b"binary content");
     }
 
+    #[test]
+    fn compressed_entry_materializes_the_original_bytes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = ArtifactStore::new(tmp.path()).with_compression(true);
+        let key = test_key();
+
+        let content = b"klib klib klib ".repeat(1000);
+        let artifact = tmp.path().join("my-app");
+        fs::write(&artifact, &content).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&artifact, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        store.store(&key, &artifact, &test_metadata()).unwrap();
+
+        let entry = store.cache_path(&key);
+        let stored = fs::read(entry.join("my-app")).unwrap();
+        assert!(
+            stored.len() < content.len() / 10,
+            "stored {} bytes",
+            stored.len()
+        );
+        let metadata = fs::read_to_string(entry.join("metadata.toml")).unwrap();
+        assert!(metadata.contains("compressed = true"), "{metadata}");
+
+        let dest = tmp.path().join("output").join("my-app");
+        store.materialize(&key, "my-app", &dest).unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), content);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&dest).unwrap().permissions().mode();
+            assert_eq!(
+                mode & 0o111,
+                0o111,
+                "materialized binary lost its exec bits"
+            );
+        }
+        assert_eq!(
+            store.artifact_sha256(&key, "my-app").unwrap(),
+            konvoy_util::hash::sha256_bytes(&content)
+        );
+    }
+
+    #[test]
+    fn either_store_reads_entries_of_the_other_form() {
+        let tmp = tempfile::tempdir().unwrap();
+        let key = test_key();
+        let artifact = tmp.path().join("my-app");
+        fs::write(&artifact, b"binary content").unwrap();
+
+        // Written uncompressed, read by a store that compresses new entries.
+        ArtifactStore::new(tmp.path())
+            .store(&key, &artifact, &test_metadata())
+            .unwrap();
+        let metadata = fs::read_to_string(
+            ArtifactStore::new(tmp.path())
+                .cache_path(&key)
+                .join("metadata.toml"),
+        )
+        .unwrap();
+        assert!(!metadata.contains("compressed"), "{metadata}");
+        let compressing = ArtifactStore::new(tmp.path()).with_compression(true);
+        let dest = tmp.path().join("a").join("my-app");
+        compressing.materialize(&key, "my-app", &dest).unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), b"binary content");
+
+        // Written compressed, read by a store that does not compress.
+        compressing.remove(&key).unwrap();
+        compressing
+            .store(&key, &artifact, &test_metadata())
+            .unwrap();
+        let plain = ArtifactStore::new(tmp.path());
+        let dest = tmp.path().join("b").join("my-app");
+        plain.materialize(&key, "my-app", &dest).unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), b"binary content");
+        assert_eq!(
+            plain.artifact_sha256(&key, "my-app").unwrap(),
+            konvoy_util::hash::sha256_bytes(b"binary content")
+        );
+    }
+
+    #[test]
+    fn compress_cache_env_overrides_the_manifest() {
+        assert!(!compress_cache_with(None, false));
+        assert!(compress_cache_with(None, true));
+        assert!(compress_cache_with(Some("1"), false));
+        assert!(!compress_cache_with(Some("0"), true));
+        assert!(compress_cache_with(Some(""), true));
+    }
+
+    #[test]
+    fn store_all_keeps_every_file_of_the_entry() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = ArtifactStore::new(tmp.path());
+        let key = test_key();
+
+        let library = tmp.path().join("libffi.so");
+        let header = tmp.path().join("libffi_api.h");
+        fs::write(&library, b"library").unwrap();
+        fs::write(&header, b"header").unwrap();
+        store
+            .store_all(&key, &[&library, &header], &test_metadata())
+            .unwrap();
+
+        let out = tmp.path().join("output");
+        store
+            .materialize(&key, "libffi.so", &out.join("libffi.so"))
+            .unwrap();
+        store
+            .materialize(&key, "libffi_api.h", &out.join("libffi_api.h"))
+            .unwrap();
+        assert_eq!(fs::read(out.join("libffi.so")).unwrap(), b"library");
+        assert_eq!(fs::read(out.join("libffi_api.h")).unwrap(), b"header");
+    }
+
+    #[test]
+    fn missing_artifact_finds_absent_and_empty_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = ArtifactStore::new(tmp.path());
+        let key = test_key();
+        let staging = tmp.path().join("staging");
+        fs::create_dir_all(&staging).unwrap();
+        fs::write(staging.join("libffi.a"), "library").unwrap();
+        fs::write(staging.join("libffi_api.h"), "header").unwrap();
+        store
+            .store_all(
+                &key,
+                &[&staging.join("libffi.a"), &staging.join("libffi_api.h")],
+                &test_metadata(),
+            )
+            .unwrap();
+        assert_eq!(
+            store.missing_artifact(&key, &["libffi.a", "libffi_api.h"]),
+            None
+        );
+
+        fs::write(store.cache_path(&key).join("libffi_api.h"), "").unwrap();
+        assert_eq!(
+            store.missing_artifact(&key, &["libffi.a", "libffi_api.h"]),
+            Some("libffi_api.h")
+        );
+        fs::remove_file(store.cache_path(&key).join("libffi.a")).unwrap();
+        assert_eq!(
+            store.missing_artifact(&key, &["libffi.a"]),
+            Some("libffi.a")
+        );
+
+        store.remove(&key).unwrap();
+        assert!(!store.has(&key));
+        store.remove(&key).unwrap();
+    }
+
     #[test]
     fn materialize_missing_entry_errors() {
         let tmp = tempfile::tempdir().unwrap();
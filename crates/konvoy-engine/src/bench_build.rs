@@ -0,0 +1,245 @@
+//! Benchmark build orchestration: compile the sources under `src/bench/`
+//! together with the project's main sources into a benchmark binary.
+//!
+//! The benchmark binary's `main` lives in `package bench`, so it never clashes
+//! with the `main` of a `kind = "bin"` package being benchmarked.
+
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::artifact::BuildMetadata;
+use crate::build::{resolve_build_context, BuildOptions, BuildOutcome};
+use crate::cache::{CacheInputs, CacheKey};
+use crate::common::now_epoch_secs;
+use crate::error::EngineError;
+use konvoy_konanc::invoke::{KonancCommand, ProduceKind};
+use konvoy_util::ignore::IgnoreRules;
+
+/// Entry point of every benchmark binary.
+const BENCH_ENTRY: &str = "bench.main";
+
+/// Result of a successful benchmark build.
+#[derive(Debug)]
+pub struct BenchBuildResult {
+    /// Whether the build used cache or compiled fresh.
+    pub outcome: BuildOutcome,
+    /// Path to the benchmark binary.
+    pub output_path: PathBuf,
+    /// How long the build took.
+    pub compile_duration: std::time::Duration,
+}
+
+/// Build benchmark sources into a runnable program.
+///
+/// Collects project sources (`src/**/*.kt` excluding `src/test/` and
+/// `src/bench/`) and benchmark sources (`src/bench/**/*.kt`), then invokes
+/// konanc with `-entry bench.main`. The profile comes from `options` — the
+/// CLI defaults it to release, since unoptimized timings are meaningless.
+///
+/// Uses `resolve_build_context()` for the shared pipeline (steps 1–7a), like
+/// [`crate::test_build::build_tests`].
+///
+/// # Errors
+/// Returns an error if benchmark sources are missing, compilation fails, or
+/// any filesystem operation fails.
+pub fn build_benches(
+    project_root: &Path,
+    options: &BuildOptions,
+    resolver: crate::common::ArtifactResolver<'_>,
+) -> Result<BenchBuildResult, EngineError> {
+    let start = Instant::now();
+    let ctx = resolve_build_context(project_root, options, resolver)?;
+
+    let mut source_dirs = crate::sources::source_dirs(project_root, &ctx.manifest);
+    source_dirs.extend(crate::features::feature_source_dirs(
+        project_root,
+        &ctx.features,
+    ));
+    let bench_dir = crate::sources::bench_dir(project_root);
+    let ignore = IgnoreRules::load(project_root)?;
+
+    if !bench_dir.is_dir() {
+        return Err(EngineError::NoBenchSources {
+            dir: bench_dir.display().to_string(),
+        });
+    }
+
+    let bench_sources = crate::sources::collect_dir_sources(&bench_dir, &ignore)?;
+    if bench_sources.is_empty() {
+        return Err(EngineError::NoBenchSources {
+            dir: bench_dir.display().to_string(),
+        });
+    }
+
+    let mut sources = crate::sources::collect_main_sources(
+        &source_dirs,
+        &crate::sources::auxiliary_dirs(project_root, &ctx.manifest),
+        &ignore,
+    )?;
+    sources.extend(bench_sources);
+
+    let generators = crate::codegen::active_generators(&ctx.manifest.codegen);
+    let codegen_hashes = crate::codegen::compute_codegen_hashes(project_root, &generators)?;
+
+    // Tag the lockfile content so the benchmark binary never shares a cache
+    // key with a regular or test build of the same tree.
+    let manifest_content = crate::build::manifest_key_content(&ctx.manifest)?;
+    let cache_inputs = CacheInputs {
+        manifest_content,
+        lockfile_content: format!("{}\n# konvoy-bench-build\n", ctx.lockfile_content),
+        konanc_version: ctx.konanc.version.clone(),
+        konanc_fingerprint: ctx.konanc.fingerprint.clone(),
+        target: ctx.target,
+        profile: ctx.profile,
+        source_dirs: crate::sources::cache_dirs_with(source_dirs, bench_dir),
+        source_glob: crate::sources::SOURCE_GLOB.to_owned(),
+        ignore,
+        os: std::env::consts::OS.to_owned(),
+        arch: std::env::consts::ARCH.to_owned(),
+        dependency_hashes: crate::build::library_hashes_of(&ctx.library_inputs)?,
+        codegen_hashes,
+        features: ctx.features.clone(),
+        deny_warnings: ctx.deny_warnings,
+    };
+    let cache_key = CacheKey::compute(&cache_inputs)?;
+    konvoy_util::hash_cache::persist();
+
+    let output_name = format!("bench-{}", ctx.manifest.package.artifact_stem());
+    let output_path = crate::build::build_output_path(
+        project_root,
+        options.target_dir.as_deref(),
+        &output_name,
+        &ctx.target,
+        ctx.profile,
+    );
+
+    if crate::build::reusable_cache_entry(
+        &ctx.store,
+        &cache_key,
+        &[&output_name],
+        &output_name,
+        options,
+    )? {
+        if !options.quiet {
+            options.progress.on_cache_hit(&output_name);
+        }
+        ctx.store
+            .materialize(&cache_key, &output_name, &output_path)?;
+        return Ok(BenchBuildResult {
+            outcome: BuildOutcome::Cached,
+            output_path,
+            compile_duration: start.elapsed(),
+        });
+    }
+
+    if !generators.is_empty() {
+        let generated = crate::codegen::run_codegen(
+            project_root,
+            &generators,
+            ctx.jre_home.as_deref(),
+            options.verbose,
+        )?;
+        sources.extend(generated);
+    }
+
+    if !options.quiet {
+        options
+            .progress
+            .on_compile_start(&output_name, &output_path);
+    }
+
+    if let Some(parent) = output_path.parent() {
+        konvoy_util::fs::ensure_dir(parent)?;
+    }
+
+    let library_paths = crate::build::library_paths_of(&ctx.library_inputs);
+
+    let mut cmd = KonancCommand::new()
+        .sources(&sources)
+        .output(&output_path)
+        .target(ctx.target.to_konanc_arg())
+        .release(options.is_release())
+        .produce(ProduceKind::Program)
+        .entry(BENCH_ENTRY)
+        .language_version(ctx.manifest.toolchain.language_version.as_deref())
+        .api_version(ctx.manifest.toolchain.api_version.as_deref())
+        .libraries(&library_paths)
+        .plugins(&ctx.plugin_jars)
+        .plugin_options(&ctx.plugin_options)
+        .stream_output(options.verbose)
+        .timeout(ctx.compile_timeout);
+
+    if let Some(jh) = ctx.jre_home.as_deref() {
+        cmd = cmd.java_home(jh);
+    }
+
+    let result = cmd.execute(&ctx.konanc)?;
+
+    crate::diagnostics::print_diagnostics(&result, options);
+    crate::build::check_compilation(&result, ctx.deny_warnings)?;
+
+    crate::build::normalize_konanc_output(&output_path)?;
+
+    let metadata = BuildMetadata {
+        target: ctx.target,
+        profile: ctx.profile,
+        konanc_version: ctx.konanc.version,
+        built_at: now_epoch_secs(),
+    };
+    ctx.store.store(&cache_key, &output_path, &metadata)?;
+
+    Ok(BenchBuildResult {
+        outcome: BuildOutcome::Fresh,
+        output_path,
+        compile_duration: start.elapsed(),
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn options() -> BuildOptions {
+        BuildOptions {
+            target: None,
+            profile: konvoy_config::Profile::Release,
+            verbose: false,
+            quiet: true,
+            force: false,
+            timeout: None,
+            dedupe_diagnostics: false,
+            color: konvoy_util::color::ColorChoice::Never,
+            timings: false,
+            features: Vec::new(),
+            no_default_features: false,
+            deny_warnings: false,
+            emit_command: None,
+            out_dir: None,
+            target_dir: None,
+            keep_going: false,
+            progress: std::sync::Arc::new(crate::progress::StderrProgress),
+        }
+    }
+
+    #[test]
+    fn build_benches_fails_without_manifest() {
+        let tmp = tempfile::tempdir().unwrap();
+        let result = build_benches(
+            tmp.path(),
+            &options(),
+            crate::common::test_resolver(false, false),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn no_bench_sources_error_explains_layout() {
+        let err = EngineError::NoBenchSources {
+            dir: "/proj/src/bench".to_owned(),
+        }
+        .to_string();
+        assert!(err.contains("src/bench/"), "got: {err}");
+        assert!(err.contains("package bench"), "got: {err}");
+    }
+}
@@ -1,22 +1,28 @@
 //! Build orchestration: resolve config, detect target, invoke compiler, store artifacts.
 
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::time::Instant;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 
-use konvoy_config::lockfile::{DepSource, DependencyLock, Lockfile, PluginLock};
-use konvoy_config::manifest::{Manifest, PackageKind};
+use konvoy_config::lockfile::{DepSource, DependencyLock, HashAlgorithm, Lockfile, PluginLock};
+use konvoy_config::manifest::{Manifest, PackageKind, Toolchain};
 use konvoy_config::Profile;
-use konvoy_konanc::detect::KonancInfo;
-use konvoy_konanc::invoke::{KonancCommand, ProduceKind};
+use konvoy_konanc::detect::{KonancInfo, KonancSource};
+use konvoy_konanc::invoke::{CompilationResult, KonancCommand, ProduceKind};
 use konvoy_targets::{host_target, Target};
+use konvoy_util::color::ColorChoice;
+use konvoy_util::ignore::IgnoreRules;
 
 use crate::artifact::{ArtifactStore, BuildMetadata};
 use crate::cache::{CacheInputs, CacheKey};
+use crate::compile_commands::CommandLog;
 use crate::error::EngineError;
+use crate::progress::{ProgressSink, StderrProgress};
 use crate::resolve::{parallel_levels, resolve_dependencies, ResolvedDep, ResolvedGraph};
+use crate::timings::PhaseTimer;
 
 /// Options controlling a build invocation.
 #[derive(Debug, Clone)]
@@ -27,8 +33,43 @@ pub struct BuildOptions {
     pub profile: Profile,
     /// Whether to show raw compiler output.
     pub verbose: bool,
+    /// Suppress progress lines (`Compiling`, `Fresh (cached)`); diagnostics,
+    /// warnings, and errors are still printed.
+    pub quiet: bool,
     /// Force a rebuild, bypassing the cache.
     pub force: bool,
+    /// Per-invocation `konanc` timeout. Overrides `[build] timeout_secs`.
+    pub timeout: Option<Duration>,
+    /// Print each distinct `(level, message)` diagnostic once with a repeat
+    /// count. Only printing collapses; `CompilationResult.diagnostics` is complete.
+    pub dedupe_diagnostics: bool,
+    /// When to color compiler diagnostics.
+    pub color: ColorChoice,
+    /// Record per-phase durations in `BuildResult::timings`.
+    pub timings: bool,
+    /// Features to enable on the root package, in addition to its defaults.
+    pub features: Vec<String>,
+    /// Do not enable the root package's `default` features.
+    pub no_default_features: bool,
+    /// Write the root project's `konanc` invocations to this path as a
+    /// `compile_commands.json`-style array. Does not skip the build itself.
+    pub emit_command: Option<PathBuf>,
+    /// After a successful build, copy the final artifact into this directory
+    /// under its usual file name. The `.konvoy` layout is unchanged.
+    pub out_dir: Option<PathBuf>,
+    /// Keep build outputs (`build/`) and the artifact cache (`cache/`) under
+    /// this directory instead of the project's `.konvoy/`. Path dependencies
+    /// share it.
+    pub target_dir: Option<PathBuf>,
+    /// Keep building the path dependencies that do not depend on a failed
+    /// one, then fail with every failure at once instead of the first.
+    pub keep_going: bool,
+    /// Fail the build when konanc reports any warning. Overrides an unset
+    /// `[build] deny_warnings`.
+    pub deny_warnings: bool,
+    /// Receives `Compiling`/`Fresh` progress, downloads, and warnings.
+    /// Defaults to [`StderrProgress`].
+    pub progress: Arc<dyn ProgressSink>,
 }
 
 impl BuildOptions {
@@ -36,6 +77,28 @@ impl BuildOptions {
     pub fn is_release(&self) -> bool {
         matches!(self.profile, Profile::Release)
     }
+
+    /// The `konanc` timeout to apply: the explicit option, else the root
+    /// manifest's `[build] timeout_secs`, else none.
+    pub fn compile_timeout(&self, manifest: &Manifest) -> Option<Duration> {
+        self.timeout
+            .or_else(|| manifest.build.timeout_secs.map(Duration::from_secs))
+    }
+
+    /// Whether compiler warnings fail the build: the explicit option or the
+    /// root manifest's `[build] deny_warnings`.
+    pub fn deny_warnings(&self, manifest: &Manifest) -> bool {
+        self.deny_warnings || manifest.build.deny_warnings
+    }
+
+    /// The artifact store for the project at `project_root`: under
+    /// `target_dir` when set, else the project's own.
+    pub(crate) fn artifact_store(&self, project_root: &Path) -> ArtifactStore {
+        match &self.target_dir {
+            Some(dir) => ArtifactStore::in_dir(dir),
+            None => ArtifactStore::new(project_root),
+        }
+    }
 }
 
 impl Default for BuildOptions {
@@ -44,7 +107,20 @@ impl Default for BuildOptions {
             target: None,
             profile: Profile::Debug,
             verbose: false,
+            quiet: false,
             force: false,
+            timeout: None,
+            dedupe_diagnostics: false,
+            color: ColorChoice::Auto,
+            timings: false,
+            features: Vec::new(),
+            no_default_features: false,
+            deny_warnings: false,
+            emit_command: None,
+            out_dir: None,
+            target_dir: None,
+            keep_going: false,
+            progress: Arc::new(StderrProgress),
         }
     }
 }
@@ -65,8 +141,21 @@ pub struct BuildResult {
     pub outcome: BuildOutcome,
     /// Path to the final output binary.
     pub output_path: PathBuf,
+    /// Size in bytes of the file at `output_path` (the binary, or the `.klib`
+    /// for a library).
+    pub artifact_size: u64,
+    /// The C header next to `output_path` for a `staticlib` or `cdylib`
+    /// package (`lib<name>_api.h`), so C build systems can find it. `None`
+    /// for other kinds.
+    pub header_path: Option<PathBuf>,
     /// How long the build took (including cache check).
     pub duration: std::time::Duration,
+    /// Duration of each build phase, in order. Empty unless
+    /// `BuildOptions::timings` was set.
+    pub timings: Vec<(String, Duration)>,
+    /// Each path dependency's outcome, in build order. Empty when the project
+    /// has no path dependencies.
+    pub dependency_outcomes: Vec<(String, BuildOutcome)>,
 }
 
 /// A klib (or plugin jar) input to a compilation, with an optional
@@ -119,6 +208,25 @@ pub(crate) fn library_paths_of(libs: &[LibraryInput]) -> Vec<PathBuf> {
     libs.iter().map(|l| l.path.clone()).collect()
 }
 
+/// SHA-256 of each library for the cache key, in input order.
+///
+/// Files without a pre-computed hash are hashed in parallel; each hash is
+/// independent, and a large Maven closure otherwise serializes the I/O. On
+/// failure the error of the earliest failing library is returned, as a
+/// sequential pass would.
+pub(crate) fn library_hashes_of(libs: &[LibraryInput]) -> Result<Vec<String>, EngineError> {
+    let hashes: Vec<Result<String, EngineError>> = libs
+        .par_iter()
+        .map(|lib| match &lib.precomputed_sha256 {
+            // Reuse the hash that `download_artifact` already computed for
+            // this file's bytes — it's value-equivalent to sha256_file.
+            Some(h) => Ok(h.clone()),
+            None => konvoy_util::hash::sha256_file(&lib.path).map_err(EngineError::from),
+        })
+        .collect();
+    hashes.into_iter().collect()
+}
+
 /// Common state resolved during steps 1–7a of the build pipeline.
 ///
 /// Shared between `build()` and `build_tests()` to avoid duplicating the
@@ -147,6 +255,8 @@ pub(crate) struct ResolvedBuildContext {
     /// which compiles the root's test sources). `build_single` derives each
     /// project's own set from its manifest.
     pub plugin_jars: Vec<PathBuf>,
+    /// The ROOT project's compiler plugin options, as `(plugin id, key, value)`.
+    pub plugin_options: Vec<(String, String, String)>,
     /// Resolved plugin lockfile pins — the deduped union across the root and
     /// all path-deps (persisted to the root lock by `update_lockfile_if_needed`).
     pub plugin_locks: Vec<konvoy_config::lockfile::PluginLock>,
@@ -155,16 +265,38 @@ pub(crate) struct ResolvedBuildContext {
     pub codegen_locks: Vec<konvoy_config::lockfile::CodegenToolLock>,
     /// Resolved path-dependency graph in topological order.
     pub dep_graph: ResolvedGraph,
+    /// Whether each path-dep was cached or compiled, in `dep_graph` order.
+    pub dependency_outcomes: Vec<(String, BuildOutcome)>,
+    /// `konanc` timeout for every compile in this build (from the options or
+    /// the root manifest).
+    pub compile_timeout: Option<Duration>,
+    /// Whether compiler warnings fail every compile in this build (from the
+    /// options or the root manifest).
+    pub deny_warnings: bool,
+    /// Whether new cache entries are stored compressed (from the root
+    /// manifest or `KONVOY_COMPRESS_CACHE`).
+    pub compress_cache: bool,
     /// Content-addressed artifact store for this project.
     pub store: ArtifactStore,
+    /// Phase durations so far, continued by the caller for its own phases.
+    pub timer: PhaseTimer,
+    /// The root package's enabled features (from `--features` and its defaults).
+    pub features: Vec<String>,
 }
 
 /// Inputs that only flow into `update_lockfile_if_needed`.
 pub(crate) struct LockfileWriteInputs {
     /// SHA-256 of the konanc tarball (from a fresh download, or `None` if already installed).
     pub konanc_tarball_sha256: Option<String>,
+    /// URL the konanc tarball was downloaded from (`None` if already installed).
+    pub konanc_tarball_url: Option<String>,
     /// SHA-256 of the JRE tarball (from a fresh download, or `None` if already installed).
     pub jre_tarball_sha256: Option<String>,
+    /// JDK major of the bundled JRE (`[toolchain] jre`, or the default).
+    pub jre_major: u32,
+    /// Whether konanc is the managed toolchain `[toolchain]` pins. `false` for
+    /// a `KONVOY_KONANC` override, which never rewrites that section.
+    pub konanc_managed: bool,
 }
 
 /// Compute the pre-stabilized "effective" lockfile used for the cache key.
@@ -178,7 +310,8 @@ pub(crate) struct LockfileWriteInputs {
 ///   contains the plugin entries — the root's enforced by the staleness check,
 ///   dep-contributed ones by the graph-wide ensure's pin gate).
 /// - Otherwise the toolchain section is stabilized to the detected konanc
-///   version (predicting the post-build write), and the predicted `[[plugins]]`
+///   version (predicting the post-build write; `konanc_version` is `None` for a
+///   `KONVOY_KONANC` override, which leaves it as is), and the predicted `[[plugins]]`
 ///   entries (`plugin_locks`, resolved before the cache key is computed) and
 ///   `[[dependencies]]` entries (path-dep locks derived from `dep_graph`, Maven
 ///   locks preserved) are folded in.
@@ -191,11 +324,12 @@ pub(crate) struct LockfileWriteInputs {
 /// cache keys. Plugins genuinely affect compilation, so they must stay in the
 /// cache key — folding the predicted entries in is what keeps it stable.
 #[allow(clippy::too_many_arguments)]
-fn predicted_effective_lockfile(
+pub(crate) fn predicted_effective_lockfile(
     lockfile: &Lockfile,
-    konanc_version: &str,
+    konanc_version: Option<&str>,
     konanc_tarball_sha256: Option<&str>,
     jre_tarball_sha256: Option<&str>,
+    jre_major: u32,
     plugin_locks: &[PluginLock],
     codegen_locks: &[konvoy_config::lockfile::CodegenToolLock],
     dep_graph: &ResolvedGraph,
@@ -203,25 +337,36 @@ fn predicted_effective_lockfile(
     resolver: crate::common::ArtifactResolver<'_>,
 ) -> Lockfile {
     resolver.cache_key_artifact_state(lockfile, || {
-        let mut effective = match &lockfile.toolchain {
-            // Lockfile already has the correct version — use as-is (preserves any
-            // existing tarball hashes and detekt info).
-            Some(tc) if tc.konanc_version == konanc_version => lockfile.clone(),
+        let mut effective = match (&lockfile.toolchain, konanc_version) {
+            // An override compiler is not what the lockfile pins, so the
+            // toolchain section is never rewritten for it.
+            (_, None) => lockfile.clone(),
+            // Lockfile already has the correct version and JRE — use as-is
+            // (preserves any existing tarball hashes and detekt info).
+            (Some(tc), Some(konanc_version))
+                if tc.konanc_version == konanc_version
+                    && crate::common::locked_jre_major(tc) == jre_major =>
+            {
+                lockfile.clone()
+            }
             // Lockfile is missing or has a different version. Build the same
             // lockfile that will eventually be written so the cache key is stable
             // from the first build. (Dependencies are folded in below, for both
             // branches.)
-            _ => {
+            (_, Some(konanc_version)) => {
                 let mut stabilized = Lockfile::with_managed_toolchain(
                     konanc_version,
                     konanc_tarball_sha256,
                     jre_tarball_sha256,
                 );
+                if let Some(tc) = stabilized.toolchain.as_mut() {
+                    tc.jre_major = crate::common::recorded_jre_major(jre_major);
+                }
                 // Mirror the detekt carry-forward that `update_lockfile_if_needed`
                 // applies to the WRITTEN lockfile, or the predicted cache-key
                 // lockfile would drop detekt and diverge from what gets written
                 // (one spurious recompile for detekt projects — issue #133 class).
-                carry_forward_detekt(&mut stabilized, lockfile);
+                carry_forward_tool_pins(&mut stabilized, lockfile);
                 stabilized
             }
         };
@@ -269,10 +414,19 @@ fn predicted_dependency_locks(
         .iter()
         .filter(|dep| !dep.source_hash.is_empty())
         .map(|dep| {
-            let rel_path = portable_dep_path(&canonical_root, &dep.project_root);
+            let source = match &dep.git {
+                Some(pin) => DepSource::Git {
+                    url: pin.url.clone(),
+                    rev: pin.rev.clone(),
+                    commit: pin.commit.clone(),
+                },
+                None => DepSource::Path {
+                    path: portable_dep_path(&canonical_root, &dep.project_root),
+                },
+            };
             DependencyLock {
                 name: dep.name.clone(),
-                source: DepSource::Path { path: rel_path },
+                source,
                 source_hash: dep.source_hash.clone(),
             }
         })
@@ -280,19 +434,28 @@ fn predicted_dependency_locks(
 
     // Preserve existing Maven dep locks from the current lockfile.
     // Maven dep locks are only modified by `konvoy update`, not by `konvoy build`.
+    // Path and git locks outside the graph belong to optional deps this build
+    // leaves out; `prune_stale_lock_entries` has already dropped undeclared ones.
+    let graph_deps: HashSet<&str> = dep_graph.order.iter().map(|d| d.name.as_str()).collect();
     for dep_lock in &lockfile.dependencies {
-        if matches!(&dep_lock.source, DepSource::Maven { .. }) {
+        let keep = match &dep_lock.source {
+            DepSource::Maven { .. } => true,
+            DepSource::Path { .. } | DepSource::Git { .. } => {
+                !graph_deps.contains(dep_lock.name.as_str())
+            }
+        };
+        if keep {
             new_deps.push(dep_lock.clone());
         }
     }
 
     // Sort by name, byte-for-byte identical to what `konvoy update` writes
-    // (update.rs sorts its merged dependency set the same way). Without this the
+    // (update.rs sorts its merged dependency set with the same helper). Without this the
     // build emits deps in topological+lockfile order while `update` emits them
     // alphabetically, so `konvoy update` then `konvoy build --locked` would see a
     // pure ordering difference and spuriously fail (and recompile, since the
     // lockfile order feeds the cache key).
-    new_deps.sort_by(|a, b| a.name.cmp(&b.name));
+    konvoy_config::lockfile::sort_dependency_locks(&mut new_deps);
     new_deps
 }
 
@@ -315,9 +478,17 @@ pub(crate) fn resolve_build_context(
     options: &BuildOptions,
     resolver: crate::common::ArtifactResolver<'_>,
 ) -> Result<ResolvedBuildContext, EngineError> {
+    let mut timer = PhaseTimer::new(options.timings);
+    konvoy_util::hash_cache::activate(&options.artifact_store(project_root).hash_cache_path());
+
     // 1. Read konvoy.toml.
     let manifest_path = project_root.join("konvoy.toml");
     let manifest = Manifest::from_path(&manifest_path)?;
+    let features = crate::features::resolve_features(
+        &manifest,
+        &options.features,
+        !options.no_default_features,
+    )?;
 
     // 2. Read konvoy.lock (or default).
     let lockfile_path = project_root.join("konvoy.lock");
@@ -333,7 +504,16 @@ pub(crate) fn resolve_build_context(
     //     lockfile prediction. `resolve_dependencies` only reads dep manifests +
     //     source hashes (it also enforces the shared Kotlin version), so it is
     //     safe to run this early, and reusing it avoids resolving the graph twice.
-    let dep_graph = resolve_dependencies(project_root, &manifest)?;
+    let dep_graph = resolve_dependencies(project_root, &manifest, &features, &lockfile, resolver)?;
+    for dep in &dep_graph.order {
+        if dep.manifest.toolchain.kotlin != manifest.toolchain.kotlin {
+            options.progress.on_warning(&format!(
+                "dependency `{}` requires Kotlin {} but is compiled with {} \
+                 (allow_toolchain_mismatch = true)",
+                dep.name, dep.manifest.toolchain.kotlin, manifest.toolchain.kotlin
+            ));
+        }
+    }
 
     // 3. Auto-resolve Maven deps if needed (unless --locked or --offline).
     //    When ANY project in the graph (root or a path-dep) declares Maven deps
@@ -358,6 +538,14 @@ pub(crate) fn resolve_build_context(
         _ => lockfile,
     };
 
+    // 3a. Drop lock entries for dependencies and plugins no konvoy.toml in the
+    //     graph declares any more (a removed Maven dep would otherwise keep being
+    //     downloaded and linked). Under --locked this is lockfile drift.
+    let lockfile = {
+        let (pruned, stale) = prune_stale_lock_entries(&manifest, &dep_graph, &lockfile);
+        resolver.prune_stale_lock_entries(lockfile, pruned, &stale, &lockfile_path)?
+    };
+
     // In --locked mode, verify the lockfile is complete and consistent with what
     // the whole graph's konvoy.toml files specify before doing any work.
     resolver.require_graph_artifacts_resolvable(
@@ -365,9 +553,15 @@ pub(crate) fn resolve_build_context(
         dep_graph.order.iter().map(|d| &d.manifest),
         &lockfile,
     )?;
+    timer.mark("dependency resolution");
 
     // 4. Resolve target.
     let target = resolve_target(&options.target)?;
+    if let Ok(host) = host_target() {
+        if let Some(warning) = cross_compile_preflight(target, host)? {
+            options.progress.on_warning(&warning);
+        }
+    }
     let profile = options.profile;
 
     // 5. Resolve the managed konanc toolchain. `resolve_konanc` auto-installs a
@@ -382,10 +576,12 @@ pub(crate) fn resolve_build_context(
     //    SHA is a separate integrity pin, verified just below (and in
     //    `update_lockfile_if_needed`); it cannot gate a cached toolchain — see
     //    #296.
-    let resolved = resolver.resolve_toolchain(&manifest.toolchain.kotlin, &lockfile)?;
+    let jre_major = crate::common::jre_major(&manifest.toolchain);
+    let resolved = resolver.resolve_toolchain(&manifest.toolchain.kotlin, jre_major, &lockfile)?;
     let konanc = resolved.info;
     let jre_home = resolved.jre_home;
     let konanc_tarball_sha256 = resolved.konanc_tarball_sha256;
+    let konanc_tarball_url = resolved.konanc_tarball_url;
     let jre_tarball_sha256 = resolved.jre_tarball_sha256;
 
     // Fast-fail tarball integrity check: if the toolchain was freshly downloaded
@@ -404,9 +600,12 @@ pub(crate) fn resolve_build_context(
             &konanc.version,
             konanc_tarball_sha256.as_deref(),
             jre_tarball_sha256.as_deref(),
+            jre_major,
             false,
+            options.progress.as_ref(),
         )?;
     }
+    timer.mark("toolchain resolution");
 
     // 6. Resolve + ensure plugin artifacts graph-wide (the dependency graph was
     //    resolved up front at step 2a), then pre-stabilize the lockfile for
@@ -436,11 +635,26 @@ pub(crate) fn resolve_build_context(
     // any), which is exactly what the effective lockfile carried before folding.
     // On a warm cache the artifacts are already present and pinned, so
     // `ensure_plugin_artifacts` only re-verifies hashes — it does not download.
+    //
+    // The graph-wide Maven klib union is fetched in the SAME work list as the
+    // plugins (one bounded pool, one progress display), so the two never
+    // serialize behind each other. Klibs are taken from the on-disk lockfile's
+    // Maven entries, which the effective lockfile below carries over unchanged
+    // (`konvoy update` owns them; `predicted_dependency_locks` preserves them).
+    // Each klib comes back with a precomputed sha256 from `download_artifact`,
+    // reused by the cache-key code.
     let graph_plugin_artifacts = crate::plugin::resolve_graph_plugin_artifacts(
         std::iter::once(&manifest).chain(dep_graph.order.iter().map(|dep| &dep.manifest)),
     )?;
-    let plugin_results =
-        crate::plugin::ensure_plugin_artifacts(&graph_plugin_artifacts, &lockfile, resolver)?;
+    let all_maven_entries: Vec<&DependencyLock> = lockfile.dependencies.iter().collect();
+    let (plugin_results, all_maven_klibs) = fetch_plugins_and_klibs(
+        &graph_plugin_artifacts,
+        &all_maven_entries,
+        &lockfile,
+        &target,
+        options.progress.as_ref(),
+        resolver,
+    )?;
     let plugin_locks = crate::plugin::build_plugin_locks(&plugin_results);
 
     // The codegen-tool union, by the same graph-wide reasoning as plugins (#293):
@@ -454,20 +668,24 @@ pub(crate) fn resolve_build_context(
     }
     let codegen_locks =
         crate::codegen::ensure_codegen_tools(&graph_generators, &lockfile.codegen_tools, resolver)?;
+    timer.mark("plugin/Maven download");
 
     // The ROOT project's own -Xplugin set, for the test build (`build_tests`
     // compiles the root's test sources). Regular compilation derives each
     // project's set from its own manifest inside `build_single`.
     let plugin_jars = crate::plugin::plugin_jar_paths(&manifest)?;
+    let plugin_options = crate::plugin::plugin_options(&manifest);
 
     // We predict the lockfile content that will eventually be written, so the
     // cache key is the same in the first and second builds. In `--locked` mode
     // the lockfile is used as-is because the user explicitly forbids changes.
+    let konanc_managed = resolved.source == KonancSource::Managed;
     let effective_lockfile = predicted_effective_lockfile(
         &lockfile,
-        &konanc.version,
+        konanc_managed.then_some(konanc.version.as_str()),
         konanc_tarball_sha256.as_deref(),
         jre_tarball_sha256.as_deref(),
+        jre_major,
         &plugin_locks,
         &codegen_locks,
         &dep_graph,
@@ -475,95 +693,46 @@ pub(crate) fn resolve_build_context(
         resolver,
     );
 
-    // 7. Resolve & download the graph-wide Maven klib union ONCE, up front, so
-    //    each project's own compile (root or path-dep) can be handed the klibs
-    //    it links. Each klib comes back with a precomputed sha256 from
-    //    `download_artifact`, reused by the cache-key code. Klibs are keyed by
-    //    COORDINATE (not the konvoy lock name): two coordinates declared under
-    //    the same key across projects must not collapse.
-    let all_maven_entries: Vec<&DependencyLock> = effective_lockfile.dependencies.iter().collect();
-    let all_maven_klibs = resolve_maven_klibs(&all_maven_entries, &target, resolver)?;
-    // A `BTreeMap` so `.values()` is coord-sorted + deduped — the exact set the
-    // root links (its subtree is the whole graph), in a deterministic order so
-    // the root's cache key is stable.
-    let klib_by_coord: std::collections::BTreeMap<MavenCoordKey, LibraryInput> = effective_lockfile
-        .dependencies
-        .iter()
-        .filter_map(maven_coord_key)
-        .zip(all_maven_klibs.iter().cloned())
-        .collect();
-
-    // Index path-deps by name, and precompute each dep's OWN Maven closure
-    // (coordinates) once — a dep shared by several dependents would otherwise be
-    // re-derived per dependent. A project's compile then links the union of its
-    // whole subtree's closures: a klib whose API exposes a Maven type forces
-    // every dependent's compile to also see that klib (verified: compiling
-    // against a klib whose API returns `Flow` needs coroutines.klib).
-    let dep_by_name: HashMap<&str, &ResolvedDep> = dep_graph
-        .order
-        .iter()
-        .map(|dep| (dep.name.as_str(), dep))
-        .collect();
-    let own_closure: HashMap<&str, Vec<MavenCoordKey>> = dep_graph
-        .order
-        .iter()
-        .map(|dep| {
-            (
-                dep.name.as_str(),
-                project_maven_closure_coords(&dep.manifest, &effective_lockfile),
-            )
-        })
-        .collect();
-
-    // Resolve the coordinate set into klibs (deduped + sorted for determinism).
-    let coords_to_klibs = |coords: BTreeSet<MavenCoordKey>| -> Vec<LibraryInput> {
-        coords
-            .iter()
-            .filter_map(|c| klib_by_coord.get(c).cloned())
-            .collect()
-    };
-
-    // Per-dep compile inputs, invariant across build levels: its transitive
-    // path-dep descendant NAMES (their klibs are looked up in `completed` during
-    // the loop) and its subtree's Maven klibs (own closure ∪ descendants').
-    let dep_inputs: HashMap<&str, (Vec<String>, Vec<LibraryInput>)> = dep_graph
-        .order
-        .iter()
-        .map(|dep| {
-            let descendants = collect_descendant_deps(&dep.dep_names, &dep_by_name);
-            // own_closure has an entry for every dep in dep_graph.order (incl.
-            // every descendant), so the lookups never miss; `flatten` over the
-            // `Option` keeps it index-panic-free.
-            let mut coords: BTreeSet<MavenCoordKey> = own_closure
-                .get(dep.name.as_str())
-                .into_iter()
-                .flatten()
-                .cloned()
-                .collect();
-            for d in &descendants {
-                coords.extend(
-                    own_closure
-                        .get(d.name.as_str())
-                        .into_iter()
-                        .flatten()
-                        .cloned(),
-                );
-            }
-            let descendant_names = descendants.iter().map(|d| d.name.clone()).collect();
-            (
-                dep.name.as_str(),
-                (descendant_names, coords_to_klibs(coords)),
-            )
-        })
-        .collect();
+    // 7. Work out each path-dep's compile inputs from the graph-wide Maven klib
+    //    union (fetched up front in step 6).
+    let GraphLibraryInputs {
+        deps: dep_inputs,
+        root_maven_klibs,
+    } = graph_library_inputs(
+        &dep_graph,
+        &all_maven_entries,
+        all_maven_klibs,
+        &effective_lockfile,
+    );
 
     // 8. Build path dependencies in topological order.
     let lockfile_content = lockfile_toml_content(&effective_lockfile)?;
 
+    // The timeout is a property of the build being run, so the root manifest's
+    // setting applies to every path-dep compile too.
+    let compile_timeout = options.compile_timeout(&manifest);
+    let deny_warnings = options.deny_warnings(&manifest);
+    let compress_cache = crate::artifact::compress_cache(manifest.build.compress_cache);
+
     let levels = parallel_levels(&dep_graph);
     let mut completed: HashMap<String, PathBuf> = HashMap::new();
-
-    for level in &levels {
+    let mut outcomes: HashMap<String, BuildOutcome> = HashMap::new();
+    // Under `keep_going`: each failed dep with its error, and the deps left
+    // unbuilt because something below them failed (`unbuilt` holds both).
+    let mut failed: Vec<(String, EngineError)> = Vec::new();
+    let mut skipped: Vec<String> = Vec::new();
+    let mut unbuilt: HashSet<String> = HashSet::new();
+
+    for (index, level) in levels.iter().enumerate() {
+        let (level, blocked) = split_blocked(level, &dep_inputs, &unbuilt);
+        for dep in blocked {
+            options.progress.on_warning(&format!(
+                "skipping `{}` due to a failed dependency",
+                dep.name
+            ));
+            unbuilt.insert(dep.name.clone());
+            skipped.push(dep.name.clone());
+        }
         let results: Vec<Result<(String, PathBuf, BuildOutcome), EngineError>> = level
             .par_iter()
             .map(|dep| {
@@ -590,12 +759,20 @@ pub(crate) fn resolve_build_context(
                     .map(LibraryInput::unhashed)
                     .collect();
                 lib_inputs.extend(maven_klibs.iter().cloned());
+                // `--features` selects the root's features; a dep always builds
+                // with its own defaults, exactly as when built standalone.
+                let dep_features = crate::features::resolve_features(&dep.manifest, &[], true)?;
                 let dep_cc = CompileContext {
                     konanc: &konanc,
                     jre_home: jre_home.as_deref(),
                     target: &target,
                     options,
                     library_inputs: &lib_inputs,
+                    timeout: compile_timeout,
+                    deny_warnings,
+                    compress_cache,
+                    features: &dep_features,
+                    command_log: None,
                 };
                 let (output, outcome) = build_single(
                     &dep.project_root,
@@ -608,12 +785,38 @@ pub(crate) fn resolve_build_context(
             })
             .collect();
 
-        for result in results {
-            let (name, output, _) = result?;
-            completed.insert(name, output);
+        for (dep, result) in level.iter().zip(results) {
+            match result {
+                Ok((name, output, outcome)) => {
+                    completed.insert(name.clone(), output);
+                    outcomes.insert(name, outcome);
+                }
+                Err(e) if options.keep_going => {
+                    unbuilt.insert(dep.name.clone());
+                    failed.push((dep.name.clone(), e));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        if timer.enabled() {
+            timer.mark(&format!(
+                "dependency level {} ({} dep(s))",
+                index + 1,
+                level.len()
+            ));
         }
     }
 
+    if !failed.is_empty() {
+        return Err(EngineError::DependenciesFailed {
+            failed: failed
+                .into_iter()
+                .map(|(name, e)| (name, e.to_string()))
+                .collect(),
+            skipped,
+        });
+    }
+
     let mut library_inputs: Vec<LibraryInput> = dep_graph
         .order
         .iter()
@@ -621,13 +824,23 @@ pub(crate) fn resolve_build_context(
         .map(LibraryInput::unhashed)
         .collect();
 
-    // The root links every path-dep klib (above) plus the WHOLE Maven union: its
-    // subtree is the entire graph, so its closure is exactly the pinned set,
-    // which `klib_by_coord.values()` already holds coord-deduped and sorted — no
-    // need to re-run the closure fixpoint over the whole graph just to rebuild it.
-    library_inputs.extend(klib_by_coord.values().cloned());
+    // The root links every path-dep klib (above) plus the WHOLE Maven union.
+    library_inputs.extend(root_maven_klibs);
 
-    let store = ArtifactStore::new(project_root);
+    // Levels finish in any order within themselves; report in graph order.
+    let dependency_outcomes = dep_graph
+        .order
+        .iter()
+        .filter_map(|dep| {
+            outcomes
+                .remove(&dep.name)
+                .map(|outcome| (dep.name.clone(), outcome))
+        })
+        .collect();
+
+    let store = options
+        .artifact_store(project_root)
+        .with_compression(compress_cache);
 
     Ok(ResolvedBuildContext {
         manifest,
@@ -639,17 +852,144 @@ pub(crate) fn resolve_build_context(
         jre_home,
         lockfile_write_inputs: LockfileWriteInputs {
             konanc_tarball_sha256,
+            konanc_tarball_url,
             jre_tarball_sha256,
+            jre_major,
+            konanc_managed,
         },
         library_inputs,
         plugin_jars,
+        plugin_options,
         plugin_locks,
         codegen_locks,
         dep_graph,
+        dependency_outcomes,
+        compile_timeout,
+        deny_warnings,
+        compress_cache,
         store,
+        timer,
+        features,
+    })
+}
+
+/// Split a dependency level into the deps that can build and those that
+/// depend, directly or transitively, on a dep in `unbuilt`.
+fn split_blocked<'d>(
+    level: &[&'d ResolvedDep],
+    dep_inputs: &HashMap<&str, (Vec<String>, Vec<LibraryInput>)>,
+    unbuilt: &HashSet<String>,
+) -> (Vec<&'d ResolvedDep>, Vec<&'d ResolvedDep>) {
+    level.iter().copied().partition(|dep| {
+        dep_inputs
+            .get(dep.name.as_str())
+            .is_none_or(|(names, _)| !names.iter().any(|n| unbuilt.contains(n)))
     })
 }
 
+/// Each path-dep's compile inputs, and the Maven klibs the root links.
+pub(crate) struct GraphLibraryInputs<'g> {
+    /// Per path-dep: the names of its transitive path-dep descendants (whose
+    /// klibs are known once they are built) and its subtree's Maven klibs.
+    pub deps: HashMap<&'g str, (Vec<String>, Vec<LibraryInput>)>,
+    /// The whole Maven union, deduped and sorted by coordinate.
+    pub root_maven_klibs: Vec<LibraryInput>,
+}
+
+/// Split the graph-wide Maven klibs (`maven_klibs`, fetched for
+/// `maven_entries` in the same order) into each project's own inputs.
+pub(crate) fn graph_library_inputs<'g>(
+    dep_graph: &'g ResolvedGraph,
+    maven_entries: &[&DependencyLock],
+    maven_klibs: Vec<LibraryInput>,
+    effective_lockfile: &Lockfile,
+) -> GraphLibraryInputs<'g> {
+    // Index the graph-wide Maven klib union so each project's own compile
+    // (root or path-dep) can be handed the klibs it links. Klibs are keyed by
+    // COORDINATE (not the konvoy lock name): two coordinates declared under the
+    // same key across projects must not collapse. A `BTreeMap` so `.values()`
+    // is coord-sorted + deduped — the exact set the root links (its subtree is
+    // the whole graph), in a deterministic order so the root's cache key is
+    // stable.
+    let klib_by_coord: std::collections::BTreeMap<MavenCoordKey, LibraryInput> = maven_entries
+        .iter()
+        .filter_map(|d| maven_coord_key(d))
+        .zip(maven_klibs)
+        .collect();
+
+    // Index path-deps by name, and precompute each dep's OWN Maven closure
+    // (coordinates) once — a dep shared by several dependents would otherwise be
+    // re-derived per dependent. A project's compile then links the union of its
+    // whole subtree's closures: a klib whose API exposes a Maven type forces
+    // every dependent's compile to also see that klib (verified: compiling
+    // against a klib whose API returns `Flow` needs coroutines.klib).
+    let dep_by_name: HashMap<&str, &ResolvedDep> = dep_graph
+        .order
+        .iter()
+        .map(|dep| (dep.name.as_str(), dep))
+        .collect();
+    let own_closure: HashMap<&str, Vec<MavenCoordKey>> = dep_graph
+        .order
+        .iter()
+        .map(|dep| {
+            (
+                dep.name.as_str(),
+                project_maven_closure_coords(&dep.manifest, effective_lockfile),
+            )
+        })
+        .collect();
+
+    // Resolve the coordinate set into klibs (deduped + sorted for determinism).
+    let coords_to_klibs = |coords: BTreeSet<MavenCoordKey>| -> Vec<LibraryInput> {
+        coords
+            .iter()
+            .filter_map(|c| klib_by_coord.get(c).cloned())
+            .collect()
+    };
+
+    // Per-dep compile inputs, invariant across build levels: its transitive
+    // path-dep descendant NAMES (their klibs are looked up once built, in
+    // `completed` during the build loop) and its subtree's Maven klibs (own closure ∪ descendants').
+    let deps: HashMap<&str, (Vec<String>, Vec<LibraryInput>)> = dep_graph
+        .order
+        .iter()
+        .map(|dep| {
+            let descendants = collect_descendant_deps(&dep.dep_names, &dep_by_name);
+            // own_closure has an entry for every dep in dep_graph.order (incl.
+            // every descendant), so the lookups never miss; `flatten` over the
+            // `Option` keeps it index-panic-free.
+            let mut coords: BTreeSet<MavenCoordKey> = own_closure
+                .get(dep.name.as_str())
+                .into_iter()
+                .flatten()
+                .cloned()
+                .collect();
+            for d in &descendants {
+                coords.extend(
+                    own_closure
+                        .get(d.name.as_str())
+                        .into_iter()
+                        .flatten()
+                        .cloned(),
+                );
+            }
+            let descendant_names = descendants.iter().map(|d| d.name.clone()).collect();
+            (
+                dep.name.as_str(),
+                (descendant_names, coords_to_klibs(coords)),
+            )
+        })
+        .collect();
+
+    // The root's subtree is the entire graph, so its closure is exactly the
+    // pinned set, which `klib_by_coord.values()` already holds coord-deduped and
+    // sorted — no need to re-run the closure fixpoint over the whole graph.
+    GraphLibraryInputs {
+        deps,
+        root_maven_klibs: klib_by_coord.into_values().collect(),
+    }
+}
+
 /// Run the full build pipeline.
 ///
 /// Steps:
@@ -674,32 +1014,54 @@ pub fn build(
     resolver: crate::common::ArtifactResolver<'_>,
 ) -> Result<BuildResult, EngineError> {
     let start = Instant::now();
-    let ctx = resolve_build_context(project_root, options, resolver)?;
+    let mut ctx = resolve_build_context(project_root, options, resolver)?;
 
     // 8. Build the root project.
+    let command_log = options
+        .emit_command
+        .as_ref()
+        .map(|_| CommandLog::new(project_root));
     let cc = CompileContext {
         konanc: &ctx.konanc,
         jre_home: ctx.jre_home.as_deref(),
         target: &ctx.target,
         options,
         library_inputs: &ctx.library_inputs,
+        timeout: ctx.compile_timeout,
+        deny_warnings: ctx.deny_warnings,
+        compress_cache: ctx.compress_cache,
+        features: &ctx.features,
+        command_log: command_log.as_ref(),
     };
-    let (output_path, outcome) = build_single(
+    let built = build_single(
         project_root,
         &ctx.manifest,
         &cc,
         ctx.profile,
         &ctx.lockfile_content,
-    )?;
+    );
+    // Written even when compilation failed: that is when the exact command
+    // matters most.
+    let emitted = match (&command_log, &options.emit_command) {
+        (Some(log), Some(path)) => log.write_to(path),
+        _ => Ok(()),
+    };
+    let (output_path, outcome) = built?;
+    emitted?;
+    ctx.timer.mark("root compile");
 
     let lockfile_path = project_root.join("konvoy.lock");
 
     // 9. Update lockfile if toolchain, dependencies, or plugins changed.
     update_lockfile_if_needed(
         &ctx.lockfile,
-        &ctx.konanc,
+        ctx.lockfile_write_inputs
+            .konanc_managed
+            .then_some(&ctx.konanc),
         ctx.lockfile_write_inputs.konanc_tarball_sha256.as_deref(),
+        ctx.lockfile_write_inputs.konanc_tarball_url.as_deref(),
         ctx.lockfile_write_inputs.jre_tarball_sha256.as_deref(),
+        ctx.lockfile_write_inputs.jre_major,
         &ctx.dep_graph,
         &ctx.plugin_locks,
         &ctx.codegen_locks,
@@ -707,19 +1069,78 @@ pub fn build(
         &lockfile_path,
         options.force,
         resolver,
+        options.progress.as_ref(),
     )?;
+    ctx.timer.mark("lockfile update");
+    konvoy_util::hash_cache::persist();
+
+    let header_path = c_header_path(&output_path, produce_kind(ctx.manifest.package.kind));
+    if let Some(out_dir) = &options.out_dir {
+        export_artifact(&output_path, out_dir)?;
+        if let Some(header) = &header_path {
+            export_artifact(header, out_dir)?;
+        }
+    }
+
+    let duration = start.elapsed();
+    let timings = ctx.timer.into_phases();
+    if options.timings {
+        crate::timings::record_history(project_root, ctx.target, ctx.profile, duration, &timings);
+    }
 
+    let artifact_size = artifact_size(&output_path)?;
     Ok(BuildResult {
         outcome,
         output_path,
-        duration: start.elapsed(),
+        artifact_size,
+        header_path,
+        duration,
+        timings,
+        dependency_outcomes: ctx.dependency_outcomes,
     })
 }
 
+/// Size in bytes of the built artifact at `path`.
+pub(crate) fn artifact_size(path: &Path) -> Result<u64, EngineError> {
+    let metadata = std::fs::metadata(path).map_err(|source| konvoy_util::error::UtilError::Io {
+        path: path.display().to_string(),
+        source,
+    })?;
+    Ok(metadata.len())
+}
+
+/// Copy the built artifact into `out_dir` (created if absent), keeping its
+/// file name, and return the copy's path.
+///
+/// A real copy rather than a hard link: the artifact under `.konvoy/build` may
+/// share its inode with the cache entry, and CI steps that strip or sign the
+/// exported file must not rewrite the cache.
+///
+/// # Errors
+/// Returns an error if the directory cannot be created or the file copied.
+pub(crate) fn export_artifact(output_path: &Path, out_dir: &Path) -> Result<PathBuf, EngineError> {
+    let file_name =
+        output_path
+            .file_name()
+            .ok_or_else(|| EngineError::InternalInvariantViolated {
+                context: format!("build output `{}` has no file name", output_path.display()),
+            })?;
+    let dest = out_dir.join(file_name);
+    konvoy_util::fs::ensure_dir(out_dir)?;
+    if dest.exists() {
+        std::fs::remove_file(&dest).map_err(|source| konvoy_util::error::UtilError::Io {
+            path: dest.display().to_string(),
+            source,
+        })?;
+    }
+    konvoy_util::fs::copy_file(output_path, &dest)?;
+    Ok(dest)
+}
+
 /// Compiler invocation context shared across compilation functions.
 ///
-/// Bundles the common parameters that flow through `build_single`, `compile`,
-/// and the two-step/single-step compilation helpers.
+/// Bundles the common parameters that flow through `build_single` and
+/// `compile`, which turns them into [`KonancSettings`].
 pub(crate) struct CompileContext<'a> {
     /// Konanc compiler info (path, version, fingerprint).
     pub konanc: &'a KonancInfo,
@@ -736,35 +1157,65 @@ pub(crate) struct CompileContext<'a> {
     /// is derived from its own manifest inside `build_single`, so the same
     /// context serves the root and every path-dep.
     pub library_inputs: &'a [LibraryInput],
+    /// Kill any `konanc` invocation that runs longer than this.
+    pub timeout: Option<Duration>,
+    /// Fail any `konanc` invocation that reports a warning.
+    pub deny_warnings: bool,
+    /// Store new cache entries compressed. Never part of the cache key.
+    pub compress_cache: bool,
+    /// This project's enabled features; each adds `features/<name>/` as a
+    /// source set. Resolved per project, like the `-Xplugin` set.
+    pub features: &'a [String],
+    /// Where to record each `konanc` invocation for `--emit-command`. Only
+    /// the root project's compile sets it.
+    pub command_log: Option<&'a CommandLog>,
 }
 
-/// Build a single project (either root or a dependency).
-///
-/// Returns the path to the output artifact and whether the build was cached.
-pub(crate) fn build_single(
+/// A project's compile inputs and the cache key they hash to, computed
+/// without compiling anything.
+pub(crate) struct ProjectInputs {
+    pub source_dirs: Vec<PathBuf>,
+    pub sources: Vec<PathBuf>,
+    pub generators: Vec<Box<dyn crate::codegen::CodeGenerator>>,
+    pub plugin_jars: Vec<PathBuf>,
+    pub plugin_options: Vec<(String, String, String)>,
+    pub produce: ProduceKind,
+    pub cache_key: CacheKey,
+    /// File name of the artifact, inside the cache entry and the build dir.
+    pub output_name: String,
+    /// Where the artifact is materialized under `.konvoy/build/`.
+    pub output_path: PathBuf,
+}
+
+/// Collect a project's sources and compute its cache key: the part of
+/// [`build_single`] that runs before the cache is consulted.
+pub(crate) fn project_inputs(
     project_root: &Path,
     manifest: &Manifest,
     cc: &CompileContext<'_>,
     profile: Profile,
     lockfile_content: &str,
-) -> Result<(PathBuf, BuildOutcome), EngineError> {
-    // Collect source files, excluding test sources (src/test/). `src/` may be
-    // absent for a project whose Kotlin is entirely generated; treat that as "no
-    // hand-written sources" rather than an I/O error. Emptiness is checked AFTER
-    // codegen (below), with a clear `NoSources` when nothing was produced.
-    let src_dir = project_root.join("src");
-    let test_dir = src_dir.join("test");
-    let all_sources = if src_dir.is_dir() {
-        konvoy_util::fs::collect_files(&src_dir, "kt")?
-    } else {
-        Vec::new()
-    };
-    let mut sources: Vec<PathBuf> = all_sources
-        .into_iter()
-        .filter(|p| !p.starts_with(&test_dir))
-        .collect();
+) -> Result<ProjectInputs, EngineError> {
+    // Collect source files from the configured source dirs (default `src/`),
+    // excluding test and benchmark sources (`test_dir`, src/bench/). A source
+    // dir may be absent for a project whose Kotlin is entirely generated; treat
+    // that as "no hand-written sources" rather than an I/O error. Emptiness is checked AFTER codegen
+    // (below), with a clear `NoSources` when nothing was produced.
+    // Files matched by `.konvoyignore` are dropped from both the source list and
+    // the cache key.
+    let mut source_dirs = crate::sources::source_dirs(project_root, manifest);
+    source_dirs.extend(crate::features::feature_source_dirs(
+        project_root,
+        cc.features,
+    ));
+    let ignore = IgnoreRules::load(project_root)?;
+    let sources = crate::sources::collect_main_sources(
+        &source_dirs,
+        &crate::sources::auxiliary_dirs(project_root, manifest),
+        &ignore,
+    )?;
 
-    let is_lib = manifest.package.kind == PackageKind::Lib;
+    let produce = produce_kind(manifest.package.kind);
 
     // This project's compiler plugins, from its OWN manifest — the root and
     // every path-dep are treated identically, so a dep's `[plugins]` are applied
@@ -772,8 +1223,10 @@ pub(crate) fn build_single(
     // The JARs were already downloaded + SHA-pinned graph-wide in
     // `resolve_build_context`; this is pure path derivation. The cache key
     // covers plugins through `manifest_content` (the `[plugins]` config) and
-    // `lockfile_content` (the graph-wide pin union, version + sha256).
+    // `lockfile_content` (the graph-wide pin union, version + sha256); plugin
+    // options are part of the `[plugins]` config.
     let plugin_jars = crate::plugin::plugin_jar_paths(manifest)?;
+    let plugin_options = crate::plugin::plugin_options(manifest);
 
     // This project's code generators, from its OWN manifest — identical treatment
     // for the root and every path-dep. The tags (spec contents + config + tool
@@ -784,7 +1237,7 @@ pub(crate) fn build_single(
     let codegen_hashes = crate::codegen::compute_codegen_hashes(project_root, &generators)?;
 
     // Compute cache key.
-    let manifest_content = manifest.to_toml()?;
+    let manifest_content = manifest_key_content(manifest)?;
     let cache_inputs = CacheInputs {
         manifest_content,
         lockfile_content: lockfile_content.to_owned(),
@@ -792,81 +1245,177 @@ pub(crate) fn build_single(
         konanc_fingerprint: cc.konanc.fingerprint.clone(),
         target: *cc.target,
         profile,
-        source_dir: project_root.join("src"),
-        source_glob: "**/*.kt".to_owned(),
+        source_dirs: source_dirs.clone(),
+        source_glob: crate::sources::SOURCE_GLOB.to_owned(),
+        ignore,
         os: std::env::consts::OS.to_owned(),
         arch: std::env::consts::ARCH.to_owned(),
-        dependency_hashes: cc
-            .library_inputs
-            .iter()
-            .map(|lib| match &lib.precomputed_sha256 {
-                // Reuse the hash that `download_artifact` already computed for
-                // this file's bytes — it's value-equivalent to sha256_file.
-                Some(h) => Ok(h.clone()),
-                None => konvoy_util::hash::sha256_file(&lib.path).map_err(EngineError::from),
-            })
-            .collect::<Result<Vec<_>, _>>()?,
+        dependency_hashes: library_hashes_of(cc.library_inputs)?,
         // Codegen inputs (spec files + generator config + tool version) — a change
         // here rebuilds. Empty when the project has no `[codegen]` config.
         codegen_hashes,
+        features: cc.features.to_vec(),
+        deny_warnings: cc.deny_warnings,
     };
     let cache_key = CacheKey::compute(&cache_inputs)?;
 
     // Output path: for deps, put .klib in deps/ subdir; for root, keep existing layout.
-    let output_name = if is_lib {
-        format!("{}.klib", manifest.package.name)
-    } else {
-        manifest.package.name.clone()
-    };
-    let output_path = project_root
-        .join(".konvoy")
-        .join("build")
-        .join(cc.target.to_konanc_arg())
-        .join(profile.as_str())
-        .join(&output_name);
-
-    let store = ArtifactStore::new(project_root);
+    // `bin_name` / `lib_name` rename the artifact; they reach the cache key via
+    // `manifest_content`, so renaming never serves an artifact stored under the old name.
+    let output_name = manifest.package.output_name(*cc.target);
+    let output_path = build_output_path(
+        project_root,
+        cc.options.target_dir.as_deref(),
+        &output_name,
+        cc.target,
+        profile,
+    );
 
-    // Check cache (skip when --force is used to force a rebuild).
-    if !cc.options.force && store.has(&cache_key) {
-        eprintln!("    Fresh {} (cached)", manifest.package.name);
-        store.materialize(&cache_key, &output_name, &output_path)?;
-        return Ok((output_path, BuildOutcome::Cached));
-    }
+    Ok(ProjectInputs {
+        source_dirs,
+        sources,
+        generators,
+        plugin_jars,
+        plugin_options,
+        produce,
+        cache_key,
+        output_name,
+        output_path,
+    })
+}
 
-    // Cache miss: run this project's code generators (tools ensured graph-wide in
-    // resolve_build_context) and add the emitted `.kt` to the source set. The
-    // generated output is NOT in the cache key directly — its inputs are, via
-    // `codegen_hashes` — so generation is deterministic w.r.t. the key.
-    if !generators.is_empty() {
-        let generated = crate::codegen::run_codegen(
-            project_root,
-            &generators,
-            cc.jre_home,
-            cc.options.verbose,
-        )?;
-        sources.extend(generated);
+/// What konanc produces for a package of `kind`.
+pub(crate) fn produce_kind(kind: PackageKind) -> ProduceKind {
+    match kind {
+        PackageKind::Bin => ProduceKind::Program,
+        PackageKind::Lib => ProduceKind::Library,
+        PackageKind::StaticLib => ProduceKind::StaticLib,
+        PackageKind::DynamicLib => ProduceKind::DynamicLib,
     }
+}
 
-    if sources.is_empty() {
-        return Err(EngineError::NoSources {
-            dir: src_dir.display().to_string(),
-        });
+/// The C header konanc writes next to a static or dynamic library at
+/// `library` (`libffi_api.h` for `libffi.so`), or `None` for other outputs.
+/// konanc's other companion file, a `.def` for Windows DLLs, never applies:
+/// Windows targets cannot be built yet.
+pub(crate) fn c_header_path(library: &Path, produce: ProduceKind) -> Option<PathBuf> {
+    if !matches!(produce, ProduceKind::StaticLib | ProduceKind::DynamicLib) {
+        return None;
     }
+    let stem = library.file_stem()?.to_string_lossy();
+    Some(library.with_file_name(format!("{stem}_api.h")))
+}
 
-    // Compile.
-    eprintln!(
-        "    Compiling {} \u{2192} {}",
-        manifest.package.name,
-        output_path.display()
-    );
+/// Where a project's artifact named `output_name` is materialized:
+/// `.konvoy/build/<target>/<profile>/<output_name>`, or the same path under
+/// `target_dir` instead of `.konvoy/` when one is given.
+pub(crate) fn build_output_path(
+    project_root: &Path,
+    target_dir: Option<&Path>,
+    output_name: &str,
+    target: &Target,
+    profile: Profile,
+) -> PathBuf {
+    target_dir
+        .map_or_else(|| project_root.join(".konvoy"), Path::to_path_buf)
+        .join("build")
+        .join(target.to_konanc_arg())
+        .join(profile.as_str())
+        .join(output_name)
+}
 
-    let produce = if is_lib {
-        ProduceKind::Library
-    } else {
-        ProduceKind::Program
-    };
-    let compile_output = compile(cc, &sources, &output_path, produce, &plugin_jars)?;
+/// Build a single project (either root or a dependency).
+///
+/// Returns the path to the output artifact and whether the build was cached.
+pub(crate) fn build_single(
+    project_root: &Path,
+    manifest: &Manifest,
+    cc: &CompileContext<'_>,
+    profile: Profile,
+    lockfile_content: &str,
+) -> Result<(PathBuf, BuildOutcome), EngineError> {
+    let ProjectInputs {
+        source_dirs,
+        mut sources,
+        generators,
+        plugin_jars,
+        plugin_options,
+        produce,
+        cache_key,
+        output_name,
+        output_path,
+    } = project_inputs(project_root, manifest, cc, profile, lockfile_content)?;
+    let header = c_header_path(&output_path, produce);
+
+    let store = cc
+        .options
+        .artifact_store(project_root)
+        .with_compression(cc.compress_cache);
+
+    // Check cache (skip when --force is used to force a rebuild).
+    let header_name = header
+        .as_deref()
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy().into_owned());
+    let mut cached_names = vec![output_name.as_str()];
+    cached_names.extend(header_name.as_deref());
+    if reusable_cache_entry(
+        &store,
+        &cache_key,
+        &cached_names,
+        &manifest.package.name,
+        cc.options,
+    )? {
+        if !cc.options.quiet {
+            cc.options.progress.on_cache_hit(&manifest.package.name);
+        }
+        store.materialize(&cache_key, &output_name, &output_path)?;
+        if let Some(header) = &header {
+            materialize_companion(&store, &cache_key, header)?;
+        }
+        return Ok((output_path, BuildOutcome::Cached));
+    }
+
+    // Cache miss: run this project's code generators (tools ensured graph-wide in
+    // resolve_build_context) and add the emitted `.kt` to the source set. The
+    // generated output is NOT in the cache key directly — its inputs are, via
+    // `codegen_hashes` — so generation is deterministic w.r.t. the key.
+    if !generators.is_empty() {
+        let generated = crate::codegen::run_codegen(
+            project_root,
+            &generators,
+            cc.jre_home,
+            cc.options.verbose,
+        )?;
+        sources.extend(generated);
+    }
+
+    if sources.is_empty() {
+        return Err(EngineError::NoSources {
+            dir: source_dirs
+                .iter()
+                .map(|d| d.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        });
+    }
+
+    // Compile.
+    if !cc.options.quiet {
+        cc.options
+            .progress
+            .on_compile_start(&manifest.package.name, &output_path);
+    }
+
+    let compile_output = compile(
+        cc,
+        &sources,
+        &output_path,
+        produce,
+        &plugin_jars,
+        &plugin_options,
+        &manifest.toolchain,
+    )?;
 
     // Store artifact in cache.
     let metadata = BuildMetadata {
@@ -875,16 +1424,68 @@ pub(crate) fn build_single(
         konanc_version: cc.konanc.version.clone(),
         built_at: crate::common::now_epoch_secs(),
     };
-    store.store(&cache_key, &compile_output, &metadata)?;
+    // A C library's header is cached alongside it, so a cache hit restores both.
+    let compiled_header = c_header_path(&compile_output, produce);
+    let mut artifacts = vec![compile_output.as_path()];
+    artifacts.extend(compiled_header.as_deref());
+    store.store_all(&cache_key, &artifacts, &metadata)?;
 
     // Materialize to the canonical output path (if compile output differs).
     if compile_output != output_path {
         store.materialize(&cache_key, &output_name, &output_path)?;
+        if let Some(header) = &header {
+            materialize_companion(&store, &cache_key, header)?;
+        }
     }
 
     Ok((output_path, BuildOutcome::Fresh))
 }
 
+/// Whether the cache entry for `key` can be reused instead of compiling: it
+/// exists, `--force` is off, and none of `artifact_names` was deleted or
+/// truncated since it was stored. A damaged entry is removed, with a warning
+/// naming `label`, so the rebuild can store a fresh one (`store_all` never
+/// overwrites an existing entry).
+pub(crate) fn reusable_cache_entry(
+    store: &ArtifactStore,
+    key: &CacheKey,
+    artifact_names: &[&str],
+    label: &str,
+    options: &BuildOptions,
+) -> Result<bool, EngineError> {
+    if options.force || !store.has(key) {
+        return Ok(false);
+    }
+    let Some(missing) = store.missing_artifact(key, artifact_names) else {
+        return Ok(true);
+    };
+    options.progress.on_warning(&format!(
+        "cached `{missing}` for {label} is missing or empty; rebuilding to repair the cache entry"
+    ));
+    store.remove(key)?;
+    Ok(false)
+}
+
+/// Materialize a file stored next to the main artifact in a cache entry, such
+/// as a C library's header, to `dest` under its own file name.
+fn materialize_companion(
+    store: &ArtifactStore,
+    key: &CacheKey,
+    dest: &Path,
+) -> Result<(), EngineError> {
+    let name = dest
+        .file_name()
+        .ok_or_else(|| EngineError::InternalInvariantViolated {
+            context: format!("companion artifact `{}` has no file name", dest.display()),
+        })?;
+    store.materialize(key, &name.to_string_lossy(), dest)
+}
+
+/// The `--target` value that asks `konvoy build` for every target buildable
+/// from the host. Only `konvoy build` accepts it; it loops over the targets
+/// itself and calls [`build`] once per target.
+pub const ALL_TARGETS: &str = "all";
+
 /// Resolve the target: use the explicit `--target` value or detect the host.
 ///
 /// Accepts `"host"` as a special alias that resolves to the current platform's
@@ -894,11 +1495,53 @@ pub(crate) fn build_single(
 /// Returns an error if the target string is not a known target triple (or `"host"`),
 /// or if host detection fails on an unsupported platform.
 pub(crate) fn resolve_target(target_opt: &Option<String>) -> Result<Target, EngineError> {
-    match target_opt {
-        Some(name) if name == "host" => Ok(host_target()?),
-        Some(name) => Ok(name.parse::<Target>()?),
-        None => Ok(host_target()?),
+    let target = match target_opt {
+        Some(name) if name == "host" => host_target()?,
+        Some(name) if name == ALL_TARGETS => return Err(EngineError::AllTargetsUnsupported),
+        Some(name) => name.parse::<Target>()?,
+        None => host_target()?,
+    };
+    if !target.is_supported() {
+        return Err(EngineError::TargetNotYetSupported {
+            target: target.to_string(),
+        });
+    }
+    Ok(target)
+}
+
+/// Check that the host can build `target` before any toolchain work happens.
+///
+/// Returns a warning to print when cross-compiling (konanc may need to fetch
+/// the target's sysroot, and linking can still fail without it), or `None`
+/// for a native build.
+///
+/// # Errors
+/// Returns `CrossCompileUnsupported` when the target can never be built from
+/// this host, instead of letting konanc fail deep inside the linker.
+fn cross_compile_preflight(target: Target, host: Target) -> Result<Option<String>, EngineError> {
+    if target == host {
+        return Ok(None);
+    }
+    if !target.buildable_from(host) {
+        // Linux targets build from any host, so they are always the fallback.
+        let reason = if target.is_apple() {
+            format!(
+                "{target} links against the macOS SDK, which is only available on macOS; \
+                 build on a macOS machine or pick a Linux target"
+            )
+        } else {
+            format!("konvoy cannot build {target} from this host; pick a Linux target")
+        };
+        return Err(EngineError::CrossCompileUnsupported {
+            target: target.to_string(),
+            host: host.to_string(),
+            reason,
+        });
     }
+    Ok(Some(format!(
+        "cross-compiling for {target} on a {host} host — konanc may download \
+         additional platform SDKs on first use, and linking needs them"
+    )))
 }
 
 /// Check whether two-step compilation is needed.
@@ -908,155 +1551,221 @@ pub(crate) fn resolve_target(target_opt: &Option<String>) -> Result<Target, Engi
 /// sources directly to a binary. Gradle works around this by always compiling
 /// sources to a klib first, then linking the klib into a binary.
 ///
-/// We do the same when producing a program with plugins active.
-fn needs_two_step_compilation(produce: ProduceKind, plugin_jars: &[PathBuf]) -> bool {
-    produce == ProduceKind::Program && !plugin_jars.is_empty()
+/// We do the same when producing a program or C library with plugins active.
+pub(crate) fn needs_two_step_compilation(produce: ProduceKind, plugin_jars: &[PathBuf]) -> bool {
+    produce != ProduceKind::Library && !plugin_jars.is_empty()
+}
+
+/// The konanc settings every step of one compile shares, whether a project
+/// build or [`crate::compile_sources`] drives it.
+pub(crate) struct KonancSettings<'a> {
+    pub target: Target,
+    pub release: bool,
+    /// `-language-version`, passed when compiling sources (not when linking).
+    pub language_version: Option<&'a str>,
+    /// `-api-version`, passed when compiling sources (not when linking).
+    pub api_version: Option<&'a str>,
+    /// Dependency `.klib` paths.
+    pub libraries: &'a [PathBuf],
+    pub jre_home: Option<&'a Path>,
+    pub timeout: Option<Duration>,
+    /// Echo konanc's output while it runs (`--verbose`).
+    pub stream_output: bool,
+    /// Treat a step that reports any warning like a failed one.
+    pub deny_warnings: bool,
+}
+
+impl KonancSettings<'_> {
+    /// A command writing `produce` to `output`, before sources or a klib to
+    /// link are added.
+    fn command(&self, output: &Path, produce: ProduceKind) -> KonancCommand {
+        let cmd = KonancCommand::new()
+            .output(output)
+            .target(self.target.to_konanc_arg())
+            .release(self.release)
+            .produce(produce)
+            .libraries(self.libraries)
+            .stream_output(self.stream_output)
+            .timeout(self.timeout);
+        match self.jre_home {
+            Some(jh) => cmd.java_home(jh),
+            None => cmd,
+        }
+    }
 }
 
-/// Two-step compilation: sources → klib → binary.
+/// Compile `sources` into `output_path` in one konanc step or two.
 ///
-/// Step 1 compiles sources into a temporary klib with plugins active so that
-/// plugin codegen (e.g. serialization) is applied. Step 2 links the klib into
-/// the final binary without plugins.
-fn compile_two_step(
-    cc: &CompileContext<'_>,
+/// When [`needs_two_step_compilation`] says so, step 1 compiles the sources
+/// into a temporary klib with the plugins active so that plugin codegen (e.g.
+/// serialization) is applied, and step 2 links the klib into the final
+/// `produce` output without plugins. The klib is removed afterwards.
+///
+/// `run` executes each step; an error from it ends the compile. A step that
+/// does not pass [`check_compilation`] also ends it, without an error. The
+/// last step's result is returned, carrying every step's diagnostics in
+/// order. A program is renamed into place only when every step passed.
+pub(crate) fn run_konanc_steps(
+    settings: &KonancSettings<'_>,
     sources: &[PathBuf],
     output_path: &Path,
+    produce: ProduceKind,
     plugin_jars: &[PathBuf],
-) -> Result<PathBuf, EngineError> {
-    // Step 1: compile sources → temporary klib (with plugins active).
+    plugin_options: &[(String, String, String)],
+    mut run: impl FnMut(&KonancCommand, &Path) -> Result<CompilationResult, EngineError>,
+) -> Result<CompilationResult, EngineError> {
+    let two_step = needs_two_step_compilation(produce, plugin_jars);
     let klib_path = output_path.with_extension("klib");
-
-    // konanc only consumes paths — strip the precomputed hashes here.
-    let lib_paths = library_paths_of(cc.library_inputs);
-
-    let mut compile_cmd = KonancCommand::new()
+    let (compile_output, compile_produce) = if two_step {
+        (klib_path.as_path(), ProduceKind::Library)
+    } else {
+        (output_path, produce)
+    };
+    let compile_cmd = settings
+        .command(compile_output, compile_produce)
         .sources(sources)
-        .output(&klib_path)
-        .target(cc.target.to_konanc_arg())
-        .release(cc.options.is_release())
-        .produce(ProduceKind::Library)
-        .libraries(&lib_paths)
-        .plugins(plugin_jars);
-
-    if let Some(jh) = cc.jre_home {
-        compile_cmd = compile_cmd.java_home(jh);
-    }
-
-    let compile_result = compile_cmd.execute(cc.konanc)?;
-    crate::diagnostics::print_diagnostics(&compile_result, cc.options.verbose);
-
-    if !compile_result.success {
-        return Err(EngineError::CompilationFailed {
-            error_count: compile_result.error_count(),
-        });
-    }
+        .language_version(settings.language_version)
+        .api_version(settings.api_version)
+        .plugins(plugin_jars)
+        .plugin_options(plugin_options);
 
-    // Step 2: link klib → binary (no plugins needed).
     // Use a closure so the temp klib is cleaned up on all exit paths.
-    let link_result = (|| {
-        let mut link_cmd = KonancCommand::new()
-            .include(&klib_path)
-            .output(output_path)
-            .target(cc.target.to_konanc_arg())
-            .release(cc.options.is_release())
-            .libraries(&lib_paths);
-
-        if let Some(jh) = cc.jre_home {
-            link_cmd = link_cmd.java_home(jh);
+    let outcome = (|| {
+        let mut result = run(&compile_cmd, compile_output)?;
+        if two_step && check_compilation(&result, settings.deny_warnings).is_ok() {
+            let link_cmd = settings.command(output_path, produce).include(&klib_path);
+            let mut linked = run(&link_cmd, output_path)?;
+            let mut diagnostics = std::mem::take(&mut result.diagnostics);
+            diagnostics.append(&mut linked.diagnostics);
+            linked.diagnostics = diagnostics;
+            result = linked;
         }
-
-        let result = link_cmd.execute(cc.konanc)?;
-        crate::diagnostics::print_diagnostics(&result, cc.options.verbose);
-
-        if !result.success {
-            return Err(EngineError::CompilationFailed {
-                error_count: result.error_count(),
-            });
+        // konanc appends `.kexe` on Linux for programs. Rename to the expected
+        // path. Libraries produce .klib directly, so skip this for them.
+        if produce == ProduceKind::Program
+            && check_compilation(&result, settings.deny_warnings).is_ok()
+        {
+            normalize_konanc_output(output_path)?;
         }
-
-        normalize_konanc_output(output_path)?;
-        Ok(output_path.to_path_buf())
+        Ok(result)
     })();
 
-    // Clean up temporary klib regardless of success or failure.
-    let _ = std::fs::remove_file(&klib_path);
-
-    link_result
+    if two_step {
+        let _ = std::fs::remove_file(&klib_path);
+    }
+    outcome
 }
 
-/// Single-step compilation: sources → artifact directly.
-///
-/// Used for library builds, or program builds without plugins.
-fn compile_single_step(
+/// Add `cmd` to the `--emit-command` log, if this compile keeps one.
+fn record_command(
     cc: &CompileContext<'_>,
-    sources: &[PathBuf],
+    cmd: &KonancCommand,
     output_path: &Path,
-    produce: ProduceKind,
-    plugin_jars: &[PathBuf],
-) -> Result<PathBuf, EngineError> {
-    let lib_paths = library_paths_of(cc.library_inputs);
-
-    let mut cmd = KonancCommand::new()
-        .sources(sources)
-        .output(output_path)
-        .target(cc.target.to_konanc_arg())
-        .release(cc.options.is_release())
-        .produce(produce)
-        .libraries(&lib_paths)
-        .plugins(plugin_jars);
-
-    if let Some(jh) = cc.jre_home {
-        cmd = cmd.java_home(jh);
-    }
-
-    let result = cmd.execute(cc.konanc)?;
-
-    crate::diagnostics::print_diagnostics(&result, cc.options.verbose);
-
-    if !result.success {
-        return Err(EngineError::CompilationFailed {
-            error_count: result.error_count(),
-        });
-    }
-
-    // konanc appends `.kexe` on Linux for programs. Rename to the expected path.
-    // Libraries produce .klib directly, so skip this for library builds.
-    if produce == ProduceKind::Program {
-        normalize_konanc_output(output_path)?;
+) -> Result<(), EngineError> {
+    match cc.command_log {
+        Some(log) => log.record(cc.konanc, cmd, output_path, cc.jre_home),
+        None => Ok(()),
     }
-
-    Ok(output_path.to_path_buf())
 }
 
 /// Invoke konanc and return the path to the compiled artifact.
-fn compile(
+pub(crate) fn compile(
     cc: &CompileContext<'_>,
     sources: &[PathBuf],
     output_path: &Path,
     produce: ProduceKind,
     plugin_jars: &[PathBuf],
+    plugin_options: &[(String, String, String)],
+    toolchain: &Toolchain,
 ) -> Result<PathBuf, EngineError> {
     // Ensure the output directory exists.
     if let Some(parent) = output_path.parent() {
         konvoy_util::fs::ensure_dir(parent)?;
     }
 
-    if needs_two_step_compilation(produce, plugin_jars) {
-        compile_two_step(cc, sources, output_path, plugin_jars)
-    } else {
-        compile_single_step(cc, sources, output_path, produce, plugin_jars)
+    // konanc only consumes paths — strip the precomputed hashes here.
+    let lib_paths = library_paths_of(cc.library_inputs);
+    let settings = KonancSettings {
+        target: *cc.target,
+        release: cc.options.is_release(),
+        language_version: toolchain.language_version.as_deref(),
+        api_version: toolchain.api_version.as_deref(),
+        libraries: &lib_paths,
+        jre_home: cc.jre_home,
+        timeout: cc.timeout,
+        stream_output: cc.options.verbose,
+        deny_warnings: cc.deny_warnings,
+    };
+    run_konanc_steps(
+        &settings,
+        sources,
+        output_path,
+        produce,
+        plugin_jars,
+        plugin_options,
+        |cmd, step_output| {
+            record_command(cc, cmd, step_output)?;
+            let result = cmd.execute(cc.konanc)?;
+            crate::diagnostics::print_diagnostics(&result, cc.options);
+            check_compilation(&result, cc.deny_warnings)?;
+            Ok(result)
+        },
+    )?;
+    Ok(output_path.to_path_buf())
+}
+
+/// Turn a finished `konanc` run into a build error: a failed run, or — when
+/// `deny_warnings` is set — a successful run that reported any warning.
+///
+/// Warnings are checked here rather than by passing `-Werror`, so denying them
+/// works the same for every compile and link step and the reported count
+/// matches the printed diagnostics.
+pub(crate) fn check_compilation(
+    result: &CompilationResult,
+    deny_warnings: bool,
+) -> Result<(), EngineError> {
+    if !result.success {
+        return Err(EngineError::CompilationFailed {
+            error_count: result.error_count(),
+        });
+    }
+    if deny_warnings && result.warning_count() > 0 {
+        return Err(EngineError::WarningsDenied {
+            warning_count: result.warning_count(),
+        });
     }
+    Ok(())
 }
 
 /// Serialize lockfile content for cache key computation.
+///
+/// The konanc fingerprint pin is left out: the compiler fingerprint already
+/// feeds the cache key directly, and backfilling the pin into an older lockfile
+/// must not invalidate every cached artifact. So is the tarball URL: switching
+/// to a toolchain mirror does not change the compiler. Entries are keyed in
+/// canonical order, the order [`Lockfile::write_to`] writes them in.
 pub(crate) fn lockfile_toml_content(lockfile: &Lockfile) -> Result<String, EngineError> {
-    toml::to_string_pretty(lockfile).map_err(|source| EngineError::TomlSerialize {
+    let mut keyed = lockfile.clone();
+    if let Some(tc) = keyed.toolchain.as_mut() {
+        tc.konanc_fingerprint = None;
+        tc.konanc_tarball_url = None;
+    }
+    keyed.canonicalize();
+    toml::to_string_pretty(&keyed).map_err(|source| EngineError::TomlSerialize {
         what: "konvoy.lock",
         source,
     })
 }
 
+/// Serialize the manifest for cache keying, with settings that only change
+/// how the cache stores an artifact (`[build] compress_cache`) reset to
+/// their defaults so toggling them reuses existing entries.
+pub(crate) fn manifest_key_content(manifest: &Manifest) -> Result<String, EngineError> {
+    let mut keyed = manifest.clone();
+    keyed.build.compress_cache = false;
+    Ok(keyed.to_toml()?)
+}
+
 /// Resolve Maven dependencies for the current build target.
 ///
 /// Iterates over all Maven dependency entries in the lockfile (both direct
@@ -1081,6 +1790,30 @@ struct MavenLockView<'a> {
     maven: &'a str,
     targets: &'a std::collections::BTreeMap<String, String>,
     classifier: Option<&'a str>,
+    hash_algorithm: HashAlgorithm,
+}
+
+/// The Maven coordinate of a dependency's per-target klib: the artifact id
+/// gains the target's Maven suffix (`kotlinx-datetime-linuxx64`), and the
+/// classifier, if any, is appended to the file name.
+///
+/// # Errors
+/// Returns an error if `maven` is not a `groupId:artifactId` coordinate.
+pub(crate) fn maven_klib_coordinate(
+    maven: &str,
+    version: &str,
+    classifier: Option<&str>,
+    target: &Target,
+) -> Result<konvoy_util::maven::MavenCoordinate, EngineError> {
+    let (group_id, artifact_id) = crate::common::split_maven_coordinate(maven)?;
+    let per_target_artifact_id = format!("{artifact_id}-{}", target.to_maven_suffix());
+    let coord =
+        konvoy_util::maven::MavenCoordinate::new(group_id, &per_target_artifact_id, version)
+            .with_packaging("klib");
+    Ok(match classifier {
+        Some(cls) => coord.with_classifier(cls),
+        None => coord,
+    })
 }
 
 /// Everything needed to download a single Maven klib for one target.
@@ -1090,7 +1823,8 @@ struct PreparedKlib<'a> {
     entry: &'a MavenLockView<'a>,
     dest: PathBuf,
     url: String,
-    expected_sha256: &'a str,
+    /// The pinned digest, in `entry.hash_algorithm`.
+    expected_digest: &'a str,
     needs_download: bool,
 }
 
@@ -1202,7 +1936,7 @@ fn collect_descendant_deps<'a>(
 /// drop one).
 type MavenCoordKey = (String, String, Option<String>);
 
-/// The coordinate key of a Maven lock entry, or `None` for a path entry.
+/// The coordinate key of a Maven lock entry, or `None` for a path or git entry.
 fn maven_coord_key(dep: &DependencyLock) -> Option<MavenCoordKey> {
     match &dep.source {
         DepSource::Maven {
@@ -1211,7 +1945,7 @@ fn maven_coord_key(dep: &DependencyLock) -> Option<MavenCoordKey> {
             classifier,
             ..
         } => Some((maven.clone(), version.clone(), classifier.clone())),
-        DepSource::Path { .. } => None,
+        DepSource::Path { .. } | DepSource::Git { .. } => None,
     }
 }
 
@@ -1226,9 +1960,9 @@ fn project_maven_closure_coords(manifest: &Manifest, lockfile: &Lockfile) -> Vec
 }
 
 /// Resolve every Maven entry in a lockfile (test convenience). Production builds
-/// resolve per-project subsets via [`project_maven_closure`] +
-/// [`resolve_maven_klibs`]; this whole-lockfile wrapper keeps the focused
-/// download/offline/hash tests independent of closure computation.
+/// fetch the graph-wide union together with the plugin JARs via
+/// [`fetch_plugins_and_klibs`]; this klib-only wrapper keeps the focused
+/// download/offline/hash tests independent of plugin resolution.
 #[cfg(test)]
 fn resolve_maven_deps(
     lockfile: &Lockfile,
@@ -1236,20 +1970,155 @@ fn resolve_maven_deps(
     resolver: crate::common::ArtifactResolver<'_>,
 ) -> Result<Vec<LibraryInput>, EngineError> {
     let entries: Vec<&DependencyLock> = lockfile.dependencies.iter().collect();
-    resolve_maven_klibs(&entries, target, resolver)
+    let (_, klibs) = fetch_plugins_and_klibs(
+        &[],
+        &entries,
+        lockfile,
+        target,
+        &crate::progress::StderrProgress,
+        resolver,
+    )?;
+    Ok(klibs)
 }
 
-/// Download the per-target klibs for a specific set of lockfile Maven entries
-/// (a project's [`project_maven_closure`]). Non-Maven entries are ignored.
-pub(crate) fn resolve_maven_klibs(
-    entries: &[&DependencyLock],
+/// Most artifacts fetched at once by [`fetch_plugins_and_klibs`]. Downloads are
+/// network-bound, so the pool is sized for a fair share of a connection rather
+/// than for the CPU count.
+const MAX_CONCURRENT_DOWNLOADS: usize = 8;
+
+/// One artifact in the combined plugin + Maven klib work list.
+enum FetchJob<'a> {
+    Plugin(&'a crate::plugin::ResolvedPluginArtifact),
+    Klib(PreparedKlib<'a>),
+}
+
+/// What a [`FetchJob`] resolved to.
+enum Fetched {
+    Plugin(crate::plugin::PluginArtifactResult),
+    Klib(LibraryInput),
+}
+
+/// Fetch the graph's compiler-plugin artifacts and the per-target klibs of the
+/// given lockfile Maven entries (non-Maven entries are ignored) as ONE work list.
+///
+/// Every artifact is gated through the shared `--locked` / `--offline` policy up
+/// front, then the whole list runs through a single pool of at most
+/// [`MAX_CONCURRENT_DOWNLOADS`] workers behind one combined progress display, so
+/// a slow plugin never holds back the klibs (or vice versa). Each artifact is
+/// still hash-verified on its own. Results come back in input order: plugins in
+/// `plugin_artifacts` order, klibs in Maven-entry order.
+///
+/// # Errors
+/// Returns the plugin policy errors of [`crate::plugin::ensure_plugin_artifacts`],
+/// [`EngineError::MissingTargetHash`] when a Maven entry has no hash for
+/// `target`, or the first download/hash error in input order.
+pub(crate) fn fetch_plugins_and_klibs(
+    plugin_artifacts: &[crate::plugin::ResolvedPluginArtifact],
+    maven_entries: &[&DependencyLock],
+    lockfile: &Lockfile,
     target: &Target,
+    progress: &dyn ProgressSink,
     resolver: crate::common::ArtifactResolver<'_>,
-) -> Result<Vec<LibraryInput>, EngineError> {
-    use rayon::prelude::IndexedParallelIterator;
+) -> Result<(Vec<crate::plugin::PluginArtifactResult>, Vec<LibraryInput>), EngineError> {
+    use rayon::prelude::IntoParallelIterator;
+
+    // Resolve plugin state once; the resolver owns locked/offline policy.
+    let plugins_present = resolver.prepare_plugin_artifacts(plugin_artifacts, lockfile)?;
+    let maven_locks = maven_lock_views(maven_entries);
+    let klibs = prepare_klibs(&maven_locks, target)?;
+
+    let mut jobs: Vec<(FetchJob<'_>, Option<String>)> = plugin_artifacts
+        .iter()
+        .zip(plugins_present)
+        .map(|(artifact, is_present)| {
+            let label = (!is_present)
+                .then(|| format!("{} {}", artifact.plugin_name, artifact.maven_coord.version));
+            (FetchJob::Plugin(artifact), label)
+        })
+        .collect();
+    jobs.extend(klibs.into_iter().map(|klib| {
+        let label = klib
+            .needs_download
+            .then(|| format!("{} {}", klib.entry.name, klib.entry.version));
+        (FetchJob::Klib(klib), label)
+    }));
+    if jobs.is_empty() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+
+    // Only allocate bars for artifacts that actually need a network fetch —
+    // cached items re-verify their hash silently. All bars share one
+    // `MultiProgress`, so plugins and klibs render as a single block.
+    let download_labels: Vec<String> = jobs.iter().filter_map(|(_, l)| l.clone()).collect();
+    for (job, _) in jobs.iter().filter(|(_, label)| label.is_some()) {
+        progress.on_download(match job {
+            FetchJob::Plugin(artifact) => &artifact.url,
+            FetchJob::Klib(klib) => &klib.url,
+        });
+    }
+    let any_downloads = !download_labels.is_empty();
+    let bars: Vec<konvoy_util::progress::DownloadBar> = if any_downloads {
+        konvoy_util::progress::pre_allocate_bars(download_labels).1
+    } else {
+        Vec::new()
+    };
+    let mut bar_iter = bars.iter();
+    let work: Vec<(FetchJob<'_>, Option<&konvoy_util::progress::DownloadBar>)> = jobs
+        .into_iter()
+        .map(|(job, label)| {
+            let bar = label.and_then(|_| bar_iter.next());
+            (job, bar)
+        })
+        .collect();
+
+    let run = || -> Vec<Result<Fetched, EngineError>> {
+        work.into_par_iter()
+            .map(|(job, maybe_bar)| match job {
+                FetchJob::Plugin(artifact) => resolver
+                    .resolve_plugin_artifact(artifact, lockfile, maybe_bar)
+                    .map(Fetched::Plugin),
+                FetchJob::Klib(p) => resolver
+                    .resolve_maven_klib(
+                        p.entry.name,
+                        &p.url,
+                        &p.dest,
+                        p.expected_digest,
+                        p.entry.hash_algorithm,
+                        maybe_bar,
+                    )
+                    .map(Fetched::Klib),
+            })
+            .collect()
+    };
+    // A dedicated pool bounds concurrency independently of the CPU count; if it
+    // cannot be built, the global pool is a safe (if differently sized) fallback.
+    let fetched = match rayon::ThreadPoolBuilder::new()
+        .num_threads(MAX_CONCURRENT_DOWNLOADS)
+        .build()
+    {
+        Ok(pool) => pool.install(run),
+        Err(_) => run(),
+    };
+
+    // Trailing newline only if we actually rendered any bars.
+    if any_downloads {
+        eprintln!();
+    }
+
+    let mut plugin_results = Vec::new();
+    let mut klib_inputs = Vec::new();
+    for result in fetched {
+        match result? {
+            Fetched::Plugin(r) => plugin_results.push(r),
+            Fetched::Klib(k) => klib_inputs.push(k),
+        }
+    }
+    Ok((plugin_results, klib_inputs))
+}
 
-    // Filter to Maven entries AND extract their fields in one pass.
-    let maven_locks: Vec<MavenLockView> = entries
+/// The Maven entries among `entries`, with their fields borrowed out.
+fn maven_lock_views<'a>(entries: &[&'a DependencyLock]) -> Vec<MavenLockView<'a>> {
+    entries
         .iter()
         .filter_map(|d| match &d.source {
             DepSource::Maven {
@@ -1257,6 +2126,7 @@ pub(crate) fn resolve_maven_klibs(
                 maven,
                 targets,
                 classifier,
+                hash_algorithm,
                 ..
             } => Some(MavenLockView {
                 name: &d.name,
@@ -1264,110 +2134,75 @@ pub(crate) fn resolve_maven_klibs(
                 maven,
                 targets,
                 classifier: classifier.as_deref(),
+                hash_algorithm: *hash_algorithm,
             }),
-            DepSource::Path { .. } => None,
+            DepSource::Path { .. } | DepSource::Git { .. } => None,
         })
-        .collect();
+        .collect()
+}
 
+/// Compute coord/dest/url/expected-hash and check existence for every entry.
+/// Sequential because these are cheap; doing it up front lets the caller skip
+/// bar creation for cached items.
+fn prepare_klibs<'a>(
+    maven_locks: &'a [MavenLockView<'a>],
+    target: &Target,
+) -> Result<Vec<PreparedKlib<'a>>, EngineError> {
     if maven_locks.is_empty() {
         return Ok(Vec::new());
     }
 
     let cache_root = crate::plugin::maven_cache_root()?;
     let target_str = target.to_konanc_arg();
-    let maven_suffix = target.to_maven_suffix();
 
-    // Compute coord/dest/url/expected-hash and check existence for every
-    // entry. Sequential because the input is small and these are cheap;
-    // doing it upfront lets us skip bar creation for cached items.
-    let prepared: Vec<PreparedKlib> =
-        maven_locks
-            .iter()
-            .map(|entry| {
-                let (group_id, artifact_id) = crate::common::split_maven_coordinate(entry.maven)?;
-                let per_target_artifact_id = format!("{artifact_id}-{maven_suffix}");
-                let mut coord = konvoy_util::maven::MavenCoordinate::new(
-                    group_id,
-                    &per_target_artifact_id,
-                    entry.version,
-                )
-                .with_packaging("klib");
-                if let Some(cls) = entry.classifier {
-                    coord = coord.with_classifier(cls);
-                }
-                let expected_sha256 = entry.targets.get(target_str).ok_or_else(|| {
-                    EngineError::MissingTargetHash {
+    maven_locks
+        .iter()
+        .map(|entry| {
+            let coord =
+                maven_klib_coordinate(entry.maven, entry.version, entry.classifier, target)?;
+            let expected_digest =
+                entry
+                    .targets
+                    .get(target_str)
+                    .ok_or_else(|| EngineError::MissingTargetHash {
                         name: entry.name.to_owned(),
                         target: target_str.to_owned(),
-                    }
-                })?;
-                let dest = coord.cache_path(&cache_root);
-                let url = coord.to_url(konvoy_util::maven::MAVEN_CENTRAL);
-                let needs_download = !dest.exists();
-                Ok(PreparedKlib {
-                    entry,
-                    dest,
-                    url,
-                    expected_sha256,
-                    needs_download,
-                })
+                    })?;
+            let dest = coord.cache_path(&cache_root);
+            let url = coord.to_url(konvoy_util::maven::MAVEN_CENTRAL);
+            let needs_download = !dest.exists();
+            Ok(PreparedKlib {
+                entry,
+                dest,
+                url,
+                expected_digest,
+                needs_download,
             })
-            .collect::<Result<Vec<_>, EngineError>>()?;
-
-    // Only allocate bars for entries that actually need a network fetch;
-    // cached entries are silent. The `aligned_bars` Vec parallels `prepared`
-    // so the par_iter zip pairs each entry with its bar (or None).
-    let download_labels: Vec<String> = prepared
-        .iter()
-        .filter(|p| p.needs_download)
-        .map(|p| format!("{} {}", p.entry.name, p.entry.version))
-        .collect();
-    let any_downloads = !download_labels.is_empty();
-    let bars: Vec<konvoy_util::progress::DownloadBar> = if any_downloads {
-        konvoy_util::progress::pre_allocate_bars(download_labels).1
-    } else {
-        Vec::new()
-    };
-    let mut bar_iter = bars.iter();
-    let aligned_bars: Vec<Option<&konvoy_util::progress::DownloadBar>> = prepared
-        .iter()
-        .map(|p| {
-            if p.needs_download {
-                bar_iter.next()
-            } else {
-                None
-            }
         })
-        .collect();
-
-    let klib_inputs: Vec<Result<LibraryInput, EngineError>> = prepared
-        .par_iter()
-        .zip(aligned_bars.par_iter())
-        .map(|(p, maybe_bar)| download_one_klib(p, *maybe_bar, resolver))
-        .collect();
-
-    // Trailing newline only if we actually rendered any bars.
-    if any_downloads {
-        eprintln!();
-    }
-
-    klib_inputs.into_iter().collect()
+        .collect()
 }
 
-/// Resolve a single prepared klib via [`konvoy_util::progress::fetch`]
-/// (cache check + atomic download). The supplied bar renders progress on
-/// the download path; cache-hit entries pass `None` and produce no UI.
-fn download_one_klib(
-    p: &PreparedKlib<'_>,
-    maybe_bar: Option<&konvoy_util::progress::DownloadBar>,
-    resolver: crate::common::ArtifactResolver<'_>,
-) -> Result<LibraryInput, EngineError> {
-    resolver.resolve_maven_klib(p.entry.name, &p.url, &p.dest, p.expected_sha256, maybe_bar)
+/// The Maven klibs among `entries` that are not in the local cache for
+/// `target` yet, as `name version` labels.
+///
+/// # Errors
+/// Returns an error if an entry has no hash for `target` or a malformed
+/// coordinate, or the cache root cannot be determined.
+pub(crate) fn missing_klibs(
+    entries: &[&DependencyLock],
+    target: &Target,
+) -> Result<Vec<String>, EngineError> {
+    let views = maven_lock_views(entries);
+    Ok(prepare_klibs(&views, target)?
+        .into_iter()
+        .filter(|klib| klib.needs_download)
+        .map(|klib| format!("{} {}", klib.entry.name, klib.entry.version))
+        .collect())
 }
 
 /// Returns the konvoy key of the first manifest Maven dep that has no matching
-/// lockfile pin (by COORDINATE + version), or `None` when everything is
-/// resolved. Used to trigger the automatic `konvoy update` during build — or,
+/// lockfile pin (by COORDINATE + a version satisfying the declared version or
+/// range), or `None` when everything is resolved. Used to trigger the automatic `konvoy update` during build — or,
 /// under `--offline`, to refuse it with the offending dependency named.
 ///
 /// Coordinate matching (not name) lets a path-dep's Maven dep be recognized as
@@ -1379,10 +2214,12 @@ pub(crate) fn first_unresolved_maven_dep(
     manifest
         .dependencies
         .iter()
-        .find(|(_, spec)| {
-            spec.as_maven_coord()
-                .is_some_and(|(maven, version)| !lockfile.has_maven_coord(maven, version))
-        })
+        .find(
+            |(_, spec)| match (spec.maven.as_deref(), spec.version_req()) {
+                (Some(maven), Some(req)) => !lockfile.has_maven_coord_matching(maven, &req),
+                _ => false,
+            },
+        )
         .map(|(name, _)| name.clone())
 }
 
@@ -1399,16 +2236,79 @@ pub(crate) fn first_unresolved_graph_maven_dep<'a>(
         .find_map(|m| first_unresolved_maven_dep(m, lockfile))
 }
 
-/// Check that the lockfile is complete and consistent with the manifest.
-///
-/// This is the staleness check for `--locked` mode. It catches cases where
-/// the lockfile is missing entries that `konvoy.toml` would generate:
-/// - Missing or mismatched toolchain version
-/// - Missing detekt entries when detekt is configured in the manifest
-/// - Missing plugin entries when plugins are configured in the manifest
-/// - Missing Maven dependency entries
+/// Split off the lockfile entries that no project in the build graph declares
+/// any more: path/git dependencies no `[dependencies]` table names (a disabled
+/// optional one keeps its pin), Maven entries outside
+/// every project's [`project_maven_closure`] (so a removed dependency takes its
+/// transitives with it), and plugins whose coordinate no `[plugins]` table names.
 ///
-/// Runs before any build work so users get fast, clear feedback.
+/// Returns the lockfile without those entries plus a description of each one
+/// dropped, e.g. ``dependency `ktor-client` ``. The toolchain section and codegen
+/// pins are left untouched.
+pub(crate) fn prune_stale_lock_entries(
+    manifest: &Manifest,
+    dep_graph: &ResolvedGraph,
+    lockfile: &Lockfile,
+) -> (Lockfile, Vec<String>) {
+    use std::collections::BTreeSet;
+
+    let manifests: Vec<&Manifest> = std::iter::once(manifest)
+        .chain(dep_graph.order.iter().map(|d| &d.manifest))
+        .collect();
+    // Path and git deps stay pinned while any manifest declares them, even an
+    // optional one no enabled feature pulls into `dep_graph`.
+    let declared_deps: BTreeSet<&str> = manifests
+        .iter()
+        .flat_map(|m| m.dependencies.iter())
+        .filter(|(_, spec)| spec.path.is_some() || spec.git.is_some())
+        .map(|(name, _)| name.as_str())
+        .chain(dep_graph.order.iter().map(|d| d.name.as_str()))
+        .collect();
+    let live_maven: BTreeSet<&str> = manifests
+        .iter()
+        .flat_map(|m| project_maven_closure(m, lockfile))
+        .map(|d| d.name.as_str())
+        .collect();
+    let declared_plugins: BTreeSet<&str> = manifests
+        .iter()
+        .flat_map(|m| m.plugins.values())
+        .filter_map(|spec| spec.maven.as_deref())
+        .collect();
+
+    let mut pruned = lockfile.clone();
+    let mut stale = Vec::new();
+    pruned.dependencies.retain(|dep| {
+        let live = match &dep.source {
+            DepSource::Maven { .. } => live_maven.contains(dep.name.as_str()),
+            DepSource::Path { .. } | DepSource::Git { .. } => {
+                declared_deps.contains(dep.name.as_str())
+            }
+        };
+        if !live {
+            stale.push(format!("dependency `{}`", dep.name));
+        }
+        live
+    });
+    pruned.plugins.retain(|plugin| {
+        let live = declared_plugins.contains(plugin.maven.as_str());
+        if !live {
+            stale.push(format!("plugin `{}`", plugin.name));
+        }
+        live
+    });
+    (pruned, stale)
+}
+
+/// Check that the lockfile is complete and consistent with the manifest.
+///
+/// This is the staleness check for `--locked` mode. It catches cases where
+/// the lockfile is missing entries that `konvoy.toml` would generate:
+/// - Missing or mismatched toolchain version
+/// - Missing detekt or ktlint entries when configured in the manifest
+/// - Missing plugin entries when plugins are configured in the manifest
+/// - Missing Maven dependency entries
+///
+/// Runs before any build work so users get fast, clear feedback.
 ///
 /// # Errors
 /// Returns `EngineError::LockfileUpdateRequired` when the lockfile is missing
@@ -1422,7 +2322,9 @@ pub(crate) fn check_lockfile_staleness(
         return Err(EngineError::LockfileUpdateRequired);
     };
 
-    if tc.konanc_version != manifest.toolchain.kotlin {
+    if tc.konanc_version != manifest.toolchain.kotlin
+        || crate::common::locked_jre_major(tc) != crate::common::jre_major(&manifest.toolchain)
+    {
         return Err(EngineError::LockfileUpdateRequired);
     }
 
@@ -1433,6 +2335,13 @@ pub(crate) fn check_lockfile_staleness(
         }
     }
 
+    // Likewise for ktlint, which `konvoy fmt` records.
+    if let Some(manifest_ktlint) = &manifest.toolchain.ktlint {
+        if tc.ktlint_version.as_deref() != Some(manifest_ktlint.as_str()) {
+            return Err(EngineError::LockfileUpdateRequired);
+        }
+    }
+
     // Each declared plugin must have a *pinned* lockfile entry for its resolved
     // `(name, maven, version)` identity. Use the same lookup the resolver gate
     // uses, so this fast-fail and the per-artifact gate agree on what "pinned"
@@ -1503,12 +2412,18 @@ pub(crate) fn check_graph_lockfile_staleness<'a>(
 /// or if a fresh download provides new tarball hashes or plugin artifacts changed.
 /// When the lockfile already contains hashes and a fresh download yields different
 /// ones, emit a warning.
+///
+/// `konanc` is `None` when the compiler is a `KONVOY_KONANC` override: it is not
+/// the toolchain `konvoy.lock` pins, so `[toolchain]` is left untouched and only
+/// the dependency, plugin, and codegen entries are updated.
 #[allow(clippy::too_many_arguments)]
 fn update_lockfile_if_needed(
     lockfile: &Lockfile,
-    konanc: &KonancInfo,
+    konanc: Option<&KonancInfo>,
     konanc_tarball_sha256: Option<&str>,
+    konanc_tarball_url: Option<&str>,
     jre_tarball_sha256: Option<&str>,
+    jre_major: u32,
     dep_graph: &ResolvedGraph,
     plugin_locks: &[konvoy_config::lockfile::PluginLock],
     codegen_locks: &[konvoy_config::lockfile::CodegenToolLock],
@@ -1516,6 +2431,7 @@ fn update_lockfile_if_needed(
     lockfile_path: &Path,
     force: bool,
     resolver: crate::common::ArtifactResolver<'_>,
+    progress: &dyn ProgressSink,
 ) -> Result<(), EngineError> {
     // Check for dependency source hash mismatches.
     // In --locked mode this is a hard error; otherwise warn and continue.
@@ -1527,12 +2443,12 @@ fn update_lockfile_if_needed(
                     &locked_dep.source_hash,
                     &dep.source_hash,
                 )?;
-                eprintln!(
-                    "warning: dependency `{}` source has changed (locked: {}, current: {})",
+                progress.on_warning(&format!(
+                    "dependency `{}` source has changed (locked: {}, current: {})",
                     dep.name,
                     crate::common::truncate_hash(&locked_dep.source_hash, 8),
                     crate::common::truncate_hash(&dep.source_hash, 8),
-                );
+                ));
             }
         }
     }
@@ -1542,12 +2458,37 @@ fn update_lockfile_if_needed(
     // in `predicted_effective_lockfile` so the two stay in lockstep).
     let new_deps = predicted_dependency_locks(lockfile, dep_graph, project_root);
 
+    let Some(konanc) = konanc else {
+        let mut updated = lockfile.clone();
+        updated.dependencies = new_deps;
+        updated.plugins = plugin_locks.to_vec();
+        updated.codegen_tools = codegen_locks.to_vec();
+        if updated == *lockfile {
+            return Ok(());
+        }
+        return resolver.persist_resolved_artifacts(lockfile, &updated, lockfile_path);
+    };
+
     let toolchain_changed = match &lockfile.toolchain {
-        Some(tc) => tc.konanc_version != konanc.version,
+        Some(tc) => {
+            tc.konanc_version != konanc.version || crate::common::locked_jre_major(tc) != jre_major
+        }
         None => true,
     };
 
     let has_new_hashes = konanc_tarball_sha256.is_some() || jre_tarball_sha256.is_some();
+
+    // The konanc fingerprint lets `konvoy doctor` / `konvoy toolchain verify`
+    // detect a modified install. A new version records the current fingerprint;
+    // otherwise an existing pin is kept — a mismatch is reported by those
+    // commands, never silently re-recorded — and a missing one is backfilled.
+    let recorded_fingerprint = lockfile.konanc_fingerprint(&konanc.version);
+    let final_fingerprint = if toolchain_changed {
+        Some(konanc.launcher_fingerprint.clone())
+    } else {
+        resolver.backfill_pin(recorded_fingerprint, &konanc.launcher_fingerprint)
+    };
+    let fingerprint_changed = final_fingerprint.as_deref() != recorded_fingerprint;
     let deps_changed = lockfile.dependencies != new_deps;
     let plugins_changed = lockfile.plugins.as_slice() != plugin_locks;
     let codegen_changed = lockfile.codegen_tools.as_slice() != codegen_locks;
@@ -1555,6 +2496,7 @@ fn update_lockfile_if_needed(
     // If nothing changed, nothing to do.
     if !toolchain_changed
         && !has_new_hashes
+        && !fingerprint_changed
         && !deps_changed
         && !plugins_changed
         && !codegen_changed
@@ -1571,7 +2513,9 @@ fn update_lockfile_if_needed(
         &konanc.version,
         konanc_tarball_sha256,
         jre_tarball_sha256,
+        jre_major,
         force,
+        progress,
     )?;
 
     // Build the candidate lockfile that a write would produce. When the version
@@ -1606,30 +2550,52 @@ fn update_lockfile_if_needed(
         final_konanc_sha.as_deref(),
         final_jre_sha.as_deref(),
     );
+    if let Some(tc) = updated.toolchain.as_mut() {
+        tc.konanc_fingerprint = final_fingerprint;
+        // The URL travels with the tarball hash it was downloaded for.
+        tc.konanc_tarball_url = match konanc_tarball_url {
+            Some(url) => Some(url.to_owned()),
+            None if toolchain_changed => None,
+            None => lockfile
+                .toolchain
+                .as_ref()
+                .and_then(|old| old.konanc_tarball_url.clone()),
+        };
+        // A new toolchain section records the JRE it pins; an unchanged one
+        // keeps whatever it had, so older lockfiles are not rewritten.
+        tc.jre_major = if toolchain_changed {
+            crate::common::recorded_jre_major(jre_major)
+        } else {
+            lockfile.toolchain.as_ref().and_then(|old| old.jre_major)
+        };
+    }
     updated.dependencies = new_deps;
     updated.plugins = plugin_locks.to_vec();
     updated.codegen_tools = codegen_locks.to_vec();
-    carry_forward_detekt(&mut updated, lockfile);
+    carry_forward_tool_pins(&mut updated, lockfile);
 
     resolver.persist_resolved_artifacts(lockfile, &updated, lockfile_path)
 }
 
-/// Carry the detekt pin from `original` onto a freshly-rebuilt toolchain section.
+/// Carry the detekt and ktlint pins from `original` onto a freshly-rebuilt
+/// toolchain section.
 ///
-/// `Lockfile::with_managed_toolchain` produces a toolchain section with no detekt
-/// fields. `konvoy build` does not manage detekt (it is written by `konvoy lint`),
-/// so every site that rebuilds the toolchain for a (possibly new) konanc version
-/// must restore the existing detekt pin. Otherwise a toolchain-changing build
+/// `Lockfile::with_managed_toolchain` produces a toolchain section with no tool
+/// fields. `konvoy build` does not manage detekt or ktlint (they are written by
+/// `konvoy lint` and `konvoy fmt`), so every site that rebuilds the toolchain for
+/// a (possibly new) konanc version must restore the existing pins. Otherwise a toolchain-changing build
 /// silently wipes detekt from the written lockfile AND the predicted cache-key
 /// lockfile and the written lockfile disagree, forcing a spurious recompile for
 /// detekt projects (issue #133 class). Centralized here so both call sites —
 /// `predicted_effective_lockfile` and `update_lockfile_if_needed` — stay in lockstep.
-fn carry_forward_detekt(rebuilt: &mut Lockfile, original: &Lockfile) {
+fn carry_forward_tool_pins(rebuilt: &mut Lockfile, original: &Lockfile) {
     if let (Some(rebuilt_tc), Some(orig_tc)) =
         (rebuilt.toolchain.as_mut(), original.toolchain.as_ref())
     {
         rebuilt_tc.detekt_version = orig_tc.detekt_version.clone();
         rebuilt_tc.detekt_jar_sha256 = orig_tc.detekt_jar_sha256.clone();
+        rebuilt_tc.ktlint_version = orig_tc.ktlint_version.clone();
+        rebuilt_tc.ktlint_jar_sha256 = orig_tc.ktlint_jar_sha256.clone();
     }
 }
 
@@ -1677,19 +2643,31 @@ fn portable_dep_path(project_root: &Path, dep_root: &Path) -> String {
 /// `update_lockfile_if_needed`. Only compares when the lockfile pins the SAME
 /// toolchain version as the one just resolved: pins for a different version
 /// belong to a different tarball (e.g. the user bumped `kotlin` in konvoy.toml)
-/// and are replaced by the lockfile write, not validated here. `None` actuals
+/// and are replaced by the lockfile write, not validated here; the same holds
+/// for a JRE pin recorded for a different JDK major. `None` actuals
 /// (cached install, no fresh download) are skipped by `verify_tarball_hash`.
 fn verify_toolchain_tarball_pins(
     lockfile: &Lockfile,
     resolved_version: &str,
     konanc_sha: Option<&str>,
     jre_sha: Option<&str>,
+    jre_major: u32,
     force: bool,
+    progress: &dyn ProgressSink,
 ) -> Result<(), EngineError> {
     if let Some(tc) = &lockfile.toolchain {
         if tc.konanc_version == resolved_version {
-            verify_tarball_hash("konanc", &tc.konanc_tarball_sha256, konanc_sha, force)?;
-            verify_tarball_hash("jre", &tc.jre_tarball_sha256, jre_sha, force)?;
+            verify_tarball_hash(
+                "konanc",
+                &tc.konanc_tarball_sha256,
+                konanc_sha,
+                force,
+                progress,
+            )?;
+            // A JRE pin for another JDK major belongs to a different tarball.
+            if crate::common::locked_jre_major(tc) == jre_major {
+                verify_tarball_hash("jre", &tc.jre_tarball_sha256, jre_sha, force, progress)?;
+            }
         }
     }
     Ok(())
@@ -1705,13 +2683,14 @@ fn verify_tarball_hash(
     existing: &Option<String>,
     actual: Option<&str>,
     force: bool,
+    progress: &dyn ProgressSink,
 ) -> Result<(), EngineError> {
     if let (Some(existing), Some(actual)) = (existing, actual) {
         if !existing.is_empty() && existing != actual {
             if force {
-                eprintln!(
-                    "warning: {kind} tarball hash changed — expected {existing}, got {actual}; lockfile updated (--force)"
-                );
+                progress.on_warning(&format!(
+                    "{kind} tarball hash changed — expected {existing}, got {actual}; lockfile updated (--force)"
+                ));
             } else {
                 return Err(EngineError::TarballHashMismatch {
                     kind: kind.to_owned(),
@@ -1745,6 +2724,8 @@ pub(crate) fn normalize_konanc_output(output_path: &Path) -> Result<(), EngineEr
 #[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
+    use konvoy_konanc::invoke::{Diagnostic, DiagnosticLevel};
+    use konvoy_konanc::toolchain::DEFAULT_JRE_MAJOR;
     use std::fs;
 
     #[test]
@@ -1811,6 +2792,68 @@ mod tests {
         assert!(target.is_err());
     }
 
+    #[test]
+    fn resolve_target_windows_is_not_yet_supported() {
+        let err = resolve_target(&Some("windows_x64".to_owned())).unwrap_err();
+        assert!(
+            matches!(err, EngineError::TargetNotYetSupported { ref target } if target == "windows_x64"),
+            "got: {err}"
+        );
+        assert!(err.to_string().contains("not supported yet"), "got: {err}");
+    }
+
+    #[test]
+    fn resolve_target_all_is_only_for_build() {
+        let err = resolve_target(&Some(ALL_TARGETS.to_owned())).unwrap_err();
+        assert!(
+            matches!(err, EngineError::AllTargetsUnsupported),
+            "got: {err}"
+        );
+        assert!(err.to_string().contains("konvoy build"), "got: {err}");
+    }
+
+    #[test]
+    fn cross_compile_preflight_native_is_silent() {
+        assert!(cross_compile_preflight(Target::LinuxX64, Target::LinuxX64)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn cross_compile_preflight_warns_for_foreign_target() {
+        let warning = cross_compile_preflight(Target::LinuxArm64, Target::MacOsArm64)
+            .unwrap()
+            .unwrap();
+        assert!(warning.contains("linux_arm64"), "got: {warning}");
+        assert!(warning.contains("SDK"), "got: {warning}");
+    }
+
+    #[test]
+    fn cross_compile_preflight_rejects_apple_target_off_macos() {
+        let err = cross_compile_preflight(Target::MacOsArm64, Target::LinuxX64).unwrap_err();
+        assert!(
+            matches!(err, EngineError::CrossCompileUnsupported { .. }),
+            "got: {err}"
+        );
+        assert!(err.to_string().contains("macos_arm64"));
+        assert!(err.to_string().contains("macOS SDK"), "got: {err}");
+    }
+
+    #[test]
+    fn cross_compile_preflight_reason_follows_the_target() {
+        let err = cross_compile_preflight(Target::WindowsX64, Target::LinuxX64).unwrap_err();
+        assert!(
+            matches!(err, EngineError::CrossCompileUnsupported { .. }),
+            "got: {err}"
+        );
+        let message = err.to_string();
+        assert!(
+            message.starts_with("cannot build for windows_x64 on a linux_x64 host"),
+            "got: {message}"
+        );
+        assert!(!message.contains("macOS"), "got: {message}");
+    }
+
     #[test]
     fn build_fails_without_manifest() {
         let tmp = tempfile::tempdir().unwrap();
@@ -1818,7 +2861,20 @@ mod tests {
             target: None,
             profile: Profile::Debug,
             verbose: false,
+            quiet: false,
             force: false,
+            timeout: None,
+            dedupe_diagnostics: false,
+            color: ColorChoice::Never,
+            timings: false,
+            features: Vec::new(),
+            no_default_features: false,
+            deny_warnings: false,
+            emit_command: None,
+            out_dir: None,
+            target_dir: None,
+            keep_going: false,
+            progress: Arc::new(StderrProgress),
         };
         let result = build(
             tmp.path(),
@@ -1865,6 +2921,59 @@ mod tests {
         );
     }
 
+    /// Records every warning it receives.
+    #[derive(Debug, Default)]
+    struct RecordingProgress {
+        warnings: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl ProgressSink for RecordingProgress {
+        fn on_warning(&self, message: &str) {
+            self.warnings.lock().unwrap().push(message.to_owned());
+        }
+    }
+
+    #[test]
+    fn build_reports_warnings_to_progress_sink() {
+        // A foreign Linux target warns about cross-compiling before toolchain
+        // resolution, which then fails offline on a version that is never installed.
+        let kotlin_version = "0.0.0-progress-test";
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src").join("main.kt"), "fun main() {}\n").unwrap();
+        fs::write(
+            root.join("konvoy.toml"),
+            format!("[package]\nname = \"demo\"\n\n[toolchain]\nkotlin = \"{kotlin_version}\"\n"),
+        )
+        .unwrap();
+        Lockfile::with_toolchain(kotlin_version)
+            .write_to(&root.join("konvoy.lock"))
+            .unwrap();
+
+        let foreign = if host_target().unwrap() == Target::LinuxX64 {
+            Target::LinuxArm64
+        } else {
+            Target::LinuxX64
+        };
+        let progress = Arc::new(RecordingProgress::default());
+        let options = BuildOptions {
+            target: Some(foreign.to_string()),
+            progress: progress.clone(),
+            ..BuildOptions::default()
+        };
+        let result = build(root, &options, crate::common::test_resolver(true, false));
+
+        assert!(result.is_err(), "expected Err, got: {result:?}");
+        let warnings = progress.warnings.lock().unwrap();
+        assert!(
+            warnings
+                .iter()
+                .any(|w| w.starts_with(&format!("cross-compiling for {foreign}"))),
+            "{warnings:?}"
+        );
+    }
+
     #[test]
     fn build_locked_errors_on_toolchain_drift() {
         // --locked's real failure mode is lockfile drift, NOT a missing download.
@@ -2008,7 +3117,20 @@ mod tests {
             target: None,
             profile: Profile::Debug,
             verbose: false,
+            quiet: false,
             force: false,
+            timeout: None,
+            dedupe_diagnostics: false,
+            color: ColorChoice::Never,
+            timings: false,
+            features: Vec::new(),
+            no_default_features: false,
+            deny_warnings: false,
+            emit_command: None,
+            out_dir: None,
+            target_dir: None,
+            keep_going: false,
+            progress: Arc::new(StderrProgress),
         };
         let result = build(
             &project,
@@ -2027,7 +3149,7 @@ mod tests {
     fn lockfile_toml_content_empty() {
         let lockfile = Lockfile::default();
         let content = lockfile_toml_content(&lockfile).unwrap();
-        assert!(content.contains("toolchain") || content.is_empty() || content.trim().is_empty());
+        assert_eq!(content.trim(), "version = 1");
     }
 
     #[test]
@@ -2084,14 +3206,17 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             None,
             None,
+            None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &[],
@@ -2099,6 +3224,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
         assert!(lockfile_path.exists());
@@ -2117,15 +3243,18 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         // Should not error and should not change the file.
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
+            None,
             None,
             None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &[],
@@ -2133,6 +3262,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
     }
@@ -2148,14 +3278,17 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             Some("deadbeef"),
+            None,
             Some("cafebabe"),
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &[],
@@ -2163,12 +3296,82 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
         let content = fs::read_to_string(&lockfile_path).unwrap();
         assert!(content.contains("2.1.0"));
     }
 
+    #[test]
+    fn update_lockfile_records_tarball_url_and_keeps_it() {
+        let tmp = tempfile::tempdir().unwrap();
+        let lockfile_path = tmp.path().join("konvoy.lock");
+        let konanc = KonancInfo {
+            path: PathBuf::from("/usr/bin/konanc"),
+            version: "2.1.0".to_owned(),
+            fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
+        };
+        let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
+        let url =
+            "https://mirror.example.com/v2.1.0/kotlin-native-prebuilt-linux-x86_64-2.1.0.tar.gz";
+        let update = |lockfile: &Lockfile, sha: Option<&str>, url: Option<&str>| {
+            update_lockfile_if_needed(
+                lockfile,
+                Some(&konanc),
+                sha,
+                url,
+                None,
+                DEFAULT_JRE_MAJOR,
+                &empty_graph,
+                &[],
+                &[],
+                tmp.path(),
+                &lockfile_path,
+                false,
+                crate::common::test_resolver(false, false),
+                &StderrProgress,
+            )
+            .unwrap();
+            Lockfile::from_path(&lockfile_path).unwrap()
+        };
+
+        let first = update(&Lockfile::default(), Some("deadbeef"), Some(url));
+        let recorded = |lockfile: &Lockfile| {
+            lockfile
+                .toolchain
+                .as_ref()
+                .and_then(|tc| tc.konanc_tarball_url.clone())
+        };
+        assert_eq!(recorded(&first).as_deref(), Some(url));
+
+        // A later build without a download keeps the recorded URL.
+        let mut changed = first.clone();
+        changed.dependencies.push(DependencyLock {
+            name: "stale".to_owned(),
+            source: DepSource::Path {
+                path: "stale".to_owned(),
+            },
+            source_hash: "0".to_owned(),
+        });
+        let second = update(&changed, None, None);
+        assert_eq!(recorded(&second).as_deref(), Some(url));
+    }
+
+    #[test]
+    fn lockfile_toml_content_ignores_tarball_url() {
+        let without = Lockfile::with_managed_toolchain("2.1.0", Some("abc"), None);
+        let mut with = without.clone();
+        if let Some(tc) = with.toolchain.as_mut() {
+            tc.konanc_tarball_url = Some("https://mirror.example.com/k.tar.gz".to_owned());
+        }
+        assert_eq!(
+            lockfile_toml_content(&without).unwrap(),
+            lockfile_toml_content(&with).unwrap()
+        );
+    }
+
     #[test]
     fn update_lockfile_first_download_stores_hashes() {
         let tmp = tempfile::tempdir().unwrap();
@@ -2178,14 +3381,17 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             Some("first-konanc-hash"),
+            None,
             Some("first-jre-hash"),
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &[],
@@ -2193,6 +3399,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
 
@@ -2214,6 +3421,238 @@ mod tests {
         assert!(!opts.is_release());
         assert!(!opts.verbose);
         assert!(!opts.force);
+        assert!(opts.timeout.is_none());
+    }
+
+    #[test]
+    fn compile_timeout_prefers_cli_option_over_manifest() {
+        let manifest = Manifest::from_str(
+            "[package]\nname = \"test\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n\n[build]\ntimeout_secs = 600\n",
+            "konvoy.toml",
+        )
+        .unwrap();
+
+        let from_manifest = BuildOptions::default();
+        assert_eq!(
+            from_manifest.compile_timeout(&manifest),
+            Some(Duration::from_secs(600))
+        );
+
+        let from_cli = BuildOptions {
+            timeout: Some(Duration::from_secs(30)),
+            ..BuildOptions::default()
+        };
+        assert_eq!(
+            from_cli.compile_timeout(&manifest),
+            Some(Duration::from_secs(30))
+        );
+    }
+
+    #[test]
+    fn compile_timeout_none_when_unset() {
+        let manifest = Manifest::from_str(
+            "[package]\nname = \"test\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n",
+            "konvoy.toml",
+        )
+        .unwrap();
+        assert!(BuildOptions::default().compile_timeout(&manifest).is_none());
+    }
+
+    #[test]
+    fn compress_cache_does_not_change_the_cache_key() {
+        let plain = Manifest::from_str(
+            "[package]\nname = \"test\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n",
+            "konvoy.toml",
+        )
+        .unwrap();
+        let compressed = Manifest::from_str(
+            "[package]\nname = \"test\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n\n[build]\ncompress_cache = true\n",
+            "konvoy.toml",
+        )
+        .unwrap();
+        assert!(compressed.build.compress_cache);
+
+        let key_for = |manifest: &Manifest| {
+            CacheKey::compute(&CacheInputs {
+                manifest_content: manifest_key_content(manifest).unwrap(),
+                lockfile_content: String::new(),
+                konanc_version: "2.1.0".to_owned(),
+                konanc_fingerprint: "fp".to_owned(),
+                target: "linux_x64".parse().unwrap(),
+                profile: Profile::Debug,
+                source_dirs: Vec::new(),
+                source_glob: "**/*.kt".to_owned(),
+                ignore: IgnoreRules::default(),
+                os: "linux".to_owned(),
+                arch: "x86_64".to_owned(),
+                dependency_hashes: Vec::new(),
+                codegen_hashes: Vec::new(),
+                features: Vec::new(),
+                deny_warnings: false,
+            })
+            .unwrap()
+        };
+        assert_eq!(key_for(&plain), key_for(&compressed));
+    }
+
+    #[test]
+    fn deny_warnings_from_manifest_or_option() {
+        let plain = Manifest::from_str(
+            "[package]\nname = \"test\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n",
+            "konvoy.toml",
+        )
+        .unwrap();
+        let denying = Manifest::from_str(
+            "[package]\nname = \"test\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n\n[build]\ndeny_warnings = true\n",
+            "konvoy.toml",
+        )
+        .unwrap();
+        let from_cli = BuildOptions {
+            deny_warnings: true,
+            ..BuildOptions::default()
+        };
+
+        assert!(!BuildOptions::default().deny_warnings(&plain));
+        assert!(BuildOptions::default().deny_warnings(&denying));
+        assert!(from_cli.deny_warnings(&plain));
+    }
+
+    fn compilation_result(success: bool, levels: &[DiagnosticLevel]) -> CompilationResult {
+        CompilationResult {
+            success,
+            output_path: PathBuf::from("out"),
+            diagnostics: levels
+                .iter()
+                .map(|level| Diagnostic {
+                    level: level.clone(),
+                    message: "unused variable 'x'".to_owned(),
+                    file: Some("src/main.kt".to_owned()),
+                    line: Some(2),
+                })
+                .collect(),
+            raw_stdout: String::new(),
+            raw_stderr: String::new(),
+        }
+    }
+
+    #[test]
+    fn warnings_pass_unless_denied() {
+        let warned =
+            compilation_result(true, &[DiagnosticLevel::Warning, DiagnosticLevel::Warning]);
+
+        assert!(check_compilation(&warned, false).is_ok());
+        let err = check_compilation(&warned, true).unwrap_err();
+        assert!(
+            matches!(err, EngineError::WarningsDenied { warning_count: 2 }),
+            "{err}"
+        );
+        assert!(err.to_string().contains("deny_warnings"), "{err}");
+
+        let clean = compilation_result(true, &[DiagnosticLevel::Info]);
+        assert!(check_compilation(&clean, true).is_ok());
+    }
+
+    #[test]
+    fn failed_compilation_reports_errors_even_when_denying_warnings() {
+        let failed = compilation_result(false, &[DiagnosticLevel::Error, DiagnosticLevel::Warning]);
+        let err = check_compilation(&failed, true).unwrap_err();
+        assert!(
+            matches!(err, EngineError::CompilationFailed { error_count: 1 }),
+            "{err}"
+        );
+    }
+
+    fn konanc_settings(deny_warnings: bool) -> KonancSettings<'static> {
+        KonancSettings {
+            target: Target::LinuxX64,
+            release: true,
+            language_version: Some("2.0"),
+            api_version: None,
+            libraries: &[],
+            jre_home: None,
+            timeout: None,
+            stream_output: false,
+            deny_warnings,
+        }
+    }
+
+    #[test]
+    fn konanc_steps_compile_to_a_klib_then_link_it() {
+        let tmp = tempfile::tempdir().unwrap();
+        let output = tmp.path().join("app");
+        let klib = tmp.path().join("app.klib");
+        let plugins = [PathBuf::from("serialization.jar")];
+        let mut steps = Vec::new();
+
+        let result = run_konanc_steps(
+            &konanc_settings(false),
+            &[PathBuf::from("main.kt")],
+            &output,
+            ProduceKind::Library,
+            &[],
+            &[],
+            |cmd, step_output| {
+                steps.push((cmd.build_args().unwrap(), step_output.to_path_buf()));
+                Ok(compilation_result(true, &[]))
+            },
+        )
+        .unwrap();
+        assert!(result.success);
+        assert_eq!(steps.len(), 1, "a library compiles in one step");
+        steps.clear();
+
+        let result = run_konanc_steps(
+            &konanc_settings(false),
+            &[PathBuf::from("main.kt")],
+            &output,
+            ProduceKind::StaticLib,
+            &plugins,
+            &[],
+            |cmd, step_output| {
+                fs::write(step_output, "artifact").unwrap();
+                steps.push((cmd.build_args().unwrap(), step_output.to_path_buf()));
+                Ok(compilation_result(true, &[DiagnosticLevel::Warning]))
+            },
+        )
+        .unwrap();
+        assert_eq!(result.warning_count(), 2, "both steps' diagnostics");
+        let [(compile_args, compile_output), (link_args, link_output)] = steps.as_slice() else {
+            panic!("expected two steps, got {steps:?}");
+        };
+        assert_eq!(compile_output, &klib);
+        assert!(compile_args.contains(&"-language-version".to_owned()));
+        assert!(compile_args.contains(&"-opt".to_owned()));
+        assert_eq!(link_output, &output);
+        assert!(link_args.contains(&format!("-Xinclude={}", klib.display())));
+        assert!(!link_args.contains(&"-language-version".to_owned()));
+        assert!(!klib.exists(), "the temporary klib is removed");
+    }
+
+    #[test]
+    fn konanc_steps_stop_after_a_denied_warning() {
+        let tmp = tempfile::tempdir().unwrap();
+        let output = tmp.path().join("app");
+        let plugins = [PathBuf::from("serialization.jar")];
+        let mut steps = 0;
+
+        let result = run_konanc_steps(
+            &konanc_settings(true),
+            &[PathBuf::from("main.kt")],
+            &output,
+            ProduceKind::Program,
+            &plugins,
+            &[],
+            |_, step_output| {
+                steps += 1;
+                fs::write(step_output, "klib").unwrap();
+                Ok(compilation_result(true, &[DiagnosticLevel::Warning]))
+            },
+        )
+        .unwrap();
+        assert_eq!(steps, 1, "the link step must not run");
+        assert!(check_compilation(&result, true).is_err());
+        assert!(!output.with_extension("klib").exists());
+        assert!(!output.exists());
     }
 
     #[test]
@@ -2225,6 +3664,7 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         let dep = crate::resolve::ResolvedDep {
@@ -2237,14 +3677,17 @@ mod tests {
             .unwrap(),
             dep_names: Vec::new(),
             source_hash: "deadbeefcafebabe".to_owned(),
+            git: None,
         };
         let graph = crate::resolve::ResolvedGraph { order: vec![dep] };
 
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             None,
             None,
+            None,
+            DEFAULT_JRE_MAJOR,
             &graph,
             &[],
             &[],
@@ -2252,6 +3695,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
         let content = fs::read_to_string(&lockfile_path).unwrap();
@@ -2272,6 +3716,34 @@ mod tests {
         assert_ne!(BuildOutcome::Cached, BuildOutcome::Fresh);
     }
 
+    #[test]
+    fn target_dir_holds_build_outputs_and_cache() {
+        let project = Path::new("/src/app");
+        let target = konvoy_targets::Target::LinuxX64;
+        assert_eq!(
+            build_output_path(project, None, "app", &target, Profile::Debug),
+            PathBuf::from("/src/app/.konvoy/build/linux_x64/debug/app")
+        );
+        let options = BuildOptions {
+            target_dir: Some(PathBuf::from("/ci/konvoy")),
+            ..BuildOptions::default()
+        };
+        assert_eq!(
+            build_output_path(
+                project,
+                options.target_dir.as_deref(),
+                "app",
+                &target,
+                Profile::Release
+            ),
+            PathBuf::from("/ci/konvoy/build/linux_x64/release/app")
+        );
+        assert_eq!(
+            options.artifact_store(project).root_dir(),
+            Path::new("/ci/konvoy/cache")
+        );
+    }
+
     #[test]
     fn build_single_returns_cached_on_cache_hit() {
         let tmp = tempfile::tempdir().unwrap();
@@ -2290,6 +3762,7 @@ mod tests {
             path: PathBuf::from("/fake/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc123".to_owned(),
+            launcher_fingerprint: "abc123".to_owned(),
         };
         let target = konvoy_targets::Target::LinuxX64;
         let profile = Profile::Debug;
@@ -2297,7 +3770,20 @@ mod tests {
             target: None,
             profile: Profile::Debug,
             verbose: false,
+            quiet: false,
             force: false,
+            timeout: None,
+            dedupe_diagnostics: false,
+            color: ColorChoice::Never,
+            timings: false,
+            features: Vec::new(),
+            no_default_features: false,
+            deny_warnings: false,
+            emit_command: None,
+            out_dir: None,
+            target_dir: None,
+            keep_going: false,
+            progress: Arc::new(StderrProgress),
         };
 
         // Compute the cache key that build_single would compute.
@@ -2311,12 +3797,15 @@ mod tests {
             konanc_fingerprint: konanc.fingerprint.clone(),
             target,
             profile,
-            source_dir: project.join("src"),
+            source_dirs: vec![project.join("src")],
             source_glob: "**/*.kt".to_owned(),
+            ignore: IgnoreRules::default(),
             os: std::env::consts::OS.to_owned(),
             arch: std::env::consts::ARCH.to_owned(),
             dependency_hashes: Vec::new(),
             codegen_hashes: Vec::new(),
+            features: Vec::new(),
+            deny_warnings: false,
         };
         let cache_key = CacheKey::compute(&cache_inputs).unwrap();
 
@@ -2342,6 +3831,11 @@ mod tests {
             target: &target,
             options: &options,
             library_inputs: &[],
+            timeout: None,
+            deny_warnings: false,
+            compress_cache: false,
+            features: &[],
+            command_log: None,
         };
         let (output_path, outcome) =
             build_single(&project, &manifest, &cc, profile, &lockfile_content).unwrap();
@@ -2351,62 +3845,680 @@ mod tests {
     }
 
     #[test]
-    fn build_single_excludes_test_sources() {
-        // Create a project with both src/main.kt and src/test/FooTest.kt.
-        // Pre-populate cache with a key computed from only non-test sources.
-        // If build_single correctly excludes test sources, it should hit cache.
+    fn build_single_rebuilds_when_cached_artifact_is_missing() {
         let tmp = tempfile::tempdir().unwrap();
         let project = tmp.path().join("myapp");
-        fs::create_dir_all(project.join("src").join("test")).unwrap();
+        fs::create_dir_all(project.join("src")).unwrap();
         fs::write(project.join("src").join("main.kt"), "fun main() {}").unwrap();
-        fs::write(
-            project.join("src").join("test").join("FooTest.kt"),
-            "import kotlin.test.Test\nclass FooTest { @Test fun foo() {} }",
-        )
-        .unwrap();
         fs::write(
             project.join("konvoy.toml"),
             "[package]\nname = \"myapp\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n",
         )
         .unwrap();
-
         let manifest =
             konvoy_config::manifest::Manifest::from_path(&project.join("konvoy.toml")).unwrap();
         let konanc = KonancInfo {
-            path: PathBuf::from("/fake/konanc"),
+            path: tmp.path().join("no-such-konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc123".to_owned(),
+            launcher_fingerprint: "abc123".to_owned(),
         };
         let target = konvoy_targets::Target::LinuxX64;
-        let profile = Profile::Debug;
+        let progress = Arc::new(RecordingProgress::default());
         let options = BuildOptions {
-            target: None,
-            profile: Profile::Debug,
-            verbose: false,
-            force: false,
+            color: ColorChoice::Never,
+            progress: progress.clone(),
+            ..BuildOptions::default()
         };
-
-        // Compute cache key the same way build_single does (without test sources).
-        let manifest_content = manifest.to_toml().unwrap();
-        let effective_lockfile = Lockfile::with_toolchain(&konanc.version);
-        let lockfile_content = lockfile_toml_content(&effective_lockfile).unwrap();
-        let cache_inputs = CacheInputs {
+        let lockfile_content =
+            lockfile_toml_content(&Lockfile::with_toolchain(&konanc.version)).unwrap();
+        let cc = CompileContext {
+            konanc: &konanc,
+            jre_home: None,
+            target: &target,
+            options: &options,
+            library_inputs: &[],
+            timeout: None,
+            deny_warnings: false,
+            compress_cache: false,
+            features: &[],
+            command_log: None,
+        };
+
+        // A cache entry whose artifact file has since been deleted.
+        let inputs =
+            project_inputs(&project, &manifest, &cc, Profile::Debug, &lockfile_content).unwrap();
+        let store = ArtifactStore::new(&project);
+        let staging = tmp.path().join("staging");
+        fs::create_dir_all(&staging).unwrap();
+        fs::write(staging.join("myapp"), "fake-binary-content").unwrap();
+        let metadata = BuildMetadata {
+            target,
+            profile: Profile::Debug,
+            konanc_version: konanc.version.clone(),
+            built_at: crate::common::now_epoch_secs(),
+        };
+        store
+            .store(&inputs.cache_key, &staging.join("myapp"), &metadata)
+            .unwrap();
+        fs::remove_file(store.cache_path(&inputs.cache_key).join("myapp")).unwrap();
+
+        // The compile is attempted (and fails, there is no konanc) instead of
+        // reporting the missing cached file, and the broken entry is gone.
+        let err =
+            build_single(&project, &manifest, &cc, Profile::Debug, &lockfile_content).unwrap_err();
+        assert!(matches!(err, EngineError::Konanc(_)), "got: {err}");
+        assert!(!store.has(&inputs.cache_key));
+        let warnings = progress.warnings.lock().unwrap();
+        assert!(
+            warnings.iter().any(|w| w.contains("missing or empty")),
+            "warnings were: {warnings:?}"
+        );
+    }
+
+    #[test]
+    fn build_single_restores_c_header_on_cache_hit() {
+        let tmp = tempfile::tempdir().unwrap();
+        let project = tmp.path().join("myapp");
+        fs::create_dir_all(project.join("src")).unwrap();
+        fs::write(project.join("src").join("main.kt"), "fun main() {}").unwrap();
+        fs::write(
+            project.join("konvoy.toml"),
+            "[package]\nname = \"myapp\"\nkind = \"staticlib\"\nversion = \"0.1.0\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n",
+        )
+        .unwrap();
+
+        let manifest =
+            konvoy_config::manifest::Manifest::from_path(&project.join("konvoy.toml")).unwrap();
+        let konanc = KonancInfo {
+            path: PathBuf::from("/fake/konanc"),
+            version: "2.1.0".to_owned(),
+            fingerprint: "abc123".to_owned(),
+            launcher_fingerprint: "abc123".to_owned(),
+        };
+        let target = konvoy_targets::Target::LinuxX64;
+        let profile = Profile::Debug;
+        let options = BuildOptions {
+            target: None,
+            profile: Profile::Debug,
+            verbose: false,
+            quiet: false,
+            force: false,
+            timeout: None,
+            dedupe_diagnostics: false,
+            color: ColorChoice::Never,
+            timings: false,
+            features: Vec::new(),
+            no_default_features: false,
+            deny_warnings: false,
+            emit_command: None,
+            out_dir: None,
+            target_dir: None,
+            keep_going: false,
+            progress: Arc::new(StderrProgress),
+        };
+
+        // Compute the cache key that build_single would compute.
+        let manifest_content = manifest.to_toml().unwrap();
+        let effective_lockfile = Lockfile::with_toolchain(&konanc.version);
+        let lockfile_content = lockfile_toml_content(&effective_lockfile).unwrap();
+        let cache_inputs = CacheInputs {
+            manifest_content,
+            lockfile_content: lockfile_content.clone(),
+            konanc_version: konanc.version.clone(),
+            konanc_fingerprint: konanc.fingerprint.clone(),
+            target,
+            profile,
+            source_dirs: vec![project.join("src")],
+            source_glob: "**/*.kt".to_owned(),
+            ignore: IgnoreRules::default(),
+            os: std::env::consts::OS.to_owned(),
+            arch: std::env::consts::ARCH.to_owned(),
+            dependency_hashes: Vec::new(),
+            codegen_hashes: Vec::new(),
+            features: Vec::new(),
+            deny_warnings: false,
+        };
+        let cache_key = CacheKey::compute(&cache_inputs).unwrap();
+
+        // Pre-populate the artifact store with a fake artifact.
+        let store = ArtifactStore::new(&project);
+        let staging = tmp.path().join("staging");
+        fs::create_dir_all(&staging).unwrap();
+        let fake_artifact = staging.join("libmyapp.a");
+        fs::write(&fake_artifact, "fake-archive").unwrap();
+        let fake_header = staging.join("libmyapp_api.h");
+        fs::write(&fake_header, "/* fake header */").unwrap();
+        let metadata = BuildMetadata {
+            target,
+            profile,
+            konanc_version: konanc.version.clone(),
+            built_at: crate::common::now_epoch_secs(),
+        };
+        store
+            .store_all(&cache_key, &[&fake_artifact, &fake_header], &metadata)
+            .unwrap();
+        assert!(store.has(&cache_key));
+
+        // The cache hit restores the header along with the archive.
+        let cc = CompileContext {
+            konanc: &konanc,
+            jre_home: None,
+            target: &target,
+            options: &options,
+            library_inputs: &[],
+            timeout: None,
+            deny_warnings: false,
+            compress_cache: false,
+            features: &[],
+            command_log: None,
+        };
+        let (output_path, outcome) =
+            build_single(&project, &manifest, &cc, profile, &lockfile_content).unwrap();
+
+        assert_eq!(outcome, BuildOutcome::Cached);
+        assert!(output_path.ends_with("libmyapp.a"));
+        let header = c_header_path(&output_path, ProduceKind::StaticLib).unwrap();
+        assert_eq!(fs::read_to_string(header).unwrap(), "/* fake header */");
+    }
+
+    #[test]
+    fn compile_records_the_command_before_running_konanc() {
+        let tmp = tempfile::tempdir().unwrap();
+        let konanc = KonancInfo {
+            path: tmp.path().join("missing-konanc"),
+            version: "2.1.0".to_owned(),
+            fingerprint: "abc123".to_owned(),
+            launcher_fingerprint: "abc123".to_owned(),
+        };
+        let target = konvoy_targets::Target::LinuxX64;
+        let options = BuildOptions::default();
+        let log = CommandLog::new(tmp.path());
+        let jre = tmp.path().join("jre");
+        let cc = CompileContext {
+            konanc: &konanc,
+            jre_home: Some(&jre),
+            target: &target,
+            options: &options,
+            library_inputs: &[],
+            timeout: None,
+            deny_warnings: false,
+            compress_cache: false,
+            features: &[],
+            command_log: Some(&log),
+        };
+        let source = tmp.path().join("main.kt");
+        let output = tmp.path().join("out").join("app");
+        let toolchain = Toolchain {
+            kotlin: "2.1.0".to_owned(),
+            detekt: None,
+            ktlint: None,
+            jre: None,
+            language_version: Some("1.9".to_owned()),
+            api_version: None,
+        };
+
+        // konanc cannot run, but the invocation is already on record.
+        assert!(compile(
+            &cc,
+            std::slice::from_ref(&source),
+            &output,
+            ProduceKind::Program,
+            &[],
+            &[],
+            &toolchain
+        )
+        .is_err());
+        let commands = log.commands();
+        assert_eq!(commands.len(), 1);
+        let entry = commands.first().unwrap();
+        assert_eq!(
+            entry.arguments.first(),
+            Some(&konanc.path.display().to_string())
+        );
+        assert!(entry.arguments.contains(&source.display().to_string()));
+        assert_eq!(entry.output, output.display().to_string());
+        let pos = entry
+            .arguments
+            .iter()
+            .position(|a| a == "-language-version")
+            .unwrap();
+        assert_eq!(
+            entry.arguments.get(pos + 1).map(String::as_str),
+            Some("1.9")
+        );
+        assert!(!entry.arguments.iter().any(|a| a == "-api-version"));
+        assert_eq!(
+            entry.environment.get("JAVA_HOME"),
+            Some(&jre.display().to_string())
+        );
+    }
+
+    #[test]
+    fn export_artifact_copies_into_a_new_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let output = tmp.path().join(".konvoy").join("build").join("myapp");
+        fs::create_dir_all(output.parent().unwrap()).unwrap();
+        fs::write(&output, "binary").unwrap();
+        let out_dir = tmp.path().join("dist").join("linux_x64");
+
+        let exported = export_artifact(&output, &out_dir).unwrap();
+        assert_eq!(exported, out_dir.join("myapp"));
+        assert_eq!(fs::read_to_string(&exported).unwrap(), "binary");
+
+        // Re-exporting replaces the copy; editing it leaves the build untouched.
+        fs::write(&exported, "stripped").unwrap();
+        export_artifact(&output, &out_dir).unwrap();
+        assert_eq!(fs::read_to_string(&exported).unwrap(), "binary");
+        fs::write(&exported, "signed").unwrap();
+        assert_eq!(fs::read_to_string(&output).unwrap(), "binary");
+    }
+
+    #[test]
+    fn build_single_excludes_test_sources() {
+        // Create a project with both src/main.kt and src/test/FooTest.kt.
+        // Pre-populate cache with a key computed from only non-test sources.
+        // If build_single correctly excludes test sources, it should hit cache.
+        let tmp = tempfile::tempdir().unwrap();
+        let project = tmp.path().join("myapp");
+        fs::create_dir_all(project.join("src").join("test")).unwrap();
+        fs::write(project.join("src").join("main.kt"), "fun main() {}").unwrap();
+        fs::write(
+            project.join("src").join("test").join("FooTest.kt"),
+            "import kotlin.test.Test\nclass FooTest { @Test fun foo() {} }",
+        )
+        .unwrap();
+        fs::write(
+            project.join("konvoy.toml"),
+            "[package]\nname = \"myapp\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n",
+        )
+        .unwrap();
+
+        let manifest =
+            konvoy_config::manifest::Manifest::from_path(&project.join("konvoy.toml")).unwrap();
+        let konanc = KonancInfo {
+            path: PathBuf::from("/fake/konanc"),
+            version: "2.1.0".to_owned(),
+            fingerprint: "abc123".to_owned(),
+            launcher_fingerprint: "abc123".to_owned(),
+        };
+        let target = konvoy_targets::Target::LinuxX64;
+        let profile = Profile::Debug;
+        let options = BuildOptions {
+            target: None,
+            profile: Profile::Debug,
+            verbose: false,
+            quiet: false,
+            force: false,
+            timeout: None,
+            dedupe_diagnostics: false,
+            color: ColorChoice::Never,
+            timings: false,
+            features: Vec::new(),
+            no_default_features: false,
+            deny_warnings: false,
+            emit_command: None,
+            out_dir: None,
+            target_dir: None,
+            keep_going: false,
+            progress: Arc::new(StderrProgress),
+        };
+
+        // Compute cache key the same way build_single does (without test sources).
+        let manifest_content = manifest.to_toml().unwrap();
+        let effective_lockfile = Lockfile::with_toolchain(&konanc.version);
+        let lockfile_content = lockfile_toml_content(&effective_lockfile).unwrap();
+        let cache_inputs = CacheInputs {
             manifest_content,
             lockfile_content: lockfile_content.clone(),
             konanc_version: konanc.version.clone(),
             konanc_fingerprint: konanc.fingerprint.clone(),
             target,
             profile,
-            source_dir: project.join("src"),
+            source_dirs: vec![project.join("src")],
             source_glob: "**/*.kt".to_owned(),
+            ignore: IgnoreRules::default(),
             os: std::env::consts::OS.to_owned(),
             arch: std::env::consts::ARCH.to_owned(),
             dependency_hashes: Vec::new(),
             codegen_hashes: Vec::new(),
+            features: Vec::new(),
+            deny_warnings: false,
         };
         let cache_key = CacheKey::compute(&cache_inputs).unwrap();
 
-        // Pre-populate the artifact store.
+        // Pre-populate the artifact store.
+        let store = ArtifactStore::new(&project);
+        let staging = tmp.path().join("staging");
+        fs::create_dir_all(&staging).unwrap();
+        let fake_artifact = staging.join("myapp");
+        fs::write(&fake_artifact, "fake-binary-content").unwrap();
+        let metadata = BuildMetadata {
+            target,
+            profile,
+            konanc_version: konanc.version.clone(),
+            built_at: crate::common::now_epoch_secs(),
+        };
+        store.store(&cache_key, &fake_artifact, &metadata).unwrap();
+
+        // build_single should hit cache because test sources are excluded,
+        // producing the same cache key we computed above.
+        let cc = CompileContext {
+            konanc: &konanc,
+            jre_home: None,
+            target: &target,
+            options: &options,
+            library_inputs: &[],
+            timeout: None,
+            deny_warnings: false,
+            compress_cache: false,
+            features: &[],
+            command_log: None,
+        };
+        let (output_path, outcome) =
+            build_single(&project, &manifest, &cc, profile, &lockfile_content).unwrap();
+
+        assert_eq!(outcome, BuildOutcome::Cached);
+        assert!(output_path.exists());
+    }
+
+    #[test]
+    fn build_single_materializes_under_bin_name() {
+        // `bin_name` renames the executable; the cached artifact is stored and
+        // materialized under that name, not the package name.
+        let tmp = tempfile::tempdir().unwrap();
+        let project = tmp.path().join("my-cool-lib");
+        let src = project.join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("main.kt"), "fun main() {}").unwrap();
+        fs::write(
+            project.join("konvoy.toml"),
+            "[package]\nname = \"my-cool-lib\"\nbin_name = \"mytool\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n",
+        )
+        .unwrap();
+
+        let manifest =
+            konvoy_config::manifest::Manifest::from_path(&project.join("konvoy.toml")).unwrap();
+        let konanc = KonancInfo {
+            path: PathBuf::from("/fake/konanc"),
+            version: "2.1.0".to_owned(),
+            fingerprint: "abc123".to_owned(),
+            launcher_fingerprint: "abc123".to_owned(),
+        };
+        let target = konvoy_targets::Target::LinuxX64;
+        let profile = Profile::Debug;
+        let options = BuildOptions::default();
+
+        let lockfile_content =
+            lockfile_toml_content(&Lockfile::with_toolchain(&konanc.version)).unwrap();
+        let cache_key = CacheKey::compute(&CacheInputs {
+            manifest_content: manifest.to_toml().unwrap(),
+            lockfile_content: lockfile_content.clone(),
+            konanc_version: konanc.version.clone(),
+            konanc_fingerprint: konanc.fingerprint.clone(),
+            target,
+            profile,
+            source_dirs: vec![src],
+            source_glob: "**/*.kt".to_owned(),
+            ignore: IgnoreRules::default(),
+            os: std::env::consts::OS.to_owned(),
+            arch: std::env::consts::ARCH.to_owned(),
+            dependency_hashes: Vec::new(),
+            codegen_hashes: Vec::new(),
+            features: Vec::new(),
+            deny_warnings: false,
+        })
+        .unwrap();
+
+        let store = ArtifactStore::new(&project);
+        let staging = tmp.path().join("staging");
+        fs::create_dir_all(&staging).unwrap();
+        let fake_artifact = staging.join("mytool");
+        fs::write(&fake_artifact, "fake-binary-content").unwrap();
+        let metadata = BuildMetadata {
+            target,
+            profile,
+            konanc_version: konanc.version.clone(),
+            built_at: crate::common::now_epoch_secs(),
+        };
+        store.store(&cache_key, &fake_artifact, &metadata).unwrap();
+
+        let cc = CompileContext {
+            konanc: &konanc,
+            jre_home: None,
+            target: &target,
+            options: &options,
+            library_inputs: &[],
+            timeout: None,
+            deny_warnings: false,
+            compress_cache: false,
+            features: &[],
+            command_log: None,
+        };
+        let (output_path, outcome) =
+            build_single(&project, &manifest, &cc, profile, &lockfile_content).unwrap();
+        assert_eq!(outcome, BuildOutcome::Cached);
+        assert_eq!(
+            output_path,
+            project.join(".konvoy/build/linux_x64/debug/mytool")
+        );
+        assert!(output_path.exists());
+    }
+
+    #[test]
+    fn build_single_uses_configured_source_dirs() {
+        // A Gradle-style layout: sources live in src/main/kotlin. The cache key
+        // must be computed over the configured dir only, so a stray .kt file
+        // elsewhere under src/ does not participate.
+        let tmp = tempfile::tempdir().unwrap();
+        let project = tmp.path().join("myapp");
+        let kotlin_dir = project.join("src").join("main").join("kotlin");
+        fs::create_dir_all(&kotlin_dir).unwrap();
+        fs::write(kotlin_dir.join("Main.kt"), "fun main() {}").unwrap();
+        fs::write(project.join("src").join("scratch.kt"), "fun scratch() {}").unwrap();
+        fs::write(
+            project.join("konvoy.toml"),
+            "[package]\nname = \"myapp\"\nsource_dirs = [\"src/main/kotlin\"]\n\n[toolchain]\nkotlin = \"2.1.0\"\n",
+        )
+        .unwrap();
+
+        let manifest =
+            konvoy_config::manifest::Manifest::from_path(&project.join("konvoy.toml")).unwrap();
+        let konanc = KonancInfo {
+            path: PathBuf::from("/fake/konanc"),
+            version: "2.1.0".to_owned(),
+            fingerprint: "abc123".to_owned(),
+            launcher_fingerprint: "abc123".to_owned(),
+        };
+        let target = konvoy_targets::Target::LinuxX64;
+        let profile = Profile::Debug;
+        let options = BuildOptions::default();
+
+        let lockfile_content =
+            lockfile_toml_content(&Lockfile::with_toolchain(&konanc.version)).unwrap();
+        let cache_key = CacheKey::compute(&CacheInputs {
+            manifest_content: manifest.to_toml().unwrap(),
+            lockfile_content: lockfile_content.clone(),
+            konanc_version: konanc.version.clone(),
+            konanc_fingerprint: konanc.fingerprint.clone(),
+            target,
+            profile,
+            source_dirs: vec![kotlin_dir.clone()],
+            source_glob: "**/*.kt".to_owned(),
+            ignore: IgnoreRules::default(),
+            os: std::env::consts::OS.to_owned(),
+            arch: std::env::consts::ARCH.to_owned(),
+            dependency_hashes: Vec::new(),
+            codegen_hashes: Vec::new(),
+            features: Vec::new(),
+            deny_warnings: false,
+        })
+        .unwrap();
+
+        let store = ArtifactStore::new(&project);
+        let staging = tmp.path().join("staging");
+        fs::create_dir_all(&staging).unwrap();
+        let fake_artifact = staging.join("myapp");
+        fs::write(&fake_artifact, "fake-binary-content").unwrap();
+        let metadata = BuildMetadata {
+            target,
+            profile,
+            konanc_version: konanc.version.clone(),
+            built_at: crate::common::now_epoch_secs(),
+        };
+        store.store(&cache_key, &fake_artifact, &metadata).unwrap();
+
+        let cc = CompileContext {
+            konanc: &konanc,
+            jre_home: None,
+            target: &target,
+            options: &options,
+            library_inputs: &[],
+            timeout: None,
+            deny_warnings: false,
+            compress_cache: false,
+            features: &[],
+            command_log: None,
+        };
+        let (_, outcome) =
+            build_single(&project, &manifest, &cc, profile, &lockfile_content).unwrap();
+        assert_eq!(outcome, BuildOutcome::Cached);
+    }
+
+    #[test]
+    fn build_single_adds_enabled_feature_source_sets_to_the_key() {
+        // An enabled feature compiles `features/<name>/` and tags the key, so the
+        // key computed over both source sets with the feature named must hit.
+        let tmp = tempfile::tempdir().unwrap();
+        let project = tmp.path().join("myapp");
+        let src = project.join("src");
+        let json_dir = project.join("features").join("json");
+        fs::create_dir_all(&src).unwrap();
+        fs::create_dir_all(&json_dir).unwrap();
+        fs::write(src.join("Main.kt"), "fun main() {}").unwrap();
+        fs::write(json_dir.join("Json.kt"), "fun json() {}").unwrap();
+        fs::write(
+            project.join("konvoy.toml"),
+            "[package]\nname = \"myapp\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n\n[features]\njson = []\n",
+        )
+        .unwrap();
+
+        let manifest =
+            konvoy_config::manifest::Manifest::from_path(&project.join("konvoy.toml")).unwrap();
+        let konanc = KonancInfo {
+            path: PathBuf::from("/fake/konanc"),
+            version: "2.1.0".to_owned(),
+            fingerprint: "abc123".to_owned(),
+            launcher_fingerprint: "abc123".to_owned(),
+        };
+        let target = konvoy_targets::Target::LinuxX64;
+        let profile = Profile::Debug;
+        let options = BuildOptions::default();
+        let features = vec!["json".to_owned()];
+
+        let lockfile_content =
+            lockfile_toml_content(&Lockfile::with_toolchain(&konanc.version)).unwrap();
+        let cache_key = CacheKey::compute(&CacheInputs {
+            manifest_content: manifest.to_toml().unwrap(),
+            lockfile_content: lockfile_content.clone(),
+            konanc_version: konanc.version.clone(),
+            konanc_fingerprint: konanc.fingerprint.clone(),
+            target,
+            profile,
+            source_dirs: vec![src, json_dir],
+            source_glob: "**/*.kt".to_owned(),
+            ignore: IgnoreRules::default(),
+            os: std::env::consts::OS.to_owned(),
+            arch: std::env::consts::ARCH.to_owned(),
+            dependency_hashes: Vec::new(),
+            codegen_hashes: Vec::new(),
+            features: features.clone(),
+            deny_warnings: false,
+        })
+        .unwrap();
+
+        let store = ArtifactStore::new(&project);
+        let staging = tmp.path().join("staging");
+        fs::create_dir_all(&staging).unwrap();
+        let fake_artifact = staging.join("myapp");
+        fs::write(&fake_artifact, "fake-binary-content").unwrap();
+        let metadata = BuildMetadata {
+            target,
+            profile,
+            konanc_version: konanc.version.clone(),
+            built_at: crate::common::now_epoch_secs(),
+        };
+        store.store(&cache_key, &fake_artifact, &metadata).unwrap();
+
+        let cc = CompileContext {
+            konanc: &konanc,
+            jre_home: None,
+            target: &target,
+            options: &options,
+            library_inputs: &[],
+            timeout: None,
+            deny_warnings: false,
+            compress_cache: false,
+            features: &features,
+            command_log: None,
+        };
+        let (_, outcome) =
+            build_single(&project, &manifest, &cc, profile, &lockfile_content).unwrap();
+        assert_eq!(outcome, BuildOutcome::Cached);
+    }
+
+    #[test]
+    fn build_single_skips_konvoyignored_sources() {
+        // A scratch file matched by .konvoyignore must not contribute to the
+        // cache key: the key computed without it must be a hit.
+        let tmp = tempfile::tempdir().unwrap();
+        let project = tmp.path().join("myapp");
+        let src = project.join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("main.kt"), "fun main() {}").unwrap();
+        fs::write(
+            project.join("konvoy.toml"),
+            "[package]\nname = \"myapp\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n",
+        )
+        .unwrap();
+
+        let manifest =
+            konvoy_config::manifest::Manifest::from_path(&project.join("konvoy.toml")).unwrap();
+        let konanc = KonancInfo {
+            path: PathBuf::from("/fake/konanc"),
+            version: "2.1.0".to_owned(),
+            fingerprint: "abc123".to_owned(),
+            launcher_fingerprint: "abc123".to_owned(),
+        };
+        let target = konvoy_targets::Target::LinuxX64;
+        let profile = Profile::Debug;
+        let options = BuildOptions::default();
+
+        let lockfile_content =
+            lockfile_toml_content(&Lockfile::with_toolchain(&konanc.version)).unwrap();
+        let cache_key = CacheKey::compute(&CacheInputs {
+            manifest_content: manifest.to_toml().unwrap(),
+            lockfile_content: lockfile_content.clone(),
+            konanc_version: konanc.version.clone(),
+            konanc_fingerprint: konanc.fingerprint.clone(),
+            target,
+            profile,
+            source_dirs: vec![src.clone()],
+            source_glob: "**/*.kt".to_owned(),
+            ignore: IgnoreRules::default(),
+            os: std::env::consts::OS.to_owned(),
+            arch: std::env::consts::ARCH.to_owned(),
+            dependency_hashes: Vec::new(),
+            codegen_hashes: Vec::new(),
+            features: Vec::new(),
+            deny_warnings: false,
+        })
+        .unwrap();
+
+        // Add the scratch file only after computing the expected key.
+        fs::write(src.join("draft.scratch.kt"), "fun draft() {}").unwrap();
+        fs::write(project.join(".konvoyignore"), "*.scratch.kt\n").unwrap();
+
         let store = ArtifactStore::new(&project);
         let staging = tmp.path().join("staging");
         fs::create_dir_all(&staging).unwrap();
@@ -2420,20 +4532,21 @@ mod tests {
         };
         store.store(&cache_key, &fake_artifact, &metadata).unwrap();
 
-        // build_single should hit cache because test sources are excluded,
-        // producing the same cache key we computed above.
         let cc = CompileContext {
             konanc: &konanc,
             jre_home: None,
             target: &target,
             options: &options,
             library_inputs: &[],
+            timeout: None,
+            deny_warnings: false,
+            compress_cache: false,
+            features: &[],
+            command_log: None,
         };
-        let (output_path, outcome) =
+        let (_, outcome) =
             build_single(&project, &manifest, &cc, profile, &lockfile_content).unwrap();
-
         assert_eq!(outcome, BuildOutcome::Cached);
-        assert!(output_path.exists());
     }
 
     #[test]
@@ -2457,6 +4570,7 @@ mod tests {
             path: PathBuf::from("/fake/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc123".to_owned(),
+            launcher_fingerprint: "abc123".to_owned(),
         };
         let target = konvoy_targets::Target::LinuxX64;
         let profile = Profile::Debug;
@@ -2464,7 +4578,20 @@ mod tests {
             target: None,
             profile: Profile::Debug,
             verbose: false,
+            quiet: false,
             force: false,
+            timeout: None,
+            dedupe_diagnostics: false,
+            color: ColorChoice::Never,
+            timings: false,
+            features: Vec::new(),
+            no_default_features: false,
+            deny_warnings: false,
+            emit_command: None,
+            out_dir: None,
+            target_dir: None,
+            keep_going: false,
+            progress: Arc::new(StderrProgress),
         };
 
         // Compute cache key before adding the outside file.
@@ -2478,12 +4605,15 @@ mod tests {
             konanc_fingerprint: konanc.fingerprint.clone(),
             target,
             profile,
-            source_dir: project.join("src"),
+            source_dirs: vec![project.join("src")],
             source_glob: "**/*.kt".to_owned(),
+            ignore: IgnoreRules::default(),
             os: std::env::consts::OS.to_owned(),
             arch: std::env::consts::ARCH.to_owned(),
             dependency_hashes: Vec::new(),
             codegen_hashes: Vec::new(),
+            features: Vec::new(),
+            deny_warnings: false,
         };
         let key_before = CacheKey::compute(&cache_inputs_before).unwrap();
 
@@ -2520,12 +4650,15 @@ mod tests {
             konanc_fingerprint: konanc.fingerprint.clone(),
             target,
             profile,
-            source_dir: project.join("src"),
+            source_dirs: vec![project.join("src")],
             source_glob: "**/*.kt".to_owned(),
+            ignore: IgnoreRules::default(),
             os: std::env::consts::OS.to_owned(),
             arch: std::env::consts::ARCH.to_owned(),
             dependency_hashes: Vec::new(),
             codegen_hashes: Vec::new(),
+            features: Vec::new(),
+            deny_warnings: false,
         };
         let key_after = CacheKey::compute(&cache_inputs_after).unwrap();
         assert_eq!(
@@ -2540,6 +4673,11 @@ mod tests {
             target: &target,
             options: &options,
             library_inputs: &[],
+            timeout: None,
+            deny_warnings: false,
+            compress_cache: false,
+            features: &[],
+            command_log: None,
         };
         let (output_path, outcome) =
             build_single(&project, &manifest, &cc, profile, &lockfile_content).unwrap();
@@ -2559,15 +4697,18 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         // No new hashes (None) — toolchain was already installed.
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             None,
             None,
+            None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &[],
@@ -2575,6 +4716,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
 
@@ -2599,15 +4741,18 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         // Fresh download provides new hashes.
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             Some("newhash1"),
+            None,
             Some("newhash2"),
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &[],
@@ -2615,6 +4760,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
 
@@ -2629,8 +4775,11 @@ mod tests {
     fn update_lockfile_same_hash_redownload_is_noop() {
         let tmp = tempfile::tempdir().unwrap();
         let lockfile_path = tmp.path().join("konvoy.lock");
-        let lockfile =
+        let mut lockfile =
             Lockfile::with_managed_toolchain("2.1.0", Some("samehash1"), Some("samehash2"));
+        if let Some(tc) = lockfile.toolchain.as_mut() {
+            tc.konanc_fingerprint = Some("abc".to_owned());
+        }
         lockfile.write_to(&lockfile_path).unwrap();
         let before = fs::read_to_string(&lockfile_path).unwrap();
 
@@ -2638,15 +4787,18 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         // Fresh download returns the same hashes as the lockfile.
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             Some("samehash1"),
+            None,
             Some("samehash2"),
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &[],
@@ -2654,6 +4806,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
 
@@ -2661,6 +4814,85 @@ mod tests {
         assert_eq!(before, after);
     }
 
+    /// Run `update_lockfile_if_needed` for konanc 2.1.0 (fingerprint `abc`) with
+    /// no fresh download and an empty graph, returning the written lockfile.
+    fn update_fingerprint_only(lockfile: &Lockfile, locked: bool) -> Lockfile {
+        let tmp = tempfile::tempdir().unwrap();
+        let lockfile_path = tmp.path().join("konvoy.lock");
+        lockfile.write_to(&lockfile_path).unwrap();
+        let konanc = KonancInfo {
+            path: PathBuf::from("/usr/bin/konanc"),
+            version: "2.1.0".to_owned(),
+            fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
+        };
+        let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
+        update_lockfile_if_needed(
+            lockfile,
+            Some(&konanc),
+            None,
+            None,
+            None,
+            DEFAULT_JRE_MAJOR,
+            &empty_graph,
+            &[],
+            &[],
+            tmp.path(),
+            &lockfile_path,
+            false,
+            crate::common::test_resolver(false, locked),
+            &StderrProgress,
+        )
+        .unwrap();
+        Lockfile::from_path(&lockfile_path).unwrap()
+    }
+
+    #[test]
+    fn update_lockfile_backfills_missing_konanc_fingerprint() {
+        let written = update_fingerprint_only(&Lockfile::with_toolchain("2.1.0"), false);
+        assert_eq!(written.konanc_fingerprint("2.1.0"), Some("abc"));
+    }
+
+    #[test]
+    fn update_lockfile_locked_does_not_backfill_konanc_fingerprint() {
+        let written = update_fingerprint_only(&Lockfile::with_toolchain("2.1.0"), true);
+        assert_eq!(written.konanc_fingerprint("2.1.0"), None);
+    }
+
+    #[test]
+    fn update_lockfile_keeps_recorded_konanc_fingerprint() {
+        // A differing install must be reported by doctor/verify, not re-pinned.
+        let mut lockfile = Lockfile::with_toolchain("2.1.0");
+        if let Some(tc) = lockfile.toolchain.as_mut() {
+            tc.konanc_fingerprint = Some("recorded".to_owned());
+        }
+        let written = update_fingerprint_only(&lockfile, false);
+        assert_eq!(written.konanc_fingerprint("2.1.0"), Some("recorded"));
+    }
+
+    #[test]
+    fn update_lockfile_records_fingerprint_for_new_version() {
+        let mut lockfile = Lockfile::with_toolchain("2.0.0");
+        if let Some(tc) = lockfile.toolchain.as_mut() {
+            tc.konanc_fingerprint = Some("old".to_owned());
+        }
+        let written = update_fingerprint_only(&lockfile, false);
+        assert_eq!(written.konanc_fingerprint("2.1.0"), Some("abc"));
+    }
+
+    #[test]
+    fn lockfile_toml_content_ignores_konanc_fingerprint() {
+        let without = Lockfile::with_toolchain("2.1.0");
+        let mut with = without.clone();
+        if let Some(tc) = with.toolchain.as_mut() {
+            tc.konanc_fingerprint = Some("abc".to_owned());
+        }
+        assert_eq!(
+            lockfile_toml_content(&without).unwrap(),
+            lockfile_toml_content(&with).unwrap()
+        );
+    }
+
     #[test]
     fn update_lockfile_hash_mismatch_is_hard_error() {
         let tmp = tempfile::tempdir().unwrap();
@@ -2673,15 +4905,18 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         // Hash mismatch without --force should be a hard error.
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         let result = update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             Some("newhash1"),
+            None,
             Some("newhash2"),
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &[],
@@ -2689,6 +4924,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         );
 
         assert!(result.is_err());
@@ -2715,15 +4951,19 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         // Hash mismatch with --force should warn but succeed.
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
+        let progress = RecordingProgress::default();
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             Some("newhash1"),
+            None,
             Some("newhash2"),
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &[],
@@ -2731,6 +4971,7 @@ mod tests {
             &lockfile_path,
             true,
             crate::common::test_resolver(false, false),
+            &progress,
         )
         .unwrap();
 
@@ -2738,6 +4979,55 @@ mod tests {
         let tc = reparsed.toolchain.as_ref().unwrap();
         assert_eq!(tc.konanc_tarball_sha256.as_deref(), Some("newhash1"));
         assert_eq!(tc.jre_tarball_sha256.as_deref(), Some("newhash2"));
+        assert_eq!(
+            *progress.warnings.lock().unwrap(),
+            [
+                "konanc tarball hash changed — expected oldhash1, got newhash1; lockfile updated (--force)",
+                "jre tarball hash changed — expected oldhash2, got newhash2; lockfile updated (--force)",
+            ]
+        );
+    }
+
+    #[test]
+    fn update_lockfile_reports_changed_dependency_source_to_progress_sink() {
+        let tmp = tempfile::tempdir().unwrap();
+        let lockfile_path = tmp.path().join("konvoy.lock");
+        let mut lockfile = Lockfile::with_toolchain("2.1.0");
+        lockfile.dependencies.push(DependencyLock {
+            name: "utils".to_owned(),
+            source: DepSource::Path {
+                path: "utils".to_owned(),
+            },
+            source_hash: "0123456789ab".to_owned(),
+        });
+        let mut utils = resolved_dep("utils", &[]);
+        utils.project_root = tmp.path().join("utils");
+        utils.source_hash = "ba9876543210".to_owned();
+        let graph = crate::resolve::ResolvedGraph { order: vec![utils] };
+        let progress = RecordingProgress::default();
+
+        update_lockfile_if_needed(
+            &lockfile,
+            None,
+            None,
+            None,
+            None,
+            DEFAULT_JRE_MAJOR,
+            &graph,
+            &[],
+            &[],
+            tmp.path(),
+            &lockfile_path,
+            false,
+            crate::common::test_resolver(false, false),
+            &progress,
+        )
+        .unwrap();
+
+        assert_eq!(
+            *progress.warnings.lock().unwrap(),
+            ["dependency `utils` source has changed (locked: 01234567, current: ba987654)"]
+        );
     }
 
     #[test]
@@ -2753,15 +5043,18 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         // Same version, no new hashes — should skip update entirely.
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             None,
             None,
+            None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &[],
@@ -2769,6 +5062,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
 
@@ -2790,15 +5084,18 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         // Version changed with no fresh download hashes: old version hashes must be cleared.
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
+            None,
             None,
             None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &[],
@@ -2806,6 +5103,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
 
@@ -2837,6 +5135,7 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         let dep = crate::resolve::ResolvedDep {
@@ -2849,14 +5148,17 @@ mod tests {
             .unwrap(),
             dep_names: Vec::new(),
             source_hash: "newhash456".to_owned(),
+            git: None,
         };
         let graph = crate::resolve::ResolvedGraph { order: vec![dep] };
 
         let result = update_lockfile_if_needed(
             &lf,
-            &konanc,
+            Some(&konanc),
             None,
             None,
+            None,
+            DEFAULT_JRE_MAJOR,
             &graph,
             &[],
             &[],
@@ -2864,6 +5166,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, true), // locked = true
+            &StderrProgress,
         );
 
         assert!(result.is_err());
@@ -2898,6 +5201,7 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         let dep = crate::resolve::ResolvedDep {
@@ -2910,15 +5214,18 @@ mod tests {
             .unwrap(),
             dep_names: Vec::new(),
             source_hash: "newhash456".to_owned(),
+            git: None,
         };
         let graph = crate::resolve::ResolvedGraph { order: vec![dep] };
 
         // Without --locked, mismatch should warn but succeed.
         let result = update_lockfile_if_needed(
             &lf,
-            &konanc,
+            Some(&konanc),
+            None,
             None,
             None,
+            DEFAULT_JRE_MAJOR,
             &graph,
             &[],
             &[],
@@ -2926,6 +5233,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false), // locked = false
+            &StderrProgress,
         );
 
         assert!(result.is_ok());
@@ -2951,6 +5259,7 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         // Dep roots are canonicalized in production (`resolve_dep_path`), so
@@ -2968,15 +5277,18 @@ mod tests {
             .unwrap(),
             dep_names: Vec::new(),
             source_hash: "samehash789".to_owned(),
+            git: None,
         };
         let graph = crate::resolve::ResolvedGraph { order: vec![dep] };
 
         // Matching hash with --locked should pass fine.
         let result = update_lockfile_if_needed(
             &lf,
-            &konanc,
+            Some(&konanc),
             None,
             None,
+            None,
+            DEFAULT_JRE_MAJOR,
             &graph,
             &[],
             &[],
@@ -2984,6 +5296,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, true), // locked = true
+            &StderrProgress,
         );
 
         assert!(result.is_ok());
@@ -3047,6 +5360,7 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
@@ -3054,9 +5368,11 @@ mod tests {
         // would need updating. With --locked, this should error.
         let result = update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
+            None,
             None,
             None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &[],
@@ -3064,6 +5380,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, true), // locked = true
+            &StderrProgress,
         );
 
         assert!(result.is_err());
@@ -3085,8 +5402,16 @@ mod tests {
         // different version, different tarball. Comparing would spuriously
         // report TarballHashMismatch on every legitimate toolchain upgrade.
         let lockfile = Lockfile::with_managed_toolchain("2.0.0", Some("old-sha"), Some("old-jre"));
-        verify_toolchain_tarball_pins(&lockfile, "2.1.0", Some("new-sha"), Some("new-jre"), false)
-            .unwrap();
+        verify_toolchain_tarball_pins(
+            &lockfile,
+            "2.1.0",
+            Some("new-sha"),
+            Some("new-jre"),
+            DEFAULT_JRE_MAJOR,
+            false,
+            &StderrProgress,
+        )
+        .unwrap();
     }
 
     #[test]
@@ -3095,8 +5420,15 @@ mod tests {
         // rotated upstream tarball — hard error without --force.
         let lockfile =
             Lockfile::with_managed_toolchain("2.1.0", Some("pinned"), Some("pinned-jre"));
-        let result =
-            verify_toolchain_tarball_pins(&lockfile, "2.1.0", Some("tampered"), None, false);
+        let result = verify_toolchain_tarball_pins(
+            &lockfile,
+            "2.1.0",
+            Some("tampered"),
+            None,
+            DEFAULT_JRE_MAJOR,
+            false,
+            &StderrProgress,
+        );
         assert!(
             matches!(result, Err(EngineError::TarballHashMismatch { .. })),
             "expected TarballHashMismatch, got: {result:?}"
@@ -3113,14 +5445,33 @@ mod tests {
             "2.1.0",
             Some("pinned"),
             Some("pinned-jre"),
+            DEFAULT_JRE_MAJOR,
             false,
+            &StderrProgress,
         )
         .unwrap();
         // Cached install (no fresh SHAs) — nothing to compare.
-        verify_toolchain_tarball_pins(&lockfile, "2.1.0", None, None, false).unwrap();
+        verify_toolchain_tarball_pins(
+            &lockfile,
+            "2.1.0",
+            None,
+            None,
+            DEFAULT_JRE_MAJOR,
+            false,
+            &StderrProgress,
+        )
+        .unwrap();
         // No toolchain section at all.
-        verify_toolchain_tarball_pins(&Lockfile::default(), "2.1.0", Some("x"), None, false)
-            .unwrap();
+        verify_toolchain_tarball_pins(
+            &Lockfile::default(),
+            "2.1.0",
+            Some("x"),
+            None,
+            DEFAULT_JRE_MAJOR,
+            false,
+            &StderrProgress,
+        )
+        .unwrap();
     }
 
     #[test]
@@ -3139,15 +5490,18 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         // Fresh download returns the SAME SHAs the lockfile already pins.
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         let result = update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             Some("pinned1"),
+            None,
             Some("pinned2"),
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &[],
@@ -3155,6 +5509,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, true), // locked = true
+            &StderrProgress,
         );
 
         assert!(
@@ -3183,14 +5538,17 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         let result = update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             Some("freshhash1"),
+            None,
             Some("freshhash2"),
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &[],
@@ -3198,6 +5556,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, true), // locked = true
+            &StderrProgress,
         );
 
         assert!(matches!(result, Err(EngineError::LockfileUpdateRequired)));
@@ -3215,9 +5574,14 @@ mod tests {
             toolchain: Some(konvoy_config::lockfile::ToolchainLock {
                 konanc_version: "2.0.0".to_owned(),
                 konanc_tarball_sha256: Some("old1".to_owned()),
+                konanc_tarball_url: None,
                 jre_tarball_sha256: Some("old2".to_owned()),
+                jre_major: None,
+                konanc_fingerprint: None,
                 detekt_version: Some("1.23.7".to_owned()),
                 detekt_jar_sha256: Some("detektsha".to_owned()),
+                ktlint_version: Some("1.3.1".to_owned()),
+                ktlint_jar_sha256: Some("ktlintsha".to_owned()),
             }),
             ..Default::default()
         };
@@ -3227,14 +5591,17 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(), // toolchain version changed
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
+            None,
             None,
             None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &[],
@@ -3242,6 +5609,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
 
@@ -3258,6 +5626,8 @@ mod tests {
             Some("detektsha"),
             "detekt jar hash must survive a toolchain-changing build"
         );
+        assert_eq!(tc.ktlint_version.as_deref(), Some("1.3.1"));
+        assert_eq!(tc.ktlint_jar_sha256.as_deref(), Some("ktlintsha"));
     }
 
     // -----------------------------------------------------------------------
@@ -3315,6 +5685,7 @@ mod tests {
             .unwrap(),
             dep_names: Vec::new(),
             source_hash: source_hash.to_owned(),
+                git: None,
         }
     }
 
@@ -3338,6 +5709,7 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
         let graph = crate::resolve::ResolvedGraph {
             order: vec![resolved_path_dep(
@@ -3349,9 +5721,11 @@ mod tests {
 
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             None,
             None,
+            None,
+            DEFAULT_JRE_MAJOR,
             &graph,
             &[],
             &[],
@@ -3359,6 +5733,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
 
@@ -3385,6 +5760,7 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         // Checkout A writes the lockfile.
@@ -3404,9 +5780,11 @@ mod tests {
         };
         update_lockfile_if_needed(
             &Lockfile::from_path(&a_lock).unwrap(),
-            &konanc,
+            Some(&konanc),
+            None,
             None,
             None,
+            DEFAULT_JRE_MAJOR,
             &a_graph,
             &[],
             &[],
@@ -3414,6 +5792,7 @@ mod tests {
             &a_lock,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
 
@@ -3435,9 +5814,11 @@ mod tests {
 
         let result = update_lockfile_if_needed(
             &Lockfile::from_path(&b_lock).unwrap(),
-            &konanc,
+            Some(&konanc),
             None,
             None,
+            None,
+            DEFAULT_JRE_MAJOR,
             &b_graph,
             &[],
             &[],
@@ -3445,6 +5826,7 @@ mod tests {
             &b_lock,
             false,
             crate::common::test_resolver(false, true), // locked = true
+            &StderrProgress,
         );
 
         assert!(
@@ -3467,9 +5849,14 @@ mod tests {
             toolchain: Some(konvoy_config::lockfile::ToolchainLock {
                 konanc_version: "2.0.0".to_owned(),
                 konanc_tarball_sha256: Some("old1".to_owned()),
+                konanc_tarball_url: None,
                 jre_tarball_sha256: Some("old2".to_owned()),
+                jre_major: None,
+                konanc_fingerprint: None,
                 detekt_version: Some("1.23.7".to_owned()),
                 detekt_jar_sha256: Some("detektsha".to_owned()),
+                ktlint_version: None,
+                ktlint_jar_sha256: None,
             }),
             ..Default::default()
         };
@@ -3478,9 +5865,10 @@ mod tests {
         // so the version-mismatch branch runs.
         let effective = predicted_effective_lockfile(
             &lockfile,
-            "2.1.0",
+            Some("2.1.0"),
             Some("new1"),
             Some("new2"),
+            DEFAULT_JRE_MAJOR,
             &[],
             &[],
             &crate::resolve::ResolvedGraph { order: Vec::new() },
@@ -3520,6 +5908,7 @@ mod tests {
             path: PathBuf::from("/fake/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc123".to_owned(),
+            launcher_fingerprint: "abc123".to_owned(),
         };
         let target = konvoy_targets::Target::LinuxX64;
         let profile = Profile::Debug;
@@ -3535,12 +5924,15 @@ mod tests {
             konanc_fingerprint: konanc.fingerprint.clone(),
             target,
             profile,
-            source_dir: project.join("src"),
+            source_dirs: vec![project.join("src")],
             source_glob: "**/*.kt".to_owned(),
+            ignore: IgnoreRules::default(),
             os: std::env::consts::OS.to_owned(),
             arch: std::env::consts::ARCH.to_owned(),
             dependency_hashes: Vec::new(),
             codegen_hashes: Vec::new(),
+            features: Vec::new(),
+            deny_warnings: false,
         };
         let cache_key = CacheKey::compute(&cache_inputs).unwrap();
 
@@ -3564,7 +5956,20 @@ mod tests {
             target: None,
             profile: Profile::Debug,
             verbose: false,
+            quiet: false,
             force: false,
+            timeout: None,
+            dedupe_diagnostics: false,
+            color: ColorChoice::Never,
+            timings: false,
+            features: Vec::new(),
+            no_default_features: false,
+            deny_warnings: false,
+            emit_command: None,
+            out_dir: None,
+            target_dir: None,
+            keep_going: false,
+            progress: Arc::new(StderrProgress),
         };
         let cc_no_force = CompileContext {
             konanc: &konanc,
@@ -3572,6 +5977,11 @@ mod tests {
             target: &target,
             options: &options_no_force,
             library_inputs: &[],
+            timeout: None,
+            deny_warnings: false,
+            compress_cache: false,
+            features: &[],
+            command_log: None,
         };
         let (_, outcome) = build_single(
             &project,
@@ -3590,7 +6000,20 @@ mod tests {
             target: None,
             profile: Profile::Debug,
             verbose: false,
+            quiet: false,
             force: true,
+            timeout: None,
+            dedupe_diagnostics: false,
+            color: ColorChoice::Never,
+            timings: false,
+            features: Vec::new(),
+            no_default_features: false,
+            deny_warnings: false,
+            emit_command: None,
+            out_dir: None,
+            target_dir: None,
+            keep_going: false,
+            progress: Arc::new(StderrProgress),
         };
         let cc_force = CompileContext {
             konanc: &konanc,
@@ -3598,6 +6021,11 @@ mod tests {
             target: &target,
             options: &options_force,
             library_inputs: &[],
+            timeout: None,
+            deny_warnings: false,
+            compress_cache: false,
+            features: &[],
+            command_log: None,
         };
         let result = build_single(&project, &manifest, &cc_force, profile, &lockfile_content);
 
@@ -3699,9 +6127,10 @@ mod tests {
         let first_on_disk = Lockfile::default();
         let effective_first = predicted_effective_lockfile(
             &first_on_disk,
-            konanc_version,
+            Some(konanc_version),
             None,
             None,
+            DEFAULT_JRE_MAJOR,
             &plugin_locks,
             &[],
             &crate::resolve::ResolvedGraph { order: Vec::new() },
@@ -3718,9 +6147,10 @@ mod tests {
         // the same plugin locks again.
         let effective_second = predicted_effective_lockfile(
             &written,
-            konanc_version,
+            Some(konanc_version),
             None,
             None,
+            DEFAULT_JRE_MAJOR,
             &plugin_locks,
             &[],
             &crate::resolve::ResolvedGraph { order: Vec::new() },
@@ -3783,9 +6213,10 @@ mod tests {
         let first_on_disk = Lockfile::default();
         let effective_first = predicted_effective_lockfile(
             &first_on_disk,
-            konanc_version,
+            Some(konanc_version),
             None,
             None,
+            DEFAULT_JRE_MAJOR,
             &plugin_locks,
             &[],
             &crate::resolve::ResolvedGraph { order: Vec::new() },
@@ -3800,9 +6231,10 @@ mod tests {
         // Second build reads `written` from disk and resolves the same union.
         let effective_second = predicted_effective_lockfile(
             &written,
-            konanc_version,
+            Some(konanc_version),
             None,
             None,
+            DEFAULT_JRE_MAJOR,
             &plugin_locks,
             &[],
             &crate::resolve::ResolvedGraph { order: Vec::new() },
@@ -3850,6 +6282,7 @@ mod tests {
                 manifest: dep_manifest,
                 dep_names: Vec::new(),
                 source_hash: "dep-source-hash".to_owned(),
+                git: None,
             }],
         };
 
@@ -3857,9 +6290,10 @@ mod tests {
         let first_on_disk = Lockfile::default();
         let effective_first = predicted_effective_lockfile(
             &first_on_disk,
-            konanc_version,
+            Some(konanc_version),
             None,
             None,
+            DEFAULT_JRE_MAJOR,
             &[],
             &[],
             &graph,
@@ -3875,12 +6309,15 @@ mod tests {
             path: PathBuf::from("/fake/konanc"),
             version: konanc_version.to_owned(),
             fingerprint: "fp".to_owned(),
+            launcher_fingerprint: "fp".to_owned(),
         };
         update_lockfile_if_needed(
             &first_on_disk,
-            &konanc,
+            Some(&konanc),
             None,
             None,
+            None,
+            DEFAULT_JRE_MAJOR,
             &graph,
             &[],
             &[],
@@ -3888,6 +6325,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
         let written = Lockfile::from_path(&lockfile_path).unwrap();
@@ -3895,9 +6333,10 @@ mod tests {
         // Second build reads `written` from disk and resolves the same graph.
         let effective_second = predicted_effective_lockfile(
             &written,
-            konanc_version,
+            Some(konanc_version),
             None,
             None,
+            DEFAULT_JRE_MAJOR,
             &[],
             &[],
             &graph,
@@ -3930,6 +6369,7 @@ mod tests {
             path: PathBuf::from("/fake/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "fp".to_owned(),
+            launcher_fingerprint: "fp".to_owned(),
         };
         let plugin_locks = vec![
             PluginLock {
@@ -3951,9 +6391,11 @@ mod tests {
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
+            None,
             None,
             None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &plugin_locks,
             &[],
@@ -3961,6 +6403,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
 
@@ -3975,9 +6418,11 @@ mod tests {
         let before = fs::read_to_string(&lockfile_path).unwrap();
         update_lockfile_if_needed(
             &written,
-            &konanc,
+            Some(&konanc),
             None,
             None,
+            None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &plugin_locks,
             &[],
@@ -3985,6 +6430,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, true),
+            &StderrProgress,
         )
         .unwrap();
         let after = fs::read_to_string(&lockfile_path).unwrap();
@@ -4061,9 +6507,10 @@ mod tests {
 
         let effective = predicted_effective_lockfile(
             &lockfile,
-            konanc_version,
+            Some(konanc_version),
             None,
             None,
+            DEFAULT_JRE_MAJOR,
             &[],
             &[],
             &crate::resolve::ResolvedGraph { order: Vec::new() },
@@ -4205,6 +6652,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn check_lockfile_staleness_tracks_ktlint_version() {
+        let manifest = konvoy_config::manifest::Manifest::from_str(
+            "[package]\nname = \"myapp\"\n\n[toolchain]\nkotlin = \"2.1.0\"\nktlint = \"1.3.1\"\n",
+            "konvoy.toml",
+        )
+        .unwrap();
+        let mut lockfile = Lockfile::with_toolchain("2.1.0");
+        assert!(check_lockfile_staleness(&manifest, &lockfile).is_err());
+
+        if let Some(tc) = &mut lockfile.toolchain {
+            tc.ktlint_version = Some("1.2.0".to_owned());
+        }
+        assert!(check_lockfile_staleness(&manifest, &lockfile).is_err());
+
+        if let Some(tc) = &mut lockfile.toolchain {
+            tc.ktlint_version = Some("1.3.1".to_owned());
+        }
+        assert!(check_lockfile_staleness(&manifest, &lockfile).is_ok());
+    }
+
     #[test]
     fn check_lockfile_staleness_missing_plugin_entries_errors() {
         // Manifest declares a plugin, but lockfile has no plugin entries.
@@ -4449,6 +6917,7 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         let plugin_locks = vec![
@@ -4464,9 +6933,11 @@ mod tests {
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             None,
             None,
+            None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &plugin_locks,
             &[],
@@ -4474,6 +6945,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
 
@@ -4569,14 +7041,17 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
         let codegen_locks = vec![fabrikt_lock("codegenhash1", "20.0.0")];
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
+            None,
             None,
             None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &codegen_locks,
@@ -4584,6 +7059,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
 
@@ -4609,14 +7085,17 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
         let new_codegen_locks = vec![fabrikt_lock("newhash", "20.0.0")];
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             None,
             None,
+            None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &new_codegen_locks,
@@ -4624,6 +7103,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
 
@@ -4650,6 +7130,7 @@ mod tests {
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         // New plugin locks differ from what's in the lockfile.
@@ -4664,9 +7145,11 @@ mod tests {
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
+            None,
             None,
             None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &new_plugin_locks,
             &[],
@@ -4674,6 +7157,7 @@ mod tests {
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
 
@@ -4724,6 +7208,7 @@ mod tests {
                 targets,
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "maven-hash".to_owned(),
         });
@@ -4753,41 +7238,233 @@ mod tests {
         );
     }
 
-    fn maven_lock(name: &str, coord: &str, version: &str, required_by: &[&str]) -> DependencyLock {
-        DependencyLock {
+    fn maven_lock(name: &str, coord: &str, version: &str, required_by: &[&str]) -> DependencyLock {
+        DependencyLock {
+            name: name.to_owned(),
+            source: DepSource::Maven {
+                version: version.to_owned(),
+                maven: coord.to_owned(),
+                targets: std::collections::BTreeMap::new(),
+                required_by: required_by.iter().map(|s| (*s).to_owned()).collect(),
+                classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
+            },
+            source_hash: "h".to_owned(),
+        }
+    }
+
+    fn closure_names(manifest: &Manifest, lockfile: &Lockfile) -> Vec<String> {
+        let mut names: Vec<String> = project_maven_closure(manifest, lockfile)
+            .iter()
+            .map(|d| d.name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn resolved_dep(name: &str, dep_names: &[&str]) -> ResolvedDep {
+        ResolvedDep {
+            name: name.to_owned(),
+            project_root: PathBuf::from(format!("/{name}")),
+            manifest: konvoy_config::manifest::Manifest::from_str(
+                "[package]\nname = \"x\"\nkind = \"lib\"\n\n[toolchain]\nkotlin = \"2.2.0\"\n",
+                "konvoy.toml",
+            )
+            .unwrap(),
+            dep_names: dep_names.iter().map(|s| (*s).to_owned()).collect(),
+            source_hash: "h".to_owned(),
+            git: None,
+        }
+    }
+
+    #[test]
+    fn prune_stale_lock_entries_drops_removed_maven_dep_and_its_transitives() {
+        // `datetime` was removed from konvoy.toml; `serialization` is still
+        // declared. The removed dep's transitive goes with it, the kept dep's
+        // stays, and the toolchain section is untouched.
+        let manifest = konvoy_config::manifest::Manifest::from_str(
+            "[package]\nname = \"app\"\n\n[toolchain]\nkotlin = \"2.2.0\"\n\n[dependencies]\nserialization = { maven = \"org.jetbrains.kotlinx:kotlinx-serialization-core\", version = \"1.7.3\" }\n",
+            "konvoy.toml",
+        )
+        .unwrap();
+        let mut lockfile = Lockfile::with_toolchain("2.2.0");
+        lockfile.dependencies = vec![
+            maven_lock(
+                "datetime",
+                "org.jetbrains.kotlinx:kotlinx-datetime",
+                "0.6.0",
+                &[],
+            ),
+            maven_lock(
+                "datetime-dep",
+                "org.example:datetime-dep",
+                "1.0",
+                &["datetime"],
+            ),
+            maven_lock(
+                "serialization",
+                "org.jetbrains.kotlinx:kotlinx-serialization-core",
+                "1.7.3",
+                &[],
+            ),
+            maven_lock(
+                "serialization-dep",
+                "org.example:ser-dep",
+                "1.0",
+                &["serialization"],
+            ),
+        ];
+        let graph = crate::resolve::ResolvedGraph { order: Vec::new() };
+
+        let (pruned, stale) = prune_stale_lock_entries(&manifest, &graph, &lockfile);
+        let names: Vec<&str> = pruned
+            .dependencies
+            .iter()
+            .map(|d| d.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["serialization", "serialization-dep"]);
+        assert_eq!(
+            stale,
+            vec![
+                "dependency `datetime`".to_owned(),
+                "dependency `datetime-dep`".to_owned()
+            ]
+        );
+        assert_eq!(pruned.toolchain, lockfile.toolchain);
+    }
+
+    #[test]
+    fn prune_stale_lock_entries_keeps_path_dep_maven_and_drops_removed_path_dep() {
+        // A Maven dep declared only by a path-dep stays pinned; a path-dep that
+        // left the graph is dropped.
+        let root = konvoy_config::manifest::Manifest::from_str(
+            "[package]\nname = \"app\"\n\n[toolchain]\nkotlin = \"2.2.0\"\n",
+            "konvoy.toml",
+        )
+        .unwrap();
+        let mut utils = resolved_dep("utils", &[]);
+        utils.manifest = konvoy_config::manifest::Manifest::from_str(
+            "[package]\nname = \"utils\"\nkind = \"lib\"\n\n[toolchain]\nkotlin = \"2.2.0\"\n\n[dependencies]\ndatetime = { maven = \"org.jetbrains.kotlinx:kotlinx-datetime\", version = \"0.6.0\" }\n",
+            "konvoy.toml",
+        )
+        .unwrap();
+        let graph = crate::resolve::ResolvedGraph { order: vec![utils] };
+        let mut lockfile = Lockfile::with_toolchain("2.2.0");
+        lockfile.dependencies = vec![
+            maven_lock(
+                "datetime",
+                "org.jetbrains.kotlinx:kotlinx-datetime",
+                "0.6.0",
+                &[],
+            ),
+            DependencyLock {
+                name: "old-lib".to_owned(),
+                source: DepSource::Path {
+                    path: "../old-lib".to_owned(),
+                },
+                source_hash: "h".to_owned(),
+            },
+            DependencyLock {
+                name: "utils".to_owned(),
+                source: DepSource::Path {
+                    path: "utils".to_owned(),
+                },
+                source_hash: "h".to_owned(),
+            },
+        ];
+
+        let (pruned, stale) = prune_stale_lock_entries(&root, &graph, &lockfile);
+        let names: Vec<&str> = pruned
+            .dependencies
+            .iter()
+            .map(|d| d.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["datetime", "utils"]);
+        assert_eq!(stale, vec!["dependency `old-lib`".to_owned()]);
+    }
+
+    #[test]
+    fn prune_stale_lock_entries_keeps_disabled_optional_git_dep() {
+        // `extra` is optional and no feature enables it, so it is absent from
+        // the graph; it is still declared, so its git pin stays.
+        let manifest = konvoy_config::manifest::Manifest::from_str(
+            "[package]\nname = \"app\"\n\n[toolchain]\nkotlin = \"2.2.0\"\n\n[dependencies]\nextra = { git = \"https://example.com/extra.git\", rev = \"main\", optional = true }\n",
+            "konvoy.toml",
+        )
+        .unwrap();
+        let mut lockfile = Lockfile::with_toolchain("2.2.0");
+        lockfile.dependencies = vec![DependencyLock {
+            name: "extra".to_owned(),
+            source: DepSource::Git {
+                url: "https://example.com/extra.git".to_owned(),
+                rev: "main".to_owned(),
+                commit: "0123456789abcdef0123456789abcdef01234567".to_owned(),
+            },
+            source_hash: "h".to_owned(),
+        }];
+        let graph = crate::resolve::ResolvedGraph { order: Vec::new() };
+
+        let (pruned, stale) = prune_stale_lock_entries(&manifest, &graph, &lockfile);
+        assert!(stale.is_empty(), "{stale:?}");
+        assert_eq!(pruned, lockfile);
+        let tmp = tempfile::tempdir().unwrap();
+        assert_eq!(
+            predicted_dependency_locks(&pruned, &graph, tmp.path()),
+            lockfile.dependencies
+        );
+    }
+
+    #[test]
+    fn prune_stale_lock_entries_drops_undeclared_plugin() {
+        let manifest = konvoy_config::manifest::Manifest::from_str(
+            "[package]\nname = \"app\"\n\n[toolchain]\nkotlin = \"2.2.0\"\n\n[plugins]\nserialization = { maven = \"org.jetbrains.kotlin:kotlin-serialization-compiler-plugin\", version = \"{kotlin}\" }\n",
+            "konvoy.toml",
+        )
+        .unwrap();
+        let plugin = |name: &str, maven: &str| PluginLock {
             name: name.to_owned(),
-            source: DepSource::Maven {
-                version: version.to_owned(),
-                maven: coord.to_owned(),
-                targets: std::collections::BTreeMap::new(),
-                required_by: required_by.iter().map(|s| (*s).to_owned()).collect(),
-                classifier: None,
-            },
-            source_hash: "h".to_owned(),
-        }
-    }
+            maven: maven.to_owned(),
+            version: "2.2.0".to_owned(),
+            sha256: "abc".to_owned(),
+            url: "https://example.com/p.jar".to_owned(),
+        };
+        let mut lockfile = Lockfile::with_toolchain("2.2.0");
+        lockfile.plugins = vec![
+            plugin(
+                "serialization",
+                "org.jetbrains.kotlin:kotlin-serialization-compiler-plugin",
+            ),
+            plugin(
+                "allopen",
+                "org.jetbrains.kotlin:kotlin-allopen-compiler-plugin",
+            ),
+        ];
+        let graph = crate::resolve::ResolvedGraph { order: Vec::new() };
 
-    fn closure_names(manifest: &Manifest, lockfile: &Lockfile) -> Vec<String> {
-        let mut names: Vec<String> = project_maven_closure(manifest, lockfile)
-            .iter()
-            .map(|d| d.name.clone())
-            .collect();
-        names.sort();
-        names
+        let (pruned, stale) = prune_stale_lock_entries(&manifest, &graph, &lockfile);
+        assert_eq!(pruned.plugins.len(), 1);
+        assert_eq!(stale, vec!["plugin `allopen`".to_owned()]);
     }
 
-    fn resolved_dep(name: &str, dep_names: &[&str]) -> ResolvedDep {
-        ResolvedDep {
-            name: name.to_owned(),
-            project_root: PathBuf::from(format!("/{name}")),
-            manifest: konvoy_config::manifest::Manifest::from_str(
-                "[package]\nname = \"x\"\nkind = \"lib\"\n\n[toolchain]\nkotlin = \"2.2.0\"\n",
-                "konvoy.toml",
-            )
-            .unwrap(),
-            dep_names: dep_names.iter().map(|s| (*s).to_owned()).collect(),
-            source_hash: "h".to_owned(),
-        }
+    #[test]
+    fn prune_stale_lock_entries_reports_nothing_for_a_current_lockfile() {
+        let manifest = konvoy_config::manifest::Manifest::from_str(
+            "[package]\nname = \"app\"\n\n[toolchain]\nkotlin = \"2.2.0\"\n\n[dependencies]\ndatetime = { maven = \"org.jetbrains.kotlinx:kotlinx-datetime\", version = \"0.6.0\" }\n",
+            "konvoy.toml",
+        )
+        .unwrap();
+        let mut lockfile = Lockfile::with_toolchain("2.2.0");
+        lockfile.dependencies = vec![maven_lock(
+            "datetime",
+            "org.jetbrains.kotlinx:kotlinx-datetime",
+            "0.6.0",
+            &[],
+        )];
+        let graph = crate::resolve::ResolvedGraph { order: Vec::new() };
+
+        let (pruned, stale) = prune_stale_lock_entries(&manifest, &graph, &lockfile);
+        assert!(stale.is_empty());
+        assert_eq!(pruned, lockfile);
     }
 
     #[test]
@@ -4807,6 +7484,7 @@ mod tests {
                 targets: std::collections::BTreeMap::new(),
                 required_by: vec!["atomicfu".to_owned()],
                 classifier: Some("cinterop-interop".to_owned()),
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "h".to_owned(),
         };
@@ -4856,6 +7534,90 @@ mod tests {
         );
     }
 
+    #[test]
+    fn lockfile_is_byte_identical_whatever_the_graph_order() {
+        // `resolve_dependencies` collects the graph through a HashMap, and
+        // plugin locks follow the order they were fetched in. Neither order may
+        // leak into the written lockfile or the cache key.
+        let tmp = tempfile::tempdir().unwrap();
+        let dep = |name: &str| {
+            let mut dep = resolved_dep(name, &[]);
+            dep.project_root = tmp.path().join(name);
+            dep
+        };
+        let plugin = |name: &str| PluginLock {
+            name: name.to_owned(),
+            maven: format!("org.jetbrains.kotlin:{name}"),
+            version: "2.1.0".to_owned(),
+            sha256: "s".to_owned(),
+            url: "u".to_owned(),
+        };
+        let mut lockfile = Lockfile::with_toolchain("2.1.0");
+        lockfile.dependencies = vec![
+            maven_lock("mlib", "g:m", "1.0", &["zlib", "alib"]),
+            maven_lock("blib", "g:b", "1.0", &[]),
+        ];
+        let predict = |order: Vec<ResolvedDep>, plugins: &[PluginLock]| {
+            predicted_effective_lockfile(
+                &lockfile,
+                Some("2.1.0"),
+                None,
+                None,
+                DEFAULT_JRE_MAJOR,
+                plugins,
+                &[],
+                &crate::resolve::ResolvedGraph { order },
+                tmp.path(),
+                crate::common::test_resolver(false, false),
+            )
+        };
+        let first = predict(
+            vec![dep("zlib"), dep("alib")],
+            &[plugin("allopen"), plugin("noarg")],
+        );
+        let second = predict(
+            vec![dep("alib"), dep("zlib")],
+            &[plugin("noarg"), plugin("allopen")],
+        );
+
+        assert_eq!(
+            lockfile_toml_content(&first).unwrap(),
+            lockfile_toml_content(&second).unwrap()
+        );
+        let (a, b) = (tmp.path().join("a.lock"), tmp.path().join("b.lock"));
+        first.write_to(&a).unwrap();
+        second.write_to(&b).unwrap();
+        assert_eq!(fs::read(&a).unwrap(), fs::read(&b).unwrap());
+    }
+
+    #[test]
+    fn split_blocked_skips_dependents_of_unbuilt_deps() {
+        let app = resolved_dep("app", &["core"]);
+        let cli = resolved_dep("cli", &["app"]);
+        let json = resolved_dep("json", &[]);
+        let level = vec![&app, &cli, &json];
+        let dep_inputs: HashMap<&str, (Vec<String>, Vec<LibraryInput>)> = HashMap::from([
+            ("app", (vec!["core".to_owned()], Vec::new())),
+            (
+                "cli",
+                (vec!["app".to_owned(), "core".to_owned()], Vec::new()),
+            ),
+            ("json", (Vec::new(), Vec::new())),
+        ]);
+        let names = |deps: Vec<&ResolvedDep>| -> Vec<String> {
+            deps.into_iter().map(|d| d.name.clone()).collect()
+        };
+
+        let (ready, blocked) = split_blocked(&level, &dep_inputs, &HashSet::new());
+        assert_eq!(names(ready), ["app", "cli", "json"]);
+        assert!(blocked.is_empty());
+
+        let unbuilt = HashSet::from(["core".to_owned()]);
+        let (ready, blocked) = split_blocked(&level, &dep_inputs, &unbuilt);
+        assert_eq!(names(ready), ["json"]);
+        assert_eq!(names(blocked), ["app", "cli"]);
+    }
+
     #[test]
     fn predicted_dependency_locks_drops_deps_with_empty_source_hash() {
         // A dep whose source hasn't been hashed (empty source_hash) is omitted —
@@ -4941,6 +7703,7 @@ mod tests {
                 targets: std::collections::BTreeMap::new(),
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "h".to_owned(),
         };
@@ -4974,6 +7737,7 @@ mod tests {
                     targets: std::collections::BTreeMap::new(),
                     required_by: vec!["atomicfu".to_owned()],
                     classifier: Some("cinterop-interop".to_owned()),
+                    hash_algorithm: HashAlgorithm::Sha256,
                 },
                 source_hash: "h".to_owned(),
             },
@@ -5236,6 +8000,7 @@ mod tests {
                 targets: std::collections::BTreeMap::new(),
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "hash".to_owned(),
         });
@@ -5270,6 +8035,7 @@ mod tests {
                 targets,
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "hash".to_owned(),
         });
@@ -5318,6 +8084,7 @@ mod tests {
                 targets,
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "hash".to_owned(),
         });
@@ -5338,6 +8105,83 @@ mod tests {
         assert_eq!(inputs[0].precomputed_sha256.as_deref(), Some(hash.as_str()));
     }
 
+    #[test]
+    fn fetch_plugins_and_klibs_resolves_both_kinds_in_one_pass() {
+        // A cached plugin JAR and a cached klib come back from the same work
+        // list, each re-verified against its own pin, with no network (--offline).
+        let target: konvoy_targets::Target = "linux_x64".parse().unwrap();
+        let group = "com.example.combinedfetch";
+        let artifact = "cached-lib";
+        let version = "9.9.9-combined";
+
+        let cache_root = crate::plugin::maven_cache_root().unwrap();
+        let per_target = format!("{artifact}-{}", target.to_maven_suffix());
+        let coord = konvoy_util::maven::MavenCoordinate::new(group, &per_target, version)
+            .with_packaging("klib");
+        let dest = coord.cache_path(&cache_root);
+        fs::create_dir_all(dest.parent().unwrap()).unwrap();
+        fs::write(&dest, b"cached klib content").unwrap();
+        let klib_hash = konvoy_util::hash::sha256_bytes(b"cached klib content");
+
+        let tmp = tempfile::tempdir().unwrap();
+        let jar = tmp.path().join("plugin.jar");
+        fs::write(&jar, b"cached plugin content").unwrap();
+        let jar_hash = konvoy_util::hash::sha256_bytes(b"cached plugin content");
+
+        let mut targets = std::collections::BTreeMap::new();
+        targets.insert("linux_x64".to_owned(), klib_hash.clone());
+        let mut lockfile = Lockfile::with_toolchain("2.1.0");
+        lockfile.dependencies.push(DependencyLock {
+            name: "cached-lib".to_owned(),
+            source: DepSource::Maven {
+                version: version.to_owned(),
+                maven: format!("{group}:{artifact}"),
+                targets,
+                required_by: Vec::new(),
+                classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
+            },
+            source_hash: "hash".to_owned(),
+        });
+        lockfile.plugins.push(PluginLock {
+            name: "present".to_owned(),
+            maven: "org.example:present".to_owned(),
+            version: "1.0.0".to_owned(),
+            sha256: jar_hash.clone(),
+            url: "http://example.com".to_owned(),
+        });
+        let plugins = vec![crate::plugin::ResolvedPluginArtifact {
+            plugin_name: "present".to_owned(),
+            maven_coord: konvoy_util::maven::MavenCoordinate::new(
+                "org.example",
+                "present",
+                "1.0.0",
+            ),
+            url: "http://127.0.0.1:1/present.jar".to_owned(), // unused
+            cache_path: jar,
+        }];
+        let entries: Vec<&DependencyLock> = lockfile.dependencies.iter().collect();
+
+        let result = fetch_plugins_and_klibs(
+            &plugins,
+            &entries,
+            &lockfile,
+            &target,
+            &crate::progress::StderrProgress,
+            crate::common::test_resolver(true, false),
+        );
+        let _ = fs::remove_dir_all(cache_root.join("com").join("example").join("combinedfetch"));
+
+        let (plugin_results, klibs) = result.expect("cached artifacts resolve under --offline");
+        assert_eq!(plugin_results.len(), 1);
+        assert_eq!(plugin_results[0].sha256, jar_hash);
+        assert_eq!(klibs.len(), 1);
+        assert_eq!(
+            klibs[0].precomputed_sha256.as_deref(),
+            Some(klib_hash.as_str())
+        );
+    }
+
     #[test]
     fn resolve_maven_deps_works_with_lockfile_missing_toolchain() {
         // Regression: when `konvoy update` wrote a lockfile without a
@@ -5358,6 +8202,7 @@ mod tests {
                 targets,
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "hash".to_owned(),
         });
@@ -5466,6 +8311,7 @@ linux_x64 = "1111"
                 targets: std::collections::BTreeMap::new(),
                 required_by: vec!["atomicfu".to_owned()],
                 classifier: Some("cinterop-interop".to_owned()),
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "hash".to_owned(),
         });
@@ -5506,6 +8352,7 @@ linux_x64 = "1111"
                 targets: targets1,
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "main-source-hash".to_owned(),
         });
@@ -5521,6 +8368,7 @@ linux_x64 = "1111"
                 targets: targets2,
                 required_by: vec!["atomicfu".to_owned()],
                 classifier: Some("cinterop-interop".to_owned()),
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "cinterop-source-hash".to_owned(),
         });
@@ -5551,6 +8399,7 @@ linux_x64 = "1111"
                 targets: targets1,
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "main-hash".to_owned(),
         });
@@ -5566,6 +8415,7 @@ linux_x64 = "1111"
                 targets: targets2,
                 required_by: vec!["atomicfu".to_owned()],
                 classifier: Some("cinterop-interop".to_owned()),
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "cinterop-hash".to_owned(),
         });
@@ -5576,14 +8426,17 @@ linux_x64 = "1111"
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             None,
             None,
+            None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &[],
@@ -5591,6 +8444,7 @@ linux_x64 = "1111"
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
 
@@ -5627,6 +8481,7 @@ linux_x64 = "1111"
                 targets,
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "maven-hash".to_owned(),
         });
@@ -5636,14 +8491,17 @@ linux_x64 = "1111"
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
+            None,
             None,
             None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &[],
@@ -5651,6 +8509,7 @@ linux_x64 = "1111"
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
 
@@ -5717,6 +8576,7 @@ linux_x64 = "1111"
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         // New plugin locks differ from what's in the lockfile.
@@ -5731,9 +8591,11 @@ linux_x64 = "1111"
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         let result = update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             None,
             None,
+            None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &new_plugin_locks,
             &[],
@@ -5741,6 +8603,7 @@ linux_x64 = "1111"
             &lockfile_path,
             false,
             crate::common::test_resolver(false, true), // locked = true
+            &StderrProgress,
         );
 
         assert!(result.is_err());
@@ -5761,6 +8624,7 @@ linux_x64 = "1111"
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         let dep = crate::resolve::ResolvedDep {
@@ -5773,6 +8637,7 @@ linux_x64 = "1111"
             .unwrap(),
             dep_names: Vec::new(),
             source_hash: "dephash".to_owned(),
+            git: None,
         };
         let graph = crate::resolve::ResolvedGraph { order: vec![dep] };
 
@@ -5786,9 +8651,11 @@ linux_x64 = "1111"
 
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
+            None,
             None,
             None,
+            DEFAULT_JRE_MAJOR,
             &graph,
             &plugin_locks,
             &[],
@@ -5796,6 +8663,7 @@ linux_x64 = "1111"
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
 
@@ -6066,6 +8934,7 @@ kotlin-serialization = { maven = "org.jetbrains.kotlin:kotlin-serialization-comp
                 targets,
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "src-hash".to_owned(),
         });
@@ -6183,12 +9052,15 @@ url = "https://example.com/plugin.jar"
             konanc_fingerprint: "abc123".to_owned(),
             target: Target::LinuxX64,
             profile: Profile::Debug,
-            source_dir: dir.path().to_path_buf(),
+            source_dirs: vec![dir.path().to_path_buf()],
             source_glob: "**/*.kt".to_owned(),
+            ignore: IgnoreRules::default(),
             os: "linux".to_owned(),
             arch: "x86_64".to_owned(),
             dependency_hashes: Vec::new(),
             codegen_hashes: Vec::new(),
+            features: Vec::new(),
+            deny_warnings: false,
         };
 
         let key_no_plugins = CacheKey::compute(&make_inputs(content_no_plugins)).unwrap();
@@ -6235,12 +9107,15 @@ url = "https://example.com/plugin.jar"
             konanc_fingerprint: "abc".to_owned(),
             target: Target::LinuxX64,
             profile: Profile::Debug,
-            source_dir: dir.path().to_path_buf(),
+            source_dirs: vec![dir.path().to_path_buf()],
             source_glob: "**/*.kt".to_owned(),
+            ignore: IgnoreRules::default(),
             os: "linux".to_owned(),
             arch: "x86_64".to_owned(),
             dependency_hashes: Vec::new(),
             codegen_hashes: Vec::new(),
+            features: Vec::new(),
+            deny_warnings: false,
         };
 
         let key_v1 = CacheKey::compute(&make_inputs(content_v1)).unwrap();
@@ -6252,6 +9127,44 @@ url = "https://example.com/plugin.jar"
         );
     }
 
+    #[test]
+    fn cache_key_changes_when_plugin_options_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest_with = |preset: &str| {
+            let toml = format!(
+                "[package]\nname = \"myapp\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n\n[plugins.kotlin-allopen]\nmaven = \"org.jetbrains.kotlin:kotlin-allopen-compiler-plugin\"\nversion = \"{{kotlin}}\"\nid = \"org.jetbrains.kotlin.allopen\"\noptions = {{ preset = \"{preset}\" }}\n"
+            );
+            Manifest::from_str(&toml, "konvoy.toml")
+                .unwrap()
+                .to_toml()
+                .unwrap()
+        };
+        let make_inputs = |manifest_content: String| CacheInputs {
+            manifest_content,
+            lockfile_content: String::new(),
+            konanc_version: "2.1.0".to_owned(),
+            konanc_fingerprint: "abc".to_owned(),
+            target: Target::LinuxX64,
+            profile: Profile::Debug,
+            source_dirs: vec![dir.path().to_path_buf()],
+            source_glob: "**/*.kt".to_owned(),
+            ignore: IgnoreRules::default(),
+            os: "linux".to_owned(),
+            arch: "x86_64".to_owned(),
+            dependency_hashes: Vec::new(),
+            codegen_hashes: Vec::new(),
+            features: Vec::new(),
+            deny_warnings: false,
+        };
+
+        let spring = CacheKey::compute(&make_inputs(manifest_with("spring"))).unwrap();
+        let quarkus = CacheKey::compute(&make_inputs(manifest_with("quarkus"))).unwrap();
+        assert_ne!(
+            spring, quarkus,
+            "cache key MUST change when a plugin option changes"
+        );
+    }
+
     /// Full pipeline: update_lockfile_if_needed writes plugins, then the written
     /// lockfile passes staleness check for the original manifest.
     #[test]
@@ -6265,6 +9178,7 @@ url = "https://example.com/plugin.jar"
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         // Simulate plugin resolution producing a PluginLock.
@@ -6280,9 +9194,11 @@ url = "https://example.com/plugin.jar"
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             None,
             None,
+            None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &plugin_locks,
             &[],
@@ -6290,6 +9206,7 @@ url = "https://example.com/plugin.jar"
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
 
@@ -6337,6 +9254,7 @@ url = "https://example.com/plugin.jar"
                 targets,
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "src-hash".to_owned(),
         });
@@ -6379,12 +9297,15 @@ url = "https://example.com/plugin.jar"
             konanc_fingerprint: "abc".to_owned(),
             target: Target::LinuxX64,
             profile: Profile::Debug,
-            source_dir: dir.path().to_path_buf(),
+            source_dirs: vec![dir.path().to_path_buf()],
             source_glob: "**/*.kt".to_owned(),
+            ignore: IgnoreRules::default(),
             os: "linux".to_owned(),
             arch: "x86_64".to_owned(),
             dependency_hashes: Vec::new(),
             codegen_hashes: Vec::new(),
+            features: Vec::new(),
+            deny_warnings: false,
         };
         let key = CacheKey::compute(&inputs).unwrap();
         assert_eq!(
@@ -6487,6 +9408,7 @@ url = "https://example.com/plugin.jar"
                 targets,
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "src-hash".to_owned(),
         });
@@ -6496,6 +9418,7 @@ url = "https://example.com/plugin.jar"
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         // Now add plugins via update_lockfile_if_needed.
@@ -6510,9 +9433,11 @@ url = "https://example.com/plugin.jar"
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
+            None,
             None,
             None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &plugin_locks,
             &[],
@@ -6520,6 +9445,7 @@ url = "https://example.com/plugin.jar"
             &lockfile_path,
             false,
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         )
         .unwrap();
 
@@ -6580,6 +9506,7 @@ kotlin-serialization = { maven = "org.jetbrains.kotlin:kotlin-serialization-comp
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         // Plugin resolution produced a DIFFERENT hash.
@@ -6596,9 +9523,11 @@ kotlin-serialization = { maven = "org.jetbrains.kotlin:kotlin-serialization-comp
         // In locked mode, this should error.
         let result = update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             None,
             None,
+            None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &new_plugin_locks,
             &[],
@@ -6606,6 +9535,7 @@ kotlin-serialization = { maven = "org.jetbrains.kotlin:kotlin-serialization-comp
             &lockfile_path,
             false,
             crate::common::test_resolver(false, true), // locked = true
+            &StderrProgress,
         );
         assert!(
             result.is_err(),
@@ -6711,6 +9641,46 @@ compose = { maven = "org.jetbrains.compose.compiler:compiler", version = "1.5.0"
         assert!(!needs_two_step_compilation(ProduceKind::Library, &[]));
     }
 
+    #[test]
+    fn two_step_needed_for_c_library_with_plugins() {
+        let plugins = vec![PathBuf::from("/cache/serialization.jar")];
+        assert!(needs_two_step_compilation(ProduceKind::StaticLib, &plugins));
+        assert!(needs_two_step_compilation(
+            ProduceKind::DynamicLib,
+            &plugins
+        ));
+        assert!(!needs_two_step_compilation(ProduceKind::DynamicLib, &[]));
+    }
+
+    #[test]
+    fn package_kinds_map_to_produce_kinds() {
+        assert_eq!(produce_kind(PackageKind::Bin), ProduceKind::Program);
+        assert_eq!(produce_kind(PackageKind::Lib), ProduceKind::Library);
+        assert_eq!(produce_kind(PackageKind::StaticLib), ProduceKind::StaticLib);
+        assert_eq!(
+            produce_kind(PackageKind::DynamicLib),
+            ProduceKind::DynamicLib
+        );
+    }
+
+    #[test]
+    fn c_header_sits_next_to_c_libraries_only() {
+        let dir = Path::new("/p/.konvoy/build/linux_x64/debug");
+        assert_eq!(
+            c_header_path(&dir.join("libffi.so"), ProduceKind::DynamicLib),
+            Some(dir.join("libffi_api.h"))
+        );
+        assert_eq!(
+            c_header_path(&dir.join("libffi.a"), ProduceKind::StaticLib),
+            Some(dir.join("libffi_api.h"))
+        );
+        assert_eq!(
+            c_header_path(&dir.join("ffi.klib"), ProduceKind::Library),
+            None
+        );
+        assert_eq!(c_header_path(&dir.join("ffi"), ProduceKind::Program), None);
+    }
+
     // -----------------------------------------------------------------------
     // two-step compilation args
     // -----------------------------------------------------------------------
@@ -6825,12 +9795,42 @@ compose = { maven = "org.jetbrains.compose.compiler:compiler", version = "1.5.0"
                 targets: std::collections::BTreeMap::new(),
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "hash".to_owned(),
         });
         assert!(first_unresolved_maven_dep(&manifest, &lockfile).is_none());
     }
 
+    #[test]
+    fn first_unresolved_maven_dep_range_matches_locked_version() {
+        let manifest = |version: &str| {
+            konvoy_config::manifest::Manifest::from_str(
+                &format!("[package]\nname = \"myapp\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n\n[dependencies]\nkotlinx-datetime = {{ maven = \"org.jetbrains.kotlinx:kotlinx-datetime\", version = \"{version}\" }}\n"),
+                "konvoy.toml",
+            )
+            .unwrap()
+        };
+        let mut lockfile = Lockfile::with_toolchain("2.1.0");
+        lockfile.dependencies.push(DependencyLock {
+            name: "kotlinx-datetime".to_owned(),
+            source: DepSource::Maven {
+                version: "0.6.2".to_owned(),
+                maven: "org.jetbrains.kotlinx:kotlinx-datetime".to_owned(),
+                targets: std::collections::BTreeMap::new(),
+                required_by: Vec::new(),
+                classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
+            },
+            source_hash: "hash".to_owned(),
+        });
+        assert!(first_unresolved_maven_dep(&manifest("^0.6"), &lockfile).is_none());
+        assert_eq!(
+            first_unresolved_maven_dep(&manifest("^0.7"), &lockfile).as_deref(),
+            Some("kotlinx-datetime")
+        );
+    }
+
     #[test]
     fn first_unresolved_maven_dep_maven_missing_from_lockfile() {
         let manifest = konvoy_config::manifest::Manifest::from_str(
@@ -6884,6 +9884,7 @@ compose = { maven = "org.jetbrains.compose.compiler:compiler", version = "1.5.0"
                 targets: std::collections::BTreeMap::new(),
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "hash".to_owned(),
         });
@@ -7010,14 +10011,17 @@ compose = { maven = "org.jetbrains.compose.compiler:compiler", version = "1.5.0"
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         let result = update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
+            None,
             None,
             None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &[],
@@ -7025,10 +10029,82 @@ compose = { maven = "org.jetbrains.compose.compiler:compiler", version = "1.5.0"
             &lockfile_path,
             false,
             crate::common::test_resolver(false, true), // locked
+            &StderrProgress,
         );
         assert!(result.is_err());
     }
 
+    #[test]
+    fn check_lockfile_staleness_jre_major_drift_errors() {
+        let manifest = konvoy_config::manifest::Manifest::from_str(
+            "[package]\nname = \"myapp\"\n\n[toolchain]\nkotlin = \"2.1.0\"\njre = 17\n",
+            "konvoy.toml",
+        )
+        .unwrap();
+        // Pinned before `jre = 17` was added: the lock still means JDK 21.
+        let lockfile = Lockfile::with_toolchain("2.1.0");
+
+        let result = check_lockfile_staleness(&manifest, &lockfile);
+        assert!(
+            matches!(result, Err(EngineError::LockfileUpdateRequired)),
+            "switching JDKs under --locked must be drift, got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn update_lockfile_records_jre_major_and_drops_old_jre_pin() {
+        let tmp = tempfile::tempdir().unwrap();
+        let lockfile_path = tmp.path().join("konvoy.lock");
+        let lockfile = Lockfile::with_managed_toolchain("2.1.0", Some("konanc-sha"), Some("jre21"));
+        lockfile.write_to(&lockfile_path).unwrap();
+
+        let konanc = KonancInfo {
+            path: PathBuf::from("/usr/bin/konanc"),
+            version: "2.1.0".to_owned(),
+            fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
+        };
+
+        let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
+        update_lockfile_if_needed(
+            &lockfile,
+            Some(&konanc),
+            None,
+            None,
+            Some("jre17"),
+            17,
+            &empty_graph,
+            &[],
+            &[],
+            tmp.path(),
+            &lockfile_path,
+            false,
+            crate::common::test_resolver(false, false),
+            &StderrProgress,
+        )
+        .unwrap();
+
+        let written = Lockfile::from_path(&lockfile_path).unwrap();
+        let tc = written.toolchain.unwrap();
+        assert_eq!(tc.jre_major, Some(17));
+        assert_eq!(tc.jre_tarball_sha256.as_deref(), Some("jre17"));
+    }
+
+    #[test]
+    fn verify_toolchain_pins_ignores_jre_pin_for_other_major() {
+        let lockfile = Lockfile::with_managed_toolchain("2.1.0", None, Some("jre21"));
+        verify_toolchain_tarball_pins(
+            &lockfile,
+            "2.1.0",
+            None,
+            Some("jre17"),
+            17,
+            false,
+            &StderrProgress,
+        )
+        .unwrap();
+    }
+
     #[test]
     fn update_lockfile_detects_tampered_konanc_hash() {
         let tmp = tempfile::tempdir().unwrap();
@@ -7040,14 +10116,17 @@ compose = { maven = "org.jetbrains.compose.compiler:compiler", version = "1.5.0"
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         let result = update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             Some("different-hash"),
             None,
+            None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &[],
@@ -7055,6 +10134,7 @@ compose = { maven = "org.jetbrains.compose.compiler:compiler", version = "1.5.0"
             &lockfile_path,
             false, // not force
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         );
         assert!(result.is_err(), "should detect tampered konanc hash");
     }
@@ -7070,14 +10150,17 @@ compose = { maven = "org.jetbrains.compose.compiler:compiler", version = "1.5.0"
             path: PathBuf::from("/usr/bin/konanc"),
             version: "2.1.0".to_owned(),
             fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
         };
 
         let empty_graph = crate::resolve::ResolvedGraph { order: Vec::new() };
         let result = update_lockfile_if_needed(
             &lockfile,
-            &konanc,
+            Some(&konanc),
             Some("different-hash"),
             None,
+            None,
+            DEFAULT_JRE_MAJOR,
             &empty_graph,
             &[],
             &[],
@@ -7085,6 +10168,7 @@ compose = { maven = "org.jetbrains.compose.compiler:compiler", version = "1.5.0"
             &lockfile_path,
             true, // force
             crate::common::test_resolver(false, false),
+            &StderrProgress,
         );
         assert!(result.is_ok(), "force should bypass hash mismatch");
     }
@@ -7137,6 +10221,32 @@ compose = { maven = "org.jetbrains.compose.compiler:compiler", version = "1.5.0"
         );
     }
 
+    #[test]
+    fn library_hashes_of_keeps_order_and_reports_first_error() {
+        let tmp = tempfile::tempdir().unwrap();
+        let a = tmp.path().join("a.klib");
+        fs::write(&a, b"a").unwrap();
+        let libs = vec![
+            LibraryInput::unhashed(a.clone()),
+            LibraryInput::with_hash(PathBuf::from("/b.klib"), "h".to_owned()),
+        ];
+        assert_eq!(
+            library_hashes_of(&libs).unwrap(),
+            vec![konvoy_util::hash::sha256_file(&a).unwrap(), "h".to_owned()]
+        );
+
+        let mut libs: Vec<LibraryInput> =
+            (0..32).map(|_| LibraryInput::unhashed(a.clone())).collect();
+        libs.push(LibraryInput::unhashed(
+            tmp.path().join("first-missing.klib"),
+        ));
+        libs.push(LibraryInput::unhashed(
+            tmp.path().join("second-missing.klib"),
+        ));
+        let err = library_hashes_of(&libs).unwrap_err().to_string();
+        assert!(err.contains("first-missing.klib"), "got: {err}");
+    }
+
     #[test]
     fn library_paths_of_empty_input_returns_empty() {
         // Empty input must not allocate a spurious element.
@@ -5,6 +5,7 @@ use std::path::Path;
 
 use konvoy_config::Profile;
 use konvoy_targets::Target;
+use konvoy_util::ignore::IgnoreRules;
 
 use crate::error::EngineError;
 
@@ -23,10 +24,12 @@ pub struct CacheInputs {
     pub target: Target,
     /// Build profile.
     pub profile: Profile,
-    /// Root directory containing source files.
-    pub source_dir: std::path::PathBuf,
-    /// Glob pattern for source files (e.g. "**/*.kt").
+    /// Directories containing source files, in configured order.
+    pub source_dirs: Vec<std::path::PathBuf>,
+    /// Glob pattern for source files within each directory (e.g. "**/*.kt").
     pub source_glob: String,
+    /// `.konvoyignore` rules; excluded files do not contribute to the key.
+    pub ignore: IgnoreRules,
     /// Operating system identifier.
     pub os: String,
     /// Architecture identifier.
@@ -37,6 +40,13 @@ pub struct CacheInputs {
     /// config + input files into the key so a spec/config change rebuilds. Empty for
     /// projects with no `[codegen]` config (leaving the key unchanged for them).
     pub codegen_hashes: Vec<String>,
+    /// Enabled `[features]`, sorted. Empty for a build with no features enabled
+    /// (leaving the key unchanged for projects that do not use them).
+    pub features: Vec<String>,
+    /// Whether warnings fail the compile (`[build] deny_warnings` or
+    /// `--deny-warnings`). `false` leaves the key unchanged; when set, an
+    /// artifact that compiled with warnings is never reused.
+    pub deny_warnings: bool,
 }
 
 /// A content-addressed cache key wrapping a SHA-256 hex string.
@@ -52,7 +62,8 @@ impl CacheKey {
     /// # Errors
     /// Returns an error if source files cannot be read.
     pub fn compute(inputs: &CacheInputs) -> Result<Self, EngineError> {
-        let source_hash = konvoy_util::hash::sha256_dir(&inputs.source_dir, &inputs.source_glob)?;
+        let source_hash =
+            crate::sources::hash_sources(&inputs.source_dirs, &inputs.source_glob, &inputs.ignore)?;
 
         let target_str = inputs.target.to_konanc_arg();
         let profile_str = inputs.profile.as_str();
@@ -78,6 +89,18 @@ impl CacheKey {
         for h in &inputs.codegen_hashes {
             parts.push(h);
         }
+        // Tag feature names so they cannot collide with the hashes above.
+        let feature_tags: Vec<String> = inputs
+            .features
+            .iter()
+            .map(|f| format!("feature:{f}"))
+            .collect();
+        for tag in &feature_tags {
+            parts.push(tag);
+        }
+        if inputs.deny_warnings {
+            parts.push("deny-warnings");
+        }
 
         let composite = konvoy_util::hash::sha256_multi(&parts);
 
@@ -117,12 +140,15 @@ mod tests {
             konanc_fingerprint: "abc123".to_owned(),
             target: Target::LinuxX64,
             profile: Profile::Debug,
-            source_dir: dir.to_path_buf(),
+            source_dirs: vec![dir.to_path_buf()],
             source_glob: "**/*.kt".to_owned(),
+            ignore: IgnoreRules::default(),
             os: "linux".to_owned(),
             arch: "x86_64".to_owned(),
             dependency_hashes: Vec::new(),
             codegen_hashes: Vec::new(),
+            features: Vec::new(),
+            deny_warnings: false,
         }
     }
 
@@ -191,6 +217,39 @@ mod tests {
         assert_ne!(key1, key2);
     }
 
+    #[test]
+    fn features_change_key() {
+        let tmp = tempfile::tempdir().unwrap();
+        setup_sources(tmp.path());
+
+        let key_none = CacheKey::compute(&make_inputs(tmp.path())).unwrap();
+
+        let mut inputs_json = make_inputs(tmp.path());
+        inputs_json.features = vec!["json".to_owned()];
+        let key_json = CacheKey::compute(&inputs_json).unwrap();
+
+        let mut inputs_both = make_inputs(tmp.path());
+        inputs_both.features = vec!["json".to_owned(), "metrics".to_owned()];
+        let key_both = CacheKey::compute(&inputs_both).unwrap();
+
+        assert_ne!(key_none, key_json);
+        assert_ne!(key_json, key_both);
+    }
+
+    #[test]
+    fn deny_warnings_changes_key() {
+        let tmp = tempfile::tempdir().unwrap();
+        setup_sources(tmp.path());
+
+        let key_allow = CacheKey::compute(&make_inputs(tmp.path())).unwrap();
+
+        let mut denied = make_inputs(tmp.path());
+        denied.deny_warnings = true;
+        let key_deny = CacheKey::compute(&denied).unwrap();
+
+        assert_ne!(key_allow, key_deny);
+    }
+
     #[test]
     fn codegen_hashes_change_key() {
         // Codegen inputs must participate in the key: adding codegen hashes, and
@@ -298,11 +357,11 @@ mod tests {
         fs::write(src2.join("b.kt"), "fun b() {}").unwrap();
 
         let mut inputs1 = make_inputs(dir1.path());
-        inputs1.source_dir = dir1.path().to_path_buf();
+        inputs1.source_dirs = vec![dir1.path().to_path_buf()];
         let key1 = CacheKey::compute(&inputs1).unwrap();
 
         let mut inputs2 = make_inputs(dir2.path());
-        inputs2.source_dir = dir2.path().to_path_buf();
+        inputs2.source_dirs = vec![dir2.path().to_path_buf()];
         let key2 = CacheKey::compute(&inputs2).unwrap();
 
         assert_eq!(key1, key2);
@@ -404,7 +463,7 @@ mod tests {
         fs::create_dir_all(&empty_src).unwrap();
 
         let mut inputs = make_inputs(tmp.path());
-        inputs.source_dir = empty_src;
+        inputs.source_dirs = vec![empty_src];
         let key = CacheKey::compute(&inputs).unwrap();
         assert_eq!(key.as_hex().len(), 64);
     }
@@ -465,12 +524,15 @@ mod tests {
                             konanc_fingerprint,
                             target,
                             profile,
-                            source_dir: dir.to_path_buf(),
+                            source_dirs: vec![dir.to_path_buf()],
                             source_glob: "**/*.kt".to_owned(),
+                            ignore: IgnoreRules::default(),
                             os,
                             arch,
                             dependency_hashes: Vec::new(),
                             codegen_hashes: Vec::new(),
+                            features: Vec::new(),
+                            deny_warnings: false,
                         }
                     },
                 )
@@ -488,7 +550,7 @@ mod tests {
                 fs::write(src.join("main.kt"), "fun main() {}").unwrap();
 
                 let inputs1 = CacheInputs {
-                    source_dir: tmp.path().to_path_buf(),
+                    source_dirs: vec![tmp.path().to_path_buf()],
                     ..seed
                 };
                 let inputs2 = CacheInputs {
@@ -498,12 +560,15 @@ mod tests {
                     konanc_fingerprint: inputs1.konanc_fingerprint.clone(),
                     target: inputs1.target,
                     profile: inputs1.profile,
-                    source_dir: tmp.path().to_path_buf(),
+                    source_dirs: vec![tmp.path().to_path_buf()],
                     source_glob: "**/*.kt".to_owned(),
+                    ignore: IgnoreRules::default(),
                     os: inputs1.os.clone(),
                     arch: inputs1.arch.clone(),
                     dependency_hashes: Vec::new(),
                     codegen_hashes: Vec::new(),
+                    features: Vec::new(),
+                    deny_warnings: false,
                 };
 
                 let key1 = CacheKey::compute(&inputs1).unwrap();
@@ -521,7 +586,7 @@ mod tests {
                 fs::write(src.join("main.kt"), "fun main() {}").unwrap();
 
                 let inputs = CacheInputs {
-                    source_dir: tmp.path().to_path_buf(),
+                    source_dirs: vec![tmp.path().to_path_buf()],
                     ..seed
                 };
                 let key = CacheKey::compute(&inputs).unwrap();
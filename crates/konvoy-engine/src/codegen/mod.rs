@@ -175,6 +175,23 @@ fn unique_codegen_tools(generators: &[Box<dyn CodeGenerator>]) -> Vec<ManagedToo
     unique.into_values().collect()
 }
 
+/// The managed tools of `generators` that are not downloaded yet, as
+/// `id version` labels.
+///
+/// # Errors
+/// Returns an error if the Konvoy home directory cannot be determined.
+pub(crate) fn missing_codegen_tools(
+    generators: &[Box<dyn CodeGenerator>],
+) -> Result<Vec<String>, EngineError> {
+    let mut missing = Vec::new();
+    for tool in unique_codegen_tools(generators) {
+        if !tool.is_installed()? {
+            missing.push(format!("{} {}", tool.id(), tool.version()));
+        }
+    }
+    Ok(missing)
+}
+
 /// Download + SHA-verify the codegen tools required by `generators`, returning the
 /// resolved lockfile pins — one per **distinct** `(name, version)`, sorted.
 ///
@@ -335,7 +352,11 @@ pub fn generate(
 
     // Fabrikt and other JVM generators run on the toolchain's bundled JRE; resolve
     // it, then download/verify the codegen tools under the command's policy.
-    let jre_home = resolver.resolve_jre(&manifest.toolchain.kotlin, &lockfile)?;
+    let jre_home = resolver.resolve_jre(
+        &manifest.toolchain.kotlin,
+        crate::common::jre_major(&manifest.toolchain),
+        &lockfile,
+    )?;
     ensure_codegen_tools(&generators, &lockfile.codegen_tools, resolver)?;
 
     let mut outputs = Vec::with_capacity(generators.len());
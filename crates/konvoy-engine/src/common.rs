@@ -54,19 +54,25 @@ impl<'a> ArtifactResolver<'a> {
     pub(crate) fn resolve_toolchain(
         self,
         version: &str,
+        jre_major: u32,
         lockfile: &Lockfile,
     ) -> Result<konvoy_konanc::detect::ResolvedKonanc, EngineError> {
-        let is_present = konvoy_konanc::toolchain::is_installed(version)?;
-        // `has_pin` stats the install location; passed lazily so the probe only
-        // runs under --locked (the offline gate uses `is_present`, not the pin).
-        self.resolve_artifact(
-            || has_required_toolchain_artifact_pins(lockfile, version),
-            is_present,
-            || EngineError::ToolchainOffline {
-                version: version.to_owned(),
-            },
-        )?;
-        Ok(konvoy_konanc::detect::resolve_konanc(version, self.net)?)
+        // A `KONVOY_KONANC` override never downloads, so there is nothing to gate.
+        if konvoy_konanc::detect::konanc_override().is_none() {
+            let is_present = konvoy_konanc::toolchain::is_installed(version, jre_major)?;
+            // `has_pin` stats the install location; passed lazily so the probe only
+            // runs under --locked (the offline gate uses `is_present`, not the pin).
+            self.resolve_artifact(
+                || has_required_toolchain_artifact_pins(lockfile, version, jre_major),
+                is_present,
+                || EngineError::ToolchainOffline {
+                    version: version.to_owned(),
+                },
+            )?;
+        }
+        Ok(konvoy_konanc::detect::resolve_konanc(
+            version, jre_major, self.net,
+        )?)
     }
 
     /// Resolve the detekt CLI JAR.
@@ -100,8 +106,36 @@ impl<'a> ArtifactResolver<'a> {
         Ok((!was_pinned).then_some(actual_sha256))
     }
 
+    /// Resolve the ktlint JAR used by `konvoy fmt`.
+    ///
+    /// Mirrors [`resolve_detekt_jar`](Self::resolve_detekt_jar): returns the
+    /// hash to persist when the JAR was not already pinned, or `None`.
+    pub(crate) fn resolve_ktlint_jar(
+        self,
+        version: &str,
+        lockfile: &Lockfile,
+    ) -> Result<Option<String>, EngineError> {
+        let expected_sha256 = lockfile.toolchain.as_ref().and_then(|tc| {
+            (tc.ktlint_version.as_deref() == Some(version))
+                .then_some(tc.ktlint_jar_sha256.as_deref())
+                .flatten()
+                .filter(|s| !s.is_empty())
+        });
+        let was_pinned = expected_sha256.is_some();
+        let is_present = crate::fmt::is_installed(version)?;
+        self.resolve_artifact(
+            || Ok(was_pinned),
+            is_present,
+            || EngineError::KtlintJarOffline {
+                version: version.to_owned(),
+            },
+        )?;
+        let (_, actual_sha256) = crate::fmt::ensure_ktlint(version, expected_sha256, self)?;
+        Ok((!was_pinned).then_some(actual_sha256))
+    }
+
     /// Resolve the Kotlin/Native toolchain's bundled JRE, used to run managed JVM
-    /// tools (detekt and the JVM codegen generators).
+    /// tools (detekt, ktlint, and the JVM codegen generators).
     ///
     /// Only the JRE is needed here — not the `konanc` compiler — so this is lighter
     /// than full toolchain resolution; it installs the toolchain solely to obtain
@@ -111,21 +145,22 @@ impl<'a> ArtifactResolver<'a> {
     pub(crate) fn resolve_jre(
         self,
         kotlin_version: &str,
+        jre_major: u32,
         lockfile: &Lockfile,
     ) -> Result<std::path::PathBuf, EngineError> {
-        if !konvoy_konanc::toolchain::is_installed(kotlin_version)? {
+        if !konvoy_konanc::toolchain::is_installed(kotlin_version, jre_major)? {
             self.resolve_artifact(
-                || has_required_toolchain_artifact_pins(lockfile, kotlin_version),
+                || has_required_toolchain_artifact_pins(lockfile, kotlin_version, jre_major),
                 false,
                 || EngineError::ToolchainJreOffline {
                     version: kotlin_version.to_owned(),
                 },
             )?;
             eprintln!("    Installing Kotlin/Native {kotlin_version} (for JRE)...");
-            konvoy_konanc::toolchain::install(kotlin_version, self.net)?;
+            konvoy_konanc::toolchain::install(kotlin_version, jre_major, self.net)?;
         }
 
-        let jre_home = konvoy_konanc::toolchain::jre_home_path(kotlin_version)?;
+        let jre_home = konvoy_konanc::toolchain::jre_home_path(kotlin_version, jre_major)?;
         if !jre_home.join("bin").join("java").exists() {
             return Err(EngineError::ToolchainNoJre);
         }
@@ -218,7 +253,8 @@ impl<'a> ArtifactResolver<'a> {
         name: &str,
         url: &str,
         dest: &std::path::Path,
-        expected_sha256: &str,
+        expected: &str,
+        hash_algorithm: konvoy_util::hash::HashAlgorithm,
         bar: Option<&konvoy_util::progress::DownloadBar>,
     ) -> Result<crate::build::LibraryInput, EngineError> {
         self.resolve_artifact(
@@ -229,7 +265,7 @@ impl<'a> ArtifactResolver<'a> {
             },
         )?;
         let result = self
-            .fetch_artifact(url, dest, Some(expected_sha256), name, bar)
+            .fetch_artifact_as(url, dest, Some(expected), hash_algorithm, name, bar)
             .map_err(|e| EngineError::LibraryDownloadFailed {
                 name: name.to_owned(),
                 url: url.to_owned(),
@@ -256,10 +292,36 @@ impl<'a> ArtifactResolver<'a> {
         eprintln!("  Maven dependencies not resolved - running update automatically...");
         // Reuse the already-resolved graph so we don't re-walk + re-hash every
         // path-dep's source tree a second time on this cold build.
-        crate::update::update_with_graph(project_root, manifest, dep_graph, self)?;
+        crate::update::update_with_graph(project_root, manifest, dep_graph, false, self)?;
         Ok(Lockfile::from_path(lockfile_path)?)
     }
 
+    /// Drop lockfile entries the manifests no longer declare (`stale` describes
+    /// each one), warning about every entry and writing the pruned lockfile.
+    ///
+    /// Returns `current` unchanged when nothing is stale; under `--locked` a
+    /// stale entry is lockfile drift and fails with `LockfileUpdateRequired`.
+    pub(crate) fn prune_stale_lock_entries(
+        self,
+        current: Lockfile,
+        pruned: Lockfile,
+        stale: &[String],
+        lockfile_path: &std::path::Path,
+    ) -> Result<Lockfile, EngineError> {
+        if stale.is_empty() {
+            return Ok(current);
+        }
+        self.lockfiles.require_update_allowed()?;
+        for entry in stale {
+            eprintln!(
+                "warning: removing {entry} from konvoy.lock — konvoy.toml no longer declares it"
+            );
+        }
+        self.lockfiles
+            .write_updated_lockfile(&current, &pruned, lockfile_path)?;
+        Ok(pruned)
+    }
+
     /// Require the manifest's managed artifacts to be resolvable under the
     /// command's policy (root-only — used by `lint`, which does not build the
     /// dependency graph).
@@ -321,6 +383,66 @@ impl<'a> ArtifactResolver<'a> {
             .write_updated_lockfile(current, updated, lockfile_path)
     }
 
+    /// Resolve a git dependency to a concrete commit and a local checkout.
+    ///
+    /// A commit pinned in `konvoy.lock` (`pinned`) is reused as-is, fetching it
+    /// only when its checkout is missing. Without a pin, `rev` is fetched and
+    /// resolved to a commit, which needs a lockfile update and the network.
+    pub(crate) fn resolve_git_dependency(
+        self,
+        name: &str,
+        url: &str,
+        rev: &str,
+        pinned: Option<&str>,
+    ) -> Result<(String, std::path::PathBuf), EngineError> {
+        let is_present = match pinned {
+            Some(commit) => crate::git::checkout_dir(url, commit)?.exists(),
+            None => false,
+        };
+        self.resolve_artifact(
+            || Ok(pinned.is_some()),
+            is_present,
+            || EngineError::GitDependencyOffline {
+                name: name.to_owned(),
+                url: url.to_owned(),
+            },
+        )?;
+        match pinned {
+            Some(commit) if is_present => {
+                Ok((commit.to_owned(), crate::git::checkout_dir(url, commit)?))
+            }
+            Some(commit) => crate::git::fetch_checkout(name, url, commit, Some(commit)),
+            None => crate::git::fetch_checkout(name, url, rev, None),
+        }
+    }
+
+    /// What [`Self::resolve_git_dependency`] would find without fetching, for
+    /// `build --dry-run`: the pinned commit and its checkout when already
+    /// present, or `None` when the build would have to fetch the dependency.
+    pub(crate) fn existing_git_checkout(
+        self,
+        url: &str,
+        pinned: Option<&str>,
+    ) -> Result<Option<(String, std::path::PathBuf)>, EngineError> {
+        self.lockfiles
+            .require_artifact_pin(|| Ok(pinned.is_some()))?;
+        let Some(commit) = pinned else {
+            return Ok(None);
+        };
+        let checkout = crate::git::checkout_dir(url, commit)?;
+        Ok(checkout.exists().then(|| (commit.to_owned(), checkout)))
+    }
+
+    /// Return the pin to record for a lockfile field: an existing pin is kept
+    /// as-is, and a missing one is filled in with `current` unless locked policy
+    /// forbids lockfile updates (then it stays missing rather than failing).
+    pub(crate) fn backfill_pin(self, existing: Option<&str>, current: &str) -> Option<String> {
+        match existing {
+            Some(pin) => Some(pin.to_owned()),
+            None => self.lockfiles.backfill(current),
+        }
+    }
+
     /// Ensure a managed tool artifact exists and is verified.
     pub(crate) fn ensure_managed_tool(
         self,
@@ -342,6 +464,36 @@ impl<'a> ArtifactResolver<'a> {
         konvoy_util::progress::fetch(self.net, url, dest, expected_sha256, label, bar)
     }
 
+    /// [`fetch_artifact`](Self::fetch_artifact), with `expected` a digest in
+    /// `algorithm`.
+    pub(crate) fn fetch_artifact_as(
+        self,
+        url: &str,
+        dest: &std::path::Path,
+        expected: Option<&str>,
+        algorithm: konvoy_util::hash::HashAlgorithm,
+        label: &str,
+        bar: Option<&konvoy_util::progress::DownloadBar>,
+    ) -> Result<konvoy_util::artifact::ArtifactResult, konvoy_util::error::UtilError> {
+        konvoy_util::progress::fetch_as(self.net, url, dest, expected, algorithm, label, bar)
+    }
+
+    /// Fetch the published versions of a Maven artifact to resolve a
+    /// `[dependencies]` version range. Under `--locked` ranges resolve from the
+    /// lockfile only, so this fails with `LockfileUpdateRequired` instead.
+    pub(crate) fn fetch_published_versions(
+        self,
+        group_id: &str,
+        artifact_id: &str,
+    ) -> Result<Vec<String>, EngineError> {
+        self.lockfiles.require_update_allowed()?;
+        Ok(konvoy_util::maven_metadata::fetch_maven_metadata(
+            self.net,
+            group_id,
+            artifact_id,
+        )?)
+    }
+
     /// Fetch artifact metadata for Maven dependency resolution.
     pub(crate) fn fetch_artifact_metadata(
         self,
@@ -360,9 +512,30 @@ impl<'a> ArtifactResolver<'a> {
     }
 }
 
+/// The JDK major of the bundled JRE a manifest asks for.
+pub(crate) fn jre_major(toolchain: &konvoy_config::manifest::Toolchain) -> u32 {
+    toolchain
+        .jre
+        .unwrap_or(konvoy_konanc::toolchain::DEFAULT_JRE_MAJOR)
+}
+
+/// The JDK major a lockfile's JRE pin was recorded for. Lockfiles written
+/// before `[toolchain] jre` existed carry no major and always used the default.
+pub(crate) fn locked_jre_major(tc: &konvoy_config::lockfile::ToolchainLock) -> u32 {
+    tc.jre_major
+        .unwrap_or(konvoy_konanc::toolchain::DEFAULT_JRE_MAJOR)
+}
+
+/// The `jre_major` a lockfile records for `jre_major`: omitted for the
+/// default, so projects that never set `[toolchain] jre` keep an unchanged lock.
+pub(crate) fn recorded_jre_major(jre_major: u32) -> Option<u32> {
+    (jre_major != konvoy_konanc::toolchain::DEFAULT_JRE_MAJOR).then_some(jre_major)
+}
+
 fn has_required_toolchain_artifact_pins(
     lockfile: &Lockfile,
     kotlin_version: &str,
+    jre_major: u32,
 ) -> Result<bool, EngineError> {
     let Some(tc) = lockfile
         .toolchain
@@ -373,7 +546,7 @@ fn has_required_toolchain_artifact_pins(
     };
 
     let konanc_missing = !konvoy_konanc::toolchain::managed_konanc_path(kotlin_version)?.exists();
-    let jre_missing = !konvoy_konanc::toolchain::jre_dir(kotlin_version)?.exists();
+    let jre_missing = !konvoy_konanc::toolchain::jre_dir(kotlin_version, jre_major)?.exists();
 
     let konanc_pinned = !konanc_missing
         || tc
@@ -381,10 +554,11 @@ fn has_required_toolchain_artifact_pins(
             .as_deref()
             .is_some_and(|s| !s.is_empty());
     let jre_pinned = !jre_missing
-        || tc
-            .jre_tarball_sha256
-            .as_deref()
-            .is_some_and(|s| !s.is_empty());
+        || (locked_jre_major(tc) == jre_major
+            && tc
+                .jre_tarball_sha256
+                .as_deref()
+                .is_some_and(|s| !s.is_empty()));
 
     Ok(konanc_pinned && jre_pinned)
 }
@@ -452,6 +626,11 @@ impl LockfileManager {
         }
     }
 
+    /// Return the value to record for a missing pin, or `None` when locked.
+    fn backfill(self, current: &str) -> Option<String> {
+        (!self.locked).then(|| current.to_owned())
+    }
+
     /// Return the candidate lockfile content that should feed cache keys.
     fn effective_lockfile(
         self,
@@ -515,12 +694,26 @@ pub(crate) fn truncate_hash(hash: &str, len: usize) -> &str {
 
 /// Current UTC timestamp formatted as `"{seconds}s-since-epoch"`.
 ///
-/// Stored in build metadata; the suffix is purely for human reading.
+/// Stored in build metadata; the suffix is purely for human reading. When
+/// `SOURCE_DATE_EPOCH` is set to a valid number of seconds it is used instead
+/// of the wall clock, so independent builds write byte-identical metadata.
+/// The timestamp is never part of the cache key.
 pub(crate) fn now_epoch_secs() -> String {
-    let duration = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default();
-    format!("{}s-since-epoch", duration.as_secs())
+    epoch_secs_from(std::env::var("SOURCE_DATE_EPOCH").ok().as_deref())
+}
+
+/// `now_epoch_secs` with the `SOURCE_DATE_EPOCH` value passed in, so tests
+/// don't have to mutate the process environment.
+fn epoch_secs_from(source_date_epoch: Option<&str>) -> String {
+    let secs = source_date_epoch
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+        });
+    format!("{secs}s-since-epoch")
 }
 
 #[cfg(test)]
@@ -528,6 +721,7 @@ pub(crate) fn now_epoch_secs() -> String {
 mod tests {
     use super::*;
     use konvoy_config::lockfile::{PluginLock, ToolchainLock};
+    use konvoy_konanc::toolchain::DEFAULT_JRE_MAJOR;
     use konvoy_util::maven::MavenCoordinate;
     use std::cell::Cell;
     use std::path::PathBuf;
@@ -572,9 +766,14 @@ mod tests {
             toolchain: Some(ToolchainLock {
                 konanc_version: version.to_owned(),
                 konanc_tarball_sha256: konanc_sha256.map(str::to_owned),
+                konanc_tarball_url: None,
                 jre_tarball_sha256: jre_sha256.map(str::to_owned),
+                jre_major: None,
+                konanc_fingerprint: None,
                 detekt_version: None,
                 detekt_jar_sha256: None,
+                ktlint_version: None,
+                ktlint_jar_sha256: None,
             }),
             ..Default::default()
         }
@@ -670,7 +869,11 @@ mod tests {
         let lockfile = Lockfile::default();
 
         let result = with_resolver(true, true, |resolver| {
-            resolver.resolve_toolchain("0.0.0-resolver-toolchain-drift", &lockfile)
+            resolver.resolve_toolchain(
+                "0.0.0-resolver-toolchain-drift",
+                DEFAULT_JRE_MAJOR,
+                &lockfile,
+            )
         });
 
         assert!(matches!(result, Err(EngineError::LockfileUpdateRequired)));
@@ -682,7 +885,7 @@ mod tests {
         let lockfile = lockfile_with_toolchain(version, Some("konanc-sha"), Some("jre-sha"));
 
         let result = with_resolver(true, false, |resolver| {
-            resolver.resolve_toolchain(version, &lockfile)
+            resolver.resolve_toolchain(version, DEFAULT_JRE_MAJOR, &lockfile)
         });
 
         assert!(matches!(
@@ -709,9 +912,14 @@ mod tests {
             toolchain: Some(ToolchainLock {
                 konanc_version: "2.1.0".to_owned(),
                 konanc_tarball_sha256: None,
+                konanc_tarball_url: None,
                 jre_tarball_sha256: None,
+                jre_major: None,
+                konanc_fingerprint: None,
                 detekt_version: Some(version.to_owned()),
                 detekt_jar_sha256: Some("0".repeat(64)),
+                ktlint_version: None,
+                ktlint_jar_sha256: None,
             }),
             ..Default::default()
         };
@@ -732,7 +940,7 @@ mod tests {
         let lockfile = lockfile_with_toolchain(version, None, None);
 
         let result = with_resolver(false, true, |resolver| {
-            resolver.resolve_jre(version, &lockfile)
+            resolver.resolve_jre(version, DEFAULT_JRE_MAJOR, &lockfile)
         });
 
         assert!(matches!(result, Err(EngineError::LockfileUpdateRequired)));
@@ -744,7 +952,7 @@ mod tests {
         let lockfile = lockfile_with_toolchain(version, Some("konanc-sha"), Some("jre-sha"));
 
         let result = with_resolver(true, false, |resolver| {
-            resolver.resolve_jre(version, &lockfile)
+            resolver.resolve_jre(version, DEFAULT_JRE_MAJOR, &lockfile)
         });
 
         assert!(matches!(
@@ -848,6 +1056,7 @@ mod tests {
                 "http://127.0.0.1:1/missing.klib",
                 &dest,
                 &"0".repeat(64),
+                konvoy_util::hash::HashAlgorithm::Sha256,
                 None,
             )
         });
@@ -873,6 +1082,7 @@ mod tests {
                     "http://127.0.0.1:1/cached.klib",
                     &dest,
                     &expected_hash,
+                    konvoy_util::hash::HashAlgorithm::Sha256,
                     None,
                 )
                 .unwrap()
@@ -927,6 +1137,62 @@ mod tests {
         assert!(!path.exists());
     }
 
+    #[test]
+    fn prune_stale_lock_entries_writes_pruned_lockfile_when_unlocked() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("konvoy.lock");
+        let current = lockfile_with_plugin("stale", "abc".to_owned());
+        current.write_to(&path).unwrap();
+        let pruned = Lockfile::default();
+
+        let result = with_resolver(false, false, |resolver| {
+            resolver.prune_stale_lock_entries(
+                current.clone(),
+                pruned.clone(),
+                &["plugin `stale`".to_owned()],
+                &path,
+            )
+        })
+        .unwrap();
+
+        assert_eq!(result, pruned);
+        assert!(Lockfile::from_path(&path).unwrap().plugins.is_empty());
+    }
+
+    #[test]
+    fn prune_stale_lock_entries_rejects_stale_entries_when_locked() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("konvoy.lock");
+        let current = lockfile_with_plugin("stale", "abc".to_owned());
+
+        let result = with_resolver(false, true, |resolver| {
+            resolver.prune_stale_lock_entries(
+                current,
+                Lockfile::default(),
+                &["plugin `stale`".to_owned()],
+                &path,
+            )
+        });
+
+        assert!(matches!(result, Err(EngineError::LockfileUpdateRequired)));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn prune_stale_lock_entries_without_stale_entries_is_a_noop_when_locked() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("konvoy.lock");
+        let current = Lockfile::with_toolchain("2.1.0");
+
+        let result = with_resolver(false, true, |resolver| {
+            resolver.prune_stale_lock_entries(current.clone(), current.clone(), &[], &path)
+        })
+        .unwrap();
+
+        assert_eq!(result, current);
+        assert!(!path.exists());
+    }
+
     #[test]
     fn persist_resolved_artifacts_writes_unlocked_changes() {
         let tmp = tempfile::tempdir().unwrap();
@@ -991,6 +1257,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn epoch_secs_honors_source_date_epoch() {
+        assert_eq!(
+            epoch_secs_from(Some("1700000000")),
+            "1700000000s-since-epoch"
+        );
+        assert_eq!(epoch_secs_from(Some(" 42\n")), "42s-since-epoch");
+    }
+
+    #[test]
+    fn epoch_secs_ignores_invalid_source_date_epoch() {
+        for value in [None, Some(""), Some("yesterday"), Some("-5")] {
+            let ts = epoch_secs_from(value);
+            let secs: u64 = ts.strip_suffix("s-since-epoch").unwrap().parse().unwrap();
+            assert!(
+                secs > 1_704_067_200,
+                "{value:?} should fall back to the clock"
+            );
+        }
+    }
+
     #[test]
     fn now_epoch_secs_is_reasonable() {
         let ts = now_epoch_secs();
@@ -0,0 +1,167 @@
+//! `--emit-command`: a `compile_commands.json`-style record of the `konanc`
+//! invocations a build ran, so they can be reproduced by hand or attached to a
+//! compiler bug report.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use konvoy_konanc::detect::KonancInfo;
+use konvoy_konanc::invoke::KonancCommand;
+use serde::Serialize;
+
+use crate::error::EngineError;
+
+/// One `konanc` invocation, in the shape of a `compile_commands.json` entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct CompileCommand {
+    /// Working directory the build ran from (the project root).
+    pub directory: String,
+    /// The `konanc` binary followed by its fully resolved arguments.
+    pub arguments: Vec<String>,
+    /// The artifact this invocation produces.
+    pub output: String,
+    /// Environment variables Konvoy sets for the compiler (`JAVA_HOME`).
+    pub environment: BTreeMap<String, String>,
+}
+
+/// Collects the invocations of one build, in the order they ran.
+#[derive(Debug)]
+pub(crate) struct CommandLog {
+    directory: PathBuf,
+    commands: Mutex<Vec<CompileCommand>>,
+}
+
+impl CommandLog {
+    /// An empty log for a build of the project at `directory`.
+    pub(crate) fn new(directory: &Path) -> Self {
+        Self {
+            directory: directory.to_path_buf(),
+            commands: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Record `cmd` as about to run with `konanc` and `java_home`.
+    ///
+    /// # Errors
+    /// Returns an error if `cmd` has no sources or output path.
+    pub(crate) fn record(
+        &self,
+        konanc: &KonancInfo,
+        cmd: &KonancCommand,
+        output: &Path,
+        java_home: Option<&Path>,
+    ) -> Result<(), EngineError> {
+        let mut arguments = vec![konanc.path.display().to_string()];
+        arguments.extend(cmd.build_args()?);
+        let environment = java_home
+            .map(|jh| ("JAVA_HOME".to_owned(), jh.display().to_string()))
+            .into_iter()
+            .collect();
+        self.lock().push(CompileCommand {
+            directory: self.directory.display().to_string(),
+            arguments,
+            output: output.display().to_string(),
+            environment,
+        });
+        Ok(())
+    }
+
+    /// The recorded invocations, in order.
+    pub(crate) fn commands(&self) -> Vec<CompileCommand> {
+        self.lock().clone()
+    }
+
+    /// Write the recorded invocations to `path` as a pretty-printed JSON
+    /// array, creating parent directories.
+    ///
+    /// # Errors
+    /// Returns an error if the log cannot be serialized or written.
+    pub(crate) fn write_to(&self, path: &Path) -> Result<(), EngineError> {
+        let json = serde_json::to_string_pretty(&self.commands()).map_err(|source| {
+            EngineError::JsonSerialize {
+                what: "compile commands",
+                source,
+            }
+        })?;
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            konvoy_util::fs::ensure_dir(parent)?;
+        }
+        konvoy_util::fs::write_file(path, json)?;
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<CompileCommand>> {
+        self.commands
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn konanc() -> KonancInfo {
+        KonancInfo {
+            path: PathBuf::from("/toolchains/2.1.0/bin/konanc"),
+            version: "2.1.0".to_owned(),
+            fingerprint: "fp".to_owned(),
+            launcher_fingerprint: "lfp".to_owned(),
+        }
+    }
+
+    #[test]
+    fn record_captures_binary_args_and_java_home() {
+        let log = CommandLog::new(Path::new("/proj"));
+        let cmd = KonancCommand::new()
+            .sources(&[PathBuf::from("/proj/src/main.kt")])
+            .output(Path::new("/proj/out/app"))
+            .release(true);
+        log.record(
+            &konanc(),
+            &cmd,
+            Path::new("/proj/out/app"),
+            Some(Path::new("/jre")),
+        )
+        .unwrap();
+
+        let commands = log.commands();
+        let entry = commands.first().unwrap();
+        assert_eq!(entry.directory, "/proj");
+        assert_eq!(
+            entry.arguments.first().map(String::as_str),
+            Some("/toolchains/2.1.0/bin/konanc")
+        );
+        assert_eq!(
+            entry.arguments.get(1..).unwrap(),
+            cmd.build_args().unwrap().as_slice()
+        );
+        assert_eq!(entry.output, "/proj/out/app");
+        assert_eq!(
+            entry.environment.get("JAVA_HOME").map(String::as_str),
+            Some("/jre")
+        );
+    }
+
+    #[test]
+    fn write_to_emits_a_json_array() {
+        let tmp = tempfile::tempdir().unwrap();
+        let log = CommandLog::new(tmp.path());
+        let cmd = KonancCommand::new()
+            .sources(&[PathBuf::from("main.kt")])
+            .output(Path::new("app"));
+        log.record(&konanc(), &cmd, Path::new("app"), None).unwrap();
+
+        let path = tmp.path().join("out").join("konanc.json");
+        log.write_to(&path).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        let entries = json.as_array().unwrap();
+        assert_eq!(entries.len(), 1);
+        let entry = entries.first().unwrap();
+        assert!(entry.get("arguments").unwrap().is_array());
+        assert_eq!(entry.get("environment").unwrap(), &serde_json::json!({}));
+    }
+}
@@ -0,0 +1,163 @@
+//! Compile an ad-hoc set of Kotlin sources without a project on disk.
+//!
+//! [`compile_sources`] is the compile step of a build with everything
+//! project-shaped taken away: no `konvoy.toml`, no lockfile, no artifact
+//! cache. It suits tools like a language server that want structured
+//! diagnostics for a handful of files. A failed compile is a normal result,
+//! not an error, so its diagnostics reach the caller.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use konvoy_config::lockfile::Lockfile;
+use konvoy_konanc::invoke::{CompilationResult, ProduceKind};
+use konvoy_konanc::toolchain::DEFAULT_JRE_MAJOR;
+use konvoy_targets::Target;
+
+use crate::build::{check_compilation, run_konanc_steps, KonancSettings};
+use crate::error::EngineError;
+
+/// Inputs to [`compile_sources`] beyond the sources and target.
+#[derive(Debug, Clone)]
+pub struct CompileSourcesOptions {
+    /// Kotlin/Native version to compile with, e.g. "2.1.0". Installed on first
+    /// use, as for a project build.
+    pub kotlin: String,
+    /// JDK major of the JRE konanc runs on, as `[toolchain] jre` sets it.
+    pub jre_major: u32,
+    /// Where konanc writes the artifact. Its directory is created if needed.
+    pub output: PathBuf,
+    /// What to produce. A library is the cheapest way to get diagnostics.
+    pub produce: ProduceKind,
+    /// Compile with optimizations (`-opt`).
+    pub release: bool,
+    /// `-language-version`, as `[toolchain] language_version` sets it.
+    pub language_version: Option<String>,
+    /// `-api-version`, as `[toolchain] api_version` sets it.
+    pub api_version: Option<String>,
+    /// Count any warning as a failure, as `[build] deny_warnings` does: the
+    /// result then has `success == false`.
+    pub deny_warnings: bool,
+    /// `.klib` files the sources compile against.
+    pub libraries: Vec<PathBuf>,
+    /// Compiler plugin JARs.
+    pub plugins: Vec<PathBuf>,
+    /// Compiler plugin options as `(plugin id, key, value)`.
+    pub plugin_options: Vec<(String, String, String)>,
+    /// Kill konanc if it runs longer than this.
+    pub timeout: Option<Duration>,
+}
+
+impl CompileSourcesOptions {
+    /// Options to compile a library into `output` with Kotlin `kotlin`, the
+    /// default JRE, and no libraries or plugins.
+    pub fn new(kotlin: &str, output: &Path) -> Self {
+        Self {
+            kotlin: kotlin.to_owned(),
+            jre_major: DEFAULT_JRE_MAJOR,
+            output: output.to_path_buf(),
+            produce: ProduceKind::Library,
+            release: false,
+            language_version: None,
+            api_version: None,
+            deny_warnings: false,
+            libraries: Vec::new(),
+            plugins: Vec::new(),
+            plugin_options: Vec::new(),
+            timeout: None,
+        }
+    }
+}
+
+/// Compile `sources` for `target` and return konanc's structured result.
+///
+/// As in a project build, a program or C library with plugins is compiled
+/// to a klib with the plugins active and then linked; the klib step's
+/// diagnostics come first in the result. Nothing is printed apart from a
+/// toolchain install.
+///
+/// # Errors
+/// Returns an error if the target is not supported yet, the toolchain cannot
+/// be resolved, or konanc cannot be run. A compile that runs and fails is
+/// `Ok` with `success == false`.
+pub fn compile_sources(
+    sources: &[PathBuf],
+    target: Target,
+    options: &CompileSourcesOptions,
+    resolver: crate::common::ArtifactResolver<'_>,
+) -> Result<CompilationResult, EngineError> {
+    if !target.is_supported() {
+        return Err(EngineError::TargetNotYetSupported {
+            target: target.to_string(),
+        });
+    }
+    let konanc =
+        resolver.resolve_toolchain(&options.kotlin, options.jre_major, &Lockfile::default())?;
+    if let Some(parent) = options.output.parent() {
+        konvoy_util::fs::ensure_dir(parent)?;
+    }
+
+    let settings = KonancSettings {
+        target,
+        release: options.release,
+        language_version: options.language_version.as_deref(),
+        api_version: options.api_version.as_deref(),
+        libraries: &options.libraries,
+        jre_home: konanc.jre_home.as_deref(),
+        timeout: options.timeout,
+        stream_output: false,
+        deny_warnings: options.deny_warnings,
+    };
+    let mut result = run_konanc_steps(
+        &settings,
+        sources,
+        &options.output,
+        options.produce,
+        &options.plugins,
+        &options.plugin_options,
+        |cmd, _| Ok(cmd.execute(&konanc.info)?),
+    )?;
+    result.success = check_compilation(&result, options.deny_warnings).is_ok();
+    Ok(result)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsupported_target_is_rejected_before_toolchain_work() {
+        let tmp = tempfile::tempdir().unwrap();
+        let options = CompileSourcesOptions::new("0.0.0-never-installed", &tmp.path().join("out"));
+        let err = compile_sources(
+            &[tmp.path().join("main.kt")],
+            Target::WindowsX64,
+            &options,
+            crate::common::test_resolver(true, false),
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, EngineError::TargetNotYetSupported { .. }),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn missing_toolchain_is_an_offline_error() {
+        let tmp = tempfile::tempdir().unwrap();
+        let options = CompileSourcesOptions::new("0.0.0-never-installed", &tmp.path().join("out"));
+        let err = compile_sources(
+            &[tmp.path().join("main.kt")],
+            Target::LinuxX64,
+            &options,
+            crate::common::test_resolver(true, false),
+        )
+        .unwrap_err();
+        assert!(
+            matches!(&err, EngineError::ToolchainOffline { version } if version == "0.0.0-never-installed"),
+            "got: {err}"
+        );
+        assert!(!tmp.path().join("out").exists());
+    }
+}
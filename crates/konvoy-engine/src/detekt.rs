@@ -3,8 +3,12 @@
 //! Downloads `detekt-cli` fat JARs from GitHub releases and runs them
 //! against Kotlin source files using the JRE bundled with managed toolchains.
 
+use std::collections::BTreeMap;
 use std::ffi::OsString;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
 
 use crate::error::EngineError;
 use crate::managed_tool::ManagedToolSpec;
@@ -14,7 +18,11 @@ fn map_download_err(version: &str, e: konvoy_util::error::UtilError) -> EngineEr
     crate::error::map_artifact_download_err(
         version,
         e,
-        |version, message| EngineError::DetektDownload { version, message },
+        |version, message| EngineError::DetektDownloadFailed {
+            url: detekt_download_url(&version),
+            version,
+            message,
+        },
         |version, expected, actual| EngineError::DetektHashMismatch {
             version,
             expected,
@@ -28,12 +36,21 @@ fn map_download_err(version: &str, e: konvoy_util::error::UtilError) -> EngineEr
 pub struct LintOptions {
     /// Whether to show raw detekt output.
     pub verbose: bool,
-    /// Optional path to a custom detekt configuration file.
-    pub config: Option<PathBuf>,
+    /// Suppress the `Linting with detekt` progress line; findings are still printed.
+    pub quiet: bool,
+    /// Custom detekt configuration files, merged by detekt in order (later
+    /// files override earlier ones). Empty means `detekt.yml` if present.
+    pub config: Vec<PathBuf>,
+    /// Re-run detekt even when cached results match the current sources and config.
+    pub force: bool,
+    /// Let detekt rewrite sources to fix what its formatting rules can (`--auto-correct`).
+    pub fix: bool,
+    /// With `fix`, skip the check that the git working tree is clean.
+    pub allow_dirty: bool,
 }
 
 /// Result of running detekt.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LintResult {
     /// Whether detekt exited successfully (no findings).
     pub success: bool,
@@ -43,10 +60,16 @@ pub struct LintResult {
     pub raw_output: String,
     /// Number of findings.
     pub finding_count: usize,
+    /// Source files rewritten by `--fix`, sorted. Always empty without it.
+    #[serde(default)]
+    pub fixed_files: Vec<PathBuf>,
+    /// The detekt version that produced these results.
+    #[serde(default)]
+    pub detekt_version: String,
 }
 
 /// A single diagnostic finding from detekt.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DetektDiagnostic {
     /// The rule name (e.g. "MagicNumber").
     pub rule: String,
@@ -131,7 +154,7 @@ pub fn ensure_detekt(
 fn persist_detekt_hash(
     lockfile_path: &Path,
     lockfile: konvoy_config::lockfile::Lockfile,
-    kotlin_version: &str,
+    toolchain: &konvoy_config::manifest::Toolchain,
     detekt_version: &str,
     hash: String,
 ) -> Result<(), EngineError> {
@@ -141,64 +164,150 @@ fn persist_detekt_hash(
         tc.detekt_jar_sha256 = Some(hash);
     } else {
         updated.toolchain = Some(konvoy_config::lockfile::ToolchainLock {
-            konanc_version: kotlin_version.to_owned(),
+            konanc_version: toolchain.kotlin.clone(),
             konanc_tarball_sha256: None,
+            konanc_tarball_url: None,
             jre_tarball_sha256: None,
+            jre_major: crate::common::recorded_jre_major(crate::common::jre_major(toolchain)),
+            konanc_fingerprint: None,
             detekt_version: Some(detekt_version.to_owned()),
             detekt_jar_sha256: Some(hash),
+            ktlint_version: None,
+            ktlint_jar_sha256: None,
         });
     }
     updated.write_to(lockfile_path)?;
     Ok(())
 }
 
-/// Resolve the detekt config file path.
+/// Resolve the detekt config file paths.
 ///
-/// If `--config` was passed, resolve it relative to the project root and
-/// verify the file exists (returning an error if it does not).
-/// Otherwise, use `detekt.yml` in the project root if it exists.
-fn resolve_config(root: &Path, explicit: Option<&Path>) -> Result<Option<PathBuf>, EngineError> {
-    if let Some(cfg) = explicit {
-        let resolved = if cfg.is_relative() {
-            root.join(cfg)
-        } else {
-            cfg.to_path_buf()
-        };
-        if !resolved.exists() {
-            return Err(EngineError::ConfigNotFound {
-                path: resolved.display().to_string(),
-            });
-        }
-        Ok(Some(resolved))
-    } else {
+/// Each `--config` path is resolved relative to the project root and must
+/// exist (the error names the first one that does not); order is preserved.
+/// Without any, `detekt.yml` in the project root is used if it exists.
+fn resolve_config(root: &Path, explicit: &[PathBuf]) -> Result<Vec<PathBuf>, EngineError> {
+    if explicit.is_empty() {
         let default_config = root.join("detekt.yml");
-        if default_config.exists() {
-            Ok(Some(default_config))
+        return Ok(if default_config.exists() {
+            vec![default_config]
         } else {
-            Ok(None)
+            Vec::new()
+        });
+    }
+    explicit
+        .iter()
+        .map(|cfg| {
+            let resolved = if cfg.is_relative() {
+                root.join(cfg)
+            } else {
+                cfg.clone()
+            };
+            if resolved.exists() {
+                Ok(resolved)
+            } else {
+                Err(EngineError::ConfigNotFound {
+                    path: resolved.display().to_string(),
+                })
+            }
+        })
+        .collect()
+}
+
+/// Versions the lint cache key, so a change to what is cached or how it is
+/// keyed never reuses old entries.
+const LINT_CACHE_VERSION: &str = "lint-v3";
+
+/// Compute the lint cache key from everything that can change detekt's output:
+/// the linted `sources` (absolute paths and contents), the detekt version, and
+/// the contents of every resolved config file, in order.
+///
+/// Source paths are part of the key because findings carry absolute paths and
+/// the cache is shared between git worktrees.
+fn lint_cache_key(
+    sources: &[PathBuf],
+    detekt_version: &str,
+    config_paths: &[PathBuf],
+) -> Result<String, EngineError> {
+    let mut source_parts = Vec::with_capacity(sources.len() * 2);
+    for (path, hash) in snapshot_files(sources)? {
+        source_parts.push(path.display().to_string());
+        source_parts.push(hash);
+    }
+    let source_parts: Vec<&str> = source_parts.iter().map(String::as_str).collect();
+    let source_hash = konvoy_util::hash::sha256_multi(&source_parts);
+    let config_hashes = config_paths
+        .iter()
+        .map(|path| konvoy_util::hash::sha256_file(path))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut parts = vec![LINT_CACHE_VERSION, detekt_version, &source_hash];
+    parts.extend(config_hashes.iter().map(String::as_str));
+    Ok(konvoy_util::hash::sha256_multi(&parts))
+}
+
+/// Read a cached `LintResult`, treating a missing or unreadable entry as a miss.
+fn load_cached_result(path: &Path) -> Option<LintResult> {
+    std::fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+}
+
+/// Write `result` to the lint cache at `path`.
+fn store_cached_result(path: &Path, result: &LintResult) -> Result<(), EngineError> {
+    let json =
+        serde_json::to_string_pretty(result).map_err(|source| EngineError::JsonSerialize {
+            what: "lint result",
+            source,
+        })?;
+    if let Some(parent) = path.parent() {
+        konvoy_util::fs::ensure_dir(parent)?;
+    }
+    konvoy_util::fs::write_file(path, json)?;
+    Ok(())
+}
+
+/// Join `paths` into one comma-separated detekt argument value.
+fn comma_joined(paths: &[PathBuf]) -> OsString {
+    let mut joined = OsString::new();
+    for (i, path) in paths.iter().enumerate() {
+        if i > 0 {
+            joined.push(",");
         }
+        joined.push(path);
     }
+    joined
+}
+
+/// Build the detekt command line: the source files as one comma-separated
+/// `--input` value, `--auto-correct` when fixing, then the config files as one
+/// comma-separated `--config` value, layered on detekt's defaults.
+fn detekt_args(sources: &[PathBuf], config_paths: &[PathBuf], fix: bool) -> Vec<OsString> {
+    let mut args = vec![OsString::from("--input"), comma_joined(sources)];
+    if fix {
+        args.push(OsString::from("--auto-correct"));
+    }
+    if !config_paths.is_empty() {
+        args.push(OsString::from("--config"));
+        args.push(comma_joined(config_paths));
+        args.push(OsString::from("--build-upon-default-config"));
+    }
+    args
 }
 
 /// Execute the detekt process and build a `LintResult` from its output.
 fn run_detekt_process(
     jre_home: &Path,
-    src_dir: &Path,
-    config_path: Option<&Path>,
+    sources: &[PathBuf],
+    config_paths: &[PathBuf],
     detekt_version: &str,
-    verbose: bool,
+    options: &LintOptions,
 ) -> Result<LintResult, EngineError> {
-    let mut args = vec![OsString::from("--input"), src_dir.as_os_str().to_owned()];
+    let args = detekt_args(sources, config_paths, options.fix);
 
-    if let Some(cfg) = config_path {
-        args.push(OsString::from("--config"));
-        args.push(cfg.as_os_str().to_owned());
-        args.push(OsString::from("--build-upon-default-config"));
+    if !options.quiet {
+        eprintln!("    Linting with detekt {detekt_version}...");
     }
 
-    eprintln!("    Linting with detekt {detekt_version}...");
-
-    let output = detekt_tool(detekt_version).run(Some(jre_home), &args, verbose)?;
+    let output = detekt_tool(detekt_version).run(Some(jre_home), &args, options.verbose)?;
 
     // Separate the streams with a newline: detekt's findings are line-oriented and
     // `parse_detekt_output` scans per line, so concatenating directly would fuse a
@@ -218,9 +327,59 @@ fn run_detekt_process(
         diagnostics,
         raw_output,
         finding_count,
+        fixed_files: Vec::new(),
+        detekt_version: detekt_version.to_owned(),
     })
 }
 
+/// Refuse `--fix` unless `root` is in a git working tree with no uncommitted
+/// changes, so every rewrite detekt makes can be reviewed and reverted.
+fn require_clean_worktree(root: &Path) -> Result<(), EngineError> {
+    let not_a_repo = || EngineError::LintFixWithoutVcs {
+        path: root.display().to_string(),
+    };
+    let output = Command::new("git")
+        .args(["status", "--porcelain"])
+        .current_dir(root)
+        .output()
+        .map_err(|_| not_a_repo())?;
+    if !output.status.success() {
+        return Err(not_a_repo());
+    }
+    let dirty: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.get(3..))
+        .map(str::to_owned)
+        .collect();
+    if dirty.is_empty() {
+        return Ok(());
+    }
+    Err(EngineError::LintFixDirty {
+        count: dirty.len(),
+        files: dirty.into_iter().take(3).collect::<Vec<_>>().join(", "),
+    })
+}
+
+/// Hash each of `files`, keyed by path.
+pub(crate) fn snapshot_files(files: &[PathBuf]) -> Result<BTreeMap<PathBuf, String>, EngineError> {
+    files
+        .iter()
+        .map(|path| Ok((path.clone(), konvoy_util::hash::sha256_file(path)?)))
+        .collect()
+}
+
+/// Return the files whose hash differs between two snapshots, sorted.
+pub(crate) fn changed_files(
+    before: &BTreeMap<PathBuf, String>,
+    after: &BTreeMap<PathBuf, String>,
+) -> Vec<PathBuf> {
+    after
+        .iter()
+        .filter(|(path, hash)| before.get(*path) != Some(*hash))
+        .map(|(path, _)| path.clone())
+        .collect()
+}
+
 /// Run detekt on a project's Kotlin source files.
 ///
 /// # Errors
@@ -240,6 +399,10 @@ pub fn lint(
         .as_deref()
         .ok_or(EngineError::LintNotConfigured)?;
 
+    if options.fix && !options.allow_dirty {
+        require_clean_worktree(root)?;
+    }
+
     // Read lockfile and resolve expected hash.
     let lockfile_path = root.join("konvoy.lock");
     let lockfile = konvoy_config::lockfile::Lockfile::from_path(&lockfile_path)?;
@@ -256,40 +419,74 @@ pub fn lint(
     // with the toolchain absent), and a failed lint must not leave a rewritten
     // konvoy.lock behind. This is also the last read of `lockfile`, so the
     // persist below can consume it without a clone.
-    let jre_home = resolver.resolve_jre(&manifest.toolchain.kotlin, &lockfile)?;
+    let jre_home = resolver.resolve_jre(
+        &manifest.toolchain.kotlin,
+        crate::common::jre_major(&manifest.toolchain),
+        &lockfile,
+    )?;
 
     // Persist the freshly-resolved hash to the lockfile if it was not pinned.
     if let Some(actual_sha256) = detekt_hash_to_persist {
         persist_detekt_hash(
             &lockfile_path,
             lockfile,
-            &manifest.toolchain.kotlin,
+            &manifest.toolchain,
             detekt_version,
             actual_sha256,
         )?;
     }
 
-    // Check for sources.
-    let src_dir = root.join("src");
-    if !src_dir.exists() {
-        eprintln!("    warning: no Kotlin sources to lint (src/ not found)");
+    // Lint what the build compiles (`source_dirs`, minus `.konvoyignore`), plus
+    // the test sources.
+    let sources = crate::sources::project_sources(root, &manifest)?;
+    if sources.is_empty() {
+        eprintln!("    warning: no Kotlin sources to lint");
         return Ok(LintResult {
             success: true,
             diagnostics: Vec::new(),
             raw_output: String::new(),
             finding_count: 0,
+            fixed_files: Vec::new(),
+            detekt_version: detekt_version.to_owned(),
         });
     }
 
-    // Resolve config and run detekt.
-    let config_path = resolve_config(root, options.config.as_deref())?;
-    run_detekt_process(
-        &jre_home,
-        &src_dir,
-        config_path.as_deref(),
-        detekt_version,
-        options.verbose,
-    )
+    // Resolve config and reuse cached results when nothing detekt reads changed.
+    let config_paths = resolve_config(root, &options.config)?;
+    let cache_key = lint_cache_key(&sources, detekt_version, &config_paths)?;
+    let cache_path = crate::artifact::ArtifactStore::new(root).lint_result_path(&cache_key);
+    // `--fix` always runs: cached findings say nothing about what can be fixed.
+    if !options.force && !options.fix {
+        if let Some(cached) = load_cached_result(&cache_path) {
+            if !options.quiet {
+                eprintln!("    Fresh lint results for detekt {detekt_version} (cached)");
+            }
+            if options.verbose {
+                eprintln!("{}", cached.raw_output.trim_end());
+            }
+            return Ok(cached);
+        }
+    }
+
+    let before_fix = if options.fix {
+        Some(snapshot_files(&sources)?)
+    } else {
+        None
+    };
+    let mut result =
+        run_detekt_process(&jre_home, &sources, &config_paths, detekt_version, options)?;
+    if let Some(before) = before_fix {
+        result.fixed_files = changed_files(&before, &snapshot_files(&sources)?);
+    }
+    // A failure without findings is detekt itself failing (bad config, crash);
+    // caching it would hide a fix to the environment. A `--fix` run rewrote the
+    // sources the key was computed from, so its result is not cached either.
+    if !options.fix && (result.success || result.finding_count > 0) {
+        if let Err(e) = store_cached_result(&cache_path, &result) {
+            eprintln!("    warning: cannot cache lint results: {e}");
+        }
+    }
+    Ok(result)
 }
 
 /// Parse detekt text output into structured diagnostics.
@@ -439,7 +636,118 @@ fn parse_detekt_line(line: &str) -> Option<DetektDiagnostic> {
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
-    use super::{detekt_download_url, detekt_jar_path, parse_detekt_output, resolve_config};
+    use super::{
+        changed_files, detekt_args, detekt_download_url, detekt_jar_path, lint_cache_key,
+        load_cached_result, map_download_err, parse_detekt_output, require_clean_worktree,
+        resolve_config, snapshot_files, store_cached_result, LintResult,
+    };
+    use crate::error::EngineError;
+
+    #[test]
+    fn lint_cache_key_tracks_sources_version_and_config_contents() {
+        let tmp = tempfile::tempdir().unwrap();
+        let src = tmp.path().join("src");
+        std::fs::create_dir(&src).unwrap();
+        std::fs::write(src.join("main.kt"), "fun main() {}\n").unwrap();
+        let src = vec![src.join("main.kt")];
+        let config = tmp.path().join("detekt.yml");
+        std::fs::write(&config, "style:\n  MagicNumber:\n    active: true\n").unwrap();
+
+        let base = lint_cache_key(&src, "1.23.7", std::slice::from_ref(&config)).unwrap();
+        assert_eq!(
+            base,
+            lint_cache_key(&src, "1.23.7", std::slice::from_ref(&config)).unwrap()
+        );
+        assert_ne!(
+            base,
+            lint_cache_key(&src, "1.23.8", std::slice::from_ref(&config)).unwrap()
+        );
+        assert_ne!(base, lint_cache_key(&src, "1.23.7", &[]).unwrap());
+
+        // Same config path, different contents.
+        std::fs::write(&config, "style:\n  MagicNumber:\n    active: false\n").unwrap();
+        let edited_config = lint_cache_key(&src, "1.23.7", std::slice::from_ref(&config)).unwrap();
+        assert_ne!(base, edited_config);
+
+        // Adding, or reordering, config files changes the key too.
+        let overrides = tmp.path().join("overrides.yml");
+        std::fs::write(&overrides, "complexity:\n  active: false\n").unwrap();
+        let layered = lint_cache_key(&src, "1.23.7", &[config.clone(), overrides.clone()]).unwrap();
+        assert_ne!(edited_config, layered);
+        assert_ne!(
+            layered,
+            lint_cache_key(&src, "1.23.7", &[overrides, config.clone()]).unwrap()
+        );
+
+        std::fs::write(&src[0], "fun main() { println(1) }\n").unwrap();
+        let edited_source = lint_cache_key(&src, "1.23.7", std::slice::from_ref(&config)).unwrap();
+        assert_ne!(edited_config, edited_source);
+
+        // Linting one more file changes the key.
+        let extra = tmp.path().join("gen.kt");
+        std::fs::write(&extra, "fun gen() {}\n").unwrap();
+        assert_ne!(
+            edited_source,
+            lint_cache_key(
+                &[src[0].clone(), extra],
+                "1.23.7",
+                std::slice::from_ref(&config)
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn cached_lint_result_round_trips() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("lint").join("key.json");
+        assert!(load_cached_result(&path).is_none());
+
+        let raw = "src/main.kt:3:5: Magic number. [MagicNumber]";
+        let diagnostics = parse_detekt_output(raw);
+        let result = LintResult {
+            success: false,
+            finding_count: diagnostics.len(),
+            diagnostics,
+            raw_output: raw.to_owned(),
+            fixed_files: Vec::new(),
+            detekt_version: "1.23.7".to_owned(),
+        };
+        store_cached_result(&path, &result).unwrap();
+
+        let cached = load_cached_result(&path).unwrap();
+        assert!(!cached.success);
+        assert_eq!(cached.finding_count, 1);
+        assert_eq!(cached.diagnostics[0].rule, "MagicNumber");
+        assert_eq!(cached.diagnostics[0].line, Some(3));
+    }
+
+    #[test]
+    fn corrupt_lint_cache_entry_is_a_miss() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("key.json");
+        std::fs::write(&path, "{not json").unwrap();
+        assert!(load_cached_result(&path).is_none());
+    }
+
+    #[test]
+    fn download_failure_names_detekt_and_url() {
+        let err = map_download_err(
+            "1.23.7",
+            konvoy_util::error::UtilError::Download {
+                message: "connection refused".to_owned(),
+            },
+        );
+        assert!(
+            matches!(&err, EngineError::DetektDownloadFailed { version, url, .. }
+                if version == "1.23.7" && *url == detekt_download_url("1.23.7")),
+            "got: {err:?}"
+        );
+        let msg = err.to_string();
+        assert!(msg.contains("cannot download detekt 1.23.7"), "{msg}");
+        assert!(msg.contains("connection refused"), "{msg}");
+        assert!(msg.contains("check your network connection"), "{msg}");
+    }
 
     #[test]
     fn detekt_download_url_format() {
@@ -647,9 +955,14 @@ src/main.kt:3:5: Magic number. [MagicNumber]
             toolchain: Some(konvoy_config::lockfile::ToolchainLock {
                 konanc_version: kotlin_version.to_owned(),
                 konanc_tarball_sha256: None,
+                konanc_tarball_url: None,
                 jre_tarball_sha256: None,
+                jre_major: None,
+                konanc_fingerprint: None,
                 detekt_version: Some(detekt_version.to_owned()),
                 detekt_jar_sha256: Some(jar_hash),
+                ktlint_version: None,
+                ktlint_jar_sha256: None,
             }),
             ..Default::default()
         };
@@ -659,7 +972,11 @@ src/main.kt:3:5: Magic number. [MagicNumber]
             root,
             &super::LintOptions {
                 verbose: false,
-                config: None,
+                quiet: false,
+                config: Vec::new(),
+                force: false,
+                fix: false,
+                allow_dirty: false,
             },
             crate::common::test_resolver(true, false),
         );
@@ -706,9 +1023,14 @@ src/main.kt:3:5: Magic number. [MagicNumber]
             toolchain: Some(konvoy_config::lockfile::ToolchainLock {
                 konanc_version: kotlin_version.to_owned(),
                 konanc_tarball_sha256: None,
+                konanc_tarball_url: None,
                 jre_tarball_sha256: None,
+                jre_major: None,
+                konanc_fingerprint: None,
                 detekt_version: Some(detekt_version.to_owned()),
                 detekt_jar_sha256: Some("0".repeat(64)),
+                ktlint_version: None,
+                ktlint_jar_sha256: None,
             }),
             ..Default::default()
         };
@@ -718,7 +1040,11 @@ src/main.kt:3:5: Magic number. [MagicNumber]
             root,
             &super::LintOptions {
                 verbose: false,
-                config: None,
+                quiet: false,
+                config: Vec::new(),
+                force: false,
+                fix: false,
+                allow_dirty: false,
             },
             crate::common::test_resolver(true, false),
         );
@@ -755,9 +1081,14 @@ src/main.kt:3:5: Magic number. [MagicNumber]
             toolchain: Some(konvoy_config::lockfile::ToolchainLock {
                 konanc_version: kotlin_version.to_owned(),
                 konanc_tarball_sha256: None,
+                konanc_tarball_url: None,
                 jre_tarball_sha256: None,
+                jre_major: None,
+                konanc_fingerprint: None,
                 detekt_version: Some(detekt_version.to_owned()),
                 detekt_jar_sha256: None,
+                ktlint_version: None,
+                ktlint_jar_sha256: None,
             }),
             ..Default::default()
         };
@@ -767,7 +1098,11 @@ src/main.kt:3:5: Magic number. [MagicNumber]
             root,
             &super::LintOptions {
                 verbose: false,
-                config: None,
+                quiet: false,
+                config: Vec::new(),
+                force: false,
+                fix: false,
+                allow_dirty: false,
             },
             crate::common::test_resolver(false, true),
         );
@@ -803,9 +1138,14 @@ src/main.kt:3:5: Magic number. [MagicNumber]
             toolchain: Some(konvoy_config::lockfile::ToolchainLock {
                 konanc_version: lockfile_kotlin.to_owned(),
                 konanc_tarball_sha256: None,
+                konanc_tarball_url: None,
                 jre_tarball_sha256: None,
+                jre_major: None,
+                konanc_fingerprint: None,
                 detekt_version: Some(detekt_version.to_owned()),
                 detekt_jar_sha256: Some("0".repeat(64)),
+                ktlint_version: None,
+                ktlint_jar_sha256: None,
             }),
             ..Default::default()
         };
@@ -815,7 +1155,11 @@ src/main.kt:3:5: Magic number. [MagicNumber]
             root,
             &super::LintOptions {
                 verbose: false,
-                config: None,
+                quiet: false,
+                config: Vec::new(),
+                force: false,
+                fix: false,
+                allow_dirty: false,
             },
             crate::common::test_resolver(false, true),
         );
@@ -856,9 +1200,14 @@ src/main.kt:3:5: Magic number. [MagicNumber]
             toolchain: Some(konvoy_config::lockfile::ToolchainLock {
                 konanc_version: kotlin_version.to_owned(),
                 konanc_tarball_sha256: None,
+                konanc_tarball_url: None,
                 jre_tarball_sha256: None,
+                jre_major: None,
+                konanc_fingerprint: None,
                 detekt_version: Some(detekt_version.to_owned()),
                 detekt_jar_sha256: Some(jar_hash),
+                ktlint_version: None,
+                ktlint_jar_sha256: None,
             }),
             ..Default::default()
         };
@@ -868,7 +1217,11 @@ src/main.kt:3:5: Magic number. [MagicNumber]
             root,
             &super::LintOptions {
                 verbose: false,
-                config: None,
+                quiet: false,
+                config: Vec::new(),
+                force: false,
+                fix: false,
+                allow_dirty: false,
             },
             crate::common::test_resolver(false, true),
         );
@@ -947,7 +1300,7 @@ src/config.kt:15:1: Line is too long. [MaxLineLength]";
         let tmp = tempfile::tempdir().unwrap();
         let root = tmp.path();
         let missing = std::path::Path::new("nonexistent.yml");
-        let result = resolve_config(root, Some(missing));
+        let result = resolve_config(root, &[missing.to_path_buf()]);
         assert!(result.is_err(), "expected Err for missing explicit config");
         let err = result.unwrap_err().to_string();
         assert!(err.contains("config file not found"), "error was: {err}");
@@ -963,9 +1316,9 @@ src/config.kt:15:1: Line is too long. [MaxLineLength]";
         let root = tmp.path();
         let cfg_path = root.join("my-detekt.yml");
         std::fs::write(&cfg_path, "# config").unwrap();
-        let result = resolve_config(root, Some(std::path::Path::new("my-detekt.yml")));
+        let result = resolve_config(root, &[std::path::PathBuf::from("my-detekt.yml")]);
         assert!(result.is_ok(), "expected Ok, got: {result:?}");
-        assert_eq!(result.unwrap(), Some(cfg_path));
+        assert_eq!(result.unwrap(), vec![cfg_path]);
     }
 
     #[test]
@@ -974,9 +1327,136 @@ src/config.kt:15:1: Line is too long. [MaxLineLength]";
         let root = tmp.path();
         let cfg_path = tmp.path().join("absolute-detekt.yml");
         std::fs::write(&cfg_path, "# config").unwrap();
-        let result = resolve_config(root, Some(&cfg_path));
+        let result = resolve_config(root, std::slice::from_ref(&cfg_path));
         assert!(result.is_ok(), "expected Ok, got: {result:?}");
-        assert_eq!(result.unwrap(), Some(cfg_path));
+        assert_eq!(result.unwrap(), vec![cfg_path]);
+    }
+
+    #[test]
+    fn resolve_config_keeps_multiple_files_in_order() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        std::fs::write(root.join("base.yml"), "# base").unwrap();
+        std::fs::write(root.join("project.yml"), "# overrides").unwrap();
+        let resolved = resolve_config(
+            root,
+            &[
+                std::path::PathBuf::from("base.yml"),
+                std::path::PathBuf::from("project.yml"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            resolved,
+            vec![root.join("base.yml"), root.join("project.yml")]
+        );
+    }
+
+    #[test]
+    fn resolve_config_names_the_missing_file_among_several() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        std::fs::write(root.join("base.yml"), "# base").unwrap();
+        let err = resolve_config(
+            root,
+            &[
+                std::path::PathBuf::from("base.yml"),
+                std::path::PathBuf::from("missing-overrides.yml"),
+            ],
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("missing-overrides.yml"), "error was: {err}");
+    }
+
+    fn git_init(dir: &std::path::Path) {
+        let status = std::process::Command::new("git")
+            .args(["init", "--quiet"])
+            .current_dir(dir)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn fix_requires_a_git_worktree() {
+        let tmp = tempfile::tempdir().unwrap();
+        let err = require_clean_worktree(tmp.path()).unwrap_err();
+        assert!(
+            matches!(err, EngineError::LintFixWithoutVcs { .. }),
+            "got: {err}"
+        );
+        assert!(err.to_string().contains("--allow-dirty"));
+    }
+
+    #[test]
+    fn fix_allows_a_clean_worktree() {
+        let tmp = tempfile::tempdir().unwrap();
+        git_init(tmp.path());
+        require_clean_worktree(tmp.path()).unwrap();
+    }
+
+    #[test]
+    fn fix_refuses_uncommitted_changes() {
+        let tmp = tempfile::tempdir().unwrap();
+        git_init(tmp.path());
+        std::fs::write(tmp.path().join("Main.kt"), "fun main() {}\n").unwrap();
+        let err = require_clean_worktree(tmp.path()).unwrap_err();
+        assert!(
+            matches!(&err, EngineError::LintFixDirty { count: 1, files } if files == "Main.kt"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn changed_files_reports_rewritten_files_only() {
+        let tmp = tempfile::tempdir().unwrap();
+        let src = tmp.path().join("src");
+        std::fs::create_dir(&src).unwrap();
+        std::fs::write(src.join("a.kt"), "val a = 1 \n").unwrap();
+        std::fs::write(src.join("b.kt"), "val b = 2\n").unwrap();
+        let files = [src.join("a.kt"), src.join("b.kt")];
+        let before = snapshot_files(&files).unwrap();
+        assert_eq!(before.len(), 2);
+
+        std::fs::write(src.join("a.kt"), "val a = 1\n").unwrap();
+        let after = snapshot_files(&files).unwrap();
+
+        assert_eq!(changed_files(&before, &after), vec![src.join("a.kt")]);
+    }
+
+    #[test]
+    fn detekt_args_join_sources_and_configs_in_order() {
+        let sources = [
+            std::path::PathBuf::from("/p/src/a.kt"),
+            std::path::PathBuf::from("/p/gen/b.kt"),
+        ];
+        let args = detekt_args(
+            &sources,
+            &[
+                std::path::PathBuf::from("/p/base.yml"),
+                std::path::PathBuf::from("/p/project.yml"),
+            ],
+            false,
+        );
+        assert_eq!(
+            args,
+            [
+                "--input",
+                "/p/src/a.kt,/p/gen/b.kt",
+                "--config",
+                "/p/base.yml,/p/project.yml",
+                "--build-upon-default-config"
+            ]
+        );
+        assert_eq!(
+            detekt_args(&sources[..1], &[], false),
+            ["--input", "/p/src/a.kt"]
+        );
+        assert_eq!(
+            detekt_args(&sources[..1], &[], true),
+            ["--input", "/p/src/a.kt", "--auto-correct"]
+        );
     }
 
     #[test]
@@ -985,17 +1465,17 @@ src/config.kt:15:1: Line is too long. [MaxLineLength]";
         let root = tmp.path();
         let default_cfg = root.join("detekt.yml");
         std::fs::write(&default_cfg, "# default config").unwrap();
-        let result = resolve_config(root, None);
+        let result = resolve_config(root, &[]);
         assert!(result.is_ok(), "expected Ok, got: {result:?}");
-        assert_eq!(result.unwrap(), Some(default_cfg));
+        assert_eq!(result.unwrap(), vec![default_cfg]);
     }
 
     #[test]
     fn resolve_config_returns_none_when_no_config() {
         let tmp = tempfile::tempdir().unwrap();
         let root = tmp.path();
-        let result = resolve_config(root, None);
+        let result = resolve_config(root, &[]);
         assert!(result.is_ok(), "expected Ok, got: {result:?}");
-        assert_eq!(result.unwrap(), None);
+        assert!(result.unwrap().is_empty());
     }
 }
@@ -1,30 +1,72 @@
 //! Shared diagnostic printing for build and test pipelines.
 
-use konvoy_konanc::invoke::{CompilationResult, DiagnosticLevel};
+use std::collections::HashMap;
+
+use konvoy_konanc::invoke::{CompilationResult, Diagnostic, DiagnosticLevel};
+use konvoy_util::color::{paint, Color};
+
+use crate::build::BuildOptions;
 
 /// Print structured diagnostics from a compilation result to stderr.
 ///
-/// When `verbose` is true, raw compiler stdout/stderr is also printed.
-pub(crate) fn print_diagnostics(result: &CompilationResult, verbose: bool) {
-    for diag in &result.diagnostics {
-        let prefix = match diag.level {
-            DiagnosticLevel::Error => "error",
-            DiagnosticLevel::Warning => "warning",
-            DiagnosticLevel::Info => "info",
-        };
-        match (&diag.file, diag.line) {
-            (Some(file), Some(line)) => eprintln!("{prefix}: {file}:{line}: {}", diag.message),
-            _ => eprintln!("{prefix}: {}", diag.message),
-        }
+/// When `options.verbose` is true, raw compiler stdout is also printed. Raw
+/// stderr is not repeated here: verbose invocations stream it live while konanc
+/// runs (see `KonancCommand::stream_output`). The level prefix is colored per
+/// `options.color`; the text is the same either way.
+pub(crate) fn print_diagnostics(result: &CompilationResult, options: &BuildOptions) {
+    let color = options.color.enabled();
+    for line in diagnostic_lines(&result.diagnostics, options.dedupe_diagnostics, color) {
+        eprintln!("{line}");
     }
 
-    if verbose {
-        if !result.raw_stdout.is_empty() {
-            eprintln!("{}", result.raw_stdout);
-        }
-        if !result.raw_stderr.is_empty() {
-            eprintln!("{}", result.raw_stderr);
-        }
+    if options.verbose && !result.raw_stdout.is_empty() {
+        eprintln!("{}", result.raw_stdout);
+    }
+}
+
+/// Render diagnostics one per line. With `dedupe`, diagnostics sharing a
+/// `(level, message)` collapse into the first occurrence, suffixed with
+/// `(and N more)` — the same content-keyed dedupe konanc's toolchain
+/// detection applies, but at print time so the result stays complete.
+fn diagnostic_lines(diagnostics: &[Diagnostic], dedupe: bool, color: bool) -> Vec<String> {
+    if !dedupe {
+        return diagnostics
+            .iter()
+            .map(|d| format_diagnostic(d, color))
+            .collect();
+    }
+
+    let mut counts: HashMap<(DiagnosticLevel, &str), usize> = HashMap::new();
+    for diag in diagnostics {
+        *counts
+            .entry((diag.level.clone(), diag.message.as_str()))
+            .or_default() += 1;
+    }
+    diagnostics
+        .iter()
+        .filter_map(|diag| {
+            // Removing the entry prints only the first occurrence of each key.
+            let count = counts.remove(&(diag.level.clone(), diag.message.as_str()))?;
+            let line = format_diagnostic(diag, color);
+            Some(match count.saturating_sub(1) {
+                0 => line,
+                more => format!("{line} (and {more} more)"),
+            })
+        })
+        .collect()
+}
+
+/// Render one diagnostic as `<level>: [<file>:<line>: ]<message>`.
+fn format_diagnostic(diag: &Diagnostic, color: bool) -> String {
+    let (prefix, prefix_color) = match diag.level {
+        DiagnosticLevel::Error => ("error", Color::Red),
+        DiagnosticLevel::Warning => ("warning", Color::Yellow),
+        DiagnosticLevel::Info => ("info", Color::Blue),
+    };
+    let prefix = paint(prefix, prefix_color, color);
+    match (&diag.file, diag.line) {
+        (Some(file), Some(line)) => format!("{prefix}: {file}:{line}: {}", diag.message),
+        _ => format!("{prefix}: {}", diag.message),
     }
 }
 
@@ -32,7 +74,6 @@ pub(crate) fn print_diagnostics(result: &CompilationResult, verbose: bool) {
 #[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
-    use konvoy_konanc::invoke::{Diagnostic, DiagnosticLevel};
     use std::path::PathBuf;
 
     fn make_result(
@@ -73,19 +114,19 @@ mod tests {
     fn prefix_for_error_level() {
         let result = make_result(vec![diag(DiagnosticLevel::Error, "bad")], "", "");
         // Just verify it doesn't panic — formatting correctness tested via structure.
-        print_diagnostics(&result, false);
+        print_diagnostics(&result, &BuildOptions::default());
     }
 
     #[test]
     fn prefix_for_warning_level() {
         let result = make_result(vec![diag(DiagnosticLevel::Warning, "meh")], "", "");
-        print_diagnostics(&result, false);
+        print_diagnostics(&result, &BuildOptions::default());
     }
 
     #[test]
     fn prefix_for_info_level() {
         let result = make_result(vec![diag(DiagnosticLevel::Info, "fyi")], "", "");
-        print_diagnostics(&result, false);
+        print_diagnostics(&result, &BuildOptions::default());
     }
 
     #[test]
@@ -100,28 +141,40 @@ mod tests {
             "",
             "",
         );
-        print_diagnostics(&result, false);
+        print_diagnostics(&result, &BuildOptions::default());
     }
 
     #[test]
     fn verbose_false_does_not_print_raw_output() {
         let result = make_result(vec![], "stdout stuff", "stderr stuff");
         // Should not panic; raw output suppressed when verbose=false.
-        print_diagnostics(&result, false);
+        print_diagnostics(&result, &BuildOptions::default());
     }
 
     #[test]
     fn verbose_true_prints_raw_output() {
         let result = make_result(vec![], "stdout stuff", "stderr stuff");
         // Should not panic; raw output printed when verbose=true.
-        print_diagnostics(&result, true);
+        print_diagnostics(
+            &result,
+            &BuildOptions {
+                verbose: true,
+                ..BuildOptions::default()
+            },
+        );
     }
 
     #[test]
     fn empty_diagnostics_and_raw_output() {
         let result = make_result(vec![], "", "");
-        print_diagnostics(&result, false);
-        print_diagnostics(&result, true);
+        print_diagnostics(&result, &BuildOptions::default());
+        print_diagnostics(
+            &result,
+            &BuildOptions {
+                verbose: true,
+                ..BuildOptions::default()
+            },
+        );
     }
 
     #[test]
@@ -136,6 +189,91 @@ mod tests {
             "",
             "",
         );
-        print_diagnostics(&result, false);
+        print_diagnostics(&result, &BuildOptions::default());
+    }
+
+    #[test]
+    fn plain_format_is_unchanged() {
+        assert_eq!(
+            format_diagnostic(&diag(DiagnosticLevel::Error, "bad"), false),
+            "error: bad"
+        );
+        assert_eq!(
+            format_diagnostic(
+                &located_diag(DiagnosticLevel::Warning, "unused", "src/main.kt", 3),
+                false
+            ),
+            "warning: src/main.kt:3: unused"
+        );
+    }
+
+    #[test]
+    fn colored_format_paints_only_the_level() {
+        assert_eq!(
+            format_diagnostic(&diag(DiagnosticLevel::Error, "bad"), true),
+            "\x1b[1;31merror\x1b[0m: bad"
+        );
+        assert_eq!(
+            format_diagnostic(&diag(DiagnosticLevel::Warning, "meh"), true),
+            "\x1b[1;33mwarning\x1b[0m: meh"
+        );
+        assert_eq!(
+            format_diagnostic(&diag(DiagnosticLevel::Info, "fyi"), true),
+            "\x1b[1;34minfo\x1b[0m: fyi"
+        );
+    }
+
+    #[test]
+    fn dedupe_off_prints_every_diagnostic() {
+        let diags = vec![
+            located_diag(
+                DiagnosticLevel::Error,
+                "unresolved reference: foo",
+                "a.kt",
+                1,
+            ),
+            located_diag(
+                DiagnosticLevel::Error,
+                "unresolved reference: foo",
+                "b.kt",
+                2,
+            ),
+        ];
+        assert_eq!(diagnostic_lines(&diags, false, false).len(), 2);
+    }
+
+    #[test]
+    fn dedupe_collapses_identical_level_and_message() {
+        let diags = vec![
+            located_diag(
+                DiagnosticLevel::Error,
+                "unresolved reference: foo",
+                "a.kt",
+                1,
+            ),
+            located_diag(DiagnosticLevel::Warning, "unused variable", "a.kt", 4),
+            located_diag(
+                DiagnosticLevel::Error,
+                "unresolved reference: foo",
+                "b.kt",
+                2,
+            ),
+            located_diag(
+                DiagnosticLevel::Error,
+                "unresolved reference: foo",
+                "c.kt",
+                9,
+            ),
+            // Same message at a different level is a distinct diagnostic.
+            diag(DiagnosticLevel::Warning, "unresolved reference: foo"),
+        ];
+        assert_eq!(
+            diagnostic_lines(&diags, true, false),
+            vec![
+                "error: a.kt:1: unresolved reference: foo (and 2 more)",
+                "warning: a.kt:4: unused variable",
+                "warning: unresolved reference: foo",
+            ]
+        );
     }
 }
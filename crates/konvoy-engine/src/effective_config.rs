@@ -0,0 +1,255 @@
+//! `konvoy config`: the configuration a build actually uses — `konvoy.toml`
+//! with every omitted default filled in, the build profiles, and the versions
+//! pinned in `konvoy.lock`.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use konvoy_config::lockfile::{DepSource, Lockfile};
+use konvoy_config::manifest::{Manifest, PackageKind};
+use konvoy_config::Profile;
+use serde::Serialize;
+
+use crate::error::EngineError;
+
+/// The fully resolved configuration of a project.
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveConfig {
+    /// The manifest, with defaults it omitted made explicit.
+    #[serde(flatten)]
+    pub manifest: Manifest,
+    /// Settings computed from the manifest and lockfile rather than written in it.
+    #[serde(flatten)]
+    pub resolved: ResolvedSettings,
+}
+
+/// The parts of [`EffectiveConfig`] that `konvoy.toml` has no keys for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResolvedSettings {
+    /// What each build profile passes to `konanc`, keyed by profile name.
+    pub profiles: BTreeMap<String, ProfileSettings>,
+    /// Every dependency pinned in `konvoy.lock`, direct and transitive, keyed
+    /// by name. Empty when there is no lockfile yet.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub locked: BTreeMap<String, LockedDependency>,
+}
+
+/// What one build profile does.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProfileSettings {
+    /// Whether `konanc` optimizes the output.
+    pub optimize: bool,
+    /// Flags the profile adds to every `konanc` invocation.
+    pub konanc_flags: Vec<String>,
+}
+
+/// A dependency as `konvoy.lock` pins it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LockedDependency {
+    /// `path`, `maven`, or `git`.
+    pub source: String,
+    /// The resolved Maven version.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The path-dependency directory, relative to the project.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// The commit a git dependency's `rev` resolved to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// Dependencies that pulled this one in; empty for direct dependencies.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub required_by: Vec<String>,
+}
+
+impl EffectiveConfig {
+    /// Render as TOML: the manifest in its usual layout, followed by the
+    /// resolved `[profiles]` and `[locked]` tables.
+    ///
+    /// # Errors
+    /// Returns an error if serialization fails.
+    pub fn to_toml(&self) -> Result<String, EngineError> {
+        let resolved = toml::to_string_pretty(&self.resolved).map_err(|source| {
+            EngineError::TomlSerialize {
+                what: "resolved configuration",
+                source,
+            }
+        })?;
+        Ok(format!("{}\n{resolved}", self.manifest.to_toml()?))
+    }
+
+    /// Render as pretty-printed JSON.
+    ///
+    /// # Errors
+    /// Returns an error if serialization fails.
+    pub fn to_json(&self) -> Result<String, EngineError> {
+        serde_json::to_string_pretty(self).map_err(|source| EngineError::JsonSerialize {
+            what: "resolved configuration",
+            source,
+        })
+    }
+}
+
+/// Load the project at `project_root` and resolve its effective configuration.
+/// Read-only: nothing is downloaded and `konvoy.lock` is not rewritten.
+///
+/// # Errors
+/// Returns an error if `konvoy.toml` or `konvoy.lock` cannot be read or parsed.
+pub fn effective_config(project_root: &Path) -> Result<EffectiveConfig, EngineError> {
+    let manifest = Manifest::from_path(&project_root.join("konvoy.toml"))?;
+    let lockfile = Lockfile::from_path(&project_root.join("konvoy.lock"))?;
+    Ok(resolve(manifest, &lockfile))
+}
+
+fn resolve(mut manifest: Manifest, lockfile: &Lockfile) -> EffectiveConfig {
+    let package = &mut manifest.package;
+    package.source_dirs = package
+        .effective_source_dirs()
+        .into_iter()
+        .map(str::to_owned)
+        .collect();
+    let stem = Some(package.artifact_stem().to_owned());
+    match package.kind {
+        PackageKind::Bin => package.bin_name = stem,
+        PackageKind::Lib | PackageKind::StaticLib | PackageKind::DynamicLib => {
+            package.lib_name = stem;
+        }
+    }
+    manifest.toolchain.jre = Some(
+        manifest
+            .toolchain
+            .jre
+            .unwrap_or(konvoy_konanc::toolchain::DEFAULT_JRE_MAJOR),
+    );
+
+    let profiles = [Profile::Debug, Profile::Release]
+        .into_iter()
+        .map(|profile| (profile.as_str().to_owned(), profile_settings(profile)))
+        .collect();
+    let locked = lockfile
+        .dependencies
+        .iter()
+        .map(|dep| (dep.name.clone(), locked_dependency(&dep.source)))
+        .collect();
+
+    EffectiveConfig {
+        manifest,
+        resolved: ResolvedSettings { profiles, locked },
+    }
+}
+
+fn profile_settings(profile: Profile) -> ProfileSettings {
+    let optimize = profile == Profile::Release;
+    ProfileSettings {
+        optimize,
+        konanc_flags: if optimize {
+            vec!["-opt".to_owned()]
+        } else {
+            Vec::new()
+        },
+    }
+}
+
+fn locked_dependency(source: &DepSource) -> LockedDependency {
+    let mut locked = LockedDependency {
+        source: String::new(),
+        version: None,
+        path: None,
+        commit: None,
+        required_by: Vec::new(),
+    };
+    match source {
+        DepSource::Path { path } => {
+            locked.source = "path".to_owned();
+            locked.path = Some(path.clone());
+        }
+        DepSource::Maven {
+            version,
+            required_by,
+            ..
+        } => {
+            locked.source = "maven".to_owned();
+            locked.version = Some(version.clone());
+            locked.required_by = required_by.clone();
+        }
+        DepSource::Git { commit, .. } => {
+            locked.source = "git".to_owned();
+            locked.commit = Some(commit.clone());
+        }
+    }
+    locked
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use konvoy_config::lockfile::{DependencyLock, HashAlgorithm};
+
+    fn manifest(content: &str) -> Manifest {
+        Manifest::from_str(content, "konvoy.toml").unwrap()
+    }
+
+    #[test]
+    fn omitted_defaults_are_made_explicit() {
+        let config = resolve(
+            manifest("[package]\nname = \"app\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n"),
+            &Lockfile::default(),
+        );
+        let package = &config.manifest.package;
+        assert_eq!(package.source_dirs, vec!["src"]);
+        assert_eq!(package.bin_name.as_deref(), Some("app"));
+        assert!(package.lib_name.is_none());
+        assert_eq!(config.manifest.toolchain.jre, Some(21));
+        assert!(config.resolved.locked.is_empty());
+
+        let toml = config.to_toml().unwrap();
+        assert!(toml.contains("source_dirs = [\"src\"]"), "{toml}");
+        assert!(toml.contains("jre = 21"), "{toml}");
+        assert!(toml.contains("[profiles.release]"), "{toml}");
+        assert!(!toml.contains("[locked"), "{toml}");
+    }
+
+    #[test]
+    fn explicit_settings_are_kept() {
+        let config = resolve(
+            manifest(
+                "[package]\nname = \"core\"\nkind = \"lib\"\nlib_name = \"corelib\"\n\n[toolchain]\nkotlin = \"2.1.0\"\njre = 17\n",
+            ),
+            &Lockfile::default(),
+        );
+        assert_eq!(config.manifest.package.lib_name.as_deref(), Some("corelib"));
+        assert!(config.manifest.package.bin_name.is_none());
+        assert_eq!(config.manifest.toolchain.jre, Some(17));
+    }
+
+    #[test]
+    fn locked_versions_and_profiles_appear_in_json() {
+        let mut lockfile = Lockfile::default();
+        lockfile.dependencies.push(DependencyLock {
+            name: "kotlinx-datetime".to_owned(),
+            source: DepSource::Maven {
+                version: "0.6.1".to_owned(),
+                maven: "org.jetbrains.kotlinx:kotlinx-datetime".to_owned(),
+                targets: BTreeMap::new(),
+                required_by: vec!["utils".to_owned()],
+                classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
+            },
+            source_hash: "abc".to_owned(),
+        });
+        let config = resolve(
+            manifest("[package]\nname = \"app\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n"),
+            &lockfile,
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&config.to_json().unwrap()).unwrap();
+        assert_eq!(json["package"]["name"], "app");
+        assert_eq!(json["profiles"]["release"]["konanc_flags"][0], "-opt");
+        assert_eq!(json["profiles"]["debug"]["optimize"], false);
+        let datetime = &json["locked"]["kotlinx-datetime"];
+        assert_eq!(datetime["source"], "maven");
+        assert_eq!(datetime["version"], "0.6.1");
+        assert_eq!(datetime["required_by"][0], "utils");
+    }
+}
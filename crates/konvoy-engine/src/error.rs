@@ -15,6 +15,18 @@ pub enum EngineError {
     #[error("konvoy.toml already exists at {path} — cannot initialize over an existing project")]
     ProjectExists { path: String },
 
+    /// `src/` already holds files, so scaffolding in place would overwrite them.
+    #[error("{path} is not empty — refusing to scaffold over existing sources")]
+    SourceDirNotEmpty { path: String },
+
+    /// `--template` named a template that does not exist.
+    #[error("unknown template `{name}` — valid templates: {valid}")]
+    UnknownTemplate { name: String, valid: String },
+
+    /// `git init` failed in a freshly scaffolded project.
+    #[error("cannot initialize a git repository in {path}: {message}")]
+    VcsInit { path: String, message: String },
+
     /// A Maven coordinate (`groupId:artifactId`) failed to parse.
     #[error("invalid maven coordinate `{coordinate}`: {reason}")]
     InvalidMavenCoordinate { coordinate: String, reason: String },
@@ -27,6 +39,14 @@ pub enum EngineError {
         source: toml::ser::Error,
     },
 
+    /// A JSON document (e.g. `.konvoy/timings.json`) failed to serialize.
+    #[error("failed to serialize {what}: {source}")]
+    JsonSerialize {
+        what: &'static str,
+        #[source]
+        source: serde_json::Error,
+    },
+
     /// A compiler operation failed.
     #[error("{0}")]
     Konanc(#[from] konvoy_konanc::error::KonancError),
@@ -47,9 +67,27 @@ pub enum EngineError {
     #[error("compilation failed with {error_count} error(s)")]
     CompilationFailed { error_count: usize },
 
-    /// A dependency cycle was detected.
-    #[error("dependency cycle detected: {cycle}")]
-    DependencyCycle { cycle: String },
+    /// Compilation succeeded with warnings while warnings are denied.
+    #[error("compilation produced {warning_count} warning(s) and warnings are denied — fix them, or drop `[build] deny_warnings` / `--deny-warnings`")]
+    WarningsDenied { warning_count: usize },
+
+    /// Under `--keep-going`, some path dependencies failed to build. `failed`
+    /// pairs each with its error; `skipped` lists those not built because a
+    /// dependency of theirs failed.
+    #[error("{}", dependencies_failed_message(failed, skipped))]
+    DependenciesFailed {
+        failed: Vec<(String, String)>,
+        skipped: Vec<String>,
+    },
+
+    /// A dependency cycle was detected. `manifests` lists the `konvoy.toml`
+    /// of each project on the cycle, in cycle order, so the offending
+    /// `[dependencies]` entry can be found.
+    #[error("dependency cycle detected: {cycle} (declared in {})", manifests.join(", "))]
+    DependencyCycle {
+        cycle: String,
+        manifests: Vec<String>,
+    },
 
     /// A dependency project was not found on disk.
     #[error("dependency `{name}` not found at {path}")]
@@ -71,6 +109,23 @@ pub enum EngineError {
     #[error("dependency `{name}` path escapes the project tree — resolved to {path}; use a relative path within the workspace")]
     DependencyPathEscape { name: String, path: String },
 
+    /// Two dependencies resolve to the same library, which konanc would be
+    /// given twice.
+    #[error("more than one dependency resolves to the library `{name}` — konanc would receive it twice; remove or rename one of them")]
+    DuplicateDependency { name: String },
+
+    /// A git dependency could not be fetched or checked out.
+    #[error("cannot fetch git dependency `{name}` from {url}: {message}")]
+    GitDependency {
+        name: String,
+        url: String,
+        message: String,
+    },
+
+    /// A git dependency is not checked out locally and --offline prevents fetching it.
+    #[error("git dependency `{name}` ({url}) is not checked out and --offline prevents fetching it — run `konvoy build` once without --offline, or drop --offline")]
+    GitDependencyOffline { name: String, url: String },
+
     /// A tarball hash in the lockfile does not match the freshly downloaded hash.
     #[error("{kind} tarball hash mismatch — expected {expected}, got {actual}; this may indicate a tampered or corrupted download — re-run with --force to re-download, or verify the hash against the upstream release")]
     TarballHashMismatch {
@@ -101,10 +156,47 @@ pub enum EngineError {
     #[error("no test source files found in {dir} — create test files in src/test/ using kotlin.test annotations")]
     NoTestSources { dir: String },
 
+    /// No benchmark source files found.
+    #[error("no benchmark source files found in {dir} — create benchmarks in src/bench/ with a `main` function in `package bench`")]
+    NoBenchSources { dir: String },
+
+    /// `--example` was used on a binary project.
+    #[error("project `{name}` is a binary — examples link against a library, set `kind = \"lib\"` in konvoy.toml or use `konvoy run` without --example")]
+    ExamplesRequireLib { name: String },
+
+    /// `--example` named an example that does not exist.
+    #[error("no example named `{name}` — available examples: {available}")]
+    UnknownExample { name: String, available: String },
+
+    /// The host cannot produce binaries for the requested `--target`.
+    #[error("cannot build for {target} on a {host} host — {reason}")]
+    CrossCompileUnsupported {
+        target: String,
+        host: String,
+        /// Why this host cannot build the target, and what to do instead.
+        reason: String,
+    },
+
+    /// The requested `--target` is recognized but Konvoy cannot build it yet.
+    #[error("target {target} is not supported yet — Windows builds are not implemented in konvoy; build for a Linux or macOS target instead")]
+    TargetNotYetSupported { target: String },
+
+    /// `--target all` was passed to a command that builds a single binary.
+    #[error("`--target all` is only supported by `konvoy build` — pass a single target such as `linux_x64`, or omit --target to use the host")]
+    AllTargetsUnsupported,
+
     /// Failed to download detekt.
     #[error("cannot download detekt {version}: {message}")]
     DetektDownload { version: String, message: String },
 
+    /// Downloading the detekt CLI JAR failed.
+    #[error("cannot download detekt {version} from {url}: {message} — check your network connection (and any HTTPS proxy), and that detekt {version} is a published release")]
+    DetektDownloadFailed {
+        version: String,
+        url: String,
+        message: String,
+    },
+
     /// The pinned detekt JAR is absent locally and --offline prevents fetching it.
     #[error("detekt {version} is not downloaded and --offline prevents downloads — run `konvoy lint` once without --offline, or drop --offline")]
     DetektJarOffline { version: String },
@@ -136,6 +228,42 @@ pub enum EngineError {
         actual: String,
     },
 
+    /// Failed to download ktlint.
+    #[error("cannot download ktlint {version}: {message}")]
+    KtlintDownload { version: String, message: String },
+
+    /// Downloading the ktlint JAR failed.
+    #[error("cannot download ktlint {version} from {url}: {message} — check your network connection (and any HTTPS proxy), and that ktlint {version} is a published release")]
+    KtlintDownloadFailed {
+        version: String,
+        url: String,
+        message: String,
+    },
+
+    /// The pinned ktlint JAR is absent locally and --offline prevents fetching it.
+    #[error("ktlint {version} is not downloaded and --offline prevents downloads — run `konvoy fmt` once without --offline, or drop --offline")]
+    KtlintJarOffline { version: String },
+
+    /// ktlint jar hash mismatch.
+    #[error("ktlint {version} jar hash mismatch — expected {expected}, got {actual}; this may indicate a tampered or corrupted download — delete ~/.konvoy/tools/ktlint/{version}/ and re-run `konvoy fmt` to re-download, or verify the hash at the ktlint release page")]
+    KtlintHashMismatch {
+        version: String,
+        expected: String,
+        actual: String,
+    },
+
+    /// `--features` named a feature the package does not declare.
+    #[error("package `{package}` has no feature `{name}` — declared features: {available}")]
+    UnknownFeature {
+        name: String,
+        package: String,
+        available: String,
+    },
+
+    /// Formatting not configured.
+    #[error("ktlint not configured — add `ktlint = \"1.3.1\"` to [toolchain] in konvoy.toml")]
+    FmtNotConfigured,
+
     /// Lint not configured.
     #[error("detekt not configured — add `detekt = \"1.23.7\"` to [toolchain] in konvoy.toml")]
     LintNotConfigured,
@@ -181,6 +309,14 @@ pub enum EngineError {
     #[error("invalid project name \"{name}\": {reason}")]
     InvalidProjectName { name: String, reason: String },
 
+    /// `konvoy lint --fix` would rewrite files with uncommitted changes.
+    #[error("refusing to run `konvoy lint --fix` with {count} uncommitted change(s) ({files}) — commit or stash them first, or pass --allow-dirty")]
+    LintFixDirty { count: usize, files: String },
+
+    /// `konvoy lint --fix` cannot tell whether the project has uncommitted changes.
+    #[error("{path} is not in a git working tree, so `konvoy lint --fix` cannot check for uncommitted changes — pass --allow-dirty to fix anyway")]
+    LintFixWithoutVcs { path: String },
+
     /// An explicit config file was not found on disk.
     #[error("config file not found: {path} — check the --config path or create the file")]
     ConfigNotFound { path: String },
@@ -248,6 +384,14 @@ pub enum EngineError {
         hint_version: String,
     },
 
+    /// No published version of a Maven artifact satisfies a dependency's range.
+    #[error("no published version of `{maven}` matches `{requirement}` for dependency `{name}` — widen the range in konvoy.toml, or pin an exact version")]
+    NoMatchingVersion {
+        name: String,
+        maven: String,
+        requirement: String,
+    },
+
     /// A cycle was detected during Maven transitive dependency resolution.
     #[error("maven dependency cycle detected: {cycle} — remove one of these dependencies from konvoy.toml or file an issue upstream")]
     MavenDependencyCycle { cycle: String },
@@ -258,6 +402,91 @@ pub enum EngineError {
     InternalInvariantViolated { context: String },
 }
 
+impl EngineError {
+    /// A stable, kebab-case identifier for this error, for callers that
+    /// handle errors programmatically instead of matching on the message.
+    ///
+    /// Codes never change once released; messages may. Compiler errors
+    /// report the code of the wrapped [`KonancError`](konvoy_konanc::error::KonancError).
+    pub fn code(&self) -> &'static str {
+        match self {
+            EngineError::Util(_) => "util",
+            EngineError::Manifest(_) => "manifest-invalid",
+            EngineError::ProjectExists { .. } => "project-exists",
+            EngineError::SourceDirNotEmpty { .. } => "source-dir-not-empty",
+            EngineError::UnknownTemplate { .. } => "unknown-template",
+            EngineError::VcsInit { .. } => "vcs-init",
+            EngineError::InvalidMavenCoordinate { .. } => "invalid-maven-coordinate",
+            EngineError::TomlSerialize { .. } => "toml-serialize",
+            EngineError::JsonSerialize { .. } => "json-serialize",
+            EngineError::Konanc(e) => e.code(),
+            EngineError::Target(_) => "target-invalid",
+            EngineError::Lockfile(_) => "lockfile-invalid",
+            EngineError::NoSources { .. } => "no-sources",
+            EngineError::CompilationFailed { .. } => "compilation-failed",
+            EngineError::WarningsDenied { .. } => "warnings-denied",
+            EngineError::DependenciesFailed { .. } => "dependencies-failed",
+            EngineError::DependencyCycle { .. } => "dependency-cycle",
+            EngineError::DependencyNotFound { .. } => "dependency-not-found",
+            EngineError::DependencyNotLib { .. } => "dependency-not-lib",
+            EngineError::DependencyToolchainMismatch { .. } => "dependency-toolchain-mismatch",
+            EngineError::DependencyPathEscape { .. } => "dependency-path-escape",
+            EngineError::DuplicateDependency { .. } => "duplicate-dependency",
+            EngineError::GitDependency { .. } => "git-dependency",
+            EngineError::GitDependencyOffline { .. } => "git-dependency-offline",
+            EngineError::TarballHashMismatch { .. } => "tarball-hash-mismatch",
+            EngineError::DependencyHashMismatch { .. } => "dependency-hash-mismatch",
+            EngineError::LockfileUpdateRequired => "lockfile-out-of-date",
+            EngineError::ToolchainOffline { .. } => "toolchain-offline",
+            EngineError::NoTestSources { .. } => "no-test-sources",
+            EngineError::NoBenchSources { .. } => "no-bench-sources",
+            EngineError::ExamplesRequireLib { .. } => "examples-require-lib",
+            EngineError::UnknownExample { .. } => "unknown-example",
+            EngineError::CrossCompileUnsupported { .. } => "cross-compile-unsupported",
+            EngineError::TargetNotYetSupported { .. } => "target-not-yet-supported",
+            EngineError::AllTargetsUnsupported => "all-targets-unsupported",
+            EngineError::DetektDownload { .. } => "detekt-download",
+            EngineError::DetektDownloadFailed { .. } => "detekt-download-failed",
+            EngineError::DetektJarOffline { .. } => "detekt-jar-offline",
+            EngineError::ToolExecFailed { .. } => "tool-exec-failed",
+            EngineError::ToolchainNoJre => "toolchain-no-jre",
+            EngineError::ToolchainJreOffline { .. } => "toolchain-jre-offline",
+            EngineError::DetektHashMismatch { .. } => "detekt-hash-mismatch",
+            EngineError::KtlintDownload { .. } => "ktlint-download",
+            EngineError::KtlintDownloadFailed { .. } => "ktlint-download-failed",
+            EngineError::KtlintJarOffline { .. } => "ktlint-jar-offline",
+            EngineError::KtlintHashMismatch { .. } => "ktlint-hash-mismatch",
+            EngineError::UnknownFeature { .. } => "unknown-feature",
+            EngineError::FmtNotConfigured => "fmt-not-configured",
+            EngineError::LintNotConfigured => "lint-not-configured",
+            EngineError::CodegenNotConfigured => "codegen-not-configured",
+            EngineError::CodegenInputNotFound { .. } => "codegen-input-not-found",
+            EngineError::CodegenInputDirNotFound { .. } => "codegen-input-dir-not-found",
+            EngineError::CodegenDownload { .. } => "codegen-download",
+            EngineError::CodegenHashMismatch { .. } => "codegen-hash-mismatch",
+            EngineError::CodegenFailed { .. } => "codegen-failed",
+            EngineError::CodegenToolOffline { .. } => "codegen-tool-offline",
+            EngineError::InvalidProjectName { .. } => "invalid-project-name",
+            EngineError::LintFixDirty { .. } => "lint-fix-dirty",
+            EngineError::LintFixWithoutVcs { .. } => "lint-fix-without-vcs",
+            EngineError::ConfigNotFound { .. } => "config-not-found",
+            EngineError::InvalidPluginConfig { .. } => "invalid-plugin-config",
+            EngineError::PluginOffline { .. } => "plugin-offline",
+            EngineError::PluginDownload { .. } => "plugin-download",
+            EngineError::PluginHashMismatch { .. } => "plugin-hash-mismatch",
+            EngineError::LibraryDownloadFailed { .. } => "library-download-failed",
+            EngineError::LibraryOffline { .. } => "library-offline",
+            EngineError::MissingLockfileEntry { .. } => "missing-lockfile-entry",
+            EngineError::MissingTargetHash { .. } => "missing-target-hash",
+            EngineError::LibraryHashMismatch { .. } => "library-hash-mismatch",
+            EngineError::MavenVersionConflict { .. } => "maven-version-conflict",
+            EngineError::NoMatchingVersion { .. } => "no-matching-version",
+            EngineError::MavenDependencyCycle { .. } => "maven-dependency-cycle",
+            EngineError::InternalInvariantViolated { .. } => "internal-invariant-violated",
+        }
+    }
+}
+
 /// Map a `UtilError` from artifact download/verify to an `EngineError`.
 ///
 /// Shared helper used by detekt, plugin, and library artifact pipelines.
@@ -274,6 +503,9 @@ pub(crate) fn map_artifact_download_err(
         konvoy_util::error::UtilError::Download { message } => {
             make_download(label.to_owned(), message)
         }
+        konvoy_util::error::UtilError::NotFound { url } => {
+            make_download(label.to_owned(), format!("{url} was not found (HTTP 404)"))
+        }
         konvoy_util::error::UtilError::ArtifactHashMismatch {
             expected, actual, ..
         } => make_hash(label.to_owned(), expected, actual),
@@ -281,8 +513,32 @@ pub(crate) fn map_artifact_download_err(
     }
 }
 
+/// The message of [`EngineError::DependenciesFailed`]: each failed
+/// dependency with its error, then the skipped ones.
+fn dependencies_failed_message(failed: &[(String, String)], skipped: &[String]) -> String {
+    let mut message = format!(
+        "{} path {} failed to build",
+        failed.len(),
+        if failed.len() == 1 {
+            "dependency"
+        } else {
+            "dependencies"
+        }
+    );
+    for (name, error) in failed {
+        message.push_str(&format!("\n  `{name}`: {error}"));
+    }
+    for name in skipped {
+        message.push_str(&format!("\n  `{name}`: skipped due to failed dependency"));
+    }
+    message
+}
+
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
+    use std::collections::BTreeSet;
+
     use super::*;
 
     #[test]
@@ -312,6 +568,21 @@ mod tests {
         assert!(msg.contains("expected `groupId:artifactId`"), "got: {msg}");
     }
 
+    #[test]
+    fn dependencies_failed_display_lists_failed_and_skipped() {
+        let err = EngineError::DependenciesFailed {
+            failed: vec![(
+                "core".to_owned(),
+                "compilation failed with 2 error(s)".to_owned(),
+            )],
+            skipped: vec!["app".to_owned()],
+        };
+        assert_eq!(
+            err.to_string(),
+            "1 path dependency failed to build\n  `core`: compilation failed with 2 error(s)\n  `app`: skipped due to failed dependency"
+        );
+    }
+
     #[test]
     fn toml_serialize_display_includes_what_and_source() {
         // Build a `toml::ser::Error` via a value that fails to serialize: TOML
@@ -348,4 +619,459 @@ mod tests {
         fn assert_send_sync<T: Send + Sync>() {}
         assert_send_sync::<EngineError>();
     }
+
+    fn s() -> String {
+        String::new()
+    }
+
+    /// One value of every variant, with the code it must keep.
+    fn every_variant() -> Vec<(EngineError, &'static str)> {
+        vec![
+            (
+                EngineError::Util(konvoy_util::error::UtilError::NoHomeDir),
+                "util",
+            ),
+            (
+                EngineError::Manifest(konvoy_config::manifest::ManifestError::EmptyName {
+                    path: s(),
+                }),
+                "manifest-invalid",
+            ),
+            (EngineError::ProjectExists { path: s() }, "project-exists"),
+            (
+                EngineError::SourceDirNotEmpty { path: s() },
+                "source-dir-not-empty",
+            ),
+            (
+                EngineError::UnknownTemplate {
+                    name: s(),
+                    valid: s(),
+                },
+                "unknown-template",
+            ),
+            (
+                EngineError::VcsInit {
+                    path: s(),
+                    message: s(),
+                },
+                "vcs-init",
+            ),
+            (
+                EngineError::InvalidMavenCoordinate {
+                    coordinate: s(),
+                    reason: s(),
+                },
+                "invalid-maven-coordinate",
+            ),
+            (
+                EngineError::TomlSerialize {
+                    what: "x",
+                    source: toml::to_string(&1_i64).unwrap_err(),
+                },
+                "toml-serialize",
+            ),
+            (
+                EngineError::JsonSerialize {
+                    what: "x",
+                    source: serde_json::from_str::<serde_json::Value>("").unwrap_err(),
+                },
+                "json-serialize",
+            ),
+            (
+                EngineError::Konanc(konvoy_konanc::error::KonancError::NotFound),
+                "konanc-not-found",
+            ),
+            (
+                EngineError::Target(konvoy_targets::TargetError::InvalidTarget { name: s() }),
+                "target-invalid",
+            ),
+            (
+                EngineError::Lockfile(konvoy_config::lockfile::LockfileError::NewerVersion {
+                    path: s(),
+                    found: 0,
+                    supported: 0,
+                }),
+                "lockfile-invalid",
+            ),
+            (EngineError::NoSources { dir: s() }, "no-sources"),
+            (
+                EngineError::CompilationFailed { error_count: 0 },
+                "compilation-failed",
+            ),
+            (
+                EngineError::WarningsDenied { warning_count: 0 },
+                "warnings-denied",
+            ),
+            (
+                EngineError::DependenciesFailed {
+                    failed: Vec::new(),
+                    skipped: Vec::new(),
+                },
+                "dependencies-failed",
+            ),
+            (
+                EngineError::DependencyCycle {
+                    cycle: s(),
+                    manifests: Vec::new(),
+                },
+                "dependency-cycle",
+            ),
+            (
+                EngineError::DependencyNotFound {
+                    name: s(),
+                    path: s(),
+                },
+                "dependency-not-found",
+            ),
+            (
+                EngineError::DependencyNotLib {
+                    name: s(),
+                    path: s(),
+                },
+                "dependency-not-lib",
+            ),
+            (
+                EngineError::DependencyToolchainMismatch {
+                    name: s(),
+                    dep_version: s(),
+                    root_version: s(),
+                },
+                "dependency-toolchain-mismatch",
+            ),
+            (
+                EngineError::DependencyPathEscape {
+                    name: s(),
+                    path: s(),
+                },
+                "dependency-path-escape",
+            ),
+            (
+                EngineError::DuplicateDependency { name: s() },
+                "duplicate-dependency",
+            ),
+            (
+                EngineError::GitDependency {
+                    name: s(),
+                    url: s(),
+                    message: s(),
+                },
+                "git-dependency",
+            ),
+            (
+                EngineError::GitDependencyOffline {
+                    name: s(),
+                    url: s(),
+                },
+                "git-dependency-offline",
+            ),
+            (
+                EngineError::TarballHashMismatch {
+                    kind: s(),
+                    expected: s(),
+                    actual: s(),
+                },
+                "tarball-hash-mismatch",
+            ),
+            (
+                EngineError::DependencyHashMismatch {
+                    name: s(),
+                    expected: s(),
+                    actual: s(),
+                },
+                "dependency-hash-mismatch",
+            ),
+            (EngineError::LockfileUpdateRequired, "lockfile-out-of-date"),
+            (
+                EngineError::ToolchainOffline { version: s() },
+                "toolchain-offline",
+            ),
+            (EngineError::NoTestSources { dir: s() }, "no-test-sources"),
+            (EngineError::NoBenchSources { dir: s() }, "no-bench-sources"),
+            (
+                EngineError::ExamplesRequireLib { name: s() },
+                "examples-require-lib",
+            ),
+            (
+                EngineError::UnknownExample {
+                    name: s(),
+                    available: s(),
+                },
+                "unknown-example",
+            ),
+            (
+                EngineError::CrossCompileUnsupported {
+                    target: s(),
+                    host: s(),
+                    reason: s(),
+                },
+                "cross-compile-unsupported",
+            ),
+            (
+                EngineError::TargetNotYetSupported { target: s() },
+                "target-not-yet-supported",
+            ),
+            (
+                EngineError::AllTargetsUnsupported,
+                "all-targets-unsupported",
+            ),
+            (
+                EngineError::DetektDownload {
+                    version: s(),
+                    message: s(),
+                },
+                "detekt-download",
+            ),
+            (
+                EngineError::DetektDownloadFailed {
+                    version: s(),
+                    url: s(),
+                    message: s(),
+                },
+                "detekt-download-failed",
+            ),
+            (
+                EngineError::DetektJarOffline { version: s() },
+                "detekt-jar-offline",
+            ),
+            (
+                EngineError::ToolExecFailed {
+                    tool: s(),
+                    message: s(),
+                },
+                "tool-exec-failed",
+            ),
+            (EngineError::ToolchainNoJre, "toolchain-no-jre"),
+            (
+                EngineError::ToolchainJreOffline { version: s() },
+                "toolchain-jre-offline",
+            ),
+            (
+                EngineError::DetektHashMismatch {
+                    version: s(),
+                    expected: s(),
+                    actual: s(),
+                },
+                "detekt-hash-mismatch",
+            ),
+            (
+                EngineError::KtlintDownload {
+                    version: s(),
+                    message: s(),
+                },
+                "ktlint-download",
+            ),
+            (
+                EngineError::KtlintDownloadFailed {
+                    version: s(),
+                    url: s(),
+                    message: s(),
+                },
+                "ktlint-download-failed",
+            ),
+            (
+                EngineError::KtlintJarOffline { version: s() },
+                "ktlint-jar-offline",
+            ),
+            (
+                EngineError::KtlintHashMismatch {
+                    version: s(),
+                    expected: s(),
+                    actual: s(),
+                },
+                "ktlint-hash-mismatch",
+            ),
+            (
+                EngineError::UnknownFeature {
+                    name: s(),
+                    package: s(),
+                    available: s(),
+                },
+                "unknown-feature",
+            ),
+            (EngineError::FmtNotConfigured, "fmt-not-configured"),
+            (EngineError::LintNotConfigured, "lint-not-configured"),
+            (EngineError::CodegenNotConfigured, "codegen-not-configured"),
+            (
+                EngineError::CodegenInputNotFound {
+                    name: s(),
+                    path: s(),
+                },
+                "codegen-input-not-found",
+            ),
+            (
+                EngineError::CodegenInputDirNotFound {
+                    name: s(),
+                    path: s(),
+                },
+                "codegen-input-dir-not-found",
+            ),
+            (
+                EngineError::CodegenDownload {
+                    name: s(),
+                    version: s(),
+                    message: s(),
+                },
+                "codegen-download",
+            ),
+            (
+                EngineError::CodegenHashMismatch {
+                    name: s(),
+                    version: s(),
+                    expected: s(),
+                    actual: s(),
+                },
+                "codegen-hash-mismatch",
+            ),
+            (
+                EngineError::CodegenFailed {
+                    name: s(),
+                    message: s(),
+                },
+                "codegen-failed",
+            ),
+            (
+                EngineError::CodegenToolOffline {
+                    name: s(),
+                    version: s(),
+                },
+                "codegen-tool-offline",
+            ),
+            (
+                EngineError::InvalidProjectName {
+                    name: s(),
+                    reason: s(),
+                },
+                "invalid-project-name",
+            ),
+            (
+                EngineError::LintFixDirty {
+                    count: 0,
+                    files: s(),
+                },
+                "lint-fix-dirty",
+            ),
+            (
+                EngineError::LintFixWithoutVcs { path: s() },
+                "lint-fix-without-vcs",
+            ),
+            (
+                EngineError::ConfigNotFound { path: s() },
+                "config-not-found",
+            ),
+            (
+                EngineError::InvalidPluginConfig {
+                    name: s(),
+                    reason: s(),
+                },
+                "invalid-plugin-config",
+            ),
+            (EngineError::PluginOffline { name: s() }, "plugin-offline"),
+            (
+                EngineError::PluginDownload {
+                    name: s(),
+                    message: s(),
+                },
+                "plugin-download",
+            ),
+            (
+                EngineError::PluginHashMismatch {
+                    name: s(),
+                    expected: s(),
+                    actual: s(),
+                },
+                "plugin-hash-mismatch",
+            ),
+            (
+                EngineError::LibraryDownloadFailed {
+                    name: s(),
+                    url: s(),
+                    message: s(),
+                },
+                "library-download-failed",
+            ),
+            (EngineError::LibraryOffline { name: s() }, "library-offline"),
+            (
+                EngineError::MissingLockfileEntry { name: s() },
+                "missing-lockfile-entry",
+            ),
+            (
+                EngineError::MissingTargetHash {
+                    name: s(),
+                    target: s(),
+                },
+                "missing-target-hash",
+            ),
+            (
+                EngineError::LibraryHashMismatch {
+                    name: s(),
+                    expected: s(),
+                    actual: s(),
+                },
+                "library-hash-mismatch",
+            ),
+            (
+                EngineError::MavenVersionConflict {
+                    maven: s(),
+                    details: s(),
+                    hint_name: s(),
+                    hint_version: s(),
+                },
+                "maven-version-conflict",
+            ),
+            (
+                EngineError::NoMatchingVersion {
+                    name: s(),
+                    maven: s(),
+                    requirement: s(),
+                },
+                "no-matching-version",
+            ),
+            (
+                EngineError::MavenDependencyCycle { cycle: s() },
+                "maven-dependency-cycle",
+            ),
+            (
+                EngineError::InternalInvariantViolated { context: s() },
+                "internal-invariant-violated",
+            ),
+        ]
+    }
+
+    #[test]
+    fn codes_are_stable() {
+        for (err, code) in every_variant() {
+            assert_eq!(err.code(), code, "{err:?}");
+        }
+    }
+
+    #[test]
+    fn codes_are_distinct_kebab_case() {
+        let mut seen = BTreeSet::new();
+        for (err, code) in every_variant() {
+            assert!(
+                code.split('-')
+                    .all(|w| !w.is_empty() && w.chars().all(|c| c.is_ascii_lowercase())),
+                "{code}"
+            );
+            // The konanc wrapper reports the inner error's code.
+            if !matches!(err, EngineError::Konanc(_)) {
+                assert!(seen.insert(code), "duplicate code {code}");
+            }
+        }
+    }
+
+    #[test]
+    fn every_code_is_explained() {
+        for (_, code) in every_variant() {
+            assert!(crate::explain::explain(code).is_some(), "{code}");
+        }
+    }
+
+    #[test]
+    fn konanc_errors_report_their_own_code() {
+        let err = EngineError::Konanc(konvoy_konanc::error::KonancError::CompilationFailed {
+            error_count: 1,
+        });
+        assert_eq!(err.code(), "compilation-failed");
+    }
 }
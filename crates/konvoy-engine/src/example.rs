@@ -0,0 +1,417 @@
+//! Runnable examples: each `examples/<name>.kt` that declares a `main` is
+//! compiled into its own binary, linked against the project's library output.
+
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use konvoy_config::manifest::PackageKind;
+use konvoy_konanc::invoke::ProduceKind;
+use konvoy_util::ignore::IgnoreRules;
+
+use crate::artifact::BuildMetadata;
+use crate::build::{
+    build_single, resolve_build_context, BuildOptions, BuildOutcome, BuildResult, CompileContext,
+    LibraryInput,
+};
+use crate::cache::{CacheInputs, CacheKey};
+use crate::common::now_epoch_secs;
+use crate::error::EngineError;
+
+/// The directory holding a project's examples.
+fn examples_dir(project_root: &Path) -> PathBuf {
+    project_root.join("examples")
+}
+
+/// Names of the project's runnable examples, sorted: every top-level
+/// `examples/<name>.kt` that declares a `main` function.
+///
+/// Returns an empty list when there is no `examples/` directory.
+///
+/// # Errors
+/// Returns an error if the directory or an example file cannot be read.
+pub fn list_examples(project_root: &Path) -> Result<Vec<String>, EngineError> {
+    let dir = examples_dir(project_root);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let ignore = IgnoreRules::load(project_root)?;
+    let entries = std::fs::read_dir(&dir).map_err(|source| konvoy_util::error::UtilError::Io {
+        path: dir.display().to_string(),
+        source,
+    })?;
+
+    let mut names = Vec::new();
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        if !path.is_file()
+            || path.extension().is_none_or(|ext| ext != "kt")
+            || ignore.is_ignored(&path)
+        {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if !is_example_name(name) {
+            continue;
+        }
+        let content = konvoy_util::fs::read_file(&path)?;
+        if declares_main(&String::from_utf8_lossy(&content)) {
+            names.push(name.to_owned());
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Example names are file stems made of ASCII letters, digits, `-`, and `_`,
+/// so they are safe both as glob patterns and as binary names.
+fn is_example_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Whether a Kotlin source declares a top-level `main` function.
+fn declares_main(content: &str) -> bool {
+    content
+        .lines()
+        .map(str::trim_start)
+        .any(|line| line.starts_with("fun main(") || line.starts_with("fun main ("))
+}
+
+/// Build the example `name`: the project's library first (as a normal build
+/// would), then `examples/<name>.kt` linked against it into
+/// `.konvoy/build/<target>/<profile>/examples/<name>`.
+///
+/// The example binary is cached like a regular build, keyed on the example
+/// source plus the hash of the library output and every dependency klib.
+///
+/// # Errors
+/// Returns an error if the project is not a library, `name` is not one of the
+/// project's examples, or either compilation fails.
+pub fn build_example(
+    project_root: &Path,
+    name: &str,
+    options: &BuildOptions,
+    resolver: crate::common::ArtifactResolver<'_>,
+) -> Result<BuildResult, EngineError> {
+    let start = Instant::now();
+
+    // Validate the request before resolving the toolchain, so a typo'd name
+    // fails fast (and offline) with the list of what exists.
+    let manifest = konvoy_config::Manifest::from_path(&project_root.join("konvoy.toml"))?;
+    if manifest.package.kind != PackageKind::Lib {
+        return Err(EngineError::ExamplesRequireLib {
+            name: manifest.package.name,
+        });
+    }
+    let available = list_examples(project_root)?;
+    if !available.iter().any(|e| e == name) {
+        return Err(EngineError::UnknownExample {
+            name: name.to_owned(),
+            available: if available.is_empty() {
+                "none — add a `.kt` file with a `main` function under examples/".to_owned()
+            } else {
+                available.join(", ")
+            },
+        });
+    }
+
+    let mut ctx = resolve_build_context(project_root, options, resolver)?;
+
+    // The library the example links against, built (or reused) exactly as
+    // `konvoy build` would.
+    let cc = CompileContext {
+        konanc: &ctx.konanc,
+        jre_home: ctx.jre_home.as_deref(),
+        target: &ctx.target,
+        options,
+        library_inputs: &ctx.library_inputs,
+        timeout: ctx.compile_timeout,
+        deny_warnings: ctx.deny_warnings,
+        compress_cache: ctx.compress_cache,
+        features: &ctx.features,
+        command_log: None,
+    };
+    let (lib_path, _) = build_single(
+        project_root,
+        &ctx.manifest,
+        &cc,
+        ctx.profile,
+        &ctx.lockfile_content,
+    )?;
+    ctx.timer.mark("root compile");
+
+    let mut library_inputs = ctx.library_inputs.clone();
+    library_inputs.push(LibraryInput::unhashed(lib_path));
+    let dependency_hashes = library_inputs
+        .iter()
+        .map(|lib| match &lib.precomputed_sha256 {
+            Some(h) => Ok(h.clone()),
+            None => konvoy_util::hash::sha256_file(&lib.path).map_err(EngineError::from),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Key on the example file alone (the glob selects it within examples/);
+    // the library's content is covered by its klib hash above. The lockfile
+    // content is tagged so an example never shares a key with the library.
+    let cache_inputs = CacheInputs {
+        manifest_content: crate::build::manifest_key_content(&ctx.manifest)?,
+        lockfile_content: format!("{}\n# konvoy-example-build: {name}\n", ctx.lockfile_content),
+        konanc_version: ctx.konanc.version.clone(),
+        konanc_fingerprint: ctx.konanc.fingerprint.clone(),
+        target: ctx.target,
+        profile: ctx.profile,
+        source_dirs: vec![examples_dir(project_root)],
+        source_glob: format!("{name}.kt"),
+        ignore: IgnoreRules::load(project_root)?,
+        os: std::env::consts::OS.to_owned(),
+        arch: std::env::consts::ARCH.to_owned(),
+        dependency_hashes,
+        codegen_hashes: Vec::new(),
+        features: Vec::new(),
+        deny_warnings: ctx.deny_warnings,
+    };
+    let cache_key = CacheKey::compute(&cache_inputs)?;
+    konvoy_util::hash_cache::persist();
+
+    let output_path = crate::build::build_output_path(
+        project_root,
+        options.target_dir.as_deref(),
+        "examples",
+        &ctx.target,
+        ctx.profile,
+    )
+    .join(name);
+
+    if crate::build::reusable_cache_entry(
+        &ctx.store,
+        &cache_key,
+        &[name],
+        &format!("example {name}"),
+        options,
+    )? {
+        if !options.quiet {
+            options.progress.on_cache_hit(&format!("example {name}"));
+        }
+        ctx.store.materialize(&cache_key, name, &output_path)?;
+        ctx.timer.mark("example compile");
+        return Ok(BuildResult {
+            outcome: BuildOutcome::Cached,
+            artifact_size: crate::build::artifact_size(&output_path)?,
+            header_path: None,
+            output_path,
+            duration: start.elapsed(),
+            timings: ctx.timer.into_phases(),
+            dependency_outcomes: ctx.dependency_outcomes,
+        });
+    }
+
+    if !options.quiet {
+        options
+            .progress
+            .on_compile_start(&format!("example {name}"), &output_path);
+    }
+    let example_cc = CompileContext {
+        library_inputs: &library_inputs,
+        ..cc
+    };
+    let source = examples_dir(project_root).join(format!("{name}.kt"));
+    let compile_output = crate::build::compile(
+        &example_cc,
+        &[source],
+        &output_path,
+        ProduceKind::Program,
+        &ctx.plugin_jars,
+        &ctx.plugin_options,
+        &ctx.manifest.toolchain,
+    )?;
+
+    let metadata = BuildMetadata {
+        target: ctx.target,
+        profile: ctx.profile,
+        konanc_version: ctx.konanc.version.clone(),
+        built_at: now_epoch_secs(),
+    };
+    ctx.store.store(&cache_key, &compile_output, &metadata)?;
+    ctx.timer.mark("example compile");
+
+    Ok(BuildResult {
+        outcome: BuildOutcome::Fresh,
+        artifact_size: crate::build::artifact_size(&output_path)?,
+        header_path: None,
+        output_path,
+        duration: start.elapsed(),
+        timings: ctx.timer.into_phases(),
+        dependency_outcomes: ctx.dependency_outcomes,
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn list_examples_without_directory_is_empty() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(list_examples(tmp.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn list_examples_finds_files_with_main() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("examples");
+        write(
+            &dir.join("hello.kt"),
+            "fun main() {\n    println(\"hi\")\n}\n",
+        );
+        write(
+            &dir.join("args.kt"),
+            "fun main(args: Array<String>) = println(args.size)\n",
+        );
+        write(&dir.join("helpers.kt"), "fun helper() = 1\n");
+        write(&dir.join("notes.md"), "fun main() {}\n");
+        write(&dir.join("nested").join("deep.kt"), "fun main() {}\n");
+
+        assert_eq!(list_examples(tmp.path()).unwrap(), vec!["args", "hello"]);
+    }
+
+    #[test]
+    fn list_examples_respects_konvoyignore() {
+        let tmp = tempfile::tempdir().unwrap();
+        write(
+            &tmp.path().join("examples").join("wip.kt"),
+            "fun main() {}\n",
+        );
+        write(
+            &tmp.path().join("examples").join("done.kt"),
+            "fun main() {}\n",
+        );
+        write(&tmp.path().join(".konvoyignore"), "examples/wip.kt\n");
+
+        assert_eq!(list_examples(tmp.path()).unwrap(), vec!["done"]);
+    }
+
+    fn options() -> BuildOptions {
+        BuildOptions {
+            target: None,
+            profile: konvoy_config::Profile::Debug,
+            verbose: false,
+            quiet: false,
+            force: false,
+            timeout: None,
+            dedupe_diagnostics: false,
+            color: konvoy_util::color::ColorChoice::Never,
+            timings: false,
+            features: Vec::new(),
+            no_default_features: false,
+            deny_warnings: false,
+            emit_command: None,
+            out_dir: None,
+            target_dir: None,
+            keep_going: false,
+            progress: std::sync::Arc::new(crate::progress::StderrProgress),
+        }
+    }
+
+    fn project(kind: &str) -> tempfile::TempDir {
+        let tmp = tempfile::tempdir().unwrap();
+        write(
+            &tmp.path().join("konvoy.toml"),
+            &format!(
+                "[package]\nname = \"mylib\"\nkind = \"{kind}\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n"
+            ),
+        );
+        write(
+            &tmp.path().join("src").join("lib.kt"),
+            "fun greet() = \"hi\"\n",
+        );
+        tmp
+    }
+
+    #[test]
+    fn build_example_rejects_binary_project() {
+        let tmp = project("bin");
+        write(
+            &tmp.path().join("examples").join("hello.kt"),
+            "fun main() {}\n",
+        );
+
+        let err = build_example(
+            tmp.path(),
+            "hello",
+            &options(),
+            crate::common::test_resolver(true, false),
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, EngineError::ExamplesRequireLib { .. }),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn build_example_unknown_name_lists_available() {
+        let tmp = project("lib");
+        write(
+            &tmp.path().join("examples").join("hello.kt"),
+            "fun main() {}\n",
+        );
+        write(
+            &tmp.path().join("examples").join("server.kt"),
+            "fun main() {}\n",
+        );
+
+        let err = build_example(
+            tmp.path(),
+            "helo",
+            &options(),
+            crate::common::test_resolver(true, false),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("no example named `helo`"), "got: {err}");
+        assert!(err.contains("hello, server"), "got: {err}");
+    }
+
+    #[test]
+    fn build_example_without_examples_explains_layout() {
+        let tmp = project("lib");
+
+        let err = build_example(
+            tmp.path(),
+            "hello",
+            &options(),
+            crate::common::test_resolver(true, false),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("under examples/"), "got: {err}");
+    }
+
+    #[test]
+    fn declares_main_ignores_other_functions() {
+        assert!(declares_main("fun main() {}"));
+        assert!(declares_main("  fun main(args: Array<String>) {}"));
+        assert!(!declares_main("fun mainly() {}"));
+        assert!(!declares_main("// no entry point"));
+    }
+
+    #[test]
+    fn example_names_are_restricted() {
+        assert!(is_example_name("hello_world-2"));
+        assert!(!is_example_name("a b"));
+        assert!(!is_example_name("glob*"));
+        assert!(!is_example_name(""));
+    }
+}
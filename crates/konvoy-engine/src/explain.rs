@@ -0,0 +1,503 @@
+//! `konvoy explain`: a longer description and a suggested fix for each stable
+//! error code reported by [`EngineError::code`](crate::EngineError::code) and
+//! [`KonancError::code`](konvoy_konanc::error::KonancError::code).
+
+/// What an error code means and how to get past it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Explanation {
+    /// The stable, kebab-case code, e.g. `lockfile-out-of-date`.
+    pub code: &'static str,
+    /// What went wrong and why Konvoy reports it.
+    pub description: &'static str,
+    /// What to do about it.
+    pub fix: &'static str,
+}
+
+/// The explanation of `code`, if it is a known error code.
+#[must_use]
+pub fn explain(code: &str) -> Option<&'static Explanation> {
+    let code = code.trim();
+    EXPLANATIONS
+        .iter()
+        .find(|e| e.code.eq_ignore_ascii_case(code))
+}
+
+/// Every explained error code, sorted by code.
+pub const EXPLANATIONS: &[Explanation] = &[
+    Explanation {
+        code: "all-targets-unsupported",
+        description: "`--target all` builds the project once per target konvoy.toml lists. Only `konvoy build` does that; run, test, and bench produce a single binary to execute, so they need one target.",
+        fix: "Pass a single target such as `--target linux_x64`, or omit --target to use the host.",
+    },
+    Explanation {
+        code: "codegen-download",
+        description: "A code generation tool (such as the Fabrikt JAR for [codegen.openapi]) could not be downloaded to ~/.konvoy/tools/.",
+        fix: "Check the network connection and any HTTPS proxy, and that the configured tool version is a published release.",
+    },
+    Explanation {
+        code: "codegen-failed",
+        description: "A code generator ran but failed, usually because the input spec is invalid or uses a construct the generator does not support. The generator's own output explains the problem.",
+        fix: "Fix the input spec the generator complains about, then run `konvoy generate` again.",
+    },
+    Explanation {
+        code: "codegen-hash-mismatch",
+        description: "A cached code generation tool no longer matches the SHA-256 pinned in konvoy.lock under [[codegen_tools]]. The file was corrupted on disk, or the download was tampered with.",
+        fix: "Delete ~/.konvoy/tools/<name>/<version>/ and run `konvoy build` (or `konvoy generate`) to download it again. If it keeps happening, check the artifact upstream.",
+    },
+    Explanation {
+        code: "codegen-input-dir-not-found",
+        description: "A directory listed in `extra_spec_dirs` of a [codegen] section does not exist.",
+        fix: "Create the directory, or fix the path in `extra_spec_dirs`. Paths are relative to the project root.",
+    },
+    Explanation {
+        code: "codegen-input-not-found",
+        description: "The input file a [codegen] section names, such as the OpenAPI `spec`, does not exist.",
+        fix: "Create the file, or fix its path in konvoy.toml. Paths are relative to the project root.",
+    },
+    Explanation {
+        code: "codegen-not-configured",
+        description: "`konvoy generate` was run in a project without any code generator in konvoy.toml.",
+        fix: "Add a generator, e.g. a [codegen.openapi] section with `version` and `spec`.",
+    },
+    Explanation {
+        code: "codegen-tool-offline",
+        description: "A code generation tool is pinned but not downloaded yet, and --offline (or `offline` in ~/.konvoy/config.toml) forbids fetching it.",
+        fix: "Run `konvoy build` or `konvoy generate` once without --offline, or drop --offline.",
+    },
+    Explanation {
+        code: "compilation-failed",
+        description: "konanc reported errors in the project's or a dependency's sources. The diagnostics are printed above the error.",
+        fix: "Fix the reported errors and build again. `--verbose` shows the full konanc command line.",
+    },
+    Explanation {
+        code: "compilation-timeout",
+        description: "konanc ran longer than the configured timeout and was killed. The last lines it printed are shown with the error. Large projects, release builds, and a cold toolchain cache can take longer than expected.",
+        fix: "Raise `timeout_secs` under [build] in konvoy.toml, or pass a larger `--timeout` for one run.",
+    },
+    Explanation {
+        code: "config-not-found",
+        description: "A configuration file passed explicitly, e.g. a detekt config with --config, does not exist.",
+        fix: "Check the path given to --config, or create the file.",
+    },
+    Explanation {
+        code: "cross-compile-unsupported",
+        description: "The host cannot build the requested target. The error says why, e.g. Apple targets link against the macOS SDK, which only exists on macOS, so a Linux host cannot build them.",
+        fix: "Build on a host that can produce the target (a macOS machine for Apple targets), or pick a target this host can build.",
+    },
+    Explanation {
+        code: "dependencies-failed",
+        description: "With `--keep-going`, path dependencies that failed were set aside while the rest kept building. The error lists each failed dependency with its own error, and the dependencies skipped because something they depend on failed.",
+        fix: "Fix the listed failures (the diagnostics are printed above the error) and build again. Skipped dependencies build once what they depend on does.",
+    },
+    Explanation {
+        code: "dependency-cycle",
+        description: "Path or git dependencies depend on each other in a loop, so no build order exists. The error lists the projects on the cycle and the konvoy.toml files that declare its edges.",
+        fix: "Remove one of the [dependencies] entries on the cycle, e.g. by moving shared code into a separate library both can depend on.",
+    },
+    Explanation {
+        code: "dependency-hash-mismatch",
+        description: "Under --locked, a path or git dependency's sources no longer match the `source_hash` recorded in konvoy.lock. The sources changed since the lockfile was written.",
+        fix: "If the change is expected, build once without --locked to record the new hash and commit konvoy.lock. Otherwise find out who changed the dependency's sources.",
+    },
+    Explanation {
+        code: "dependency-not-found",
+        description: "A path dependency's directory does not exist or holds no konvoy.toml. Paths are resolved relative to the manifest that declares them.",
+        fix: "Fix the `path` in [dependencies], or create the dependency project there.",
+    },
+    Explanation {
+        code: "dependency-not-lib",
+        description: "Only libraries can be dependencies, but the project a dependency points at has `kind = \"bin\"`.",
+        fix: "Set `kind = \"lib\"` in the dependency's konvoy.toml, or move the shared code into a library project.",
+    },
+    Explanation {
+        code: "dependency-path-escape",
+        description: "A path dependency resolves to a directory outside the project tree. Konvoy refuses such paths so a build cannot read arbitrary directories.",
+        fix: "Use a relative path within the workspace, or turn the dependency into a git dependency.",
+    },
+    Explanation {
+        code: "dependency-toolchain-mismatch",
+        description: "Every project in a build must use the same Kotlin/Native version, because klibs are not compatible across compiler versions. A dependency's [toolchain] `kotlin` differs from the root project's.",
+        fix: "Set the same `kotlin` version in the dependency's and the root project's konvoy.toml. If the dependency is known to build with the root's version, set `allow_toolchain_mismatch = true` on its entry to build it anyway with a warning.",
+    },
+    Explanation {
+        code: "detekt-download",
+        description: "detekt could not be set up for `konvoy lint`.",
+        fix: "Check the network connection and the `detekt` version in [toolchain], then run `konvoy lint` again.",
+    },
+    Explanation {
+        code: "detekt-download-failed",
+        description: "Downloading the detekt-cli JAR failed.",
+        fix: "Check the network connection and any HTTPS proxy, and that the `detekt` version in [toolchain] is a published release.",
+    },
+    Explanation {
+        code: "detekt-hash-mismatch",
+        description: "The cached detekt JAR does not match the SHA-256 pinned in konvoy.lock. The file was corrupted on disk, or the download was tampered with.",
+        fix: "Delete ~/.konvoy/tools/detekt/<version>/ and run `konvoy lint` to download it again, or check the hash against the detekt release page.",
+    },
+    Explanation {
+        code: "detekt-jar-offline",
+        description: "detekt is configured but not downloaded yet, and --offline forbids fetching it.",
+        fix: "Run `konvoy lint` once without --offline, or drop --offline.",
+    },
+    Explanation {
+        code: "duplicate-dependency",
+        description: "Two dependencies would hand konanc the same library: a path or git dependency whose name matches a Maven dependency's key or artifact id, or a manifest declaring the same Maven coordinate twice.",
+        fix: "Remove one of the two entries, or rename the path or git dependency's library.",
+    },
+    Explanation {
+        code: "examples-require-lib",
+        description: "Examples in `examples/` link against the project's library, so `--example` only works in a project with `kind = \"lib\"`.",
+        fix: "Set `kind = \"lib\"` in konvoy.toml, or run the binary with `konvoy run` and no --example.",
+    },
+    Explanation {
+        code: "fmt-not-configured",
+        description: "`konvoy fmt` runs ktlint, which is opt-in per project.",
+        fix: "Add a ktlint version to [toolchain] in konvoy.toml, e.g. `ktlint = \"1.3.1\"`.",
+    },
+    Explanation {
+        code: "git-dependency",
+        description: "A git dependency could not be fetched or checked out. The URL may be wrong, the `rev` may not exist, git may be missing, or the fetched commit may not match the one pinned in konvoy.lock.",
+        fix: "Check `git` and `rev` in konvoy.toml and that `git` is installed and can reach the repository. If the pin is stale, run without --locked to re-pin it.",
+    },
+    Explanation {
+        code: "git-dependency-offline",
+        description: "A git dependency has not been checked out into ~/.konvoy/cache/git/ yet, and --offline forbids fetching it.",
+        fix: "Run `konvoy build` once without --offline, or drop --offline.",
+    },
+    Explanation {
+        code: "internal-invariant-violated",
+        description: "Konvoy reached a state its own code should rule out. This is a bug in Konvoy, not in the project.",
+        fix: "Please file a bug report with the full error message and, if possible, the konvoy.toml and konvoy.lock that trigger it.",
+    },
+    Explanation {
+        code: "invalid-maven-coordinate",
+        description: "A `maven` value is not a `groupId:artifactId` coordinate.",
+        fix: "Write the coordinate as `groupId:artifactId`, e.g. \"org.jetbrains.kotlinx:kotlinx-coroutines-core\", and put the version in `version`.",
+    },
+    Explanation {
+        code: "invalid-plugin-config",
+        description: "A [plugins] entry is malformed: a plugin must use `maven` and `version`, cannot be optional, and its `options` need an `id` to pass them under.",
+        fix: "Fix the entry as the error describes. See the Plugins section of the README for the accepted keys.",
+    },
+    Explanation {
+        code: "invalid-project-name",
+        description: "The name given to `konvoy init` or `konvoy new` cannot be a package name.",
+        fix: "Use ASCII letters, digits, `-`, and `_`, starting with a letter or underscore. `konvoy new` takes the directory's name unless --name is given.",
+    },
+    Explanation {
+        code: "jre-install-failed",
+        description: "The JRE bundled with the managed Kotlin/Native toolchain could not be installed or found. JVM tools such as detekt and code generators run on it.",
+        fix: "Run `konvoy toolchain install` again. If it keeps failing, remove ~/.konvoy/toolchains/<version>/ first.",
+    },
+    Explanation {
+        code: "json-serialize",
+        description: "Konvoy could not serialize one of its own JSON files, such as .konvoy/timings.json. This should not happen.",
+        fix: "Please file a bug report with the full error message.",
+    },
+    Explanation {
+        code: "konanc-exec-failed",
+        description: "The konanc process could not be started, e.g. because the file is not a valid executable for this machine.",
+        fix: "Run `konvoy toolchain which` to see which konanc is used, then reinstall it with `konvoy toolchain install` or fix KONVOY_KONANC.",
+    },
+    Explanation {
+        code: "konanc-fingerprint-failed",
+        description: "Konvoy hashes the konanc launcher to key its build cache and could not read it.",
+        fix: "Check the permissions of the konanc file `konvoy toolchain which` prints, or reinstall the toolchain.",
+    },
+    Explanation {
+        code: "konanc-not-executable",
+        description: "konanc was found but lacks execute permission.",
+        fix: "Make it executable (`chmod +x`), or reinstall the toolchain with `konvoy toolchain install`.",
+    },
+    Explanation {
+        code: "konanc-not-found",
+        description: "No Kotlin/Native compiler was found.",
+        fix: "Run `konvoy toolchain install` to install the version konvoy.toml asks for, or point KONVOY_KONANC at a konanc binary.",
+    },
+    Explanation {
+        code: "konanc-override-not-found",
+        description: "KONVOY_KONANC names a file that does not exist.",
+        fix: "Point KONVOY_KONANC at a konanc binary, or unset it to use the managed toolchain.",
+    },
+    Explanation {
+        code: "konanc-version-unparsable",
+        description: "`konanc -version` printed something Konvoy cannot read a version from, so the binary is probably not konanc.",
+        fix: "Check the binary KONVOY_KONANC points at, or unset it to use the managed toolchain.",
+    },
+    Explanation {
+        code: "ktlint-download",
+        description: "ktlint could not be set up for `konvoy fmt`.",
+        fix: "Check the network connection and the `ktlint` version in [toolchain], then run `konvoy fmt` again.",
+    },
+    Explanation {
+        code: "ktlint-download-failed",
+        description: "Downloading the ktlint JAR failed.",
+        fix: "Check the network connection and any HTTPS proxy, and that the `ktlint` version in [toolchain] is a published release.",
+    },
+    Explanation {
+        code: "ktlint-hash-mismatch",
+        description: "The cached ktlint JAR does not match its pinned SHA-256. The file was corrupted on disk, or the download was tampered with.",
+        fix: "Delete ~/.konvoy/tools/ktlint/<version>/ and run `konvoy fmt` to download it again, or check the hash against the ktlint release page.",
+    },
+    Explanation {
+        code: "ktlint-jar-offline",
+        description: "ktlint is configured but not downloaded yet, and --offline forbids fetching it.",
+        fix: "Run `konvoy fmt` once without --offline, or drop --offline.",
+    },
+    Explanation {
+        code: "library-download-failed",
+        description: "A Maven dependency's klib could not be downloaded from Maven Central or the configured `maven_mirror`.",
+        fix: "Check the network connection and any HTTPS proxy. If the artifact has no klib for this target, pick another version or target.",
+    },
+    Explanation {
+        code: "library-hash-mismatch",
+        description: "A freshly downloaded Maven klib does not match the hash pinned in konvoy.lock. Either the download was intercepted or corrupted in transit, or the upstream artifact was republished and the pin is stale.",
+        fix: "Run `konvoy update` to re-resolve and re-hash all Maven dependencies. If it keeps happening, check the network for interference.",
+    },
+    Explanation {
+        code: "library-offline",
+        description: "A Maven dependency's klib is pinned in konvoy.lock but not downloaded yet, and --offline forbids fetching it.",
+        fix: "Run `konvoy build` once without --offline, or drop --offline.",
+    },
+    Explanation {
+        code: "lint-fix-dirty",
+        description: "`konvoy lint --fix` rewrites sources in place. It refuses to when the git working tree has uncommitted changes, because those could not be told apart from detekt's fixes or restored.",
+        fix: "Commit or stash the changes first, or pass --allow-dirty.",
+    },
+    Explanation {
+        code: "lint-fix-without-vcs",
+        description: "`konvoy lint --fix` checks for uncommitted changes before rewriting sources, and cannot do so outside a git working tree.",
+        fix: "Put the project under git, or pass --allow-dirty to fix anyway.",
+    },
+    Explanation {
+        code: "lint-not-configured",
+        description: "`konvoy lint` runs detekt, which is opt-in per project.",
+        fix: "Add a detekt version to [toolchain] in konvoy.toml, e.g. `detekt = \"1.23.7\"`.",
+    },
+    Explanation {
+        code: "lockfile-invalid",
+        description: "konvoy.lock cannot be read: it is not valid TOML, has an unexpected shape, or was written by a newer Konvoy with a lockfile `version` this one does not understand.",
+        fix: "Resolve any merge conflict markers in konvoy.lock. For a newer lockfile, upgrade Konvoy. As a last resort, delete konvoy.lock and run `konvoy update` and `konvoy build` to regenerate it.",
+    },
+    Explanation {
+        code: "lockfile-out-of-date",
+        description: "--locked forbids any change to konvoy.lock, and this build would have to change it: a pin is missing or no longer matches konvoy.toml, e.g. after changing the Kotlin version, adding a dependency or plugin, or editing a path dependency. --locked is meant for CI, where the committed lockfile must already describe the build exactly. Pinned artifacts are still downloaded under --locked; only changes to the lockfile are refused.",
+        fix: "Run the same command without --locked (or `konvoy update` for Maven dependencies) to regenerate konvoy.lock, then commit it. In CI, make sure the committed konvoy.lock is current.",
+    },
+    Explanation {
+        code: "manifest-invalid",
+        description: "konvoy.toml is not valid: a TOML syntax error, an unknown key, or a value Konvoy rejects. The error names the file and the offending key.",
+        fix: "Fix the reported key. `konvoy check` lists every problem in konvoy.toml with its line and column.",
+    },
+    Explanation {
+        code: "maven-dependency-cycle",
+        description: "Resolving Maven dependencies through their POMs ran into artifacts that depend on each other in a loop.",
+        fix: "Remove one of the dependencies on the cycle from konvoy.toml, or report the cycle to the library's maintainers.",
+    },
+    Explanation {
+        code: "maven-version-conflict",
+        description: "Two dependencies require incompatible versions of the same Maven artifact, and Konvoy will not pick one silently.",
+        fix: "Add the artifact to [dependencies] with an explicit version both sides accept; the error suggests an entry.",
+    },
+    Explanation {
+        code: "missing-lockfile-entry",
+        description: "A Maven dependency in konvoy.toml has no entry in konvoy.lock yet. Resolving it needs the network, which --offline forbids.",
+        fix: "Run `konvoy update` with network access, then commit konvoy.lock.",
+    },
+    Explanation {
+        code: "missing-target-hash",
+        description: "konvoy.lock pins a Maven dependency but has no klib hash for the target being built, e.g. after adding a target.",
+        fix: "Run `konvoy update` to resolve and hash the dependency for every target.",
+    },
+    Explanation {
+        code: "no-bench-sources",
+        description: "`konvoy bench` found no benchmark sources.",
+        fix: "Add benchmarks under src/bench/ with a `main` function in `package bench`.",
+    },
+    Explanation {
+        code: "no-matching-version",
+        description: "No published version of a Maven artifact satisfies the version range in konvoy.toml. Ranges never match pre-release versions.",
+        fix: "Widen the range, or pin an exact version (including a pre-release).",
+    },
+    Explanation {
+        code: "no-output",
+        description: "konanc was invoked without an output path. This indicates a bug in Konvoy.",
+        fix: "Please file a bug report with the full error message.",
+    },
+    Explanation {
+        code: "no-sources",
+        description: "The project's source directories hold no .kt files, after .konvoyignore and the test and benchmark directories are excluded.",
+        fix: "Add sources under src/ (or the directories `source_dirs` lists), or check .konvoyignore.",
+    },
+    Explanation {
+        code: "no-test-sources",
+        description: "`konvoy test` found no test sources.",
+        fix: "Add tests under src/test/ (or `test_dir`) using kotlin.test annotations.",
+    },
+    Explanation {
+        code: "platform-toolchain-missing",
+        description: "konanc needs a platform toolchain that is not installed, such as the macOS Command Line Tools.",
+        fix: "Run the command the error suggests, e.g. `xcode-select --install`, then build again.",
+    },
+    Explanation {
+        code: "plugin-download",
+        description: "A compiler plugin JAR could not be downloaded from Maven Central or the configured `maven_mirror`.",
+        fix: "Check the network connection and any HTTPS proxy, and that the plugin's `maven` and `version` name a published artifact.",
+    },
+    Explanation {
+        code: "plugin-hash-mismatch",
+        description: "A compiler plugin JAR does not match the SHA-256 pinned in konvoy.lock. The file was corrupted on disk, or the download was tampered with.",
+        fix: "Delete the cached JAR under ~/.konvoy/cache/maven/ and build again to download it.",
+    },
+    Explanation {
+        code: "plugin-offline",
+        description: "A compiler plugin is pinned but not downloaded yet, and --offline forbids fetching it.",
+        fix: "Run `konvoy build` once without --offline, or drop --offline.",
+    },
+    Explanation {
+        code: "project-exists",
+        description: "`konvoy init` or `konvoy new` found a konvoy.toml where the project would go, and will not overwrite an existing project.",
+        fix: "Pick another name with `konvoy init --name <name>`, or run `konvoy new` in a directory without a konvoy.toml.",
+    },
+    Explanation {
+        code: "source-dir-not-empty",
+        description: "`konvoy new` sets up the current directory and would write into a src/ directory that already holds files.",
+        fix: "Move the existing sources aside, or create the project in a new directory with `konvoy init --name <name>`.",
+    },
+    Explanation {
+        code: "tarball-hash-mismatch",
+        description: "A downloaded toolchain tarball does not match the SHA-256 pinned in konvoy.lock. The download was corrupted or tampered with, or the pin is wrong.",
+        fix: "Re-run with --force to download it again, or check the hash against the upstream release.",
+    },
+    Explanation {
+        code: "target-invalid",
+        description: "A --target value, or a target listed in konvoy.toml, is not a Kotlin/Native target Konvoy knows.",
+        fix: "Use a target such as `linux_x64`, `linux_arm64`, `macos_x64`, or `macos_arm64`, or `host`.",
+    },
+    Explanation {
+        code: "target-not-yet-supported",
+        description: "The target is recognized, but Konvoy cannot build it yet. Windows builds are not implemented.",
+        fix: "Build for a Linux or macOS target on a Linux or macOS machine.",
+    },
+    Explanation {
+        code: "toml-serialize",
+        description: "Konvoy could not serialize one of its own TOML files, such as konvoy.lock or a cache metadata file. This should not happen.",
+        fix: "Please file a bug report with the full error message.",
+    },
+    Explanation {
+        code: "tool-exec-failed",
+        description: "A JVM tool such as detekt, ktlint, or a code generator could not be started on the bundled JRE.",
+        fix: "Run `konvoy doctor` to check the toolchain and its JRE, and `konvoy toolchain install` to repair them.",
+    },
+    Explanation {
+        code: "toolchain-corrupt",
+        description: "The managed Kotlin/Native installation is incomplete or damaged, e.g. after an interrupted install.",
+        fix: "Run `konvoy toolchain install <version>` to reinstall it.",
+    },
+    Explanation {
+        code: "toolchain-download-failed",
+        description: "The Kotlin/Native tarball could not be downloaded from the JetBrains releases or the configured toolchain mirror.",
+        fix: "Check the network connection and any HTTPS proxy, and that the `kotlin` version in konvoy.toml is released. If the download stalled on a slow link, raise KONVOY_DOWNLOAD_IDLE_TIMEOUT (seconds, default 60).",
+    },
+    Explanation {
+        code: "toolchain-extract-failed",
+        description: "The downloaded Kotlin/Native tarball could not be unpacked, e.g. because the download was truncated or the disk is full.",
+        fix: "Check the free space under ~/.konvoy, then run `konvoy toolchain install` again.",
+    },
+    Explanation {
+        code: "toolchain-fingerprint-mismatch",
+        description: "The installed Kotlin/Native no longer matches the konanc fingerprint recorded in konvoy.lock. The install was modified or only partially extracted.",
+        fix: "Remove the toolchain directory named in the error and run `konvoy toolchain install <version>`.",
+    },
+    Explanation {
+        code: "toolchain-io",
+        description: "A file under ~/.konvoy/toolchains/ could not be read or written while managing toolchains.",
+        fix: "Check the permissions and free space of ~/.konvoy.",
+    },
+    Explanation {
+        code: "toolchain-jre-offline",
+        description: "A JVM tool needs the JRE bundled with the Kotlin/Native toolchain, which is not installed, and --offline forbids downloading it.",
+        fix: "Run `konvoy toolchain install` first, or drop --offline.",
+    },
+    Explanation {
+        code: "toolchain-no-jre",
+        description: "The installed Kotlin/Native toolchain has no bundled JRE, which JVM tools such as detekt and code generators run on.",
+        fix: "Run `konvoy toolchain install` to install the toolchain with its JRE.",
+    },
+    Explanation {
+        code: "toolchain-not-on-mirror",
+        description: "The configured toolchain mirror does not host the requested Kotlin/Native version for this platform.",
+        fix: "Upload the tarball to the mirror, or install without it by unsetting KONVOY_TOOLCHAIN_MIRROR and `toolchain_mirror` in ~/.konvoy/config.toml.",
+    },
+    Explanation {
+        code: "toolchain-offline",
+        description: "The Kotlin/Native version konvoy.toml asks for is not installed, and --offline forbids downloading it.",
+        fix: "Run `konvoy toolchain install` first, or drop --offline.",
+    },
+    Explanation {
+        code: "toolchain-path-traversal",
+        description: "The Kotlin/Native tarball contains an entry that would be written outside the install directory. Konvoy stops rather than extract it, since the tarball may have been tampered with.",
+        fix: "Check where the tarball came from, especially when a toolchain mirror is configured.",
+    },
+    Explanation {
+        code: "toolchain-version-mismatch",
+        description: "The konanc in use reports a different Kotlin/Native version than konvoy.toml asks for.",
+        fix: "Run `konvoy toolchain install` for the requested version, or update `kotlin` in konvoy.toml. If KONVOY_KONANC is set, point it at the matching version.",
+    },
+    Explanation {
+        code: "unknown-example",
+        description: "`--example` named an example that is not in examples/.",
+        fix: "Use one of the example names the error lists.",
+    },
+    Explanation {
+        code: "unknown-feature",
+        description: "`--features` named a feature the package does not declare in [features] and that is not an optional dependency.",
+        fix: "Use one of the declared features the error lists, or declare the feature in konvoy.toml.",
+    },
+    Explanation {
+        code: "unknown-template",
+        description: "`--template` named a project template that does not exist.",
+        fix: "Use one of the templates the error lists.",
+    },
+    Explanation {
+        code: "unsupported-platform",
+        description: "JetBrains publishes no prebuilt Kotlin/Native for this operating system and architecture, so the toolchain cannot be managed.",
+        fix: "Build on a supported platform, or install Kotlin/Native yourself and point KONVOY_KONANC at it.",
+    },
+    Explanation {
+        code: "util",
+        description: "A file system, hashing, or network operation failed. The message names the file or URL involved.",
+        fix: "Check the named path's permissions and free space, or the network connection for a URL.",
+    },
+    Explanation {
+        code: "vcs-init",
+        description: "The project was created, but `git init` failed in it.",
+        fix: "Check that `git` is installed, or pass `--vcs none`.",
+    },
+    Explanation {
+        code: "warnings-denied",
+        description: "The build compiled, but konanc reported warnings while `[build] deny_warnings` or `--deny-warnings` is set.",
+        fix: "Fix the warnings printed above, or drop `deny_warnings` / `--deny-warnings`.",
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_are_sorted_and_distinct() {
+        for pair in EXPLANATIONS.windows(2) {
+            if let [a, b] = pair {
+                assert!(a.code < b.code, "{} must sort before {}", a.code, b.code);
+            }
+        }
+    }
+
+    #[test]
+    fn lookup_ignores_case_and_whitespace() {
+        let explanation = explain(" Lockfile-Out-Of-Date\n").map(|e| e.code);
+        assert_eq!(explanation, Some("lockfile-out-of-date"));
+        assert!(explain("no-such-code").is_none());
+    }
+}
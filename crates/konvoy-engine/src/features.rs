@@ -0,0 +1,198 @@
+//! Feature selection: which of a package's `[features]` a build enables, and
+//! the source sets they contribute.
+//!
+//! Kotlin/Native has no preprocessor, so a feature is a source set: enabling
+//! `json` compiles `features/json/**/*.kt` alongside the main sources. Shared
+//! code declares `expect` APIs, and each feature directory supplies the
+//! matching `actual`s.
+//!
+//! An `optional = true` dependency is also a feature, named after it: it is
+//! only resolved and linked when enabled.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use konvoy_config::manifest::DEFAULT_FEATURES_KEY;
+use konvoy_config::Manifest;
+
+use crate::error::EngineError;
+
+/// Directory (relative to the project root) holding one source set per feature.
+pub(crate) const FEATURES_DIR: &str = "features";
+
+/// Resolve the features enabled for `manifest`: the `requested` ones, plus the
+/// `default` set unless `default_features` is false, plus everything those
+/// enable in turn. Sorted; never contains `default` itself. Enabled optional
+/// dependencies are included by name.
+///
+/// # Errors
+/// Returns [`EngineError::UnknownFeature`] if a requested feature is neither
+/// declared in `[features]` nor an optional dependency.
+pub(crate) fn resolve_features(
+    manifest: &Manifest,
+    requested: &[String],
+    default_features: bool,
+) -> Result<Vec<String>, EngineError> {
+    let mut pending: Vec<&str> = Vec::new();
+    for name in requested {
+        if name != DEFAULT_FEATURES_KEY
+            && !manifest.features.contains_key(name)
+            && !is_optional_dependency(manifest, name)
+        {
+            return Err(EngineError::UnknownFeature {
+                name: name.clone(),
+                package: manifest.package.name.clone(),
+                available: declared_features(manifest),
+            });
+        }
+        pending.push(name);
+    }
+    if default_features {
+        pending.push(DEFAULT_FEATURES_KEY);
+    }
+
+    let mut enabled = BTreeSet::new();
+    while let Some(name) = pending.pop() {
+        if name != DEFAULT_FEATURES_KEY && !enabled.insert(name.to_owned()) {
+            continue;
+        }
+        if let Some(implied) = manifest.features.get(name) {
+            pending.extend(implied.iter().map(String::as_str));
+        }
+    }
+    Ok(enabled.into_iter().collect())
+}
+
+/// Whether `name` is a dependency of `manifest` marked `optional = true`.
+fn is_optional_dependency(manifest: &Manifest, name: &str) -> bool {
+    manifest
+        .dependencies
+        .get(name)
+        .is_some_and(|spec| spec.optional)
+}
+
+/// Whether the dependency `name` of `manifest` is part of a build with
+/// `features` enabled: a regular dependency always is, an optional one only
+/// when enabled.
+pub(crate) fn dependency_enabled(manifest: &Manifest, name: &str, features: &[String]) -> bool {
+    !is_optional_dependency(manifest, name) || features.iter().any(|f| f == name)
+}
+
+/// Comma-separated declared feature and optional dependency names, for error
+/// messages.
+fn declared_features(manifest: &Manifest) -> String {
+    let optional_deps = manifest
+        .dependencies
+        .iter()
+        .filter(|(_, spec)| spec.optional)
+        .map(|(name, _)| name);
+    let names: BTreeSet<&str> = manifest
+        .features
+        .keys()
+        .chain(optional_deps)
+        .map(String::as_str)
+        .filter(|name| *name != DEFAULT_FEATURES_KEY)
+        .collect();
+    let names: Vec<&str> = names.into_iter().collect();
+    if names.is_empty() {
+        "none".to_owned()
+    } else {
+        names.join(", ")
+    }
+}
+
+/// The existing source directories of the enabled `features` under `project_root`.
+pub(crate) fn feature_source_dirs(project_root: &Path, features: &[String]) -> Vec<PathBuf> {
+    features
+        .iter()
+        .map(|name| project_root.join(FEATURES_DIR).join(name))
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn manifest(features: &str) -> Manifest {
+        Manifest::from_str(
+            &format!(
+                "[package]\nname = \"demo\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n\n[features]\n{features}"
+            ),
+            "konvoy.toml",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn defaults_are_enabled_unless_opted_out() {
+        let m = manifest("default = [\"json\"]\njson = []\nmetrics = []\n");
+        assert_eq!(resolve_features(&m, &[], true).unwrap(), vec!["json"]);
+        assert!(resolve_features(&m, &[], false).unwrap().is_empty());
+        assert_eq!(
+            resolve_features(&m, &["metrics".to_owned()], false).unwrap(),
+            vec!["metrics"]
+        );
+    }
+
+    #[test]
+    fn implied_features_are_enabled_transitively() {
+        let m = manifest("full = [\"metrics\"]\nmetrics = [\"json\"]\njson = []\n");
+        assert_eq!(
+            resolve_features(&m, &["full".to_owned()], true).unwrap(),
+            vec!["full", "json", "metrics"]
+        );
+    }
+
+    #[test]
+    fn mutually_implied_features_terminate() {
+        let m = manifest("a = [\"b\"]\nb = [\"a\"]\n");
+        assert_eq!(
+            resolve_features(&m, &["a".to_owned()], true).unwrap(),
+            vec!["a", "b"]
+        );
+    }
+
+    #[test]
+    fn unknown_feature_lists_the_declared_ones() {
+        let m = manifest("default = []\njson = []\nmetrics = []\n");
+        let err = resolve_features(&m, &["jsn".to_owned()], true).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("`jsn`"), "got: {msg}");
+        assert!(msg.contains("json, metrics"), "got: {msg}");
+    }
+
+    #[test]
+    fn optional_dependencies_are_enabled_like_features() {
+        let m = Manifest::from_str(
+            "[package]\nname = \"demo\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n\n\
+             [features]\nmetrics = [\"prom\"]\n\n\
+             [dependencies]\nprom = { path = \"../prom\", optional = true }\nutil = { path = \"../util\" }\n",
+            "konvoy.toml",
+        )
+        .unwrap();
+        let none = resolve_features(&m, &[], true).unwrap();
+        assert!(!dependency_enabled(&m, "prom", &none));
+        assert!(dependency_enabled(&m, "util", &none));
+
+        let direct = resolve_features(&m, &["prom".to_owned()], true).unwrap();
+        assert_eq!(direct, vec!["prom"]);
+        assert!(dependency_enabled(&m, "prom", &direct));
+
+        let implied = resolve_features(&m, &["metrics".to_owned()], true).unwrap();
+        assert_eq!(implied, vec!["metrics", "prom"]);
+        assert!(dependency_enabled(&m, "prom", &implied));
+
+        let err = resolve_features(&m, &["util".to_owned()], true).unwrap_err();
+        assert!(err.to_string().contains("metrics, prom"), "{err}");
+    }
+
+    #[test]
+    fn feature_source_dirs_skip_missing_directories() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp.path().join("features").join("json")).unwrap();
+        let dirs = feature_source_dirs(tmp.path(), &["json".to_owned(), "metrics".to_owned()]);
+        assert_eq!(dirs, vec![tmp.path().join("features").join("json")]);
+    }
+}
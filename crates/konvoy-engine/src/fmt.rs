@@ -0,0 +1,414 @@
+//! ktlint tool management and the `fmt` command.
+//!
+//! Downloads the ktlint CLI JAR from GitHub releases and runs it in `--format`
+//! mode over the project's Kotlin sources, using the JRE bundled with managed
+//! toolchains — the same way `detekt.rs` runs detekt.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use crate::detekt::{changed_files, snapshot_files};
+use crate::error::EngineError;
+use crate::managed_tool::ManagedToolSpec;
+
+/// Map a `UtilError` from artifact operations to the corresponding `EngineError`.
+fn map_download_err(version: &str, e: konvoy_util::error::UtilError) -> EngineError {
+    crate::error::map_artifact_download_err(
+        version,
+        e,
+        |version, message| EngineError::KtlintDownloadFailed {
+            url: ktlint_download_url(&version),
+            version,
+            message,
+        },
+        |version, expected, actual| EngineError::KtlintHashMismatch {
+            version,
+            expected,
+            actual,
+        },
+    )
+}
+
+/// Options for the `fmt` command.
+#[derive(Debug, Clone)]
+pub struct FmtOptions {
+    /// Whether to show raw ktlint output.
+    pub verbose: bool,
+    /// Suppress the `Formatting with ktlint` progress line.
+    pub quiet: bool,
+    /// Report the files that would change instead of rewriting them.
+    pub check: bool,
+}
+
+/// Result of running the formatter.
+#[derive(Debug, Clone)]
+pub struct FmtResult {
+    /// Source files that were rewritten — or, with `check`, that would be — sorted.
+    pub changed_files: Vec<PathBuf>,
+    /// Whether ktlint fixed everything it found. `false` means some violations
+    /// cannot be corrected automatically; `raw_output` lists them.
+    pub fully_formatted: bool,
+    /// Raw stdout and stderr from ktlint.
+    pub raw_output: String,
+}
+
+/// The managed-JAR-tool spec for a ktlint version — a GitHub release downloaded
+/// into `~/.konvoy/tools/ktlint/<version>/`.
+///
+/// The release asset is a self-executing JAR (a shell stub in front of the zip),
+/// which `java -jar` runs as-is.
+fn ktlint_tool(version: &str) -> ManagedToolSpec {
+    ManagedToolSpec::direct_url(
+        "ktlint",
+        "ktlint",
+        version,
+        ktlint_download_url(version),
+        format!("ktlint-{version}.jar"),
+    )
+}
+
+/// Construct the download URL for a ktlint release.
+pub(crate) fn ktlint_download_url(version: &str) -> String {
+    format!("https://github.com/pinterest/ktlint/releases/download/{version}/ktlint")
+}
+
+/// Check if ktlint is already downloaded for a given version.
+///
+/// # Errors
+/// Returns an error if the home directory cannot be determined.
+pub fn is_installed(version: &str) -> Result<bool, EngineError> {
+    ktlint_tool(version)
+        .is_installed()
+        .map_err(EngineError::from)
+}
+
+/// Download ktlint if not already present, returning the path to the JAR and
+/// its SHA-256.
+///
+/// If `expected_sha256` is `Some`, the downloaded (or existing) JAR is verified
+/// against it.
+///
+/// # Errors
+/// Returns an error if the version is invalid, the download fails, the hash
+/// doesn't match, or the home directory cannot be determined.
+pub fn ensure_ktlint(
+    version: &str,
+    expected_sha256: Option<&str>,
+    resolver: crate::common::ArtifactResolver<'_>,
+) -> Result<(PathBuf, String), EngineError> {
+    konvoy_util::artifact::validate_identifier(version).map_err(|_| EngineError::KtlintDownload {
+        version: version.to_owned(),
+        message: format!(
+            "invalid ktlint version \"{version}\" — only alphanumeric characters, dots, hyphens, and underscores are allowed, and it cannot be `..`"
+        ),
+    })?;
+
+    resolver
+        .ensure_managed_tool(&ktlint_tool(version), expected_sha256)
+        .map_err(|e| map_download_err(version, e))
+}
+
+/// Persist the ktlint version and JAR hash into the lockfile.
+///
+/// # Errors
+/// Returns an error if the lockfile cannot be written.
+fn persist_ktlint_hash(
+    lockfile_path: &Path,
+    lockfile: konvoy_config::lockfile::Lockfile,
+    toolchain: &konvoy_config::manifest::Toolchain,
+    ktlint_version: &str,
+    hash: String,
+) -> Result<(), EngineError> {
+    let mut updated = lockfile;
+    if let Some(ref mut tc) = updated.toolchain {
+        tc.ktlint_version = Some(ktlint_version.to_owned());
+        tc.ktlint_jar_sha256 = Some(hash);
+    } else {
+        updated.toolchain = Some(konvoy_config::lockfile::ToolchainLock {
+            konanc_version: toolchain.kotlin.clone(),
+            konanc_tarball_sha256: None,
+            konanc_tarball_url: None,
+            jre_tarball_sha256: None,
+            jre_major: crate::common::recorded_jre_major(crate::common::jre_major(toolchain)),
+            konanc_fingerprint: None,
+            detekt_version: None,
+            detekt_jar_sha256: None,
+            ktlint_version: Some(ktlint_version.to_owned()),
+            ktlint_jar_sha256: Some(hash),
+        });
+    }
+    updated.write_to(lockfile_path)?;
+    Ok(())
+}
+
+/// Build the ktlint argument list: format every given file in place.
+fn ktlint_args(files: &[&Path]) -> Vec<OsString> {
+    let mut args: Vec<OsString> = vec!["--format".into()];
+    args.extend(files.iter().map(|f| f.as_os_str().to_owned()));
+    args
+}
+
+/// Copy `sources` from under `root` into `dest`, keeping their paths relative
+/// to `root`, along with any `.editorconfig` in the directories between `root`
+/// and each file. Returns the copies, in the order of `sources`.
+fn copy_sources(
+    root: &Path,
+    sources: &[PathBuf],
+    dest: &Path,
+) -> Result<Vec<PathBuf>, EngineError> {
+    let mut editorconfigs = std::collections::BTreeSet::new();
+    let mut copies = Vec::with_capacity(sources.len());
+    for path in sources {
+        let Ok(relative) = path.strip_prefix(root) else {
+            continue;
+        };
+        let target = dest.join(relative);
+        if let Some(parent) = target.parent() {
+            konvoy_util::fs::ensure_dir(parent)?;
+        }
+        konvoy_util::fs::copy_file(path, &target)?;
+        copies.push(target);
+
+        for dir in relative.ancestors().skip(1) {
+            if dir.as_os_str().is_empty() {
+                break;
+            }
+            let editorconfig = dir.join(".editorconfig");
+            if root.join(&editorconfig).is_file() {
+                editorconfigs.insert(editorconfig);
+            }
+        }
+    }
+    for editorconfig in editorconfigs {
+        konvoy_util::fs::copy_file(&root.join(&editorconfig), &dest.join(&editorconfig))?;
+    }
+    Ok(copies)
+}
+
+/// Run ktlint in format mode over `sources`, returning the rewritten files and
+/// ktlint's outcome.
+fn format_sources(
+    jre_home: &Path,
+    sources: &[PathBuf],
+    version: &str,
+    verbose: bool,
+) -> Result<(Vec<PathBuf>, bool, String), EngineError> {
+    let before = snapshot_files(sources)?;
+    if before.is_empty() {
+        return Ok((Vec::new(), true, String::new()));
+    }
+    let files: Vec<&Path> = before.keys().map(PathBuf::as_path).collect();
+    let output = ktlint_tool(version).run(Some(jre_home), &ktlint_args(&files), verbose)?;
+    let changed = changed_files(&before, &snapshot_files(sources)?);
+    let raw_output = format!("{}\n{}", output.stdout, output.stderr);
+    Ok((changed, output.success, raw_output))
+}
+
+/// Format a project's Kotlin sources with ktlint.
+///
+/// Formats the same `.kt` files the build compiles (`source_dirs`, minus
+/// `.konvoyignore`), plus the test sources.
+///
+/// With `options.check`, ktlint runs on a scratch copy of them (kept under
+/// `.konvoy/` so the project's `.editorconfig` still applies) and the result
+/// lists the files that would change, mapped back into the project.
+///
+/// # Errors
+/// Returns an error if ktlint is not configured, cannot be downloaded, the JRE
+/// is unavailable, or the ktlint process fails to execute.
+pub fn fmt(
+    root: &Path,
+    options: &FmtOptions,
+    resolver: crate::common::ArtifactResolver<'_>,
+) -> Result<FmtResult, EngineError> {
+    let manifest = konvoy_config::Manifest::from_path(&root.join("konvoy.toml"))?;
+
+    let ktlint_version = manifest
+        .toolchain
+        .ktlint
+        .as_deref()
+        .ok_or(EngineError::FmtNotConfigured)?;
+
+    let lockfile_path = root.join("konvoy.lock");
+    let lockfile = konvoy_config::lockfile::Lockfile::from_path(&lockfile_path)?;
+    resolver.require_manifest_artifacts_resolvable(&manifest, &lockfile)?;
+
+    let ktlint_hash_to_persist = resolver.resolve_ktlint_jar(ktlint_version, &lockfile)?;
+
+    // Resolve the JRE before persisting, so a failure leaves konvoy.lock untouched.
+    let jre_home = resolver.resolve_jre(
+        &manifest.toolchain.kotlin,
+        crate::common::jre_major(&manifest.toolchain),
+        &lockfile,
+    )?;
+
+    if let Some(actual_sha256) = ktlint_hash_to_persist {
+        persist_ktlint_hash(
+            &lockfile_path,
+            lockfile,
+            &manifest.toolchain,
+            ktlint_version,
+            actual_sha256,
+        )?;
+    }
+
+    let sources = crate::sources::project_sources(root, &manifest)?;
+    if sources.is_empty() {
+        eprintln!("    warning: no Kotlin sources to format");
+        return Ok(FmtResult {
+            changed_files: Vec::new(),
+            fully_formatted: true,
+            raw_output: String::new(),
+        });
+    }
+
+    if !options.quiet {
+        eprintln!("    Formatting with ktlint {ktlint_version}...");
+    }
+
+    if !options.check {
+        let (changed_files, fully_formatted, raw_output) =
+            format_sources(&jre_home, &sources, ktlint_version, options.verbose)?;
+        return Ok(FmtResult {
+            changed_files,
+            fully_formatted,
+            raw_output,
+        });
+    }
+
+    let konvoy_dir = root.join(".konvoy");
+    konvoy_util::fs::ensure_dir(&konvoy_dir)?;
+    let staging = tempfile::Builder::new()
+        .prefix(".tmp-fmt-")
+        .tempdir_in(&konvoy_dir)
+        .map_err(|source| {
+            EngineError::from(konvoy_util::error::UtilError::Io {
+                path: konvoy_dir.display().to_string(),
+                source,
+            })
+        })?;
+    let scratch = copy_sources(root, &sources, staging.path())?;
+    let (changed, fully_formatted, raw_output) =
+        format_sources(&jre_home, &scratch, ktlint_version, options.verbose)?;
+    let changed_files = changed
+        .iter()
+        .filter_map(|path| path.strip_prefix(staging.path()).ok())
+        .map(|relative| root.join(relative))
+        .collect();
+    Ok(FmtResult {
+        changed_files,
+        fully_formatted,
+        raw_output,
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ktlint_download_url_points_at_release_asset() {
+        assert_eq!(
+            ktlint_download_url("1.3.1"),
+            "https://github.com/pinterest/ktlint/releases/download/1.3.1/ktlint"
+        );
+    }
+
+    #[test]
+    fn ktlint_args_format_each_file() {
+        let a = Path::new("/p/src/a.kt");
+        let b = Path::new("/p/src/b.kts");
+        let args = ktlint_args(&[a, b]);
+        assert_eq!(
+            args,
+            vec![
+                OsString::from("--format"),
+                OsString::from("/p/src/a.kt"),
+                OsString::from("/p/src/b.kts"),
+            ]
+        );
+    }
+
+    #[test]
+    fn copy_sources_keeps_relative_layout() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("proj");
+        let src = root.join("src");
+        std::fs::create_dir_all(src.join("nested")).unwrap();
+        std::fs::create_dir_all(root.join("gen")).unwrap();
+        std::fs::write(src.join("main.kt"), "fun main() {}\n").unwrap();
+        std::fs::write(src.join("nested").join("util.kt"), "val x = 1\n").unwrap();
+        std::fs::write(src.join(".editorconfig"), "root = true\n").unwrap();
+        std::fs::write(src.join("skipped.kt"), "val y = 2\n").unwrap();
+        std::fs::write(root.join("gen").join("gen.kt"), "val z = 3\n").unwrap();
+
+        let dest = tmp.path().join("copy");
+        let sources = [
+            src.join("main.kt"),
+            src.join("nested").join("util.kt"),
+            root.join("gen").join("gen.kt"),
+        ];
+        let copies = copy_sources(&root, &sources, &dest).unwrap();
+
+        assert_eq!(
+            copies,
+            vec![
+                dest.join("src").join("main.kt"),
+                dest.join("src").join("nested").join("util.kt"),
+                dest.join("gen").join("gen.kt"),
+            ]
+        );
+        assert_eq!(
+            std::fs::read_to_string(dest.join("src").join("nested").join("util.kt")).unwrap(),
+            "val x = 1\n"
+        );
+        assert!(dest.join("src").join(".editorconfig").exists());
+        assert!(!dest.join("src").join("skipped.kt").exists());
+    }
+
+    #[test]
+    fn invalid_ktlint_version_is_rejected_before_download() {
+        let err =
+            ensure_ktlint("../evil", None, crate::common::test_resolver(false, false)).unwrap_err();
+        assert!(
+            matches!(&err, EngineError::KtlintDownload { version, .. } if version == "../evil"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn download_failure_names_the_url() {
+        let err = map_download_err(
+            "1.3.1",
+            konvoy_util::error::UtilError::Download {
+                message: "connection refused".to_owned(),
+            },
+        );
+        let msg = err.to_string();
+        assert!(msg.contains("ktlint 1.3.1"), "got: {msg}");
+        assert!(msg.contains("releases/download/1.3.1/ktlint"), "got: {msg}");
+    }
+
+    #[test]
+    fn fmt_requires_ktlint_in_toolchain() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tmp.path().join("konvoy.toml"),
+            "[package]\nname = \"demo\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n",
+        )
+        .unwrap();
+        let options = FmtOptions {
+            verbose: false,
+            quiet: true,
+            check: false,
+        };
+        let err = fmt(
+            tmp.path(),
+            &options,
+            crate::common::test_resolver(false, false),
+        )
+        .unwrap_err();
+        assert!(matches!(err, EngineError::FmtNotConfigured), "got: {err}");
+    }
+}
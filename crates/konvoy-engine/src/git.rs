@@ -0,0 +1,253 @@
+//! Git dependencies: resolve a `rev` to a concrete commit and materialize a
+//! shallow checkout of it under `~/.konvoy/cache/git/`.
+//!
+//! Each checkout directory is keyed by `(url, commit)` and never modified after
+//! it is created, so a pinned commit always maps to the same sources. The `.git`
+//! directory is dropped once the commit is known — builds only need the tree.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::EngineError;
+
+/// Root directory holding all git dependency checkouts.
+fn git_cache_root() -> Result<PathBuf, EngineError> {
+    #[cfg(test)]
+    if let Some(root) = tests::CACHE_ROOT.with(|root| root.borrow().clone()) {
+        return Ok(root);
+    }
+    Ok(konvoy_util::fs::konvoy_home()?.join("cache").join("git"))
+}
+
+/// Directory holding the checkout of `url` at `commit`.
+///
+/// # Errors
+/// Returns an error if the home directory cannot be determined.
+pub(crate) fn checkout_dir(url: &str, commit: &str) -> Result<PathBuf, EngineError> {
+    let key = konvoy_util::hash::sha256_bytes(format!("{url}\n{commit}").as_bytes());
+    Ok(git_cache_root()?.join(key))
+}
+
+/// Fetch `rev` (a commit, tag, or branch) from `url` with a depth-1 fetch and
+/// return the concrete commit it resolved to, plus its checkout directory.
+///
+/// When `expected_commit` is set (a pin from `konvoy.lock`), the fetched commit
+/// must match it. An existing checkout of the resulting commit is reused.
+///
+/// # Errors
+/// Returns an error if `git` cannot be run, the fetch fails, or the fetched
+/// commit does not match `expected_commit`.
+pub(crate) fn fetch_checkout(
+    name: &str,
+    url: &str,
+    rev: &str,
+    expected_commit: Option<&str>,
+) -> Result<(String, PathBuf), EngineError> {
+    let root = git_cache_root()?;
+    konvoy_util::fs::ensure_dir(&root)?;
+    let staging = tempfile::Builder::new()
+        .prefix(".tmp-")
+        .tempdir_in(&root)
+        .map_err(|source| io_error(&root, source))?;
+    let work = staging.path();
+
+    eprintln!("    Fetching {name} from {url} ({rev})");
+    run_git(name, url, work, &["init", "--quiet"])?;
+    run_git(
+        name,
+        url,
+        work,
+        &["fetch", "--quiet", "--depth", "1", url, rev],
+    )?;
+    run_git(name, url, work, &["checkout", "--quiet", "FETCH_HEAD"])?;
+    let commit = run_git(name, url, work, &["rev-parse", "HEAD"])?;
+
+    if let Some(expected) = expected_commit {
+        if commit != expected {
+            return Err(EngineError::GitDependency {
+                name: name.to_owned(),
+                url: url.to_owned(),
+                message: format!("fetched commit {commit}, but konvoy.lock pins {expected}"),
+            });
+        }
+    }
+
+    let dest = checkout_dir(url, &commit)?;
+    if !dest.exists() {
+        let git_dir = work.join(".git");
+        std::fs::remove_dir_all(&git_dir).map_err(|source| io_error(&git_dir, source))?;
+        // A concurrent build may have materialized the same commit first; its
+        // checkout is identical, so losing the race is fine.
+        if let Err(source) = std::fs::rename(work, &dest) {
+            if !dest.exists() {
+                return Err(io_error(&dest, source));
+            }
+        }
+    }
+    Ok((commit, dest))
+}
+
+fn io_error(path: &Path, source: std::io::Error) -> EngineError {
+    konvoy_util::error::UtilError::Io {
+        path: path.display().to_string(),
+        source,
+    }
+    .into()
+}
+
+/// Run `git` in `dir` and return its trimmed stdout.
+fn run_git(name: &str, url: &str, dir: &Path, args: &[&str]) -> Result<String, EngineError> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(|e| EngineError::GitDependency {
+            name: name.to_owned(),
+            url: url.to_owned(),
+            message: format!("cannot run git: {e} — is git installed and on PATH?"),
+        })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(EngineError::GitDependency {
+            name: name.to_owned(),
+            url: url.to_owned(),
+            message: format!("`git {}` failed: {}", args.join(" "), stderr.trim()),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+pub(crate) mod tests {
+    use std::cell::RefCell;
+    use std::path::Path;
+    use std::process::Command;
+
+    use super::*;
+
+    thread_local! {
+        /// This test's git cache, set by [`isolated_cache`]. Each test runs on
+        /// its own thread, so tests never see each other's checkouts.
+        pub(crate) static CACHE_ROOT: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+    }
+
+    /// A temporary git cache used in place of `~/.konvoy/cache/git` until it
+    /// is dropped.
+    pub(crate) struct IsolatedCache(tempfile::TempDir);
+
+    impl Drop for IsolatedCache {
+        fn drop(&mut self) {
+            CACHE_ROOT.with(|root| root.borrow_mut().take());
+        }
+    }
+
+    /// Point the calling test's git checkouts at a fresh temporary directory.
+    pub(crate) fn isolated_cache() -> IsolatedCache {
+        let dir = tempfile::tempdir().unwrap();
+        CACHE_ROOT.with(|root| *root.borrow_mut() = Some(dir.path().to_path_buf()));
+        IsolatedCache(dir)
+    }
+
+    pub(crate) fn git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).trim().to_owned()
+    }
+
+    /// Create a git repository at `dir` holding a konvoy library project named
+    /// `name`, and return the commit of its single `main` branch commit.
+    pub(crate) fn init_lib_repo(dir: &Path, name: &str) -> String {
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(dir.join("src/lib.kt"), format!("fun {name}() = 1\n")).unwrap();
+        std::fs::write(
+            dir.join("konvoy.toml"),
+            format!(
+                "[package]\nname = \"{name}\"\nkind = \"lib\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n"
+            ),
+        )
+        .unwrap();
+        git(dir, &["init", "--quiet", "--initial-branch=main"]);
+        git(dir, &["add", "-A"]);
+        git(
+            dir,
+            &[
+                "-c",
+                "user.name=test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "--quiet",
+                "-m",
+                "init",
+            ],
+        );
+        git(dir, &["rev-parse", "HEAD"])
+    }
+
+    /// A `file://` URL for a local repository.
+    pub(crate) fn file_url(dir: &Path) -> String {
+        format!("file://{}", dir.display())
+    }
+
+    #[test]
+    fn checkout_dir_is_keyed_by_url_and_commit() {
+        let a = checkout_dir("https://example.com/a.git", "c1").unwrap();
+        let b = checkout_dir("https://example.com/a.git", "c2").unwrap();
+        let c = checkout_dir("https://example.com/b.git", "c1").unwrap();
+        assert_ne!(a, b);
+        assert_ne!(a, c);
+        assert!(a.display().to_string().contains("cache"));
+    }
+
+    #[test]
+    fn fetch_checkout_pins_branch_to_commit() {
+        let cache = isolated_cache();
+        let tmp = tempfile::tempdir().unwrap();
+        let commit = init_lib_repo(tmp.path(), "branchlib");
+        let url = file_url(tmp.path());
+
+        let (resolved, dir) = fetch_checkout("branchlib", &url, "main", None).unwrap();
+        assert_eq!(resolved, commit);
+        assert_eq!(dir, checkout_dir(&url, &commit).unwrap());
+        assert!(dir.starts_with(cache.0.path()));
+        assert!(dir.join("konvoy.toml").exists());
+        assert!(!dir.join(".git").exists());
+    }
+
+    #[test]
+    fn fetch_checkout_rejects_commit_differing_from_pin() {
+        let _cache = isolated_cache();
+        let tmp = tempfile::tempdir().unwrap();
+        init_lib_repo(tmp.path(), "pinnedlib");
+        let url = file_url(tmp.path());
+
+        let err = fetch_checkout("pinnedlib", &url, "main", Some(&"0".repeat(40)))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("konvoy.lock pins"), "{err}");
+    }
+
+    #[test]
+    fn fetch_checkout_reports_unknown_rev() {
+        let _cache = isolated_cache();
+        let tmp = tempfile::tempdir().unwrap();
+        init_lib_repo(tmp.path(), "revlib");
+        let url = file_url(tmp.path());
+
+        let err = fetch_checkout("revlib", &url, "no-such-branch", None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("revlib"), "{err}");
+        assert!(err.contains("git fetch"), "{err}");
+    }
+}
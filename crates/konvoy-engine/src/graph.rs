@@ -0,0 +1,365 @@
+//! `konvoy graph`: the project's dependency graph as a text tree or a
+//! Graphviz `dot` document.
+//!
+//! Path and git dependencies come from [`resolve_dependencies`]; Maven
+//! dependencies come from the manifests that declare them, and their
+//! transitive dependencies from the `required_by` entries in `konvoy.lock`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::path::Path;
+
+use konvoy_config::lockfile::{DepSource, Lockfile};
+use konvoy_config::manifest::Manifest;
+
+use crate::common::ArtifactResolver;
+use crate::error::EngineError;
+use crate::resolve::{resolve_dependencies, ResolvedGraph};
+
+/// Where a node in the [`DependencyGraph`] comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    /// The project itself.
+    Root,
+    /// A path dependency.
+    Path,
+    /// A git dependency.
+    Git,
+    /// A Maven dependency, direct or transitive.
+    Maven,
+}
+
+/// One package in the [`DependencyGraph`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphNode {
+    pub kind: NodeKind,
+    /// Shown next to the name: the locked (or requested) Maven version, or
+    /// the commit a git dependency resolved to.
+    pub detail: Option<String>,
+}
+
+/// The project's dependencies as nodes keyed by name, with an edge from each
+/// dependent to each of its dependencies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyGraph {
+    /// Name of the root package.
+    pub root: String,
+    pub nodes: BTreeMap<String, GraphNode>,
+    /// `(dependent, dependency)` pairs.
+    pub edges: BTreeSet<(String, String)>,
+}
+
+/// Resolve the dependency graph of the project at `project_root`.
+///
+/// # Errors
+/// Returns an error if `konvoy.toml` or `konvoy.lock` cannot be read, or the
+/// path/git dependency graph cannot be resolved.
+pub fn dependency_graph(
+    project_root: &Path,
+    resolver: ArtifactResolver<'_>,
+) -> Result<DependencyGraph, EngineError> {
+    let manifest = Manifest::from_path(&project_root.join("konvoy.toml"))?;
+    let lockfile = Lockfile::from_path(&project_root.join("konvoy.lock"))?;
+    // Optional dependencies appear as a default build would include them.
+    let features = crate::features::resolve_features(&manifest, &[], true)?;
+    let resolved = resolve_dependencies(project_root, &manifest, &features, &lockfile, resolver)?;
+    Ok(DependencyGraph::new(&manifest, &resolved, &lockfile))
+}
+
+impl DependencyGraph {
+    /// Build the graph from an already-resolved path/git graph.
+    pub fn new(manifest: &Manifest, resolved: &ResolvedGraph, lockfile: &Lockfile) -> Self {
+        let mut graph = Self {
+            root: manifest.package.name.clone(),
+            nodes: BTreeMap::new(),
+            edges: BTreeSet::new(),
+        };
+        graph.nodes.insert(
+            graph.root.clone(),
+            GraphNode {
+                kind: NodeKind::Root,
+                detail: manifest.package.version.clone(),
+            },
+        );
+
+        let locked_maven: BTreeMap<&str, (&str, &[String])> = lockfile
+            .dependencies
+            .iter()
+            .filter_map(|dep| match &dep.source {
+                DepSource::Maven {
+                    version,
+                    required_by,
+                    ..
+                } => Some((
+                    dep.name.as_str(),
+                    (version.as_str(), required_by.as_slice()),
+                )),
+                _ => None,
+            })
+            .collect();
+
+        let declaring = std::iter::once((graph.root.clone(), manifest)).chain(
+            resolved
+                .order
+                .iter()
+                .map(|dep| (dep.name.clone(), &dep.manifest)),
+        );
+        for (dependent, declared_in) in declaring {
+            for (name, spec) in &declared_in.dependencies {
+                if let Some((_, requested)) = spec.as_maven_coord() {
+                    let version = locked_maven
+                        .get(name.as_str())
+                        .map_or(requested, |(locked, _)| locked);
+                    graph.add_maven(name, version);
+                }
+                graph.edges.insert((dependent.clone(), name.clone()));
+            }
+        }
+        for dep in &resolved.order {
+            let (kind, detail) = match &dep.git {
+                Some(pin) => (NodeKind::Git, Some(short_commit(&pin.commit))),
+                None => (NodeKind::Path, None),
+            };
+            graph
+                .nodes
+                .insert(dep.name.clone(), GraphNode { kind, detail });
+        }
+        for (name, (version, required_by)) in &locked_maven {
+            for parent in *required_by {
+                graph.add_maven(name, version);
+                graph.edges.insert((parent.clone(), (*name).to_owned()));
+            }
+        }
+        // An edge to a name nothing resolved (e.g. a Maven dep whose
+        // transitive parent is no longer declared) would dangle; drop it.
+        let nodes = &graph.nodes;
+        graph
+            .edges
+            .retain(|(from, to)| nodes.contains_key(from) && nodes.contains_key(to));
+        graph
+    }
+
+    fn add_maven(&mut self, name: &str, version: &str) {
+        self.nodes.entry(name.to_owned()).or_insert(GraphNode {
+            kind: NodeKind::Maven,
+            detail: Some(version.to_owned()),
+        });
+    }
+
+    /// Direct dependencies of `name`, sorted.
+    fn dependencies_of<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.edges
+            .iter()
+            .filter(move |(from, _)| from == name)
+            .map(|(_, to)| to.as_str())
+    }
+
+    fn label(&self, name: &str) -> String {
+        match self.nodes.get(name).and_then(|node| node.detail.as_deref()) {
+            Some(detail) => format!("{name} {detail}"),
+            None => name.to_owned(),
+        }
+    }
+
+    /// Render as a tree under the project's own name. A package reached a
+    /// second time is marked `(*)` and not expanded again.
+    pub fn to_tree(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "{}", self.label(&self.root));
+        let mut seen = BTreeSet::new();
+        self.write_tree(&mut out, &self.root, "", &mut seen);
+        out
+    }
+
+    fn write_tree<'a>(
+        &'a self,
+        out: &mut String,
+        name: &'a str,
+        prefix: &str,
+        seen: &mut BTreeSet<&'a str>,
+    ) {
+        let children: Vec<&str> = self.dependencies_of(name).collect();
+        for (i, child) in children.iter().enumerate() {
+            let last = i + 1 == children.len();
+            let branch = if last { "└── " } else { "├── " };
+            let repeat = !seen.insert(child);
+            let marker = if repeat { " (*)" } else { "" };
+            let _ = writeln!(out, "{prefix}{branch}{}{marker}", self.label(child));
+            if !repeat {
+                let nested = format!("{prefix}{}", if last { "    " } else { "│   " });
+                self.write_tree(out, child, &nested, seen);
+            }
+        }
+    }
+
+    /// Render as a Graphviz `dot` document: one node per package, colored by
+    /// [`NodeKind`], and an edge from each dependent to its dependencies.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph dependencies {\n");
+        out.push_str("    rankdir=LR;\n");
+        out.push_str("    node [shape=box, style=filled, fontname=\"Helvetica\"];\n");
+        for (name, node) in &self.nodes {
+            let color = match node.kind {
+                NodeKind::Root => "gold",
+                NodeKind::Path => "lightblue",
+                NodeKind::Git => "palegreen",
+                NodeKind::Maven => "lightsalmon",
+            };
+            let _ = writeln!(
+                out,
+                "    {} [label={}, fillcolor={color}];",
+                dot_id(name),
+                dot_id(&self.label(name)),
+            );
+        }
+        for (from, to) in &self.edges {
+            let _ = writeln!(out, "    {} -> {};", dot_id(from), dot_id(to));
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Quote `s` as a `dot` string identifier.
+fn dot_id(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The first 12 characters of a commit hash.
+fn short_commit(commit: &str) -> String {
+    commit.chars().take(12).collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::fs;
+
+    use konvoy_config::lockfile::{DependencyLock, HashAlgorithm};
+
+    use super::*;
+
+    fn write_project(dir: &Path, name: &str, kind: &str, deps: &str) {
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src/lib.kt"), "// lib").unwrap();
+        fs::write(
+            dir.join("konvoy.toml"),
+            format!(
+                "[package]\nname = \"{name}\"\nkind = \"{kind}\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n\n[dependencies]\n{deps}"
+            ),
+        )
+        .unwrap();
+    }
+
+    fn maven_lock(name: &str, version: &str, required_by: &[&str]) -> DependencyLock {
+        DependencyLock {
+            name: name.to_owned(),
+            source: DepSource::Maven {
+                version: version.to_owned(),
+                maven: format!("org.example:{name}"),
+                targets: BTreeMap::new(),
+                required_by: required_by.iter().map(|s| (*s).to_owned()).collect(),
+                classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
+            },
+            source_hash: String::new(),
+        }
+    }
+
+    /// `app -> {core (path), json (maven)}`, `core -> json`, and `json ->
+    /// atomicfu` (transitive, from the lockfile).
+    fn sample() -> DependencyGraph {
+        let tmp = tempfile::tempdir().unwrap();
+        let json = "json = { maven = \"org.example:json\", version = \"^1.6\" }\n";
+        write_project(&tmp.path().join("core"), "core", "lib", json);
+        let root = tmp.path().join("app");
+        write_project(
+            &root,
+            "app",
+            "bin",
+            &format!("core = {{ path = \"../core\" }}\n{json}"),
+        );
+        let manifest = Manifest::from_path(&root.join("konvoy.toml")).unwrap();
+        let mut lockfile = Lockfile::default();
+        lockfile.dependencies.push(maven_lock("json", "1.6.3", &[]));
+        lockfile
+            .dependencies
+            .push(maven_lock("atomicfu", "0.23.1", &["json"]));
+        let resolved = resolve_dependencies(
+            &root,
+            &manifest,
+            &[],
+            &lockfile,
+            crate::common::test_resolver(true, false),
+        )
+        .unwrap();
+        DependencyGraph::new(&manifest, &resolved, &lockfile)
+    }
+
+    #[test]
+    fn graph_covers_path_and_maven_deps() {
+        let graph = sample();
+        let kinds: Vec<(&str, NodeKind)> = graph
+            .nodes
+            .iter()
+            .map(|(name, node)| (name.as_str(), node.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("app", NodeKind::Root),
+                ("atomicfu", NodeKind::Maven),
+                ("core", NodeKind::Path),
+                ("json", NodeKind::Maven),
+            ]
+        );
+        let edges: Vec<(&str, &str)> = graph
+            .edges
+            .iter()
+            .map(|(from, to)| (from.as_str(), to.as_str()))
+            .collect();
+        assert_eq!(
+            edges,
+            [
+                ("app", "core"),
+                ("app", "json"),
+                ("core", "json"),
+                ("json", "atomicfu"),
+            ]
+        );
+        assert_eq!(
+            graph.nodes.get("json").unwrap().detail.as_deref(),
+            Some("1.6.3")
+        );
+    }
+
+    #[test]
+    fn tree_marks_repeated_packages() {
+        assert_eq!(
+            sample().to_tree(),
+            "app\n├── core\n│   └── json 1.6.3\n│       └── atomicfu 0.23.1\n└── json 1.6.3 (*)\n"
+        );
+    }
+
+    #[test]
+    fn dot_colors_nodes_by_kind() {
+        let dot = sample().to_dot();
+        assert!(dot.starts_with("digraph dependencies {\n"), "{dot}");
+        assert!(
+            dot.contains("\"core\" [label=\"core\", fillcolor=lightblue];"),
+            "{dot}"
+        );
+        assert!(
+            dot.contains("\"json\" [label=\"json 1.6.3\", fillcolor=lightsalmon];"),
+            "{dot}"
+        );
+        assert!(dot.contains("\"app\" -> \"core\";"), "{dot}");
+        assert!(dot.contains("\"json\" -> \"atomicfu\";"), "{dot}");
+        assert!(dot.ends_with("}\n"), "{dot}");
+    }
+
+    #[test]
+    fn dot_ids_are_escaped() {
+        assert_eq!(dot_id("a\"b\\c"), "\"a\\\"b\\\\c\"");
+    }
+}
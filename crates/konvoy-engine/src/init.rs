@@ -1,6 +1,9 @@
 //! Project scaffolding for `konvoy init`.
 
+use std::fmt;
 use std::path::Path;
+use std::process::Command;
+use std::str::FromStr;
 
 use konvoy_config::manifest::{Manifest, Package, PackageKind, Toolchain};
 
@@ -9,6 +12,150 @@ use crate::error::EngineError;
 /// Default Kotlin version used when scaffolding new projects.
 pub const DEFAULT_KOTLIN_VERSION: &str = "2.2.0";
 
+/// Starter layout written by `konvoy init --template <name>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectTemplate {
+    /// A hello-world binary.
+    Bin,
+    /// A library producing a `.klib`.
+    Lib,
+    /// A binary with command-line argument parsing scaffolding.
+    Cli,
+}
+
+impl ProjectTemplate {
+    /// Every template, in the order they are listed to users.
+    pub const ALL: [ProjectTemplate; 3] = [Self::Bin, Self::Lib, Self::Cli];
+
+    /// The template name accepted by `--template`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Bin => "bin",
+            Self::Lib => "lib",
+            Self::Cli => "cli",
+        }
+    }
+
+    /// The package kind the template's manifest declares.
+    pub fn kind(self) -> PackageKind {
+        match self {
+            Self::Bin | Self::Cli => PackageKind::Bin,
+            Self::Lib => PackageKind::Lib,
+        }
+    }
+
+    /// The plain template for a package kind. C libraries start from the
+    /// library template.
+    pub fn for_kind(kind: PackageKind) -> Self {
+        match kind {
+            PackageKind::Bin => Self::Bin,
+            PackageKind::Lib | PackageKind::StaticLib | PackageKind::DynamicLib => Self::Lib,
+        }
+    }
+
+    /// The starter source file (relative to `src/`) and its content.
+    fn source(self, name: &str) -> (&'static str, String) {
+        match self {
+            Self::Bin => (
+                "main.kt",
+                format!("fun main() {{\n    println(\"Hello, {name}!\")\n}}\n"),
+            ),
+            Self::Lib => (
+                "lib.kt",
+                format!("// {name} library\n\nfun greet(who: String): String {{\n    return \"Hello, $who!\"\n}}\n"),
+            ),
+            Self::Cli => ("main.kt", CLI_MAIN.replace("{name}", name)),
+        }
+    }
+}
+
+impl fmt::Display for ProjectTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ProjectTemplate {
+    type Err = EngineError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|t| t.as_str() == s)
+            .ok_or_else(|| EngineError::UnknownTemplate {
+                name: s.to_owned(),
+                valid: Self::ALL.map(Self::as_str).join(", "),
+            })
+    }
+}
+
+/// `.gitignore` written into every new project: the `.konvoy/` build and cache
+/// tree, plus `.kexe` executables left behind by running konanc by hand.
+pub const GITIGNORE: &str = "/.konvoy/\n*.kexe\n";
+
+/// Version control to set up in a new project (`konvoy init --vcs`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Vcs {
+    /// Run `git init`, unless `git` is missing or the directory is already in a repository.
+    #[default]
+    Git,
+    /// Leave version control alone.
+    None,
+}
+
+/// `src/main.kt` for the `cli` template. `{name}` is replaced by the project name.
+const CLI_MAIN: &str = r#"import kotlin.system.exitProcess
+
+data class Options(
+    val name: String = "world",
+    val verbose: Boolean = false,
+)
+
+const val USAGE = """Usage: {name} [OPTIONS]
+
+Options:
+  -n, --name <NAME>  Who to greet (default: world)
+  -v, --verbose      Print extra output
+  -h, --help         Print this help"""
+
+fun parseArgs(args: Array<String>): Options {
+    var options = Options()
+    var i = 0
+    while (i < args.size) {
+        when (val arg = args[i]) {
+            "-h", "--help" -> {
+                println(USAGE)
+                exitProcess(0)
+            }
+            "-v", "--verbose" -> options = options.copy(verbose = true)
+            "-n", "--name" -> {
+                i += 1
+                val value = args.getOrNull(i) ?: usageError("missing value for $arg")
+                options = options.copy(name = value)
+            }
+            else -> usageError("unexpected argument '$arg'")
+        }
+        i += 1
+    }
+    return options
+}
+
+fun usageError(message: String): Nothing {
+    println("error: $message
+
+$USAGE")
+    exitProcess(2)
+}
+
+fun main(args: Array<String>) {
+    val options = parseArgs(args)
+    if (options.verbose) {
+        println("options: $options")
+    }
+    println("Hello, ${options.name}!")
+}
+"#;
+
 /// Scaffold a new Konvoy project in a new subdirectory.
 ///
 /// Creates the project directory (if it doesn't exist), a `konvoy.toml` manifest,
@@ -26,27 +173,55 @@ pub fn init_project(name: &str, dir: &Path) -> Result<(), EngineError> {
 
 /// Initialize a Konvoy project in the current (existing) directory.
 ///
-/// Derives the project name from the directory's final component. Writes
-/// `konvoy.toml`, `src/main.kt` (or `src/lib.kt`), and `.gitignore` into
-/// `dir` without creating a subdirectory.
+/// Uses `name` as the project name, or derives it from the directory's final
+/// component when `None`, replacing characters a package name cannot contain
+/// (see [`konvoy_config::manifest::sanitize_name`]). Writes `konvoy.toml`, `src/main.kt` (or `src/lib.kt`),
+/// and `.gitignore` into `dir` without creating a subdirectory.
 ///
 /// # Errors
 /// Returns an error if:
 /// - The directory name cannot be determined (e.g., root `/`)
-/// - The derived project name is invalid
+/// - The project name is invalid
 /// - A `konvoy.toml` already exists in `dir`
+/// - `dir/src` already exists and is not empty
 /// - Files cannot be created or the manifest cannot be serialized
-pub fn init_project_in_place(dir: &Path, kind: PackageKind) -> Result<String, EngineError> {
-    let name = dir
-        .file_name()
-        .and_then(|n| n.to_str())
-        .ok_or_else(|| EngineError::InvalidProjectName {
-            name: dir.display().to_string(),
-            reason: "cannot derive project name from directory path".to_owned(),
-        })?
-        .to_owned();
-
-    init_project_with_kind(&name, dir, kind)?;
+pub fn init_project_in_place(
+    dir: &Path,
+    name: Option<&str>,
+    template: ProjectTemplate,
+) -> Result<String, EngineError> {
+    let name = match name {
+        Some(name) => name.to_owned(),
+        None => dir
+            .file_name()
+            .map(|n| konvoy_config::manifest::sanitize_name(&n.to_string_lossy()))
+            .ok_or_else(|| EngineError::InvalidProjectName {
+                name: dir.display().to_string(),
+                reason: "cannot derive project name from directory path".to_owned(),
+            })?,
+    };
+    validate_project_name(&name)?;
+
+    let manifest_path = dir.join("konvoy.toml");
+    if manifest_path.exists() {
+        return Err(EngineError::ProjectExists {
+            path: manifest_path.display().to_string(),
+        });
+    }
+
+    // Never scaffold over existing sources.
+    let src_dir = dir.join("src");
+    let src_has_entries = match std::fs::read_dir(&src_dir) {
+        Ok(mut entries) => entries.next().is_some(),
+        Err(_) => false,
+    };
+    if src_has_entries {
+        return Err(EngineError::SourceDirNotEmpty {
+            path: src_dir.display().to_string(),
+        });
+    }
+
+    init_project_from_template(&name, dir, template)?;
     Ok(name)
 }
 
@@ -70,6 +245,29 @@ pub fn init_project_with_kind(
     name: &str,
     dir: &Path,
     kind: PackageKind,
+) -> Result<(), EngineError> {
+    scaffold(name, dir, ProjectTemplate::for_kind(kind), kind)
+}
+
+/// Scaffold a new Konvoy project from a starter template.
+///
+/// # Errors
+/// Returns an error if the project name is invalid, the project directory cannot be created,
+/// or a manifest already exists.
+pub fn init_project_from_template(
+    name: &str,
+    dir: &Path,
+    template: ProjectTemplate,
+) -> Result<(), EngineError> {
+    scaffold(name, dir, template, template.kind())
+}
+
+/// Write a project of package kind `kind` from `template`'s starter files.
+fn scaffold(
+    name: &str,
+    dir: &Path,
+    template: ProjectTemplate,
+    kind: PackageKind,
 ) -> Result<(), EngineError> {
     validate_project_name(name)?;
 
@@ -90,50 +288,96 @@ pub fn init_project_with_kind(
         package: Package {
             name: name.to_owned(),
             kind,
-            version: if kind == PackageKind::Lib {
+            version: if kind != PackageKind::Bin {
                 Some("0.1.0".to_owned())
             } else {
                 None
             },
-            entrypoint: if kind == PackageKind::Lib {
+            description: None,
+            authors: Vec::new(),
+            license: None,
+            entrypoint: if kind != PackageKind::Bin {
                 "src/lib.kt".to_owned()
             } else {
                 "src/main.kt".to_owned()
             },
+            source_dirs: Vec::new(),
+            test_dir: None,
+            konvoy_version: None,
+            expand_env: false,
+            bin_name: None,
+            lib_name: None,
         },
         toolchain: Toolchain {
             kotlin: DEFAULT_KOTLIN_VERSION.to_owned(),
             detekt: None,
+            ktlint: None,
+            jre: None,
+            language_version: None,
+            api_version: None,
         },
         codegen: Default::default(),
+        build: Default::default(),
         dependencies: std::collections::BTreeMap::new(),
         plugins: std::collections::BTreeMap::new(),
+        features: std::collections::BTreeMap::new(),
     };
     let toml_content = manifest.to_toml()?;
     konvoy_util::fs::write_file(&manifest_path, toml_content)?;
 
     // Generate and write source file.
-    let (source_name, source_content) = if kind == PackageKind::Lib {
-        (
-            "lib.kt",
-            format!("// {name} library\n\nfun greet(who: String): String {{\n    return \"Hello, $who!\"\n}}\n"),
-        )
-    } else {
-        (
-            "main.kt",
-            format!("fun main() {{\n    println(\"Hello, {name}!\")\n}}\n"),
-        )
-    };
+    let (source_name, source_content) = template.source(name);
     let source_path = src_dir.join(source_name);
     konvoy_util::fs::write_file(&source_path, source_content)?;
 
     // Generate .gitignore.
     let gitignore_path = dir.join(".gitignore");
-    konvoy_util::fs::write_file(&gitignore_path, ".konvoy/\n")?;
+    konvoy_util::fs::write_file(&gitignore_path, GITIGNORE)?;
 
     Ok(())
 }
 
+/// Set up version control in a freshly scaffolded project at `dir`.
+///
+/// With [`Vcs::Git`] this runs `git init`, returning `true` when a repository was
+/// created. Nothing happens (and `false` is returned) for [`Vcs::None`], when
+/// `git` cannot be run, or when `dir` is already inside a git work tree, so a
+/// project created within an existing repository does not get a nested one.
+///
+/// # Errors
+/// Returns an error if `git` runs but `git init` fails.
+pub fn init_vcs(dir: &Path, vcs: Vcs) -> Result<bool, EngineError> {
+    if vcs == Vcs::None {
+        return Ok(false);
+    }
+    let inside = Command::new("git")
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .current_dir(dir)
+        .output();
+    match inside {
+        // No usable git binary: skip silently.
+        Err(_) => return Ok(false),
+        Ok(output) if output.status.success() => return Ok(false),
+        Ok(_) => {}
+    }
+
+    let output = Command::new("git")
+        .args(["init", "--quiet"])
+        .current_dir(dir)
+        .output()
+        .map_err(|e| EngineError::VcsInit {
+            path: dir.display().to_string(),
+            message: e.to_string(),
+        })?;
+    if !output.status.success() {
+        return Err(EngineError::VcsInit {
+            path: dir.display().to_string(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        });
+    }
+    Ok(true)
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
@@ -155,6 +399,38 @@ mod tests {
         assert!(gitignore.contains(".konvoy/"));
     }
 
+    #[test]
+    fn init_vcs_creates_git_repository() {
+        let tmp = tempfile::tempdir().unwrap();
+        let project_dir = tmp.path().join("vcs-app");
+        init_project("vcs-app", &project_dir).unwrap();
+
+        assert!(init_vcs(&project_dir, Vcs::Git).unwrap());
+        assert!(project_dir.join(".git").is_dir());
+        assert_eq!(
+            fs::read_to_string(project_dir.join(".gitignore")).unwrap(),
+            GITIGNORE
+        );
+    }
+
+    #[test]
+    fn init_vcs_skips_nested_repository_and_none() {
+        let tmp = tempfile::tempdir().unwrap();
+        let outer = tmp.path().join("outer");
+        fs::create_dir_all(&outer).unwrap();
+        assert!(init_vcs(&outer, Vcs::Git).unwrap());
+
+        let inner = outer.join("inner");
+        init_project("inner", &inner).unwrap();
+        assert!(!init_vcs(&inner, Vcs::Git).unwrap());
+        assert!(!inner.join(".git").exists());
+
+        let plain = tmp.path().join("plain");
+        init_project("plain", &plain).unwrap();
+        assert!(!init_vcs(&plain, Vcs::None).unwrap());
+        assert!(!plain.join(".git").exists());
+    }
+
     #[test]
     fn manifest_parses_back() {
         let tmp = tempfile::tempdir().unwrap();
@@ -220,6 +496,19 @@ mod tests {
         assert_eq!(manifest.package.entrypoint, "src/lib.kt");
     }
 
+    #[test]
+    fn init_c_library_keeps_its_kind() {
+        let tmp = tempfile::tempdir().unwrap();
+        let project_dir = tmp.path().join("ffi");
+
+        init_project_with_kind("ffi", &project_dir, PackageKind::DynamicLib).unwrap();
+
+        assert!(project_dir.join("src").join("lib.kt").exists());
+        let manifest = Manifest::from_path(&project_dir.join("konvoy.toml")).unwrap();
+        assert_eq!(manifest.package.kind, PackageKind::DynamicLib);
+        assert_eq!(manifest.package.entrypoint, "src/lib.kt");
+    }
+
     #[test]
     fn init_bin_has_no_version() {
         let tmp = tempfile::tempdir().unwrap();
@@ -337,7 +626,7 @@ mod tests {
         let project_dir = tmp.path().join("my-app");
         fs::create_dir_all(&project_dir).unwrap();
 
-        let name = init_project_in_place(&project_dir, PackageKind::Bin).unwrap();
+        let name = init_project_in_place(&project_dir, None, ProjectTemplate::Bin).unwrap();
 
         assert_eq!(name, "my-app");
         assert!(project_dir.join("konvoy.toml").exists());
@@ -351,7 +640,7 @@ mod tests {
         let project_dir = tmp.path().join("cool-project");
         fs::create_dir_all(&project_dir).unwrap();
 
-        let name = init_project_in_place(&project_dir, PackageKind::Bin).unwrap();
+        let name = init_project_in_place(&project_dir, None, ProjectTemplate::Bin).unwrap();
 
         assert_eq!(name, "cool-project");
         let manifest = Manifest::from_path(&project_dir.join("konvoy.toml")).unwrap();
@@ -364,7 +653,7 @@ mod tests {
         let project_dir = tmp.path().join("my-lib");
         fs::create_dir_all(&project_dir).unwrap();
 
-        let name = init_project_in_place(&project_dir, PackageKind::Lib).unwrap();
+        let name = init_project_in_place(&project_dir, None, ProjectTemplate::Lib).unwrap();
 
         assert_eq!(name, "my-lib");
         assert!(project_dir.join("src").join("lib.kt").exists());
@@ -381,7 +670,7 @@ mod tests {
         fs::create_dir_all(&project_dir).unwrap();
         fs::write(project_dir.join("konvoy.toml"), "").unwrap();
 
-        let result = init_project_in_place(&project_dir, PackageKind::Bin);
+        let result = init_project_in_place(&project_dir, None, ProjectTemplate::Bin);
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("already exists"));
@@ -393,7 +682,7 @@ mod tests {
         let project_dir = tmp.path().join("no-sub");
         fs::create_dir_all(&project_dir).unwrap();
 
-        init_project_in_place(&project_dir, PackageKind::Bin).unwrap();
+        init_project_in_place(&project_dir, None, ProjectTemplate::Bin).unwrap();
 
         // The project files should be directly in project_dir, not in a nested subdirectory.
         assert!(project_dir.join("konvoy.toml").exists());
@@ -402,9 +691,113 @@ mod tests {
 
     #[test]
     fn in_place_rejects_root_path() {
-        let result = init_project_in_place(std::path::Path::new("/"), PackageKind::Bin);
+        let result = init_project_in_place(std::path::Path::new("/"), None, ProjectTemplate::Bin);
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("cannot derive project name"), "got: {err}");
     }
+
+    #[test]
+    fn in_place_sanitizes_derived_name() {
+        let tmp = tempfile::tempdir().unwrap();
+        for (dir_name, expected) in [("my project", "my-project"), ("123abc", "_123abc")] {
+            let project_dir = tmp.path().join(dir_name);
+            fs::create_dir_all(&project_dir).unwrap();
+
+            let name = init_project_in_place(&project_dir, None, ProjectTemplate::Bin).unwrap();
+
+            assert_eq!(name, expected);
+            let manifest = Manifest::from_path(&project_dir.join("konvoy.toml")).unwrap();
+            assert_eq!(manifest.package.name, expected);
+        }
+    }
+
+    #[test]
+    fn in_place_uses_explicit_name() {
+        let tmp = tempfile::tempdir().unwrap();
+        let project_dir = tmp.path().join("checkout");
+        fs::create_dir_all(&project_dir).unwrap();
+
+        let name =
+            init_project_in_place(&project_dir, Some("my-tool"), ProjectTemplate::Bin).unwrap();
+
+        assert_eq!(name, "my-tool");
+        let manifest = Manifest::from_path(&project_dir.join("konvoy.toml")).unwrap();
+        assert_eq!(manifest.package.name, "my-tool");
+    }
+
+    #[test]
+    fn in_place_refuses_non_empty_src() {
+        let tmp = tempfile::tempdir().unwrap();
+        let project_dir = tmp.path().join("has-src");
+        fs::create_dir_all(project_dir.join("src")).unwrap();
+        fs::write(project_dir.join("src").join("main.kt"), "fun main() {}\n").unwrap();
+
+        let err = init_project_in_place(&project_dir, None, ProjectTemplate::Bin)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("not empty"), "got: {err}");
+        assert!(!project_dir.join("konvoy.toml").exists());
+        let main = fs::read_to_string(project_dir.join("src").join("main.kt")).unwrap();
+        assert_eq!(main, "fun main() {}\n");
+    }
+
+    #[test]
+    fn in_place_accepts_empty_src() {
+        let tmp = tempfile::tempdir().unwrap();
+        let project_dir = tmp.path().join("empty-src");
+        fs::create_dir_all(project_dir.join("src")).unwrap();
+
+        init_project_in_place(&project_dir, None, ProjectTemplate::Bin).unwrap();
+
+        assert!(project_dir.join("src").join("main.kt").exists());
+    }
+
+    // --- Template tests ---
+
+    #[test]
+    fn template_names_round_trip() {
+        for template in ProjectTemplate::ALL {
+            assert_eq!(
+                template.as_str().parse::<ProjectTemplate>().unwrap(),
+                template
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_template_lists_valid_names() {
+        let err = "web".parse::<ProjectTemplate>().unwrap_err().to_string();
+        assert!(err.contains("unknown template `web`"), "got: {err}");
+        assert!(err.contains("bin, lib, cli"), "got: {err}");
+    }
+
+    #[test]
+    fn cli_template_scaffolds_argument_parsing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let project_dir = tmp.path().join("greeter");
+
+        init_project_from_template("greeter", &project_dir, ProjectTemplate::Cli).unwrap();
+
+        let manifest = Manifest::from_path(&project_dir.join("konvoy.toml")).unwrap();
+        assert_eq!(manifest.package.kind, PackageKind::Bin);
+        assert_eq!(manifest.package.entrypoint, "src/main.kt");
+        let main = fs::read_to_string(project_dir.join("src").join("main.kt")).unwrap();
+        assert!(main.contains("fun main(args: Array<String>)"));
+        assert!(main.contains("fun parseArgs("));
+        assert!(main.contains("Usage: greeter [OPTIONS]"));
+        assert!(!main.contains("{name}"));
+    }
+
+    #[test]
+    fn lib_template_matches_lib_kind() {
+        let tmp = tempfile::tempdir().unwrap();
+        let project_dir = tmp.path().join("shapes");
+
+        init_project_from_template("shapes", &project_dir, ProjectTemplate::Lib).unwrap();
+
+        assert!(project_dir.join("src").join("lib.kt").exists());
+        let manifest = Manifest::from_path(&project_dir.join("konvoy.toml")).unwrap();
+        assert_eq!(manifest.package.kind, PackageKind::Lib);
+    }
 }
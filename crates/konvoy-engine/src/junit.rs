@@ -0,0 +1,408 @@
+//! Test result parsing and JUnit XML reporting.
+//!
+//! Kotlin/Native test binaries log in GTest style (`--ktest_logger=GTEST`,
+//! the default). This module turns that console output into a [`TestReport`]
+//! and renders it as JUnit XML for CI dashboards.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::error::EngineError;
+
+/// Outcome of a single test case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestOutcome {
+    /// The test passed.
+    Passed,
+    /// The test failed; `message` holds the output logged while it ran
+    /// (typically the assertion message and stack trace).
+    Failed { message: String },
+    /// The test was ignored.
+    Skipped,
+}
+
+/// A single test case parsed from the test runner output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestCase {
+    /// The suite (test class) the case belongs to, e.g. `sample.MathTest`.
+    pub suite: String,
+    /// The test function name.
+    pub name: String,
+    /// Reported duration in milliseconds, if the runner printed one.
+    pub duration_ms: Option<u64>,
+    /// How the test ended.
+    pub outcome: TestOutcome,
+}
+
+/// All test cases from one run of a test binary, in execution order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TestReport {
+    pub cases: Vec<TestCase>,
+}
+
+impl TestReport {
+    /// Number of passed test cases.
+    pub fn passed(&self) -> usize {
+        self.count(|o| matches!(o, TestOutcome::Passed))
+    }
+
+    /// Number of failed test cases.
+    pub fn failed(&self) -> usize {
+        self.count(|o| matches!(o, TestOutcome::Failed { .. }))
+    }
+
+    /// Number of skipped (ignored) test cases.
+    pub fn skipped(&self) -> usize {
+        self.count(|o| matches!(o, TestOutcome::Skipped))
+    }
+
+    fn count(&self, pred: impl Fn(&TestOutcome) -> bool) -> usize {
+        self.cases.iter().filter(|c| pred(&c.outcome)).count()
+    }
+
+    /// Render the report as a JUnit XML document.
+    ///
+    /// Cases are grouped into one `<testsuite>` per suite, in order of first
+    /// appearance.
+    pub fn to_junit_xml(&self) -> String {
+        let mut suites: Vec<(&str, Vec<&TestCase>)> = Vec::new();
+        for case in &self.cases {
+            match suites.iter_mut().find(|(name, _)| *name == case.suite) {
+                Some((_, cases)) => cases.push(case),
+                None => suites.push((&case.suite, vec![case])),
+            }
+        }
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuites tests=\"{}\" failures=\"{}\" skipped=\"{}\">",
+            self.cases.len(),
+            self.failed(),
+            self.skipped()
+        );
+        for (suite, cases) in &suites {
+            let failures = cases
+                .iter()
+                .filter(|c| matches!(c.outcome, TestOutcome::Failed { .. }))
+                .count();
+            let skipped = cases
+                .iter()
+                .filter(|c| c.outcome == TestOutcome::Skipped)
+                .count();
+            let _ = writeln!(
+                xml,
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{failures}\" skipped=\"{skipped}\">",
+                xml_escape(suite),
+                cases.len()
+            );
+            for case in cases {
+                write_testcase(&mut xml, case);
+            }
+            xml.push_str("  </testsuite>\n");
+        }
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+}
+
+/// Append one `<testcase>` element.
+fn write_testcase(xml: &mut String, case: &TestCase) {
+    let time = case
+        .duration_ms
+        .map(|ms| format!(" time=\"{}.{:03}\"", ms / 1000, ms % 1000))
+        .unwrap_or_default();
+    let open = format!(
+        "    <testcase classname=\"{}\" name=\"{}\"{time}",
+        xml_escape(&case.suite),
+        xml_escape(&case.name)
+    );
+    match &case.outcome {
+        TestOutcome::Passed => {
+            let _ = writeln!(xml, "{open}/>");
+        }
+        TestOutcome::Skipped => {
+            let _ = writeln!(xml, "{open}>\n      <skipped/>\n    </testcase>");
+        }
+        TestOutcome::Failed { message } => {
+            let summary = message.lines().next().unwrap_or("test failed");
+            let _ = writeln!(
+                xml,
+                "{open}>\n      <failure message=\"{}\">{}</failure>\n    </testcase>",
+                xml_escape(summary),
+                xml_escape(message)
+            );
+        }
+    }
+}
+
+/// Escape text for use in XML content or attribute values.
+///
+/// Control characters that XML 1.0 cannot represent are dropped.
+fn xml_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(ch),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Parse GTest-style output from a Kotlin/Native test binary.
+///
+/// Lines between `[ RUN      ]` and `[  FAILED  ]` become the failure
+/// message. A test that was started but never finished (the binary crashed)
+/// is reported as failed. Parsing stops at the end-of-run summary so failed
+/// and skipped tests listed there are not counted twice.
+pub fn parse_gtest_output(output: &str) -> TestReport {
+    let mut report = TestReport::default();
+    let mut running: Option<&str> = None;
+    let mut logged: Vec<&str> = Vec::new();
+
+    for line in output.lines() {
+        let trimmed = line.trim_end();
+        if is_run_summary(trimmed) {
+            break;
+        }
+        if let Some(name) = trimmed.strip_prefix("[ RUN      ] ") {
+            running = Some(name.trim());
+            logged.clear();
+        } else if let Some(rest) = trimmed.strip_prefix("[       OK ] ") {
+            if let Some(name) = running.take() {
+                report
+                    .cases
+                    .push(make_case(name, parse_duration(rest), TestOutcome::Passed));
+            }
+        } else if let Some(rest) = trimmed.strip_prefix("[  FAILED  ] ") {
+            if let Some(name) = running.take() {
+                let message = logged.join("\n").trim().to_owned();
+                report.cases.push(make_case(
+                    name,
+                    parse_duration(rest),
+                    TestOutcome::Failed { message },
+                ));
+            }
+        } else if let Some(rest) = trimmed.strip_prefix("[  SKIPPED ] ") {
+            if running.is_none() {
+                report
+                    .cases
+                    .push(make_case(rest.trim(), None, TestOutcome::Skipped));
+            }
+        } else if running.is_some() {
+            logged.push(line);
+        }
+    }
+
+    if let Some(name) = running {
+        let partial = logged.join("\n");
+        let message = format!("test did not finish (the test binary exited early)\n{partial}")
+            .trim()
+            .to_owned();
+        report
+            .cases
+            .push(make_case(name, None, TestOutcome::Failed { message }));
+    }
+
+    report
+}
+
+/// Split `suite.Class.test` into (`suite.Class`, `test`).
+fn make_case(full_name: &str, duration_ms: Option<u64>, outcome: TestOutcome) -> TestCase {
+    let (suite, name) = full_name.rsplit_once('.').unwrap_or(("", full_name));
+    TestCase {
+        suite: suite.to_owned(),
+        name: name.to_owned(),
+        duration_ms,
+        outcome,
+    }
+}
+
+/// Extract `N` from a trailing `(N ms)`.
+fn parse_duration(rest: &str) -> Option<u64> {
+    let open = rest.rfind('(')?;
+    rest.get(open + 1..)?
+        .strip_suffix(" ms)")?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Whether `line` is the `[==========] N tests from M test cases ran.` marker
+/// that precedes the end-of-run summary.
+fn is_run_summary(line: &str) -> bool {
+    line.starts_with("[==========]") && line.contains(" ran.")
+}
+
+/// Write a JUnit XML report for `report` to `path`, creating parent directories.
+///
+/// # Errors
+/// Returns an error if the parent directory cannot be created or the file
+/// cannot be written.
+pub fn write_junit_report(path: &Path, report: &TestReport) -> Result<(), EngineError> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        konvoy_util::fs::ensure_dir(parent)?;
+    }
+    konvoy_util::fs::write_file(path, report.to_junit_xml())?;
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    const MIXED_RUN: &str = "\
+[==========] Running 4 tests from 1 test cases.
+[----------] Global test environment set-up.
+[----------] 4 tests from sample.MathTest
+[ RUN      ] sample.MathTest.adds
+[       OK ] sample.MathTest.adds (0 ms)
+[ RUN      ] sample.MathTest.divides
+kotlin.AssertionError: Expected <2>, actual <3>.
+    at kotlin.Throwable#<init>(Unknown Source)
+[  FAILED  ] sample.MathTest.divides (3 ms)
+[  SKIPPED ] sample.MathTest.later
+[ RUN      ] sample.MathTest.subtracts
+[       OK ] sample.MathTest.subtracts (1250 ms)
+[----------] 4 tests from sample.MathTest (1253 ms total)
+
+[----------] Global test environment tear-down
+[==========] 4 tests from 1 test cases ran. (1253 ms total)
+[  PASSED  ] 2 tests.
+[  SKIPPED ] 1 tests, listed below:
+[  SKIPPED ] sample.MathTest.later
+[  FAILED  ] 1 tests, listed below:
+[  FAILED  ] sample.MathTest.divides
+";
+
+    // ── parse_gtest_output ──────────────────────────────────────────
+
+    #[test]
+    fn parse_counts_outcomes_without_double_counting_summary() {
+        let report = parse_gtest_output(MIXED_RUN);
+        assert_eq!(report.cases.len(), 4);
+        assert_eq!(report.passed(), 2);
+        assert_eq!(report.failed(), 1);
+        assert_eq!(report.skipped(), 1);
+    }
+
+    #[test]
+    fn parse_splits_suite_and_name() {
+        let report = parse_gtest_output(MIXED_RUN);
+        let first = report.cases.first().unwrap();
+        assert_eq!(first.suite, "sample.MathTest");
+        assert_eq!(first.name, "adds");
+        assert_eq!(first.duration_ms, Some(0));
+    }
+
+    #[test]
+    fn parse_failure_captures_message() {
+        let report = parse_gtest_output(MIXED_RUN);
+        let failed = report.cases.iter().find(|c| c.name == "divides").unwrap();
+        assert_eq!(failed.duration_ms, Some(3));
+        let TestOutcome::Failed { message } = &failed.outcome else {
+            panic!("expected failure, got {:?}", failed.outcome);
+        };
+        assert!(message.starts_with("kotlin.AssertionError: Expected <2>, actual <3>."));
+        assert!(message.contains("at kotlin.Throwable"));
+    }
+
+    #[test]
+    fn parse_unfinished_test_is_failure() {
+        let output = "[ RUN      ] sample.CrashTest.boom\nSegmentation fault\n";
+        let report = parse_gtest_output(output);
+        assert_eq!(report.failed(), 1);
+        let TestOutcome::Failed { message } = &report.cases.first().unwrap().outcome else {
+            panic!("expected failure");
+        };
+        assert!(message.contains("did not finish"));
+        assert!(message.contains("Segmentation fault"));
+    }
+
+    #[test]
+    fn parse_empty_output() {
+        let report = parse_gtest_output("");
+        assert!(report.cases.is_empty());
+        assert_eq!(report.passed(), 0);
+    }
+
+    #[test]
+    fn parse_ignores_unrelated_lines() {
+        let report = parse_gtest_output("hello\n[----------] Global test environment set-up.\n");
+        assert!(report.cases.is_empty());
+    }
+
+    // ── to_junit_xml ────────────────────────────────────────────────
+
+    #[test]
+    fn junit_xml_has_totals_and_cases() {
+        let xml = parse_gtest_output(MIXED_RUN).to_junit_xml();
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+        assert!(xml.contains("<testsuites tests=\"4\" failures=\"1\" skipped=\"1\">"));
+        assert!(xml.contains(
+            "<testsuite name=\"sample.MathTest\" tests=\"4\" failures=\"1\" skipped=\"1\">"
+        ));
+        assert!(
+            xml.contains("<testcase classname=\"sample.MathTest\" name=\"adds\" time=\"0.000\"/>")
+        );
+        assert!(xml.contains("name=\"subtracts\" time=\"1.250\"/>"));
+        assert!(xml.contains("<skipped/>"));
+        assert!(xml.ends_with("</testsuites>\n"));
+    }
+
+    #[test]
+    fn junit_xml_escapes_failure_message() {
+        let xml = parse_gtest_output(MIXED_RUN).to_junit_xml();
+        assert!(xml.contains(
+            "<failure message=\"kotlin.AssertionError: Expected &lt;2&gt;, actual &lt;3&gt;.\">"
+        ));
+        assert!(xml.contains("at kotlin.Throwable#&lt;init&gt;(Unknown Source)</failure>"));
+    }
+
+    #[test]
+    fn junit_xml_groups_by_suite() {
+        let output = "\
+[ RUN      ] a.First.one
+[       OK ] a.First.one (1 ms)
+[ RUN      ] b.Second.two
+[       OK ] b.Second.two (1 ms)
+[ RUN      ] a.First.three
+[       OK ] a.First.three (1 ms)
+";
+        let xml = parse_gtest_output(output).to_junit_xml();
+        assert_eq!(xml.matches("<testsuite ").count(), 2);
+        assert!(xml.contains("<testsuite name=\"a.First\" tests=\"2\""));
+        assert!(xml.contains("<testsuite name=\"b.Second\" tests=\"1\""));
+    }
+
+    #[test]
+    fn junit_xml_empty_report() {
+        let xml = TestReport::default().to_junit_xml();
+        assert!(xml.contains("<testsuites tests=\"0\" failures=\"0\" skipped=\"0\">"));
+    }
+
+    #[test]
+    fn xml_escape_drops_control_characters() {
+        assert_eq!(xml_escape("a\u{1b}[31mb&\"'"), "a[31mb&amp;&quot;&apos;");
+    }
+
+    // ── write_junit_report ──────────────────────────────────────────
+
+    #[test]
+    fn write_junit_report_creates_parent_dirs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("reports").join("junit.xml");
+        let report = parse_gtest_output(MIXED_RUN);
+        write_junit_report(&path, &report).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(written, report.to_junit_xml());
+    }
+}
@@ -2,37 +2,69 @@
 //! Build orchestration, cache keying, and artifact store for Konvoy.
 
 pub mod artifact;
+pub mod bench_build;
 pub mod build;
 pub mod cache;
 pub mod codegen;
 mod common;
+mod compile_commands;
+pub mod compile_sources;
 pub mod detekt;
 mod diagnostics;
+pub mod effective_config;
 pub mod error;
+pub mod example;
+pub mod explain;
+mod features;
+pub mod fmt;
+mod git;
+pub mod graph;
 pub mod init;
+pub mod junit;
 pub mod managed_tool;
+pub mod plan;
 pub mod plugin;
+pub mod progress;
 pub mod resolve;
+pub mod sarif;
+mod sources;
 pub mod test_build;
+mod timings;
+pub mod unused;
 pub mod update;
+pub mod verify;
 
 pub use artifact::{ArtifactStore, BuildMetadata};
-pub use build::{build, BuildOptions, BuildOutcome, BuildResult};
+pub use bench_build::{build_benches, BenchBuildResult};
+pub use build::{build, BuildOptions, BuildOutcome, BuildResult, ALL_TARGETS};
 pub use cache::{CacheInputs, CacheKey};
 pub use codegen::{
     compute_codegen_hash_pairs, compute_codegen_hashes, generate, generator_output_dir,
     CodeGenerator, GenerateResult, GeneratedOutput,
 };
 pub use common::{ArtifactResolver, LockfileManager};
+pub use compile_sources::{compile_sources, CompileSourcesOptions};
 pub use detekt::{lint, DetektDiagnostic, LintOptions, LintResult};
+pub use effective_config::{effective_config, EffectiveConfig};
 pub use error::EngineError;
+pub use example::{build_example, list_examples};
+pub use explain::{explain, Explanation};
+pub use fmt::{fmt, FmtOptions, FmtResult};
+pub use graph::{dependency_graph, DependencyGraph};
 pub use init::{
-    init_project, init_project_in_place, init_project_with_kind, DEFAULT_KOTLIN_VERSION,
+    init_project, init_project_from_template, init_project_in_place, init_project_with_kind,
+    init_vcs, ProjectTemplate, Vcs, DEFAULT_KOTLIN_VERSION,
 };
+pub use junit::{parse_gtest_output, write_junit_report, TestCase, TestOutcome, TestReport};
 pub use managed_tool::{ManagedToolSpec, ToolOutput, ToolRuntime, ToolSource};
+pub use plan::{plan_build, BuildPlan, PlanStatus, PlannedStep};
 pub use plugin::{
     ensure_plugin_artifacts, resolve_plugin_artifacts, PluginArtifactResult, ResolvedPluginArtifact,
 };
+pub use progress::{ProgressSink, StderrProgress};
 pub use resolve::{resolve_dependencies, ResolvedGraph};
+pub use sarif::{render_sarif, to_sarif, write_sarif_report, SarifLog};
 pub use test_build::{build_tests, TestBuildResult};
+pub use unused::{unused_dependencies, UnusedDependency};
 pub use update::{update, UpdateResult};
+pub use verify::{verify, ArtifactCheck, ArtifactStatus, VerifyReport};
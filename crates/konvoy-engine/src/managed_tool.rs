@@ -10,7 +10,7 @@
 //! `DirectUrl` artifact can be a JAR (detekt) or a native binary, and a `Maven`
 //! artifact is a JAR today (Fabrikt) but nothing here assumes the JVM.
 //!
-//! Download error mapping (`DetektDownload` vs `CodegenDownload`, …) and lockfile
+//! Download error mapping (`DetektDownloadFailed` vs `CodegenDownload`, …) and lockfile
 //! pinning stay with the caller: [`ensure`](ManagedToolSpec::ensure) returns the
 //! raw [`UtilError`] so each caller can map it via `error::map_artifact_download_err`
 //! and persist the hash wherever its lockfile section lives. Execution, by
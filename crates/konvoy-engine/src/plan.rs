@@ -0,0 +1,560 @@
+//! `konvoy build --dry-run`: what a build would do — which projects it would
+//! compile and which it would take from the cache, and what it would
+//! download — without compiling, downloading, or writing `konvoy.lock`.
+//!
+//! A cache hit can only be confirmed once every compile input is local. While
+//! a git dependency, the toolchain, a compiler plugin, a Maven klib, or a
+//! code-generation tool still has to be downloaded, or a Maven dependency still has to be resolved,
+//! every project is planned as compiled. A project is also compiled when one
+//! of its path dependencies is, since its cache key hashes that dependency's
+//! klib.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use konvoy_config::lockfile::{DependencyLock, Lockfile};
+use konvoy_config::manifest::Manifest;
+use konvoy_config::Profile;
+use konvoy_targets::Target;
+
+use crate::build::{
+    build_output_path, first_unresolved_maven_dep, graph_library_inputs, lockfile_toml_content,
+    predicted_effective_lockfile, project_inputs, prune_stale_lock_entries, resolve_target,
+    BuildOptions, CompileContext, GraphLibraryInputs, LibraryInput, ProjectInputs,
+};
+use crate::common::ArtifactResolver;
+use crate::error::EngineError;
+use crate::resolve::{plan_dependencies, ResolvedGraph};
+
+/// What a build would do with one project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanStatus {
+    /// The artifact is in the cache and would be reused.
+    Cached,
+    /// The project would be compiled.
+    Compile,
+}
+
+/// One project of a [`BuildPlan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedStep {
+    /// Package name.
+    pub name: String,
+    /// Where the artifact would be materialized.
+    pub output_path: PathBuf,
+    pub status: PlanStatus,
+}
+
+/// What `konvoy build` would do for one target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildPlan {
+    pub target: Target,
+    pub profile: Profile,
+    /// What would be resolved or downloaded first, e.g. `Kotlin/Native 2.1.0`.
+    pub downloads: Vec<String>,
+    /// Path dependencies in build order, then the project itself.
+    pub steps: Vec<PlannedStep>,
+}
+
+impl BuildPlan {
+    /// How many projects would be compiled.
+    pub fn compile_count(&self) -> usize {
+        self.steps
+            .iter()
+            .filter(|step| step.status == PlanStatus::Compile)
+            .count()
+    }
+}
+
+/// Work out what `build` would do for the project at `project_root` with
+/// `options`, without compiling, downloading, or writing anything.
+///
+/// # Errors
+/// Returns an error where the build itself would stop before compiling: an
+/// invalid `konvoy.toml` or `konvoy.lock`, an unresolvable path or git
+/// dependency, an unknown target, or lockfile drift under `--locked`.
+pub fn plan_build(
+    project_root: &Path,
+    options: &BuildOptions,
+    resolver: ArtifactResolver<'_>,
+) -> Result<BuildPlan, EngineError> {
+    let manifest = Manifest::from_path(&project_root.join("konvoy.toml"))?;
+    let features = crate::features::resolve_features(
+        &manifest,
+        &options.features,
+        !options.no_default_features,
+    )?;
+    let lockfile = Lockfile::from_path(&project_root.join("konvoy.lock"))?;
+    let (dep_graph, mut downloads) =
+        plan_dependencies(project_root, &manifest, &features, &lockfile, resolver)?;
+    let manifests =
+        || std::iter::once(&manifest).chain(dep_graph.order.iter().map(|d| &d.manifest));
+
+    downloads.extend(
+        manifests()
+            .filter_map(|m| first_unresolved_maven_dep(m, &lockfile))
+            .map(|name| format!("Maven dependency `{name}` (not in konvoy.lock yet)")),
+    );
+    // The build prunes stale entries from the file; the plan only in memory.
+    let (lockfile, _) = prune_stale_lock_entries(&manifest, &dep_graph, &lockfile);
+    resolver.require_graph_artifacts_resolvable(
+        &manifest,
+        dep_graph.order.iter().map(|d| &d.manifest),
+        &lockfile,
+    )?;
+
+    let target = resolve_target(&options.target)?;
+    let jre_major = crate::common::jre_major(&manifest.toolchain);
+    let kotlin = &manifest.toolchain.kotlin;
+    if konvoy_konanc::detect::konanc_override().is_none()
+        && !konvoy_konanc::toolchain::is_installed(kotlin, jre_major)?
+    {
+        downloads.push(format!("Kotlin/Native {kotlin}"));
+    }
+    let plugin_artifacts = crate::plugin::resolve_graph_plugin_artifacts(manifests())?;
+    downloads.extend(
+        plugin_artifacts
+            .iter()
+            .filter(|artifact| !artifact.cache_path.exists())
+            .map(|artifact| {
+                format!(
+                    "plugin {} {}",
+                    artifact.plugin_name, artifact.maven_coord.version
+                )
+            }),
+    );
+    let maven_entries: Vec<&DependencyLock> = lockfile.dependencies.iter().collect();
+    downloads.extend(crate::build::missing_klibs(&maven_entries, &target)?);
+    let generators: Vec<_> = manifests()
+        .flat_map(|m| crate::codegen::active_generators(&m.codegen))
+        .collect();
+    downloads.extend(
+        crate::codegen::missing_codegen_tools(&generators)?
+            .into_iter()
+            .map(|tool| format!("codegen tool {tool}")),
+    );
+
+    let steps = if downloads.is_empty() {
+        planned_steps(
+            project_root,
+            &manifest,
+            &features,
+            &lockfile,
+            &dep_graph,
+            target,
+            options,
+            resolver,
+        )?
+    } else {
+        dep_graph
+            .order
+            .iter()
+            .map(|dep| (dep.project_root.as_path(), &dep.manifest))
+            .chain(std::iter::once((project_root, &manifest)))
+            .map(|(root, m)| PlannedStep {
+                name: m.package.name.clone(),
+                output_path: build_output_path(
+                    root,
+                    options.target_dir.as_deref(),
+                    &m.package.output_name(target),
+                    &target,
+                    options.profile,
+                ),
+                status: PlanStatus::Compile,
+            })
+            .collect()
+    };
+
+    Ok(BuildPlan {
+        target,
+        profile: options.profile,
+        downloads,
+        steps,
+    })
+}
+
+/// Plan each project once every input is local: compute its cache key exactly
+/// as `build` would and look it up in its artifact store.
+#[allow(clippy::too_many_arguments)]
+fn planned_steps(
+    project_root: &Path,
+    manifest: &Manifest,
+    features: &[String],
+    lockfile: &Lockfile,
+    dep_graph: &ResolvedGraph,
+    target: Target,
+    options: &BuildOptions,
+    resolver: ArtifactResolver<'_>,
+) -> Result<Vec<PlannedStep>, EngineError> {
+    // Everything is installed, so none of these download; they only read and
+    // re-verify what is already there.
+    let jre_major = crate::common::jre_major(&manifest.toolchain);
+    let resolved = resolver.resolve_toolchain(&manifest.toolchain.kotlin, jre_major, lockfile)?;
+    let plugin_artifacts = crate::plugin::resolve_graph_plugin_artifacts(
+        std::iter::once(manifest).chain(dep_graph.order.iter().map(|dep| &dep.manifest)),
+    )?;
+    let maven_entries: Vec<&DependencyLock> = lockfile.dependencies.iter().collect();
+    let (plugin_results, maven_klibs) = crate::build::fetch_plugins_and_klibs(
+        &plugin_artifacts,
+        &maven_entries,
+        lockfile,
+        &target,
+        options.progress.as_ref(),
+        resolver,
+    )?;
+    let plugin_locks = crate::plugin::build_plugin_locks(&plugin_results);
+    let mut generators = crate::codegen::active_generators(&manifest.codegen);
+    for dep in &dep_graph.order {
+        generators.extend(crate::codegen::active_generators(&dep.manifest.codegen));
+    }
+    let codegen_locks =
+        crate::codegen::ensure_codegen_tools(&generators, &lockfile.codegen_tools, resolver)?;
+    let effective_lockfile = predicted_effective_lockfile(
+        lockfile,
+        (resolved.source == konvoy_konanc::detect::KonancSource::Managed)
+            .then_some(resolved.info.version.as_str()),
+        None,
+        None,
+        jre_major,
+        &plugin_locks,
+        &codegen_locks,
+        dep_graph,
+        project_root,
+        resolver,
+    );
+    let lockfile_content = lockfile_toml_content(&effective_lockfile)?;
+    let GraphLibraryInputs {
+        deps: dep_inputs,
+        root_maven_klibs,
+    } = graph_library_inputs(dep_graph, &maven_entries, maven_klibs, &effective_lockfile);
+
+    let planner = Planner {
+        konanc: &resolved.info,
+        jre_home: resolved.jre_home.as_deref(),
+        target: &target,
+        options,
+        timeout: options.compile_timeout(manifest),
+        deny_warnings: options.deny_warnings(manifest),
+        compress_cache: crate::artifact::compress_cache(manifest.build.compress_cache),
+        lockfile_content: &lockfile_content,
+    };
+
+    // The cached artifact of each path-dep planned so far, `None` if it would
+    // be compiled. `order` is topological, so descendants come first.
+    let mut artifacts: HashMap<&str, Option<LibraryInput>> = HashMap::new();
+    let mut steps = Vec::with_capacity(dep_graph.order.len() + 1);
+    for dep in &dep_graph.order {
+        let (descendants, dep_maven_klibs) =
+            dep_inputs.get(dep.name.as_str()).ok_or_else(|| {
+                EngineError::InternalInvariantViolated {
+                    context: format!("missing precomputed inputs for dep `{}`", dep.name),
+                }
+            })?;
+        let descendant_klibs: Option<Vec<LibraryInput>> = descendants
+            .iter()
+            .map(|name| artifacts.get(name.as_str()).cloned().flatten())
+            .collect();
+        let library_inputs = descendant_klibs.map(|mut libs| {
+            libs.extend(dep_maven_klibs.iter().cloned());
+            libs
+        });
+        let dep_features = crate::features::resolve_features(&dep.manifest, &[], true)?;
+        let (step, artifact) = planner.plan(
+            &dep.project_root,
+            &dep.manifest,
+            &dep_features,
+            library_inputs.as_deref(),
+        )?;
+        artifacts.insert(dep.name.as_str(), artifact);
+        steps.push(step);
+    }
+
+    let dep_klibs: Option<Vec<LibraryInput>> = dep_graph
+        .order
+        .iter()
+        .map(|dep| artifacts.get(dep.name.as_str()).cloned().flatten())
+        .collect();
+    let library_inputs = dep_klibs.map(|mut libs| {
+        libs.extend(root_maven_klibs);
+        libs
+    });
+    let (step, _) = planner.plan(project_root, manifest, features, library_inputs.as_deref())?;
+    steps.push(step);
+    Ok(steps)
+}
+
+/// The build-wide part of each project's [`CompileContext`].
+struct Planner<'a> {
+    konanc: &'a konvoy_konanc::detect::KonancInfo,
+    jre_home: Option<&'a Path>,
+    target: &'a Target,
+    options: &'a BuildOptions,
+    timeout: Option<std::time::Duration>,
+    deny_warnings: bool,
+    compress_cache: bool,
+    lockfile_content: &'a str,
+}
+
+impl Planner<'_> {
+    /// Plan one project. `library_inputs` is `None` when a path dependency
+    /// would be compiled, so this project's cache key is not known yet.
+    /// Returns the step and, if it is cached, the cached artifact as a library
+    /// input hashed as it materializes.
+    fn plan(
+        &self,
+        project_root: &Path,
+        manifest: &Manifest,
+        features: &[String],
+        library_inputs: Option<&[LibraryInput]>,
+    ) -> Result<(PlannedStep, Option<LibraryInput>), EngineError> {
+        let name = manifest.package.name.clone();
+        let Some(library_inputs) = library_inputs else {
+            let output_path = build_output_path(
+                project_root,
+                self.options.target_dir.as_deref(),
+                &manifest.package.output_name(*self.target),
+                self.target,
+                self.options.profile,
+            );
+            let status = PlanStatus::Compile;
+            return Ok((
+                PlannedStep {
+                    name,
+                    output_path,
+                    status,
+                },
+                None,
+            ));
+        };
+        let cc = CompileContext {
+            konanc: self.konanc,
+            jre_home: self.jre_home,
+            target: self.target,
+            options: self.options,
+            library_inputs,
+            timeout: self.timeout,
+            deny_warnings: self.deny_warnings,
+            compress_cache: self.compress_cache,
+            features,
+            command_log: None,
+        };
+        let ProjectInputs {
+            cache_key,
+            output_name,
+            output_path,
+            ..
+        } = project_inputs(
+            project_root,
+            manifest,
+            &cc,
+            self.options.profile,
+            self.lockfile_content,
+        )?;
+        let store = self.options.artifact_store(project_root);
+        if self.options.force || !store.has(&cache_key) {
+            let status = PlanStatus::Compile;
+            return Ok((
+                PlannedStep {
+                    name,
+                    output_path,
+                    status,
+                },
+                None,
+            ));
+        }
+        let sha256 = store.artifact_sha256(&cache_key, &output_name)?;
+        let artifact =
+            LibraryInput::with_hash(store.cache_path(&cache_key).join(output_name), sha256);
+        let status = PlanStatus::Cached;
+        Ok((
+            PlannedStep {
+                name,
+                output_path,
+                status,
+            },
+            Some(artifact),
+        ))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::fs;
+
+    use konvoy_konanc::detect::KonancInfo;
+
+    use super::*;
+    use crate::artifact::{ArtifactStore, BuildMetadata};
+
+    fn write_project(dir: &Path, name: &str, kind: &str, kotlin: &str, deps: &str) {
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src/main.kt"), "fun main() {}").unwrap();
+        fs::write(
+            dir.join("konvoy.toml"),
+            format!(
+                "[package]\nname = \"{name}\"\nkind = \"{kind}\"\n\n[toolchain]\nkotlin = \"{kotlin}\"\n\n[dependencies]\n{deps}"
+            ),
+        )
+        .unwrap();
+    }
+
+    fn konanc() -> KonancInfo {
+        KonancInfo {
+            path: PathBuf::from("/usr/bin/konanc"),
+            version: "2.1.0".to_owned(),
+            fingerprint: "abc".to_owned(),
+            launcher_fingerprint: "abc".to_owned(),
+        }
+    }
+
+    #[test]
+    fn missing_toolchain_plans_every_project_as_compiled() {
+        let tmp = tempfile::tempdir().unwrap();
+        let version = "0.0.0-plan-missing-toolchain";
+        write_project(&tmp.path().join("core"), "core", "lib", version, "");
+        let root = tmp.path().join("app");
+        write_project(
+            &root,
+            "app",
+            "bin",
+            version,
+            "core = { path = \"../core\" }\n",
+        );
+        let options = BuildOptions {
+            target: Some("linux_x64".to_owned()),
+            ..BuildOptions::default()
+        };
+
+        let plan = plan_build(&root, &options, crate::common::test_resolver(true, false)).unwrap();
+
+        assert_eq!(plan.downloads, [format!("Kotlin/Native {version}")]);
+        let steps: Vec<(&str, PlanStatus)> = plan
+            .steps
+            .iter()
+            .map(|step| (step.name.as_str(), step.status))
+            .collect();
+        assert_eq!(
+            steps,
+            [("core", PlanStatus::Compile), ("app", PlanStatus::Compile)]
+        );
+        assert_eq!(
+            plan.steps.last().unwrap().output_path,
+            root.join(".konvoy/build/linux_x64/debug/app")
+        );
+        assert_eq!(plan.compile_count(), 2);
+        assert!(!root.join("konvoy.lock").exists());
+        assert!(!root.join(".konvoy").exists());
+    }
+
+    #[test]
+    fn unfetched_git_dependency_is_planned_as_a_download() {
+        // Offline, like every dry run: the git dependency is listed instead of
+        // failing the plan with a "drop --offline" error.
+        let tmp = tempfile::tempdir().unwrap();
+        let version = "0.0.0-plan-git-dependency";
+        write_project(
+            tmp.path(),
+            "app",
+            "bin",
+            version,
+            "utils = { git = \"https://example.com/utils.git\", rev = \"main\" }\n",
+        );
+        let options = BuildOptions {
+            target: Some("linux_x64".to_owned()),
+            ..BuildOptions::default()
+        };
+
+        let plan = plan_build(
+            tmp.path(),
+            &options,
+            crate::common::test_resolver(true, false),
+        )
+        .unwrap();
+
+        assert_eq!(
+            plan.downloads,
+            [
+                "git dependency `utils` (https://example.com/utils.git at main)".to_owned(),
+                format!("Kotlin/Native {version}"),
+            ]
+        );
+        let steps: Vec<(&str, PlanStatus)> = plan
+            .steps
+            .iter()
+            .map(|step| (step.name.as_str(), step.status))
+            .collect();
+        assert_eq!(steps, [("app", PlanStatus::Compile)]);
+        assert!(!tmp.path().join("konvoy.lock").exists());
+    }
+
+    #[test]
+    fn planner_reports_a_stored_artifact_as_cached() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_project(tmp.path(), "app", "bin", "2.1.0", "");
+        let manifest = Manifest::from_path(&tmp.path().join("konvoy.toml")).unwrap();
+        let konanc = konanc();
+        let options = BuildOptions::default();
+        let planner = Planner {
+            konanc: &konanc,
+            jre_home: None,
+            target: &Target::LinuxX64,
+            options: &options,
+            timeout: None,
+            deny_warnings: false,
+            compress_cache: false,
+            lockfile_content: "",
+        };
+
+        let (step, artifact) = planner.plan(tmp.path(), &manifest, &[], Some(&[])).unwrap();
+        assert_eq!(step.status, PlanStatus::Compile);
+        assert!(artifact.is_none());
+
+        let cc = CompileContext {
+            konanc: &konanc,
+            jre_home: None,
+            target: &Target::LinuxX64,
+            options: &options,
+            library_inputs: &[],
+            timeout: None,
+            deny_warnings: false,
+            compress_cache: false,
+            features: &[],
+            command_log: None,
+        };
+        let inputs = project_inputs(tmp.path(), &manifest, &cc, Profile::Debug, "").unwrap();
+        let built = tmp.path().join(&inputs.output_name);
+        fs::write(&built, "binary").unwrap();
+        let store = ArtifactStore::new(tmp.path());
+        let metadata = BuildMetadata {
+            target: Target::LinuxX64,
+            profile: Profile::Debug,
+            konanc_version: "2.1.0".to_owned(),
+            built_at: "0".to_owned(),
+        };
+        store.store(&inputs.cache_key, &built, &metadata).unwrap();
+
+        let (step, artifact) = planner.plan(tmp.path(), &manifest, &[], Some(&[])).unwrap();
+        assert_eq!(step.status, PlanStatus::Cached);
+        assert_eq!(step.output_path, inputs.output_path);
+        assert_eq!(
+            artifact.map(|lib| lib.path),
+            Some(store.cache_path(&inputs.cache_key).join("app"))
+        );
+
+        // A dependency that would be compiled leaves the key unknown.
+        let (step, _) = planner.plan(tmp.path(), &manifest, &[], None).unwrap();
+        assert_eq!(step.status, PlanStatus::Compile);
+
+        let forced = BuildOptions {
+            force: true,
+            ..BuildOptions::default()
+        };
+        let planner = Planner {
+            options: &forced,
+            ..planner
+        };
+        let (step, _) = planner.plan(tmp.path(), &manifest, &[], Some(&[])).unwrap();
+        assert_eq!(step.status, PlanStatus::Compile);
+    }
+}
@@ -184,6 +184,21 @@ pub fn plugin_jar_paths(manifest: &Manifest) -> Result<Vec<PathBuf>, EngineError
         .collect())
 }
 
+/// This project's compiler-plugin options as `(plugin id, key, value)`, from
+/// each `[plugins]` entry's `id` and `options`, in manifest order.
+pub fn plugin_options(manifest: &Manifest) -> Vec<(String, String, String)> {
+    manifest
+        .plugins
+        .values()
+        .filter_map(|spec| spec.id.as_ref().map(|id| (id, &spec.options)))
+        .flat_map(|(id, options)| {
+            options
+                .iter()
+                .map(move |(key, value)| (id.clone(), key.clone(), value.clone()))
+        })
+        .collect()
+}
+
 // ---------------------------------------------------------------------------
 // Download / verification
 // ---------------------------------------------------------------------------
@@ -405,14 +420,23 @@ mod tests {
                 path: None,
                 maven: None,
                 version: Some("1.0.0".to_owned()),
+                git: None,
+                rev: None,
+                optional: false,
+                allow_unused: false,
+                allow_toolchain_mismatch: false,
+                id: None,
+                options: BTreeMap::new(),
             },
         );
         let manifest = Manifest {
             package: default_package(),
             toolchain: default_toolchain(),
             codegen: Default::default(),
+            build: Default::default(),
             dependencies: BTreeMap::new(),
             plugins,
+            features: BTreeMap::new(),
         };
         let result = resolve_plugin_artifacts(&manifest);
         assert!(result.is_err());
@@ -429,14 +453,23 @@ mod tests {
                 path: None,
                 maven: Some("org.example:plugin".to_owned()),
                 version: None,
+                git: None,
+                rev: None,
+                optional: false,
+                allow_unused: false,
+                allow_toolchain_mismatch: false,
+                id: None,
+                options: BTreeMap::new(),
             },
         );
         let manifest = Manifest {
             package: default_package(),
             toolchain: default_toolchain(),
             codegen: Default::default(),
+            build: Default::default(),
             dependencies: BTreeMap::new(),
             plugins,
+            features: BTreeMap::new(),
         };
         let result = resolve_plugin_artifacts(&manifest);
         assert!(result.is_err());
@@ -704,8 +737,10 @@ mod tests {
             package: default_package(),
             toolchain: default_toolchain(),
             codegen: Default::default(),
+            build: Default::default(),
             dependencies: BTreeMap::new(),
             plugins: BTreeMap::new(),
+            features: BTreeMap::new(),
         };
         let artifacts = resolve_plugin_artifacts(&manifest).unwrap();
         assert!(artifacts.is_empty());
@@ -720,6 +755,13 @@ mod tests {
                 path: None,
                 maven: Some("org.jetbrains.kotlin:kotlin-serialization-compiler-plugin".to_owned()),
                 version: Some("{kotlin}".to_owned()),
+                git: None,
+                rev: None,
+                optional: false,
+                allow_unused: false,
+                allow_toolchain_mismatch: false,
+                id: None,
+                options: BTreeMap::new(),
             },
         );
         plugins.insert(
@@ -728,14 +770,23 @@ mod tests {
                 path: None,
                 maven: Some("org.jetbrains.kotlin:kotlin-allopen-compiler-plugin".to_owned()),
                 version: Some("2.1.0".to_owned()),
+                git: None,
+                rev: None,
+                optional: false,
+                allow_unused: false,
+                allow_toolchain_mismatch: false,
+                id: None,
+                options: BTreeMap::new(),
             },
         );
         let manifest = Manifest {
             package: default_package(),
             toolchain: default_toolchain(),
             codegen: Default::default(),
+            build: Default::default(),
             dependencies: BTreeMap::new(),
             plugins,
+            features: BTreeMap::new(),
         };
         let artifacts = resolve_plugin_artifacts(&manifest).unwrap();
         assert_eq!(artifacts.len(), 2);
@@ -790,14 +841,23 @@ mod tests {
                 path: None,
                 maven: Some("nocolon".to_owned()),
                 version: Some("1.0.0".to_owned()),
+                git: None,
+                rev: None,
+                optional: false,
+                allow_unused: false,
+                allow_toolchain_mismatch: false,
+                id: None,
+                options: BTreeMap::new(),
             },
         );
         let manifest = Manifest {
             package: default_package(),
             toolchain: default_toolchain(),
             codegen: Default::default(),
+            build: Default::default(),
             dependencies: BTreeMap::new(),
             plugins,
+            features: BTreeMap::new(),
         };
         let result = resolve_plugin_artifacts(&manifest);
         assert!(result.is_err());
@@ -1008,6 +1068,13 @@ mod tests {
                 path: None,
                 maven: Some("org.jetbrains.kotlin:kotlin-serialization-compiler-plugin".to_owned()),
                 version: Some("{kotlin}".to_owned()),
+                git: None,
+                rev: None,
+                optional: false,
+                allow_unused: false,
+                allow_toolchain_mismatch: false,
+                id: None,
+                options: BTreeMap::new(),
             },
         );
         let mut dependencies = BTreeMap::new();
@@ -1017,14 +1084,23 @@ mod tests {
                 path: None,
                 maven: Some("org.jetbrains.kotlinx:kotlinx-coroutines-core".to_owned()),
                 version: Some("1.8.0".to_owned()),
+                git: None,
+                rev: None,
+                optional: false,
+                allow_unused: false,
+                allow_toolchain_mismatch: false,
+                id: None,
+                options: BTreeMap::new(),
             },
         );
         let manifest = Manifest {
             package: default_package(),
             toolchain: default_toolchain(),
             codegen: Default::default(),
+            build: Default::default(),
             dependencies,
             plugins,
+            features: BTreeMap::new(),
         };
         let artifacts = resolve_plugin_artifacts(&manifest).unwrap();
         // Only plugin artifacts, not dependency artifacts.
@@ -1065,7 +1141,16 @@ mod tests {
             name: "test-project".to_owned(),
             kind: konvoy_config::manifest::PackageKind::Bin,
             version: None,
+            description: None,
+            authors: Vec::new(),
+            license: None,
             entrypoint: "src/main.kt".to_owned(),
+            source_dirs: Vec::new(),
+            test_dir: None,
+            konvoy_version: None,
+            expand_env: false,
+            bin_name: None,
+            lib_name: None,
         }
     }
 
@@ -1073,6 +1158,10 @@ mod tests {
         konvoy_config::manifest::Toolchain {
             kotlin: "2.1.0".to_owned(),
             detekt: None,
+            ktlint: None,
+            jre: None,
+            language_version: None,
+            api_version: None,
         }
     }
 
@@ -1084,17 +1173,58 @@ mod tests {
                 path: None,
                 maven: Some(maven.to_owned()),
                 version: Some(version.to_owned()),
+                git: None,
+                rev: None,
+                optional: false,
+                allow_unused: false,
+                allow_toolchain_mismatch: false,
+                id: None,
+                options: BTreeMap::new(),
             },
         );
         Manifest {
             package: default_package(),
             toolchain: default_toolchain(),
             codegen: Default::default(),
+            build: Default::default(),
             dependencies: BTreeMap::new(),
             plugins,
+            features: BTreeMap::new(),
         }
     }
 
+    #[test]
+    fn plugin_options_are_tagged_with_the_plugin_id() {
+        let mut manifest = make_manifest_with_plugin(
+            "kotlin-allopen",
+            "org.jetbrains.kotlin:kotlin-allopen-compiler-plugin",
+            "{kotlin}",
+        );
+        assert!(plugin_options(&manifest).is_empty());
+
+        let spec = manifest.plugins.get_mut("kotlin-allopen").unwrap();
+        spec.id = Some("org.jetbrains.kotlin.allopen".to_owned());
+        spec.options
+            .insert("preset".to_owned(), "spring".to_owned());
+        spec.options
+            .insert("annotation".to_owned(), "com.example.Open".to_owned());
+        assert_eq!(
+            plugin_options(&manifest),
+            [
+                (
+                    "org.jetbrains.kotlin.allopen".to_owned(),
+                    "annotation".to_owned(),
+                    "com.example.Open".to_owned()
+                ),
+                (
+                    "org.jetbrains.kotlin.allopen".to_owned(),
+                    "preset".to_owned(),
+                    "spring".to_owned()
+                ),
+            ]
+        );
+    }
+
     #[test]
     fn ensure_plugin_artifacts_locked_mode_requires_hash() {
         // In --locked mode, a plugin without a lockfile hash should fail.
@@ -0,0 +1,61 @@
+//! Build progress events, so a caller embedding the engine can observe a build
+//! instead of scraping stderr.
+//!
+//! [`BuildOptions::progress`](crate::BuildOptions::progress) receives every
+//! event; [`StderrProgress`] prints the lines the `konvoy` CLI shows.
+//! Compiler diagnostics are not progress events and are still printed as
+//! konanc reports them.
+
+use std::fmt;
+use std::path::Path;
+
+/// Receives progress events from a build. Every method defaults to doing
+/// nothing, so an implementation only overrides the events it cares about.
+///
+/// Path dependencies build in parallel, so events may arrive from several
+/// threads at once.
+pub trait ProgressSink: fmt::Debug + Send + Sync {
+    /// `name` is about to be compiled into `output`. Not reported under
+    /// `quiet`.
+    fn on_compile_start(&self, name: &str, output: &Path) {
+        let _ = (name, output);
+    }
+
+    /// `name` was up to date and its artifact was reused from the cache. Not
+    /// reported under `quiet`.
+    fn on_cache_hit(&self, name: &str) {
+        let _ = name;
+    }
+
+    /// An artifact (a Maven klib or a compiler-plugin JAR) is about to be
+    /// downloaded from `url`.
+    fn on_download(&self, url: &str) {
+        let _ = url;
+    }
+
+    /// A condition that does not stop the build, e.g. cross-compiling for a
+    /// foreign target.
+    fn on_warning(&self, message: &str) {
+        let _ = message;
+    }
+}
+
+/// Prints progress to stderr in the CLI's format. Downloads are shown by
+/// their progress bars, so [`on_download`](ProgressSink::on_download) prints
+/// nothing.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StderrProgress;
+
+impl ProgressSink for StderrProgress {
+    fn on_compile_start(&self, name: &str, output: &Path) {
+        eprintln!("    Compiling {name} \u{2192} {}", output.display());
+    }
+
+    fn on_cache_hit(&self, name: &str) {
+        eprintln!("    Fresh {name} (cached)");
+    }
+
+    fn on_warning(&self, message: &str) {
+        eprintln!("    warning: {message}");
+    }
+}
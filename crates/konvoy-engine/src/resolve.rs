@@ -1,11 +1,15 @@
 //! Dependency graph resolution with topological ordering and cycle detection.
 
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
-use konvoy_config::manifest::{Manifest, PackageKind};
+use konvoy_config::lockfile::Lockfile;
+use konvoy_config::manifest::{DependencySpec, Manifest, PackageKind};
 
+use crate::common::ArtifactResolver;
 use crate::error::EngineError;
+use crate::features::{dependency_enabled, resolve_features};
 
 /// Three-color marking for DFS cycle detection.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -31,6 +35,77 @@ pub struct ResolvedDep {
     pub dep_names: Vec<String>,
     /// SHA-256 hash of the dependency's source tree (`src/**/*.kt`).
     pub source_hash: String,
+    /// Where a git dependency was fetched from, or `None` for a path dependency.
+    pub git: Option<GitPin>,
+}
+
+/// The commit a git dependency's `rev` resolved to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitPin {
+    /// Repository URL.
+    pub url: String,
+    /// The `rev` requested in `konvoy.toml`.
+    pub rev: String,
+    /// The concrete commit `rev` resolved to.
+    pub commit: String,
+}
+
+/// State shared by every step of the dependency walk.
+struct Walk<'a> {
+    root_kotlin: &'a str,
+    lockfile: &'a Lockfile,
+    resolver: ArtifactResolver<'a>,
+    /// Set when planning (`build --dry-run`): git dependencies that would have
+    /// to be fetched are recorded here and left out of the graph.
+    unfetched: Option<RefCell<Vec<String>>>,
+}
+
+impl Walk<'_> {
+    /// Locate a dependency declared in the project at `parent_root`: a path
+    /// dependency is resolved relative to it, a git dependency is fetched (or
+    /// reused from its pinned checkout).
+    ///
+    /// Returns `None` for a git dependency a plan records as unfetched.
+    fn locate(
+        &self,
+        parent_root: &Path,
+        name: &str,
+        spec: &DependencySpec,
+    ) -> Result<Option<(PathBuf, Option<GitPin>)>, EngineError> {
+        let Some((url, rev)) = spec.as_git() else {
+            return Ok(Some((
+                resolve_dep_path(parent_root, name, spec.path.as_deref())?,
+                None,
+            )));
+        };
+        let pinned = self.lockfile.git_commit(name, url, rev);
+        let (commit, checkout) = match &self.unfetched {
+            Some(unfetched) => {
+                let Some(existing) = self.resolver.existing_git_checkout(url, pinned)? else {
+                    unfetched
+                        .borrow_mut()
+                        .push(format!("git dependency `{name}` ({url} at {rev})"));
+                    return Ok(None);
+                };
+                existing
+            }
+            None => self
+                .resolver
+                .resolve_git_dependency(name, url, rev, pinned)?,
+        };
+        let canonical = checkout
+            .canonicalize()
+            .map_err(|_| EngineError::DependencyNotFound {
+                name: name.to_owned(),
+                path: checkout.display().to_string(),
+            })?;
+        let pin = GitPin {
+            url: url.to_owned(),
+            rev: rev.to_owned(),
+            commit,
+        };
+        Ok(Some((canonical, Some(pin))))
+    }
 }
 
 /// The fully resolved dependency graph in topological order.
@@ -44,26 +119,79 @@ pub struct ResolvedGraph {
 ///
 /// # Algorithm
 /// 1. For each dep in the manifest, resolve path relative to `project_root`, canonicalize.
+///    Git deps are checked out at the commit pinned in `lockfile` (or at the
+///    commit their `rev` currently resolves to) and treated like path deps.
 /// 2. Read the dep's `konvoy.toml`, validate it's `kind = "lib"`.
 /// 3. Recursively resolve transitive deps.
 /// 4. DFS with three-color marking (white→gray→black) for cycle detection.
 /// 5. Deduplicate diamond deps by canonical path.
-/// 6. Enforce all deps use same Kotlin version as root.
+/// 6. Enforce all deps use same Kotlin version as root, unless the declaring
+///    entry sets `allow_toolchain_mismatch` (the first declaration reached
+///    decides for a shared dependency).
 /// 7. Return topological order (leaves first).
 ///
+/// An `optional` dependency of the root is skipped unless `features` (the
+/// root's enabled features) names it; one of a dependency is skipped unless
+/// that dependency's default features enable it.
+///
 /// # Errors
 /// Returns an error if a cycle is detected, a dependency is missing, a dependency
-/// is not a library, or toolchain versions don't match.
+/// is not a library, toolchain versions don't match, or a git dependency cannot
+/// be fetched.
 pub fn resolve_dependencies(
     project_root: &Path,
     manifest: &Manifest,
+    features: &[String],
+    lockfile: &Lockfile,
+    resolver: ArtifactResolver<'_>,
+) -> Result<ResolvedGraph, EngineError> {
+    let walk = Walk {
+        root_kotlin: &manifest.toolchain.kotlin,
+        lockfile,
+        resolver,
+        unfetched: None,
+    };
+    walk_graph(project_root, manifest, features, &walk)
+}
+
+/// [`resolve_dependencies`] without fetching, for `build --dry-run`: a git
+/// dependency whose checkout is not already present is left out of the graph
+/// (with everything only it depends on) and described in the returned list,
+/// e.g. ``git dependency `utils` (https://example.com/utils.git at main)``.
+///
+/// # Errors
+/// As [`resolve_dependencies`], except that nothing is fetched.
+pub(crate) fn plan_dependencies(
+    project_root: &Path,
+    manifest: &Manifest,
+    features: &[String],
+    lockfile: &Lockfile,
+    resolver: ArtifactResolver<'_>,
+) -> Result<(ResolvedGraph, Vec<String>), EngineError> {
+    let walk = Walk {
+        root_kotlin: &manifest.toolchain.kotlin,
+        lockfile,
+        resolver,
+        unfetched: Some(RefCell::new(Vec::new())),
+    };
+    let graph = walk_graph(project_root, manifest, features, &walk)?;
+    Ok((
+        graph,
+        walk.unfetched.map(RefCell::into_inner).unwrap_or_default(),
+    ))
+}
+
+/// Walk the root's enabled dependencies into a checked topological order.
+fn walk_graph(
+    project_root: &Path,
+    manifest: &Manifest,
+    features: &[String],
+    walk: &Walk<'_>,
 ) -> Result<ResolvedGraph, EngineError> {
     if manifest.dependencies.is_empty() {
         return Ok(ResolvedGraph { order: Vec::new() });
     }
 
-    let root_kotlin = &manifest.toolchain.kotlin;
-
     // Collect all dependencies by canonical path to deduplicate diamonds.
     let mut visited: HashMap<PathBuf, ResolvedDep> = HashMap::new();
     // Three-color marking for cycle detection.
@@ -75,27 +203,83 @@ pub fn resolve_dependencies(
         if dep_spec.version.is_some() {
             continue; // Maven deps are resolved separately
         }
-        let dep_path = resolve_dep_path(project_root, dep_name, dep_spec.path.as_deref())?;
+        if !dependency_enabled(manifest, dep_name, features) {
+            continue;
+        }
+        let Some((dep_path, git)) = walk.locate(project_root, dep_name, dep_spec)? else {
+            continue;
+        };
 
         dfs(
             dep_name,
             &dep_path,
-            root_kotlin,
+            git,
+            dep_spec.allow_toolchain_mismatch,
+            walk,
             &mut visited,
             &mut color,
             &mut topo,
-            &mut vec![manifest.package.name.clone()],
+            &mut vec![(manifest.package.name.clone(), project_root.to_path_buf())],
         )?;
     }
 
-    let order = topo
+    let order: Vec<ResolvedDep> = topo
         .into_iter()
         .filter_map(|path| visited.remove(&path))
         .collect();
+    check_duplicate_libraries(manifest, &order)?;
 
     Ok(ResolvedGraph { order })
 }
 
+/// Reject a graph in which two dependencies resolve to the same library.
+///
+/// A path or git dependency is identified by its `[dependencies]` key and its
+/// library name (`lib_name`, else the package name); a Maven dependency by its
+/// key and its artifact id. A path/git identity that is also a Maven identity
+/// is a collision, and so is one manifest declaring the same Maven coordinate
+/// under two keys. Different manifests sharing a Maven coordinate is fine:
+/// the lockfile records it once.
+fn check_duplicate_libraries(
+    manifest: &Manifest,
+    order: &[ResolvedDep],
+) -> Result<(), EngineError> {
+    let duplicate = |name: &str| EngineError::DuplicateDependency {
+        name: name.to_owned(),
+    };
+
+    let built: HashSet<String> = order
+        .iter()
+        .flat_map(|dep| {
+            [
+                dep.name.clone(),
+                dep.manifest.package.artifact_stem().to_owned(),
+            ]
+        })
+        .collect();
+
+    let declaring = std::iter::once(manifest).chain(order.iter().map(|dep| &dep.manifest));
+    for declared_in in declaring {
+        let mut coordinates: HashSet<&str> = HashSet::new();
+        for (name, spec) in &declared_in.dependencies {
+            let Some((coordinate, _)) = spec.as_maven_coord() else {
+                continue;
+            };
+            if !coordinates.insert(coordinate) {
+                return Err(duplicate(coordinate));
+            }
+            if built.contains(name.as_str()) {
+                return Err(duplicate(name));
+            }
+            let artifact_id = coordinate.rsplit(':').next().unwrap_or(coordinate);
+            if built.contains(artifact_id) {
+                return Err(duplicate(artifact_id));
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Group dependencies into parallel build levels.
 ///
 /// Each level contains deps whose own dependencies are all in previous levels.
@@ -129,14 +313,18 @@ pub fn parallel_levels(graph: &ResolvedGraph) -> Vec<Vec<&ResolvedDep>> {
 }
 
 /// DFS traversal for topological sort with cycle detection.
+/// `allow_toolchain_mismatch` comes from the entry that declared `name`.
+#[allow(clippy::too_many_arguments)]
 fn dfs(
     name: &str,
     canonical_path: &Path,
-    root_kotlin: &str,
+    git: Option<GitPin>,
+    allow_toolchain_mismatch: bool,
+    walk: &Walk<'_>,
     visited: &mut HashMap<PathBuf, ResolvedDep>,
     color: &mut HashMap<PathBuf, DfsColor>,
     topo: &mut Vec<PathBuf>,
-    stack: &mut Vec<String>,
+    stack: &mut Vec<(String, PathBuf)>,
 ) -> Result<(), EngineError> {
     let current_color = color
         .get(canonical_path)
@@ -150,18 +338,27 @@ fn dfs(
 
     if current_color == DfsColor::Gray {
         // Currently on the stack — cycle detected.
-        stack.push(name.to_owned());
-        let cycle_start = stack.iter().position(|n| n == name).unwrap_or(0);
-        let cycle = stack
-            .get(cycle_start..)
-            .unwrap_or(stack.as_slice())
+        let cycle_start = stack
+            .iter()
+            .position(|(_, path)| path == canonical_path)
+            .unwrap_or(0);
+        let on_cycle = stack.get(cycle_start..).unwrap_or(stack.as_slice());
+        let cycle = on_cycle
+            .iter()
+            .map(|(n, _)| n.as_str())
+            .chain(std::iter::once(name))
+            .collect::<Vec<_>>()
             .join(" -> ");
-        return Err(EngineError::DependencyCycle { cycle });
+        let manifests = on_cycle
+            .iter()
+            .map(|(_, path)| path.join("konvoy.toml").display().to_string())
+            .collect();
+        return Err(EngineError::DependencyCycle { cycle, manifests });
     }
 
     // Mark gray (in-stack).
     color.insert(canonical_path.to_path_buf(), DfsColor::Gray);
-    stack.push(name.to_owned());
+    stack.push((name.to_owned(), canonical_path.to_path_buf()));
 
     // Read the dependency manifest.
     let manifest_path = canonical_path.join("konvoy.toml");
@@ -181,31 +378,39 @@ fn dfs(
         });
     }
 
-    // Validate: same Kotlin version.
-    if dep_manifest.toolchain.kotlin != root_kotlin {
+    // Validate: same Kotlin version, unless the declaring entry opted out.
+    if dep_manifest.toolchain.kotlin != walk.root_kotlin && !allow_toolchain_mismatch {
         return Err(EngineError::DependencyToolchainMismatch {
             name: name.to_owned(),
-            dep_version: dep_manifest.toolchain.kotlin.clone(),
-            root_version: root_kotlin.to_owned(),
+            dep_version: dep_manifest.toolchain.kotlin,
+            root_version: walk.root_kotlin.to_owned(),
         });
     }
 
-    // Recurse into this dep's own dependencies (skip Maven deps).
+    // Recurse into this dep's own dependencies (skip Maven deps, and optional
+    // ones its default features leave off — it builds with its defaults).
+    let dep_features = resolve_features(&dep_manifest, &[], true)?;
     let dep_names: Vec<String> = dep_manifest
         .dependencies
         .iter()
-        .filter(|(_, spec)| spec.version.is_none())
+        .filter(|(dep_key, spec)| {
+            spec.version.is_none() && dependency_enabled(&dep_manifest, dep_key, &dep_features)
+        })
         .map(|(dep_key, _)| dep_key.clone())
         .collect();
-    for (sub_name, sub_spec) in &dep_manifest.dependencies {
-        if sub_spec.version.is_some() {
-            continue; // Maven deps are resolved separately
-        }
-        let sub_path = resolve_dep_path(canonical_path, sub_name, sub_spec.path.as_deref())?;
+    for sub_name in &dep_names {
+        let Some(sub_spec) = dep_manifest.dependencies.get(sub_name) else {
+            continue;
+        };
+        let Some((sub_path, sub_git)) = walk.locate(canonical_path, sub_name, sub_spec)? else {
+            continue;
+        };
         dfs(
             sub_name,
             &sub_path,
-            root_kotlin,
+            sub_git,
+            sub_spec.allow_toolchain_mismatch,
+            walk,
             visited,
             color,
             topo,
@@ -218,8 +423,10 @@ fn dfs(
     stack.pop();
 
     // Compute source hash for integrity verification.
-    let src_dir = canonical_path.join("src");
-    let source_hash = konvoy_util::hash::sha256_dir(&src_dir, "**/*.kt")?;
+    let source_dirs = crate::sources::source_dirs(canonical_path, &dep_manifest);
+    let ignore = konvoy_util::ignore::IgnoreRules::load(canonical_path)?;
+    let source_hash =
+        crate::sources::hash_sources(&source_dirs, crate::sources::SOURCE_GLOB, &ignore)?;
 
     visited.insert(
         canonical_path.to_path_buf(),
@@ -229,6 +436,7 @@ fn dfs(
             manifest: dep_manifest,
             dep_names,
             source_hash,
+            git,
         },
     );
     topo.push(canonical_path.to_path_buf());
@@ -284,6 +492,100 @@ fn resolve_dep_path(
         })
 }
 
+/// Resolves `[dependencies]` version ranges (`^1.8`, `>=1.8, <2.0`) to the
+/// concrete Maven versions `konvoy update` pins in `konvoy.lock`.
+///
+/// An exact version is used as-is. A range keeps the version the lockfile
+/// already pins for the coordinate while it still satisfies the range, so
+/// re-resolving is stable; otherwise it picks the highest matching release
+/// listed in the artifact's `maven-metadata.xml`. Under `--locked` the metadata
+/// fetch is refused, so a range resolves only from the lockfile.
+pub(crate) struct VersionResolution<'a> {
+    lockfile: &'a Lockfile,
+    /// Ignore the lockfile's pins and pick the newest matching release
+    /// (explicit `konvoy update`).
+    refresh: bool,
+    resolver: ArtifactResolver<'a>,
+    /// Published versions per `groupId:artifactId`, fetched at most once.
+    published: HashMap<String, Vec<String>>,
+}
+
+impl<'a> VersionResolution<'a> {
+    pub(crate) fn new(
+        lockfile: &'a Lockfile,
+        refresh: bool,
+        resolver: ArtifactResolver<'a>,
+    ) -> Self {
+        Self {
+            lockfile,
+            refresh,
+            resolver,
+            published: HashMap::new(),
+        }
+    }
+
+    /// A copy of `manifest` with every Maven dependency's `version` replaced by
+    /// the concrete version it resolves to.
+    pub(crate) fn resolve_manifest(
+        &mut self,
+        manifest: &Manifest,
+    ) -> Result<Manifest, EngineError> {
+        let mut resolved = manifest.clone();
+        for (name, spec) in &mut resolved.dependencies {
+            let Some((maven, version)) = spec.as_maven_coord() else {
+                continue;
+            };
+            let concrete = self.resolve(name, maven, version)?;
+            spec.version = Some(concrete);
+        }
+        Ok(resolved)
+    }
+
+    /// Resolve one dependency's `version` requirement to a concrete version.
+    pub(crate) fn resolve(
+        &mut self,
+        name: &str,
+        maven: &str,
+        requirement: &str,
+    ) -> Result<String, EngineError> {
+        let Ok(req) = konvoy_config::version_req::VersionReq::parse(requirement) else {
+            // Rejected at manifest load; pass through unchanged if it slips by.
+            return Ok(requirement.to_owned());
+        };
+        if let Some(exact) = req.as_exact() {
+            return Ok(exact.to_owned());
+        }
+
+        if !self.refresh {
+            if let Some(pinned) = req.best_match(self.lockfile.maven_versions(maven)) {
+                return Ok(pinned.to_owned());
+            }
+        }
+
+        if !self.published.contains_key(maven) {
+            let (group_id, artifact_id) = crate::common::split_maven_coordinate(maven)?;
+            let versions = self
+                .resolver
+                .fetch_published_versions(group_id, artifact_id)?;
+            self.published.insert(maven.to_owned(), versions);
+        }
+        let published = self
+            .published
+            .get(maven)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let chosen = req
+            .best_match(published.iter().map(String::as_str))
+            .ok_or_else(|| EngineError::NoMatchingVersion {
+                name: name.to_owned(),
+                maven: maven.to_owned(),
+                requirement: requirement.to_owned(),
+            })?;
+        eprintln!("  Resolved {name} {requirement} to {chosen}");
+        Ok(chosen.to_owned())
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
@@ -291,6 +593,16 @@ mod tests {
 
     use super::*;
 
+    fn resolve(project_root: &Path, manifest: &Manifest) -> Result<ResolvedGraph, EngineError> {
+        resolve_dependencies(
+            project_root,
+            manifest,
+            &[],
+            &Lockfile::default(),
+            crate::common::test_resolver(false, false),
+        )
+    }
+
     fn write_manifest(dir: &Path, name: &str, kind: &str, deps: &str) {
         fs::create_dir_all(dir.join("src")).unwrap();
         fs::write(dir.join("src/lib.kt"), "// lib").unwrap();
@@ -318,7 +630,7 @@ mod tests {
         let tmp = tempfile::tempdir().unwrap();
         write_manifest(tmp.path(), "root", "bin", "");
         let manifest = Manifest::from_path(&tmp.path().join("konvoy.toml")).unwrap();
-        let graph = resolve_dependencies(tmp.path(), &manifest).unwrap();
+        let graph = resolve(tmp.path(), &manifest).unwrap();
         assert!(graph.order.is_empty());
     }
 
@@ -337,11 +649,145 @@ mod tests {
         );
 
         let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
-        let graph = resolve_dependencies(&root_dir, &manifest).unwrap();
+        let graph = resolve(&root_dir, &manifest).unwrap();
         assert_eq!(graph.order.len(), 1);
         assert_eq!(graph.order.first().unwrap().name, "my-lib");
     }
 
+    #[test]
+    fn path_and_maven_dependency_with_same_library_name_collide() {
+        // The path dependency `core` builds a library named `json`.
+        let tmp = tempfile::tempdir().unwrap();
+        write_manifest(&tmp.path().join("core"), "json", "lib", "");
+        let root_dir = tmp.path().join("root");
+        write_manifest(
+            &root_dir,
+            "root",
+            "bin",
+            "core = { path = \"../core\" }\nkjson = { maven = \"org.example:json\", version = \"1.0.0\" }\n",
+        );
+        let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
+
+        let err = resolve(&root_dir, &manifest).unwrap_err();
+        assert!(
+            matches!(&err, EngineError::DuplicateDependency { name } if name == "json"),
+            "got: {err:?}"
+        );
+    }
+
+    #[test]
+    fn maven_artifact_matching_path_dependency_name_collides() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_manifest(&tmp.path().join("core"), "core", "lib", "");
+        let root_dir = tmp.path().join("root");
+        write_manifest(
+            &root_dir,
+            "root",
+            "bin",
+            "core = { path = \"../core\" }\nkcore = { maven = \"org.example:core\", version = \"1.0.0\" }\n",
+        );
+        let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
+
+        let err = resolve(&root_dir, &manifest).unwrap_err().to_string();
+        assert!(err.contains("`core`"), "got: {err}");
+    }
+
+    #[test]
+    fn same_maven_coordinate_twice_in_one_manifest_collides() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_manifest(
+            tmp.path(),
+            "root",
+            "bin",
+            "a = { maven = \"org.example:json\", version = \"1.0.0\" }\nb = { maven = \"org.example:json\", version = \"1.0.0\" }\n",
+        );
+        let manifest = Manifest::from_path(&tmp.path().join("konvoy.toml")).unwrap();
+
+        let err = resolve(tmp.path(), &manifest).unwrap_err();
+        assert!(
+            matches!(&err, EngineError::DuplicateDependency { name } if name == "org.example:json"),
+            "got: {err:?}"
+        );
+    }
+
+    #[test]
+    fn shared_maven_coordinate_across_manifests_is_allowed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let json = "json = { maven = \"org.example:json\", version = \"1.0.0\" }\n";
+        write_manifest(&tmp.path().join("core"), "core", "lib", json);
+        let root_dir = tmp.path().join("root");
+        write_manifest(
+            &root_dir,
+            "root",
+            "bin",
+            &format!("core = {{ path = \"../core\" }}\n{json}"),
+        );
+        let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
+
+        assert_eq!(resolve(&root_dir, &manifest).unwrap().order.len(), 1);
+    }
+
+    #[test]
+    fn optional_dependency_excluded_unless_enabled() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_manifest(&tmp.path().join("extra"), "extra", "lib", "");
+        write_manifest(&tmp.path().join("core"), "core", "lib", "");
+        let root_dir = tmp.path().join("root");
+        write_manifest(
+            &root_dir,
+            "root",
+            "bin",
+            "core = { path = \"../core\" }\nextra = { path = \"../extra\", optional = true }\n",
+        );
+        let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
+
+        let names = |features: &[String]| -> Vec<String> {
+            resolve_dependencies(
+                &root_dir,
+                &manifest,
+                features,
+                &Lockfile::default(),
+                crate::common::test_resolver(false, false),
+            )
+            .unwrap()
+            .order
+            .into_iter()
+            .map(|dep| dep.name)
+            .collect()
+        };
+        assert_eq!(names(&[]), ["core"]);
+        assert_eq!(names(&["extra".to_owned()]), ["core", "extra"]);
+    }
+
+    #[test]
+    fn optional_dependency_of_a_dependency_follows_its_defaults() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_manifest(&tmp.path().join("on"), "on", "lib", "");
+        write_manifest(&tmp.path().join("off"), "off", "lib", "");
+        let mid_dir = tmp.path().join("mid");
+        write_manifest(
+            &mid_dir,
+            "mid",
+            "lib",
+            "on = { path = \"../on\", optional = true }\noff = { path = \"../off\", optional = true }\n",
+        );
+        let mid_manifest = fs::read_to_string(mid_dir.join("konvoy.toml")).unwrap();
+        fs::write(
+            mid_dir.join("konvoy.toml"),
+            format!("{mid_manifest}\n[features]\ndefault = [\"on\"]\n"),
+        )
+        .unwrap();
+        let root_dir = tmp.path().join("root");
+        write_manifest(&root_dir, "root", "bin", "mid = { path = \"../mid\" }\n");
+
+        let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
+        let graph = resolve(&root_dir, &manifest).unwrap();
+        let names: Vec<&str> = graph.order.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["on", "mid"]);
+        let mid = graph.order.iter().find(|d| d.name == "mid").unwrap();
+        assert_eq!(mid.dep_names, ["on"]);
+    }
+
     #[test]
     fn transitive_dependencies() {
         let tmp = tempfile::tempdir().unwrap();
@@ -359,7 +805,7 @@ mod tests {
         write_manifest(&root_dir, "root", "bin", "mid = { path = \"../mid\" }\n");
 
         let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
-        let graph = resolve_dependencies(&root_dir, &manifest).unwrap();
+        let graph = resolve(&root_dir, &manifest).unwrap();
         assert_eq!(graph.order.len(), 2);
         // leaf must come before mid (topo order)
         assert_eq!(graph.order.first().unwrap().name, "leaf");
@@ -392,7 +838,7 @@ mod tests {
         );
 
         let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
-        let graph = resolve_dependencies(&root_dir, &manifest).unwrap();
+        let graph = resolve(&root_dir, &manifest).unwrap();
         // shared should only appear once
         let shared_count = graph.order.iter().filter(|d| d.name == "shared").count();
         assert_eq!(shared_count, 1);
@@ -416,10 +862,23 @@ mod tests {
         write_manifest(&root_dir, "root", "bin", "a = { path = \"../a\" }\n");
 
         let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
-        let result = resolve_dependencies(&root_dir, &manifest);
+        let result = resolve(&root_dir, &manifest);
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("cycle"), "error was: {err}");
+        assert!(err.contains("a -> b -> a"), "error was: {err}");
+        // Both manifests on the cycle are named; the root's is not.
+        let a_manifest = a_dir.canonicalize().unwrap().join("konvoy.toml");
+        let b_manifest = b_dir.canonicalize().unwrap().join("konvoy.toml");
+        assert!(
+            err.contains(&a_manifest.display().to_string()),
+            "error was: {err}"
+        );
+        assert!(
+            err.contains(&b_manifest.display().to_string()),
+            "error was: {err}"
+        );
+        assert!(!err.contains("root/konvoy.toml"), "error was: {err}");
     }
 
     #[test]
@@ -434,7 +893,7 @@ mod tests {
         );
 
         let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
-        let result = resolve_dependencies(&root_dir, &manifest);
+        let result = resolve(&root_dir, &manifest);
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("not found"), "error was: {err}");
@@ -452,7 +911,7 @@ mod tests {
         write_manifest(&root_dir, "root", "bin", "dep = { path = \"../dep\" }\n");
 
         let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
-        let result = resolve_dependencies(&root_dir, &manifest);
+        let result = resolve(&root_dir, &manifest);
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("lib"), "error was: {err}");
@@ -473,7 +932,7 @@ mod tests {
         );
 
         let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
-        let graph = resolve_dependencies(&root_dir, &manifest).unwrap();
+        let graph = resolve(&root_dir, &manifest).unwrap();
         assert_eq!(graph.order.len(), 1);
         let dep = graph.order.first().unwrap();
         assert!(
@@ -497,13 +956,13 @@ mod tests {
         );
 
         let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
-        let graph1 = resolve_dependencies(&root_dir, &manifest).unwrap();
+        let graph1 = resolve(&root_dir, &manifest).unwrap();
         let hash1 = graph1.order.first().unwrap().source_hash.clone();
 
         // Modify the dependency source.
         fs::write(lib_dir.join("src/lib.kt"), "// modified").unwrap();
 
-        let graph2 = resolve_dependencies(&root_dir, &manifest).unwrap();
+        let graph2 = resolve(&root_dir, &manifest).unwrap();
         let hash2 = graph2.order.first().unwrap().source_hash.clone();
 
         assert_ne!(
@@ -530,13 +989,40 @@ mod tests {
         write_manifest(&root_dir, "root", "bin", "dep = { path = \"../dep\" }\n");
 
         let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
-        let result = resolve_dependencies(&root_dir, &manifest);
+        let result = resolve(&root_dir, &manifest);
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("2.0.0"), "error was: {err}");
         assert!(err.contains("2.1.0"), "error was: {err}");
     }
 
+    #[test]
+    fn toolchain_mismatch_allowed_with_opt_in() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dep_dir = tmp.path().join("dep");
+        fs::create_dir_all(dep_dir.join("src")).unwrap();
+        fs::write(dep_dir.join("src/lib.kt"), "// lib").unwrap();
+        fs::write(
+            dep_dir.join("konvoy.toml"),
+            "[package]\nname = \"dep\"\nkind = \"lib\"\n\n[toolchain]\nkotlin = \"2.0.0\"\n",
+        )
+        .unwrap();
+
+        let root_dir = tmp.path().join("root");
+        write_manifest(
+            &root_dir,
+            "root",
+            "bin",
+            "dep = { path = \"../dep\", allow_toolchain_mismatch = true }\n",
+        );
+
+        let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
+        let graph = resolve(&root_dir, &manifest).unwrap();
+        let dep = graph.order.first().unwrap();
+        assert_eq!(dep.name, "dep");
+        assert_eq!(dep.manifest.toolchain.kotlin, "2.0.0");
+    }
+
     #[test]
     fn sibling_dependency_allowed() {
         // ../sibling-lib is a common pattern and must work
@@ -553,7 +1039,7 @@ mod tests {
         );
 
         let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
-        let graph = resolve_dependencies(&root_dir, &manifest).unwrap();
+        let graph = resolve(&root_dir, &manifest).unwrap();
         assert_eq!(graph.order.len(), 1);
         assert_eq!(graph.order.first().unwrap().name, "sibling-lib");
     }
@@ -570,7 +1056,7 @@ mod tests {
         );
 
         let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
-        let result = resolve_dependencies(&root_dir, &manifest);
+        let result = resolve(&root_dir, &manifest);
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("escapes the project tree"), "error was: {err}");
@@ -598,7 +1084,7 @@ mod tests {
         );
 
         let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
-        let graph = resolve_dependencies(&root_dir, &manifest).unwrap();
+        let graph = resolve(&root_dir, &manifest).unwrap();
         assert_eq!(graph.order.len(), 1);
         assert_eq!(graph.order.first().unwrap().name, "lib");
     }
@@ -616,7 +1102,7 @@ mod tests {
         );
 
         let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
-        let result = resolve_dependencies(&root_dir, &manifest);
+        let result = resolve(&root_dir, &manifest);
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("escapes the project tree"), "error was: {err}");
@@ -634,7 +1120,7 @@ mod tests {
         );
 
         let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
-        let result = resolve_dependencies(&root_dir, &manifest);
+        let result = resolve(&root_dir, &manifest);
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("escapes the project tree"), "error was: {err}");
@@ -653,6 +1139,7 @@ mod tests {
             .unwrap(),
             dep_names: dep_names.iter().map(|s| s.to_string()).collect(),
             source_hash: "deadbeef".to_owned(),
+            git: None,
         }
     }
 
@@ -763,7 +1250,7 @@ mod tests {
         );
 
         let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
-        let graph = resolve_dependencies(&root_dir, &manifest).unwrap();
+        let graph = resolve(&root_dir, &manifest).unwrap();
         let levels = parallel_levels(&graph);
 
         assert_eq!(levels.len(), 1);
@@ -794,7 +1281,7 @@ mod tests {
         );
 
         let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
-        let graph = resolve_dependencies(&root_dir, &manifest).unwrap();
+        let graph = resolve(&root_dir, &manifest).unwrap();
         let levels = parallel_levels(&graph);
 
         // Level 0: shared (leaf); Level 1: a and b (siblings).
@@ -835,7 +1322,7 @@ mod tests {
         );
 
         let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
-        let graph = resolve_dependencies(&root_dir, &manifest).unwrap();
+        let graph = resolve(&root_dir, &manifest).unwrap();
         let levels = parallel_levels(&graph);
 
         // All three are independent → single level.
@@ -858,7 +1345,7 @@ mod tests {
         write_manifest(&root_dir, "root", "bin", "mid = { path = \"../mid\" }\n");
 
         let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
-        let graph = resolve_dependencies(&root_dir, &manifest).unwrap();
+        let graph = resolve(&root_dir, &manifest).unwrap();
         let levels = parallel_levels(&graph);
 
         // leaf first, then mid — strictly sequential.
@@ -911,7 +1398,7 @@ mod tests {
         );
 
         let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
-        let graph = resolve_dependencies(&root_dir, &manifest).unwrap();
+        let graph = resolve(&root_dir, &manifest).unwrap();
         let levels = parallel_levels(&graph);
 
         // Level 0: shared and logging (both are leaves).
@@ -969,7 +1456,7 @@ kotlinx-coroutines = { maven = "org.jetbrains.kotlinx:kotlinx-coroutines-core",
         .unwrap();
 
         let manifest = Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap();
-        let graph = resolve_dependencies(&root_dir, &manifest).unwrap();
+        let graph = resolve(&root_dir, &manifest).unwrap();
 
         // Only the path dep should appear in the resolved graph
         assert_eq!(graph.order.len(), 1);
@@ -980,6 +1467,198 @@ kotlinx-coroutines = { maven = "org.jetbrains.kotlinx:kotlinx-coroutines-core",
         assert!(!has_maven, "Maven dep should not appear in resolved graph");
     }
 
+    fn write_git_root(root_dir: &Path, url: &str) -> Manifest {
+        write_manifest(
+            root_dir,
+            "root",
+            "bin",
+            &format!("gitlib = {{ git = \"{url}\", rev = \"main\" }}\n"),
+        );
+        Manifest::from_path(&root_dir.join("konvoy.toml")).unwrap()
+    }
+
+    #[test]
+    fn git_dependency_resolved_to_commit() {
+        let _cache = crate::git::tests::isolated_cache();
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path().join("gitlib");
+        let commit = crate::git::tests::init_lib_repo(&repo, "gitlib");
+        let url = crate::git::tests::file_url(&repo);
+        let manifest = write_git_root(&tmp.path().join("root"), &url);
+
+        let graph = resolve(&tmp.path().join("root"), &manifest).unwrap();
+        let dep = graph.order.first().unwrap();
+        assert_eq!(dep.name, "gitlib");
+        let pin = dep.git.as_ref().unwrap();
+        assert_eq!(pin.commit, commit);
+        assert_eq!(pin.rev, "main");
+        assert!(dep.project_root.join("src/lib.kt").exists());
+    }
+
+    #[test]
+    fn git_dependency_uses_commit_pinned_in_lockfile() {
+        use konvoy_config::lockfile::{DepSource, DependencyLock};
+
+        let _cache = crate::git::tests::isolated_cache();
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path().join("gitlib");
+        let pinned = crate::git::tests::init_lib_repo(&repo, "gitlib");
+        let url = crate::git::tests::file_url(&repo);
+        let manifest = write_git_root(&tmp.path().join("root"), &url);
+
+        // Move `main` past the pinned commit.
+        fs::write(repo.join("src/lib.kt"), "fun gitlib() = 2\n").unwrap();
+        crate::git::tests::git(
+            &repo,
+            &[
+                "-c",
+                "user.name=test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "--quiet",
+                "-am",
+                "bump",
+            ],
+        );
+
+        let mut lockfile = Lockfile::with_toolchain("2.1.0");
+        lockfile.dependencies.push(DependencyLock {
+            name: "gitlib".to_owned(),
+            source: DepSource::Git {
+                url: url.clone(),
+                rev: "main".to_owned(),
+                commit: pinned.clone(),
+            },
+            source_hash: String::new(),
+        });
+        let graph = resolve_dependencies(
+            &tmp.path().join("root"),
+            &manifest,
+            &[],
+            &lockfile,
+            crate::common::test_resolver(false, true),
+        )
+        .unwrap();
+        let dep = graph.order.first().unwrap();
+        assert_eq!(dep.git.as_ref().unwrap().commit, pinned);
+        let source = fs::read_to_string(dep.project_root.join("src/lib.kt")).unwrap();
+        assert_eq!(source, "fun gitlib() = 1\n");
+    }
+
+    #[test]
+    fn git_dependency_unpinned_rejected_when_locked() {
+        let _cache = crate::git::tests::isolated_cache();
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path().join("gitlib");
+        crate::git::tests::init_lib_repo(&repo, "gitlib");
+        let url = crate::git::tests::file_url(&repo);
+        let manifest = write_git_root(&tmp.path().join("root"), &url);
+
+        let err = resolve_dependencies(
+            &tmp.path().join("root"),
+            &manifest,
+            &[],
+            &Lockfile::default(),
+            crate::common::test_resolver(false, true),
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, EngineError::LockfileUpdateRequired),
+            "unexpected error: {err}"
+        );
+    }
+
+    fn pinned(version: &str) -> Lockfile {
+        Lockfile {
+            dependencies: vec![konvoy_config::lockfile::DependencyLock {
+                name: "coroutines".to_owned(),
+                source: konvoy_config::lockfile::DepSource::Maven {
+                    version: version.to_owned(),
+                    maven: "org.example:coroutines".to_owned(),
+                    targets: std::collections::BTreeMap::new(),
+                    required_by: Vec::new(),
+                    classifier: None,
+                    hash_algorithm: konvoy_config::lockfile::HashAlgorithm::Sha256,
+                },
+                source_hash: "h".to_owned(),
+            }],
+            ..Lockfile::default()
+        }
+    }
+
+    #[test]
+    fn version_resolution_passes_exact_versions_through() {
+        // Offline: an exact version must never touch the network.
+        let lockfile = Lockfile::default();
+        let mut versions =
+            VersionResolution::new(&lockfile, true, crate::common::test_resolver(true, true));
+        let v = versions
+            .resolve("coroutines", "org.example:coroutines", "1.8.0")
+            .unwrap();
+        assert_eq!(v, "1.8.0");
+    }
+
+    #[test]
+    fn version_resolution_keeps_a_satisfying_lock_pin() {
+        let lockfile = pinned("1.8.1");
+        let mut versions =
+            VersionResolution::new(&lockfile, false, crate::common::test_resolver(true, true));
+        let v = versions
+            .resolve("coroutines", "org.example:coroutines", "^1.8")
+            .unwrap();
+        assert_eq!(v, "1.8.1");
+    }
+
+    #[test]
+    fn version_resolution_locked_never_fetches_metadata() {
+        // The pin no longer satisfies the range; --locked must refuse rather
+        // than consult maven-metadata.xml.
+        let lockfile = pinned("1.7.3");
+        let mut versions =
+            VersionResolution::new(&lockfile, false, crate::common::test_resolver(false, true));
+        let err = versions
+            .resolve("coroutines", "org.example:coroutines", "^1.8")
+            .unwrap_err();
+        assert!(matches!(err, EngineError::LockfileUpdateRequired), "{err}");
+    }
+
+    #[test]
+    fn version_resolution_refresh_consults_metadata() {
+        // Refreshing ignores the pin and goes to the network (refused offline).
+        let lockfile = pinned("1.8.1");
+        let mut versions =
+            VersionResolution::new(&lockfile, true, crate::common::test_resolver(true, false));
+        let err = versions
+            .resolve("coroutines", "org.example:coroutines", "^1.8")
+            .unwrap_err();
+        assert!(err.to_string().contains("--offline"), "{err}");
+    }
+
+    #[test]
+    fn version_resolution_rewrites_manifest_versions() {
+        let manifest = Manifest::from_str(
+            r#"
+[package]
+name = "app"
+
+[toolchain]
+kotlin = "2.1.0"
+
+[dependencies]
+coroutines = { maven = "org.example:coroutines", version = "~1.8.0" }
+"#,
+            "konvoy.toml",
+        )
+        .unwrap();
+        let lockfile = pinned("1.8.4");
+        let mut versions =
+            VersionResolution::new(&lockfile, false, crate::common::test_resolver(true, true));
+        let resolved = versions.resolve_manifest(&manifest).unwrap();
+        let spec = resolved.dependencies.get("coroutines").unwrap();
+        assert_eq!(spec.version.as_deref(), Some("1.8.4"));
+    }
+
     mod property_tests {
         use super::*;
         use proptest::prelude::*;
@@ -0,0 +1,312 @@
+//! SARIF reporting for lint results.
+//!
+//! Renders detekt findings as a SARIF 2.1.0 log so code-scanning services
+//! (e.g. GitHub code scanning) can annotate them on the source.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::detekt::LintResult;
+use crate::error::EngineError;
+
+/// The SARIF schema version emitted.
+const SARIF_VERSION: &str = "2.1.0";
+
+/// The JSON schema the emitted log conforms to.
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// A SARIF log: the top-level document.
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    pub schema: &'static str,
+    pub version: &'static str,
+    pub runs: Vec<SarifRun>,
+}
+
+/// One run of one analysis tool.
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifRun {
+    pub tool: SarifTool,
+    pub results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifTool {
+    pub driver: SarifDriver,
+}
+
+/// The analysis tool and the rules it can report.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifDriver {
+    pub name: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub information_uri: &'static str,
+    pub rules: Vec<SarifRule>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifRule {
+    pub id: String,
+}
+
+/// A single finding.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    pub rule_id: String,
+    /// Index of `rule_id` in the driver's `rules`.
+    pub rule_index: usize,
+    pub level: &'static str,
+    pub message: SarifMessage,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub locations: Vec<SarifLocation>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifMessage {
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifLocation {
+    pub physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifPhysicalLocation {
+    pub artifact_location: SarifArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<SarifRegion>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifArtifactLocation {
+    /// `/`-separated path relative to `uri_base_id`.
+    pub uri: String,
+    pub uri_base_id: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRegion {
+    pub start_line: u32,
+}
+
+/// Build a SARIF 2.1.0 log for `result`.
+///
+/// Each distinct detekt rule becomes a rule of the `detekt` driver, and each
+/// diagnostic a result referencing it. File paths are made relative to `root`
+/// (against the `%SRCROOT%` base) so they resolve in the repository checkout.
+pub fn to_sarif(result: &LintResult, root: &Path) -> SarifLog {
+    let mut rule_ids: Vec<&str> = result.diagnostics.iter().map(|d| d.rule.as_str()).collect();
+    rule_ids.sort_unstable();
+    rule_ids.dedup();
+
+    let results = result
+        .diagnostics
+        .iter()
+        .map(|diag| SarifResult {
+            rule_id: diag.rule.clone(),
+            rule_index: rule_ids
+                .binary_search(&diag.rule.as_str())
+                .unwrap_or_default(),
+            level: "warning",
+            message: SarifMessage {
+                text: diag.message.clone(),
+            },
+            locations: diag
+                .file
+                .iter()
+                .map(|file| SarifLocation {
+                    physical_location: SarifPhysicalLocation {
+                        artifact_location: SarifArtifactLocation {
+                            uri: relative_uri(file, root),
+                            uri_base_id: "%SRCROOT%",
+                        },
+                        // SARIF lines are 1-based; 0 means detekt did not know.
+                        region: diag
+                            .line
+                            .filter(|l| *l > 0)
+                            .map(|start_line| SarifRegion { start_line }),
+                    },
+                })
+                .collect(),
+        })
+        .collect();
+
+    let driver = SarifDriver {
+        name: "detekt",
+        version: Some(result.detekt_version.clone()).filter(|v| !v.is_empty()),
+        information_uri: "https://detekt.dev",
+        rules: rule_ids
+            .iter()
+            .map(|id| SarifRule {
+                id: (*id).to_owned(),
+            })
+            .collect(),
+    };
+
+    SarifLog {
+        schema: SARIF_SCHEMA,
+        version: SARIF_VERSION,
+        runs: vec![SarifRun {
+            tool: SarifTool { driver },
+            results,
+        }],
+    }
+}
+
+/// Render `result` as a pretty-printed SARIF document.
+///
+/// # Errors
+/// Returns an error if the log cannot be serialized.
+pub fn render_sarif(result: &LintResult, root: &Path) -> Result<String, EngineError> {
+    serde_json::to_string_pretty(&to_sarif(result, root)).map_err(|source| {
+        EngineError::JsonSerialize {
+            what: "SARIF report",
+            source,
+        }
+    })
+}
+
+/// Write a SARIF report for `result` to `path`, creating parent directories.
+///
+/// # Errors
+/// Returns an error if the log cannot be serialized, the parent directory
+/// cannot be created, or the file cannot be written.
+pub fn write_sarif_report(
+    path: &Path,
+    result: &LintResult,
+    root: &Path,
+) -> Result<(), EngineError> {
+    let sarif = render_sarif(result, root)?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        konvoy_util::fs::ensure_dir(parent)?;
+    }
+    konvoy_util::fs::write_file(path, sarif)?;
+    Ok(())
+}
+
+/// The `/`-separated path of `file` relative to `root`.
+///
+/// detekt reports the paths it was given, which are absolute under the project
+/// root; paths outside it (or already relative) are kept as reported.
+fn relative_uri(file: &str, root: &Path) -> String {
+    let path = Path::new(file);
+    let relative = path
+        .strip_prefix(root)
+        .ok()
+        .map(Path::to_path_buf)
+        .or_else(|| {
+            let canonical = root.canonicalize().ok()?;
+            path.strip_prefix(canonical).ok().map(Path::to_path_buf)
+        })
+        .unwrap_or_else(|| path.to_path_buf());
+    relative.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::detekt::DetektDiagnostic;
+    use serde_json::Value;
+
+    fn diag(rule: &str, file: Option<&str>, line: Option<u32>) -> DetektDiagnostic {
+        DetektDiagnostic {
+            rule: rule.to_owned(),
+            message: format!("{rule} finding"),
+            file: file.map(str::to_owned),
+            line,
+        }
+    }
+
+    fn lint_result(diagnostics: Vec<DetektDiagnostic>) -> LintResult {
+        LintResult {
+            success: diagnostics.is_empty(),
+            finding_count: diagnostics.len(),
+            diagnostics,
+            raw_output: String::new(),
+            fixed_files: Vec::new(),
+            detekt_version: "1.23.7".to_owned(),
+        }
+    }
+
+    #[test]
+    fn sarif_lists_each_rule_once_and_indexes_results() {
+        let root = Path::new("/work/app");
+        let result = lint_result(vec![
+            diag("MagicNumber", Some("/work/app/src/main.kt"), Some(3)),
+            diag("LongMethod", Some("/work/app/src/util.kt"), Some(10)),
+            diag("MagicNumber", Some("/work/app/src/util.kt"), Some(12)),
+        ]);
+        let sarif = serde_json::to_value(to_sarif(&result, root)).unwrap();
+
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "detekt");
+        assert_eq!(run["tool"]["driver"]["version"], "1.23.7");
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        let ids: Vec<&str> = rules.iter().map(|r| r["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, vec!["LongMethod", "MagicNumber"]);
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0]["ruleId"], "MagicNumber");
+        assert_eq!(results[0]["ruleIndex"], 1);
+        assert_eq!(results[1]["ruleIndex"], 0);
+        assert_eq!(results[0]["message"]["text"], "MagicNumber finding");
+    }
+
+    #[test]
+    fn sarif_locations_are_relative_to_the_project_root() {
+        let root = Path::new("/work/app");
+        let result = lint_result(vec![diag(
+            "MagicNumber",
+            Some("/work/app/src/nested/main.kt"),
+            Some(7),
+        )]);
+        let sarif = serde_json::to_value(to_sarif(&result, root)).unwrap();
+
+        let physical = &sarif["runs"][0]["results"][0]["locations"][0]["physicalLocation"];
+        assert_eq!(physical["artifactLocation"]["uri"], "src/nested/main.kt");
+        assert_eq!(physical["artifactLocation"]["uriBaseId"], "%SRCROOT%");
+        assert_eq!(physical["region"]["startLine"], 7);
+    }
+
+    #[test]
+    fn sarif_keeps_relative_paths_and_omits_unknown_locations() {
+        let root = Path::new("/work/app");
+        let result = lint_result(vec![
+            diag("MaxLineLength", Some("src/main.kt"), None),
+            diag("TooManyFunctions", None, None),
+        ]);
+        let sarif = serde_json::to_value(to_sarif(&result, root)).unwrap();
+
+        let results = sarif["runs"][0]["results"].as_array().unwrap();
+        let physical = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(physical["artifactLocation"]["uri"], "src/main.kt");
+        assert!(physical.get("region").is_none());
+        assert!(results[1].get("locations").is_none());
+    }
+
+    #[test]
+    fn write_sarif_report_creates_parent_dirs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("reports").join("lint.sarif");
+        let result = lint_result(vec![diag("MagicNumber", None, Some(1))]);
+
+        write_sarif_report(&path, &result, tmp.path()).unwrap();
+
+        let written: Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written["runs"][0]["results"][0]["ruleId"], "MagicNumber");
+    }
+}
@@ -0,0 +1,350 @@
+//! Source set discovery: which directories hold a project's Kotlin sources and
+//! which files in them are compiled.
+
+use std::path::{Path, PathBuf};
+
+use konvoy_config::Manifest;
+use konvoy_util::ignore::IgnoreRules;
+
+use crate::error::EngineError;
+
+/// Glob selecting Kotlin sources within each source directory.
+pub(crate) const SOURCE_GLOB: &str = "**/*.kt";
+
+/// The project's main source directories (`[package] source_dirs`, defaulting
+/// to `src`), resolved against `project_root`.
+pub(crate) fn source_dirs(project_root: &Path, manifest: &Manifest) -> Vec<PathBuf> {
+    manifest
+        .package
+        .effective_source_dirs()
+        .into_iter()
+        .map(|dir| project_root.join(dir))
+        .collect()
+}
+
+/// The directory holding test sources (`[package] test_dir`, defaulting to
+/// `src/test`), resolved against `project_root`. It is excluded from main
+/// builds even when it lies inside a source directory.
+pub(crate) fn test_dir(project_root: &Path, manifest: &Manifest) -> PathBuf {
+    project_root.join(manifest.package.effective_test_dir())
+}
+
+/// The directory holding benchmark sources, excluded from main builds just
+/// like [`test_dir`].
+pub(crate) fn bench_dir(project_root: &Path) -> PathBuf {
+    project_root.join("src").join("bench")
+}
+
+/// Directories whose sources only belong to test and benchmark binaries.
+pub(crate) fn auxiliary_dirs(project_root: &Path, manifest: &Manifest) -> Vec<PathBuf> {
+    vec![test_dir(project_root, manifest), bench_dir(project_root)]
+}
+
+/// Directories whose contents key a test or benchmark binary: the main source
+/// dirs plus `extra_dir`, unless a source dir already contains it (the default
+/// layout, where `src/` holds `src/test/`), which keeps that layout's key
+/// unchanged.
+pub(crate) fn cache_dirs_with(mut source_dirs: Vec<PathBuf>, extra_dir: PathBuf) -> Vec<PathBuf> {
+    if !source_dirs.iter().any(|dir| extra_dir.starts_with(dir)) {
+        source_dirs.push(extra_dir);
+    }
+    source_dirs
+}
+
+/// Collect the `.kt` files under `dirs`, skipping anything inside one of the
+/// `excluded` directories and anything excluded by `ignore`.
+///
+/// Missing directories contribute nothing (a project may be entirely
+/// generated). Overlapping directories do not yield duplicates, and the result
+/// is sorted for a deterministic compiler command line.
+///
+/// # Errors
+/// Returns an error if a directory cannot be walked.
+pub(crate) fn collect_main_sources(
+    dirs: &[PathBuf],
+    excluded: &[PathBuf],
+    ignore: &IgnoreRules,
+) -> Result<Vec<PathBuf>, EngineError> {
+    let mut sources = Vec::new();
+    for dir in dirs.iter().filter(|d| d.is_dir()) {
+        sources.extend(
+            konvoy_util::fs::collect_files(dir, "kt")?
+                .into_iter()
+                .filter(|p| {
+                    !excluded.iter().any(|skip| p.starts_with(skip)) && !ignore.is_ignored(p)
+                }),
+        );
+    }
+    sources.sort();
+    sources.dedup();
+    Ok(sources)
+}
+
+/// Collect the `.kt` files under a test or benchmark directory, except those
+/// excluded by `ignore`.
+///
+/// # Errors
+/// Returns an error if the directory cannot be walked.
+pub(crate) fn collect_dir_sources(
+    dir: &Path,
+    ignore: &IgnoreRules,
+) -> Result<Vec<PathBuf>, EngineError> {
+    Ok(konvoy_util::fs::collect_files(dir, "kt")?
+        .into_iter()
+        .filter(|p| !ignore.is_ignored(p))
+        .collect())
+}
+
+/// Every Kotlin source the project owns, for tools that read the whole project
+/// (`lint`, `fmt`): the `.kt` files of its main source dirs and its test dir,
+/// minus `.konvoyignore` matches, sorted.
+///
+/// # Errors
+/// Returns an error if `.konvoyignore` cannot be read or a directory cannot be
+/// walked.
+pub(crate) fn project_sources(
+    project_root: &Path,
+    manifest: &Manifest,
+) -> Result<Vec<PathBuf>, EngineError> {
+    let ignore = IgnoreRules::load(project_root)?;
+    let dirs = cache_dirs_with(
+        source_dirs(project_root, manifest),
+        test_dir(project_root, manifest),
+    );
+    collect_main_sources(&dirs, &[], &ignore)
+}
+
+/// Content hash of every file matching `glob` under `dirs`, except those
+/// excluded by `ignore`.
+///
+/// A single directory with no ignore rules hashes exactly as
+/// [`konvoy_util::hash::sha256_dir`], so projects on the default layout keep
+/// their existing cache keys and lockfile source hashes. Multiple directories
+/// combine their per-directory hashes in the configured order.
+///
+/// # Errors
+/// Returns an error if a source file cannot be read.
+pub(crate) fn hash_sources(
+    dirs: &[PathBuf],
+    glob: &str,
+    ignore: &IgnoreRules,
+) -> Result<String, EngineError> {
+    let hashes = dirs
+        .iter()
+        .map(|dir| konvoy_util::hash::sha256_dir_filtered(dir, glob, |p| !ignore.is_ignored(p)))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let [single] = hashes.as_slice() {
+        return Ok(single.clone());
+    }
+    let parts: Vec<&str> = hashes.iter().map(String::as_str).collect();
+    Ok(konvoy_util::hash::sha256_multi(&parts))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn manifest(extra: &str) -> Manifest {
+        Manifest::from_str(
+            &format!("[package]\nname = \"app\"\n{extra}\n[toolchain]\nkotlin = \"2.1.0\"\n"),
+            "konvoy.toml",
+        )
+        .unwrap()
+    }
+
+    fn write(path: &Path) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "fun f() {}\n").unwrap();
+    }
+
+    #[test]
+    fn source_dirs_default_to_src() {
+        let root = Path::new("/proj");
+        assert_eq!(
+            source_dirs(root, &manifest("")),
+            vec![PathBuf::from("/proj/src")]
+        );
+    }
+
+    #[test]
+    fn source_dirs_from_manifest() {
+        let root = Path::new("/proj");
+        let m = manifest("source_dirs = [\"src/main/kotlin\", \"gen\"]");
+        assert_eq!(
+            source_dirs(root, &m),
+            vec![
+                PathBuf::from("/proj/src/main/kotlin"),
+                PathBuf::from("/proj/gen")
+            ]
+        );
+    }
+
+    #[test]
+    fn collect_excludes_test_dir_and_missing_dirs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        write(&root.join("src").join("main.kt"));
+        write(&root.join("src").join("test").join("MainTest.kt"));
+        write(&root.join("src").join("bench").join("MainBench.kt"));
+
+        let dirs = vec![root.join("src"), root.join("absent")];
+        let sources = collect_main_sources(
+            &dirs,
+            &auxiliary_dirs(root, &manifest("")),
+            &IgnoreRules::default(),
+        )
+        .unwrap();
+        assert_eq!(sources, vec![root.join("src").join("main.kt")]);
+    }
+
+    #[test]
+    fn custom_test_dir_is_compiled_for_tests_but_not_for_build() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let ignore = IgnoreRules::default();
+        let main_kt = root.join("src").join("main.kt");
+        let nested_test = root.join("src").join("it").join("AppTest.kt");
+        let top_level_test = root.join("tests").join("AppTest.kt");
+        write(&main_kt);
+        write(&nested_test);
+        write(&top_level_test);
+
+        // A test dir inside the source dir is dropped from the main build.
+        let nested = manifest("test_dir = \"src/it\"");
+        let build_sources = collect_main_sources(
+            &source_dirs(root, &nested),
+            &auxiliary_dirs(root, &nested),
+            &ignore,
+        )
+        .unwrap();
+        assert_eq!(build_sources, vec![main_kt.clone()]);
+        let test_sources = collect_dir_sources(&test_dir(root, &nested), &ignore).unwrap();
+        assert_eq!(test_sources, vec![nested_test]);
+
+        // A top-level `tests/` is outside the sources, so only the tests see it.
+        let top_level = manifest("test_dir = \"tests\"");
+        let build_sources = collect_main_sources(
+            &source_dirs(root, &top_level),
+            &auxiliary_dirs(root, &top_level),
+            &ignore,
+        )
+        .unwrap();
+        assert_eq!(
+            build_sources.len(),
+            2,
+            "src/it is an ordinary source dir now"
+        );
+        assert!(!build_sources.contains(&top_level_test));
+        let test_sources = collect_dir_sources(&test_dir(root, &top_level), &ignore).unwrap();
+        assert_eq!(test_sources, vec![top_level_test]);
+    }
+
+    #[test]
+    fn collect_gradle_layout_and_dedups_overlap() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let main_kt = root.join("src").join("main").join("kotlin").join("Main.kt");
+        write(&main_kt);
+
+        let dirs = vec![
+            root.join("src").join("main").join("kotlin"),
+            root.join("src"),
+        ];
+        let sources = collect_main_sources(
+            &dirs,
+            &auxiliary_dirs(root, &manifest("")),
+            &IgnoreRules::default(),
+        )
+        .unwrap();
+        assert_eq!(sources, vec![main_kt]);
+    }
+
+    #[test]
+    fn hash_single_dir_matches_sha256_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let src = tmp.path().join("src");
+        write(&src.join("main.kt"));
+        assert_eq!(
+            hash_sources(
+                std::slice::from_ref(&src),
+                SOURCE_GLOB,
+                &IgnoreRules::default()
+            )
+            .unwrap(),
+            konvoy_util::hash::sha256_dir(&src, SOURCE_GLOB).unwrap()
+        );
+    }
+
+    #[test]
+    fn hash_changes_when_file_moves_between_dirs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let a = tmp.path().join("a");
+        let b = tmp.path().join("b");
+        fs::create_dir_all(&a).unwrap();
+        fs::create_dir_all(&b).unwrap();
+        write(&a.join("x.kt"));
+        let dirs = vec![a.clone(), b.clone()];
+        let before = hash_sources(&dirs, SOURCE_GLOB, &IgnoreRules::default()).unwrap();
+
+        fs::rename(a.join("x.kt"), b.join("x.kt")).unwrap();
+        let after = hash_sources(&dirs, SOURCE_GLOB, &IgnoreRules::default()).unwrap();
+        assert_ne!(before, after);
+    }
+
+    #[test]
+    fn ignored_files_are_neither_collected_nor_hashed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let src = root.join("src");
+        write(&src.join("main.kt"));
+        let dirs = vec![src.clone()];
+        let before = hash_sources(&dirs, SOURCE_GLOB, &IgnoreRules::default()).unwrap();
+
+        write(&src.join("notes.scratch.kt"));
+        let ignore = IgnoreRules::parse(root, "*.scratch.kt\n", ".konvoyignore").unwrap();
+        let sources =
+            collect_main_sources(&dirs, &auxiliary_dirs(root, &manifest("")), &ignore).unwrap();
+        assert_eq!(sources, vec![src.join("main.kt")]);
+        assert_eq!(hash_sources(&dirs, SOURCE_GLOB, &ignore).unwrap(), before);
+        assert_ne!(
+            hash_sources(&dirs, SOURCE_GLOB, &IgnoreRules::default()).unwrap(),
+            before
+        );
+    }
+
+    #[test]
+    fn project_sources_follow_source_dirs_and_konvoyignore() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let main = root.join("src").join("main").join("kotlin");
+        write(&main.join("App.kt"));
+        write(&root.join("gen").join("Gen.kt"));
+        write(&root.join("gen").join("notes.scratch.kt"));
+        write(&root.join("scripts").join("Stray.kt"));
+        fs::write(root.join(".konvoyignore"), "*.scratch.kt\n").unwrap();
+
+        let m = manifest("source_dirs = [\"src/main/kotlin\", \"gen\"]");
+        let mut expected = vec![main.join("App.kt"), root.join("gen").join("Gen.kt")];
+        expected.sort();
+        assert_eq!(project_sources(root, &m).unwrap(), expected);
+    }
+
+    #[test]
+    fn cache_dirs_default_layout_unchanged() {
+        let root = PathBuf::from("/proj");
+        let dirs = cache_dirs_with(vec![root.join("src")], root.join("src").join("test"));
+        assert_eq!(dirs, vec![root.join("src")]);
+    }
+
+    #[test]
+    fn cache_dirs_adds_dir_outside_sources() {
+        let root = PathBuf::from("/proj");
+        let main = root.join("src").join("main").join("kotlin");
+        let benches = root.join("src").join("bench");
+        let dirs = cache_dirs_with(vec![main.clone()], benches.clone());
+        assert_eq!(dirs, vec![main, benches]);
+    }
+}
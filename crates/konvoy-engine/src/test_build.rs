@@ -10,6 +10,8 @@ use crate::cache::{CacheInputs, CacheKey};
 use crate::common::now_epoch_secs;
 use crate::error::EngineError;
 use konvoy_konanc::invoke::{KonancCommand, ProduceKind};
+use konvoy_targets::Target;
+use konvoy_util::ignore::IgnoreRules;
 
 /// Result of a successful test build.
 #[derive(Debug)]
@@ -18,6 +20,8 @@ pub struct TestBuildResult {
     pub outcome: BuildOutcome,
     /// Path to the test binary.
     pub output_path: PathBuf,
+    /// The target the test binary was built for.
+    pub target: Target,
     /// How long the build took.
     pub compile_duration: std::time::Duration,
 }
@@ -42,9 +46,14 @@ pub fn build_tests(
     let start = Instant::now();
     let ctx = resolve_build_context(project_root, options, resolver)?;
 
-    // Collect project sources (excluding src/test/) and test sources.
-    let src_dir = project_root.join("src");
-    let test_dir = src_dir.join("test");
+    // Collect project sources (excluding the test dir) and test sources.
+    let mut source_dirs = crate::sources::source_dirs(project_root, &ctx.manifest);
+    source_dirs.extend(crate::features::feature_source_dirs(
+        project_root,
+        &ctx.features,
+    ));
+    let test_dir = crate::sources::test_dir(project_root, &ctx.manifest);
+    let ignore = IgnoreRules::load(project_root)?;
 
     if !test_dir.is_dir() {
         return Err(EngineError::NoTestSources {
@@ -52,19 +61,20 @@ pub fn build_tests(
         });
     }
 
-    let test_sources = konvoy_util::fs::collect_files(&test_dir, "kt")?;
+    let test_sources = crate::sources::collect_dir_sources(&test_dir, &ignore)?;
     if test_sources.is_empty() {
         return Err(EngineError::NoTestSources {
             dir: test_dir.display().to_string(),
         });
     }
 
-    // Collect main sources but exclude the test directory.
-    let all_sources = konvoy_util::fs::collect_files(&src_dir, "kt")?;
-    let mut sources: Vec<PathBuf> = all_sources
-        .into_iter()
-        .filter(|p| !p.starts_with(&test_dir))
-        .collect();
+    // Main sources come from the same helper as `build_single`, so the test
+    // binary compiles exactly what a regular build would, plus the tests.
+    let mut sources = crate::sources::collect_main_sources(
+        &source_dirs,
+        &crate::sources::auxiliary_dirs(project_root, &ctx.manifest),
+        &ignore,
+    )?;
     sources.extend(test_sources);
 
     // Codegen for the root project, derived from its own manifest (identical to
@@ -77,7 +87,7 @@ pub fn build_tests(
     // key from a regular build of the same source tree, so we tag the lockfile
     // content with a "test" marker (the lockfile_content is already a free-form
     // hashed input). Keeps Profile cleanly debug/release.
-    let manifest_content = ctx.manifest.to_toml()?;
+    let manifest_content = crate::build::manifest_key_content(&ctx.manifest)?;
     let cache_inputs = CacheInputs {
         manifest_content,
         lockfile_content: format!("{}\n# konvoy-test-build\n", ctx.lockfile_content),
@@ -85,40 +95,47 @@ pub fn build_tests(
         konanc_fingerprint: ctx.konanc.fingerprint.clone(),
         target: ctx.target,
         profile: ctx.profile,
-        source_dir: project_root.join("src"),
-        source_glob: "**/*.kt".to_owned(),
+        source_dirs: crate::sources::cache_dirs_with(source_dirs, test_dir),
+        source_glob: crate::sources::SOURCE_GLOB.to_owned(),
+        ignore,
         os: std::env::consts::OS.to_owned(),
         arch: std::env::consts::ARCH.to_owned(),
-        dependency_hashes: ctx
-            .library_inputs
-            .iter()
-            .map(|lib| match &lib.precomputed_sha256 {
-                Some(h) => Ok(h.clone()),
-                None => konvoy_util::hash::sha256_file(&lib.path).map_err(EngineError::from),
-            })
-            .collect::<Result<Vec<_>, _>>()?,
+        dependency_hashes: crate::build::library_hashes_of(&ctx.library_inputs)?,
         // Codegen inputs (shared with the regular build) — a spec/config change
         // rebuilds the test binary too. Empty when no `[codegen]` is configured.
         codegen_hashes,
+        features: ctx.features.clone(),
+        deny_warnings: ctx.deny_warnings,
     };
     let cache_key = CacheKey::compute(&cache_inputs)?;
-
-    let output_name = format!("test-{}", ctx.manifest.package.name);
-    let output_path = project_root
-        .join(".konvoy")
-        .join("build")
-        .join(ctx.target.to_konanc_arg())
-        .join(ctx.profile.as_str())
-        .join(&output_name);
+    konvoy_util::hash_cache::persist();
+
+    let output_name = format!("test-{}", ctx.manifest.package.artifact_stem());
+    let output_path = crate::build::build_output_path(
+        project_root,
+        options.target_dir.as_deref(),
+        &output_name,
+        &ctx.target,
+        ctx.profile,
+    );
 
     // Check cache (respecting --force).
-    if !options.force && ctx.store.has(&cache_key) {
-        eprintln!("    Fresh {} (cached)", output_name);
+    if crate::build::reusable_cache_entry(
+        &ctx.store,
+        &cache_key,
+        &[&output_name],
+        &output_name,
+        options,
+    )? {
+        if !options.quiet {
+            options.progress.on_cache_hit(&output_name);
+        }
         ctx.store
             .materialize(&cache_key, &output_name, &output_path)?;
         return Ok(TestBuildResult {
             outcome: BuildOutcome::Cached,
             output_path,
+            target: ctx.target,
             compile_duration: start.elapsed(),
         });
     }
@@ -137,11 +154,11 @@ pub fn build_tests(
     }
 
     // Compile with test runner generation.
-    eprintln!(
-        "    Compiling {} \u{2192} {}",
-        output_name,
-        output_path.display()
-    );
+    if !options.quiet {
+        options
+            .progress
+            .on_compile_start(&output_name, &output_path);
+    }
 
     if let Some(parent) = output_path.parent() {
         konvoy_util::fs::ensure_dir(parent)?;
@@ -156,8 +173,13 @@ pub fn build_tests(
         .release(options.is_release())
         .produce(ProduceKind::Program)
         .generate_test_runner(true)
+        .language_version(ctx.manifest.toolchain.language_version.as_deref())
+        .api_version(ctx.manifest.toolchain.api_version.as_deref())
         .libraries(&library_paths)
-        .plugins(&ctx.plugin_jars);
+        .plugins(&ctx.plugin_jars)
+        .plugin_options(&ctx.plugin_options)
+        .stream_output(options.verbose)
+        .timeout(ctx.compile_timeout);
 
     if let Some(jh) = ctx.jre_home.as_deref() {
         cmd = cmd.java_home(jh);
@@ -165,13 +187,8 @@ pub fn build_tests(
 
     let result = cmd.execute(&ctx.konanc)?;
 
-    crate::diagnostics::print_diagnostics(&result, options.verbose);
-
-    if !result.success {
-        return Err(EngineError::CompilationFailed {
-            error_count: result.error_count(),
-        });
-    }
+    crate::diagnostics::print_diagnostics(&result, options);
+    crate::build::check_compilation(&result, ctx.deny_warnings)?;
 
     // Handle .kexe suffix on Linux.
     crate::build::normalize_konanc_output(&output_path)?;
@@ -188,6 +205,7 @@ pub fn build_tests(
     Ok(TestBuildResult {
         outcome: BuildOutcome::Fresh,
         output_path,
+        target: ctx.target,
         compile_duration: start.elapsed(),
     })
 }
@@ -214,7 +232,20 @@ mod tests {
             target: None,
             profile: konvoy_config::Profile::Debug,
             verbose: false,
+            quiet: false,
             force: false,
+            timeout: None,
+            dedupe_diagnostics: false,
+            color: konvoy_util::color::ColorChoice::Never,
+            timings: false,
+            features: Vec::new(),
+            no_default_features: false,
+            deny_warnings: false,
+            emit_command: None,
+            out_dir: None,
+            target_dir: None,
+            keep_going: false,
+            progress: std::sync::Arc::new(crate::progress::StderrProgress),
         };
 
         let result = build_tests(
@@ -246,7 +277,20 @@ mod tests {
             target: None,
             profile: konvoy_config::Profile::Debug,
             verbose: false,
+            quiet: false,
             force: false,
+            timeout: None,
+            dedupe_diagnostics: false,
+            color: konvoy_util::color::ColorChoice::Never,
+            timings: false,
+            features: Vec::new(),
+            no_default_features: false,
+            deny_warnings: false,
+            emit_command: None,
+            out_dir: None,
+            target_dir: None,
+            keep_going: false,
+            progress: std::sync::Arc::new(crate::progress::StderrProgress),
         };
 
         let result = build_tests(
@@ -269,7 +313,20 @@ mod tests {
             target: None,
             profile: konvoy_config::Profile::Debug,
             verbose: false,
+            quiet: false,
             force: false,
+            timeout: None,
+            dedupe_diagnostics: false,
+            color: konvoy_util::color::ColorChoice::Never,
+            timings: false,
+            features: Vec::new(),
+            no_default_features: false,
+            deny_warnings: false,
+            emit_command: None,
+            out_dir: None,
+            target_dir: None,
+            keep_going: false,
+            progress: std::sync::Arc::new(crate::progress::StderrProgress),
         };
 
         let result = build_tests(
@@ -0,0 +1,235 @@
+//! Per-phase build durations for `konvoy build --timings`, and the rolling
+//! history of them kept in `.konvoy/timings.json`.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use konvoy_config::Profile;
+use konvoy_targets::Target;
+use serde::{Deserialize, Serialize};
+
+use crate::error::EngineError;
+
+/// How many builds `.konvoy/timings.json` keeps; older entries are dropped.
+const HISTORY_LIMIT: usize = 50;
+
+/// Records how long each build phase took, as consecutive `mark` intervals.
+///
+/// A disabled timer records nothing, so builds without `--timings` pay only
+/// for the `enabled` check.
+#[derive(Debug)]
+pub(crate) struct PhaseTimer {
+    enabled: bool,
+    last: Instant,
+    phases: Vec<(String, Duration)>,
+}
+
+impl PhaseTimer {
+    /// Start a timer; the first phase is measured from now.
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            last: Instant::now(),
+            phases: Vec::new(),
+        }
+    }
+
+    /// Whether phases are being recorded.
+    pub(crate) fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Record the time since the previous mark (or since the timer started)
+    /// as `phase`.
+    pub(crate) fn mark(&mut self, phase: &str) {
+        if !self.enabled {
+            return;
+        }
+        let now = Instant::now();
+        self.phases
+            .push((phase.to_owned(), now.duration_since(self.last)));
+        self.last = now;
+    }
+
+    /// The recorded phases, in the order they were marked.
+    pub(crate) fn into_phases(self) -> Vec<(String, Duration)> {
+        self.phases
+    }
+}
+
+/// The `.konvoy/timings.json` document.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TimingHistory {
+    /// Recorded builds, oldest first.
+    builds: Vec<TimingRecord>,
+}
+
+/// One `--timings` build in the history.
+#[derive(Debug, Serialize, Deserialize)]
+struct TimingRecord {
+    /// Seconds since the Unix epoch when the build finished.
+    timestamp: u64,
+    target: String,
+    profile: String,
+    /// Wall-clock duration of the whole build, in seconds.
+    total_secs: f64,
+    phases: Vec<PhaseRecord>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PhaseRecord {
+    name: String,
+    secs: f64,
+}
+
+/// Path of the timing history for a project.
+fn history_path(project_root: &Path) -> PathBuf {
+    project_root.join(".konvoy").join("timings.json")
+}
+
+/// Append a build's phase durations to `.konvoy/timings.json`, keeping the
+/// last `HISTORY_LIMIT` builds.
+///
+/// Best-effort: a failure to write is printed as a warning and never fails
+/// the build. An unreadable or malformed history is replaced.
+pub(crate) fn record_history(
+    project_root: &Path,
+    target: Target,
+    profile: Profile,
+    total: Duration,
+    phases: &[(String, Duration)],
+) {
+    let record = TimingRecord {
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+        target: target.to_string(),
+        profile: profile.to_string(),
+        total_secs: total.as_secs_f64(),
+        phases: phases
+            .iter()
+            .map(|(name, duration)| PhaseRecord {
+                name: name.clone(),
+                secs: duration.as_secs_f64(),
+            })
+            .collect(),
+    };
+    let path = history_path(project_root);
+    if let Err(e) = append_record(&path, record) {
+        eprintln!("    warning: cannot write {}: {e}", path.display());
+    }
+}
+
+fn append_record(path: &Path, record: TimingRecord) -> Result<(), EngineError> {
+    let mut history: TimingHistory = std::fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+    history.builds.push(record);
+    let excess = history.builds.len().saturating_sub(HISTORY_LIMIT);
+    history.builds.drain(..excess);
+
+    let json =
+        serde_json::to_string_pretty(&history).map_err(|source| EngineError::JsonSerialize {
+            what: "timings.json",
+            source,
+        })?;
+    if let Some(parent) = path.parent() {
+        konvoy_util::fs::ensure_dir(parent)?;
+    }
+    konvoy_util::fs::write_file(path, json)?;
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn read_history(project_root: &Path) -> TimingHistory {
+        serde_json::from_slice(&std::fs::read(history_path(project_root)).unwrap()).unwrap()
+    }
+
+    fn record(project_root: &Path) {
+        record_history(
+            project_root,
+            Target::LinuxX64,
+            Profile::Release,
+            Duration::from_millis(1500),
+            &[
+                (
+                    "toolchain resolution".to_owned(),
+                    Duration::from_millis(500),
+                ),
+                ("root compile".to_owned(), Duration::from_secs(1)),
+            ],
+        );
+    }
+
+    #[test]
+    fn record_history_writes_phases_target_and_profile() {
+        let tmp = tempfile::tempdir().unwrap();
+        record(tmp.path());
+
+        let history = read_history(tmp.path());
+        assert_eq!(history.builds.len(), 1);
+        let build = history.builds.first().unwrap();
+        assert_eq!(build.target, "linux_x64");
+        assert_eq!(build.profile, "release");
+        assert!((build.total_secs - 1.5).abs() < f64::EPSILON);
+        let names: Vec<&str> = build.phases.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["toolchain resolution", "root compile"]);
+        assert!(build.timestamp > 0);
+    }
+
+    #[test]
+    fn record_history_keeps_only_the_latest_builds() {
+        let tmp = tempfile::tempdir().unwrap();
+        for _ in 0..HISTORY_LIMIT + 3 {
+            record(tmp.path());
+        }
+        assert_eq!(read_history(tmp.path()).builds.len(), HISTORY_LIMIT);
+    }
+
+    #[test]
+    fn record_history_replaces_malformed_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = history_path(tmp.path());
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "not json").unwrap();
+
+        record(tmp.path());
+        assert_eq!(read_history(tmp.path()).builds.len(), 1);
+    }
+
+    #[test]
+    fn record_history_failure_does_not_panic() {
+        let tmp = tempfile::tempdir().unwrap();
+        // `.konvoy` is a file, so the history directory cannot be created.
+        std::fs::write(tmp.path().join(".konvoy"), "").unwrap();
+        record(tmp.path());
+        assert!(!history_path(tmp.path()).exists());
+    }
+
+    #[test]
+    fn disabled_timer_records_nothing() {
+        let mut timer = PhaseTimer::new(false);
+        timer.mark("toolchain resolution");
+        timer.mark("root compile");
+        assert!(timer.into_phases().is_empty());
+    }
+
+    #[test]
+    fn enabled_timer_records_phases_in_order() {
+        let mut timer = PhaseTimer::new(true);
+        timer.mark("dependency resolution");
+        std::thread::sleep(Duration::from_millis(5));
+        timer.mark("root compile");
+
+        let phases = timer.into_phases();
+        let names: Vec<&str> = phases.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["dependency resolution", "root compile"]);
+        assert!(phases.iter().any(|(_, d)| *d >= Duration::from_millis(5)));
+    }
+}
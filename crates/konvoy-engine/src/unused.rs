@@ -0,0 +1,291 @@
+//! Heuristic detection of `[dependencies]` entries the project's sources no
+//! longer use, reported as warnings by `konvoy doctor`.
+//!
+//! A dependency counts as used when some `import` in the project's main, test,
+//! benchmark, or generated sources names one of its packages, or a source file
+//! shares a package with it (no import needed). The packages of a path or git
+//! dependency are the `package` declarations in its sources; those of a Maven
+//! dependency are guessed from its artifact id. Usage that never appears in an
+//! import, such as reflection or fully-qualified names, is not seen, so a
+//! dependency used only that way is reported; `allow_unused = true` silences
+//! it.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use konvoy_config::lockfile::Lockfile;
+use konvoy_config::manifest::{DependencySpec, Manifest};
+use konvoy_util::ignore::IgnoreRules;
+
+use crate::error::EngineError;
+
+/// A dependency no source imports from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnusedDependency {
+    /// The dependency's key in `[dependencies]`.
+    pub name: String,
+    /// The packages searched for, e.g. `kotlinx.coroutines`.
+    pub packages: Vec<String>,
+}
+
+/// How a dependency's package names are matched against an import.
+#[derive(Debug)]
+enum Packages {
+    /// Declared by the dependency's sources: an import must start with one.
+    Declared(BTreeSet<String>),
+    /// Guessed from a Maven artifact id: an import must contain one as a run
+    /// of whole segments (`ktor.client` matches `io.ktor.client.HttpClient`).
+    Guessed(Vec<String>),
+}
+
+/// The dependencies of the project at `project_root` that none of its sources
+/// import from, in `[dependencies]` order.
+///
+/// Dependencies with `allow_unused = true` are skipped, as are those whose
+/// packages cannot be determined: a git dependency not checked out yet, or a
+/// path or git dependency whose sources declare no package.
+///
+/// # Errors
+/// Returns an error if `konvoy.lock` or a `.konvoyignore` cannot be read, or
+/// a source directory cannot be walked.
+pub fn unused_dependencies(
+    project_root: &Path,
+    manifest: &Manifest,
+) -> Result<Vec<UnusedDependency>, EngineError> {
+    let lockfile = Lockfile::from_path(&project_root.join("konvoy.lock"))?;
+    let mut dirs = crate::sources::source_dirs(project_root, manifest);
+    dirs.extend(crate::sources::auxiliary_dirs(project_root, manifest));
+    dirs.push(project_root.join(".konvoy").join("gen"));
+    let ignore = IgnoreRules::load(project_root)?;
+    let references = scan(&crate::sources::collect_main_sources(&dirs, &[], &ignore)?)?;
+
+    let mut unused = Vec::new();
+    for (name, spec) in &manifest.dependencies {
+        if spec.allow_unused {
+            continue;
+        }
+        let Some(packages) = dependency_packages(project_root, name, spec, &lockfile)? else {
+            continue;
+        };
+        let used = references
+            .imports
+            .iter()
+            .chain(&references.packages)
+            .any(|reference| packages.matches(reference));
+        if !used {
+            let packages = match packages {
+                Packages::Declared(set) => set.into_iter().collect(),
+                Packages::Guessed(list) => list,
+            };
+            unused.push(UnusedDependency {
+                name: name.clone(),
+                packages,
+            });
+        }
+    }
+    Ok(unused)
+}
+
+/// The package names `spec` provides, or `None` if they cannot be determined.
+fn dependency_packages(
+    project_root: &Path,
+    name: &str,
+    spec: &DependencySpec,
+    lockfile: &Lockfile,
+) -> Result<Option<Packages>, EngineError> {
+    if let Some((_, artifact_id)) = spec
+        .as_maven_coord()
+        .and_then(|(coord, _)| coord.split_once(':'))
+    {
+        return Ok(Some(Packages::Guessed(guess_packages(artifact_id))));
+    }
+    let dep_root = match (&spec.path, &spec.git, &spec.rev) {
+        (Some(path), _, _) => project_root.join(path),
+        (None, Some(url), Some(rev)) => match lockfile.git_commit(name, url, rev) {
+            Some(commit) => crate::git::checkout_dir(url, commit)?,
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    // A dependency that cannot be read is reported by `konvoy build`, not here.
+    let Ok(dep_manifest) = Manifest::from_path(&dep_root.join("konvoy.toml")) else {
+        return Ok(None);
+    };
+    let dirs = crate::sources::source_dirs(&dep_root, &dep_manifest);
+    let excluded = crate::sources::auxiliary_dirs(&dep_root, &dep_manifest);
+    let ignore = IgnoreRules::load(&dep_root)?;
+    let sources = crate::sources::collect_main_sources(&dirs, &excluded, &ignore)?;
+    let declared = scan(&sources)?.packages;
+    Ok((!declared.is_empty()).then_some(Packages::Declared(declared)))
+}
+
+/// Candidate packages for a Maven artifact: its first two `-`-separated words
+/// joined with a dot and run together (`kotlin-logging` gives
+/// `kotlin.logging` and `kotlinlogging`), or the single word.
+fn guess_packages(artifact_id: &str) -> Vec<String> {
+    let words: Vec<&str> = artifact_id.split('-').take(2).collect();
+    let mut packages = vec![words.join(".")];
+    if words.len() > 1 {
+        packages.push(words.concat());
+    }
+    packages
+}
+
+impl Packages {
+    fn matches(&self, reference: &str) -> bool {
+        match self {
+            Packages::Declared(set) => set.iter().any(|package| {
+                reference == package
+                    || reference
+                        .strip_prefix(package.as_str())
+                        .is_some_and(|rest| rest.starts_with('.'))
+            }),
+            Packages::Guessed(list) => {
+                let segments: Vec<&str> = reference.split('.').collect();
+                list.iter().any(|package| {
+                    let wanted: Vec<&str> = package.split('.').collect();
+                    segments.windows(wanted.len()).any(|run| run == wanted)
+                })
+            }
+        }
+    }
+}
+
+/// The names a set of sources refers to.
+#[derive(Debug, Default)]
+struct References {
+    /// Imported names, without a trailing `.*` or `as` alias.
+    imports: BTreeSet<String>,
+    /// Packages the sources declare.
+    packages: BTreeSet<String>,
+}
+
+/// Collect the `import` and `package` directives of `files`.
+fn scan(files: &[PathBuf]) -> Result<References, EngineError> {
+    let mut references = References::default();
+    for file in files {
+        let content = konvoy_util::fs::read_file(file)?;
+        for line in String::from_utf8_lossy(&content)
+            .lines()
+            .map(str::trim_start)
+        {
+            if let Some(rest) = line.strip_prefix("import ") {
+                if let Some(name) = directive_name(rest) {
+                    references
+                        .imports
+                        .insert(name.trim_end_matches(".*").to_owned());
+                }
+            } else if let Some(rest) = line.strip_prefix("package ") {
+                if let Some(name) = directive_name(rest) {
+                    references.packages.insert(name.to_owned());
+                }
+            }
+        }
+    }
+    Ok(references)
+}
+
+/// The dotted name at the start of an `import` or `package` directive.
+fn directive_name(rest: &str) -> Option<&str> {
+    rest.split(|c: char| c.is_whitespace() || c == ';')
+        .next()
+        .map(|name| name.trim_matches('`'))
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn write_project(dir: &Path, name: &str, deps: &str, source: &str) {
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src/lib.kt"), source).unwrap();
+        fs::write(
+            dir.join("konvoy.toml"),
+            format!(
+                "[package]\nname = \"{name}\"\nkind = \"lib\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n\n[dependencies]\n{deps}"
+            ),
+        )
+        .unwrap();
+    }
+
+    fn unused_names(root: &Path) -> Vec<String> {
+        let manifest = Manifest::from_path(&root.join("konvoy.toml")).unwrap();
+        unused_dependencies(root, &manifest)
+            .unwrap()
+            .into_iter()
+            .map(|dep| dep.name)
+            .collect()
+    }
+
+    #[test]
+    fn guesses_packages_from_artifact_id() {
+        assert_eq!(
+            guess_packages("kotlinx-coroutines-core"),
+            ["kotlinx.coroutines", "kotlinxcoroutines"]
+        );
+        assert_eq!(guess_packages("okio"), ["okio"]);
+        let ktor = Packages::Guessed(guess_packages("ktor-client-core"));
+        assert!(ktor.matches("io.ktor.client.HttpClient"));
+        assert!(!ktor.matches("io.ktor.server.Application"));
+    }
+
+    #[test]
+    fn reports_dependencies_without_imports() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_project(
+            &tmp.path().join("core"),
+            "core",
+            "",
+            "package com.example.core\n\nfun greet() = \"hi\"\n",
+        );
+        write_project(
+            &tmp.path().join("extra"),
+            "extra",
+            "",
+            "package com.example.extra\n",
+        );
+        let root = tmp.path().join("app");
+        write_project(
+            &root,
+            "app",
+            concat!(
+                "core = { path = \"../core\" }\n",
+                "extra = { path = \"../extra\" }\n",
+                "coroutines = { maven = \"org.jetbrains.kotlinx:kotlinx-coroutines-core\", version = \"1.8.0\" }\n",
+                "datetime = { maven = \"org.jetbrains.kotlinx:kotlinx-datetime\", version = \"0.6.0\" }\n",
+                "reflected = { maven = \"org.example:reflected\", version = \"1.0.0\", allow_unused = true }\n",
+            ),
+            "import com.example.core.greet\nimport kotlinx.coroutines.*\n\nfun main() = greet()\n",
+        );
+        assert_eq!(unused_names(&root), ["datetime", "extra"]);
+    }
+
+    #[test]
+    fn shared_package_and_test_imports_count_as_usage() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_project(
+            &tmp.path().join("core"),
+            "core",
+            "",
+            "package com.example\n",
+        );
+        let root = tmp.path().join("app");
+        write_project(
+            &root,
+            "app",
+            "core = { path = \"../core\" }\ndatetime = { maven = \"org.jetbrains.kotlinx:kotlinx-datetime\", version = \"0.6.0\" }\n",
+            "package com.example\n",
+        );
+        fs::create_dir_all(root.join("src/test")).unwrap();
+        fs::write(
+            root.join("src/test/clock_test.kt"),
+            "import kotlinx.datetime.Clock as KClock\n",
+        )
+        .unwrap();
+        assert!(unused_names(&root).is_empty());
+    }
+}
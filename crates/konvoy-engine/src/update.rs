@@ -19,8 +19,9 @@ use std::path::{Path, PathBuf};
 
 use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
-use konvoy_config::lockfile::{DepSource, DependencyLock, Lockfile};
+use konvoy_config::lockfile::{DepSource, DependencyLock, HashAlgorithm, Lockfile};
 use konvoy_config::manifest::Manifest;
+use konvoy_config::version_req::compare_maven_versions;
 use konvoy_util::maven::MAVEN_CENTRAL;
 use konvoy_util::metadata::ArtifactMetadata;
 use konvoy_util::pom::strip_target_suffix;
@@ -46,7 +47,11 @@ const FILTERED_GROUP_ARTIFACTS: &[(&str, &str)] = &[
 ///
 /// The placeholder is overwritten before the lockfile is written, so its
 /// `targets`/`source_hash` are never observed.
-fn placeholder_lock(dep: &ResolvedMavenDep, maven_coord: &str) -> DependencyLock {
+fn placeholder_lock(
+    dep: &ResolvedMavenDep,
+    maven_coord: &str,
+    hash_algorithm: HashAlgorithm,
+) -> DependencyLock {
     DependencyLock {
         name: dep.name.clone(),
         source: DepSource::Maven {
@@ -55,41 +60,45 @@ fn placeholder_lock(dep: &ResolvedMavenDep, maven_coord: &str) -> DependencyLock
             targets: BTreeMap::new(),
             required_by: dep.required_by.clone(),
             classifier: dep.classifier.clone(),
+            hash_algorithm,
         },
         source_hash: String::new(),
     }
 }
 
-/// Download a Maven dep's klib for every known target and produce its lock entry.
+/// Download a Maven dep's klib for every supported target and produce its lock entry.
 ///
 /// Klibs are written directly into the shared Maven cache at
 /// `~/.konvoy/cache/maven/.../<artifact>-<version>.klib` so subsequent
 /// `konvoy build` runs reuse the verified file. Per-target downloads run in
 /// parallel via rayon and each renders into a pre-allocated progress bar (one
-/// per target, in `KNOWN_TARGETS` order) so the on-screen layout stays in
+/// per target, in `SUPPORTED_TARGETS` order) so the on-screen layout stays in
 /// the same stable rows regardless of completion order.
 ///
-/// `bars` must have one entry per `KNOWN_TARGETS` element; caller-enforced
-/// via the zip in `update()`.
+/// `bars` must have one entry per `SUPPORTED_TARGETS` element; caller-enforced
+/// via the zip in `update()`. The klibs are pinned with `hash_algorithm`.
 fn download_dep(
     dep: &ResolvedMavenDep,
     cache_root: &Path,
+    hash_algorithm: HashAlgorithm,
     bars: &[konvoy_util::progress::DownloadBar],
     resolver: crate::common::ArtifactResolver<'_>,
 ) -> Result<DependencyLock, EngineError> {
-    let known_targets = konvoy_targets::KNOWN_TARGETS;
+    let known_targets = konvoy_targets::SUPPORTED_TARGETS;
     let maven_coord = dep.key();
 
     let target_results: Vec<Result<(konvoy_targets::Target, String), EngineError>> = known_targets
         .par_iter()
         .zip(bars.par_iter())
-        .map(|(&target, bar)| download_target_klib(dep, target, cache_root, bar, resolver))
+        .map(|(&target, bar)| {
+            download_target_klib(dep, target, cache_root, hash_algorithm, bar, resolver)
+        })
         .collect();
 
     let mut targets_map: BTreeMap<String, String> = BTreeMap::new();
     for result in target_results {
-        let (target, sha256) = result?;
-        targets_map.insert(target.to_string(), sha256);
+        let (target, digest) = result?;
+        targets_map.insert(target.to_string(), digest);
     }
 
     let hash_input: String = targets_map
@@ -107,12 +116,14 @@ fn download_dep(
             targets: targets_map,
             required_by: dep.required_by.clone(),
             classifier: dep.classifier.clone(),
+            hash_algorithm,
         },
         source_hash,
     })
 }
 
-/// Download a single dep's klib for one target and return `(target, sha256)`.
+/// Download a single dep's klib for one target and return `(target, digest)`,
+/// the digest in `hash_algorithm`.
 ///
 /// Writes the klib to the shared Maven cache so `konvoy build` can reuse it
 /// without re-downloading. The bar is provided by the caller so the on-screen
@@ -121,6 +132,7 @@ fn download_target_klib(
     dep: &ResolvedMavenDep,
     target: konvoy_targets::Target,
     cache_root: &Path,
+    hash_algorithm: HashAlgorithm,
     progress: &konvoy_util::progress::DownloadBar,
     resolver: crate::common::ArtifactResolver<'_>,
 ) -> Result<(konvoy_targets::Target, String), EngineError> {
@@ -141,7 +153,7 @@ fn download_target_klib(
     let label = format!("{}:{}", dep.name, target);
 
     let result = resolver
-        .fetch_artifact(&url, &dest, None, &label, Some(progress))
+        .fetch_artifact_as(&url, &dest, None, hash_algorithm, &label, Some(progress))
         .map_err(|e| match e {
             konvoy_util::error::UtilError::Download { message } => {
                 EngineError::LibraryDownloadFailed {
@@ -150,10 +162,15 @@ fn download_target_klib(
                     message,
                 }
             }
+            konvoy_util::error::UtilError::NotFound { .. } => EngineError::LibraryDownloadFailed {
+                name: dep.name.clone(),
+                url: url.clone(),
+                message: "not found (HTTP 404)".to_owned(),
+            },
             other => EngineError::Util(other),
         })?;
 
-    Ok((target, result.sha256))
+    Ok((target, result.digest))
 }
 
 // ---------------------------------------------------------------------------
@@ -179,9 +196,19 @@ pub fn update(
     resolver: crate::common::ArtifactResolver<'_>,
 ) -> Result<UpdateResult, EngineError> {
     let manifest = Manifest::from_path(&project_root.join("konvoy.toml"))?;
-    // Resolve the path-dependency graph once; the Maven union spans it.
-    let dep_graph = crate::resolve::resolve_dependencies(project_root, &manifest)?;
-    update_with_graph(project_root, &manifest, &dep_graph, resolver)
+    // Resolve the path-dependency graph once; the Maven union spans it. Git deps
+    // stay at the commits already pinned in `konvoy.lock`.
+    let lockfile = Lockfile::from_path(&project_root.join("konvoy.lock"))?;
+    // Optional path deps are locked as a default build would use them.
+    let features = crate::features::resolve_features(&manifest, &[], true)?;
+    let dep_graph = crate::resolve::resolve_dependencies(
+        project_root,
+        &manifest,
+        &features,
+        &lockfile,
+        resolver,
+    )?;
+    update_with_graph(project_root, &manifest, &dep_graph, true, resolver)
 }
 
 /// [`update`] with the path-dependency graph already resolved.
@@ -189,10 +216,15 @@ pub fn update(
 /// The build's auto-update path has already resolved (and source-hashed) the
 /// graph at `resolve_build_context`, so it calls this directly to avoid walking
 /// and hashing every path-dep's source tree a second time on a cold build.
+///
+/// `refresh_ranges` re-resolves version ranges to the newest matching release
+/// (`konvoy update`); otherwise a range keeps a lockfile pin that still
+/// satisfies it, so an automatic update never silently bumps it.
 pub(crate) fn update_with_graph(
     project_root: &Path,
     manifest: &Manifest,
     dep_graph: &crate::resolve::ResolvedGraph,
+    refresh_ranges: bool,
     resolver: crate::common::ArtifactResolver<'_>,
 ) -> Result<UpdateResult, EngineError> {
     // 1. Read konvoy.lock.
@@ -205,9 +237,16 @@ pub(crate) fn update_with_graph(
         lockfile.toolchain = Some(konvoy_config::lockfile::ToolchainLock {
             konanc_version: manifest.toolchain.kotlin.clone(),
             konanc_tarball_sha256: None,
+            konanc_tarball_url: None,
             jre_tarball_sha256: None,
+            jre_major: crate::common::recorded_jre_major(crate::common::jre_major(
+                &manifest.toolchain,
+            )),
+            konanc_fingerprint: None,
             detekt_version: None,
             detekt_jar_sha256: None,
+            ktlint_version: None,
+            ktlint_jar_sha256: None,
         });
     }
 
@@ -217,23 +256,25 @@ pub(crate) fn update_with_graph(
     //    because the dep's own compile links the klibs it declares (the
     //    `[dependencies]` analogue of the path-dep `[plugins]` fix, #293).
     //    Cross-project version clashes are surfaced (libraries are linked).
-    let mut projects: Vec<(&str, &Manifest)> = vec![("konvoy.toml", manifest)];
-    projects.extend(
-        dep_graph
-            .order
-            .iter()
-            .map(|dep| (dep.name.as_str(), &dep.manifest)),
-    );
-    let direct_deps = collect_graph_direct_maven_deps(projects)?;
+    //    Version ranges are resolved to concrete versions first, so the union
+    //    (and its conflict check) compares what will actually be pinned.
+    let mut versions = crate::resolve::VersionResolution::new(&lockfile, refresh_ranges, resolver);
+    let mut resolved: Vec<(&str, Manifest)> =
+        vec![("konvoy.toml", versions.resolve_manifest(manifest)?)];
+    for dep in &dep_graph.order {
+        resolved.push((dep.name.as_str(), versions.resolve_manifest(&dep.manifest)?));
+    }
+    let direct_deps =
+        collect_graph_direct_maven_deps(resolved.iter().map(|(label, m)| (*label, m)))?;
 
     if direct_deps.is_empty() {
         // No Maven deps anywhere in the graph — prune any stale Maven pins (the
-        // last Maven dep may have just been removed) while preserving path-dep
-        // locks, then write. Without this, a removed Maven dep's pin would
+        // last Maven dep may have just been removed) while preserving path- and
+        // git-dep locks, then write. Without this, a removed Maven dep's pin would
         // linger and still be linked/enforced under `--locked`.
         lockfile
             .dependencies
-            .retain(|d| matches!(&d.source, DepSource::Path { .. }));
+            .retain(|d| !matches!(&d.source, DepSource::Maven { .. }));
         lockfile.write_to(&lockfile_path)?;
         return Ok(UpdateResult { updated_count: 0 });
     }
@@ -254,6 +295,10 @@ pub(crate) fn update_with_graph(
     //    Then download the "needs download" set in parallel (across deps),
     //    while each dep also parallelizes its per-target downloads (nested
     //    rayon is fine).
+    //
+    //    An entry pinned with another algorithm than the root's `maven_hash`
+    //    is re-pinned; its klibs are usually cached, so that only re-hashes.
+    let hash_algorithm = manifest.build.maven_hash;
     let mut new_dep_locks: Vec<DependencyLock> = Vec::with_capacity(all_deps.len());
     let mut needs_download: Vec<(usize, &ResolvedMavenDep)> = Vec::new();
 
@@ -262,8 +307,8 @@ pub(crate) fn update_with_graph(
         let dep_classifier = &dep.classifier;
         let already_locked = lockfile.dependencies.iter().find(|d| {
             d.name == dep.name
-                && matches!(&d.source, DepSource::Maven { version: v, maven: m, classifier: c, .. }
-                    if v == &dep.version && m == &maven_coord && c == dep_classifier)
+                && matches!(&d.source, DepSource::Maven { version: v, maven: m, classifier: c, hash_algorithm: h, .. }
+                    if v == &dep.version && m == &maven_coord && c == dep_classifier && *h == hash_algorithm)
         });
 
         if let Some(existing) = already_locked {
@@ -274,7 +319,7 @@ pub(crate) fn update_with_graph(
             eprintln!("    (already up to date)");
         } else {
             // Placeholder; replaced below.
-            new_dep_locks.push(placeholder_lock(dep, &maven_coord));
+            new_dep_locks.push(placeholder_lock(dep, &maven_coord, hash_algorithm));
             needs_download.push((idx, dep));
         }
     }
@@ -284,11 +329,11 @@ pub(crate) fn update_with_graph(
     let cache_root: PathBuf = crate::plugin::maven_cache_root()?;
 
     // Hosts all per-(dep, target) bars. Bars are added in stable order
-    // (`needs_download` order × `KNOWN_TARGETS` order) BEFORE any parallel
+    // (`needs_download` order × `SUPPORTED_TARGETS` order) BEFORE any parallel
     // work starts so the on-screen rows stay fixed regardless of which
     // download finishes first.
     let multi = konvoy_util::progress::new_multi_progress();
-    let known_targets = konvoy_targets::KNOWN_TARGETS;
+    let known_targets = konvoy_targets::SUPPORTED_TARGETS;
 
     // Build labels once, then reuse them to compute the max width AND to
     // construct the bars — avoids formatting each prefix twice.
@@ -318,7 +363,7 @@ pub(crate) fn update_with_graph(
         .par_iter()
         .zip(dep_bars.par_iter())
         .map(|((idx, dep), bars)| {
-            let lock = download_dep(dep, &cache_root, bars, resolver)?;
+            let lock = download_dep(dep, &cache_root, hash_algorithm, bars, resolver)?;
             Ok((*idx, lock))
         })
         .collect();
@@ -357,11 +402,11 @@ pub(crate) fn update_with_graph(
         );
     }
 
-    // 6. Merge: preserve existing path deps, replace all Maven deps.
+    // 6. Merge: preserve existing path and git deps, replace all Maven deps.
     let path_deps: Vec<_> = lockfile
         .dependencies
         .iter()
-        .filter(|d| matches!(&d.source, DepSource::Path { .. }))
+        .filter(|d| !matches!(&d.source, DepSource::Maven { .. }))
         .cloned()
         .collect();
 
@@ -369,7 +414,7 @@ pub(crate) fn update_with_graph(
     lockfile.dependencies.extend(new_dep_locks);
 
     // Sort dependencies by name for deterministic output.
-    lockfile.dependencies.sort_by(|a, b| a.name.cmp(&b.name));
+    konvoy_config::lockfile::sort_dependency_locks(&mut lockfile.dependencies);
 
     // 7. Write updated lockfile.
     lockfile.write_to(&lockfile_path)?;
@@ -499,59 +544,6 @@ fn collect_graph_direct_maven_deps<'a>(
     Ok(by_key.into_values().map(|(dep, _)| dep).collect())
 }
 
-/// Order two Maven version strings well enough to pick the higher one for a
-/// conflict hint. Compares the dotted-numeric core (`1.10.0` > `1.9.0`, and a
-/// missing trailing segment counts as `0` so `1.0` == `1.0.0`); a version with a
-/// pre-release suffix sorts BELOW the same core (`1.0.0-beta` < `1.0.0`), and two
-/// suffixes compare lexicographically. Not a full semver implementation — it
-/// only has to tolerate arbitrary Maven version strings and produce a sensible
-/// hint.
-fn compare_maven_versions(a: &str, b: &str) -> std::cmp::Ordering {
-    use std::cmp::Ordering;
-
-    // Split a version into its numeric core (segments before the first `-`) and
-    // an optional pre-release suffix.
-    fn split_core(v: &str) -> (&str, Option<&str>) {
-        match v.split_once('-') {
-            Some((core, pre)) => (core, Some(pre)),
-            None => (v, None),
-        }
-    }
-
-    let (a_core, a_pre) = split_core(a);
-    let (b_core, b_pre) = split_core(b);
-
-    let mut a_parts = a_core.split('.');
-    let mut b_parts = b_core.split('.');
-    loop {
-        match (a_parts.next(), b_parts.next()) {
-            (None, None) => break,
-            // Pad a missing segment with 0 so `1.0` and `1.0.0` compare equal,
-            // and `1.0.1` > `1.0`.
-            (a_seg, b_seg) => {
-                let x = a_seg.unwrap_or("0");
-                let y = b_seg.unwrap_or("0");
-                let ord = match (x.parse::<u64>(), y.parse::<u64>()) {
-                    (Ok(nx), Ok(ny)) => nx.cmp(&ny),
-                    _ => x.cmp(y),
-                };
-                if ord != Ordering::Equal {
-                    return ord;
-                }
-            }
-        }
-    }
-
-    // Equal numeric core: a pre-release suffix is LESS than no suffix; two
-    // suffixes compare lexicographically.
-    match (a_pre, b_pre) {
-        (None, None) => Ordering::Equal,
-        (None, Some(_)) => Ordering::Greater,
-        (Some(_), None) => Ordering::Less,
-        (Some(x), Some(y)) => x.cmp(y),
-    }
-}
-
 /// Build a [`EngineError::MavenVersionConflict`] naming the two clashing
 /// requirers and suggesting the higher version to pin.
 ///
@@ -1010,7 +1002,7 @@ mod tests {
     use std::collections::BTreeMap;
     use std::fs;
 
-    use konvoy_config::lockfile::{DepSource, DependencyLock, Lockfile};
+    use konvoy_config::lockfile::{DepSource, DependencyLock, HashAlgorithm, Lockfile};
 
     use super::*;
 
@@ -1192,6 +1184,7 @@ kotlinx-coroutines = { maven = "org.jetbrains.kotlinx:kotlinx-coroutines-core",
                 targets: targets.clone(),
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "existing-hash".to_owned(),
         });
@@ -1445,6 +1438,7 @@ kotlin = "2.1.0"
                 targets,
                 required_by: vec!["atomicfu".to_owned()],
                 classifier: Some("cinterop-interop".to_owned()),
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "cinterop-hash".to_owned(),
         });
@@ -1617,6 +1611,7 @@ atomicfu = { maven = "org.jetbrains.kotlinx:atomicfu", version = "0.23.1" }
                 targets: targets1,
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "main-source-hash".to_owned(),
         });
@@ -1635,6 +1630,7 @@ atomicfu = { maven = "org.jetbrains.kotlinx:atomicfu", version = "0.23.1" }
                 targets: targets2,
                 required_by: vec!["atomicfu".to_owned()],
                 classifier: Some("cinterop-interop".to_owned()),
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "cinterop-source-hash".to_owned(),
         });
@@ -1714,7 +1710,7 @@ atomicfu = { maven = "org.jetbrains.kotlinx:atomicfu", version = "0.23.1" }
             classifier: Some("cinterop-interop".to_owned()),
         };
         let coord = "org.jetbrains.kotlinx:kotlinx-coroutines-core";
-        let lock = placeholder_lock(&dep, coord);
+        let lock = placeholder_lock(&dep, coord, HashAlgorithm::Sha512);
 
         assert_eq!(lock.name, "kotlinx-coroutines");
         assert!(
@@ -1728,6 +1724,7 @@ atomicfu = { maven = "org.jetbrains.kotlinx:atomicfu", version = "0.23.1" }
                 targets,
                 required_by,
                 classifier,
+                hash_algorithm,
             } => {
                 assert_eq!(version, "1.8.0");
                 assert_eq!(maven, coord);
@@ -1737,6 +1734,7 @@ atomicfu = { maven = "org.jetbrains.kotlinx:atomicfu", version = "0.23.1" }
                 );
                 assert_eq!(required_by, &vec!["root".to_owned()]);
                 assert_eq!(classifier.as_deref(), Some("cinterop-interop"));
+                assert_eq!(*hash_algorithm, HashAlgorithm::Sha512);
             }
             other => panic!("expected Maven source, got: {other:?}"),
         }
@@ -1753,7 +1751,11 @@ atomicfu = { maven = "org.jetbrains.kotlinx:atomicfu", version = "0.23.1" }
             required_by: Vec::new(),
             classifier: None,
         };
-        let lock = placeholder_lock(&dep, "org.jetbrains.kotlinx:atomicfu");
+        let lock = placeholder_lock(
+            &dep,
+            "org.jetbrains.kotlinx:atomicfu",
+            HashAlgorithm::Sha256,
+        );
         match &lock.source {
             DepSource::Maven { classifier, .. } => assert!(classifier.is_none()),
             other => panic!("expected Maven source, got: {other:?}"),
@@ -1890,71 +1892,6 @@ atomicfu = { maven = "org.jetbrains.kotlinx:atomicfu", version = "0.23.1" }
         );
     }
 
-    #[test]
-    fn compare_maven_versions_is_numeric_not_lexicographic() {
-        use std::cmp::Ordering;
-        // The bug this guards: a string compare makes "1.10.0" < "1.9.0".
-        assert_eq!(compare_maven_versions("1.10.0", "1.9.0"), Ordering::Greater);
-        assert_eq!(compare_maven_versions("1.9.0", "1.10.0"), Ordering::Less);
-        assert_eq!(compare_maven_versions("2.0.0", "2.0.0"), Ordering::Equal);
-        assert_eq!(compare_maven_versions("1.0.1", "1.0"), Ordering::Greater);
-        assert_eq!(compare_maven_versions("0.6.1", "0.6.0"), Ordering::Greater);
-    }
-
-    #[test]
-    fn compare_maven_versions_handles_segments_and_prerelease() {
-        use std::cmp::Ordering;
-        // Missing trailing segments count as 0.
-        assert_eq!(compare_maven_versions("1.0", "1.0.0"), Ordering::Equal);
-        assert_eq!(compare_maven_versions("1.0.0", "1.0"), Ordering::Equal);
-        // A pre-release suffix sorts BELOW the same numeric core (the bug the
-        // old lexicographic fallback got backwards: "1.0.0-beta" > "1.0.0").
-        assert_eq!(
-            compare_maven_versions("1.0.0-beta", "1.0.0"),
-            Ordering::Less
-        );
-        assert_eq!(
-            compare_maven_versions("1.0.0", "1.0.0-RC1"),
-            Ordering::Greater
-        );
-        // Two suffixes compare lexicographically; numeric core still dominates.
-        assert_eq!(
-            compare_maven_versions("1.0.0-rc1", "1.0.0-rc2"),
-            Ordering::Less
-        );
-        assert_eq!(
-            compare_maven_versions("2.0.0-beta", "1.0.0"),
-            Ordering::Greater
-        );
-    }
-
-    #[test]
-    fn compare_maven_versions_reflexive_and_antisymmetric() {
-        use std::cmp::Ordering;
-        for v in ["1.0.0", "1.2", "0.6.1", "1.0.0-beta", "2.0"] {
-            assert_eq!(compare_maven_versions(v, v), Ordering::Equal);
-        }
-        // a < b  <=>  b > a, across mixed numeric/suffix cases.
-        for (a, b) in [
-            ("1.9.0", "1.10.0"),
-            ("1.0.0-beta", "1.0.0"),
-            ("0.6.0", "0.6.1"),
-        ] {
-            assert_eq!(compare_maven_versions(a, b), Ordering::Less);
-            assert_eq!(compare_maven_versions(b, a), Ordering::Greater);
-        }
-    }
-
-    #[test]
-    fn compare_maven_versions_non_numeric_segment_falls_back_lexically() {
-        use std::cmp::Ordering;
-        // A non-numeric core segment can't be parsed → byte comparison for that
-        // segment (best effort; the function is hint-only).
-        assert_eq!(compare_maven_versions("1.x.0", "1.y.0"), Ordering::Less);
-        // Trailing zeros don't change ordering.
-        assert_eq!(compare_maven_versions("1", "1.0.0.0"), Ordering::Equal);
-    }
-
     #[test]
     fn maven_version_conflict_munges_hint_name_and_picks_existing_when_higher() {
         // hint_name turns dots into dashes (the konvoy.toml key convention); when
@@ -2102,6 +2039,7 @@ atomicfu = { maven = "org.jetbrains.kotlinx:atomicfu", version = "0.23.1" }
                 targets: std::collections::BTreeMap::new(),
                 required_by: Vec::new(),
                 classifier: None,
+                hash_algorithm: HashAlgorithm::Sha256,
             },
             source_hash: "stale".to_owned(),
         });
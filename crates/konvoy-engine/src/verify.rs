@@ -0,0 +1,411 @@
+//! `konvoy verify`: check that every artifact `konvoy.lock` pins is present in
+//! the local caches and still matches its recorded hash, without building.
+//!
+//! Unlike `konvoy doctor`, which checks the environment, this only compares the
+//! caches against the lockfile. Every cached file is re-hashed from disk, so a
+//! truncated or modified artifact is reported even when its size is unchanged.
+
+use std::path::{Path, PathBuf};
+
+use konvoy_config::lockfile::{DepSource, HashAlgorithm, Lockfile};
+use konvoy_config::manifest::Manifest;
+use konvoy_targets::Target;
+
+use crate::error::EngineError;
+
+/// What `konvoy verify` found for one pinned artifact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArtifactStatus {
+    /// Present, and its hash matches the lockfile (or the lockfile records no
+    /// hash to compare against).
+    Verified,
+    /// Not present in the local cache.
+    Missing,
+    /// Present, but its SHA-256 differs from the one pinned in the lockfile.
+    Mismatch { expected: String, actual: String },
+    /// The lockfile does not pin this artifact the way `konvoy.toml` asks for
+    /// (e.g. a different toolchain version, or no hash for the target).
+    LockfileOutdated { reason: String },
+}
+
+impl ArtifactStatus {
+    /// Whether this status is a problem `konvoy verify` should fail on.
+    pub fn is_problem(&self) -> bool {
+        !matches!(self, ArtifactStatus::Verified)
+    }
+}
+
+/// The verification result for one artifact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactCheck {
+    /// Human-readable name, e.g. `Kotlin/Native 2.1.0` or `dependency kotlinx-datetime`.
+    pub label: String,
+    /// Where the artifact is expected on disk.
+    pub path: PathBuf,
+    pub status: ArtifactStatus,
+}
+
+/// Every artifact check from one `konvoy verify` run, in lockfile order.
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    /// The target whose Maven klibs were checked.
+    pub target: Option<Target>,
+    pub checks: Vec<ArtifactCheck>,
+}
+
+impl VerifyReport {
+    /// Number of checks that found a missing, corrupted, or unpinned artifact.
+    pub fn problem_count(&self) -> usize {
+        self.checks.iter().filter(|c| c.status.is_problem()).count()
+    }
+
+    fn push(&mut self, label: String, path: PathBuf, status: ArtifactStatus) {
+        self.checks.push(ArtifactCheck {
+            label,
+            path,
+            status,
+        });
+    }
+
+    /// Record `path`'s status against `expected` (`None` or empty: presence only).
+    fn push_file(
+        &mut self,
+        label: String,
+        path: PathBuf,
+        expected: Option<&str>,
+    ) -> Result<(), EngineError> {
+        self.push_file_as(label, path, expected, HashAlgorithm::Sha256)
+    }
+
+    /// [`push_file`](Self::push_file), with `expected` a digest in `algorithm`.
+    fn push_file_as(
+        &mut self,
+        label: String,
+        path: PathBuf,
+        expected: Option<&str>,
+        algorithm: HashAlgorithm,
+    ) -> Result<(), EngineError> {
+        let status = file_status(&path, expected, algorithm)?;
+        self.push(label, path, status);
+        Ok(())
+    }
+}
+
+/// Verify the artifacts pinned in the project's `konvoy.lock` against the
+/// local caches: the managed toolchain, every Maven klib for `target` (the host
+/// when `None`), compiler plugins, the detekt JAR, and code-generation tools.
+///
+/// Nothing is downloaded, built, or written.
+///
+/// # Errors
+/// Returns an error if `konvoy.toml` or `konvoy.lock` cannot be read, the
+/// target is unknown, or a cached file exists but cannot be read.
+pub fn verify(project_root: &Path, target: &Option<String>) -> Result<VerifyReport, EngineError> {
+    let manifest = Manifest::from_path(&project_root.join("konvoy.toml"))?;
+    let lockfile = Lockfile::from_path(&project_root.join("konvoy.lock"))?;
+    let target = crate::build::resolve_target(target)?;
+
+    let mut report = VerifyReport {
+        target: Some(target),
+        checks: Vec::new(),
+    };
+    verify_toolchain(&manifest, &lockfile, &mut report)?;
+    verify_maven_klibs(&lockfile, target, &mut report)?;
+
+    let maven_root = crate::plugin::maven_cache_root()?;
+    for plugin in &lockfile.plugins {
+        let (group_id, artifact_id) = crate::common::split_maven_coordinate(&plugin.maven)?;
+        let coord =
+            konvoy_util::maven::MavenCoordinate::new(group_id, artifact_id, &plugin.version);
+        report.push_file(
+            format!("plugin {} {}", plugin.name, plugin.version),
+            coord.cache_path(&maven_root),
+            Some(&plugin.sha256),
+        )?;
+    }
+
+    if let Some(tc) = &lockfile.toolchain {
+        if let Some(version) = &tc.detekt_version {
+            report.push_file(
+                format!("detekt {version}"),
+                crate::detekt::detekt_jar_path(version)?,
+                tc.detekt_jar_sha256.as_deref(),
+            )?;
+        }
+    }
+
+    for generator in crate::codegen::active_generators(&manifest.codegen) {
+        let tool = generator.managed_tool();
+        let label = format!("codegen tool {} {}", tool.id(), tool.version());
+        let path = tool.artifact_path()?;
+        match lockfile
+            .codegen_tools
+            .iter()
+            .find(|t| t.name == tool.id() && t.version == tool.version())
+        {
+            Some(pin) => report.push_file(label, path, Some(&pin.sha256))?,
+            None => report.push(
+                label,
+                path,
+                ArtifactStatus::LockfileOutdated {
+                    reason: "not pinned in konvoy.lock — run `konvoy build`".to_owned(),
+                },
+            ),
+        }
+    }
+
+    Ok(report)
+}
+
+/// Check the managed toolchain the lockfile pins: the version must match
+/// `konvoy.toml`, `konanc` must be installed, and its fingerprint must match
+/// the recorded one when there is one.
+fn verify_toolchain(
+    manifest: &Manifest,
+    lockfile: &Lockfile,
+    report: &mut VerifyReport,
+) -> Result<(), EngineError> {
+    let version = &manifest.toolchain.kotlin;
+    let label = format!("Kotlin/Native {version}");
+    let path = konvoy_konanc::toolchain::managed_konanc_path(version)?;
+    let Some(tc) = &lockfile.toolchain else {
+        report.push(
+            label,
+            path,
+            ArtifactStatus::LockfileOutdated {
+                reason: "konvoy.lock pins no toolchain — run `konvoy build`".to_owned(),
+            },
+        );
+        return Ok(());
+    };
+    if &tc.konanc_version != version {
+        report.push(
+            label,
+            path,
+            ArtifactStatus::LockfileOutdated {
+                reason: format!(
+                    "konvoy.lock pins Kotlin/Native {} — run `konvoy build` to re-pin",
+                    tc.konanc_version
+                ),
+            },
+        );
+        return Ok(());
+    }
+    let status = match konvoy_konanc::detect::installed_fingerprint(version)? {
+        None => ArtifactStatus::Missing,
+        Some(actual) => match &tc.konanc_fingerprint {
+            Some(expected) if *expected != actual => ArtifactStatus::Mismatch {
+                expected: expected.clone(),
+                actual,
+            },
+            _ => ArtifactStatus::Verified,
+        },
+    };
+    report.push(label, path, status);
+    Ok(())
+}
+
+/// Check every Maven klib the lockfile pins for `target`.
+fn verify_maven_klibs(
+    lockfile: &Lockfile,
+    target: Target,
+    report: &mut VerifyReport,
+) -> Result<(), EngineError> {
+    let cache_root = crate::plugin::maven_cache_root()?;
+    for dep in &lockfile.dependencies {
+        let DepSource::Maven {
+            version,
+            maven,
+            targets,
+            classifier,
+            hash_algorithm,
+            ..
+        } = &dep.source
+        else {
+            continue;
+        };
+        let coord =
+            crate::build::maven_klib_coordinate(maven, version, classifier.as_deref(), &target)?;
+        let label = format!("dependency {} {version}", dep.name);
+        let path = coord.cache_path(&cache_root);
+        match targets.get(target.to_konanc_arg()) {
+            Some(expected) => report.push_file_as(label, path, Some(expected), *hash_algorithm)?,
+            None => report.push(
+                label,
+                path,
+                ArtifactStatus::LockfileOutdated {
+                    reason: format!("konvoy.lock has no {target} hash — run `konvoy update`"),
+                },
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// Compare the file at `path` against `expected`, re-hashing it from disk
+/// with `algorithm`.
+fn file_status(
+    path: &Path,
+    expected: Option<&str>,
+    algorithm: HashAlgorithm,
+) -> Result<ArtifactStatus, EngineError> {
+    if !path.is_file() {
+        return Ok(ArtifactStatus::Missing);
+    }
+    let Some(expected) = expected.filter(|e| !e.is_empty()) else {
+        return Ok(ArtifactStatus::Verified);
+    };
+    match konvoy_util::artifact::check_cached_as(
+        path,
+        Some(expected),
+        algorithm,
+        konvoy_util::artifact::CacheVerification::Full,
+    ) {
+        Ok(_) => Ok(ArtifactStatus::Verified),
+        Err(konvoy_util::error::UtilError::ArtifactHashMismatch {
+            expected, actual, ..
+        }) => Ok(ArtifactStatus::Mismatch { expected, actual }),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_status_missing_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let status = file_status(
+            &tmp.path().join("absent.klib"),
+            Some("abc"),
+            HashAlgorithm::Sha256,
+        )
+        .unwrap();
+        assert_eq!(status, ArtifactStatus::Missing);
+    }
+
+    #[test]
+    fn file_status_matching_hash_is_verified() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("lib.klib");
+        std::fs::write(&path, "klib").unwrap();
+        let expected = konvoy_util::hash::sha256_bytes(b"klib");
+        assert_eq!(
+            file_status(&path, Some(&expected), HashAlgorithm::Sha256).unwrap(),
+            ArtifactStatus::Verified
+        );
+    }
+
+    #[test]
+    fn file_status_uses_the_pinned_algorithm() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("lib.klib");
+        std::fs::write(&path, "klib").unwrap();
+        let sha512 = konvoy_util::hash::sha512_bytes(b"klib");
+        assert_eq!(
+            file_status(&path, Some(&sha512), HashAlgorithm::Sha512).unwrap(),
+            ArtifactStatus::Verified
+        );
+        let status = file_status(&path, Some(&sha512), HashAlgorithm::Sha256).unwrap();
+        assert!(status.is_problem(), "got: {status:?}");
+    }
+
+    #[test]
+    fn file_status_reports_corrupted_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("lib.klib");
+        std::fs::write(&path, "tampered").unwrap();
+        let expected = konvoy_util::hash::sha256_bytes(b"klib");
+        let status = file_status(&path, Some(&expected), HashAlgorithm::Sha256).unwrap();
+        assert!(
+            matches!(&status, ArtifactStatus::Mismatch { expected: e, .. } if *e == expected),
+            "got: {status:?}"
+        );
+        assert!(status.is_problem());
+    }
+
+    #[test]
+    fn file_status_without_pin_checks_presence_only() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("detekt.jar");
+        std::fs::write(&path, "jar").unwrap();
+        assert_eq!(
+            file_status(&path, None, HashAlgorithm::Sha256).unwrap(),
+            ArtifactStatus::Verified
+        );
+        assert_eq!(
+            file_status(&path, Some(""), HashAlgorithm::Sha256).unwrap(),
+            ArtifactStatus::Verified
+        );
+    }
+
+    #[test]
+    fn verify_reports_toolchain_version_drift() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tmp.path().join("konvoy.toml"),
+            "[package]\nname = \"app\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n",
+        )
+        .unwrap();
+        Lockfile::with_toolchain("2.0.0")
+            .write_to(&tmp.path().join("konvoy.lock"))
+            .unwrap();
+
+        let report = verify(tmp.path(), &Some("linux_x64".to_owned())).unwrap();
+        let toolchain = report.checks.first().unwrap();
+        assert_eq!(toolchain.label, "Kotlin/Native 2.1.0");
+        assert!(
+            matches!(&toolchain.status, ArtifactStatus::LockfileOutdated { reason } if reason.contains("2.0.0")),
+            "got: {:?}",
+            toolchain.status
+        );
+        assert_eq!(report.problem_count(), 1);
+    }
+
+    #[test]
+    fn verify_reports_maven_dep_without_target_hash() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tmp.path().join("konvoy.toml"),
+            "[package]\nname = \"app\"\n\n[toolchain]\nkotlin = \"2.1.0\"\n",
+        )
+        .unwrap();
+        let mut lockfile = Lockfile::with_toolchain("2.0.0");
+        lockfile
+            .dependencies
+            .push(konvoy_config::lockfile::DependencyLock {
+                name: "datetime".to_owned(),
+                source: DepSource::Maven {
+                    version: "0.6.0".to_owned(),
+                    maven: "org.jetbrains.kotlinx:kotlinx-datetime".to_owned(),
+                    targets: std::collections::BTreeMap::from([(
+                        "macos_arm64".to_owned(),
+                        "abc".to_owned(),
+                    )]),
+                    required_by: Vec::new(),
+                    classifier: None,
+                    hash_algorithm: HashAlgorithm::Sha256,
+                },
+                source_hash: "h".to_owned(),
+            });
+        lockfile.write_to(&tmp.path().join("konvoy.lock")).unwrap();
+
+        let report = verify(tmp.path(), &Some("linux_x64".to_owned())).unwrap();
+        let dep = report
+            .checks
+            .iter()
+            .find(|c| c.label == "dependency datetime 0.6.0")
+            .unwrap();
+        assert!(
+            matches!(&dep.status, ArtifactStatus::LockfileOutdated { reason } if reason.contains("linux_x64")),
+            "got: {:?}",
+            dep.status
+        );
+        assert!(dep
+            .path
+            .to_string_lossy()
+            .contains("kotlinx-datetime-linuxx64"));
+    }
+}
@@ -6,6 +6,10 @@ use std::process::Command;
 use crate::error::KonancError;
 use crate::toolchain;
 
+/// Environment variable pointing at a `konanc` binary to use instead of the
+/// managed toolchain, e.g. a compiler built from source.
+pub const KONANC_OVERRIDE_ENV: &str = "KONVOY_KONANC";
+
 /// Information about a detected `konanc` installation.
 #[derive(Debug, Clone)]
 pub struct KonancInfo {
@@ -13,8 +17,30 @@ pub struct KonancInfo {
     pub path: PathBuf,
     /// Parsed semantic version (e.g. "2.1.0").
     pub version: String,
-    /// SHA-256 hex digest of the `konanc` binary, used for cache keying.
+    /// Cache-keying fingerprint: the launcher's SHA-256 combined with the
+    /// identity of the JRE it runs on, so upgrading the JRE in place changes it.
     pub fingerprint: String,
+    /// SHA-256 hex digest of the `konanc` launcher alone — the value pinned as
+    /// `konanc_fingerprint` in `konvoy.lock` and checked by `verify_fingerprint`.
+    pub launcher_fingerprint: String,
+}
+
+/// Where a resolved `konanc` comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KonancSource {
+    /// A managed toolchain under `~/.konvoy/toolchains/`.
+    Managed,
+    /// The binary named by `KONVOY_KONANC`.
+    Override,
+}
+
+impl std::fmt::Display for KonancSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Managed => f.write_str("managed (~/.konvoy/toolchains)"),
+            Self::Override => write!(f, "{KONANC_OVERRIDE_ENV} override"),
+        }
+    }
 }
 
 /// Result of resolving a managed konanc toolchain.
@@ -22,42 +48,144 @@ pub struct KonancInfo {
 pub struct ResolvedKonanc {
     /// Compiler information (path, version, fingerprint).
     pub info: KonancInfo,
+    /// Where the compiler comes from.
+    pub source: KonancSource,
     /// SHA-256 of the downloaded Kotlin/Native tarball, if this was a managed install.
     pub konanc_tarball_sha256: Option<String>,
+    /// URL the Kotlin/Native tarball was downloaded from, if this was a managed install.
+    pub konanc_tarball_url: Option<String>,
     /// SHA-256 of the downloaded JRE tarball, if this was a managed install.
     pub jre_tarball_sha256: Option<String>,
     /// JAVA_HOME path for the bundled JRE.
     pub jre_home: Option<PathBuf>,
 }
 
-/// Resolve a managed `konanc` installation for the given version.
+/// Resolve a managed `konanc` installation for the given version, with a
+/// bundled JRE of JDK major `jre_major`.
 ///
-/// If the requested version is not installed, downloads and installs it
-/// from `download.jetbrains.com`. After installation, verifies the version
+/// If the requested version (or its JRE) is not installed, downloads and
+/// installs it (from the toolchain mirror, if `net` has one). After installation, verifies the version
 /// matches and computes a fingerprint for cache keying.
 ///
+/// When `KONVOY_KONANC` is set, that binary is used as-is instead and nothing
+/// is installed; see [`konanc_override`].
+///
 /// # Errors
 /// Returns an error if the toolchain cannot be installed, the version
 /// doesn't match, or the binary cannot be fingerprinted.
 pub fn resolve_konanc(
     version: &str,
+    jre_major: u32,
     net: &konvoy_util::net::NetworkClient,
 ) -> Result<ResolvedKonanc, KonancError> {
-    let installed = toolchain::is_installed(version)?;
+    if let Some(path) = konanc_override() {
+        return resolve_override(&path, version, jre_major);
+    }
+    let installed = toolchain::is_installed(version, jre_major)?;
 
-    let (konanc_tarball_sha256, jre_tarball_sha256) = if !installed {
+    let (konanc_tarball_sha256, konanc_tarball_url, jre_tarball_sha256) = if !installed {
         eprintln!("    Installing Kotlin/Native {version}...");
-        let result = toolchain::install(version, net)?;
-        (result.konanc_tarball_sha256, result.jre_tarball_sha256)
+        let result = toolchain::install(version, jre_major, net)?;
+        (
+            result.konanc_tarball_sha256,
+            result.konanc_tarball_url,
+            result.jre_tarball_sha256,
+        )
     } else {
-        (None, None)
+        (None, None, None)
     };
 
+    Ok(ResolvedKonanc {
+        konanc_tarball_sha256,
+        konanc_tarball_url,
+        jre_tarball_sha256,
+        ..inspect_managed(version, jre_major)?
+    })
+}
+
+/// Look up the installed managed `konanc` for `version` without downloading
+/// anything.
+///
+/// Returns `None` when the toolchain (or its JRE) is not installed. Unlike
+/// [`resolve_konanc`], the tarball hashes are always `None`.
+///
+/// # Errors
+/// Returns an error if the installed binary cannot be run, reports a
+/// different version, or cannot be fingerprinted.
+pub fn find_installed_konanc(
+    version: &str,
+    jre_major: u32,
+) -> Result<Option<ResolvedKonanc>, KonancError> {
+    if let Some(path) = konanc_override() {
+        return resolve_override(&path, version, jre_major).map(Some);
+    }
+    if !toolchain::is_installed(version, jre_major)? {
+        return Ok(None);
+    }
+    inspect_managed(version, jre_major).map(Some)
+}
+
+/// Return the `konanc` path from `KONVOY_KONANC`, or `None` when it is unset
+/// or empty.
+#[must_use]
+pub fn konanc_override() -> Option<PathBuf> {
+    std::env::var_os(KONANC_OVERRIDE_ENV)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Query and fingerprint the `konanc` named by `KONVOY_KONANC`.
+///
+/// The version is whatever the binary reports — a dev build rarely matches
+/// `toolchain.kotlin` exactly, so a difference is only warned about. The
+/// managed JRE for `version` is used when installed; otherwise konanc finds
+/// Java on its own.
+fn resolve_override(
+    path: &Path,
+    version: &str,
+    jre_major: u32,
+) -> Result<ResolvedKonanc, KonancError> {
+    if !path.is_file() {
+        return Err(KonancError::OverrideNotFound {
+            path: path.to_path_buf(),
+        });
+    }
+    check_executable(path)?;
+
+    let jre_home = toolchain::jre_home_path(version, jre_major).ok();
+    let actual_version = query_version(path, jre_home.as_deref())?;
+    if actual_version != version {
+        eprintln!(
+            "    warning: {KONANC_OVERRIDE_ENV} reports Kotlin/Native {actual_version}, but konvoy.toml requests {version}"
+        );
+    }
+
+    // Fingerprint the binary itself so each dev build gets its own cache entries.
+    let launcher_fingerprint = compute_fingerprint(path)?;
+    let fingerprint = toolchain_fingerprint(&launcher_fingerprint, jre_home.as_deref());
+
+    Ok(ResolvedKonanc {
+        info: KonancInfo {
+            path: path.to_path_buf(),
+            version: actual_version,
+            fingerprint,
+            launcher_fingerprint,
+        },
+        source: KonancSource::Override,
+        konanc_tarball_sha256: None,
+        konanc_tarball_url: None,
+        jre_tarball_sha256: None,
+        jre_home,
+    })
+}
+
+/// Query and fingerprint an installed managed `konanc`.
+fn inspect_managed(version: &str, jre_major: u32) -> Result<ResolvedKonanc, KonancError> {
     let path = toolchain::managed_konanc_path(version)?;
     check_executable(&path)?;
 
     // Resolve bundled JRE for version queries and compilation.
-    let jre_home = toolchain::jre_home_path(version).ok();
+    let jre_home = toolchain::jre_home_path(version, jre_major).ok();
 
     let actual_version = query_version(&path, jre_home.as_deref())?;
 
@@ -69,20 +197,63 @@ pub fn resolve_konanc(
         });
     }
 
-    let fingerprint = compute_fingerprint(&path)?;
+    let launcher_fingerprint = compute_fingerprint(&path)?;
+    let fingerprint = toolchain_fingerprint(&launcher_fingerprint, jre_home.as_deref());
 
     Ok(ResolvedKonanc {
         info: KonancInfo {
             path,
             version: actual_version,
             fingerprint,
+            launcher_fingerprint,
         },
-        konanc_tarball_sha256,
-        jre_tarball_sha256,
+        source: KonancSource::Managed,
+        konanc_tarball_sha256: None,
+        konanc_tarball_url: None,
+        jre_tarball_sha256: None,
         jre_home,
     })
 }
 
+/// Fingerprint the installed managed `konanc` for `version`, without running it.
+///
+/// Returns `None` when the `konanc` launcher is not installed. The bundled
+/// JRE is not part of the fingerprint, so its presence is not checked.
+///
+/// # Errors
+/// Returns an error if the home directory cannot be determined or the binary
+/// cannot be read.
+pub fn installed_fingerprint(version: &str) -> Result<Option<String>, KonancError> {
+    let path = toolchain::managed_konanc_path(version)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    compute_fingerprint(&path).map(Some)
+}
+
+/// Verify the installed managed toolchain for `version` against the fingerprint
+/// recorded in `konvoy.lock`.
+///
+/// Returns `Ok(false)` when the toolchain is not installed (nothing to verify).
+///
+/// # Errors
+/// Returns [`KonancError::FingerprintMismatch`] when the installed `konanc`
+/// differs from `expected`, or an error if it cannot be fingerprinted.
+pub fn verify_fingerprint(version: &str, expected: &str) -> Result<bool, KonancError> {
+    let Some(actual) = installed_fingerprint(version)? else {
+        return Ok(false);
+    };
+    if actual != expected {
+        return Err(KonancError::FingerprintMismatch {
+            version: version.to_owned(),
+            path: toolchain::version_dir(version)?,
+            expected: expected.to_owned(),
+            actual,
+        });
+    }
+    Ok(true)
+}
+
 /// Parse a semver version from raw `konanc -version` output.
 ///
 /// Handles formats like:
@@ -172,11 +343,77 @@ fn compute_fingerprint(path: &Path) -> Result<String, KonancError> {
     })
 }
 
+/// Combine the launcher fingerprint with the identity of the JRE at
+/// `jre_home`, for cache keying.
+///
+/// The JRE's identity is its `release` file (which records the exact
+/// `JAVA_VERSION` and vendor build), falling back to the `jre_home` path when
+/// that file is missing. Without a JRE the launcher fingerprint is returned
+/// unchanged.
+fn toolchain_fingerprint(launcher_fingerprint: &str, jre_home: Option<&Path>) -> String {
+    let Some(jre_home) = jre_home else {
+        return launcher_fingerprint.to_owned();
+    };
+    let identity = std::fs::read_to_string(jre_home.join("release"))
+        .unwrap_or_else(|_| jre_home.display().to_string());
+    konvoy_util::hash::sha256_bytes(format!("{launcher_fingerprint}\n{identity}").as_bytes())
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn find_installed_konanc_none_when_missing() {
+        let found = find_installed_konanc("99.99.99", toolchain::DEFAULT_JRE_MAJOR).unwrap();
+        assert!(found.is_none());
+    }
+
+    #[test]
+    fn konanc_source_display_names_the_location() {
+        assert!(KonancSource::Managed.to_string().contains("~/.konvoy"));
+    }
+
+    fn fake_jre(release: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("release"), release).unwrap();
+        dir
+    }
+
+    #[test]
+    fn toolchain_fingerprint_differs_per_jre_identity() {
+        let jre17 = fake_jre("JAVA_VERSION=\"17.0.12\"\n");
+        let jre21 = fake_jre("JAVA_VERSION=\"21.0.5\"\n");
+        let a = toolchain_fingerprint("launcher", Some(jre17.path()));
+        let b = toolchain_fingerprint("launcher", Some(jre21.path()));
+        assert_ne!(a, b);
+        assert_ne!(a, "launcher");
+    }
+
+    #[test]
+    fn toolchain_fingerprint_tracks_in_place_jre_upgrade() {
+        let jre = fake_jre("JAVA_VERSION=\"21.0.5\"\n");
+        let before = toolchain_fingerprint("launcher", Some(jre.path()));
+        std::fs::write(jre.path().join("release"), "JAVA_VERSION=\"21.0.6\"\n").unwrap();
+        let after = toolchain_fingerprint("launcher", Some(jre.path()));
+        assert_ne!(before, after);
+    }
+
+    #[test]
+    fn toolchain_fingerprint_is_stable_for_same_inputs() {
+        let jre = fake_jre("JAVA_VERSION=\"21.0.5\"\n");
+        assert_eq!(
+            toolchain_fingerprint("launcher", Some(jre.path())),
+            toolchain_fingerprint("launcher", Some(jre.path()))
+        );
+    }
+
+    #[test]
+    fn toolchain_fingerprint_without_jre_is_launcher_hash() {
+        assert_eq!(toolchain_fingerprint("launcher", None), "launcher");
+    }
+
     #[test]
     fn parse_version_kotlinc_native_format() {
         let raw = "info: kotlinc-native 2.1.0 (JRE 17.0.2+8)";
@@ -238,4 +475,99 @@ mod tests {
         assert!(!is_semver_like("abc"));
         assert!(!is_semver_like("2.1.0.4"));
     }
+
+    /// Lay out a fake managed install (launcher + JRE dir) in a temporary
+    /// toolchains dir, which stays in use until the returned guard drops.
+    fn fake_install(version: &str, launcher: &[u8]) -> toolchain::tests::IsolatedToolchains {
+        let toolchains = toolchain::tests::isolated_toolchains();
+        let dir = toolchain::version_dir(version).unwrap();
+        std::fs::create_dir_all(dir.join("bin")).unwrap();
+        std::fs::create_dir_all(dir.join("jre")).unwrap();
+        std::fs::write(dir.join("bin").join("konanc"), launcher).unwrap();
+        toolchains
+    }
+
+    #[test]
+    fn installed_fingerprint_none_when_not_installed() {
+        assert_eq!(installed_fingerprint("0.0.0-fp-absent").unwrap(), None);
+        assert!(!verify_fingerprint("0.0.0-fp-absent", "abc").unwrap());
+    }
+
+    #[test]
+    fn verify_fingerprint_accepts_matching_install() {
+        let version = "0.0.0-fp-match";
+        let _toolchains = fake_install(version, b"#!/bin/sh\n");
+        let expected = konvoy_util::hash::sha256_bytes(b"#!/bin/sh\n");
+        assert!(verify_fingerprint(version, &expected).unwrap());
+    }
+
+    #[test]
+    fn verify_fingerprint_rejects_modified_install() {
+        let version = "0.0.0-fp-mismatch";
+        let _toolchains = fake_install(version, b"#!/bin/sh\necho tampered\n");
+        let err = verify_fingerprint(version, "0".repeat(64).as_str())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("does not match konvoy.lock"), "{err}");
+        assert!(
+            err.contains("konvoy toolchain install 0.0.0-fp-mismatch"),
+            "{err}"
+        );
+    }
+
+    #[cfg(unix)]
+    fn fake_konanc(dir: &Path, version: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let script = dir.join("konanc");
+        std::fs::write(
+            &script,
+            format!("#!/bin/sh\necho 'info: kotlinc-native {version}'\n"),
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        script
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn override_reports_binary_version_and_fingerprint() {
+        let tmp = tempfile::tempdir().unwrap();
+        let script = fake_konanc(tmp.path(), "2.3.255-SNAPSHOT");
+
+        let resolved = resolve_override(&script, "2.1.0", toolchain::DEFAULT_JRE_MAJOR).unwrap();
+
+        assert_eq!(resolved.source, KonancSource::Override);
+        assert_eq!(resolved.info.path, script);
+        assert_eq!(resolved.info.version, "2.3.255-SNAPSHOT");
+        assert_eq!(
+            resolved.info.launcher_fingerprint,
+            konvoy_util::hash::sha256_file(&script).unwrap()
+        );
+        assert!(resolved.konanc_tarball_sha256.is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn override_fingerprint_changes_with_the_binary() {
+        let tmp = tempfile::tempdir().unwrap();
+        let script = fake_konanc(tmp.path(), "2.3.0");
+        let first = resolve_override(&script, "2.3.0", toolchain::DEFAULT_JRE_MAJOR).unwrap();
+        fake_konanc(tmp.path(), "2.3.1");
+        let second = resolve_override(&script, "2.3.0", toolchain::DEFAULT_JRE_MAJOR).unwrap();
+        assert_ne!(first.info.fingerprint, second.info.fingerprint);
+    }
+
+    #[test]
+    fn override_missing_path_is_a_clear_error() {
+        let tmp = tempfile::tempdir().unwrap();
+        let missing = tmp.path().join("no-such-konanc");
+        let err = resolve_override(&missing, "2.1.0", toolchain::DEFAULT_JRE_MAJOR).unwrap_err();
+        assert!(matches!(err, KonancError::OverrideNotFound { .. }));
+        let msg = err.to_string();
+        assert!(
+            msg.contains("KONVOY_KONANC") && msg.contains("no-such-konanc"),
+            "{msg}"
+        );
+    }
 }
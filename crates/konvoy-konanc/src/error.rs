@@ -9,6 +9,10 @@ pub enum KonancError {
     #[error("konanc not found — install Kotlin/Native and add it to PATH, or set KONANC_HOME")]
     NotFound,
 
+    /// `KONVOY_KONANC` names a path with no file behind it.
+    #[error("KONVOY_KONANC points to {path}, which does not exist — point it at a konanc binary or unset it to use the managed toolchain")]
+    OverrideNotFound { path: PathBuf },
+
     /// konanc was found but is not executable.
     #[error("konanc found at {path} but is not executable — check file permissions")]
     NotExecutable { path: PathBuf },
@@ -32,6 +36,16 @@ pub enum KonancError {
     #[error("compilation failed with {error_count} error(s)")]
     CompilationFailed { error_count: usize },
 
+    /// konanc ran past the configured timeout and was killed.
+    ///
+    /// `partial_stderr` holds whatever the compiler printed before it was
+    /// killed; the tail is shown so users can see where it got stuck.
+    #[error("konanc did not finish within {seconds}s and was killed — raise `timeout_secs` under [build] in konvoy.toml or pass a larger `--timeout`{}", last_output(partial_stderr))]
+    Timeout {
+        seconds: u64,
+        partial_stderr: String,
+    },
+
     /// No source files provided.
     #[error("no source files specified — add .kt files to the sources list")]
     NoSources,
@@ -55,10 +69,24 @@ pub enum KonancError {
     #[error("unsupported platform {os}/{arch} — Kotlin/Native prebuilt binaries are not available for this platform")]
     UnsupportedPlatform { os: String, arch: String },
 
+    /// The platform is one Konvoy intends to support but does not yet.
+    #[error("{target} is not supported yet — Windows builds are not implemented in konvoy; use a Linux or macOS machine")]
+    TargetNotYetSupported { target: String },
+
     /// Failed to download a toolchain tarball.
     #[error("cannot download Kotlin/Native {version}: {message}")]
     Download { version: String, message: String },
 
+    /// The toolchain mirror answered 404: it does not host this version for
+    /// this platform.
+    #[error("toolchain mirror {mirror} has no Kotlin/Native {version} for {platform} ({url} was not found) — upload the tarball to the mirror or install without it")]
+    NotOnMirror {
+        mirror: String,
+        version: String,
+        platform: String,
+        url: String,
+    },
+
     /// Failed to extract a toolchain tarball.
     #[error("cannot extract Kotlin/Native {version}: {message}")]
     Extract { version: String, message: String },
@@ -75,6 +103,16 @@ pub enum KonancError {
     #[error("corrupt toolchain at {path} — run `konvoy toolchain install {version}` to reinstall")]
     CorruptToolchain { path: PathBuf, version: String },
 
+    /// The installed toolchain no longer matches the fingerprint recorded in
+    /// `konvoy.lock` (modified or partially extracted install).
+    #[error("Kotlin/Native {version} at {path} does not match konvoy.lock (expected konanc fingerprint {expected}, found {actual}) — remove {path} and run `konvoy toolchain install {version}`")]
+    FingerprintMismatch {
+        version: String,
+        path: PathBuf,
+        expected: String,
+        actual: String,
+    },
+
     /// Failed to install or locate the bundled JRE.
     #[error("jre installation failed: {message}")]
     JreInstall { message: String },
@@ -89,3 +127,205 @@ pub enum KonancError {
         source: std::io::Error,
     },
 }
+
+impl KonancError {
+    /// A stable, kebab-case identifier for this error, for callers that
+    /// handle errors programmatically instead of matching on the message.
+    pub fn code(&self) -> &'static str {
+        match self {
+            KonancError::NotFound => "konanc-not-found",
+            KonancError::OverrideNotFound { .. } => "konanc-override-not-found",
+            KonancError::NotExecutable { .. } => "konanc-not-executable",
+            KonancError::Exec { .. } => "konanc-exec-failed",
+            KonancError::VersionParse { .. } => "konanc-version-unparsable",
+            KonancError::Fingerprint { .. } => "konanc-fingerprint-failed",
+            KonancError::CompilationFailed { .. } => "compilation-failed",
+            KonancError::Timeout { .. } => "compilation-timeout",
+            KonancError::NoSources => "no-sources",
+            KonancError::NoOutput => "no-output",
+            KonancError::MissingToolchain { .. } => "platform-toolchain-missing",
+            KonancError::Util(_) => "util",
+            KonancError::UnsupportedPlatform { .. } => "unsupported-platform",
+            KonancError::TargetNotYetSupported { .. } => "target-not-yet-supported",
+            KonancError::Download { .. } => "toolchain-download-failed",
+            KonancError::NotOnMirror { .. } => "toolchain-not-on-mirror",
+            KonancError::Extract { .. } => "toolchain-extract-failed",
+            KonancError::PathTraversal { .. } => "toolchain-path-traversal",
+            KonancError::VersionMismatch { .. } => "toolchain-version-mismatch",
+            KonancError::CorruptToolchain { .. } => "toolchain-corrupt",
+            KonancError::FingerprintMismatch { .. } => "toolchain-fingerprint-mismatch",
+            KonancError::JreInstall { .. } => "jre-install-failed",
+            KonancError::Io { .. } => "toolchain-io",
+        }
+    }
+}
+
+/// How many trailing lines of partial compiler output a timeout error shows.
+const TIMEOUT_TAIL_LINES: usize = 20;
+
+/// Render the last few non-empty lines of `output` as an indented block, or
+/// nothing when the compiler printed nothing.
+fn last_output(output: &str) -> String {
+    let lines: Vec<&str> = output.lines().filter(|l| !l.trim().is_empty()).collect();
+    let tail = lines
+        .get(lines.len().saturating_sub(TIMEOUT_TAIL_LINES)..)
+        .unwrap_or_default();
+    if tail.is_empty() {
+        return String::new();
+    }
+    let mut rendered = String::from("\n  last compiler output:");
+    for line in tail {
+        rendered.push_str("\n    ");
+        rendered.push_str(line);
+    }
+    rendered
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    fn s() -> String {
+        String::new()
+    }
+
+    #[test]
+    fn every_variant_has_a_distinct_stable_code() {
+        let cases = [
+            (KonancError::NotFound, "konanc-not-found"),
+            (
+                KonancError::OverrideNotFound {
+                    path: PathBuf::new(),
+                },
+                "konanc-override-not-found",
+            ),
+            (
+                KonancError::NotExecutable {
+                    path: PathBuf::new(),
+                },
+                "konanc-not-executable",
+            ),
+            (
+                KonancError::Exec {
+                    source: std::io::Error::from(std::io::ErrorKind::Other),
+                },
+                "konanc-exec-failed",
+            ),
+            (
+                KonancError::VersionParse { output: s() },
+                "konanc-version-unparsable",
+            ),
+            (
+                KonancError::Fingerprint {
+                    path: PathBuf::new(),
+                    source: konvoy_util::error::UtilError::NoHomeDir,
+                },
+                "konanc-fingerprint-failed",
+            ),
+            (
+                KonancError::CompilationFailed { error_count: 0 },
+                "compilation-failed",
+            ),
+            (
+                KonancError::Timeout {
+                    seconds: 0,
+                    partial_stderr: s(),
+                },
+                "compilation-timeout",
+            ),
+            (KonancError::NoSources, "no-sources"),
+            (KonancError::NoOutput, "no-output"),
+            (
+                KonancError::MissingToolchain {
+                    message: s(),
+                    fix_command: s(),
+                },
+                "platform-toolchain-missing",
+            ),
+            (
+                KonancError::Util(konvoy_util::error::UtilError::NoHomeDir),
+                "util",
+            ),
+            (
+                KonancError::UnsupportedPlatform { os: s(), arch: s() },
+                "unsupported-platform",
+            ),
+            (
+                KonancError::TargetNotYetSupported { target: s() },
+                "target-not-yet-supported",
+            ),
+            (
+                KonancError::Download {
+                    version: s(),
+                    message: s(),
+                },
+                "toolchain-download-failed",
+            ),
+            (
+                KonancError::NotOnMirror {
+                    mirror: s(),
+                    version: s(),
+                    platform: s(),
+                    url: s(),
+                },
+                "toolchain-not-on-mirror",
+            ),
+            (
+                KonancError::Extract {
+                    version: s(),
+                    message: s(),
+                },
+                "toolchain-extract-failed",
+            ),
+            (
+                KonancError::PathTraversal {
+                    entry_path: s(),
+                    dest: s(),
+                },
+                "toolchain-path-traversal",
+            ),
+            (
+                KonancError::VersionMismatch {
+                    expected: s(),
+                    actual: s(),
+                },
+                "toolchain-version-mismatch",
+            ),
+            (
+                KonancError::CorruptToolchain {
+                    path: PathBuf::new(),
+                    version: s(),
+                },
+                "toolchain-corrupt",
+            ),
+            (
+                KonancError::FingerprintMismatch {
+                    version: s(),
+                    path: PathBuf::new(),
+                    expected: s(),
+                    actual: s(),
+                },
+                "toolchain-fingerprint-mismatch",
+            ),
+            (
+                KonancError::JreInstall { message: s() },
+                "jre-install-failed",
+            ),
+            (
+                KonancError::Io {
+                    path: s(),
+                    source: std::io::Error::from(std::io::ErrorKind::Other),
+                },
+                "toolchain-io",
+            ),
+        ];
+        let mut seen = BTreeSet::new();
+        for (err, code) in cases {
+            assert_eq!(err.code(), code, "{err:?}");
+            assert!(seen.insert(code), "duplicate code {code}");
+        }
+    }
+}
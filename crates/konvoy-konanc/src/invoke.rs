@@ -3,15 +3,18 @@
 use std::collections::HashSet;
 use std::env;
 use std::fs;
-use std::io;
+use std::io::{self, BufRead, BufReader, Read, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use crate::detect::KonancInfo;
 use crate::error::KonancError;
 
 /// Severity level of a compiler diagnostic.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum DiagnosticLevel {
     Error,
     Warning,
@@ -87,6 +90,10 @@ pub enum ProduceKind {
     Program,
     /// A Kotlin/Native library (`.klib`).
     Library,
+    /// A static C library (`.a`) plus its `_api.h` header.
+    StaticLib,
+    /// A dynamic C library (`.so`, `.dylib`) plus its `_api.h` header.
+    DynamicLib,
 }
 
 /// Builder for constructing a `konanc` invocation.
@@ -99,10 +106,22 @@ pub struct KonancCommand {
     produce: ProduceKind,
     libraries: Vec<PathBuf>,
     plugins: Vec<PathBuf>,
+    /// Compiler plugin options as `(plugin id, key, value)`.
+    plugin_options: Vec<(String, String, String)>,
     java_home: Option<PathBuf>,
     generate_test_runner: bool,
+    /// Kotlin language level (`-language-version`), e.g. `1.9`.
+    language_version: Option<String>,
+    /// Kotlin API level (`-api-version`), e.g. `1.9`.
+    api_version: Option<String>,
+    /// Fully qualified entry point (`-entry`), when not the root-package `main`.
+    entry: Option<String>,
     /// Klib to include via `-Xinclude=` (used for linking a klib into a binary).
     include: Option<PathBuf>,
+    /// Echo compiler stderr to the terminal line-by-line while it runs.
+    stream_output: bool,
+    /// Kill the compiler if it runs longer than this.
+    timeout: Option<Duration>,
 }
 
 impl KonancCommand {
@@ -153,6 +172,13 @@ impl KonancCommand {
         self
     }
 
+    /// Set compiler plugin options as `(plugin id, key, value)` triples (emits
+    /// `-P plugin:<id>:<key>=<value>` for each).
+    pub fn plugin_options(mut self, options: &[(String, String, String)]) -> Self {
+        self.plugin_options = options.to_vec();
+        self
+    }
+
     /// Set JAVA_HOME for the bundled JRE.
     pub fn java_home(mut self, path: &Path) -> Self {
         self.java_home = Some(path.to_path_buf());
@@ -165,12 +191,43 @@ impl KonancCommand {
         self
     }
 
+    /// Compile against an older Kotlin language level (adds `-language-version <version>`).
+    pub fn language_version(mut self, version: Option<&str>) -> Self {
+        self.language_version = version.map(str::to_owned);
+        self
+    }
+
+    /// Restrict the usable standard library API (adds `-api-version <version>`).
+    pub fn api_version(mut self, version: Option<&str>) -> Self {
+        self.api_version = version.map(str::to_owned);
+        self
+    }
+
+    /// Set the program's entry point (adds `-entry <name>`), e.g. `bench.main`.
+    pub fn entry(mut self, name: &str) -> Self {
+        self.entry = Some(name.to_owned());
+        self
+    }
+
     /// Set a klib to include via `-Xinclude=` (for linking a klib into a binary).
     pub fn include(mut self, path: &Path) -> Self {
         self.include = Some(path.to_path_buf());
         self
     }
 
+    /// Stream compiler stderr to the terminal as it is produced (used for
+    /// `--verbose`). The output is still captured in full for diagnostics.
+    pub fn stream_output(mut self, enabled: bool) -> Self {
+        self.stream_output = enabled;
+        self
+    }
+
+    /// Kill the compiler if it has not exited within `limit` (`None` waits forever).
+    pub fn timeout(mut self, limit: Option<Duration>) -> Self {
+        self.timeout = limit;
+        self
+    }
+
     /// Build the argument list without executing.
     ///
     /// # Errors
@@ -207,6 +264,30 @@ impl KonancCommand {
                 args.push("-produce".to_owned());
                 args.push("library".to_owned());
             }
+            ProduceKind::StaticLib => {
+                args.push("-produce".to_owned());
+                args.push("static".to_owned());
+            }
+            ProduceKind::DynamicLib => {
+                args.push("-produce".to_owned());
+                args.push("dynamic".to_owned());
+            }
+        }
+
+        // Language and API level
+        if let Some(version) = &self.language_version {
+            args.push("-language-version".to_owned());
+            args.push(version.clone());
+        }
+        if let Some(version) = &self.api_version {
+            args.push("-api-version".to_owned());
+            args.push(version.clone());
+        }
+
+        // Entry point other than the root-package `main`
+        if let Some(entry) = &self.entry {
+            args.push("-entry".to_owned());
+            args.push(entry.clone());
         }
 
         // Include klib (for linking step)
@@ -224,6 +305,10 @@ impl KonancCommand {
         for plugin in &self.plugins {
             args.push(format!("-Xplugin={}", plugin.display()));
         }
+        for (id, key, value) in &self.plugin_options {
+            args.push("-P".to_owned());
+            args.push(format!("plugin:{id}:{key}={value}"));
+        }
 
         // Test runner generation
         if self.generate_test_runner {
@@ -244,6 +329,7 @@ impl KonancCommand {
     /// Returns an error if:
     /// - Sources or output path are not set
     /// - The konanc binary cannot be executed
+    /// - A timeout is set and konanc runs past it (the process is killed)
     pub fn execute(&self, konanc: &KonancInfo) -> Result<CompilationResult, KonancError> {
         let args = self.build_args()?;
         let Some(output_path) = &self.output else {
@@ -267,18 +353,55 @@ impl KonancCommand {
             })?;
             cmd.env("PATH", path);
         }
-        let cmd_output = cmd
-            .output()
+        // With a timeout, run konanc in its own process group so the kill below
+        // also reaches the JVM it launches, not just the launcher script.
+        #[cfg(unix)]
+        if self.timeout.is_some() {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+        let mut child = cmd
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
             .map_err(|source| KonancError::Exec { source })?;
 
-        let raw_stdout = String::from_utf8_lossy(&cmd_output.stdout).into_owned();
-        let raw_stderr = String::from_utf8_lossy(&cmd_output.stderr).into_owned();
+        // Drain both pipes on their own threads so a chatty compiler can never
+        // block on a full pipe while we wait for it to exit.
+        let stdout_capture = OutputCapture::spawn(child.stdout.take(), false);
+        let stderr_capture = OutputCapture::spawn(child.stderr.take(), self.stream_output);
+
+        let status = match self.timeout {
+            Some(limit) => wait_with_timeout(&mut child, limit),
+            None => child.wait().map(Some),
+        }
+        .map_err(|source| KonancError::Exec { source })?;
+
+        let Some(status) = status else {
+            // The whole process group is dead, so the pipes are closed and the
+            // readers finish with everything konanc printed before the kill.
+            let _ = stdout_capture.finish();
+            let partial_stderr = stderr_capture
+                .finish()
+                .map_err(|source| KonancError::Exec { source })?;
+            return Err(KonancError::Timeout {
+                seconds: self.timeout.map_or(0, |limit| limit.as_secs()),
+                partial_stderr,
+            });
+        };
+
+        let raw_stdout = stdout_capture
+            .finish()
+            .map_err(|source| KonancError::Exec { source })?;
+        let raw_stderr = stderr_capture
+            .finish()
+            .map_err(|source| KonancError::Exec { source })?;
 
-        let mut diagnostics = parse_diagnostics(&raw_stderr);
+        let mut diagnostics = parse_output_diagnostics(&raw_stdout, &raw_stderr);
         detect_toolchain_errors(&raw_stderr, &mut diagnostics);
 
         Ok(CompilationResult {
-            success: cmd_output.status.success(),
+            success: status.success(),
             output_path: output_path.clone(),
             diagnostics,
             raw_stdout,
@@ -287,6 +410,124 @@ impl KonancCommand {
     }
 }
 
+/// How often `wait_with_timeout` polls the child for exit.
+const TIMEOUT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Wait for `child` to exit, killing it once `limit` has elapsed.
+///
+/// On Unix the child must lead its own process group (`process_group(0)`): the
+/// kill takes the whole group, so processes it spawned die with it.
+///
+/// Returns `Ok(None)` when the child was killed for exceeding the limit.
+fn wait_with_timeout(child: &mut Child, limit: Duration) -> io::Result<Option<ExitStatus>> {
+    let deadline = Instant::now() + limit;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            // The child may exit between the check and the kill; either way it
+            // is reaped below so no zombie is left behind.
+            kill_process_group(child);
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(TIMEOUT_POLL_INTERVAL);
+    }
+}
+
+/// SIGKILL the process group `child` leads, falling back to killing the child
+/// alone when the group cannot be signalled.
+fn kill_process_group(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        let killed = Command::new("kill")
+            .args(["-KILL", "--", &format!("-{}", child.id())])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+        if killed {
+            return;
+        }
+    }
+    let _ = child.kill();
+}
+
+/// A compiler output pipe being drained on a background thread.
+///
+/// The bytes land in a shared buffer as they arrive, so a partial capture can be
+/// taken (`snapshot`) even when the reader never reaches EOF.
+struct OutputCapture {
+    buffer: Arc<Mutex<Vec<u8>>>,
+    reader: Option<thread::JoinHandle<io::Result<()>>>,
+}
+
+impl OutputCapture {
+    /// Start draining `pipe` (an absent pipe captures nothing). When `echo` is
+    /// set, each line is also written to stderr as it arrives.
+    fn spawn(pipe: Option<impl Read + Send + 'static>, echo: bool) -> Self {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let reader = pipe.map(|pipe| {
+            let sink = Arc::clone(&buffer);
+            thread::spawn(move || {
+                if echo {
+                    capture_lines(pipe, Some(&mut io::stderr()), &sink)
+                } else {
+                    capture_lines(pipe, None, &sink)
+                }
+            })
+        });
+        Self { buffer, reader }
+    }
+
+    /// Everything captured so far, without waiting for the pipe to close.
+    fn snapshot(&self) -> String {
+        let bytes = self
+            .buffer
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    /// Wait for the pipe to reach EOF and return the complete capture.
+    fn finish(mut self) -> io::Result<String> {
+        if let Some(reader) = self.reader.take() {
+            reader
+                .join()
+                .map_err(|_| io::Error::other("compiler output reader thread panicked"))??;
+        }
+        Ok(self.snapshot())
+    }
+}
+
+/// Read `reader` to EOF line-by-line, appending everything read to `sink`.
+///
+/// When `echo` is set, each line is written through as soon as it arrives, so
+/// a long-running compile shows progress instead of a blank terminal. The
+/// capture in `sink` is always complete regardless of echoing.
+fn capture_lines(
+    reader: impl Read,
+    mut echo: Option<&mut dyn Write>,
+    sink: &Mutex<Vec<u8>>,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(reader);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(());
+        }
+        if let Some(out) = echo.as_mut() {
+            // Echoing is best-effort: a closed terminal must not lose the capture.
+            let _ = out.write_all(&line).and_then(|()| out.flush());
+        }
+        sink.lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .extend_from_slice(&line);
+    }
+}
+
 fn should_try_macos_xcodebuild_shim(target: Option<&str>) -> bool {
     target.is_some_and(|target| target.starts_with("macos_"))
 }
@@ -379,13 +620,46 @@ pub fn parse_diagnostics(stderr: &str) -> Vec<Diagnostic> {
     diagnostics
 }
 
-/// Try to parse a diagnostic with file location: `file.kt:10:5: error: message`
+/// Parse the diagnostics konanc printed on either stream.
+///
+/// Some compiler configurations route warnings to stdout. Stderr's diagnostics
+/// come first; a stdout diagnostic identical to one on stderr is dropped, so a
+/// line echoed to both streams counts once.
+pub fn parse_output_diagnostics(stdout: &str, stderr: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = parse_diagnostics(stderr);
+    let on_stderr: HashSet<_> = diagnostics.iter().map(diagnostic_key).collect();
+    let only_on_stdout: Vec<Diagnostic> = parse_diagnostics(stdout)
+        .into_iter()
+        .filter(|diag| !on_stderr.contains(&diagnostic_key(diag)))
+        .collect();
+    diagnostics.extend(only_on_stdout);
+    diagnostics
+}
+
+/// Everything that makes two diagnostics the same report.
+fn diagnostic_key(diag: &Diagnostic) -> (DiagnosticLevel, &str, Option<&str>, Option<u32>) {
+    (
+        diag.level.clone(),
+        diag.message.as_str(),
+        diag.file.as_deref(),
+        diag.line,
+    )
+}
+
+/// Try to parse a diagnostic with file location: `file.kt:10:5: error: message`,
+/// or the Gradle-style short form `e: file.kt:10:5: message`.
 fn try_parse_located_diagnostic(line: &str) -> Option<Diagnostic> {
     // Pattern: <file>:<line>:<col>: <level>: <message>
     // or:      <file>:<line>: <level>: <message>
-    let (file_part, rest) = split_file_location(line)?;
-
-    let (level, message) = parse_level_message(rest)?;
+    // or:      <e|w|i>: <file>:<line>:<col>: <message>
+    let (file_part, level, message) = if let Some((file_part, rest)) = split_file_location(line) {
+        let (level, message) = parse_level_message(rest)?;
+        (file_part, level, message)
+    } else {
+        let (level, rest) = parse_short_level(line)?;
+        let (file_part, message) = split_leading_location(rest)?;
+        (file_part, level, message.trim().to_owned())
+    };
 
     Some(Diagnostic {
         level,
@@ -417,6 +691,16 @@ fn split_file_location(line: &str) -> Option<(FileLocation, &str)> {
     None
 }
 
+/// Split the location off the front of a short-form diagnostic body:
+/// `file.kt:10:5: message`, or `file:///abs/file.kt:10:5 message` as newer
+/// compilers print it.
+fn split_leading_location(s: &str) -> Option<(FileLocation, &str)> {
+    let (location, message) = s.split_once(char::is_whitespace)?;
+    let location = location.trim_end_matches(':');
+    let location = location.strip_prefix("file://").unwrap_or(location);
+    Some((parse_file_and_line(location)?, message))
+}
+
 fn parse_file_and_line(s: &str) -> Option<FileLocation> {
     // Try "file:line:col" first, then "file:line"
     let mut parts: Vec<&str> = s.rsplitn(3, ':').collect();
@@ -451,10 +735,28 @@ fn parse_level_message(s: &str) -> Option<(DiagnosticLevel, String)> {
         ("info:", DiagnosticLevel::Info),
     ];
 
-    prefixes.into_iter().find_map(|(prefix, level)| {
-        s.strip_prefix(prefix)
-            .map(|msg| (level, msg.trim().to_owned()))
-    })
+    prefixes
+        .into_iter()
+        .find_map(|(prefix, level)| {
+            s.strip_prefix(prefix)
+                .map(|msg| (level, msg.trim().to_owned()))
+        })
+        .or_else(|| parse_short_level(s).map(|(level, msg)| (level, msg.trim().to_owned())))
+}
+
+/// Parse the Gradle-style short level prefix (`e: `, `w: `, `i: `) some Kotlin
+/// compiler configurations print instead of `error:`/`warning:`/`info:`,
+/// returning the level and the rest of the line.
+fn parse_short_level(s: &str) -> Option<(DiagnosticLevel, &str)> {
+    let prefixes = [
+        ("e: ", DiagnosticLevel::Error),
+        ("w: ", DiagnosticLevel::Warning),
+        ("i: ", DiagnosticLevel::Info),
+    ];
+
+    prefixes
+        .into_iter()
+        .find_map(|(prefix, level)| s.strip_prefix(prefix).map(|rest| (level, rest)))
 }
 
 /// Detect platform toolchain errors and add actionable diagnostics.
@@ -495,6 +797,55 @@ fn detect_toolchain_errors(stderr: &str, diagnostics: &mut Vec<Diagnostic>) {
             line: None,
         });
     }
+
+    // Linux: libraries slim container images leave out. Reported together in
+    // one diagnostic so several missing libs yield a single install command.
+    let missing: Vec<(&str, &str)> = MISSING_LIB_PACKAGES
+        .iter()
+        .copied()
+        .filter(|(lib, _)| links_missing_library(stderr, lib))
+        .collect();
+    if !missing.is_empty() {
+        let libs: Vec<String> = missing.iter().map(|(lib, _)| format!("lib{lib}")).collect();
+        let mut packages: Vec<&str> = missing.iter().map(|(_, package)| *package).collect();
+        packages.dedup();
+        push_unique(Diagnostic {
+            level: DiagnosticLevel::Error,
+            message: format!(
+                "missing system libraries {} — install the development packages: `sudo apt install {}`",
+                libs.join(", "),
+                packages.join(" ")
+            ),
+            file: None,
+            line: None,
+        });
+    }
+}
+
+/// Linker libraries often absent from minimal Linux images, with the Debian/Ubuntu
+/// package that provides each.
+const MISSING_LIB_PACKAGES: &[(&str, &str)] = &[
+    ("z", "zlib1g-dev"),
+    ("crypt", "libcrypt-dev"),
+    ("pthread", "libc6-dev"),
+];
+
+/// Whether the linker reported `-l<lib>` as not found (GNU ld's `cannot find
+/// -lz` or lld's `unable to find library -lz`), matching the whole library name
+/// so `-lz` does not match `-lzstd`.
+fn links_missing_library(stderr: &str, lib: &str) -> bool {
+    let flag = format!("-l{lib}");
+    ["cannot find ", "unable to find library "]
+        .iter()
+        .any(|prefix| {
+            let needle = format!("{prefix}{flag}");
+            stderr.match_indices(&needle).any(|(at, _)| {
+                stderr
+                    .get(at + needle.len()..)
+                    .and_then(|rest| rest.chars().next())
+                    .is_none_or(|c| !(c.is_alphanumeric() || c == '_' || c == '+'))
+            })
+        })
 }
 
 #[cfg(test)]
@@ -538,6 +889,45 @@ mod tests {
         assert!(!args.contains(&"-opt".to_owned()));
     }
 
+    #[test]
+    fn build_args_entry_point() {
+        let cmd = KonancCommand::new()
+            .sources(&[PathBuf::from("main.kt")])
+            .output(Path::new("out"))
+            .entry("bench.main");
+
+        let args = cmd.build_args().unwrap();
+        let pos = args.iter().position(|a| a == "-entry").unwrap();
+        assert_eq!(args.get(pos + 1).map(String::as_str), Some("bench.main"));
+    }
+
+    #[test]
+    fn build_args_language_and_api_version() {
+        let cmd = KonancCommand::new()
+            .sources(&[PathBuf::from("main.kt")])
+            .output(Path::new("out"))
+            .language_version(Some("1.9"))
+            .api_version(Some("1.8"));
+
+        let args = cmd.build_args().unwrap();
+        let pos = args.iter().position(|a| a == "-language-version").unwrap();
+        assert_eq!(args.get(pos + 1).map(String::as_str), Some("1.9"));
+        let pos = args.iter().position(|a| a == "-api-version").unwrap();
+        assert_eq!(args.get(pos + 1).map(String::as_str), Some("1.8"));
+    }
+
+    #[test]
+    fn build_args_omit_unset_language_version() {
+        let cmd = KonancCommand::new()
+            .sources(&[PathBuf::from("main.kt")])
+            .output(Path::new("out"))
+            .language_version(None);
+
+        let args = cmd.build_args().unwrap();
+        assert!(!args.iter().any(|a| a == "-language-version"));
+        assert!(!args.iter().any(|a| a == "-api-version"));
+    }
+
     #[test]
     fn build_args_multiple_sources() {
         let cmd = KonancCommand::new()
@@ -578,6 +968,57 @@ mod tests {
         assert_eq!(diags.get(0).unwrap().level, DiagnosticLevel::Warning);
     }
 
+    #[test]
+    fn parse_diagnostics_short_prefix_located() {
+        let diags = parse_diagnostics(
+            "e: src/main.kt:10:5: unresolved reference: foo\nw: src/util.kt:3: parameter 'x' is never used\n",
+        );
+        assert_eq!(diags.len(), 2);
+        let e = diags.get(0).unwrap();
+        assert_eq!(e.level, DiagnosticLevel::Error);
+        assert_eq!(e.file, Some("src/main.kt".to_owned()));
+        assert_eq!(e.line, Some(10));
+        assert_eq!(e.message, "unresolved reference: foo");
+        let w = diags.get(1).unwrap();
+        assert_eq!(w.level, DiagnosticLevel::Warning);
+        assert_eq!(w.file, Some("src/util.kt".to_owned()));
+        assert_eq!(w.line, Some(3));
+        assert_eq!(w.message, "parameter 'x' is never used");
+    }
+
+    #[test]
+    fn parse_diagnostics_short_prefix_file_uri() {
+        let diags =
+            parse_diagnostics("e: file:///home/me/app/src/main.kt:7:9 Unresolved reference 'bar'.");
+        assert_eq!(diags.len(), 1);
+        let d = diags.get(0).unwrap();
+        assert_eq!(d.level, DiagnosticLevel::Error);
+        assert_eq!(d.file, Some("/home/me/app/src/main.kt".to_owned()));
+        assert_eq!(d.line, Some(7));
+        assert_eq!(d.message, "Unresolved reference 'bar'.");
+    }
+
+    #[test]
+    fn parse_diagnostics_short_prefix_bare() {
+        let diags = parse_diagnostics(
+            "e: java.lang.OutOfMemoryError: Java heap space\nw: flag is deprecated\ni: using cache\n",
+        );
+        assert_eq!(diags.len(), 3);
+        let e = diags.get(0).unwrap();
+        assert_eq!(e.level, DiagnosticLevel::Error);
+        assert_eq!(e.message, "java.lang.OutOfMemoryError: Java heap space");
+        assert!(e.file.is_none());
+        assert_eq!(diags.get(1).unwrap().level, DiagnosticLevel::Warning);
+        assert_eq!(diags.get(1).unwrap().message, "flag is deprecated");
+        assert_eq!(diags.get(2).unwrap().level, DiagnosticLevel::Info);
+    }
+
+    #[test]
+    fn parse_diagnostics_short_prefix_requires_space() {
+        // `e:` must be followed by a space; `e:foo` is not a diagnostic.
+        assert!(parse_diagnostics("e:foo\nwe: are fine\n").is_empty());
+    }
+
     #[test]
     fn parse_diagnostics_located_error() {
         let diags = parse_diagnostics("src/main.kt:10:5: error: expecting ')'");
@@ -612,6 +1053,20 @@ mod tests {
         assert!(diags.is_empty());
     }
 
+    #[test]
+    fn parse_output_diagnostics_merges_streams_without_double_counting() {
+        let diags = parse_output_diagnostics(
+            "w: a.kt:1:1 unused\ne: b.kt:2:3 broken\n",
+            "e: b.kt:2:3 broken\nwarning: deprecated flag\n",
+        );
+        let messages: Vec<&str> = diags.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, ["broken", "deprecated flag", "unused"]);
+
+        // The same message at another location is a separate diagnostic.
+        let diags = parse_output_diagnostics("e: b.kt:5:1 broken\n", "e: b.kt:2:3 broken\n");
+        assert_eq!(diags.len(), 2);
+    }
+
     #[test]
     fn parse_diagnostics_non_diagnostic_lines_skipped() {
         let stderr = "some info line\nerror: real problem\nanother line\n";
@@ -641,6 +1096,54 @@ mod tests {
         assert!(diags.get(0).unwrap().message.contains("build-essential"));
     }
 
+    #[test]
+    fn detect_toolchain_linux_missing_libz() {
+        let mut diags = Vec::new();
+        detect_toolchain_errors("ld.lld: error: unable to find library -lz", &mut diags);
+        assert_eq!(diags.len(), 1);
+        let message = &diags.get(0).unwrap().message;
+        assert!(message.contains("libz"), "got: {message}");
+        assert!(message.contains("zlib1g-dev"), "got: {message}");
+    }
+
+    #[test]
+    fn detect_toolchain_linux_missing_libcrypt() {
+        let mut diags = Vec::new();
+        detect_toolchain_errors("/usr/bin/ld: cannot find -lcrypt", &mut diags);
+        assert_eq!(diags.len(), 1);
+        assert!(diags.get(0).unwrap().message.contains("libcrypt-dev"));
+    }
+
+    #[test]
+    fn detect_toolchain_linux_missing_libpthread() {
+        let mut diags = Vec::new();
+        detect_toolchain_errors("ld: cannot find -lpthread: No such file", &mut diags);
+        assert_eq!(diags.len(), 1);
+        assert!(diags.get(0).unwrap().message.contains("libc6-dev"));
+    }
+
+    #[test]
+    fn detect_toolchain_linux_several_missing_libs_yield_one_diagnostic() {
+        let mut diags = Vec::new();
+        let stderr = "ld: cannot find -lz\nld: cannot find -lcrypt\nld: cannot find -lz";
+        detect_toolchain_errors(stderr, &mut diags);
+        detect_toolchain_errors(stderr, &mut diags);
+        assert_eq!(diags.len(), 1);
+        let message = &diags.get(0).unwrap().message;
+        assert!(
+            message.contains("libz, libcrypt")
+                && message.contains("sudo apt install zlib1g-dev libcrypt-dev"),
+            "got: {message}"
+        );
+    }
+
+    #[test]
+    fn detect_toolchain_linux_lib_name_must_match_exactly() {
+        let mut diags = Vec::new();
+        detect_toolchain_errors("ld: cannot find -lzstd", &mut diags);
+        assert!(diags.is_empty());
+    }
+
     #[test]
     fn detect_toolchain_no_issues() {
         let mut diags = Vec::new();
@@ -752,6 +1255,23 @@ mod tests {
         assert!(args.contains(&"library".to_owned()));
     }
 
+    #[test]
+    fn build_args_produce_c_libraries() {
+        for (produce, value) in [
+            (ProduceKind::StaticLib, "static"),
+            (ProduceKind::DynamicLib, "dynamic"),
+        ] {
+            let args = KonancCommand::new()
+                .sources(&[PathBuf::from("lib.kt")])
+                .output(Path::new("out/libffi.a"))
+                .produce(produce)
+                .build_args()
+                .unwrap();
+            let idx = args.iter().position(|a| a == "-produce").unwrap();
+            assert_eq!(args.get(idx + 1).map(String::as_str), Some(value));
+        }
+    }
+
     #[test]
     fn build_args_produce_program_omits_flag() {
         let cmd = KonancCommand::new()
@@ -838,6 +1358,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn build_args_with_plugin_options() {
+        let cmd = KonancCommand::new()
+            .sources(&[PathBuf::from("main.kt")])
+            .output(Path::new("out"))
+            .plugins(&[PathBuf::from("/cache/allopen.jar")])
+            .plugin_options(&[
+                (
+                    "org.jetbrains.kotlin.allopen".to_owned(),
+                    "annotation".to_owned(),
+                    "com.example.Open".to_owned(),
+                ),
+                (
+                    "org.jetbrains.kotlin.allopen".to_owned(),
+                    "preset".to_owned(),
+                    "spring".to_owned(),
+                ),
+            ]);
+
+        let args = cmd.build_args().unwrap();
+        let start = args.iter().position(|a| a == "-P").unwrap();
+        assert_eq!(
+            args.get(start..start + 4).unwrap(),
+            [
+                "-P",
+                "plugin:org.jetbrains.kotlin.allopen:annotation=com.example.Open",
+                "-P",
+                "plugin:org.jetbrains.kotlin.allopen:preset=spring",
+            ]
+        );
+    }
+
     #[test]
     fn build_args_no_plugins_by_default() {
         let cmd = KonancCommand::new()
@@ -846,8 +1398,8 @@ mod tests {
 
         let args = cmd.build_args().unwrap();
         assert!(
-            !args.iter().any(|a| a.starts_with("-Xplugin=")),
-            "should not have -Xplugin args by default"
+            !args.iter().any(|a| a.starts_with("-Xplugin=") || a == "-P"),
+            "should not have plugin args by default"
         );
     }
 
@@ -913,6 +1465,217 @@ mod tests {
         assert!(cmd.plugins.is_empty());
         assert!(cmd.java_home.is_none());
         assert!(!cmd.generate_test_runner);
+        assert!(cmd.entry.is_none());
+        assert!(!cmd.stream_output);
+    }
+
+    #[test]
+    fn stream_output_builder_sets_value() {
+        let cmd = KonancCommand::new().stream_output(true);
+        assert!(cmd.stream_output);
+    }
+
+    #[test]
+    fn stream_output_does_not_change_args() {
+        let base = KonancCommand::new()
+            .sources(&[PathBuf::from("main.kt")])
+            .output(Path::new("out"));
+        let streamed = KonancCommand::new()
+            .sources(&[PathBuf::from("main.kt")])
+            .output(Path::new("out"))
+            .stream_output(true);
+        assert_eq!(base.build_args().unwrap(), streamed.build_args().unwrap());
+    }
+
+    // ── Output capture ──────────────────────────────────────────────────
+
+    fn captured(sink: &Mutex<Vec<u8>>) -> String {
+        String::from_utf8(sink.lock().unwrap().clone()).unwrap()
+    }
+
+    #[test]
+    fn capture_lines_without_echo_returns_everything() {
+        let input = "error: first\nwarning: second\nno trailing newline";
+        let sink = Mutex::new(Vec::new());
+        capture_lines(input.as_bytes(), None, &sink).unwrap();
+        assert_eq!(captured(&sink), input);
+    }
+
+    #[test]
+    fn capture_lines_echo_sees_same_content_as_capture() {
+        let input = "src/main.kt:1:1: error: boom\ninfo: done\n";
+        let sink = Mutex::new(Vec::new());
+        let mut echoed: Vec<u8> = Vec::new();
+        capture_lines(input.as_bytes(), Some(&mut echoed), &sink).unwrap();
+        assert_eq!(captured(&sink), input);
+        assert_eq!(String::from_utf8(echoed).unwrap(), input);
+        // The capture still feeds diagnostic parsing in full.
+        assert_eq!(parse_diagnostics(&captured(&sink)).len(), 2);
+    }
+
+    #[test]
+    fn capture_lines_empty_input() {
+        let sink = Mutex::new(Vec::new());
+        capture_lines(io::empty(), None, &sink).unwrap();
+        assert_eq!(captured(&sink), "");
+    }
+
+    #[test]
+    fn output_capture_absent_pipe_is_empty() {
+        let capture = OutputCapture::spawn(None::<io::Empty>, false);
+        assert_eq!(capture.snapshot(), "");
+        assert_eq!(capture.finish().unwrap(), "");
+    }
+
+    #[test]
+    fn output_capture_finish_returns_full_pipe() {
+        let capture = OutputCapture::spawn(Some("a\nb\n".as_bytes()), false);
+        assert_eq!(capture.finish().unwrap(), "a\nb\n");
+    }
+
+    // ── Timeout ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn timeout_not_set_by_default() {
+        assert!(KonancCommand::new().timeout.is_none());
+    }
+
+    #[test]
+    fn timeout_builder_sets_value() {
+        let cmd = KonancCommand::new().timeout(Some(Duration::from_secs(30)));
+        assert_eq!(cmd.timeout, Some(Duration::from_secs(30)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn wait_with_timeout_returns_status_for_fast_child() {
+        use std::os::unix::process::CommandExt;
+
+        let mut child = Command::new("sh")
+            .args(["-c", "exit 0"])
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let status = wait_with_timeout(&mut child, Duration::from_secs(10)).unwrap();
+        assert!(status.is_some_and(|s| s.success()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn wait_with_timeout_kills_slow_child_and_its_children() {
+        use std::os::unix::process::CommandExt;
+
+        // The child forks a long-running grandchild (like konanc's launcher
+        // starting the JVM) and reports its pid before waiting on it.
+        let tmp = tempfile::tempdir().unwrap();
+        let pid_file = tmp.path().join("grandchild.pid");
+        let mut child = Command::new("sh")
+            .args([
+                "-c",
+                &format!("sleep 30 & echo $! > '{}'; wait", pid_file.display()),
+            ])
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let start = Instant::now();
+        while !fs::read_to_string(&pid_file).is_ok_and(|pid| pid.ends_with('\n')) {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        }
+        let grandchild = fs::read_to_string(&pid_file).unwrap().trim().to_owned();
+
+        let status = wait_with_timeout(&mut child, Duration::from_millis(100)).unwrap();
+        assert!(status.is_none());
+        assert!(start.elapsed() < Duration::from_secs(5));
+        // `kill -0` fails once the grandchild is gone (it is reparented and
+        // reaped by init, so it does not linger as a zombie for long).
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Command::new("kill")
+            .args(["-0", &grandchild])
+            .stderr(Stdio::null())
+            .status()
+            .unwrap()
+            .success()
+        {
+            assert!(
+                Instant::now() < deadline,
+                "grandchild {grandchild} outlived the timeout"
+            );
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn execute_timeout_reports_partial_stderr() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let script = tmp.path().join("konanc");
+        fs::write(
+            &script,
+            "#!/bin/sh\necho 'linking main.kexe' >&2\nexec sleep 10\n",
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        let info = KonancInfo {
+            path: script,
+            version: "2.1.0".to_owned(),
+            fingerprint: String::new(),
+            launcher_fingerprint: String::new(),
+        };
+
+        let err = KonancCommand::new()
+            .sources(&[PathBuf::from("main.kt")])
+            .output(&tmp.path().join("out"))
+            .timeout(Some(Duration::from_millis(500)))
+            .execute(&info)
+            .unwrap_err();
+        match err {
+            KonancError::Timeout { partial_stderr, .. } => {
+                assert!(partial_stderr.contains("linking main.kexe"));
+            }
+            other => panic!("expected Timeout, got {other:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn execute_captures_diagnostics_printed_on_stdout() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let script = tmp.path().join("konanc");
+        fs::write(
+            &script,
+            concat!(
+                "#!/bin/sh\n",
+                "echo 'main.kt:3:5: warning: variable x is never used'\n",
+                "echo 'main.kt:9:1: error: unresolved reference: y' >&2\n",
+                "echo 'main.kt:9:1: error: unresolved reference: y'\n",
+                "exit 1\n",
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        let info = KonancInfo {
+            path: script,
+            version: "2.1.0".to_owned(),
+            fingerprint: String::new(),
+            launcher_fingerprint: String::new(),
+        };
+
+        let result = KonancCommand::new()
+            .sources(&[PathBuf::from("main.kt")])
+            .output(&tmp.path().join("out"))
+            .execute(&info)
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(result.error_count(), 1);
+        assert_eq!(result.warning_count(), 1);
+        let warning = result.diagnostics.get(1).unwrap();
+        assert_eq!(warning.message, "variable x is never used");
+        assert_eq!(warning.line, Some(3));
     }
 
     #[test]
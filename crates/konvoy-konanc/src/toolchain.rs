@@ -1,12 +1,26 @@
 //! Managed toolchain download, installation, and discovery.
 //!
-//! Downloads Kotlin/Native prebuilt tarballs from `download.jetbrains.com`
-//! and installs them under `~/.konvoy/toolchains/<version>/`.
+//! Downloads Kotlin/Native prebuilt tarballs from the JetBrains GitHub
+//! releases (or a configured mirror) and installs them under
+//! `~/.konvoy/toolchains/<version>/`.
 
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
 use crate::error::KonancError;
 
+/// JDK major version of the bundled JRE when `[toolchain] jre` is not set.
+pub const DEFAULT_JRE_MAJOR: u32 = 21;
+
+/// How long a `.tmp-*` entry must sit untouched before it counts as the
+/// leftover of an interrupted install rather than one still in progress.
+pub const STALE_TEMP_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Base URL of the Kotlin/Native prebuilt tarballs; a toolchain mirror
+/// replaces it and keeps the path below it.
+pub const DEFAULT_TOOLCHAIN_BASE_URL: &str =
+    "https://github.com/JetBrains/kotlin/releases/download";
+
 /// Map a `UtilError` to `KonancError::Download` or propagate through `Util`.
 fn map_download_err(version: &str, e: konvoy_util::error::UtilError) -> KonancError {
     match e {
@@ -14,6 +28,10 @@ fn map_download_err(version: &str, e: konvoy_util::error::UtilError) -> KonancEr
             version: version.to_owned(),
             message,
         },
+        konvoy_util::error::UtilError::NotFound { url } => KonancError::Download {
+            version: version.to_owned(),
+            message: format!("{url} was not found (HTTP 404)"),
+        },
         other => KonancError::Util(other),
     }
 }
@@ -26,6 +44,9 @@ pub struct InstallResult {
     /// SHA-256 hex digest of the downloaded Kotlin/Native tarball, or `None` if
     /// the toolchain was already installed (no download occurred).
     pub konanc_tarball_sha256: Option<String>,
+    /// URL the Kotlin/Native tarball was downloaded from, or `None` if no
+    /// download occurred.
+    pub konanc_tarball_url: Option<String>,
     /// Absolute path to the bundled JRE's JAVA_HOME directory.
     pub jre_home: PathBuf,
     /// SHA-256 hex digest of the downloaded JRE tarball, or `None` if the JRE
@@ -38,6 +59,10 @@ pub struct InstallResult {
 /// # Errors
 /// Returns an error if the home directory cannot be determined.
 pub fn toolchains_dir() -> Result<PathBuf, KonancError> {
+    #[cfg(test)]
+    if let Some(root) = tests::TOOLCHAINS_ROOT.with(|root| root.borrow().clone()) {
+        return Ok(root);
+    }
     let konvoy_home = konvoy_util::fs::konvoy_home()?;
     Ok(konvoy_home.join("toolchains"))
 }
@@ -58,12 +83,21 @@ pub fn managed_konanc_path(version: &str) -> Result<PathBuf, KonancError> {
     Ok(version_dir(version)?.join("bin").join("konanc"))
 }
 
-/// Return the JRE directory for a specific toolchain version.
+/// Return the JRE directory for a specific toolchain version and JDK major.
+///
+/// The default JRE lives in `jre/` (the layout predating `[toolchain] jre`);
+/// any other major gets its own `jre-<major>/` so switching back and forth
+/// never reuses the wrong JDK.
 ///
 /// # Errors
 /// Returns an error if the home directory cannot be determined.
-pub fn jre_dir(version: &str) -> Result<PathBuf, KonancError> {
-    Ok(version_dir(version)?.join("jre"))
+pub fn jre_dir(version: &str, jre_major: u32) -> Result<PathBuf, KonancError> {
+    let dir = version_dir(version)?;
+    if jre_major == DEFAULT_JRE_MAJOR {
+        Ok(dir.join("jre"))
+    } else {
+        Ok(dir.join(format!("jre-{jre_major}")))
+    }
 }
 
 /// Return the JAVA_HOME path for the bundled JRE.
@@ -75,8 +109,8 @@ pub fn jre_dir(version: &str) -> Result<PathBuf, KonancError> {
 /// # Errors
 /// Returns an error if the JRE is not installed or the home directory cannot
 /// be determined.
-pub fn jre_home_path(version: &str) -> Result<PathBuf, KonancError> {
-    let jre_root = jre_dir(version)?;
+pub fn jre_home_path(version: &str, jre_major: u32) -> Result<PathBuf, KonancError> {
+    let jre_root = jre_dir(version, jre_major)?;
     if !jre_root.exists() {
         return Err(KonancError::JreInstall {
             message: format!(
@@ -102,13 +136,13 @@ pub fn jre_home_path(version: &str) -> Result<PathBuf, KonancError> {
 ///
 /// # Errors
 /// Returns an error if the home directory cannot be determined.
-pub fn is_installed(version: &str) -> Result<bool, KonancError> {
+pub fn is_installed(version: &str, jre_major: u32) -> Result<bool, KonancError> {
     let konanc = managed_konanc_path(version)?;
     if !konanc.exists() {
         return Ok(false);
     }
     // Also check that the JRE is present.
-    let jre_root = jre_dir(version)?;
+    let jre_root = jre_dir(version, jre_major)?;
     Ok(jre_root.exists())
 }
 
@@ -150,29 +184,95 @@ pub fn list_installed() -> Result<Vec<String>, KonancError> {
     Ok(versions)
 }
 
-/// Download and install a Kotlin/Native toolchain.
+/// Remove `.tmp-*` leftovers of interrupted installs from the toolchains
+/// directory, returning the paths that were removed.
+///
+/// Only entries untouched for at least [`STALE_TEMP_AGE`] are removed, so the
+/// temp files of an install running concurrently are left alone.
+///
+/// # Errors
+/// Returns an error if the toolchains directory cannot be read or a stale
+/// entry cannot be removed.
+pub fn gc_temp_entries() -> Result<Vec<PathBuf>, KonancError> {
+    let Ok(dir) = toolchains_dir() else {
+        return Ok(Vec::new());
+    };
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    remove_stale_temp_entries(&dir, STALE_TEMP_AGE)
+}
+
+/// Remove every `.tmp-*` file or directory directly under `dir` whose mtime is
+/// older than `max_age`.
+fn remove_stale_temp_entries(dir: &Path, max_age: Duration) -> Result<Vec<PathBuf>, KonancError> {
+    let io_err = |path: &Path, source| KonancError::Io {
+        path: path.display().to_string(),
+        source,
+    };
+    let now = SystemTime::now();
+    let mut removed = Vec::new();
+
+    for entry in std::fs::read_dir(dir).map_err(|e| io_err(dir, e))? {
+        let entry = entry.map_err(|e| io_err(dir, e))?;
+        let is_temp = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.starts_with(".tmp-"));
+        if !is_temp {
+            continue;
+        }
+
+        let path = entry.path();
+        let metadata = entry.metadata().map_err(|e| io_err(&path, e))?;
+        let modified = metadata.modified().map_err(|e| io_err(&path, e))?;
+        // A modification time in the future counts as fresh.
+        let age = now.duration_since(modified).unwrap_or_default();
+        if age < max_age {
+            continue;
+        }
+
+        if metadata.is_dir() {
+            std::fs::remove_dir_all(&path).map_err(|e| io_err(&path, e))?;
+        } else {
+            std::fs::remove_file(&path).map_err(|e| io_err(&path, e))?;
+        }
+        removed.push(path);
+    }
+
+    removed.sort();
+    Ok(removed)
+}
+
+/// Download and install a Kotlin/Native toolchain with a JRE of the given
+/// JDK major.
 ///
 /// # Errors
 /// Returns an error if the download fails, the tarball is corrupt, or the
 /// extraction fails.
 pub fn install(
     version: &str,
+    jre_major: u32,
     net: &konvoy_util::net::NetworkClient,
 ) -> Result<InstallResult, KonancError> {
     let dest = version_dir(version)?;
 
+    // Best-effort: an unreadable leftover must not block a fresh install.
+    let _ = gc_temp_entries();
+
     // Check if konanc is already installed.
     let konanc_path = dest.join("bin").join("konanc");
     let konanc_already_installed = konanc_path.exists();
 
     // If konanc exists, check if JRE also exists. If both present, return early.
     if konanc_already_installed {
-        let jre_root = jre_dir(version)?;
+        let jre_root = jre_dir(version, jre_major)?;
         if jre_root.exists() {
-            let jre_home = jre_home_path(version)?;
+            let jre_home = jre_home_path(version, jre_major)?;
             return Ok(InstallResult {
                 konanc_path,
                 konanc_tarball_sha256: None,
+                konanc_tarball_url: None,
                 jre_home,
                 jre_tarball_sha256: None,
             });
@@ -181,10 +281,11 @@ pub fn install(
     }
 
     // --- Install konanc if needed ---
-    let konanc_sha256 = if konanc_already_installed {
-        None
+    let (konanc_sha256, konanc_url) = if konanc_already_installed {
+        (None, None)
     } else {
-        let url = download_url(version)?;
+        let (os, arch) = platform_slug()?;
+        let url = download_url(net.toolchain_mirror(), version, os, arch);
         let toolchains_root = toolchains_dir()?;
         konvoy_util::fs::ensure_dir(&toolchains_root)?;
 
@@ -194,7 +295,17 @@ pub fn install(
         let progress = konvoy_util::progress::new_download_bar(format!("Kotlin/Native {version}"));
         let sha256 =
             konvoy_util::progress::stream_with_bar(net, &url, &tmp_tarball, Some(&progress))
-                .map_err(|e| map_download_err(version, e))?;
+                .map_err(|e| match (e, net.toolchain_mirror()) {
+                    (konvoy_util::error::UtilError::NotFound { .. }, Some(mirror)) => {
+                        KonancError::NotOnMirror {
+                            mirror: mirror.to_owned(),
+                            version: version.to_owned(),
+                            platform: format!("{os}-{arch}"),
+                            url: url.clone(),
+                        }
+                    }
+                    (e, _) => map_download_err(version, e),
+                })?;
         eprintln!();
 
         let (_extract_guard, tmp_extract) = temp_extract_dir(&toolchains_root, &prefix)?;
@@ -213,43 +324,81 @@ pub fn install(
         }
         ensure_executable(&final_konanc)?;
 
-        Some(sha256)
+        (Some(sha256), Some(url))
     };
 
     // --- Install JRE if needed ---
-    let (jre_home, jre_sha256) = install_jre(version, net)?;
+    let (jre_home, jre_sha256) = install_jre(version, jre_major, net)?;
 
     Ok(InstallResult {
         konanc_path: dest.join("bin").join("konanc"),
         konanc_tarball_sha256: konanc_sha256,
+        konanc_tarball_url: konanc_url,
         jre_home,
         jre_tarball_sha256: jre_sha256,
     })
 }
 
+/// Pre-fetch konanc's per-target dependencies (sysroot, platform libraries)
+/// for `target` by compiling a stub program for it in a scratch directory.
+///
+/// konanc downloads a target's dependencies into `~/.konan` the first time it
+/// compiles for that target, so doing it up front lets later builds for
+/// `target` run without fetching anything.
+///
+/// # Errors
+/// Returns an error if konanc cannot be run or the stub fails to compile.
+pub fn prefetch_target(
+    konanc: &crate::detect::ResolvedKonanc,
+    target: &str,
+) -> Result<(), KonancError> {
+    let scratch = tempfile::tempdir().map_err(|source| KonancError::Io {
+        path: std::env::temp_dir().display().to_string(),
+        source,
+    })?;
+    let stub = scratch.path().join("prefetch.kt");
+    konvoy_util::fs::write_file(&stub, "fun main() {}\n")?;
+
+    let mut cmd = crate::invoke::KonancCommand::new()
+        .sources(&[stub])
+        .output(&scratch.path().join("prefetch"))
+        .target(target);
+    if let Some(jre_home) = &konanc.jre_home {
+        cmd = cmd.java_home(jre_home);
+    }
+    let result = cmd.execute(&konanc.info)?;
+    if !result.success {
+        return Err(KonancError::CompilationFailed {
+            error_count: result.error_count(),
+        });
+    }
+    Ok(())
+}
+
 /// Download and install the bundled JRE for a toolchain version.
 ///
 /// Returns `(jre_home, tarball_sha256)`. The SHA-256 is `None` if the JRE was
 /// already installed and no download occurred.
 fn install_jre(
     version: &str,
+    jre_major: u32,
     net: &konvoy_util::net::NetworkClient,
 ) -> Result<(PathBuf, Option<String>), KonancError> {
-    let jre_root = jre_dir(version)?;
+    let jre_root = jre_dir(version, jre_major)?;
 
     // Already installed — return existing path.
     if jre_root.exists() {
-        let home = jre_home_path(version)?;
+        let home = jre_home_path(version, jre_major)?;
         return Ok((home, None));
     }
 
-    let url = jre_download_url()?;
+    let url = jre_download_url(jre_major)?;
     let toolchains_root = toolchains_dir()?;
 
     let prefix = format!(".tmp-{version}-jre-");
     let (_tarball_guard, tmp_tarball) = temp_tarball(&toolchains_root, &prefix)?;
 
-    let progress = konvoy_util::progress::new_download_bar(format!("JRE {version}"));
+    let progress = konvoy_util::progress::new_download_bar(format!("JRE {jre_major}"));
     let sha256 = konvoy_util::progress::stream_with_bar(net, &url, &tmp_tarball, Some(&progress))
         .map_err(|e| map_download_err(version, e))?;
     eprintln!();
@@ -260,7 +409,7 @@ fn install_jre(
 
     atomic_rename_into(&tmp_extract, &jre_root, &tmp_extract)?;
 
-    let home = jre_home_path(version)?;
+    let home = jre_home_path(version, jre_major)?;
     let java_bin = home.join("bin").join("java");
     if !java_bin.exists() {
         return Err(KonancError::JreInstall {
@@ -415,24 +564,30 @@ fn find_jre_root(jre_dir: &Path) -> Result<PathBuf, KonancError> {
     )
 }
 
-/// Construct the download URL for an Adoptium Temurin JRE.
-fn jre_download_url() -> Result<String, KonancError> {
+/// Construct the download URL for an Adoptium Temurin JRE of `jre_major`.
+fn jre_download_url(jre_major: u32) -> Result<String, KonancError> {
     let (os, arch) = jre_platform_slug()?;
     Ok(format!(
-        "https://api.adoptium.net/v3/binary/latest/21/ga/{os}/{arch}/jre/hotspot/normal/eclipse"
+        "https://api.adoptium.net/v3/binary/latest/{jre_major}/ga/{os}/{arch}/jre/hotspot/normal/eclipse"
     ))
 }
 
 /// Map the current OS and architecture to Adoptium's naming convention.
 fn jre_platform_slug() -> Result<(&'static str, &'static str), KonancError> {
-    let os = std::env::consts::OS;
-    let arch = std::env::consts::ARCH;
+    jre_platform_slug_for(std::env::consts::OS, std::env::consts::ARCH)
+}
 
+/// Map an `(os, arch)` pair to Adoptium's naming convention.
+fn jre_platform_slug_for(
+    os: &str,
+    arch: &str,
+) -> Result<(&'static str, &'static str), KonancError> {
     match (os, arch) {
         ("linux", "x86_64") => Ok(("linux", "x64")),
         ("linux", "aarch64") => Ok(("linux", "aarch64")),
         ("macos", "x86_64") => Ok(("mac", "x64")),
         ("macos", "aarch64") => Ok(("mac", "aarch64")),
+        ("windows", "x86_64") => Err(windows_not_yet_supported()),
         _ => Err(KonancError::UnsupportedPlatform {
             os: os.to_owned(),
             arch: arch.to_owned(),
@@ -440,27 +595,30 @@ fn jre_platform_slug() -> Result<(&'static str, &'static str), KonancError> {
     }
 }
 
-/// Construct the download URL for a Kotlin/Native prebuilt tarball.
+/// Construct the download URL for a Kotlin/Native prebuilt tarball under
+/// `mirror`, or under [`DEFAULT_TOOLCHAIN_BASE_URL`] without one.
 ///
-/// Uses GitHub releases which hosts prebuilt tarballs for all supported
-/// platforms. The JetBrains CDN stopped publishing prebuilts after 2.1.10.
-fn download_url(version: &str) -> Result<String, KonancError> {
-    let (os, arch) = platform_slug()?;
-    Ok(format!(
-        "https://github.com/JetBrains/kotlin/releases/download/v{version}/kotlin-native-prebuilt-{os}-{arch}-{version}.tar.gz"
-    ))
+/// The default is GitHub releases, which hosts prebuilt tarballs for all
+/// supported platforms. The JetBrains CDN stopped publishing prebuilts after
+/// 2.1.10.
+fn download_url(mirror: Option<&str>, version: &str, os: &str, arch: &str) -> String {
+    let base = mirror.map_or(DEFAULT_TOOLCHAIN_BASE_URL, |m| m.trim_end_matches('/'));
+    format!("{base}/v{version}/kotlin-native-prebuilt-{os}-{arch}-{version}.tar.gz")
 }
 
 /// Map the current OS and architecture to the JetBrains release slug.
 fn platform_slug() -> Result<(&'static str, &'static str), KonancError> {
-    let os = std::env::consts::OS;
-    let arch = std::env::consts::ARCH;
+    platform_slug_for(std::env::consts::OS, std::env::consts::ARCH)
+}
 
+/// Map an `(os, arch)` pair to the JetBrains release slug.
+fn platform_slug_for(os: &str, arch: &str) -> Result<(&'static str, &'static str), KonancError> {
     match (os, arch) {
         ("linux", "x86_64") => Ok(("linux", "x86_64")),
         ("linux", "aarch64") => Ok(("linux", "aarch64")),
         ("macos", "x86_64") => Ok(("macos", "x86_64")),
         ("macos", "aarch64") => Ok(("macos", "aarch64")),
+        ("windows", "x86_64") => Err(windows_not_yet_supported()),
         _ => Err(KonancError::UnsupportedPlatform {
             os: os.to_owned(),
             arch: arch.to_owned(),
@@ -468,6 +626,13 @@ fn platform_slug() -> Result<(&'static str, &'static str), KonancError> {
     }
 }
 
+/// Windows has Kotlin/Native prebuilts, but Konvoy cannot drive them yet.
+fn windows_not_yet_supported() -> KonancError {
+    KonancError::TargetNotYetSupported {
+        target: "windows_x64".to_owned(),
+    }
+}
+
 /// Extract a `.tar.gz` tarball to a directory.
 ///
 /// Each entry's path is validated to ensure it stays within `dest`,
@@ -559,9 +724,34 @@ fn find_extracted_root(extract_dir: &Path, version: &str) -> Result<PathBuf, Kon
 
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
-mod tests {
+pub(crate) mod tests {
+    use std::cell::RefCell;
+
     use super::*;
 
+    thread_local! {
+        /// This test's toolchains dir, set by [`isolated_toolchains`]. Each
+        /// test runs on its own thread, so tests never see each other's installs.
+        pub(crate) static TOOLCHAINS_ROOT: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+    }
+
+    /// A temporary toolchains dir used in place of `~/.konvoy/toolchains`
+    /// until it is dropped.
+    pub(crate) struct IsolatedToolchains(tempfile::TempDir);
+
+    impl Drop for IsolatedToolchains {
+        fn drop(&mut self) {
+            TOOLCHAINS_ROOT.with(|root| root.borrow_mut().take());
+        }
+    }
+
+    /// Point the calling test's managed toolchains at a fresh temporary directory.
+    pub(crate) fn isolated_toolchains() -> IsolatedToolchains {
+        let dir = tempfile::tempdir().unwrap();
+        TOOLCHAINS_ROOT.with(|root| *root.borrow_mut() = Some(dir.path().to_path_buf()));
+        IsolatedToolchains(dir)
+    }
+
     #[test]
     fn toolchains_dir_under_home() {
         let dir = toolchains_dir().unwrap();
@@ -585,7 +775,7 @@ mod tests {
     #[test]
     fn is_installed_false_for_missing() {
         // A version that doesn't exist should return false.
-        let result = is_installed("99.99.99").unwrap();
+        let result = is_installed("99.99.99", DEFAULT_JRE_MAJOR).unwrap();
         assert!(!result);
     }
 
@@ -607,28 +797,48 @@ mod tests {
 
     #[test]
     fn download_url_format() {
-        if let Ok(url) = download_url("2.1.0") {
-            assert!(url.contains("2.1.0"));
-            assert!(url.contains("kotlin-native-prebuilt"));
-            assert!(url.contains(".tar.gz"));
-            assert!(url.starts_with("https://"));
-            assert!(url.contains("github.com/JetBrains/kotlin/releases"));
-        }
+        let url = download_url(None, "2.1.0", "linux", "x86_64");
+        assert_eq!(
+            url,
+            "https://github.com/JetBrains/kotlin/releases/download/v2.1.0/kotlin-native-prebuilt-linux-x86_64-2.1.0.tar.gz"
+        );
+    }
+
+    #[test]
+    fn download_url_under_mirror_keeps_path() {
+        let url = download_url(
+            Some("https://mirror.example.com/kotlin/"),
+            "2.1.0",
+            "macos",
+            "aarch64",
+        );
+        assert_eq!(
+            url,
+            "https://mirror.example.com/kotlin/v2.1.0/kotlin-native-prebuilt-macos-aarch64-2.1.0.tar.gz"
+        );
     }
 
     #[test]
     fn jre_dir_under_version() {
-        let dir = jre_dir("2.1.0").unwrap();
+        let dir = jre_dir("2.1.0", DEFAULT_JRE_MAJOR).unwrap();
         let s = dir.display().to_string();
         assert!(s.contains(".konvoy"));
         assert!(s.contains("toolchains"));
         assert!(s.contains("2.1.0"));
-        assert!(s.contains("jre"));
+        assert!(s.ends_with("jre"));
+    }
+
+    #[test]
+    fn jre_dir_separates_non_default_majors() {
+        let default = jre_dir("2.1.0", DEFAULT_JRE_MAJOR).unwrap();
+        let pinned = jre_dir("2.1.0", 17).unwrap();
+        assert_ne!(default, pinned);
+        assert!(pinned.display().to_string().ends_with("jre-17"));
     }
 
     #[test]
     fn jre_download_url_format() {
-        if let Ok(url) = jre_download_url() {
+        if let Ok(url) = jre_download_url(DEFAULT_JRE_MAJOR) {
             assert!(url.contains("api.adoptium.net"));
             assert!(url.contains("/jre/"));
             assert!(url.contains("/21/"));
@@ -636,6 +846,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn jre_download_url_uses_requested_major() {
+        if let Ok(url) = jre_download_url(17) {
+            assert!(url.contains("/latest/17/ga/"));
+            assert!(!url.contains("/21/"));
+        }
+    }
+
+    #[test]
+    fn windows_slugs_report_not_yet_supported() {
+        let err = platform_slug_for("windows", "x86_64").unwrap_err();
+        assert!(
+            matches!(err, KonancError::TargetNotYetSupported { ref target } if target == "windows_x64"),
+            "got: {err}"
+        );
+        let err = jre_platform_slug_for("windows", "x86_64").unwrap_err();
+        assert!(matches!(err, KonancError::TargetNotYetSupported { .. }));
+    }
+
+    #[test]
+    fn other_unknown_platforms_stay_unsupported() {
+        let err = platform_slug_for("freebsd", "x86_64").unwrap_err();
+        assert!(matches!(err, KonancError::UnsupportedPlatform { .. }));
+        let err = jre_platform_slug_for("windows", "aarch64").unwrap_err();
+        assert!(matches!(err, KonancError::UnsupportedPlatform { .. }));
+    }
+
     #[test]
     fn jre_platform_slug_valid() {
         if let Ok((os, arch)) = jre_platform_slug() {
@@ -652,7 +889,7 @@ mod tests {
     #[test]
     fn jre_home_path_errors_when_missing() {
         // A version that doesn't exist should error.
-        let result = jre_home_path("99.99.99");
+        let result = jre_home_path("99.99.99", DEFAULT_JRE_MAJOR);
         assert!(result.is_err());
     }
 
@@ -1146,7 +1383,7 @@ mod tests {
     #[test]
     fn is_installed_false_for_nonexistent_version() {
         // A completely made-up version should not be installed.
-        let result = is_installed("0.0.0-nonexistent").unwrap();
+        let result = is_installed("0.0.0-nonexistent", DEFAULT_JRE_MAJOR).unwrap();
         assert!(
             !result,
             "non-existent version should not report as installed"
@@ -1214,4 +1451,47 @@ mod tests {
             "other variants should propagate through Util preserving original message: {msg}"
         );
     }
+
+    /// Backdate `path`'s mtime by twice the stale threshold.
+    fn make_stale(path: &Path) {
+        let file = std::fs::File::open(path).unwrap();
+        file.set_modified(SystemTime::now() - 2 * STALE_TEMP_AGE)
+            .unwrap();
+    }
+
+    #[test]
+    fn gc_removes_stale_temp_entries_and_keeps_fresh_ones() {
+        let root = tempfile::tempdir().unwrap();
+        let stale_dir = root.path().join(".tmp-2.1.0-abc-extract");
+        let stale_tarball = root.path().join(".tmp-2.1.0-def.tar.gz");
+        let fresh_dir = root.path().join(".tmp-2.1.0-ghi-extract");
+        std::fs::create_dir_all(stale_dir.join("bin")).unwrap();
+        std::fs::write(&stale_tarball, "partial").unwrap();
+        std::fs::create_dir(&fresh_dir).unwrap();
+        make_stale(&stale_dir);
+        make_stale(&stale_tarball);
+
+        let removed = remove_stale_temp_entries(root.path(), STALE_TEMP_AGE).unwrap();
+
+        assert_eq!(removed, vec![stale_dir.clone(), stale_tarball.clone()]);
+        assert!(!stale_dir.exists());
+        assert!(!stale_tarball.exists());
+        assert!(
+            fresh_dir.exists(),
+            "an in-progress install must be preserved"
+        );
+    }
+
+    #[test]
+    fn gc_ignores_installed_versions() {
+        let root = tempfile::tempdir().unwrap();
+        let installed = root.path().join("2.1.0");
+        std::fs::create_dir(&installed).unwrap();
+        make_stale(&installed);
+
+        let removed = remove_stale_temp_entries(root.path(), STALE_TEMP_AGE).unwrap();
+
+        assert!(removed.is_empty());
+        assert!(installed.exists());
+    }
 }
@@ -14,14 +14,27 @@ pub enum Target {
     LinuxArm64,
     MacOsX64,
     MacOsArm64,
+    /// Recognized so `--target windows_x64` gets a "not yet supported" error
+    /// rather than "unknown target"; Konvoy cannot build it yet.
+    WindowsX64,
 }
 
-/// All known Kotlin/Native targets supported by Konvoy.
+/// All Kotlin/Native targets Konvoy recognizes, including ones it cannot build
+/// yet (see [`Target::is_supported`]).
 pub const KNOWN_TARGETS: &[Target] = &[
     Target::LinuxX64,
     Target::LinuxArm64,
     Target::MacOsX64,
     Target::MacOsArm64,
+    Target::WindowsX64,
+];
+
+/// The targets Konvoy can build today.
+pub const SUPPORTED_TARGETS: &[Target] = &[
+    Target::LinuxX64,
+    Target::LinuxArm64,
+    Target::MacOsX64,
+    Target::MacOsArm64,
 ];
 
 impl Target {
@@ -32,6 +45,7 @@ impl Target {
             Target::LinuxArm64 => "linux_arm64",
             Target::MacOsX64 => "macos_x64",
             Target::MacOsArm64 => "macos_arm64",
+            Target::WindowsX64 => "windows_x64",
         }
     }
 
@@ -45,6 +59,7 @@ impl Target {
             Target::LinuxArm64 => "linuxarm64",
             Target::MacOsX64 => "macosx64",
             Target::MacOsArm64 => "macosarm64",
+            Target::WindowsX64 => "windowsx64",
         }
     }
 
@@ -54,6 +69,43 @@ impl Target {
     pub fn is_host(self) -> bool {
         matches!(host_target(), Ok(host) if host == self)
     }
+
+    /// Returns `true` for targets that need Apple's SDK (Xcode) to link.
+    pub fn is_apple(self) -> bool {
+        matches!(self, Target::MacOsX64 | Target::MacOsArm64)
+    }
+
+    /// Returns `true` if Konvoy can build this target at all (Windows cannot
+    /// be built yet).
+    pub fn is_supported(self) -> bool {
+        SUPPORTED_TARGETS.contains(&self)
+    }
+
+    /// Returns `true` if konanc running on `host` can produce this target.
+    ///
+    /// Apple targets link against the macOS SDK, which only exists on macOS
+    /// hosts. Linux targets can be built from any host: konanc downloads the
+    /// Linux sysroot on first use. Unsupported targets are never buildable.
+    pub fn buildable_from(self, host: Target) -> bool {
+        self.is_supported() && (!self.is_apple() || host.is_apple())
+    }
+
+    /// File name konanc gives a static library (`-produce static`) with file
+    /// stem `stem`: `lib<stem>.a` on every target.
+    pub fn static_lib_name(self, stem: &str) -> String {
+        format!("lib{stem}.a")
+    }
+
+    /// File name konanc gives a dynamic library (`-produce dynamic`) with file
+    /// stem `stem`: `lib<stem>.so` on Linux, `lib<stem>.dylib` on macOS, and
+    /// `<stem>.dll` on Windows.
+    pub fn dynamic_lib_name(self, stem: &str) -> String {
+        match self {
+            Target::LinuxX64 | Target::LinuxArm64 => format!("lib{stem}.so"),
+            Target::MacOsX64 | Target::MacOsArm64 => format!("lib{stem}.dylib"),
+            Target::WindowsX64 => format!("{stem}.dll"),
+        }
+    }
 }
 
 impl fmt::Display for Target {
@@ -75,6 +127,7 @@ impl FromStr for Target {
             "linux_arm64" => Ok(Target::LinuxArm64),
             "macos_x64" => Ok(Target::MacOsX64),
             "macos_arm64" => Ok(Target::MacOsArm64),
+            "windows_x64" => Ok(Target::WindowsX64),
             _ => Err(TargetError::InvalidTarget { name: s.to_owned() }),
         }
     }
@@ -222,7 +275,26 @@ mod tests {
 
     #[test]
     fn from_str_rejects_invalid_target() {
-        assert!(Target::from_str("windows_x64").is_err());
+        assert!(Target::from_str("windows_arm64").is_err());
+    }
+
+    #[test]
+    fn windows_is_known_but_not_supported() {
+        assert_eq!(
+            Target::from_str("windows_x64").ok(),
+            Some(Target::WindowsX64)
+        );
+        assert!(KNOWN_TARGETS.contains(&Target::WindowsX64));
+        assert!(!SUPPORTED_TARGETS.contains(&Target::WindowsX64));
+        assert!(!Target::WindowsX64.is_supported());
+        assert!(Target::LinuxX64.is_supported());
+    }
+
+    #[test]
+    fn windows_is_not_buildable_from_any_host() {
+        for &host in SUPPORTED_TARGETS {
+            assert!(!Target::WindowsX64.buildable_from(host));
+        }
     }
 
     #[test]
@@ -234,7 +306,7 @@ mod tests {
     fn invalid_target_error_lists_supported_targets() {
         let err = Target::from_str("bsd_x64").expect_err("bsd_x64 should not parse");
         let msg = err.to_string();
-        for t in KNOWN_TARGETS {
+        for t in SUPPORTED_TARGETS {
             assert!(
                 msg.contains(t.to_konanc_arg()),
                 "error message should list `{t}`, got: {msg}"
@@ -318,6 +390,30 @@ mod tests {
         assert!(!non_host.is_host());
     }
 
+    #[test]
+    fn apple_targets_need_an_apple_host() {
+        assert!(!Target::MacOsArm64.buildable_from(Target::LinuxX64));
+        assert!(!Target::MacOsX64.buildable_from(Target::LinuxArm64));
+        assert!(Target::MacOsX64.buildable_from(Target::MacOsArm64));
+    }
+
+    #[test]
+    fn linux_targets_build_from_any_host() {
+        for &host in KNOWN_TARGETS {
+            assert!(Target::LinuxX64.buildable_from(host));
+            assert!(Target::LinuxArm64.buildable_from(host));
+        }
+    }
+
+    #[test]
+    fn c_library_names_follow_the_target_platform() {
+        assert_eq!(Target::LinuxX64.static_lib_name("ffi"), "libffi.a");
+        assert_eq!(Target::MacOsArm64.static_lib_name("ffi"), "libffi.a");
+        assert_eq!(Target::LinuxArm64.dynamic_lib_name("ffi"), "libffi.so");
+        assert_eq!(Target::MacOsX64.dynamic_lib_name("ffi"), "libffi.dylib");
+        assert_eq!(Target::WindowsX64.dynamic_lib_name("ffi"), "ffi.dll");
+    }
+
     #[test]
     fn target_equality() {
         let a: Target = "linux_x64".parse().expect("valid");
@@ -342,6 +438,11 @@ mod tests {
         assert_eq!(Target::MacOsArm64.to_maven_suffix(), "macosarm64");
     }
 
+    #[test]
+    fn to_maven_suffix_windows_x64() {
+        assert_eq!(Target::WindowsX64.to_maven_suffix(), "windowsx64");
+    }
+
     #[test]
     fn to_maven_suffix_no_underscores() {
         for &t in KNOWN_TARGETS {
@@ -383,14 +484,14 @@ mod tests {
             }
 
             #[test]
-            fn known_targets_always_parse(idx in 0usize..4) {
+            fn known_targets_always_parse(idx in 0usize..5) {
                 let t = KNOWN_TARGETS[idx];
                 let parsed = Target::from_str(t.to_konanc_arg());
                 prop_assert_eq!(parsed.ok(), Some(t));
             }
 
             #[test]
-            fn maven_suffix_never_contains_underscore(idx in 0usize..4) {
+            fn maven_suffix_never_contains_underscore(idx in 0usize..5) {
                 let t = KNOWN_TARGETS[idx];
                 prop_assert!(!t.to_maven_suffix().contains('_'));
             }
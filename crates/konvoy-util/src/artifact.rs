@@ -1,8 +1,10 @@
-//! Generic artifact download, SHA-256 verification, and atomic placement.
+//! Generic artifact download, SHA-256 (or SHA-512) verification, and atomic
+//! placement.
 
 use std::path::{Path, PathBuf};
 
 use crate::error::UtilError;
+use crate::hash::HashAlgorithm;
 
 /// Result of resolving an artifact locally — either a cache-hit reported by
 /// [`check_cached`] or a fresh fetch from [`download_artifact`].
@@ -12,6 +14,9 @@ pub struct ArtifactResult {
     pub path: PathBuf,
     /// Hex-encoded SHA-256 hash of the artifact.
     pub sha256: String,
+    /// Hex-encoded hash in the algorithm the artifact was verified with; the
+    /// same as `sha256` for SHA-256.
+    pub digest: String,
     /// `true` if the artifact was downloaded this call, `false` if it already existed.
     pub freshly_downloaded: bool,
 }
@@ -62,6 +67,32 @@ pub fn validate_identifier(identifier: &str) -> Result<(), UtilError> {
     Ok(())
 }
 
+/// Set to re-hash every cached artifact from disk instead of trusting the
+/// hash cache, e.g. `KONVOY_VERIFY_CACHED=1 konvoy build`.
+pub const VERIFY_CACHED_ENV: &str = "KONVOY_VERIFY_CACHED";
+
+/// How thoroughly [`check_cached_with`] re-checks a file already on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheVerification {
+    /// Reuse a recorded digest while the file's size and mtime are unchanged.
+    Stamped,
+    /// Always recompute the SHA-256 from the file's contents.
+    Full,
+}
+
+impl CacheVerification {
+    /// `Full` when `KONVOY_VERIFY_CACHED` is set to a non-empty value other
+    /// than `0`, `Stamped` otherwise.
+    pub fn from_env() -> Self {
+        let full = std::env::var(VERIFY_CACHED_ENV).is_ok_and(|v| !v.is_empty() && v != "0");
+        if full {
+            Self::Full
+        } else {
+            Self::Stamped
+        }
+    }
+}
+
 /// Check whether `dest` is already on disk with the expected SHA-256.
 ///
 /// Returns:
@@ -75,30 +106,66 @@ pub fn validate_identifier(identifier: &str) -> Result<(), UtilError> {
 /// - Other `UtilError` variants for I/O failures reading the file.
 ///
 /// This function does no UI work. Pair it with [`download_artifact`] when
-/// the result is `None`.
+/// the result is `None`. The verification mode comes from
+/// [`CacheVerification::from_env`].
 ///
 /// # Errors
 /// See above.
 pub fn check_cached(
     dest: &Path,
     expected_sha256: Option<&str>,
+) -> Result<Option<ArtifactResult>, UtilError> {
+    check_cached_with(dest, expected_sha256, CacheVerification::from_env())
+}
+
+/// [`check_cached`] with an explicit verification mode. `konvoy verify`
+/// uses [`CacheVerification::Full`] so a cached file corrupted on disk is
+/// reported even when its size and mtime are unchanged.
+///
+/// # Errors
+/// Same as [`check_cached`].
+pub fn check_cached_with(
+    dest: &Path,
+    expected_sha256: Option<&str>,
+    verification: CacheVerification,
+) -> Result<Option<ArtifactResult>, UtilError> {
+    check_cached_as(dest, expected_sha256, HashAlgorithm::Sha256, verification)
+}
+
+/// [`check_cached_with`], with `expected` a hex digest in `algorithm`.
+///
+/// # Errors
+/// Same as [`check_cached`].
+pub fn check_cached_as(
+    dest: &Path,
+    expected: Option<&str>,
+    algorithm: HashAlgorithm,
+    verification: CacheVerification,
 ) -> Result<Option<ArtifactResult>, UtilError> {
     if !dest.exists() {
         return Ok(None);
     }
-    let actual_hash = crate::hash::sha256_file(dest)?;
-    if let Some(expected) = expected_sha256 {
-        if actual_hash != expected {
+    let sha256 = match verification {
+        CacheVerification::Stamped => crate::hash::sha256_file(dest)?,
+        CacheVerification::Full => crate::hash::sha256_file_uncached(dest)?,
+    };
+    let digest = match algorithm {
+        HashAlgorithm::Sha256 => sha256.clone(),
+        HashAlgorithm::Sha512 => algorithm.hash_file_uncached(dest)?,
+    };
+    if let Some(expected) = expected {
+        if digest != expected {
             return Err(UtilError::ArtifactHashMismatch {
                 path: dest.display().to_string(),
                 expected: expected.to_owned(),
-                actual: actual_hash,
+                actual: digest,
             });
         }
     }
     Ok(Some(ArtifactResult {
         path: dest.to_path_buf(),
-        sha256: actual_hash,
+        sha256,
+        digest,
         freshly_downloaded: false,
     }))
 }
@@ -130,6 +197,33 @@ pub fn download_artifact<F>(
     label: &str,
     on_progress: F,
 ) -> Result<ArtifactResult, UtilError>
+where
+    F: FnMut(u64, Option<u64>),
+{
+    download_artifact_as(
+        net,
+        url,
+        dest,
+        expected_sha256,
+        HashAlgorithm::Sha256,
+        label,
+        on_progress,
+    )
+}
+
+/// [`download_artifact`], with `expected` a hex digest in `algorithm`.
+///
+/// # Errors
+/// Same as [`download_artifact`].
+pub fn download_artifact_as<F>(
+    net: &crate::net::NetworkClient,
+    url: &str,
+    dest: &Path,
+    expected: Option<&str>,
+    algorithm: HashAlgorithm,
+    label: &str,
+    on_progress: F,
+) -> Result<ArtifactResult, UtilError>
 where
     F: FnMut(u64, Option<u64>),
 {
@@ -150,17 +244,28 @@ where
         .map(|p| p.join(&tmp_name))
         .unwrap_or_else(|| PathBuf::from(&tmp_name));
 
-    // Download to temp file.
+    // Download to temp file. The SHA-256 is computed while streaming; any
+    // other digest needs a second pass over the file.
     let download_hash = crate::download::stream_download(net, url, &tmp_path, on_progress)?;
+    let digest = match algorithm {
+        HashAlgorithm::Sha256 => download_hash.clone(),
+        HashAlgorithm::Sha512 => match algorithm.hash_file_uncached(&tmp_path) {
+            Ok(digest) => digest,
+            Err(e) => {
+                let _ = std::fs::remove_file(&tmp_path);
+                return Err(e);
+            }
+        },
+    };
 
     // Verify hash of downloaded file before placing it.
-    if let Some(expected) = expected_sha256 {
-        if download_hash != expected {
+    if let Some(expected) = expected {
+        if digest != expected {
             let _ = std::fs::remove_file(&tmp_path);
             return Err(UtilError::ArtifactHashMismatch {
                 path: dest.display().to_string(),
                 expected: expected.to_owned(),
-                actual: download_hash,
+                actual: digest,
             });
         }
     }
@@ -171,8 +276,8 @@ where
         Err(_) if dest.exists() => {
             // Another process placed the file concurrently — verify its hash.
             let _ = std::fs::remove_file(&tmp_path);
-            if let Some(expected) = expected_sha256 {
-                let placed_hash = crate::hash::sha256_file(dest)?;
+            if let Some(expected) = expected {
+                let placed_hash = algorithm.hash_file(dest)?;
                 if placed_hash != expected {
                     return Err(UtilError::ArtifactHashMismatch {
                         path: dest.display().to_string(),
@@ -194,6 +299,7 @@ where
     Ok(ArtifactResult {
         path: dest.to_path_buf(),
         sha256: download_hash,
+        digest,
         freshly_downloaded: true,
     })
 }
@@ -307,6 +413,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn full_verification_detects_corrupted_cached_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dest = tmp.path().join("dep.klib");
+        std::fs::write(&dest, b"original klib").unwrap();
+        let expected = crate::hash::sha256_bytes(b"original klib");
+        let hit = check_cached_with(&dest, Some(&expected), CacheVerification::Full)
+            .unwrap()
+            .unwrap();
+        assert_eq!(hit.sha256, expected);
+
+        // Same length, different bytes: a flipped-bit style corruption.
+        std::fs::write(&dest, b"corrupt! klib").unwrap();
+        let err = check_cached_with(&dest, Some(&expected), CacheVerification::Full).unwrap_err();
+        assert!(
+            matches!(&err, UtilError::ArtifactHashMismatch { expected: e, .. } if *e == expected),
+            "got: {err:?}"
+        );
+    }
+
     #[test]
     fn check_cached_returns_some_without_expected_hash() {
         let tmp = tempfile::tempdir().unwrap();
@@ -322,6 +448,97 @@ mod tests {
         assert_eq!(result.sha256, expected_hash);
     }
 
+    #[test]
+    fn check_cached_as_verifies_sha512() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dest = tmp.path().join("dep.klib");
+        std::fs::write(&dest, b"klib").unwrap();
+        let sha512 = crate::hash::sha512_bytes(b"klib");
+
+        let hit = check_cached_as(
+            &dest,
+            Some(&sha512),
+            HashAlgorithm::Sha512,
+            CacheVerification::Stamped,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(hit.digest, sha512);
+        assert_eq!(hit.sha256, crate::hash::sha256_bytes(b"klib"));
+
+        // A SHA-256 pin is not a valid SHA-512 pin for the same bytes.
+        let err = check_cached_as(
+            &dest,
+            Some(&hit.sha256),
+            HashAlgorithm::Sha512,
+            CacheVerification::Stamped,
+        )
+        .unwrap_err();
+        assert!(
+            matches!(&err, UtilError::ArtifactHashMismatch { actual, .. } if *actual == sha512),
+            "got: {err:?}"
+        );
+    }
+
+    /// Serve `body` once over HTTP and return its URL.
+    fn serve_once(body: &'static [u8]) -> String {
+        use std::io::{Read, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/dep.klib", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0_u8; 2048];
+            let _ = stream.read(&mut request).unwrap();
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            stream.write_all(head.as_bytes()).unwrap();
+            stream.write_all(body).unwrap();
+        });
+        url
+    }
+
+    #[test]
+    fn download_artifact_as_verifies_sha512() {
+        let tmp = tempfile::tempdir().unwrap();
+        let net = crate::net::NetworkClient::new(false);
+        let sha512 = crate::hash::sha512_bytes(b"klib");
+
+        let dest = tmp.path().join("ok.klib");
+        let result = download_artifact_as(
+            &net,
+            &serve_once(b"klib"),
+            &dest,
+            Some(&sha512),
+            HashAlgorithm::Sha512,
+            "ok",
+            |_, _| {},
+        )
+        .unwrap();
+        assert!(result.freshly_downloaded);
+        assert_eq!(result.digest, sha512);
+        assert_eq!(result.sha256, crate::hash::sha256_bytes(b"klib"));
+        assert_eq!(std::fs::read(&dest).unwrap(), b"klib");
+
+        let dest = tmp.path().join("bad.klib");
+        let err = download_artifact_as(
+            &net,
+            &serve_once(b"tampered"),
+            &dest,
+            Some(&sha512),
+            HashAlgorithm::Sha512,
+            "bad",
+            |_, _| {},
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, UtilError::ArtifactHashMismatch { .. }),
+            "got: {err:?}"
+        );
+        assert!(!dest.exists());
+    }
+
     #[test]
     fn download_artifact_errors_on_invalid_url() {
         let tmp = tempfile::tempdir().unwrap();
@@ -0,0 +1,107 @@
+//! ANSI color for terminal output, controlled by `--color <auto|always|never>`.
+//!
+//! Only the styling changes: a painted string has exactly the same text as the
+//! plain one, so output scrapers keep working once escape codes are stripped
+//! (and see the plain text when color is off).
+
+use std::io::IsTerminal;
+
+/// Environment variable that disables color in `auto` mode (<https://no-color.org>).
+const NO_COLOR_ENV: &str = "NO_COLOR";
+
+/// When to emit ANSI color codes. Spelled `"auto"`, `"always"`, or `"never"`
+/// in config files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// Color when stderr is a terminal and `NO_COLOR` is unset or empty.
+    #[default]
+    Auto,
+    /// Always color.
+    Always,
+    /// Never color.
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether output written to stderr should be colored.
+    pub fn enabled(self) -> bool {
+        color_enabled(
+            self,
+            std::io::stderr().is_terminal(),
+            std::env::var(NO_COLOR_ENV).ok().as_deref(),
+        )
+    }
+}
+
+fn color_enabled(choice: ColorChoice, stderr_is_terminal: bool, no_color: Option<&str>) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        // Per no-color.org, an empty NO_COLOR does not disable color.
+        ColorChoice::Auto => stderr_is_terminal && no_color.is_none_or(str::is_empty),
+    }
+}
+
+/// A foreground color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Yellow,
+    Blue,
+}
+
+impl Color {
+    fn code(self) -> &'static str {
+        match self {
+            Color::Red => "31",
+            Color::Yellow => "33",
+            Color::Blue => "34",
+        }
+    }
+}
+
+/// Wrap `text` in bold `color` when `enabled`, else return it unchanged.
+pub fn paint(text: &str, color: Color, enabled: bool) -> String {
+    if enabled {
+        format!("\x1b[1;{}m{text}\x1b[0m", color.code())
+    } else {
+        text.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn always_and_never_ignore_environment() {
+        assert!(color_enabled(ColorChoice::Always, false, Some("1")));
+        assert!(!color_enabled(ColorChoice::Never, true, None));
+    }
+
+    #[test]
+    fn auto_requires_terminal() {
+        assert!(color_enabled(ColorChoice::Auto, true, None));
+        assert!(!color_enabled(ColorChoice::Auto, false, None));
+    }
+
+    #[test]
+    fn auto_respects_no_color() {
+        assert!(!color_enabled(ColorChoice::Auto, true, Some("1")));
+        assert!(color_enabled(ColorChoice::Auto, true, Some("")));
+    }
+
+    #[test]
+    fn paint_disabled_is_identity() {
+        assert_eq!(paint("error", Color::Red, false), "error");
+    }
+
+    #[test]
+    fn paint_enabled_wraps_text_in_escape_codes() {
+        assert_eq!(
+            paint("warning", Color::Yellow, true),
+            "\x1b[1;33mwarning\x1b[0m"
+        );
+    }
+}
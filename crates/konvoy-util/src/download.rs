@@ -1,6 +1,9 @@
 //! Shared HTTP download with progress reporting and SHA-256 hashing.
 
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
 
 use sha2::{Digest, Sha256};
 
@@ -28,11 +31,28 @@ fn u64_from_usize(n: usize) -> u64 {
 /// `total` is consistent across chunks — either always `Some(n)` for the
 /// same `n` or always `None`.
 ///
+/// If the connection drops mid-body, the download is resumed from the
+/// current byte offset with a `Range` request (up to
+/// [`download_retries`](crate::net::NetworkClient::download_retries) times),
+/// made conditional with `If-Range` on the response's strong `ETag` or its
+/// `Last-Modified`, so a tail is never appended to a different version of the
+/// file. The hasher already covers the bytes on disk, so the resumed tail
+/// simply continues it. When the first response carries neither validator, or
+/// the server does not answer with a matching `206 Partial Content`, the file
+/// is truncated and the download restarts from the beginning, reporting
+/// progress from zero.
+///
+/// A download that receives no bytes for
+/// [`download_idle_timeout_secs`](crate::net::NetworkClient::download_idle_timeout_secs)
+/// counts as stalled and is resumed the same way; if it still stalls after
+/// the last resume, it fails with a `stalled` error instead of waiting for
+/// the request's global timeout.
+///
 /// Returns the hex-encoded SHA-256 hash of the downloaded content.
 ///
 /// # Errors
 /// Returns an error if the HTTP request fails, the file cannot be written,
-/// or a read error occurs during streaming.
+/// or a read error persists after every resume attempt.
 pub(crate) fn stream_download<F>(
     net: &crate::net::NetworkClient,
     url: &str,
@@ -42,61 +62,235 @@ pub(crate) fn stream_download<F>(
 where
     F: FnMut(u64, Option<u64>),
 {
-    let response = net.get(url, 600).map_err(|e| match e {
-        crate::net::RequestError::Offline => UtilError::Offline {
-            url: url.to_owned(),
-        },
-        crate::net::RequestError::Status { message, .. }
-        | crate::net::RequestError::Transport { message } => UtilError::Download { message },
-    })?;
+    let response = net.get(url, 600).map_err(|e| request_error(url, e))?;
 
-    let content_length: Option<u64> = response
-        .headers()
-        .get("content-length")
-        .and_then(|v| v.to_str().ok())
+    let content_length: Option<u64> = header_value(&response, "content-length")
         .and_then(|s| s.parse().ok())
         .filter(|t: &u64| *t > 0);
+    let mut validator = range_validator(&response);
 
-    let mut body = response.into_body();
+    let idle_timeout = Duration::from_secs(net.download_idle_timeout_secs());
+    let mut chunks = read_chunks(response.into_body());
     let mut file = std::fs::File::create(dest).map_err(|source| UtilError::Io {
         path: dest.display().to_string(),
         source,
     })?;
 
-    let mut hasher = Sha256::new();
-    let mut downloaded: u64 = 0;
-    let mut buf = vec![0u8; 64 * 1024];
+    let mut progress = Progress {
+        hasher: Sha256::new(),
+        downloaded: 0,
+    };
+    let mut resumes = 0;
 
     loop {
-        let n = std::io::Read::read(&mut body.as_reader(), &mut buf).map_err(|e| {
-            UtilError::Download {
-                message: e.to_string(),
+        match copy_body(
+            &chunks,
+            idle_timeout,
+            &mut file,
+            dest,
+            &mut progress,
+            content_length,
+            &mut on_progress,
+        ) {
+            Ok(()) => break,
+            Err(err @ UtilError::Download { .. }) if resumes < net.download_retries() => {
+                resumes += 1;
+                let body = match resume(net, url, progress.downloaded, &mut validator) {
+                    Ok(Resumed::Tail(tail)) => tail,
+                    Ok(Resumed::Restart(whole)) => {
+                        restart(&mut file, dest, &mut progress)?;
+                        on_progress(0, content_length);
+                        whole
+                    }
+                    // The original read error is the one worth reporting.
+                    Err(_) => return Err(err),
+                };
+                chunks = read_chunks(body);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(finalize_hex(progress.hasher))
+}
+
+/// Running state of a download: the hash and length of the bytes on disk.
+struct Progress {
+    hasher: Sha256,
+    downloaded: u64,
+}
+
+/// The body to continue with after a dropped connection.
+enum Resumed {
+    /// The remainder of the content, starting at the current offset.
+    Tail(ureq::Body),
+    /// The whole content; what is on disk must be discarded first.
+    Restart(ureq::Body),
+}
+
+/// Read `body` on a background thread, sending each chunk as it arrives; an
+/// empty chunk marks the end of the body.
+///
+/// A blocked read cannot be interrupted, so this is what lets the receiver
+/// give up on a silent connection. An abandoned reader exits once its read
+/// returns, at the latest when the request's global timeout expires.
+fn read_chunks(body: ureq::Body) -> Receiver<std::io::Result<Vec<u8>>> {
+    let (tx, rx) = mpsc::sync_channel(4);
+    std::thread::spawn(move || {
+        let mut reader = body.into_reader();
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            let chunk = reader
+                .read(&mut buf)
+                .map(|n| buf.get(..n).unwrap_or_default().to_vec());
+            let done = chunk.as_ref().map_or(true, Vec::is_empty);
+            if tx.send(chunk).is_err() || done {
+                return;
             }
-        })?;
-        if n == 0 {
-            break;
         }
+    });
+    rx
+}
 
-        let Some(chunk) = buf.get(..n) else {
-            break; // unreachable: n is bounded by buf.len()
+/// Copy the chunks of a body into `file` until EOF, updating the hash and
+/// byte count.
+///
+/// Read failures and stalls (no chunk within `idle_timeout`) surface as
+/// `UtilError::Download` (retryable by resuming); write failures as
+/// `UtilError::Io`.
+fn copy_body<F>(
+    chunks: &Receiver<std::io::Result<Vec<u8>>>,
+    idle_timeout: Duration,
+    file: &mut std::fs::File,
+    dest: &Path,
+    progress: &mut Progress,
+    content_length: Option<u64>,
+    on_progress: &mut F,
+) -> Result<(), UtilError>
+where
+    F: FnMut(u64, Option<u64>),
+{
+    loop {
+        let chunk = match chunks.recv_timeout(idle_timeout) {
+            Ok(chunk) => chunk.map_err(|e| UtilError::Download {
+                message: e.to_string(),
+            })?,
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(UtilError::Download {
+                    message: format!(
+                        "stalled — no data received for {}s (set KONVOY_DOWNLOAD_IDLE_TIMEOUT to wait longer)",
+                        idle_timeout.as_secs()
+                    ),
+                })
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(UtilError::Download {
+                    message: "the body reader stopped unexpectedly".to_owned(),
+                })
+            }
         };
-        std::io::Write::write_all(&mut file, chunk).map_err(|source| UtilError::Io {
+        if chunk.is_empty() {
+            return Ok(());
+        }
+
+        file.write_all(&chunk).map_err(|source| UtilError::Io {
             path: dest.display().to_string(),
             source,
         })?;
-        hasher.update(chunk);
+        progress.hasher.update(&chunk);
 
-        downloaded = downloaded.saturating_add(u64_from_usize(n));
-        on_progress(downloaded, content_length);
+        progress.downloaded = progress
+            .downloaded
+            .saturating_add(u64_from_usize(chunk.len()));
+        on_progress(progress.downloaded, content_length);
     }
+}
 
-    Ok(finalize_hex(hasher))
+/// Re-request `url` after a dropped connection, asking for the bytes from
+/// `offset` onwards when there are any to keep and `validator` can tie them to
+/// the same version of the content.
+///
+/// On a restart, `validator` is replaced with the new response's.
+fn resume(
+    net: &crate::net::NetworkClient,
+    url: &str,
+    offset: u64,
+    validator: &mut Option<String>,
+) -> Result<Resumed, UtilError> {
+    if let Some(current) = validator.as_deref().filter(|_| offset > 0) {
+        let response = net
+            .get_from_offset(url, 600, offset, current)
+            .map_err(|e| request_error(url, e))?;
+        if response.status() == 206 {
+            let expected = format!("bytes {offset}-");
+            if header_value(&response, "content-range")
+                .is_some_and(|range| range.starts_with(&expected))
+            {
+                return Ok(Resumed::Tail(response.into_body()));
+            }
+        } else {
+            // The content changed, or the server ignored the Range header;
+            // either way it sent everything.
+            *validator = range_validator(&response);
+            return Ok(Resumed::Restart(response.into_body()));
+        }
+    }
+    let response = net.get(url, 600).map_err(|e| request_error(url, e))?;
+    *validator = range_validator(&response);
+    Ok(Resumed::Restart(response.into_body()))
+}
+
+/// The `If-Range` value for resuming `response`'s body: its `ETag` when that
+/// is strong (weak ones are not allowed in `If-Range`), else its
+/// `Last-Modified`.
+fn range_validator(response: &ureq::http::Response<ureq::Body>) -> Option<String> {
+    header_value(response, "etag")
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| header_value(response, "last-modified"))
+        .map(str::to_owned)
+}
+
+/// Discard the partial download so it can be rewritten from the first byte.
+fn restart(
+    file: &mut std::fs::File,
+    dest: &Path,
+    progress: &mut Progress,
+) -> Result<(), UtilError> {
+    let io_err = |source| UtilError::Io {
+        path: dest.display().to_string(),
+        source,
+    };
+    file.set_len(0).map_err(io_err)?;
+    file.seek(SeekFrom::Start(0)).map_err(io_err)?;
+    progress.hasher = Sha256::new();
+    progress.downloaded = 0;
+    Ok(())
+}
+
+fn header_value<'a>(response: &'a ureq::http::Response<ureq::Body>, name: &str) -> Option<&'a str> {
+    response.headers().get(name).and_then(|v| v.to_str().ok())
+}
+
+fn request_error(url: &str, e: crate::net::RequestError) -> UtilError {
+    match e {
+        crate::net::RequestError::Offline => UtilError::Offline {
+            url: url.to_owned(),
+        },
+        crate::net::RequestError::Status { code: 404, .. } => UtilError::NotFound {
+            url: url.to_owned(),
+        },
+        crate::net::RequestError::Status { message, .. }
+        | crate::net::RequestError::Transport { message } => UtilError::Download { message },
+    }
 }
 
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
-    use super::{stream_download, u64_from_usize};
+    use super::{stream_download, u64_from_usize, UtilError};
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
 
     /// No-op progress callback used by tests that don't care about events.
     fn ignore_progress(_: u64, _: Option<u64>) {}
@@ -171,4 +365,185 @@ mod tests {
         );
         assert!(!dest.exists(), "no file must be created when offline");
     }
+
+    /// Serve one canned raw response per connection, in order, reporting
+    /// each request's head on the returned channel.
+    fn serve(responses: Vec<Vec<u8>>) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/toolchain.tar.gz", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0_u8; 2048];
+                let n = stream.read(&mut request).unwrap();
+                tx.send(String::from_utf8_lossy(request.get(..n).unwrap()).into_owned())
+                    .unwrap();
+                stream.write_all(&response).unwrap();
+            }
+        });
+        (url, rx)
+    }
+
+    /// A response that promises `total` bytes but sends only `sent`, then
+    /// closes the connection.
+    fn truncated(total: &[u8], sent: usize, extra_headers: &str) -> Vec<u8> {
+        let mut response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n{extra_headers}Connection: close\r\n\r\n",
+            total.len()
+        )
+        .into_bytes();
+        response.extend_from_slice(total.get(..sent).unwrap());
+        response
+    }
+
+    fn full(status_line: &str, extra_headers: &str, body: &[u8]) -> Vec<u8> {
+        let mut response = format!(
+            "{status_line}\r\nContent-Length: {}\r\n{extra_headers}Connection: close\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    #[test]
+    fn interrupted_download_resumes_with_range_request() {
+        let content = b"0123456789abcdefghij";
+        let tail = content.get(8..).unwrap();
+        let (url, requests) = serve(vec![
+            truncated(content, 8, "ETag: \"v1\"\r\n"),
+            full(
+                "HTTP/1.1 206 Partial Content",
+                &format!("Content-Range: bytes 8-19/{}\r\n", content.len()),
+                tail,
+            ),
+        ]);
+        let tmp = tempfile::tempdir().unwrap();
+        let dest = tmp.path().join("out.bin");
+        let mut last = (0, None);
+
+        let hash = stream_download(&online(), &url, &dest, |done, total| {
+            last = (done, total);
+        })
+        .unwrap();
+
+        assert_eq!(hash, crate::hash::sha256_bytes(content));
+        assert_eq!(std::fs::read(&dest).unwrap(), content);
+        assert_eq!(last, (20, Some(20)));
+        let first = requests.recv().unwrap().to_ascii_lowercase();
+        assert!(!first.contains("range:"), "first request: {first}");
+        let second = requests.recv().unwrap().to_ascii_lowercase();
+        assert!(
+            second.contains("range: bytes=8-"),
+            "resume request: {second}"
+        );
+        assert!(
+            second.contains("if-range: \"v1\""),
+            "resume request: {second}"
+        );
+    }
+
+    #[test]
+    fn resume_falls_back_to_last_modified_for_weak_etags() {
+        let content = b"0123456789abcdefghij";
+        let (url, requests) = serve(vec![
+            truncated(
+                content,
+                8,
+                "ETag: W/\"v1\"\r\nLast-Modified: Tue, 01 Sep 2026 10:00:00 GMT\r\n",
+            ),
+            full("HTTP/1.1 200 OK", "", content),
+        ]);
+        let tmp = tempfile::tempdir().unwrap();
+        let dest = tmp.path().join("out.bin");
+
+        stream_download(&online(), &url, &dest, ignore_progress).unwrap();
+
+        let _ = requests.recv().unwrap();
+        let second = requests.recv().unwrap().to_ascii_lowercase();
+        assert!(
+            second.contains("if-range: tue, 01 sep 2026 10:00:00 gmt"),
+            "resume request: {second}"
+        );
+    }
+
+    #[test]
+    fn interrupted_download_without_validator_restarts_from_zero() {
+        let content = b"0123456789abcdefghij";
+        let (url, requests) = serve(vec![
+            truncated(content, 8, ""),
+            full("HTTP/1.1 200 OK", "", content),
+        ]);
+        let tmp = tempfile::tempdir().unwrap();
+        let dest = tmp.path().join("out.bin");
+
+        let hash = stream_download(&online(), &url, &dest, ignore_progress).unwrap();
+
+        assert_eq!(hash, crate::hash::sha256_bytes(content));
+        assert_eq!(std::fs::read(&dest).unwrap(), content);
+        let _ = requests.recv().unwrap();
+        let second = requests.recv().unwrap().to_ascii_lowercase();
+        assert!(!second.contains("range:"), "restart request: {second}");
+    }
+
+    #[test]
+    fn interrupted_download_restarts_when_content_changed() {
+        let content = b"0123456789abcdefghij";
+        let (url, _requests) = serve(vec![
+            truncated(b"stale-version-of-it!", 8, "ETag: \"v1\"\r\n"),
+            full("HTTP/1.1 200 OK", "ETag: \"v2\"\r\n", content),
+        ]);
+        let tmp = tempfile::tempdir().unwrap();
+        let dest = tmp.path().join("out.bin");
+
+        let hash = stream_download(&online(), &url, &dest, ignore_progress).unwrap();
+
+        assert_eq!(hash, crate::hash::sha256_bytes(content));
+        assert_eq!(std::fs::read(&dest).unwrap(), content);
+    }
+
+    #[test]
+    fn silent_download_fails_as_stalled() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/toolchain.tar.gz", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0_u8; 2048];
+            let _ = stream.read(&mut request).unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\nfirst bytes")
+                .unwrap();
+            // Keep the connection open without sending the rest.
+            std::thread::sleep(std::time::Duration::from_secs(10));
+        });
+        let tmp = tempfile::tempdir().unwrap();
+        let dest = tmp.path().join("out.bin");
+        let net = online()
+            .with_download_retries(0)
+            .with_download_idle_timeout(1);
+
+        let started = std::time::Instant::now();
+        let err = stream_download(&net, &url, &dest, ignore_progress).unwrap_err();
+
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        assert!(
+            matches!(&err, UtilError::Download { message } if message.contains("stalled")),
+            "got: {err:?}"
+        );
+    }
+
+    #[test]
+    fn missing_file_is_reported_as_not_found() {
+        let (url, _requests) = serve(vec![full("HTTP/1.1 404 Not Found", "", b"")]);
+        let tmp = tempfile::tempdir().unwrap();
+        let dest = tmp.path().join("out.bin");
+
+        let err = stream_download(&online(), &url, &dest, ignore_progress).unwrap_err();
+
+        assert!(
+            matches!(&err, UtilError::NotFound { url: missing } if *missing == url),
+            "got: {err:?}"
+        );
+    }
 }
@@ -14,6 +14,15 @@ pub enum UtilError {
     #[error("invalid glob pattern `{pattern}`: {message}")]
     GlobPattern { pattern: String, message: String },
 
+    /// A `.konvoyignore` line could not be parsed as a pattern.
+    #[error("invalid pattern `{pattern}` at {path}:{line}: {message}")]
+    IgnorePattern {
+        path: String,
+        line: usize,
+        pattern: String,
+        message: String,
+    },
+
     /// A command failed to execute.
     #[error("cannot execute command: {source}")]
     CommandExec { source: std::io::Error },
@@ -22,6 +31,10 @@ pub enum UtilError {
     #[error("download failed: {message}")]
     Download { message: String },
 
+    /// The server answered a download with `404 Not Found`.
+    #[error("download failed: {url} was not found (HTTP 404)")]
+    NotFound { url: String },
+
     /// An outbound request was refused because offline mode is active.
     ///
     /// Raised by the wire-level [`NetworkClient`](crate::net::NetworkClient)
@@ -69,4 +82,12 @@ pub enum UtilError {
     /// Gradle Module Metadata could not be parsed.
     #[error("cannot parse module metadata: {reason}")]
     ModuleMetadataParse { reason: String },
+
+    /// Free disk space could not be determined.
+    #[error("cannot determine free space on {path}: {message}")]
+    DiskSpace { path: String, message: String },
+
+    /// `maven-metadata.xml` could not be parsed.
+    #[error("cannot parse maven-metadata.xml: {reason}")]
+    MavenMetadataParse { reason: String },
 }
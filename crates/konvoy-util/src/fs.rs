@@ -209,6 +209,51 @@ fn collect_files_recursive(
     Ok(())
 }
 
+/// Bytes available to unprivileged users on the filesystem holding `path`.
+///
+/// `path` need not exist yet: the nearest existing ancestor is queried, so
+/// `~/.konvoy` can be checked before the first install. Uses POSIX `df -Pk`,
+/// which every supported host (Linux and macOS) provides.
+///
+/// # Errors
+/// Returns an error if `df` cannot be run, fails, or prints output that cannot
+/// be parsed.
+pub fn available_space(path: &Path) -> Result<u64, UtilError> {
+    let existing = path
+        .ancestors()
+        .find(|p| p.exists())
+        .unwrap_or_else(|| Path::new("/"));
+    let output = std::process::Command::new("df")
+        .arg("-Pk")
+        .arg(existing)
+        .output()
+        .map_err(|source| UtilError::CommandExec { source })?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        return Err(UtilError::DiskSpace {
+            path: existing.display().to_string(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        });
+    }
+    parse_df_available(&stdout).ok_or_else(|| UtilError::DiskSpace {
+        path: existing.display().to_string(),
+        message: format!("unexpected `df` output: {}", stdout.trim()),
+    })
+}
+
+/// Extract the `Available` column (in 1024-byte blocks) from `df -Pk` output
+/// and convert it to bytes.
+fn parse_df_available(stdout: &str) -> Option<u64> {
+    // POSIX format: one header line, then one line per filesystem. The
+    // filesystem name may contain spaces, so count columns from the right:
+    // `<blocks> <used> <available> <capacity%> <mount point>`.
+    let line = stdout.lines().nth(1)?;
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let capacity_idx = fields.iter().rposition(|f| f.ends_with('%'))?;
+    let available = fields.get(capacity_idx.checked_sub(1)?)?;
+    available.parse::<u64>().ok()?.checked_mul(1024)
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
@@ -764,4 +809,22 @@ mod tests {
         // hard_link fails, then copy fails too => error
         assert!(result.is_err());
     }
+
+    #[test]
+    fn parse_df_available_reads_the_available_column() {
+        let linux = "Filesystem     1024-blocks     Used Available Capacity Mounted on\n/dev/vda         264212084 17571684  78485724      19% /\n";
+        assert_eq!(parse_df_available(linux), Some(78_485_724 * 1024));
+        // A filesystem name with spaces (macOS map mounts) still parses.
+        let macos = "Filesystem 1024-blocks Used Available Capacity Mounted on\nmap auto_home 0 0 12 100% /System/Volumes/Data/home\n";
+        assert_eq!(parse_df_available(macos), Some(12 * 1024));
+        assert_eq!(parse_df_available("Filesystem\n"), None);
+        assert_eq!(parse_df_available(""), None);
+    }
+
+    #[test]
+    fn available_space_queries_nearest_existing_ancestor() {
+        let tmp = tempfile::tempdir().unwrap();
+        let missing = tmp.path().join("not").join("yet").join("created");
+        assert!(available_space(&missing).unwrap() > 0);
+    }
 }
@@ -1,14 +1,89 @@
 //! Hashing utilities for deterministic cache key computation.
 
+use std::fmt::{self, Write as _};
 use std::path::Path;
 
-use sha2::{Digest, Sha256};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
 
 use crate::error::UtilError;
 
-/// Format a finalized SHA-256 hasher as a lowercase hex string.
-pub(crate) fn finalize_hex(hasher: Sha256) -> String {
-    format!("{:x}", hasher.finalize())
+/// The digest algorithm of an artifact hash pinned in `konvoy.lock`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    /// SHA-256, used unless configured otherwise.
+    #[default]
+    Sha256,
+    /// SHA-512, for registries whose policy prefers it.
+    Sha512,
+}
+
+impl HashAlgorithm {
+    /// Whether this is the default, SHA-256.
+    #[must_use]
+    pub fn is_sha256(&self) -> bool {
+        *self == Self::Sha256
+    }
+
+    /// The name used in `konvoy.toml` and `konvoy.lock`.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::Sha512 => "sha512",
+        }
+    }
+
+    /// Hex digest of `data`.
+    #[must_use]
+    pub fn hash_bytes(self, data: &[u8]) -> String {
+        match self {
+            Self::Sha256 => sha256_bytes(data),
+            Self::Sha512 => sha512_bytes(data),
+        }
+    }
+
+    /// Hex digest of the file at `path`. A SHA-256 digest may come from the
+    /// [`crate::hash_cache`] (see [`sha256_file`]); a SHA-512 digest is
+    /// always computed from the file's contents.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be opened or read.
+    pub fn hash_file(self, path: &Path) -> Result<String, UtilError> {
+        match self {
+            Self::Sha256 => sha256_file(path),
+            Self::Sha512 => sha512_file_uncached(path),
+        }
+    }
+
+    /// Hex digest of the file at `path`, always read from disk.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be opened or read.
+    pub fn hash_file_uncached(self, path: &Path) -> Result<String, UtilError> {
+        match self {
+            Self::Sha256 => sha256_file_uncached(path),
+            Self::Sha512 => sha512_file_uncached(path),
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Finalize a SHA-256 or SHA-512 hasher as a lowercase hex string.
+pub(crate) fn finalize_hex<D: Digest>(hasher: D) -> String {
+    let digest = hasher.finalize();
+    let mut hex = String::with_capacity(digest.len() * 2);
+    for byte in &digest {
+        // Writing to a `String` cannot fail.
+        let _ = write!(hex, "{byte:02x}");
+    }
+    hex
 }
 
 /// Compute the SHA-256 hex digest of a byte slice.
@@ -18,20 +93,84 @@ pub fn sha256_bytes(data: &[u8]) -> String {
     finalize_hex(hasher)
 }
 
+/// Compute the SHA-512 hex digest of a byte slice.
+pub fn sha512_bytes(data: &[u8]) -> String {
+    let mut hasher = Sha512::new();
+    hasher.update(data);
+    finalize_hex(hasher)
+}
+
 /// Compute the SHA-256 hex digest of a file using streaming reads.
 ///
 /// Uses a 64 KiB buffer to avoid loading the entire file into memory,
-/// which matters for large files like detekt JARs (~50 MB).
+/// which matters for large files like detekt JARs (~50 MB). When a
+/// [`crate::hash_cache`] is active and the file's size and mtime are
+/// unchanged since it was last hashed, the recorded digest is returned
+/// without reading the file.
 ///
 /// # Errors
 /// Returns an error if the file cannot be opened or read.
 pub fn sha256_file(path: &Path) -> Result<String, UtilError> {
+    let stamp = crate::hash_cache::file_stamp(path);
+    let key = path.display().to_string();
+    if let Some(hit) = stamp
+        .as_deref()
+        .and_then(|stamp| crate::hash_cache::lookup(&key, stamp))
+    {
+        return Ok(hit);
+    }
+    let digest = sha256_file_uncached(path)?;
+    if let Some(stamp) = stamp {
+        crate::hash_cache::record(key, stamp, &digest);
+    }
+    Ok(digest)
+}
+
+/// Compute the SHA-256 hex digest of a file, always reading it from disk.
+///
+/// Unlike [`sha256_file`], this never consults the [`crate::hash_cache`], so
+/// on-disk corruption that left the size and mtime intact is still caught.
+///
+/// # Errors
+/// Returns an error if the file cannot be opened or read.
+pub fn sha256_file_uncached(path: &Path) -> Result<String, UtilError> {
+    digest_file(path, Sha256::new())
+}
+
+/// Compute the SHA-512 hex digest of a file using streaming reads. There is
+/// no cached variant: the [`crate::hash_cache`] only records SHA-256 digests.
+///
+/// # Errors
+/// Returns an error if the file cannot be opened or read.
+pub fn sha512_file_uncached(path: &Path) -> Result<String, UtilError> {
+    digest_file(path, Sha512::new())
+}
+
+/// Compute the SHA-256 hex digest of everything `reader` yields, in 64 KiB
+/// chunks. `path` names the source in error messages; use this when the bytes
+/// to hash are not a plain file on disk, e.g. a decompressing reader.
+///
+/// # Errors
+/// Returns an error if reading fails.
+pub fn sha256_reader(reader: impl std::io::Read, path: &Path) -> Result<String, UtilError> {
+    digest_reader(reader, path, Sha256::new())
+}
+
+/// Feed the file at `path` through `hasher` in 64 KiB chunks.
+fn digest_file<D: Digest>(path: &Path, hasher: D) -> Result<String, UtilError> {
     let file = std::fs::File::open(path).map_err(|source| UtilError::Io {
         path: path.display().to_string(),
         source,
     })?;
-    let mut reader = std::io::BufReader::new(file);
-    let mut hasher = Sha256::new();
+    digest_reader(std::io::BufReader::new(file), path, hasher)
+}
+
+/// Feed `reader` through `hasher` in 64 KiB chunks; `path` labels errors.
+fn digest_reader<D: Digest>(
+    mut reader: impl std::io::Read,
+    path: &Path,
+    mut hasher: D,
+) -> Result<String, UtilError> {
     let mut buf = vec![0u8; 64 * 1024];
     loop {
         let n = std::io::Read::read(&mut reader, &mut buf).map_err(|source| UtilError::Io {
@@ -58,6 +197,24 @@ pub fn sha256_file(path: &Path) -> Result<String, UtilError> {
 /// Returns an error if the glob pattern is invalid, `dir` cannot be read, or any
 /// matched file cannot be read.
 pub fn sha256_dir(dir: &Path, pattern: &str) -> Result<String, UtilError> {
+    sha256_dir_filtered(dir, pattern, |_| true)
+}
+
+/// Like [`sha256_dir`], but only files for which `keep` returns `true` are
+/// hashed. With a filter that keeps everything the digest equals `sha256_dir`.
+///
+/// When a [`crate::hash_cache`] is active and every kept file has the same
+/// path, size, and mtime as when the directory was last hashed, the recorded
+/// digest is returned without reading any file.
+///
+/// # Errors
+/// Returns an error if the glob pattern is invalid, `dir` cannot be read, or any
+/// kept file cannot be read.
+pub fn sha256_dir_filtered(
+    dir: &Path,
+    pattern: &str,
+    keep: impl Fn(&Path) -> bool,
+) -> Result<String, UtilError> {
     use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 
     let full_pattern = dir.join(pattern);
@@ -69,11 +226,20 @@ pub fn sha256_dir(dir: &Path, pattern: &str) -> Result<String, UtilError> {
             message: e.to_string(),
         })?
         .filter_map(Result::ok)
-        .filter(|p| p.is_file())
+        .filter(|p| p.is_file() && keep(p))
         .collect();
 
     paths.sort();
 
+    let stamp = crate::hash_cache::dir_stamp(dir, &paths);
+    let key = format!("{}\0{pattern}", dir.display());
+    if let Some(hit) = stamp
+        .as_deref()
+        .and_then(|stamp| crate::hash_cache::lookup(&key, stamp))
+    {
+        return Ok(hit);
+    }
+
     // Read files in parallel; preserve sorted order when feeding the hasher so
     // the digest is bit-identical to the sequential implementation (cache keys
     // must remain stable across releases).
@@ -94,7 +260,11 @@ pub fn sha256_dir(dir: &Path, pattern: &str) -> Result<String, UtilError> {
         hasher.update(data);
     }
 
-    Ok(finalize_hex(hasher))
+    let digest = finalize_hex(hasher);
+    if let Some(stamp) = stamp {
+        crate::hash_cache::record(key, stamp, &digest);
+    }
+    Ok(digest)
 }
 
 /// Combine multiple string parts into a single composite SHA-256 hash.
@@ -146,6 +316,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn sha512_bytes_empty() {
+        assert_eq!(
+            sha512_bytes(b""),
+            "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce\
+             47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"
+        );
+    }
+
+    #[test]
+    fn hash_algorithm_hashes_files_with_its_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("lib.klib");
+        fs::write(&file, b"klib").unwrap();
+
+        for algorithm in [HashAlgorithm::Sha256, HashAlgorithm::Sha512] {
+            let expected = algorithm.hash_bytes(b"klib");
+            assert_eq!(algorithm.hash_file(&file).unwrap(), expected);
+            assert_eq!(algorithm.hash_file_uncached(&file).unwrap(), expected);
+        }
+        assert_eq!(HashAlgorithm::Sha256.hash_bytes(b"klib").len(), 64);
+        assert_eq!(HashAlgorithm::Sha512.hash_bytes(b"klib").len(), 128);
+        assert_eq!(HashAlgorithm::default(), HashAlgorithm::Sha256);
+    }
+
     #[test]
     fn sha256_file_reads_correctly() {
         let dir = tempfile::tempdir().unwrap();
@@ -157,6 +352,13 @@ mod tests {
         assert_eq!(hash, expected);
     }
 
+    #[test]
+    fn sha256_reader_matches_bytes_across_chunks() {
+        let data = vec![7u8; 200 * 1024];
+        let hash = sha256_reader(data.as_slice(), Path::new("in-memory")).unwrap();
+        assert_eq!(hash, sha256_bytes(&data));
+    }
+
     #[test]
     fn sha256_file_missing() {
         let result = sha256_file(Path::new("/nonexistent/path/file.txt"));
@@ -214,6 +416,22 @@ mod tests {
         assert_eq!(hash.len(), 64);
     }
 
+    #[test]
+    fn sha256_dir_filtered_excludes_rejected_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.kt"), b"fun a()").unwrap();
+        let only_a = sha256_dir(dir.path(), "**/*.kt").unwrap();
+
+        fs::write(dir.path().join("scratch.kt"), b"fun scratch()").unwrap();
+        let filtered =
+            sha256_dir_filtered(dir.path(), "**/*.kt", |p| !p.ends_with("scratch.kt")).unwrap();
+        assert_eq!(filtered, only_a);
+
+        let kept_all = sha256_dir_filtered(dir.path(), "**/*.kt", |_| true).unwrap();
+        assert_eq!(kept_all, sha256_dir(dir.path(), "**/*.kt").unwrap());
+        assert_ne!(kept_all, only_a);
+    }
+
     #[test]
     fn sha256_dir_parallel_reads_match_sequential_reference() {
         // Build a tree large enough that the par_iter reads matter, then check
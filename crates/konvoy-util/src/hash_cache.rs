@@ -0,0 +1,279 @@
+//! Persistent cache of file and directory hashes, keyed by path and validated
+//! by size + modification time.
+//!
+//! [`crate::hash::sha256_file`] and [`crate::hash::sha256_dir_filtered`]
+//! consult the cache once a build has [`activate`]d it, so unchanged
+//! dependency klibs and source trees are not re-read on every build. An entry
+//! is only reused when the recorded size and nanosecond mtime still match, so
+//! any rewrite that bumps the mtime is re-hashed even if the size is unchanged.
+//!
+//! Files modified within [`RECENT_WINDOW`] of the lookup are never cached:
+//! a second write within the filesystem's timestamp granularity could
+//! otherwise leave the mtime unchanged. Set `KONVOY_NO_HASH_CACHE=1` to
+//! disable the cache and always hash from scratch.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+/// Environment variable that disables the hash cache when set (to anything
+/// but an empty string or `0`).
+pub const DISABLE_ENV: &str = "KONVOY_NO_HASH_CACHE";
+
+/// Files modified more recently than this are hashed but not cached.
+const RECENT_WINDOW: Duration = Duration::from_secs(2);
+
+/// The process-wide active cache, if a build activated one.
+static ACTIVE: Mutex<Option<HashCache>> = Mutex::new(None);
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HashCache {
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(skip)]
+    dirty: bool,
+    entries: BTreeMap<String, Entry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    /// Size + mtime of a file, or a digest of the listing for a directory.
+    stamp: String,
+    sha256: String,
+}
+
+fn active() -> MutexGuard<'static, Option<HashCache>> {
+    ACTIVE
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Whether `KONVOY_NO_HASH_CACHE` turns the cache off.
+fn disabled_by_env() -> bool {
+    std::env::var(DISABLE_ENV).is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Load the cache stored at `cache_file` and make it the one consulted by
+/// the hashing functions, saving any previously active cache first.
+///
+/// A missing or unreadable file starts an empty cache. Does nothing when
+/// `KONVOY_NO_HASH_CACHE` is set.
+pub fn activate(cache_file: &Path) {
+    if disabled_by_env() {
+        return;
+    }
+    let mut guard = active();
+    if guard.as_ref().is_some_and(|cache| cache.path == cache_file) {
+        return;
+    }
+    if let Some(previous) = guard.take() {
+        previous.save();
+    }
+    let mut cache: HashCache = std::fs::read(cache_file)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+    cache.path = cache_file.to_path_buf();
+    *guard = Some(cache);
+}
+
+/// Write the active cache back to disk if it gained entries.
+///
+/// Best-effort: a failed write only loses the cached hashes.
+pub fn persist() {
+    let mut guard = active();
+    if let Some(cache) = guard.as_mut() {
+        if cache.dirty {
+            cache.save();
+            cache.dirty = false;
+        }
+    }
+}
+
+impl HashCache {
+    fn save(&self) {
+        let Ok(json) = serde_json::to_vec(self) else {
+            return;
+        };
+        if let Some(parent) = self.path.parent() {
+            if crate::fs::ensure_dir(parent).is_err() {
+                return;
+            }
+        }
+        let _ = crate::fs::write_file(&self.path, json);
+    }
+}
+
+/// The cache stamp of a file: its size and nanosecond mtime, or `None` when
+/// it cannot be cached (no cache is active, the file cannot be stat'ed, or it
+/// was modified too recently to trust its mtime).
+pub(crate) fn file_stamp(path: &Path) -> Option<String> {
+    if active().is_none() {
+        return None;
+    }
+    stamp_of(path)
+}
+
+fn stamp_of(path: &Path) -> Option<String> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?;
+    if SystemTime::now()
+        .duration_since(modified)
+        .map_or(true, |age| age < RECENT_WINDOW)
+    {
+        return None;
+    }
+    let since_epoch = modified.duration_since(SystemTime::UNIX_EPOCH).ok()?;
+    Some(format!(
+        "{}:{}.{:09}",
+        metadata.len(),
+        since_epoch.as_secs(),
+        since_epoch.subsec_nanos()
+    ))
+}
+
+/// The cache stamp of a directory hash: a digest of every hashed file's
+/// relative path and file stamp, or `None` if any file cannot be cached.
+pub(crate) fn dir_stamp(dir: &Path, paths: &[PathBuf]) -> Option<String> {
+    if active().is_none() {
+        return None;
+    }
+    let mut listing = String::new();
+    for path in paths {
+        let relative = path.strip_prefix(dir).unwrap_or(path);
+        listing.push_str(&relative.display().to_string());
+        listing.push('\0');
+        listing.push_str(&stamp_of(path)?);
+        listing.push('\n');
+    }
+    Some(crate::hash::sha256_bytes(listing.as_bytes()))
+}
+
+/// The cached hash for `key`, if it was recorded with the same `stamp`.
+pub(crate) fn lookup(key: &str, stamp: &str) -> Option<String> {
+    let guard = active();
+    let entry = guard.as_ref()?.entries.get(key)?;
+    (entry.stamp == stamp).then(|| entry.sha256.clone())
+}
+
+/// Record the hash for `key` at `stamp` in the active cache.
+pub(crate) fn record(key: String, stamp: String, sha256: &str) {
+    let mut guard = active();
+    if let Some(cache) = guard.as_mut() {
+        cache.entries.insert(
+            key,
+            Entry {
+                stamp,
+                sha256: sha256.to_owned(),
+            },
+        );
+        cache.dirty = true;
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn age(path: &Path) {
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(60))
+            .unwrap();
+    }
+
+    #[test]
+    fn recent_files_have_no_stamp() {
+        let tmp = tempfile::tempdir().unwrap();
+        let file = tmp.path().join("fresh.klib");
+        std::fs::write(&file, "data").unwrap();
+        assert!(stamp_of(&file).is_none());
+
+        age(&file);
+        assert!(stamp_of(&file).is_some());
+    }
+
+    #[test]
+    fn stamp_changes_with_mtime_at_same_size() {
+        let tmp = tempfile::tempdir().unwrap();
+        let file = tmp.path().join("lib.klib");
+        std::fs::write(&file, "aaaa").unwrap();
+        age(&file);
+        let before = stamp_of(&file).unwrap();
+
+        std::fs::write(&file, "bbbb").unwrap();
+        let handle = std::fs::File::options().write(true).open(&file).unwrap();
+        handle
+            .set_modified(SystemTime::now() - Duration::from_secs(30))
+            .unwrap();
+        let after = stamp_of(&file).unwrap();
+        assert_ne!(before, after);
+    }
+
+    /// The only test that activates the process-wide cache, so concurrent
+    /// tests never swap it out from under each other's assertions.
+    #[test]
+    fn active_cache_reuses_and_invalidates_hashes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache_file = tmp.path().join("cache").join("hashes.json");
+        activate(&cache_file);
+
+        let file = tmp.path().join("dep.klib");
+        std::fs::write(&file, "aaaa").unwrap();
+        age(&file);
+        let first = crate::hash::sha256_file(&file).unwrap();
+        let key = file.display().to_string();
+        let stamp = stamp_of(&file).unwrap();
+        assert_eq!(lookup(&key, &stamp).as_deref(), Some(first.as_str()));
+
+        // A recorded hash is served without reading the file.
+        record(key.clone(), stamp.clone(), "cached");
+        assert_eq!(crate::hash::sha256_file(&file).unwrap(), "cached");
+
+        // Same size, newer mtime: re-hashed from the new contents.
+        std::fs::write(&file, "bbbb").unwrap();
+        let handle = std::fs::File::options().write(true).open(&file).unwrap();
+        handle
+            .set_modified(SystemTime::now() - Duration::from_secs(30))
+            .unwrap();
+        assert_eq!(
+            crate::hash::sha256_file(&file).unwrap(),
+            crate::hash::sha256_bytes(b"bbbb")
+        );
+
+        // Directory hashes are cached and invalidated the same way.
+        let src = tmp.path().join("src");
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::write(src.join("a.kt"), "fun a() {}").unwrap();
+        age(&src.join("a.kt"));
+        let dir_hash = crate::hash::sha256_dir(&src, "**/*.kt").unwrap();
+        assert_eq!(crate::hash::sha256_dir(&src, "**/*.kt").unwrap(), dir_hash);
+        std::fs::write(src.join("b.kt"), "fun b() {}").unwrap();
+        assert_ne!(crate::hash::sha256_dir(&src, "**/*.kt").unwrap(), dir_hash);
+
+        persist();
+        let saved: HashCache =
+            serde_json::from_slice(&std::fs::read(&cache_file).unwrap()).unwrap();
+        assert!(saved.entries.contains_key(&key));
+    }
+
+    #[test]
+    fn cache_round_trips_through_json() {
+        let mut cache = HashCache::default();
+        cache.entries.insert(
+            "/tmp/a.klib".to_owned(),
+            Entry {
+                stamp: "4:1.000000000".to_owned(),
+                sha256: "abc".to_owned(),
+            },
+        );
+        let json = serde_json::to_vec(&cache).unwrap();
+        let back: HashCache = serde_json::from_slice(&json).unwrap();
+        let entry = back.entries.get("/tmp/a.klib").unwrap();
+        assert_eq!(entry.sha256, "abc");
+        assert_eq!(entry.stamp, "4:1.000000000");
+    }
+}
@@ -0,0 +1,286 @@
+//! `.konvoyignore` parsing and matching.
+//!
+//! Supports the common subset of gitignore syntax: `#` comments, blank lines,
+//! `!` negation, a leading `/` to anchor a pattern to the project root, a
+//! trailing `/` to match directories only, and `*`, `?`, `[...]`, `**` globs.
+//! A pattern without a `/` (other than a trailing one) matches a file or
+//! directory name at any depth.
+//!
+//! Precedence follows git: patterns are evaluated in file order and the **last**
+//! matching pattern decides, so a later `!keep.kt` re-includes a file excluded
+//! by an earlier `*.kt`, and a later `*.kt` excludes it again. Unlike git, a
+//! negation can re-include a file even when its parent directory is excluded —
+//! every path is evaluated on its own.
+
+use std::path::{Component, Path, PathBuf};
+
+use glob::{MatchOptions, Pattern};
+
+use crate::error::UtilError;
+
+/// File name of the ignore file, looked up in the project root.
+pub const IGNORE_FILE_NAME: &str = ".konvoyignore";
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Parsed ignore rules, matched against paths relative to `root`.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    root: PathBuf,
+    rules: Vec<IgnoreRule>,
+}
+
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    pattern: Pattern,
+    negated: bool,
+    dir_only: bool,
+    anchored: bool,
+}
+
+impl IgnoreRules {
+    /// Load `<root>/.konvoyignore`. An absent file yields empty rules, which
+    /// ignore nothing.
+    ///
+    /// # Errors
+    /// Returns an error if the file exists but cannot be read, or contains an
+    /// invalid pattern.
+    pub fn load(root: &Path) -> Result<Self, UtilError> {
+        let path = root.join(IGNORE_FILE_NAME);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Self::parse(root, &content, &path.display().to_string()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self {
+                root: root.to_path_buf(),
+                rules: Vec::new(),
+            }),
+            Err(source) => Err(UtilError::Io {
+                path: path.display().to_string(),
+                source,
+            }),
+        }
+    }
+
+    /// Parse ignore rules from `content`. `origin` names the source in errors.
+    ///
+    /// # Errors
+    /// Returns an error if a line is not a valid glob pattern.
+    pub fn parse(root: &Path, content: &str, origin: &str) -> Result<Self, UtilError> {
+        let mut rules = Vec::new();
+        for (index, raw) in content.lines().enumerate() {
+            let line = raw.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (negated, body) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line.strip_prefix('\\').unwrap_or(line)),
+            };
+            let (dir_only, body) = match body.strip_suffix('/') {
+                Some(rest) => (true, rest),
+                None => (false, body),
+            };
+            let anchored = body.contains('/');
+            let body = body.strip_prefix('/').unwrap_or(body);
+            if body.is_empty() {
+                continue;
+            }
+            let pattern = Pattern::new(body).map_err(|e| UtilError::IgnorePattern {
+                path: origin.to_owned(),
+                line: index + 1,
+                pattern: line.to_owned(),
+                message: e.msg.to_owned(),
+            })?;
+            rules.push(IgnoreRule {
+                pattern,
+                negated,
+                dir_only,
+                anchored,
+            });
+        }
+        Ok(Self {
+            root: root.to_path_buf(),
+            rules,
+        })
+    }
+
+    /// Return `true` when there are no rules (nothing is ignored).
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether `path` is excluded. Paths outside the root are never ignored.
+    #[must_use]
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        let components: Vec<String> = relative
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect();
+
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.matches(&components) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
+
+impl IgnoreRule {
+    /// Match against the path's components. Every component but the last is a
+    /// directory; a dir-only rule matches only those (and so everything below).
+    fn matches(&self, components: &[String]) -> bool {
+        let last = components.len().saturating_sub(1);
+        components.iter().enumerate().any(|(i, name)| {
+            if self.dir_only && i == last {
+                return false;
+            }
+            if self.anchored {
+                let prefix = components
+                    .get(..=i)
+                    .map(|parts| parts.join("/"))
+                    .unwrap_or_default();
+                self.pattern.matches_with(&prefix, MATCH_OPTIONS)
+            } else {
+                self.pattern.matches_with(name, MATCH_OPTIONS)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn rules(content: &str) -> IgnoreRules {
+        IgnoreRules::parse(Path::new("/proj"), content, ".konvoyignore").unwrap()
+    }
+
+    fn ignored(rules: &IgnoreRules, rel: &str) -> bool {
+        rules.is_ignored(&Path::new("/proj").join(rel))
+    }
+
+    #[test]
+    fn empty_rules_ignore_nothing() {
+        let r = rules("");
+        assert!(r.is_empty());
+        assert!(!ignored(&r, "src/main.kt"));
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_skipped() {
+        let r = rules("# scratch files\n\n   \n");
+        assert!(r.is_empty());
+    }
+
+    #[test]
+    fn unanchored_pattern_matches_name_at_any_depth() {
+        let r = rules("*.scratch.kt\n");
+        assert!(ignored(&r, "src/a.scratch.kt"));
+        assert!(ignored(&r, "src/deep/nested/b.scratch.kt"));
+        assert!(!ignored(&r, "src/main.kt"));
+    }
+
+    #[test]
+    fn anchored_pattern_matches_from_root_only() {
+        let r = rules("/src/gen.kt\n");
+        assert!(ignored(&r, "src/gen.kt"));
+        assert!(!ignored(&r, "src/sub/gen.kt"));
+    }
+
+    #[test]
+    fn pattern_with_slash_is_anchored() {
+        let r = rules("src/*.kt\n");
+        assert!(ignored(&r, "src/a.kt"));
+        assert!(!ignored(&r, "src/sub/a.kt"));
+    }
+
+    #[test]
+    fn double_star_spans_directories() {
+        let r = rules("src/**/Draft*.kt\n");
+        assert!(ignored(&r, "src/Draft1.kt"));
+        assert!(ignored(&r, "src/a/b/DraftX.kt"));
+        assert!(!ignored(&r, "src/a/b/Final.kt"));
+    }
+
+    #[test]
+    fn directory_pattern_excludes_everything_below() {
+        let r = rules("scratch/\n");
+        assert!(ignored(&r, "src/scratch/a.kt"));
+        assert!(ignored(&r, "src/scratch/deep/b.kt"));
+        // A file named `scratch` is not a directory.
+        assert!(!ignored(&r, "src/scratch"));
+    }
+
+    #[test]
+    fn last_matching_pattern_wins() {
+        let r = rules("*.kt\n!keep.kt\n");
+        assert!(ignored(&r, "src/drop.kt"));
+        assert!(!ignored(&r, "src/keep.kt"));
+
+        let r = rules("!keep.kt\n*.kt\n");
+        assert!(ignored(&r, "src/keep.kt"));
+    }
+
+    #[test]
+    fn negation_reincludes_inside_ignored_directory() {
+        let r = rules("gen/\n!gen/Api.kt\n");
+        assert!(ignored(&r, "gen/Other.kt"));
+        assert!(!ignored(&r, "gen/Api.kt"));
+    }
+
+    #[test]
+    fn escaped_hash_and_bang_are_literal() {
+        let r = rules("\\#odd.kt\n\\!odd.kt\n");
+        assert!(ignored(&r, "src/#odd.kt"));
+        assert!(ignored(&r, "src/!odd.kt"));
+    }
+
+    #[test]
+    fn paths_outside_root_are_not_ignored() {
+        let r = rules("*.kt\n");
+        assert!(!r.is_ignored(Path::new("/elsewhere/a.kt")));
+    }
+
+    #[test]
+    fn invalid_pattern_reports_line() {
+        let err =
+            IgnoreRules::parse(Path::new("/proj"), "ok.kt\n[bad\n", ".konvoyignore").unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains(".konvoyignore:2"), "{msg}");
+        assert!(msg.contains("[bad"), "{msg}");
+    }
+
+    #[test]
+    fn load_absent_file_is_empty() {
+        let tmp = tempfile::tempdir().unwrap();
+        let r = IgnoreRules::load(tmp.path()).unwrap();
+        assert!(r.is_empty());
+    }
+
+    #[test]
+    fn load_reads_project_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::write(tmp.path().join(IGNORE_FILE_NAME), "*.tmp.kt\n").unwrap();
+        let r = IgnoreRules::load(tmp.path()).unwrap();
+        assert!(r.is_ignored(&tmp.path().join("src").join("x.tmp.kt")));
+        assert!(!r.is_ignored(&tmp.path().join("src").join("x.kt")));
+    }
+}
@@ -2,11 +2,15 @@
 //! Hashing, filesystem utilities, and download helpers for Konvoy.
 
 pub mod artifact;
+pub mod color;
 pub mod download;
 pub mod error;
 pub mod fs;
 pub mod hash;
+pub mod hash_cache;
+pub mod ignore;
 pub mod maven;
+pub mod maven_metadata;
 pub mod metadata;
 pub mod module_metadata;
 pub mod naming;
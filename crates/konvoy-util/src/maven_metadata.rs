@@ -0,0 +1,129 @@
+//! `maven-metadata.xml` parser: the list of published versions of an artifact.
+//!
+//! Used to resolve a `[dependencies]` version range to a concrete release. The
+//! file lives next to the version directories:
+//! `{repo}/{group_path}/{artifact_id}/maven-metadata.xml`.
+
+use crate::error::UtilError;
+
+/// Build the Maven Central URL of an artifact's `maven-metadata.xml`.
+pub fn maven_metadata_url(group_id: &str, artifact_id: &str) -> String {
+    let group_path = group_id.replace('.', "/");
+    format!(
+        "{}/{group_path}/{artifact_id}/maven-metadata.xml",
+        crate::maven::MAVEN_CENTRAL
+    )
+}
+
+/// Parse the `<versioning><versions><version>` list out of `maven-metadata.xml`,
+/// in document order.
+///
+/// # Errors
+/// Returns `UtilError::MavenMetadataParse` if the XML is malformed or has no
+/// `<versions>` element.
+pub fn parse_maven_metadata(xml: &str) -> Result<Vec<String>, UtilError> {
+    let doc = roxmltree::Document::parse(xml).map_err(|e| UtilError::MavenMetadataParse {
+        reason: e.to_string(),
+    })?;
+
+    let versions = doc
+        .descendants()
+        .find(|n| n.has_tag_name("versions"))
+        .ok_or_else(|| UtilError::MavenMetadataParse {
+            reason: "no <versions> element".to_owned(),
+        })?;
+
+    Ok(versions
+        .children()
+        .filter(|n| n.has_tag_name("version"))
+        .filter_map(|n| n.text())
+        .map(|v| v.trim().to_owned())
+        .filter(|v| !v.is_empty())
+        .collect())
+}
+
+/// Fetch and parse the published versions of `group_id:artifact_id`.
+///
+/// Not cached: the version list changes with every release, and a range is
+/// only re-resolved when the lockfile has no matching pin.
+///
+/// # Errors
+/// Returns `UtilError::Offline` under `--offline`, `UtilError::Download` if the
+/// request fails, or `UtilError::MavenMetadataParse` for a malformed file.
+pub fn fetch_maven_metadata(
+    net: &crate::net::NetworkClient,
+    group_id: &str,
+    artifact_id: &str,
+) -> Result<Vec<String>, UtilError> {
+    crate::artifact::validate_identifier(group_id)?;
+    crate::artifact::validate_identifier(artifact_id)?;
+    let url = maven_metadata_url(group_id, artifact_id);
+
+    let response = net.get(&url, 60).map_err(|e| match e {
+        crate::net::RequestError::Offline => UtilError::Offline { url: url.clone() },
+        crate::net::RequestError::Status { message, .. }
+        | crate::net::RequestError::Transport { message } => UtilError::Download {
+            message: format!("failed to fetch Maven metadata from {url}: {message}"),
+        },
+    })?;
+
+    let body = response
+        .into_body()
+        .read_to_string()
+        .map_err(|e| UtilError::Download {
+            message: format!("failed to read Maven metadata response body from {url}: {e}"),
+        })?;
+
+    parse_maven_metadata(&body)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata_url_uses_group_path() {
+        assert_eq!(
+            maven_metadata_url("org.jetbrains.kotlinx", "kotlinx-coroutines-core"),
+            "https://repo1.maven.org/maven2/org/jetbrains/kotlinx/kotlinx-coroutines-core/maven-metadata.xml"
+        );
+    }
+
+    #[test]
+    fn parse_lists_versions_in_order() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<metadata>
+  <groupId>org.jetbrains.kotlinx</groupId>
+  <artifactId>kotlinx-coroutines-core</artifactId>
+  <versioning>
+    <latest>1.9.0-RC</latest>
+    <release>1.8.1</release>
+    <versions>
+      <version>1.7.3</version>
+      <version>1.8.0</version>
+      <version> 1.8.1 </version>
+      <version>1.9.0-RC</version>
+    </versions>
+  </versioning>
+</metadata>"#;
+        assert_eq!(
+            parse_maven_metadata(xml).unwrap(),
+            vec!["1.7.3", "1.8.0", "1.8.1", "1.9.0-RC"]
+        );
+    }
+
+    #[test]
+    fn parse_rejects_missing_versions() {
+        let err = parse_maven_metadata("<metadata><versioning/></metadata>").unwrap_err();
+        assert!(err.to_string().contains("no <versions>"), "{err}");
+        assert!(parse_maven_metadata("<metadata>").is_err());
+    }
+
+    #[test]
+    fn fetch_offline_is_refused() {
+        let net = crate::net::NetworkClient::new(true);
+        let err = fetch_maven_metadata(&net, "org.example", "lib").unwrap_err();
+        assert!(matches!(err, UtilError::Offline { .. }), "{err}");
+    }
+}
@@ -13,6 +13,21 @@
 //! wire, before any connection attempt, no matter which path asked.
 //! Engine-level gates still fail fast earlier with friendlier, artifact-
 //! specific errors — this is the floor beneath them.
+//!
+//! The same funnel applies a Maven mirror: with one configured, every request
+//! for a Maven Central URL is sent to the mirror instead.
+
+use std::borrow::Cow;
+
+use crate::maven::MAVEN_CENTRAL;
+
+/// How many times one download may be resumed after its connection drops
+/// before the read error is reported, unless the user config overrides it.
+pub const DEFAULT_DOWNLOAD_RETRIES: u32 = 3;
+
+/// How many seconds a download may go without receiving a byte before it
+/// counts as stalled, unless `KONVOY_DOWNLOAD_IDLE_TIMEOUT` overrides it.
+pub const DEFAULT_DOWNLOAD_IDLE_TIMEOUT_SECS: u64 = 60;
 
 /// Owns Konvoy's outbound network access and the offline policy.
 ///
@@ -21,9 +36,25 @@
 /// `konvoy toolchain install` all receive the same client. Do not construct
 /// ad-hoc clients deep inside resolution paths (or per-command): that
 /// reintroduces the per-site-policy problem this type exists to end.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct NetworkClient {
     offline: bool,
+    maven_mirror: Option<String>,
+    toolchain_mirror: Option<String>,
+    download_retries: u32,
+    download_idle_timeout_secs: u64,
+}
+
+/// Whether a host answered [`NetworkClient::probe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reachability {
+    /// The server answered with this HTTP status. Any status counts: the host
+    /// is reachable even if it refuses the particular URL.
+    Reachable(u16),
+    /// No HTTP response arrived (DNS, connect, TLS, or timeout failure).
+    Unreachable(String),
+    /// The client is offline, so nothing was sent.
+    Offline,
 }
 
 /// Outcome of a refused or failed request, before any domain mapping.
@@ -47,7 +78,71 @@ impl NetworkClient {
     /// `RequestError::Offline` without touching the network.
     #[must_use]
     pub const fn new(offline: bool) -> Self {
-        Self { offline }
+        Self {
+            offline,
+            maven_mirror: None,
+            toolchain_mirror: None,
+            download_retries: DEFAULT_DOWNLOAD_RETRIES,
+            download_idle_timeout_secs: DEFAULT_DOWNLOAD_IDLE_TIMEOUT_SECS,
+        }
+    }
+
+    /// Send requests for Maven Central URLs to `mirror` instead, e.g.
+    /// `https://maven.example.com/maven2`.
+    #[must_use]
+    pub fn with_maven_mirror(mut self, mirror: impl Into<String>) -> Self {
+        self.maven_mirror = Some(mirror.into());
+        self
+    }
+
+    /// Download Kotlin/Native tarballs from `mirror` instead of the JetBrains
+    /// releases; the path below the base URL stays the same.
+    #[must_use]
+    pub fn with_toolchain_mirror(mut self, mirror: impl Into<String>) -> Self {
+        self.toolchain_mirror = Some(mirror.into());
+        self
+    }
+
+    /// The base URL Kotlin/Native tarballs are downloaded from instead of
+    /// the JetBrains releases, if one is configured.
+    #[must_use]
+    pub fn toolchain_mirror(&self) -> Option<&str> {
+        self.toolchain_mirror.as_deref()
+    }
+
+    /// The base URL Maven Central requests are sent to instead, if one is
+    /// configured.
+    #[must_use]
+    pub fn maven_mirror(&self) -> Option<&str> {
+        self.maven_mirror.as_deref()
+    }
+
+    /// Resume a dropped download up to `retries` times.
+    #[must_use]
+    pub const fn with_download_retries(mut self, retries: u32) -> Self {
+        self.download_retries = retries;
+        self
+    }
+
+    /// How many times a dropped download is resumed before failing.
+    #[must_use]
+    pub const fn download_retries(&self) -> u32 {
+        self.download_retries
+    }
+
+    /// Treat a download as stalled once `secs` seconds pass without a byte
+    /// arriving. Unlike the global request timeout, this catches a
+    /// connection that stays open but silent.
+    #[must_use]
+    pub const fn with_download_idle_timeout(mut self, secs: u64) -> Self {
+        self.download_idle_timeout_secs = secs;
+        self
+    }
+
+    /// How many seconds a download may receive nothing before it stalls.
+    #[must_use]
+    pub const fn download_idle_timeout_secs(&self) -> u64 {
+        self.download_idle_timeout_secs
     }
 
     /// Whether this client refuses network access. Engine gates read this to
@@ -68,17 +163,75 @@ impl NetworkClient {
         &self,
         url: &str,
         global_timeout_secs: u64,
+    ) -> Result<ureq::http::Response<ureq::Body>, RequestError> {
+        self.request(url, global_timeout_secs, None)
+    }
+
+    /// Like [`get`](Self::get), but asks for the content from byte `offset`
+    /// onwards with a `Range: bytes={offset}-` header, conditional on
+    /// `If-Range: {validator}` (the ETag or Last-Modified of the first
+    /// response).
+    ///
+    /// Servers answer `206 Partial Content` only while the content still
+    /// matches `validator`; otherwise, or when they do not support ranges,
+    /// they send the whole body with `200 OK`, so callers must check the
+    /// status before appending.
+    pub(crate) fn get_from_offset(
+        &self,
+        url: &str,
+        global_timeout_secs: u64,
+        offset: u64,
+        validator: &str,
+    ) -> Result<ureq::http::Response<ureq::Body>, RequestError> {
+        self.request(url, global_timeout_secs, Some((offset, validator)))
+    }
+
+    /// Send a HEAD request to `url` (after mirroring) and report whether the
+    /// server answered, giving up after `timeout_secs` in total.
+    ///
+    /// Used by `konvoy doctor --network` to tell firewalled hosts apart from
+    /// missing artifacts; nothing is downloaded.
+    #[must_use]
+    pub fn probe(&self, url: &str, timeout_secs: u64) -> Reachability {
+        if self.offline {
+            return Reachability::Offline;
+        }
+        let url = self.mirrored(url);
+        let agent = ureq::Agent::new_with_config(
+            ureq::config::Config::builder()
+                .timeout_global(Some(std::time::Duration::from_secs(timeout_secs)))
+                .http_status_as_error(false)
+                .build(),
+        );
+        match agent.head(url.as_ref()).call() {
+            Ok(response) => Reachability::Reachable(response.status().as_u16()),
+            Err(e) => Reachability::Unreachable(e.to_string()),
+        }
+    }
+
+    fn request(
+        &self,
+        url: &str,
+        global_timeout_secs: u64,
+        range: Option<(u64, &str)>,
     ) -> Result<ureq::http::Response<ureq::Body>, RequestError> {
         if self.offline {
             return Err(RequestError::Offline);
         }
+        let url = self.mirrored(url);
         let agent = ureq::Agent::new_with_config(
             ureq::config::Config::builder()
                 .timeout_connect(Some(std::time::Duration::from_secs(30)))
                 .timeout_global(Some(std::time::Duration::from_secs(global_timeout_secs)))
                 .build(),
         );
-        agent.get(url).call().map_err(|e| {
+        let mut request = agent.get(url.as_ref());
+        if let Some((offset, validator)) = range {
+            request = request
+                .header("Range", format!("bytes={offset}-"))
+                .header("If-Range", validator);
+        }
+        request.call().map_err(|e| {
             let message = e.to_string();
             match e {
                 ureq::Error::StatusCode(code) => RequestError::Status { code, message },
@@ -86,6 +239,19 @@ impl NetworkClient {
             }
         })
     }
+
+    /// `url` with a Maven Central prefix replaced by the configured mirror.
+    fn mirrored<'u>(&self, url: &'u str) -> Cow<'u, str> {
+        let rest = url
+            .strip_prefix(MAVEN_CENTRAL)
+            .filter(|rest| rest.starts_with('/'));
+        match (&self.maven_mirror, rest) {
+            (Some(mirror), Some(rest)) => {
+                Cow::Owned(format!("{}{rest}", mirror.trim_end_matches('/')))
+            }
+            _ => Cow::Borrowed(url),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -171,9 +337,53 @@ mod tests {
         }
     }
 
+    #[test]
+    fn probe_reports_any_http_status_as_reachable() {
+        let client = NetworkClient::new(false);
+        let url = serve_once("HTTP/1.1 404 Not Found", "");
+        assert_eq!(client.probe(&url, 5), Reachability::Reachable(404));
+        assert!(matches!(
+            client.probe("http://127.0.0.1:1/unreachable", 5),
+            Reachability::Unreachable(_)
+        ));
+        assert_eq!(
+            NetworkClient::new(true).probe("http://127.0.0.1:1/never-contacted", 5),
+            Reachability::Offline
+        );
+    }
+
     #[test]
     fn is_offline_reports_construction_flag() {
         assert!(NetworkClient::new(true).is_offline());
         assert!(!NetworkClient::new(false).is_offline());
     }
+
+    #[test]
+    fn mirror_replaces_maven_central_prefix() {
+        let client = NetworkClient::new(false).with_maven_mirror("https://mirror.example.com/m2/");
+        assert_eq!(
+            client.mirrored("https://repo1.maven.org/maven2/org/x/x-1.0.pom"),
+            "https://mirror.example.com/m2/org/x/x-1.0.pom"
+        );
+        assert_eq!(
+            client.mirrored("https://github.com/x/x.jar"),
+            "https://github.com/x/x.jar"
+        );
+        assert_eq!(
+            NetworkClient::new(false).mirrored("https://repo1.maven.org/maven2/a"),
+            "https://repo1.maven.org/maven2/a"
+        );
+    }
+
+    #[test]
+    fn mirrored_request_reaches_the_mirror() {
+        let mirror = serve_once("HTTP/1.1 200 OK", "from mirror");
+        let response = NetworkClient::new(false)
+            .with_maven_mirror(mirror)
+            .get("https://repo1.maven.org/maven2/org/x/x-1.0.pom", 5)
+            .expect("mirror response");
+
+        let body = response.into_body().read_to_string().unwrap();
+        assert_eq!(body, "from mirror");
+    }
 }
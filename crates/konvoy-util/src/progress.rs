@@ -362,14 +362,48 @@ pub fn fetch(
     expected_sha256: Option<&str>,
     label: &str,
     bar: Option<&DownloadBar>,
+) -> Result<crate::artifact::ArtifactResult, crate::error::UtilError> {
+    fetch_as(
+        net,
+        url,
+        dest,
+        expected_sha256,
+        crate::hash::HashAlgorithm::Sha256,
+        label,
+        bar,
+    )
+}
+
+/// [`fetch`], with `expected` a hex digest in `algorithm`.
+///
+/// # Errors
+/// Same as [`fetch`].
+pub fn fetch_as(
+    net: &crate::net::NetworkClient,
+    url: &str,
+    dest: &std::path::Path,
+    expected: Option<&str>,
+    algorithm: crate::hash::HashAlgorithm,
+    label: &str,
+    bar: Option<&DownloadBar>,
 ) -> Result<crate::artifact::ArtifactResult, crate::error::UtilError> {
     // The cache check runs before any network involvement, so an offline
     // client resolves already-cached artifacts exactly like an online one.
-    if let Some(cached) = crate::artifact::check_cached(dest, expected_sha256)? {
+    let verification = crate::artifact::CacheVerification::from_env();
+    if let Some(cached) = crate::artifact::check_cached_as(dest, expected, algorithm, verification)?
+    {
         return Ok(cached);
     }
     let download = |on_progress: &mut dyn FnMut(u64, Option<u64>)| {
-        crate::artifact::download_artifact(net, url, dest, expected_sha256, label, on_progress)
+        crate::artifact::download_artifact_as(
+            net,
+            url,
+            dest,
+            expected,
+            algorithm,
+            label,
+            on_progress,
+        )
     };
     if let Some(b) = bar {
         b.run(|pb| download(&mut bar_progress(pb)))
@@ -615,15 +649,21 @@ fn plain_progress_line(
     )
 }
 
-fn format_bytes(bytes: u64) -> String {
+/// Format a byte count in binary units with two decimals, e.g. `1.80 MiB`.
+///
+/// Counts below 1 KiB are printed exactly (`512 B`).
+pub fn format_bytes(bytes: u64) -> String {
     const KIB: u64 = 1024;
     const MIB: u64 = KIB * 1024;
+    const GIB: u64 = MIB * 1024;
     if bytes < KIB {
         format!("{bytes} B")
     } else if bytes < MIB {
         format_decimal_bytes(bytes, KIB, "KiB")
-    } else {
+    } else if bytes < GIB {
         format_decimal_bytes(bytes, MIB, "MiB")
+    } else {
+        format_decimal_bytes(bytes, GIB, "GiB")
     }
 }
 
@@ -698,6 +738,14 @@ fn write_indicator(state: &ProgressState, w: &mut dyn Write, current_state: u8)
 mod tests {
     use super::*;
 
+    #[test]
+    fn format_bytes_uses_binary_units() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1024), "1.00 KiB");
+        assert_eq!(format_bytes(1_887_437), "1.80 MiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.00 GiB");
+    }
+
     #[test]
     fn new_download_bar_sets_prefix() {
         let bar = new_download_bar("test-prefix");
@@ -14,6 +14,7 @@ data class ToolchainLock(
     val konancVersion: String,
     val konancTarballSha256: String? = null,
     val jreTarballSha256: String? = null,
+    val jreMajor: Int? = null,
     val detektVersion: String? = null,
     val detektJarSha256: String? = null,
 )
@@ -26,6 +27,7 @@ data class DependencyLock(
 
 sealed class DepSource {
     data class Path(val path: String) : DepSource()
+    data class Git(val url: String, val rev: String, val commit: String) : DepSource()
     data class Maven(
         val version: String,
         val maven: String,
@@ -33,13 +33,17 @@ enum class PackageKind {
 data class KonvoyToolchain(
     val kotlin: String,
     val detekt: String? = null,
+    val jre: Int? = null,
 )
 
 data class DependencySpec(
     val path: String? = null,
     val version: String? = null,
     val maven: String? = null,
+    val git: String? = null,
+    val rev: String? = null,
 ) {
     val isPath: Boolean get() = path != null
     val isMaven: Boolean get() = maven != null && version != null
+    val isGit: Boolean get() = git != null && rev != null
 }
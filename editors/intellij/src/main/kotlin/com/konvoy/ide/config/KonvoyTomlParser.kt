@@ -71,6 +71,7 @@ object KonvoyTomlParser {
         val toolchain = KonvoyToolchain(
             kotlin = toolchainTable.stringValue("kotlin") ?: return null,
             detekt = toolchainTable.stringValue("detekt"),
+            jre = toolchainTable.stringValue("jre")?.toIntOrNull(),
         )
 
         val dependencies = parseDependencySpecs(tables, "dependencies")
@@ -95,6 +96,8 @@ object KonvoyTomlParser {
                     path = table.stringValue("path"),
                     version = table.stringValue("version"),
                     maven = table.stringValue("maven"),
+                    git = table.stringValue("git"),
+                    rev = table.stringValue("rev"),
                 )
             }
         }
@@ -109,6 +112,8 @@ object KonvoyTomlParser {
                     path = inlineTable.stringValue("path"),
                     version = inlineTable.stringValue("version"),
                     maven = inlineTable.stringValue("maven"),
+                    git = inlineTable.stringValue("git"),
+                    rev = inlineTable.stringValue("rev"),
                 )
             }
         }
@@ -126,6 +131,7 @@ object KonvoyTomlParser {
                 konancVersion = it.stringValue("konanc_version") ?: "",
                 konancTarballSha256 = it.stringValue("konanc_tarball_sha256"),
                 jreTarballSha256 = it.stringValue("jre_tarball_sha256"),
+                jreMajor = it.stringValue("jre_major")?.toIntOrNull(),
                 detektVersion = it.stringValue("detekt_version"),
                 detektJarSha256 = it.stringValue("detekt_jar_sha256"),
             )
@@ -152,6 +158,11 @@ object KonvoyTomlParser {
 
         val source = when (sourceType) {
             "path" -> DepSource.Path(path = table.stringValue("path") ?: "")
+            "git" -> DepSource.Git(
+                url = table.stringValue("url") ?: "",
+                rev = table.stringValue("rev") ?: "",
+                commit = table.stringValue("commit") ?: return null,
+            )
             "maven" -> {
                 // Targets may be in a sub-table [dependencies.targets]
                 val targets = mutableMapOf<String, String>()
@@ -215,6 +226,8 @@ object KonvoyTomlParser {
                     path = values["path"],
                     version = values["version"],
                     maven = values["maven"],
+                    git = values["git"],
+                    rev = values["rev"],
                 )
             }
         }
@@ -25,6 +25,7 @@ import com.intellij.notification.NotificationType
 import org.jetbrains.jps.model.java.JavaSourceRootType
 import org.jetbrains.jps.model.java.JpsJavaExtensionService
 import java.io.File
+import java.security.MessageDigest
 
 /**
  * Updates the IntelliJ workspace model to reflect the current Konvoy project state.
@@ -207,6 +208,12 @@ object KonvoyWorkspaceModelUpdater {
                         val depPath = File(basePath, source.path).canonicalPath
                         addPathDependency(tableModel, moduleModel, "konvoy:${dep.name}", depPath)
                     }
+                    is DepSource.Git -> {
+                        val checkout = gitCheckoutPath(konvoyHome, source)
+                        if (File(checkout).isDirectory) {
+                            addPathDependency(tableModel, moduleModel, "konvoy:${dep.name}", checkout)
+                        }
+                    }
                 }
             }
         }
@@ -242,6 +249,17 @@ object KonvoyWorkspaceModelUpdater {
         return if (File(path).exists()) path else null
     }
 
+    /**
+     * The checkout directory `konvoy build` materializes for a pinned git
+     * dependency: `~/.konvoy/cache/git/<sha256(url + "\n" + commit)>`.
+     */
+    private fun gitCheckoutPath(konvoyHome: String, source: DepSource.Git): String {
+        val digest = MessageDigest.getInstance("SHA-256")
+            .digest("${source.url}\n${source.commit}".toByteArray())
+        val key = digest.joinToString("") { "%02x".format(it) }
+        return "$konvoyHome/cache/git/$key"
+    }
+
     /**
      * Find the Kotlin/Native stdlib klib for the exact requested version.
      * Returns the path or null if the toolchain isn't installed.
@@ -13,7 +13,7 @@ object KonvoyTomlSchema {
     )
 
     /** Top-level sections that can appear in konvoy.toml. */
-    val SECTIONS = setOf("package", "toolchain", "dependencies", "plugins", "codegen")
+    val SECTIONS = setOf("package", "toolchain", "dependencies", "plugins", "codegen", "build")
 
     /** Keys within each section. */
     val SECTION_KEYS: Map<String, Map<String, KeyInfo>> = mapOf(
@@ -22,10 +22,15 @@ object KonvoyTomlSchema {
             "kind" to KeyInfo("Output kind", values = listOf("bin", "lib")),
             "version" to KeyInfo("Package version (semver)"),
             "entrypoint" to KeyInfo("Entry point file path (default: src/main.kt)"),
+            "source_dirs" to KeyInfo("Kotlin source directories (default: [\"src\"])"),
         ),
         "toolchain" to mapOf(
             "kotlin" to KeyInfo("Kotlin/Native version", required = true),
             "detekt" to KeyInfo("Detekt linter version"),
+            "jre" to KeyInfo("Bundled JRE major version (default: 21)"),
+        ),
+        "build" to mapOf(
+            "timeout_secs" to KeyInfo("Kill a konanc invocation after this many seconds (default: no limit)"),
         ),
         // OpenAPI code generation via Fabrikt (the [codegen.openapi] sub-table).
         "codegen.openapi" to mapOf(
@@ -90,6 +90,28 @@ class KonvoyTomlParserTest {
         assertEquals("1.7.3", dep.version)
     }
 
+    @Test
+    fun `parse manifest with git dependency`() {
+        val manifest = KonvoyTomlParser.parseManifestFromText("""
+            [package]
+            name = "app"
+
+            [toolchain]
+            kotlin = "2.3.20"
+
+            [dependencies.utils]
+            git = "https://github.com/example/utils.git"
+            rev = "v1.2.0"
+        """.trimIndent())
+
+        assertNotNull(manifest)
+        val dep = manifest!!.dependencies["utils"]
+        assertNotNull(dep)
+        assertTrue(dep!!.isGit)
+        assertEquals("https://github.com/example/utils.git", dep.git)
+        assertEquals("v1.2.0", dep.rev)
+    }
+
     @Test
     fun `returns null for missing package section`() {
         val manifest = KonvoyTomlParser.parseManifestFromText("""